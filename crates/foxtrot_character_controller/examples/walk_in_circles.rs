@@ -0,0 +1,65 @@
+//! Drops a character onto a floor and lets it walk in circles, jumping every second.
+//! Runs headless and prints where the character is until it exits after a few seconds.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use foxtrot_character_controller::{
+    character_controller_plugin, CharacterControllerBundle, CharacterJumped, CharacterLanded,
+    Jumping, Walking,
+};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+    character_controller_plugin(&mut app);
+    app.add_startup_system(setup)
+        .add_system(steer)
+        .add_system(report)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Floor"),
+        TransformBundle::from_transform(Transform::from_xyz(0., -0.5, 0.)),
+        Collider::cuboid(50., 0.5, 50.),
+    ));
+    commands.spawn((
+        Name::new("Character"),
+        TransformBundle::from_transform(Transform::from_xyz(0., 2., 0.)),
+        CharacterControllerBundle::capsule(1., 0.4),
+    ));
+}
+
+fn steer(time: Res<Time>, mut characters: Query<(&mut Walking, &mut Jumping)>) {
+    let elapsed = time.elapsed_seconds();
+    for (mut walking, mut jumping) in &mut characters {
+        walking.direction = Some(Vec3::new(elapsed.cos(), 0., elapsed.sin()));
+        jumping.requested = elapsed.fract() < time.delta_seconds();
+    }
+}
+
+fn report(
+    time: Res<Time>,
+    characters: Query<&Transform, With<Walking>>,
+    mut jumped_events: EventReader<CharacterJumped>,
+    mut landed_events: EventReader<CharacterLanded>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    for _ in jumped_events.iter() {
+        println!("Jumped");
+    }
+    for _ in landed_events.iter() {
+        println!("Landed");
+    }
+    for transform in &characters {
+        println!("Character at {}", transform.translation);
+    }
+    if time.elapsed_seconds() > 5. {
+        exit_events.send(AppExit);
+    }
+}
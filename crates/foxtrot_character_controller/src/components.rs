@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Bundle)]
+pub struct CharacterControllerBundle {
+    pub gravity_scale: GravityScale,
+    pub mass: ColliderMassProperties,
+    pub read_mass: ReadMassProperties,
+    pub walking: Walking,
+    pub jumping: Jumping,
+    pub grounded: Grounded,
+    pub damping: Damping,
+    pub rigid_body: RigidBody,
+    pub locked_axes: LockedAxes,
+    pub collider: Collider,
+    pub force: ExternalForce,
+    pub impulse: ExternalImpulse,
+    pub velocity: Velocity,
+    pub dominance: Dominance,
+    pub up: CharacterUp,
+    pub modifiers: MovementModifiers,
+}
+
+impl Default for CharacterControllerBundle {
+    fn default() -> Self {
+        Self {
+            read_mass: default(),
+            gravity_scale: GravityScale(1.0),
+            force: default(),
+            mass: ColliderMassProperties::Mass(3.0),
+            walking: default(),
+            jumping: default(),
+            grounded: default(),
+            damping: Damping {
+                linear_damping: 1.5,
+                ..default()
+            },
+            collider: default(),
+            rigid_body: RigidBody::Dynamic,
+            locked_axes: LockedAxes::ROTATION_LOCKED,
+            impulse: default(),
+            velocity: default(),
+            dominance: default(),
+            up: default(),
+            modifiers: default(),
+        }
+    }
+}
+
+impl CharacterControllerBundle {
+    pub fn capsule(height: f32, radius: f32) -> Self {
+        Self {
+            collider: Collider::capsule_y(height / 2., radius),
+            ..default()
+        }
+    }
+}
+
+/// The visible model of a character, which is kept separate from it so that it can follow the character smoothly.
+/// It is despawned together with the character.
+#[derive(Debug, Clone, Eq, PartialEq, Component, Serialize, Deserialize)]
+pub struct Model {
+    pub target: Entity,
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Walking {
+    /// Acceleration on the ground
+    pub ground_acceleration: f32,
+    /// Acceleration on the ground when[`Walking::sprinting`] is `true`
+    pub sprinting_acceleration: f32,
+    /// Acceleration in the air
+    pub aerial_acceleration: f32,
+    /// Acceleration in opposide direction of velocity when not explicitely walking, i.e. [`Walking::direction`] is [`Option::None`]
+    pub braking_acceleration: f32,
+    /// Speed at which we stop braking and just set the horizontal velocity to 0
+    pub stopping_speed: f32,
+    /// Direction in which we want to walk this tick. When not normalized, the acceleration will be scaled accordingly.
+    pub direction: Option<Vec3>,
+    /// Whether we are sprinting this tick
+    pub sprinting: bool,
+}
+
+impl Walking {
+    pub fn get_acceleration(&self, grounded: bool) -> Option<Vec3> {
+        let acceleration = if grounded {
+            if self.sprinting {
+                self.sprinting_acceleration
+            } else {
+                self.ground_acceleration
+            }
+        } else {
+            self.aerial_acceleration
+        };
+        self.direction.map(|dir| dir * acceleration)
+    }
+}
+
+impl Default for Walking {
+    fn default() -> Self {
+        Self {
+            ground_acceleration: 14.,
+            sprinting_acceleration: 19.,
+            aerial_acceleration: 9.,
+            braking_acceleration: 5.,
+            stopping_speed: 0.1,
+            direction: None,
+            sprinting: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Default, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Grounded(pub bool);
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Jumping {
+    /// Speed of the jump in m/s
+    pub speed: f32,
+    /// Was jump requested?
+    pub requested: bool,
+    /// Time in s after walking off a ledge during which the character can still jump
+    pub coyote_time: f32,
+    /// Time in s a jump requested in the air is remembered, so that it is executed when landing shortly after
+    pub buffer_time: f32,
+    /// Time in s since the character was last grounded
+    pub time_since_grounded: f32,
+    /// Time in s since the last jump request that was not executed yet
+    pub time_since_request: Option<f32>,
+    /// Was the jump button released?
+    pub released: bool,
+    /// Fraction of the upward velocity that is kept when the jump button is released while still rising,
+    /// so that short taps result in short hops. `1.0` disables cutting jumps.
+    pub cut_factor: f32,
+    /// Whether the character is rising from a jump that can still be cut
+    pub rising: bool,
+}
+
+impl Default for Jumping {
+    fn default() -> Self {
+        Self {
+            speed: 3.5,
+            requested: false,
+            coyote_time: 0.1,
+            buffer_time: 0.15,
+            time_since_grounded: 0.,
+            time_since_request: None,
+            released: false,
+            cut_factor: 0.5,
+            rising: false,
+        }
+    }
+}
+
+/// The direction the character stands upright in. It points against the gravity acting on the character unless
+/// the game changes it, e.g. to stand on curved terrain.
+/// The character's transform is turned towards it over time, so use [`Transform::up`] for the current orientation.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct CharacterUp(pub Vec3);
+
+impl Default for CharacterUp {
+    fn default() -> Self {
+        Self(Vec3::Y)
+    }
+}
+
+/// Factors on a character's movement that the game sets, e.g. from status effects or the ground the character stands on.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct MovementModifiers {
+    /// Scales how fast the character accelerates while walking
+    pub speed: f32,
+    /// Scales how fast the character jumps off
+    pub jump: f32,
+    /// Scales how well the character can walk and brake on the ground, e.g. below `1` on ice. Has no effect in the air.
+    pub grip: f32,
+}
+
+impl Default for MovementModifiers {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            jump: 1.,
+            grip: 1.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CharacterJumped {
+    pub entity: Entity,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CharacterLanded {
+    pub entity: Entity,
+}
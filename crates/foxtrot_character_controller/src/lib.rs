@@ -0,0 +1,363 @@
+// These two generate a lot of false positives for Bevy systems
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::type_complexity)]
+
+//! Dynamic rigid body character controller on top of bevy_rapier3d, as used by Foxtrot's player and NPCs.
+//! See [`character_controller_plugin`] for how characters move and `examples/walk_in_circles.rs` for a minimal app.
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod components;
+pub use components::*;
+
+/// Handles movement of character controllers, i.e. entities with the [`CharacterControllerBundle`].
+/// The default forces on a character going right are:
+/// ```text
+/// ┌──────────────────────────────┐
+/// │            Gravity           │
+/// │               ↓              │
+/// │              ╔═╗             │
+/// │   Walking ─► ║ ║ ◄─ Damping  │
+/// │              ╚═╝             │
+/// │                              │
+/// └──────────────────────────────┘
+/// ```
+/// All physics values are assumed to be in SI units, e.g. forces are measured in N and acceleration in m/s².
+///
+/// The [`Walking`] and [`Jumping`] components are user friendly ways of influencing the corresponding forces.
+/// There is no explicit maximum speed since the damping counteracts all other forces until reaching an equilibrium.
+/// The [`Grounded`] component is used to determine whether the character is on the ground or not.
+/// Characters send a [`CharacterJumped`] when they jump and a [`CharacterLanded`] when they touch the ground after a fall or jump.
+/// The game can scale walking and jumping per character through its [`MovementModifiers`].
+/// To influence movement, apply your force by adding it to the character's total [`ExternalForce`] or [`ExternalImpulse`]. This is usually done like this:
+/// - A continuous force like walking: `external_force.force += acceleration * read_mass_properties.0.mass`, with `external_force`: [`ExternalForce`], `read_mass_properties`: [`ReadMassProperties`], and a user-defined `acceleration`: [`Vec3`]
+/// - An instantaneous force (i.e. an impulse) like jumping: `external_impulse.impulse += velocity * read_mass_properties.0.mass`, with `external_impulse`: [`ExternalImpulse`], `read_mass_properties`: [`ReadMassProperties`], and a user-defined `velocity`: [`Vec3`]
+///
+/// Note: you might notice that the normal force is not included in the above diagram. This is because rapier emulates it by moving penetrating colliders out of each other.
+///
+/// Characters that move further than their own radius in a single frame, e.g. when falling from great heights,
+/// are swept along their velocity first so that they cannot tunnel through thin colliders, see [`prevent_tunneling`].
+///
+/// All systems run in the [`CharacterControllerSystemSet`], which games usually restrict to the state in which they are played.
+/// They are tuned through the [`CharacterControllerConfig`].
+pub fn character_controller_plugin(app: &mut App) {
+    app.register_type::<Grounded>()
+        .register_type::<Jumping>()
+        .register_type::<Velocity>()
+        .register_type::<Walking>()
+        .register_type::<CharacterUp>()
+        .register_type::<MovementModifiers>()
+        .register_type::<CharacterControllerConfig>()
+        .init_resource::<CharacterControllerConfig>()
+        .add_event::<CharacterJumped>()
+        .add_event::<CharacterLanded>()
+        .add_systems(
+            (
+                reset_forces_and_impulses,
+                update_grounded,
+                apply_jumping,
+                apply_walking,
+                prevent_tunneling,
+                rotate_characters,
+                sync_models,
+                reset_movement_components,
+            )
+                .chain()
+                .in_set(CharacterControllerSystemSet),
+        );
+}
+
+/// Time in seconds a character has to be in the air for touching the ground again to count as landing.
+/// Keeps bumps and stairs from counting as falls.
+const MIN_AIRBORNE_TIME: f32 = 0.3;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct CharacterControllerSystemSet;
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct CharacterControllerConfig {
+    /// Distance in m between a character's collider and the ground up to which it counts as grounded
+    pub ground_distance: f32,
+    /// How slowly characters turn towards the direction they move in
+    pub rotation_smoothing: f32,
+    /// How slowly a [`Model`] follows its character
+    pub model_sync_smoothing: f32,
+    /// Distance in m that fast characters keep to the first obstacle in their way, see [`prevent_tunneling`]
+    pub skin_width: f32,
+}
+
+impl Default for CharacterControllerConfig {
+    fn default() -> Self {
+        Self {
+            ground_distance: 0.1,
+            rotation_smoothing: 1.,
+            model_sync_smoothing: 0.15,
+            skin_width: 0.02,
+        }
+    }
+}
+
+pub fn update_grounded(
+    mut query: Query<(Entity, &Transform, &Collider, &mut Grounded)>,
+    rapier_context: Res<RapierContext>,
+    config: Res<CharacterControllerConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_grounded").entered();
+    for (entity, transform, collider, mut grounded) in &mut query {
+        let height = collider.raw.compute_local_aabb().maxs.y;
+        grounded.0 = rapier_context
+            .cast_ray(
+                transform.translation,
+                transform.down(),
+                height + config.ground_distance,
+                true,
+                QueryFilter::new()
+                    .exclude_collider(entity)
+                    .exclude_sensors(),
+            )
+            .is_some();
+    }
+}
+
+pub fn reset_forces_and_impulses(
+    mut forces: Query<&mut ExternalForce>,
+    mut impulses: Query<&mut ExternalImpulse>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reset_forces_and_impulses").entered();
+    for mut force in &mut forces {
+        *force = default();
+    }
+    for mut impulse in &mut impulses {
+        *impulse = default();
+    }
+}
+
+pub fn reset_movement_components(
+    mut walking: Query<&mut Walking>,
+    mut jumpers: Query<&mut Jumping>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reset_movement_components").entered();
+    for mut walk in &mut walking {
+        walk.direction = None;
+    }
+    for mut jumper in &mut jumpers {
+        jumper.requested = false;
+        jumper.released = false;
+    }
+}
+
+/// Jumps are also executed shortly after leaving the ground and shortly before landing, see [`Jumping`],
+/// because players tend to press the button a little too late or too early.
+/// Releasing the button while still rising cuts the jump short, see [`Jumping::cut_factor`].
+pub fn apply_jumping(
+    time: Res<Time>,
+    mut character_query: Query<(
+        Entity,
+        &Grounded,
+        &mut ExternalImpulse,
+        &mut Velocity,
+        &ReadMassProperties,
+        &mut Jumping,
+        &Transform,
+        Option<&MovementModifiers>,
+    )>,
+    mut jumped_events: EventWriter<CharacterJumped>,
+    mut landed_events: EventWriter<CharacterLanded>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_jumping").entered();
+    let dt = time.delta_seconds();
+    for (entity, grounded, mut impulse, mut velocity, mass, mut jump, transform, modifiers) in
+        &mut character_query
+    {
+        if grounded.0 {
+            // Right after a jump, the ground is still in reach while the character rises
+            if jump.time_since_grounded >= MIN_AIRBORNE_TIME && !jump.rising {
+                landed_events.send(CharacterLanded { entity });
+            }
+            jump.time_since_grounded = 0.;
+        } else {
+            jump.time_since_grounded += dt;
+        }
+        jump.time_since_request = if jump.requested {
+            Some(0.)
+        } else {
+            jump.time_since_request
+                .map(|time_since_request| time_since_request + dt)
+                .filter(|&time_since_request| time_since_request <= jump.buffer_time)
+        };
+
+        if jump.rising {
+            let up = transform.up();
+            let upward_speed = velocity.linvel.dot(up);
+            if upward_speed <= 0. {
+                jump.rising = false;
+            } else if jump.released {
+                velocity.linvel -= up * upward_speed * (1. - jump.cut_factor);
+                jump.rising = false;
+            }
+        }
+        if jump.time_since_request.is_some() && jump.time_since_grounded <= jump.coyote_time {
+            jump.time_since_request = None;
+            jump.rising = true;
+            // Prevents jumping a second time while still within the coyote time
+            jump.time_since_grounded = f32::INFINITY;
+            let up = transform.up();
+            let jump_boost = modifiers.map_or(1., |modifiers| modifiers.jump);
+            impulse.impulse += up * mass.0.mass * jump.speed * jump_boost;
+            jumped_events.send(CharacterJumped { entity });
+
+            // Kill any downward velocity. This ensures that repeated jumps are always the same height.
+            // Otherwise the falling velocity from the last tick would dampen the jump velocity.
+            let vertical = up * velocity.linvel.dot(up);
+            velocity.linvel -= vertical;
+        }
+    }
+}
+
+pub fn apply_walking(
+    mut character_query: Query<(
+        &mut ExternalForce,
+        &Walking,
+        &mut Velocity,
+        &Grounded,
+        &ReadMassProperties,
+        &Transform,
+        Option<&MovementModifiers>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_walking").entered();
+    for (mut force, walking, mut velocity, grounded, mass, transform, modifiers) in
+        &mut character_query
+    {
+        let mass = mass.0.mass;
+        let modifiers = modifiers.copied().unwrap_or_default();
+        let grip = if grounded.0 { modifiers.grip } else { 1. };
+        if let Some(acceleration) = walking.get_acceleration(grounded.0) {
+            let walking_force = acceleration * grip * modifiers.speed * mass;
+            force.force += walking_force;
+        } else if grounded.0 {
+            let up = transform.up();
+            let vertical = up * velocity.linvel.dot(up);
+            let horizontal = velocity.linvel - vertical;
+            if horizontal.length_squared() < walking.stopping_speed * walking.stopping_speed {
+                velocity.linvel = vertical;
+            } else if let Some(braking_direction) = horizontal.try_normalize().map(|v| -v) {
+                let braking_force = walking.braking_acceleration * grip * braking_direction * mass;
+                force.force += braking_force;
+            }
+        }
+    }
+}
+
+/// Casts each fast character's collider along its velocity and slows it down so that this frame's movement
+/// ends the configured skin width in front of the first hit. Slow characters are left to rapier's contact handling.
+/// Since characters are dynamic bodies that are moved by rapier, this clamps their [`Velocity`] instead of their translation.
+pub fn prevent_tunneling(
+    time: Res<Time>,
+    config: Res<CharacterControllerConfig>,
+    rapier_context: Res<RapierContext>,
+    mut character_query: Query<(Entity, &Transform, &Collider, &mut Velocity), With<Walking>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("prevent_tunneling").entered();
+    let dt = time.delta_seconds();
+    let skin_width = config.skin_width;
+    for (entity, transform, collider, mut velocity) in &mut character_query {
+        let speed = velocity.linvel.length();
+        let aabb = collider.raw.compute_local_aabb();
+        let thickness = aabb.half_extents().min();
+        if speed * dt <= thickness {
+            continue;
+        }
+        let Some((_hit, toi)) = rapier_context.cast_shape(
+            transform.translation,
+            transform.rotation,
+            velocity.linvel,
+            collider,
+            dt,
+            QueryFilter::new()
+                .exclude_collider(entity)
+                .exclude_sensors(),
+        ) else {
+            continue;
+        };
+        if toi.status == TOIStatus::Penetrating {
+            // Already touching, which rapier resolves on its own
+            continue;
+        }
+        // Only the part of the movement towards the obstacle is limited, so the character can still slide along it.
+        // The normal is given in world space and points out of the obstacle, i.e. against the cast.
+        let normal = -toi.normal1;
+        let approach_speed = velocity.linvel.dot(normal);
+        if approach_speed <= 0. {
+            continue;
+        }
+        let allowed_distance = (toi.toi * approach_speed - skin_width).max(0.);
+        velocity.linvel += normal * (allowed_distance / dt - approach_speed);
+    }
+}
+
+pub fn rotate_characters(
+    time: Res<Time>,
+    mut player_query: Query<(&Velocity, &mut Transform)>,
+    config: Res<CharacterControllerConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("rotate_characters").entered();
+    let dt = time.delta_seconds();
+    for (velocity, mut transform) in player_query.iter_mut() {
+        let up = transform.up();
+        let horizontal_movement = velocity.linvel - up * velocity.linvel.dot(up);
+        if horizontal_movement.length_squared() < 1e-5 {
+            continue;
+        }
+        let target_transform =
+            transform.looking_at(transform.translation + horizontal_movement, up);
+        // Asymptotic averaging
+        let factor = smoothness_to_lerp_factor(config.rotation_smoothing, dt);
+        let rotation = transform.rotation.slerp(target_transform.rotation, factor);
+        transform.rotation = rotation;
+    }
+}
+
+/// Moves each [`Model`] towards its character and despawns models whose character is gone.
+pub fn sync_models(
+    time: Res<Time>,
+    mut commands: Commands,
+    without_model: Query<(&Transform, &Visibility), Without<Model>>,
+    mut with_model: Query<(Entity, &mut Transform, &mut Visibility, &Model)>,
+    config: Res<CharacterControllerConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("sync_models").entered();
+    let dt = time.delta_seconds();
+    for (model_entity, mut model_transform, mut visibility, model) in with_model.iter_mut() {
+        if let Ok((target_transform, target_visibility)) = without_model.get(model.target) {
+            let factor = smoothness_to_lerp_factor(config.model_sync_smoothing, dt);
+            model_transform.translation = model_transform
+                .translation
+                .lerp(target_transform.translation, factor);
+            model_transform.rotation = model_transform
+                .rotation
+                .slerp(target_transform.rotation, factor);
+            model_transform.scale = model_transform.scale.lerp(target_transform.scale, factor);
+            *visibility = *target_visibility;
+        } else {
+            commands.entity(model_entity).despawn_recursive();
+        }
+    }
+}
+
+fn smoothness_to_lerp_factor(smoothness: f32, dt: f32) -> f32 {
+    // Taken from https://github.com/h3r2tic/dolly/blob/main/src/util.rs#L34
+    const SMOOTHNESS_MULTIPLIER: f32 = 8.0;
+    1.0 - (-SMOOTHNESS_MULTIPLIER * dt / smoothness.max(1e-5)).exp()
+}
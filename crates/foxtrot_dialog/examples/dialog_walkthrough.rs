@@ -0,0 +1,118 @@
+//! Loads a small dialog, enters it and picks the first available choice on every page until it ends.
+//! Runs headless and prints the texts and effects along the way.
+
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use foxtrot_dialog::{
+    dialog_runtime_plugin, ActiveConditions, CurrentDialog, Dialog, DialogContext,
+    DialogEffectEvent, DialogId, DialogJournal, DialogLocalization, NextPage, RequirementContext,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Gesture {
+    Bow,
+}
+
+#[derive(Debug, Clone, Resource, Default)]
+struct Texts(HashMap<String, String>);
+
+impl DialogLocalization for Texts {
+    fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.0.get(key).map_or(key, String::as_str)
+    }
+}
+
+const DIALOG: &str = r#"(
+    initial_page: [(id: "greeting")],
+    pages: {
+        "greeting": (
+            text: "greeting.text",
+            emote: Some(Bow),
+            next_page: Choice({
+                "ask_for_key": (text: "greeting.ask", next_page_id: "key"),
+            }),
+            effects: [SetFlag("met_keeper")],
+        ),
+        "key": (
+            text: "key.text",
+            next_page: Exit,
+            lines: [(text: "key.thanks", context: (flags: ["met_keeper"]))],
+            effects: [GiveItem(item: "key", amount: 1)],
+        ),
+    },
+)"#;
+
+fn main() -> Result<()> {
+    let texts = [
+        ("greeting.text", "Welcome, traveller."),
+        ("greeting.ask", "Can I have the key?"),
+        ("key.text", "Here you go."),
+        ("key.thanks", "Say hi to the others for me."),
+    ]
+    .into_iter()
+    .map(|(key, text)| (key.to_owned(), text.to_owned()))
+    .collect();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(Texts(texts));
+    dialog_runtime_plugin::<Gesture, Texts>(&mut app);
+
+    let dialog: Dialog<Gesture> = ron::from_str(DIALOG).context("Failed to parse dialog")?;
+    let id = DialogId::new("keeper");
+    let current_page = dialog
+        .initial_page_id(
+            &RequirementContext {
+                active_conditions: &ActiveConditions::default(),
+                journal: &DialogJournal::default(),
+                memory: None,
+                dialog_context: &DialogContext::default(),
+            },
+            &id,
+        )
+        .context("No initial page available")?;
+    let source = app.world.spawn(Name::new("Keeper")).id();
+    app.insert_resource(CurrentDialog {
+        source,
+        id,
+        dialog,
+        current_page,
+        last_choice: None,
+        text: String::new(),
+        entered_page: None,
+    });
+
+    loop {
+        app.update();
+        for event in app
+            .world
+            .resource_mut::<Events<DialogEffectEvent>>()
+            .drain()
+        {
+            println!("  Effect: {:?}", event.effect);
+        }
+        let mut current_dialog = app.world.resource_mut::<CurrentDialog<Gesture>>();
+        let page = current_dialog.fetch_current_page()?;
+        println!("{}", current_dialog.text);
+        if let Some(gesture) = page.emote {
+            println!("  Gesture: {gesture:?}");
+        }
+        match current_dialog.resolve_next_page()? {
+            NextPage::Choice(choices) => {
+                let (choice_id, choice) = choices.first().context("Page has no choices")?;
+                println!("> {}", choice.text);
+                current_dialog.last_choice = Some(choice_id.clone());
+                current_dialog.current_page = choice.next_page_id.clone();
+            }
+            NextPage::Continue(_) => current_dialog.auto_advance()?,
+            NextPage::Exit | NextPage::SameAs(_) => break,
+        }
+    }
+    println!(
+        "Items held: {:?}",
+        app.world.resource::<DialogContext>().items
+    );
+    Ok(())
+}
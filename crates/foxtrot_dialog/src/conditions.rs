@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+
+/// Trackers of player actions such as chosen dialog options, which pages and choices can require.
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct ActiveConditions(pub HashSet<ConditionId>);
+impl ActiveConditions {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Default, Reflect, Hash, Serialize, Deserialize, FromReflect,
+)]
+#[reflect(Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct ConditionId(pub String);
+
+impl From<String> for ConditionId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ConditionId> for String {
+    fn from(value: ConditionId) -> Self {
+        value.0
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default, Reflect, Hash, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct ConditionAddEvent(pub ConditionId);
+
+pub fn add_conditions(
+    mut conditions: ResMut<ActiveConditions>,
+    mut incoming_conditions: EventReader<ConditionAddEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("add_conditions").entered();
+    for incoming_condition in incoming_conditions.iter() {
+        conditions.0.insert(incoming_condition.0.clone());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::resources::{CurrentDialog, DialogId, PageId};
+use crate::DialogLocalization;
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+/// World state that dialogs can check and change, i.e. flags, the items the player holds and the quests they started and completed.
+/// Other systems write into it to make their state visible to dialogs, e.g. picking up an item or finishing an area.
+/// It is kept when changing levels and stored in save games.
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct DialogContext {
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub flags: HashSet<String>,
+    /// Number of each item held. Items that run out are removed.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub items: HashMap<String, u32>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub started_quests: HashSet<String>,
+    /// Filled by the game's quest tracking
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub completed_quests: HashSet<String>,
+}
+
+impl DialogContext {
+    pub fn is_empty(&self) -> bool {
+        self == &default()
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+
+    pub fn item_count(&self, item: &str) -> u32 {
+        self.items.get(item).copied().unwrap_or_default()
+    }
+
+    pub fn give_item(&mut self, item: impl Into<String>, amount: u32) {
+        if amount > 0 {
+            *self.items.entry(item.into()).or_default() += amount;
+        }
+    }
+
+    /// Removes up to `amount` of the item and returns how many were actually removed.
+    pub fn take_item(&mut self, item: &str, amount: u32) -> u32 {
+        let Some(count) = self.items.get_mut(item) else {
+            return 0;
+        };
+        let taken = amount.min(*count);
+        *count -= taken;
+        if *count == 0 {
+            self.items.remove(item);
+        }
+        taken
+    }
+
+    pub fn apply(&mut self, effect: &DialogEffect) {
+        match effect {
+            DialogEffect::SetFlag(flag) => {
+                self.flags.insert(flag.clone());
+            }
+            DialogEffect::ClearFlag(flag) => {
+                self.flags.remove(flag);
+            }
+            DialogEffect::GiveItem { item, amount } => self.give_item(item.clone(), *amount),
+            DialogEffect::TakeItem { item, amount } => {
+                self.take_item(item, *amount);
+            }
+            DialogEffect::StartQuest(quest) => {
+                self.started_quests.insert(quest.clone());
+            }
+            // Progress is tracked by the game, which reacts to the `DialogEffectEvent`
+            DialogEffect::AdvanceQuest(_) | DialogEffect::CompleteQuest(_) => {}
+            // The wallet and shops are handled by the game as well
+            DialogEffect::GiveCurrency(_)
+            | DialogEffect::TakeCurrency(_)
+            | DialogEffect::OpenShop => {}
+        }
+    }
+}
+
+/// Requirements on the [`DialogContext`].
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct ContextRequirements {
+    /// Flags that must be set
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub flags: HashSet<String>,
+    /// Flags that must not be set
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub missing_flags: HashSet<String>,
+    /// Minimum number of each item the player must hold
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub items: HashMap<String, u32>,
+    /// Items the player must not hold any of
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub missing_items: HashSet<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub started_quests: HashSet<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub completed_quests: HashSet<String>,
+}
+
+impl ContextRequirements {
+    pub fn is_empty(&self) -> bool {
+        self == &default()
+    }
+
+    pub fn is_met(&self, context: &DialogContext) -> bool {
+        self.flags.iter().all(|flag| context.has_flag(flag))
+            && !self.missing_flags.iter().any(|flag| context.has_flag(flag))
+            && self
+                .items
+                .iter()
+                .all(|(item, amount)| context.item_count(item) >= *amount)
+            && self
+                .missing_items
+                .iter()
+                .all(|item| context.item_count(item) == 0)
+            && self.started_quests.is_subset(&context.started_quests)
+            && self.completed_quests.is_subset(&context.completed_quests)
+    }
+}
+
+/// Executed when the page it belongs to is shown.
+/// Effects on state that the [`DialogContext`] does not track are left to the game, which reacts to the [`DialogEffectEvent`].
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum DialogEffect {
+    SetFlag(String),
+    ClearFlag(String),
+    GiveItem {
+        item: String,
+        amount: u32,
+    },
+    TakeItem {
+        item: String,
+        amount: u32,
+    },
+    /// Adds coins to the player's wallet
+    GiveCurrency(u32),
+    /// Removes up to this many coins from the player's wallet
+    TakeCurrency(u32),
+    /// Opens the shop of the character the dialog is held with, if it sells anything
+    OpenShop,
+    StartQuest(String),
+    /// Completes the current stage of a quest regardless of its requirements
+    AdvanceQuest(String),
+    CompleteQuest(String),
+}
+
+/// Sent for every [`DialogEffect`] after it has been applied to the [`DialogContext`],
+/// so that other systems can react to it, e.g. by showing a notification.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DialogEffectEvent {
+    pub dialog: DialogId,
+    pub page: PageId,
+    /// The character the dialog is held with
+    pub source: Entity,
+    pub effect: DialogEffect,
+}
+
+/// Fixes the text of a newly shown page and executes its effects.
+/// The conditional lines are evaluated before the effects, so a page cannot contradict itself.
+/// When the language changes, the text of the current page is translated again without repeating its effects.
+/// The same goes for a page restored from a save game, whose effects were executed before saving.
+pub fn enter_pages<E: Clone + Send + Sync + 'static, L: DialogLocalization>(
+    current_dialog: Option<ResMut<CurrentDialog<E>>>,
+    mut context: ResMut<DialogContext>,
+    localization: Res<L>,
+    mut effect_events: EventWriter<DialogEffectEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("enter_pages").entered();
+    let Some(mut current_dialog) = current_dialog else {
+        return;
+    };
+    let is_new_page = current_dialog.entered_page.as_ref() != Some(&current_dialog.current_page);
+    if !is_new_page && !current_dialog.is_added() && !localization.is_changed() {
+        return;
+    }
+    let page = match current_dialog.fetch_current_page() {
+        Ok(page) => page,
+        Err(e) => {
+            error!("Failed to enter dialog page: {e:#}");
+            return;
+        }
+    };
+    current_dialog.text = page.text_in(&context, &*localization);
+    if !is_new_page {
+        return;
+    }
+    current_dialog.entered_page = Some(current_dialog.current_page.clone());
+    for effect in page.effects {
+        context.apply(&effect);
+        effect_events.send(DialogEffectEvent {
+            dialog: current_dialog.id.clone(),
+            page: current_dialog.current_page.clone(),
+            source: current_dialog.source,
+            effect,
+        });
+    }
+}
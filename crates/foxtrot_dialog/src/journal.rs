@@ -0,0 +1,85 @@
+use crate::conditions::ConditionId;
+use crate::resources::{DialogId, PageId};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Every dialog page the player has seen and every choice they made, by dialog.
+/// Games usually keep it with the player's profile, so it persists between save states.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct DialogJournal(pub HashMap<DialogId, JournalEntry>);
+
+impl DialogJournal {
+    pub fn has_seen(&self, dialog: &DialogId, page: &PageId) -> bool {
+        self.0
+            .get(dialog)
+            .map_or(false, |entry| entry.visited_pages.contains(page))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Name of the character that was last talked to in this dialog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// In the order they were first seen
+    #[serde(default)]
+    pub visited_pages: Vec<PageId>,
+    /// In the order they were made, including repeated choices
+    #[serde(default)]
+    pub choices: Vec<JournalChoice>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct JournalChoice {
+    /// The page the choice was made on
+    pub page: PageId,
+    pub choice: ConditionId,
+    /// The player's answer at the time it was chosen
+    pub text: String,
+}
+
+/// Refers to a dialog page in requirements, either as `"page"` within the same dialog or as `"dialog/page"`.
+#[derive(
+    Debug, Clone, Eq, PartialEq, Default, Reflect, FromReflect, Hash, Serialize, Deserialize,
+)]
+#[reflect(Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct PageRef(pub String);
+
+impl PageRef {
+    pub fn resolve(&self, current_dialog: &DialogId) -> (DialogId, PageId) {
+        match self.0.split_once('/') {
+            Some((dialog, page)) => (DialogId::new(dialog), PageId(page.to_owned())),
+            None => (current_dialog.clone(), PageId(self.0.clone())),
+        }
+    }
+}
+
+impl From<String> for PageRef {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<PageRef> for String {
+    fn from(value: PageRef) -> Self {
+        value.0
+    }
+}
+
+/// Sent when the player picks an answer in a dialog.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DialogChoiceEvent {
+    pub dialog: DialogId,
+    pub page: PageId,
+    pub choice: ConditionId,
+    pub text: String,
+}
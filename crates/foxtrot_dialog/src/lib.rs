@@ -0,0 +1,59 @@
+//! Branching dialogs loaded from `.dlg.ron` files, as used by Foxtrot's NPCs.
+//! This crate holds the dialog data and decides which pages and choices are available, while presenting them is left to the game.
+//! See [`dialog_runtime_plugin`] for what it does on its own and `examples/dialog_walkthrough.rs` for a minimal app.
+
+use bevy::prelude::*;
+
+mod conditions;
+mod context;
+mod journal;
+mod memory;
+mod resources;
+
+pub use conditions::{add_conditions, ActiveConditions, ConditionAddEvent, ConditionId};
+pub use context::{
+    enter_pages, ContextRequirements, DialogContext, DialogEffect, DialogEffectEvent,
+};
+pub use journal::{DialogChoiceEvent, DialogJournal, JournalChoice, JournalEntry, PageRef};
+pub use memory::{MemoryRequirements, NpcMemory};
+pub use resources::{
+    ConditionalLine, CurrentDialog, Dialog, DialogChoice, DialogEvent, DialogId, InitialPage,
+    NextPage, Page, PageId, RequirementContext,
+};
+
+/// Keeps track of the world state dialogs depend on and executes the effects of pages, for a game whose gestures are of type `E`
+/// and whose texts are translated by `L`:
+/// - [`ConditionAddEvent`]s are collected into the [`ActiveConditions`].
+/// - Whenever the [`CurrentDialog`] shows a new page, its text is fixed and its [`DialogEffect`]s are applied to the [`DialogContext`],
+///   each announced by a [`DialogEffectEvent`], see [`enter_pages`].
+///
+/// Starting dialogs, showing them and recording the player's choices in a [`DialogJournal`] is up to the game,
+/// which sends the [`DialogEvent`], [`DialogChoiceEvent`] and [`DialogEffectEvent`] registered here.
+/// All systems run in the [`DialogSystemSet`], which games usually restrict to the state in which they are played.
+pub fn dialog_runtime_plugin<E, L>(app: &mut App)
+where
+    E: Clone + Send + Sync + 'static,
+    L: DialogLocalization,
+{
+    app.register_type::<DialogId>()
+        .register_type::<DialogJournal>()
+        .register_type::<DialogContext>()
+        .register_type::<ActiveConditions>()
+        .init_resource::<DialogContext>()
+        .init_resource::<ActiveConditions>()
+        .add_event::<DialogEvent>()
+        .add_event::<DialogChoiceEvent>()
+        .add_event::<DialogEffectEvent>()
+        .add_event::<ConditionAddEvent>()
+        .add_systems((add_conditions, enter_pages::<E, L>).in_set(DialogSystemSet));
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct DialogSystemSet;
+
+/// Translates the keys that dialogs store instead of texts.
+/// Changing the resource translates the text of the current page again.
+pub trait DialogLocalization: Resource {
+    /// The text for the key, or the key itself if there is no translation for it.
+    fn get<'a>(&'a self, key: &'a str) -> &'a str;
+}
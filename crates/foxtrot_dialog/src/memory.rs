@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What the player did to a character, which dialogs held with it can require.
+/// The game decides what counts, e.g. when the character has seen the player.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct NpcMemory {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_seen_player_position: Option<Vec3>,
+    #[serde(default)]
+    pub times_talked_to: u32,
+    #[serde(default)]
+    pub was_attacked: bool,
+}
+
+/// Requirements on the [`NpcMemory`] of the character a dialog is held with.
+/// Characters without a memory are treated as having an empty one.
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct MemoryRequirements {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_times_talked_to: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_times_talked_to: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub was_attacked: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_seen_player: Option<bool>,
+}
+
+impl MemoryRequirements {
+    pub fn is_empty(&self) -> bool {
+        self == &default()
+    }
+
+    pub fn is_met(&self, memory: Option<&NpcMemory>) -> bool {
+        let memory = memory.cloned().unwrap_or_default();
+        self.min_times_talked_to
+            .map_or(true, |min| memory.times_talked_to >= min)
+            && self
+                .max_times_talked_to
+                .map_or(true, |max| memory.times_talked_to <= max)
+            && self
+                .was_attacked
+                .map_or(true, |was_attacked| memory.was_attacked == was_attacked)
+            && self.has_seen_player.map_or(true, |has_seen_player| {
+                memory.last_seen_player_position.is_some() == has_seen_player
+            })
+    }
+}
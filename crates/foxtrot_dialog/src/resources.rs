@@ -0,0 +1,365 @@
+use crate::conditions::{ActiveConditions, ConditionId};
+use crate::context::{ContextRequirements, DialogContext, DialogEffect};
+use crate::journal::{DialogJournal, PageRef};
+use crate::memory::{MemoryRequirements, NpcMemory};
+use crate::DialogLocalization;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::reflect::{TypeUuid, Uuid};
+use bevy::utils::{HashMap, HashSet};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, Serialize, Deserialize, FromReflect)]
+#[reflect(Serialize, Deserialize)]
+pub struct DialogEvent {
+    pub dialog: DialogId,
+    pub source: Entity,
+    pub page: Option<PageId>,
+    /// Set when a dialog is restored from a save game, whose page was already entered before saving,
+    /// so that its effects are not executed again.
+    #[serde(default)]
+    pub resumed: bool,
+}
+
+/// The dialog the player is currently in. `E` is the game's type of gestures, see [`Page::emote`].
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+pub struct CurrentDialog<E> {
+    pub source: Entity,
+    pub id: DialogId,
+    pub dialog: Dialog<E>,
+    pub current_page: PageId,
+    pub last_choice: Option<ConditionId>,
+    /// Text of the current page together with the conditional lines that applied when it was shown
+    #[serde(default)]
+    pub text: String,
+    /// The page whose effects were executed last, so they run only once per visit of the page
+    #[serde(default)]
+    pub entered_page: Option<PageId>,
+}
+impl<E: Clone> CurrentDialog<E> {
+    pub fn fetch_page(&self, page_id: &PageId) -> Result<Page<E>> {
+        self.dialog
+            .pages
+            .get(page_id)
+            .with_context(|| format!("Failed to fetch page with id {}", page_id.0))
+            .cloned()
+    }
+    pub fn fetch_current_page(&self) -> Result<Page<E>> {
+        self.fetch_page(&self.current_page)
+    }
+
+    /// The options for leaving the current page, with [`NextPage::SameAs`] resolved.
+    pub fn resolve_next_page(&self) -> Result<NextPage> {
+        let mut visited = vec![self.current_page.clone()];
+        let mut next_page = self.fetch_current_page()?.next_page;
+        while let NextPage::SameAs(other_page_id) = next_page {
+            if visited.contains(&other_page_id) {
+                bail!(
+                    "Page {} of dialog {} refers back to itself through `SameAs`",
+                    self.current_page.0,
+                    self.id.0
+                );
+            }
+            next_page = self.fetch_page(&other_page_id)?.next_page;
+            visited.push(other_page_id);
+        }
+        Ok(next_page)
+    }
+
+    /// Continues to the next page if there is exactly one option for it.
+    pub fn auto_advance(&mut self) -> Result<()> {
+        if let NextPage::Continue(next_page_id) = self.resolve_next_page()? {
+            self.current_page = next_page_id;
+        }
+        Ok(())
+    }
+
+    /// Whether the choice led to the current page, so it should not be offered again right away.
+    pub fn was_just_picked(&self, choice_id: &ConditionId) -> bool {
+        self.last_choice.as_ref() == Some(choice_id)
+    }
+}
+
+/// A dialog as loaded from a `.dlg.ron` file. `E` is the game's type of gestures, see [`Page::emote`].
+/// All dialogs share one [`TypeUuid`], so a game can only use a single type of gestures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dialog<E> {
+    pub initial_page: Vec<InitialPage>,
+    pub pages: HashMap<PageId, Page<E>>,
+}
+
+impl<E> TypeUuid for Dialog<E> {
+    const TYPE_UUID: Uuid = Uuid::from_u128(0xf7c10043_7196_4ead_a4dd_040c33798a62);
+}
+
+impl<E> Default for Dialog<E> {
+    fn default() -> Self {
+        Self {
+            initial_page: default(),
+            pages: default(),
+        }
+    }
+}
+
+impl<E> Dialog<E> {
+    /// The first initial page whose requirements are met.
+    pub fn initial_page_id(
+        &self,
+        context: &RequirementContext,
+        dialog: &DialogId,
+    ) -> Option<PageId> {
+        self.initial_page
+            .iter()
+            .find(|page| page.is_available(context, dialog))
+            .map(|page| page.id.clone())
+    }
+}
+
+/// Everything the requirements of pages and choices are checked against.
+#[derive(Debug, Clone, Copy)]
+pub struct RequirementContext<'a> {
+    pub active_conditions: &'a ActiveConditions,
+    pub journal: &'a DialogJournal,
+    /// Memory of the character the dialog is held with
+    pub memory: Option<&'a NpcMemory>,
+    pub dialog_context: &'a DialogContext,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, Serialize, Deserialize, Default, FromReflect)]
+#[reflect(Serialize, Deserialize)]
+pub struct InitialPage {
+    pub id: PageId,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub positive_requirements: HashSet<ConditionId>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub negative_requirements: HashSet<ConditionId>,
+    /// Pages that must have been seen before, as recorded in the [`DialogJournal`]
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub seen_pages: HashSet<PageRef>,
+    /// Pages that must not have been seen yet
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub unseen_pages: HashSet<PageRef>,
+    #[serde(default, skip_serializing_if = "MemoryRequirements::is_empty")]
+    pub memory: MemoryRequirements,
+    #[serde(default, skip_serializing_if = "ContextRequirements::is_empty")]
+    pub context: ContextRequirements,
+}
+
+impl InitialPage {
+    pub fn is_available(&self, context: &RequirementContext, dialog: &DialogId) -> bool {
+        self.positive_requirements
+            .is_subset(&context.active_conditions.0)
+            && self
+                .negative_requirements
+                .is_disjoint(&context.active_conditions.0)
+            && meets_page_requirements(
+                &self.seen_pages,
+                &self.unseen_pages,
+                context.journal,
+                dialog,
+            )
+            && self.memory.is_met(context.memory)
+            && self.context.is_met(context.dialog_context)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<E> {
+    /// Key of the text in the [`DialogLocalization`]
+    pub text: String,
+    /// Key of the name shown above the text in the [`DialogLocalization`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speaker: Option<String>,
+    /// Path of an image shown next to the text relative to the assets directory, e.g. `"portraits/fox.png"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portrait: Option<String>,
+    #[serde(default = "get_default_talking_speed")]
+    pub talking_speed: f32,
+    pub next_page: NextPage,
+    /// Path of an audio clip relative to the assets directory, e.g. `"audio/voice/fox_greet.ogg"`.
+    /// While it plays, the text is revealed in sync with the clip instead of using [`Page::talking_speed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice_over: Option<String>,
+    /// Continue to the next page when the voice-over ends. Only applies to pages with [`NextPage::Continue`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_advance: bool,
+    /// Gesture the speaker plays when this page is shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emote: Option<E>,
+    /// Appended to [`Page::text`] on their own line if their requirements are met when the page is shown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<ConditionalLine>,
+    /// Executed in order when the page is shown, after its conditional lines have been evaluated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub effects: Vec<DialogEffect>,
+}
+
+impl<E> Page<E> {
+    /// The translated text shown for this page in the given context.
+    pub fn text_in(
+        &self,
+        context: &DialogContext,
+        localization: &impl DialogLocalization,
+    ) -> String {
+        self.lines
+            .iter()
+            .filter(|line| line.context.is_met(context))
+            .fold(localization.get(&self.text).to_owned(), |text, line| {
+                format!("{text}\n{}", localization.get(&line.text))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, Serialize, Deserialize, Default, FromReflect)]
+#[reflect(Serialize, Deserialize)]
+pub struct ConditionalLine {
+    /// Key of the text in the [`DialogLocalization`]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "ContextRequirements::is_empty")]
+    pub context: ContextRequirements,
+}
+
+fn meets_page_requirements(
+    seen_pages: &HashSet<PageRef>,
+    unseen_pages: &HashSet<PageRef>,
+    journal: &DialogJournal,
+    dialog: &DialogId,
+) -> bool {
+    let has_seen = |page: &PageRef| {
+        let (dialog, page) = page.resolve(dialog);
+        journal.has_seen(&dialog, &page)
+    };
+    seen_pages.iter().all(has_seen) && !unseen_pages.iter().any(has_seen)
+}
+
+fn get_default_talking_speed() -> f32 {
+    1.
+}
+
+impl<E> Default for Page<E> {
+    fn default() -> Self {
+        Self {
+            text: default(),
+            speaker: default(),
+            portrait: default(),
+            talking_speed: get_default_talking_speed(),
+            next_page: default(),
+            voice_over: default(),
+            auto_advance: default(),
+            emote: default(),
+            lines: default(),
+            effects: default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum NextPage {
+    /// There is only one automatic option for the next page
+    Continue(PageId),
+    /// The user can choose between different answers that determine the next page
+    Choice(IndexMap<ConditionId, DialogChoice>),
+    /// Use `next_page` of the specified `Page`
+    SameAs(PageId),
+    /// Exit dialog after this page
+    Exit,
+}
+impl Default for NextPage {
+    fn default() -> Self {
+        Self::Exit
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default, Reflect, Serialize, Deserialize, FromReflect)]
+#[reflect(Serialize, Deserialize)]
+pub struct DialogChoice {
+    /// Key of the player's answer in the [`DialogLocalization`]
+    pub text: String,
+    pub next_page_id: PageId,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub positive_requirements: HashSet<ConditionId>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub negative_requirements: HashSet<ConditionId>,
+    /// Pages that must have been seen before, as recorded in the [`DialogJournal`]
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub seen_pages: HashSet<PageRef>,
+    /// Pages that must not have been seen yet
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub unseen_pages: HashSet<PageRef>,
+    #[serde(default, skip_serializing_if = "MemoryRequirements::is_empty")]
+    pub memory: MemoryRequirements,
+    #[serde(default, skip_serializing_if = "ContextRequirements::is_empty")]
+    pub context: ContextRequirements,
+}
+
+impl DialogChoice {
+    pub fn is_available(&self, context: &RequirementContext, dialog: &DialogId) -> bool {
+        self.positive_requirements
+            .is_subset(&context.active_conditions.0)
+            && self
+                .negative_requirements
+                .is_disjoint(&context.active_conditions.0)
+            && meets_page_requirements(
+                &self.seen_pages,
+                &self.unseen_pages,
+                context.journal,
+                dialog,
+            )
+            && self.memory.is_met(context.memory)
+            && self.context.is_met(context.dialog_context)
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Eq,
+    PartialEq,
+    Default,
+    Component,
+    Reflect,
+    Hash,
+    Serialize,
+    Deserialize,
+    FromReflect,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct DialogId(pub String);
+impl DialogId {
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl From<String> for DialogId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<DialogId> for String {
+    fn from(value: DialogId) -> Self {
+        value.0
+    }
+}
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Default, Reflect, FromReflect, Hash, Serialize, Deserialize,
+)]
+#[reflect(Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct PageId(pub String);
+
+impl From<String> for PageId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<PageId> for String {
+    fn from(value: PageId) -> Self {
+        value.0
+    }
+}
@@ -0,0 +1,49 @@
+//! Registers a spawner for a single kind of object and spawns a few of them, one of them a frame late.
+//! Runs headless and exits after a couple of frames.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use foxtrot_spawning::{spawning_plugin, AddObjectSpawnerExt, SpawnEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Object {
+    Marker,
+}
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    spawning_plugin::<Object>(&mut app);
+    app.add_object_spawner(Object::Marker, spawn_marker)
+        .add_startup_system(request_markers)
+        .add_system(exit_after_a_few_frames)
+        .run();
+}
+
+fn spawn_marker(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    commands.spawn((Name::new("Marker"), transform)).id()
+}
+
+fn request_markers(mut spawn_events: EventWriter<SpawnEvent<Object>>) {
+    spawn_events.send(SpawnEvent::new(
+        Object::Marker,
+        Transform::from_xyz(0., 0., 0.),
+    ));
+    spawn_events
+        .send(SpawnEvent::new(Object::Marker, Transform::from_xyz(1., 0., 0.)).delay_frames(1));
+}
+
+fn exit_after_a_few_frames(
+    mut frames: Local<u32>,
+    markers: Query<(&Name, &Transform)>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    *frames += 1;
+    if *frames < 3 {
+        return;
+    }
+    for (name, transform) in &markers {
+        println!("{name} at {}", transform.translation);
+    }
+    exit_events.send(AppExit);
+}
@@ -0,0 +1,128 @@
+//! Registry of spawners for the objects of a game, as used by Foxtrot.
+//!
+//! Objects are identified by a key, usually a fieldless enum like Foxtrot's `GameObject`.
+//! Each key gets a spawner system registered with [`AddObjectSpawnerExt::add_object_spawner`],
+//! which receives the object's transform and returns the root entity it spawned.
+//! Objects are then spawned by sending a [`SpawnEvent`] or right away with [`spawn_object`].
+//! See `examples/spawn_markers.rs` for a minimal app.
+
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Identifies a kind of object that can be spawned.
+/// Implemented for every type that fits, so it never needs to be implemented by hand.
+pub trait ObjectKey: Debug + Copy + Eq + Hash + Send + Sync + 'static {}
+
+impl<T: Debug + Copy + Eq + Hash + Send + Sync + 'static> ObjectKey for T {}
+
+/// Registers the [`ObjectSpawners`] for objects identified by `K` and spawns the objects of incoming [`SpawnEvent`]s.
+pub fn spawning_plugin<K: ObjectKey>(app: &mut App) {
+    app.init_resource::<ObjectSpawners<K>>()
+        .add_event::<SpawnEvent<K>>()
+        .add_system(spawn_objects::<K>);
+}
+
+/// Spawner of an object. Receives the object's transform and returns the root entity it spawned.
+pub type ObjectSpawner = Box<dyn System<In = Transform, Out = Entity>>;
+
+#[derive(Resource)]
+pub struct ObjectSpawners<K: ObjectKey>(pub HashMap<K, ObjectSpawner>);
+
+impl<K: ObjectKey> Default for ObjectSpawners<K> {
+    fn default() -> Self {
+        Self(default())
+    }
+}
+
+/// Requests spawning an object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnEvent<K> {
+    pub object: K,
+    pub transform: Transform,
+    /// Number of frames to wait before spawning, e.g. so that the player spawns after the level it stands on.
+    pub delay: usize,
+}
+
+impl<K> SpawnEvent<K> {
+    pub fn new(object: K, transform: Transform) -> Self {
+        Self {
+            object,
+            transform,
+            delay: 0,
+        }
+    }
+
+    pub fn delay_frames(self, delay: usize) -> Self {
+        Self { delay, ..self }
+    }
+}
+
+pub trait AddObjectSpawnerExt {
+    /// Registers the spawner of an object.
+    /// The spawner receives the object's transform and returns the root entity it spawned.
+    fn add_object_spawner<K: ObjectKey, M>(
+        &mut self,
+        object: K,
+        spawner: impl IntoSystem<Transform, Entity, M>,
+    ) -> &mut Self;
+}
+
+impl AddObjectSpawnerExt for App {
+    fn add_object_spawner<K: ObjectKey, M>(
+        &mut self,
+        object: K,
+        spawner: impl IntoSystem<Transform, Entity, M>,
+    ) -> &mut Self {
+        let mut spawner = IntoSystem::into_system(spawner);
+        spawner.initialize(&mut self.world);
+        self.world
+            .get_resource_or_insert_with(ObjectSpawners::<K>::default)
+            .0
+            .insert(object, Box::new(spawner));
+        self
+    }
+}
+
+/// Runs the spawner of an object right away and returns the entity it spawned.
+pub fn spawn_object<K: ObjectKey>(
+    world: &mut World,
+    object: K,
+    transform: Transform,
+) -> Option<Entity> {
+    world.resource_scope(|world, mut spawners: Mut<ObjectSpawners<K>>| {
+        let Some(spawner) = spawners.0.get_mut(&object) else {
+            error!("Failed to spawn {object:?}: No spawner registered");
+            return None;
+        };
+        let entity = spawner.run(transform, world);
+        spawner.apply_buffers(world);
+        Some(entity)
+    })
+}
+
+/// Spawns the objects of all [`SpawnEvent`]s whose delay has passed.
+pub fn spawn_objects<K: ObjectKey>(
+    world: &mut World,
+    spawn_events: &mut SystemState<EventReader<SpawnEvent<K>>>,
+    mut delayed: Local<Vec<SpawnEvent<K>>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_objects").entered();
+    let requests: Vec<_> = spawn_events.get_mut(world).iter().copied().collect();
+    delayed.extend(requests);
+    if delayed.is_empty() {
+        return;
+    }
+    let (ready, waiting): (Vec<_>, Vec<_>) =
+        delayed.drain(..).partition(|request| request.delay == 0);
+    *delayed = waiting
+        .into_iter()
+        .map(|request| request.delay_frames(request.delay - 1))
+        .collect();
+    for request in ready {
+        spawn_object(world, request.object, request.transform);
+    }
+}
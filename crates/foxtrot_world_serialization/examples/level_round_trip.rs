@@ -0,0 +1,79 @@
+//! Defines a small level format with one migration, loads a level of the old version and prints it in the current one.
+
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use foxtrot_world_serialization::{
+    object_names, Migration, ObjectMetadata, ObjectName, VersionedFormat,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Object {
+    Crate,
+    Lamp,
+}
+
+impl ObjectName for Object {
+    fn name(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Object::Crate, Object::Lamp]
+            .into_iter()
+            .find(|object| object.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Level {
+    version: u32,
+    #[serde(with = "object_names")]
+    objects: Vec<(Object, Transform, ObjectMetadata)>,
+}
+
+/// Version 0 only stored the names of the objects, which all stood at the origin.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct UnversionedLevel {
+    objects: Vec<String>,
+}
+
+fn migrate_from_v0(level: &str) -> Result<String> {
+    let level: UnversionedLevel = ron::from_str(level)?;
+    let objects = level
+        .objects
+        .iter()
+        .map(|name| {
+            Object::from_name(name)
+                .map(|object| (object, Transform::IDENTITY, ObjectMetadata::default()))
+                .with_context(|| format!("Unknown object \"{name}\""))
+        })
+        .collect::<Result<_>>()?;
+    Ok(ron::to_string(&Level {
+        version: 1,
+        objects,
+    })?)
+}
+
+const MIGRATIONS: &[Migration] = &[migrate_from_v0];
+
+const LEVEL_FORMAT: VersionedFormat = VersionedFormat {
+    name: "level",
+    current_version: 1,
+    oldest_version: 0,
+    migrations: MIGRATIONS,
+};
+
+fn main() -> Result<()> {
+    let old_level = r#"(objects: ["Crate", "Lamp"])"#;
+    let (mut level, version): (Level, _) = LEVEL_FORMAT.deserialize(old_level.as_bytes())?;
+    println!("Loaded a level of format version {version}");
+
+    level.objects[1].1.translation = Vec3::new(0., 2., 0.);
+    level.objects[1].2.insert("color", "warm");
+    println!(
+        "{}",
+        ron::ser::to_string_pretty(&level, default()).context("Failed to serialize level")?
+    );
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::{fs, iter};
+
+/// Converts the text of a file of one format version into the next one.
+/// Migrations work on text instead of [`ron::Value`]s, since those cannot represent enum variants.
+pub type Migration = fn(&str) -> Result<String>;
+
+/// A RON format with a top-level `version` field. Files without one are version 0.
+#[derive(Debug, Clone, Copy)]
+pub struct VersionedFormat {
+    /// Name of the stored data in messages, e.g. `"level"`
+    pub name: &'static str,
+    /// Version of newly written files.
+    /// Bump this whenever a change would break existing files and append a migration from the previous version.
+    pub current_version: u32,
+    /// Oldest version that can still be migrated to the current one.
+    pub oldest_version: u32,
+    /// The migration at index `i` converts files of version `oldest_version + i` to the version after it.
+    pub migrations: &'static [Migration],
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+struct FormatVersion {
+    #[serde(default)]
+    version: u32,
+}
+
+impl VersionedFormat {
+    /// Reads only the `version` field.
+    pub fn read_version(&self, serialized: &str) -> Result<u32> {
+        let FormatVersion { version } = ron::from_str(serialized)
+            .with_context(|| format!("Failed to read {} format version", self.name))?;
+        Ok(version)
+    }
+
+    /// Runs all migrations from `version` up to the current version.
+    pub fn migrate(&self, serialized: &str, version: u32) -> Result<String> {
+        let name = self.name;
+        if version > self.current_version {
+            bail!(
+                "The {name} has format version {version}, but this build only supports versions up to {}",
+                self.current_version
+            );
+        }
+        if version < self.oldest_version {
+            bail!(
+                "The {name} has format version {version}, which is too old to be migrated. The oldest supported version is {}",
+                self.oldest_version
+            );
+        }
+        let mut serialized = serialized.to_owned();
+        let first_migration = (version - self.oldest_version) as usize;
+        for (index, migrate) in self.migrations.iter().enumerate().skip(first_migration) {
+            let from = self.oldest_version + index as u32;
+            serialized = migrate(&serialized).with_context(|| {
+                format!(
+                    "Failed to migrate {name} from format version {from} to {}",
+                    from + 1
+                )
+            })?;
+        }
+        Ok(serialized)
+    }
+
+    /// Deserializes a file of any supported format version after migrating it to the current version.
+    /// Returns the data together with the version it was stored in.
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<(T, u32)> {
+        let serialized = std::str::from_utf8(bytes)
+            .with_context(|| format!("The {} is not valid UTF-8", self.name))?;
+        let version = self.read_version(serialized)?;
+        let migrated = self.migrate(serialized, version)?;
+        let data = ron::from_str(&migrated).with_context(|| {
+            format!(
+                "Failed to deserialize {} of format version {version}",
+                self.name
+            )
+        })?;
+        Ok((data, version))
+    }
+}
+
+/// Writes `contents` to `<dir>/<name>.<extension>` and returns the path written to.
+/// Unless `overwrite` is set, existing files are kept and the first free one of `<name>-1` up to `<name>-9` is used instead.
+pub fn write_numbered(
+    dir: &Path,
+    name: &str,
+    extension: &str,
+    contents: &str,
+    overwrite: bool,
+) -> Result<PathBuf> {
+    let max_candidates = if overwrite { 1 } else { 10 };
+    let valid_candidates: Vec<_> = iter::once(name.to_owned())
+        .chain((1..).map(|n| format!("{name}-{n}")))
+        .map(|filename| dir.join(filename).with_extension(extension))
+        .map(|path| (path.clone(), fs::try_exists(path).ok()))
+        .take(max_candidates)
+        .filter_map(|(path, maybe_exists)| maybe_exists.map(|exists| (path, exists)))
+        .collect();
+    if valid_candidates.is_empty() {
+        bail!("Invalid path");
+    }
+    let path = valid_candidates
+        .into_iter()
+        .find_map(|(path, exists)| (overwrite || !exists).then_some(path))
+        .context("Already got too many files with this name")?;
+    let dir = path.parent().context("Failed to get directory")?;
+    fs::create_dir_all(dir).context("Failed to create directory")?;
+    fs::write(&path, contents).context("Failed to write file")?;
+    Ok(path)
+}
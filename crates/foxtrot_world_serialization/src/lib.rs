@@ -0,0 +1,17 @@
+#![feature(fs_try_exists)]
+
+//! Building blocks for storing the objects of a level in versioned RON files, as used by Foxtrot's levels:
+//! - [`VersionedFormat`] reads files of any supported format version and migrates them to the current one.
+//! - [`object_names`] and [`stored_objects`] store objects as `(name, transform)`, or as `(name, transform, metadata)`
+//!   if they have [`ObjectMetadata`], so that the files stay readable and diffable.
+//! - [`write_numbered`] writes a file without replacing existing ones unless asked to.
+//!
+//! The level type itself is up to the game, see `examples/level_round_trip.rs`.
+
+mod format;
+mod metadata;
+mod objects;
+
+pub use format::{write_numbered, Migration, VersionedFormat};
+pub use metadata::ObjectMetadata;
+pub use objects::{object_names, stored_objects, ObjectName, StoredObject};
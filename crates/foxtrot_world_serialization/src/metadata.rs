@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Free-form key/value pairs attached to a spawned object in the editor, e.g. `dialog = merchant_01` or `loot_table = chest_rare`.
+/// They are saved in levels and prefabs together with the object, so gameplay systems can query them at runtime.
+#[derive(
+    Debug, Clone, Eq, PartialEq, Component, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct ObjectMetadata(pub Vec<(String, String)>);
+
+impl ObjectMetadata {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Replaces the value of an existing key or appends a new entry.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        match self.0.iter_mut().find(|(entry_key, _)| *entry_key == key) {
+            Some((_, entry_value)) => *entry_value = value,
+            None => self.0.push((key, value)),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self.0.iter().position(|(entry_key, _)| entry_key == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,120 @@
+use crate::ObjectMetadata;
+use bevy::prelude::*;
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Formatter};
+
+/// A kind of object that is stored by name, usually a fieldless enum.
+/// Storing names instead of enum variants keeps files readable as [`ron::Value`]s, e.g. for migrations.
+pub trait ObjectName: Sized {
+    fn name(&self) -> String;
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+/// An object as it is stored: `(name, transform)`, or `(name, transform, metadata)` if it has [`ObjectMetadata`],
+/// so that objects without metadata keep the same format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredObject(pub String, pub Transform, pub ObjectMetadata);
+
+impl Serialize for StoredObject {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let StoredObject(name, transform, metadata) = self;
+        if metadata.is_empty() {
+            (name, transform).serialize(serializer)
+        } else {
+            (name, transform, metadata).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for StoredObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(3, StoredObjectVisitor)
+    }
+}
+
+struct StoredObjectVisitor;
+
+impl<'de> Visitor<'de> for StoredObjectVisitor {
+    type Value = StoredObject;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a tuple of an object name, a transform and optional metadata")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let name = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let transform = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let metadata = seq.next_element()?.unwrap_or_default();
+        Ok(StoredObject(name, transform, metadata))
+    }
+}
+
+/// Use with `#[serde(with = "object_names")]` on a `Vec<(K, Transform, ObjectMetadata)>` to store the objects as [`StoredObject`]s.
+pub mod object_names {
+    use super::{ObjectName, StoredObject};
+    use crate::ObjectMetadata;
+    use bevy::prelude::*;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<K: ObjectName, S: Serializer>(
+        objects: &[(K, Transform, ObjectMetadata)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        objects
+            .iter()
+            .map(|(object, transform, metadata)| {
+                StoredObject(object.name(), *transform, metadata.clone())
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, K: ObjectName, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(K, Transform, ObjectMetadata)>, D::Error> {
+        Vec::<StoredObject>::deserialize(deserializer)?
+            .into_iter()
+            .map(|StoredObject(name, transform, metadata)| {
+                K::from_name(&name)
+                    .map(|object| (object, transform, metadata))
+                    .ok_or_else(|| D::Error::custom(format!("Unknown object \"{name}\"")))
+            })
+            .collect()
+    }
+}
+
+/// Use with `#[serde(with = "stored_objects")]` on a `Vec<(String, Transform, ObjectMetadata)>` to store the objects as [`StoredObject`]s.
+pub mod stored_objects {
+    use super::StoredObject;
+    use crate::ObjectMetadata;
+    use bevy::prelude::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        objects: &[(String, Transform, ObjectMetadata)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        objects
+            .iter()
+            .map(|(name, transform, metadata)| {
+                StoredObject(name.clone(), *transform, metadata.clone())
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(String, Transform, ObjectMetadata)>, D::Error> {
+        Ok(Vec::<StoredObject>::deserialize(deserializer)?
+            .into_iter()
+            .map(|StoredObject(name, transform, metadata)| (name, transform, metadata))
+            .collect())
+    }
+}
@@ -1,4 +1,8 @@
+#[cfg(all(feature = "native", not(feature = "tracing")))]
+use crate::file_system_interaction::log_history::init_logging;
 use anyhow::{Context, Result};
+#[cfg(all(feature = "native", not(feature = "tracing")))]
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use bevy::window::PresentMode;
 use bevy::window::PrimaryWindow;
@@ -24,6 +28,12 @@ pub fn bevy_config_plugin(app: &mut App) {
         watch_for_changes: true,
         ..default()
     });
+    // Logging is set up by hand so that bug reports can include the log output.
+    // Builds with the `tracing` feature keep Bevy's logging, as it also sets up the profiler.
+    #[cfg(all(feature = "native", not(feature = "tracing")))]
+    let default_plugins = default_plugins.disable::<LogPlugin>();
+    #[cfg(all(feature = "native", not(feature = "tracing")))]
+    app.insert_resource(init_logging());
     app.insert_resource(Msaa::Sample4)
         .insert_resource(ClearColor(Color::rgb(0.4, 0.4, 0.4)))
         .add_plugins(default_plugins)
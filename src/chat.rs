@@ -0,0 +1,191 @@
+use crate::console::{
+    CommandSource, ConsoleCommandEvent, ConsoleLine, ConsoleOutputEvent, PermissionLevel,
+};
+use crate::player_control::actions::{ActionsFrozen, UiAction};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Seconds for which new messages stay visible while the chat is closed.
+const MESSAGE_VISIBILITY: f32 = 10.0;
+const MAX_VISIBLE_MESSAGES: usize = 8;
+
+/// Handles the text chat overlay, opened with T. Messages starting with a slash are routed to the
+/// console command registry as [`ConsoleCommandEvent`]s, limited to the [`LocalPermissionLevel`].
+/// Messages are exchanged via [`ChatMessageEvent`]s so that a network layer can forward them.
+pub fn chat_plugin(app: &mut App) {
+    app.init_resource::<ChatLog>()
+        .init_resource::<ChatInput>()
+        .init_resource::<LocalPermissionLevel>()
+        .add_event::<ChatMessageEvent>()
+        .add_systems(
+            (
+                receive_chat_messages,
+                receive_command_output,
+                toggle_chat,
+                show_chat,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// `None` for system messages, e.g. the output of slash commands.
+    pub sender: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessageEvent(pub ChatMessage);
+
+/// The permission level slash commands are run with. In a multiplayer session this is assigned by the server.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Resource, Serialize, Deserialize)]
+pub struct LocalPermissionLevel(pub PermissionLevel);
+
+impl Default for LocalPermissionLevel {
+    fn default() -> Self {
+        #[cfg(feature = "dev")]
+        return Self(PermissionLevel::Admin);
+        #[cfg(not(feature = "dev"))]
+        return Self(PermissionLevel::Player);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct ChatLog {
+    /// Messages with the time in seconds at which they were received.
+    pub messages: VecDeque<(ChatMessage, f32)>,
+}
+
+impl ChatLog {
+    const MAX_MESSAGES: usize = 100;
+
+    pub fn push(&mut self, message: ChatMessage, received_at: f32) {
+        self.messages.push_back((message, received_at));
+        while self.messages.len() > Self::MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Default)]
+pub struct ChatInput {
+    pub open: bool,
+    pub text: String,
+}
+
+fn receive_chat_messages(
+    time: Res<Time>,
+    mut chat_messages: EventReader<ChatMessageEvent>,
+    mut chat_log: ResMut<ChatLog>,
+) {
+    for ChatMessageEvent(message) in chat_messages.iter() {
+        chat_log.push(message.clone(), time.raw_elapsed_seconds());
+    }
+}
+
+fn receive_command_output(
+    time: Res<Time>,
+    mut console_output: EventReader<ConsoleOutputEvent>,
+    mut chat_log: ResMut<ChatLog>,
+) {
+    for output in console_output.iter() {
+        if !matches!(output.source, CommandSource::Chat(_)) {
+            continue;
+        }
+        let text = match &output.line {
+            ConsoleLine::Input(text) | ConsoleLine::Output(text) => text.clone(),
+            ConsoleLine::Error(error) => format!("Error: {error}"),
+        };
+        chat_log.push(
+            ChatMessage { sender: None, text },
+            time.raw_elapsed_seconds(),
+        );
+    }
+}
+
+fn toggle_chat(
+    actions: Query<&ActionState<UiAction>>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut chat_input: ResMut<ChatInput>,
+) {
+    if chat_input.open || actions_frozen.is_frozen() {
+        return;
+    }
+    for action in actions.iter() {
+        if action.just_pressed(UiAction::ToggleChat) {
+            chat_input.open = true;
+            actions_frozen.freeze();
+        }
+    }
+}
+
+fn show_chat(
+    time: Res<Time>,
+    chat_log: Res<ChatLog>,
+    local_permission: Res<LocalPermissionLevel>,
+    mut chat_input: ResMut<ChatInput>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut chat_messages: EventWriter<ChatMessageEvent>,
+    mut console_commands: EventWriter<ConsoleCommandEvent>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_chat").entered();
+    let now = time.raw_elapsed_seconds();
+    let visible_messages: Vec<_> = chat_log
+        .messages
+        .iter()
+        .rev()
+        .take(MAX_VISIBLE_MESSAGES)
+        .filter(|(_, received_at)| chat_input.open || now - received_at < MESSAGE_VISIBILITY)
+        .rev()
+        .map(|(message, _)| message)
+        .collect();
+    if visible_messages.is_empty() && !chat_input.open {
+        return;
+    }
+
+    egui::Area::new("Chat")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::Vec2::new(10., -10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.set_max_width(400.);
+            for message in visible_messages {
+                match &message.sender {
+                    Some(sender) => ui.label(format!("{sender}: {}", message.text)),
+                    None => ui.label(egui::RichText::new(&message.text).italics()),
+                };
+            }
+            if !chat_input.open {
+                return;
+            }
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut chat_input.text)
+                    .desired_width(f32::INFINITY)
+                    .hint_text("Type a message or a /command"),
+            );
+            response.request_focus();
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let text = std::mem::take(&mut chat_input.text);
+                let text = text.trim();
+                if let Some(command) = text.strip_prefix('/') {
+                    console_commands.send(ConsoleCommandEvent::new(
+                        command,
+                        CommandSource::Chat(local_permission.0),
+                    ));
+                } else if !text.is_empty() {
+                    chat_messages.send(ChatMessageEvent(ChatMessage {
+                        sender: Some("Player".to_owned()),
+                        text: text.to_owned(),
+                    }));
+                }
+                chat_input.open = false;
+                actions_frozen.unfreeze();
+            }
+        });
+}
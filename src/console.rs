@@ -0,0 +1,214 @@
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Handles text commands that can be entered through a console UI, e.g. the one in the dev editor.
+/// Commands are registered by name with [`AddConsoleCommandExt::add_console_command`] and executed
+/// by sending a [`ConsoleCommandEvent`]. Their output is collected in [`ConsoleHistory`] and
+/// reported back to the issuer via [`ConsoleOutputEvent`].
+/// Commands can only be run by a [`CommandSource`] with at least the command's [`PermissionLevel`].
+pub fn console_plugin(app: &mut App) {
+    app.init_resource::<ConsoleCommands>()
+        .init_resource::<ConsoleHistory>()
+        .add_event::<ConsoleCommandEvent>()
+        .add_event::<ConsoleOutputEvent>()
+        .add_system(execute_console_commands)
+        .add_console_command_with_permission(
+            "help",
+            "Lists all available commands",
+            PermissionLevel::Player,
+            help,
+        )
+        .add_console_command_with_permission(
+            "clear",
+            "Clears the console output",
+            PermissionLevel::Player,
+            clear,
+        );
+}
+
+/// Receives the world and the whitespace separated arguments following the command name.
+/// The returned string is printed to the console if it is not empty.
+pub type ConsoleCommandFn = fn(&mut World, &[&str]) -> Result<String>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ConsoleCommand {
+    pub description: &'static str,
+    pub permission: PermissionLevel,
+    pub run: ConsoleCommandFn,
+}
+
+/// Commands that change the game state in ways a regular player could not, i.e. cheats, require [`PermissionLevel::Admin`].
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Default,
+)]
+pub enum PermissionLevel {
+    #[default]
+    Player,
+    Admin,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize, Default)]
+pub enum CommandSource {
+    /// The dev console, which is trusted with all commands.
+    #[default]
+    Console,
+    /// Slash commands from the chat, which are limited to the sender's permission level.
+    Chat(PermissionLevel),
+}
+
+impl CommandSource {
+    pub fn permission(&self) -> PermissionLevel {
+        match self {
+            CommandSource::Console => PermissionLevel::Admin,
+            CommandSource::Chat(permission) => *permission,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Resource)]
+pub struct ConsoleCommands(pub BTreeMap<String, ConsoleCommand>);
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleCommandEvent {
+    pub input: String,
+    pub source: CommandSource,
+}
+
+impl ConsoleCommandEvent {
+    pub fn new(input: impl Into<String>, source: CommandSource) -> Self {
+        Self {
+            input: input.into(),
+            source,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ConsoleOutputEvent {
+    pub source: CommandSource,
+    pub line: ConsoleLine,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ConsoleLine {
+    Input(String),
+    Output(String),
+    Error(String),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Serialize, Deserialize, Default)]
+pub struct ConsoleHistory {
+    pub lines: VecDeque<ConsoleLine>,
+}
+
+impl ConsoleHistory {
+    const MAX_LINES: usize = 200;
+
+    pub fn push(&mut self, line: ConsoleLine) {
+        self.lines.push_back(line);
+        while self.lines.len() > Self::MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+}
+
+pub trait AddConsoleCommandExt {
+    /// Registers a command that requires [`PermissionLevel::Admin`].
+    fn add_console_command(
+        &mut self,
+        name: &str,
+        description: &'static str,
+        run: ConsoleCommandFn,
+    ) -> &mut Self {
+        self.add_console_command_with_permission(name, description, PermissionLevel::Admin, run)
+    }
+
+    fn add_console_command_with_permission(
+        &mut self,
+        name: &str,
+        description: &'static str,
+        permission: PermissionLevel,
+        run: ConsoleCommandFn,
+    ) -> &mut Self;
+}
+
+impl AddConsoleCommandExt for App {
+    fn add_console_command_with_permission(
+        &mut self,
+        name: &str,
+        description: &'static str,
+        permission: PermissionLevel,
+        run: ConsoleCommandFn,
+    ) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ConsoleCommands::default)
+            .0
+            .insert(
+                name.to_owned(),
+                ConsoleCommand {
+                    description,
+                    permission,
+                    run,
+                },
+            );
+        self
+    }
+}
+
+fn execute_console_commands(world: &mut World) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("execute_console_commands").entered();
+    let events: Vec<_> = world
+        .resource_mut::<Events<ConsoleCommandEvent>>()
+        .drain()
+        .collect();
+    for ConsoleCommandEvent { input, source } in events {
+        world
+            .resource_mut::<ConsoleHistory>()
+            .push(ConsoleLine::Input(input.clone()));
+        let line = match run_command(world, &input, source) {
+            Ok(output) if output.is_empty() => continue,
+            Ok(output) => ConsoleLine::Output(output),
+            Err(e) => ConsoleLine::Error(format!("{e:#}")),
+        };
+        world.resource_mut::<ConsoleHistory>().push(line.clone());
+        world.send_event(ConsoleOutputEvent { source, line });
+    }
+}
+
+fn run_command(world: &mut World, input: &str, source: CommandSource) -> Result<String> {
+    let mut words = input.split_whitespace();
+    let Some(name) = words.next() else {
+        return Ok(String::new());
+    };
+    let args: Vec<_> = words.collect();
+    let command = world
+        .resource::<ConsoleCommands>()
+        .0
+        .get(name)
+        .copied()
+        .with_context(|| {
+            format!("Unknown command \"{name}\". Type \"help\" for a list of commands")
+        })?;
+    if source.permission() < command.permission {
+        bail!("Insufficient permissions to run \"{name}\"");
+    }
+    (command.run)(world, &args)
+}
+
+fn help(world: &mut World, _args: &[&str]) -> Result<String> {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands
+        .0
+        .iter()
+        .map(|(name, command)| format!("{name}: {}", command.description))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn clear(world: &mut World, _args: &[&str]) -> Result<String> {
+    world.resource_mut::<ConsoleHistory>().lines.clear();
+    Ok(String::new())
+}
@@ -1,4 +1,20 @@
+use crate::dev::asset_errors::asset_errors_plugin;
+use crate::dev::autosave::autosave_plugin;
+use crate::dev::brush::brush_plugin;
 use crate::dev::dev_editor::dev_editor_plugin;
+use crate::dev::editor_flags::editor_flags_plugin;
+use crate::dev::editor_layout::editor_layout_plugin;
+use crate::dev::gltf_export::gltf_export_plugin;
+use crate::dev::launch_preview::launch_preview_plugin;
+use crate::dev::level_validation::level_validation_plugin;
+use crate::dev::placement::placement_plugin;
+use crate::dev::scene_viewer::scene_viewer_plugin;
+use crate::dev::spline_editor::spline_editor_plugin;
+use crate::dev::transform_gizmo::transform_gizmo_plugin;
+use crate::dev::volume_editor::volume_editor_plugin;
+use crate::dev::waypoint_editor::waypoint_editor_plugin;
+use crate::dev::world_hash::world_hash_plugin;
+use crate::player_control::input_bindings::{qwerty_key_code, BindableAction, InputBindings};
 use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy_editor_pls::prelude::*;
@@ -6,17 +22,48 @@ use bevy_prototype_debug_lines::DebugLinesPlugin;
 use bevy_rapier3d::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
+pub mod asset_errors;
+pub mod autosave;
+pub mod brush;
 pub mod dev_editor;
+pub mod editor_flags;
+pub mod editor_layout;
+pub mod gltf_export;
+pub mod launch_preview;
+pub mod level_validation;
+pub mod placement;
+pub mod scene_viewer;
+pub mod spline_editor;
+pub mod transform_gizmo;
+pub mod volume_editor;
+pub mod waypoint_editor;
+pub mod world_hash;
 
 /// Plugin with debugging utility intended for use during development only.
 /// Don't include this in a release build.
 pub fn dev_plugin(app: &mut App) {
     {
         app.add_plugin(EditorPlugin)
-            .insert_resource(default_editor_controls())
+            .insert_resource(bevy_editor_pls::controls::EditorControls::default_bindings())
+            .add_system(apply_editor_binding.run_if(resource_changed::<InputBindings>()))
             .add_plugin(FrameTimeDiagnosticsPlugin::default())
             .add_plugin(DebugLinesPlugin::default())
+            .fn_plugin(asset_errors_plugin)
+            .fn_plugin(autosave_plugin)
+            .fn_plugin(brush_plugin)
             .fn_plugin(dev_editor_plugin)
+            .fn_plugin(editor_flags_plugin)
+            .fn_plugin(editor_layout_plugin)
+            .fn_plugin(gltf_export_plugin)
+            .fn_plugin(launch_preview_plugin)
+            .fn_plugin(level_validation_plugin)
+            .fn_plugin(placement_plugin)
+            .fn_plugin(scene_viewer_plugin)
+            .fn_plugin(spline_editor_plugin)
+            .fn_plugin(transform_gizmo_plugin)
+            .fn_plugin(volume_editor_plugin)
+            .fn_plugin(waypoint_editor_plugin)
+            .fn_plugin(world_hash_plugin)
             .add_plugin(LogDiagnosticsPlugin::filtered(vec![]))
             .add_plugin(RapierDebugRenderPlugin {
                 enabled: false,
@@ -25,16 +72,31 @@ pub fn dev_plugin(app: &mut App) {
     }
 }
 
-fn default_editor_controls() -> bevy_editor_pls::controls::EditorControls {
+/// Toggles the editor with the [`BindableAction::ToggleEditor`] bindings instead of the editor's default key.
+fn apply_editor_binding(
+    input_bindings: Res<InputBindings>,
+    mut editor_controls: ResMut<bevy_editor_pls::controls::EditorControls>,
+) {
+    use crate::player_control::input_bindings::Binding as InputBinding;
     use bevy_editor_pls::controls::*;
-    let mut editor_controls = EditorControls::default_bindings();
     editor_controls.unbind(Action::PlayPauseEditor);
-    editor_controls.insert(
-        Action::PlayPauseEditor,
-        Binding {
-            input: UserInput::Single(Button::Keyboard(KeyCode::Q)),
-            conditions: vec![BindingCondition::ListeningForText(false)],
-        },
-    );
-    editor_controls
+    for binding in input_bindings.get(BindableAction::ToggleEditor) {
+        let button = match *binding {
+            // The editor only knows key codes, so keys are handed over as they are labeled on a QWERTY layout
+            InputBinding::Key(key) => match qwerty_key_code(key) {
+                Some(key) => Button::Keyboard(key),
+                None => continue,
+            },
+            InputBinding::Mouse(button) => Button::Mouse(button),
+            // The editor does not support gamepads
+            InputBinding::Gamepad(_) => continue,
+        };
+        editor_controls.insert(
+            Action::PlayPauseEditor,
+            Binding {
+                input: UserInput::Single(button),
+                conditions: vec![BindingCondition::ListeningForText(false)],
+            },
+        );
+    }
 }
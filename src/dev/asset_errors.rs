@@ -0,0 +1,148 @@
+use bevy::asset::{HandleId, LoadState};
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::utils::{HashMap, HashSet};
+use bevy_egui::{egui, EguiContexts};
+use std::time::Duration;
+
+/// Keeps assets that failed to load, e.g. a glTF that cannot be parsed or a missing texture, on screen as toasts
+/// until they are dismissed, instead of only logging them once. Each toast names the asset's path and can reload it.
+/// The assets of scenes, meshes, materials and images used by entities are checked every second.
+pub fn asset_errors_plugin(app: &mut App) {
+    app.init_resource::<AssetErrors>().add_systems((
+        find_failed_assets.run_if(on_timer(Duration::from_secs(1))),
+        show_asset_error_toasts,
+    ));
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct AssetErrors {
+    pub failed: HashMap<HandleId, FailedAsset>,
+    /// Failures the user does not want to see again
+    pub dismissed: HashSet<HandleId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedAsset {
+    pub path: String,
+    /// Set while a reload is pending, so the toast can tell it apart from a new failure
+    pub retrying: bool,
+}
+
+fn find_failed_assets(
+    asset_server: Res<AssetServer>,
+    mut asset_errors: ResMut<AssetErrors>,
+    scenes: Query<&Handle<Scene>>,
+    meshes: Query<&Handle<Mesh>>,
+    material_handles: Query<&Handle<StandardMaterial>>,
+    images: Query<&Handle<Image>>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("find_failed_assets").entered();
+    let textures = materials.iter().flat_map(|(_, material)| {
+        [
+            &material.base_color_texture,
+            &material.emissive_texture,
+            &material.metallic_roughness_texture,
+            &material.normal_map_texture,
+            &material.occlusion_texture,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|handle| handle.id())
+    });
+    let handles: HashSet<_> = scenes
+        .iter()
+        .map(|handle| handle.id())
+        .chain(meshes.iter().map(|handle| handle.id()))
+        .chain(material_handles.iter().map(|handle| handle.id()))
+        .chain(images.iter().map(|handle| handle.id()))
+        .chain(textures)
+        .chain(asset_errors.failed.keys().copied())
+        .collect();
+
+    let asset_errors = asset_errors.as_mut();
+    for id in handles {
+        match asset_server.get_load_state(id) {
+            LoadState::Failed => {
+                if asset_errors.dismissed.contains(&id) {
+                    continue;
+                }
+                // Assets created at runtime have no path and cannot fail to load
+                let Some(path) = asset_server.get_handle_path(id) else {
+                    continue;
+                };
+                let path = path.path().to_string_lossy().into_owned();
+                match asset_errors.failed.get_mut(&id) {
+                    Some(failed) => failed.retrying = false,
+                    None => {
+                        asset_errors.failed.insert(
+                            id,
+                            FailedAsset {
+                                path,
+                                retrying: false,
+                            },
+                        );
+                    }
+                }
+            }
+            LoadState::Loaded => {
+                if let Some(failed) = asset_errors.failed.remove(&id) {
+                    info!("Loaded {} after retrying", failed.path);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn show_asset_error_toasts(
+    mut egui_contexts: EguiContexts,
+    asset_server: Res<AssetServer>,
+    mut asset_errors: ResMut<AssetErrors>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_asset_error_toasts").entered();
+    if asset_errors.failed.is_empty() {
+        return;
+    }
+    let mut failed: Vec<_> = asset_errors
+        .failed
+        .iter()
+        .map(|(id, failed)| (*id, failed.clone()))
+        .collect();
+    failed.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+    let mut retried = Vec::new();
+    let mut dismissed = Vec::new();
+    egui::Area::new("Asset Errors")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::Vec2::new(-10., -10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            for (id, failed) in &failed {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.colored_label(egui::Color32::LIGHT_RED, "Failed to load asset");
+                    ui.label(&failed.path);
+                    ui.horizontal(|ui| {
+                        if failed.retrying {
+                            ui.label("Retrying...");
+                        } else if ui.button("Retry").clicked() {
+                            retried.push((*id, failed.path.clone()));
+                        }
+                        if ui.button("Dismiss").clicked() {
+                            dismissed.push(*id);
+                        }
+                    });
+                });
+            }
+        });
+    for (id, path) in retried {
+        asset_server.reload_asset(path.as_str());
+        if let Some(failed) = asset_errors.failed.get_mut(&id) {
+            failed.retrying = true;
+        }
+    }
+    for id in dismissed {
+        asset_errors.failed.remove(&id);
+        asset_errors.dismissed.insert(id);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::dev::scene_viewer::SceneViewer;
+use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::level_serialization::{
+    CurrentLevel, WorldLoadProgress, WorldSaveRequest,
+};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use glob::glob;
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the level names autosaves are written to, followed by the slot number.
+pub const AUTOSAVE_PREFIX: &str = "autosave_";
+
+/// Periodically saves the current level so that edits made in the editor survive a crash.
+/// Autosaves rotate through the levels `autosave_0` to `autosave_<slots - 1>`, overwriting the oldest one.
+/// The interval, the number of slots and whether autosaving is enabled at all are set in the `autosave` section of the [`GameConfig`].
+/// The most recent autosave, including the ones from previous sessions, can be loaded from the "Foxtrot Dev" window.
+pub fn autosave_plugin(app: &mut App) {
+    app.register_type::<Autosaves>()
+        .init_resource::<Autosaves>()
+        .add_system(find_existing_autosaves.on_startup())
+        .add_system(
+            autosave
+                .run_if(resource_exists::<CurrentLevel>())
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Autosaves {
+    /// Level name of the most recent autosave.
+    pub latest: Option<String>,
+    pub next_slot: usize,
+}
+
+#[sysfail(log(level = "error"))]
+fn find_existing_autosaves(mut autosaves: ResMut<Autosaves>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("find_existing_autosaves").entered();
+    let pattern = format!("./assets/levels/{AUTOSAVE_PREFIX}*.lvl.ron");
+    let latest = glob(&pattern)
+        .context("Failed to read glob pattern")?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|path| {
+            let modified = path.metadata().and_then(|data| data.modified()).ok()?;
+            let name = path.file_name()?.to_str()?.strip_suffix(".lvl.ron")?;
+            let slot: usize = name.strip_prefix(AUTOSAVE_PREFIX)?.parse().ok()?;
+            Some((modified, slot))
+        })
+        .max();
+    if let Some((_modified, slot)) = latest {
+        autosaves.latest = Some(format!("{AUTOSAVE_PREFIX}{slot}"));
+        autosaves.next_slot = slot + 1;
+    }
+    Ok(())
+}
+
+fn autosave(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut autosaves: ResMut<Autosaves>,
+    mut save_requests: EventWriter<WorldSaveRequest>,
+    load_progress: Option<Res<WorldLoadProgress>>,
+    scene_viewer: Option<Res<SceneViewer>>,
+    mut elapsed: Local<f32>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("autosave").entered();
+    let config = &config.autosave;
+    if !config.enabled || config.slots == 0 || scene_viewer.is_some() {
+        return;
+    }
+    // Use the raw delta so that edits made while the game is paused are saved as well
+    *elapsed += time.raw_delta_seconds();
+    // Saving a partially spawned level would lose the rest of it
+    if *elapsed < config.interval || load_progress.is_some() {
+        return;
+    }
+    *elapsed = 0.;
+    let slot = autosaves.next_slot % config.slots;
+    let filename = format!("{AUTOSAVE_PREFIX}{slot}");
+    save_requests.send(WorldSaveRequest {
+        filename: filename.clone(),
+        overwrite: true,
+        skip_validation: true,
+    });
+    autosaves.latest = Some(filename);
+    autosaves.next_slot = slot + 1;
+}
@@ -0,0 +1,212 @@
+use crate::dev::editor_flags::{EditorLayers, EditorLocked};
+use crate::dev::placement::to_viewport_position;
+use crate::file_system_interaction::config::GameConfig;
+use crate::level_instantiation::spawning::custom::ObjectKind;
+use crate::level_instantiation::spawning::despawn::{DespawnEvent, ObjectSelector};
+use crate::level_instantiation::spawning::layer::Layer;
+use crate::level_instantiation::spawning::placement::get_placement_position;
+use crate::level_instantiation::spawning::spawn_queue::SpawnQueue;
+use crate::level_instantiation::spawning::GameObject;
+use crate::util::trait_extension::F32Ext;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::EguiContexts;
+use bevy_prototype_debug_lines::DebugLines;
+use bevy_rapier3d::prelude::*;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Scatters objects over surfaces by dragging the mouse while a [`Brush`] is active, e.g. to dress an area with trees and rocks.
+/// In [`BrushMode::Paint`], random objects of [`Brush::objects`] are spawned under the brush until the covered area
+/// reaches [`Brush::density`]. Spots steeper than [`Brush::max_slope`] are skipped.
+/// In [`BrushMode::Erase`], objects of the same kinds under the brush are despawned instead, except for locked ones and those on locked layers.
+/// Right click deactivates the brush.
+pub fn brush_plugin(app: &mut App) {
+    app.register_type::<Brush>()
+        .register_type::<BrushMode>()
+        .add_system(
+            apply_brush
+                .run_if(resource_exists::<Brush>())
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Brush {
+    pub mode: BrushMode,
+    /// Objects to pick from when painting and the only objects that are erased
+    pub objects: Vec<GameObject>,
+    /// Radius in m
+    pub radius: f32,
+    /// Objects per m²
+    pub density: f32,
+    /// Steepest slope in degrees on which objects are painted
+    pub max_slope: f32,
+    /// Whether painted objects are rotated randomly around the up axis
+    pub random_rotation: bool,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            mode: default(),
+            objects: default(),
+            radius: 3.,
+            density: 0.2,
+            max_slope: 30.,
+            random_rotation: true,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum BrushMode {
+    #[default]
+    Paint,
+    Erase,
+}
+
+/// State of the current stroke, i.e. of the current drag of the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Stroke {
+    last_position: Option<Vec3>,
+    /// Number of objects that should have been painted so far but were not, as they are only spawned whole
+    pending: f32,
+}
+
+fn apply_brush(
+    mut commands: Commands,
+    brush: Res<Brush>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    objects: Query<(Entity, &GameObject, &GlobalTransform, Option<&Layer>), Without<EditorLocked>>,
+    layers: Res<EditorLayers>,
+    mut lines: ResMut<DebugLines>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut despawn_requests: EventWriter<DespawnEvent>,
+    mut egui_contexts: EguiContexts,
+    mut stroke: Local<Stroke>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_brush").entered();
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        commands.remove_resource::<Brush>();
+        return;
+    }
+    let position = windows.get_single().ok().and_then(|window| {
+        let cursor = window.cursor_position()?;
+        cameras
+            .iter()
+            .filter(|(camera, _)| camera.is_active)
+            .find_map(|(camera, camera_transform)| {
+                let viewport_position = to_viewport_position(window, camera, cursor)?;
+                let ray = camera.viewport_to_world(camera_transform, viewport_position)?;
+                get_placement_position(
+                    &rapier_context,
+                    ray.origin,
+                    ray.direction,
+                    config.editor.max_brush_distance,
+                    default(),
+                )
+            })
+    });
+    let Some(position) = position else {
+        *stroke = default();
+        return;
+    };
+    let color = match brush.mode {
+        BrushMode::Paint => Color::rgb(0.3, 1.0, 0.4),
+        BrushMode::Erase => Color::rgb(1.0, 0.3, 0.3),
+    };
+    draw_circle(&mut lines, position, brush.radius, color);
+
+    if !mouse_buttons.pressed(MouseButton::Left) || egui_contexts.ctx_mut().is_using_pointer() {
+        *stroke = default();
+        return;
+    }
+    match brush.mode {
+        BrushMode::Paint => {
+            // A new stroke covers the whole brush, afterwards only the band swept since the last frame is new
+            let area = match stroke.last_position {
+                None => std::f32::consts::PI * brush.radius.squared(),
+                Some(last_position) => 2. * brush.radius * last_position.distance(position),
+            };
+            stroke.last_position = Some(position);
+            stroke.pending += area * brush.density;
+            if brush.objects.is_empty() {
+                return;
+            }
+            let mut rng = SmallRng::from_entropy();
+            while stroke.pending >= 1. {
+                stroke.pending -= 1.;
+                let object = brush.objects[rng.gen_range(0..brush.objects.len())];
+                if let Some(transform) =
+                    find_paint_spot(&rapier_context, &brush, position, &mut rng)
+                {
+                    // Queued so that dense strokes are spread over several frames
+                    spawn_queue.push(ObjectKind::Builtin(object), transform);
+                }
+            }
+        }
+        BrushMode::Erase => {
+            for (entity, object, transform, layer) in objects.iter() {
+                if brush.objects.contains(object)
+                    && !layers.is_locked(layer)
+                    && transform.translation().distance_squared(position) <= brush.radius.squared()
+                {
+                    despawn_requests.send(DespawnEvent(ObjectSelector::Entity(entity)));
+                }
+            }
+        }
+    }
+}
+
+/// Picks a random point under the brush and drops it onto the surface below. Returns `None` if the surface there is too steep.
+fn find_paint_spot(
+    rapier_context: &RapierContext,
+    brush: &Brush,
+    center: Vec3,
+    rng: &mut SmallRng,
+) -> Option<Transform> {
+    // Uniformly distributed over the disk
+    let angle = rng.gen_range(0.0..TAU);
+    let distance = brush.radius * rng.gen::<f32>().sqrt();
+    let offset = Vec3::new(angle.cos(), 0., angle.sin()) * distance;
+    let origin = center + offset + Vec3::Y * brush.radius;
+    let (_entity, intersection) = rapier_context.cast_ray_and_get_normal(
+        origin,
+        Vec3::NEG_Y,
+        brush.radius * 2.,
+        true,
+        QueryFilter::new().exclude_sensors(),
+    )?;
+    let slope = intersection.normal.angle_between(Vec3::Y).to_degrees();
+    if slope > brush.max_slope {
+        return None;
+    }
+    let mut transform = Transform::from_translation(intersection.point);
+    if brush.random_rotation {
+        transform.rotate_y(rng.gen_range(0.0..TAU));
+    }
+    Some(transform)
+}
+
+fn draw_circle(lines: &mut DebugLines, center: Vec3, radius: f32, color: Color) {
+    const SEGMENTS: usize = 32;
+    let point = |index: usize| {
+        let angle = index as f32 / SEGMENTS as f32 * TAU;
+        center + Vec3::new(angle.cos(), 0., angle.sin()) * radius
+    };
+    for index in 0..SEGMENTS {
+        lines.line_colored(point(index), point(index + 1), 0.0, color);
+    }
+}
@@ -1,34 +1,91 @@
+use crate::console::{CommandSource, ConsoleCommandEvent, ConsoleHistory, ConsoleLine};
+use crate::dev::autosave::Autosaves;
+use crate::dev::brush::{Brush, BrushMode};
+use crate::dev::editor_flags::{
+    flag_object, EditorFlagsChanged, EditorHidden, EditorLayers, EditorLocked,
+};
+use crate::dev::gltf_export::GltfExportRequest;
+use crate::dev::level_validation::{IssueSeverity, LevelValidationReport, LevelValidationRequest};
+use crate::dev::placement::Placement;
+use crate::dev::scene_viewer::SceneViewer;
+use crate::dev::spline_editor::SplinePointDrag;
+use crate::dev::transform_gizmo::GizmoMode;
+use crate::dev::volume_editor::VolumeFaceDrag;
+use crate::dev::world_hash::WorldHashHistory;
+use crate::file_system_interaction::asset_loading::CutsceneAssets;
 use crate::file_system_interaction::game_state_serialization::{GameLoadRequest, GameSaveRequest};
 use crate::file_system_interaction::level_serialization::{WorldLoadRequest, WorldSaveRequest};
+use crate::ingame_menu::Paused;
+use crate::level_instantiation::demo_scene::DemoSceneRequest;
+use crate::level_instantiation::spawning::custom::{CustomObject, CustomObjects, ObjectKind};
+use crate::level_instantiation::spawning::despawn::{DespawnEvent, ObjectSelector, RespawnEvent};
+use crate::level_instantiation::spawning::layer::{Layer, LAYERS};
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::waypoint;
+use crate::level_instantiation::spawning::prefab::{PrefabSaveRequest, Prefabs};
 use crate::level_instantiation::spawning::GameObject;
+use crate::movement::patrol_path::Waypoint;
+use crate::movement::spline::{Spline, SplinePoint};
 use crate::player_control::camera::ForceCursorGrabMode;
+use crate::state_transition::AddStateTransitionGuardExt;
+use crate::world_interaction::cutscene::{
+    get_rail_asset_path, get_rail_path, CameraKeyframe, CameraRail, Easing, PlayCutsceneEvent,
+};
+use crate::world_interaction::dialog::DialogId;
+use crate::world_interaction::volume::{Volume, VolumeKind, VolumeShape};
 use crate::GameState;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use bevy::window::CursorGrabMode;
+use bevy_editor_pls::default_windows::hierarchy::HierarchyWindow;
 use bevy_editor_pls::editor_window::EditorWindow;
 use bevy_editor_pls::{AddEditorWindow, Editor, EditorEvent};
 use bevy_egui::egui;
-use bevy_egui::egui::ScrollArea;
+use bevy_egui::egui::{Color32, RichText, ScrollArea};
 use bevy_mod_sysfail::macros::*;
 use bevy_prototype_debug_lines::DebugLines;
 use bevy_rapier3d::prelude::*;
 use oxidized_navigation::NavMesh;
 use serde::{Deserialize, Serialize};
-use spew::prelude::*;
+use std::fs;
 use strum::IntoEnumIterator;
 
+/// Distance in m in front of the camera at which a recorded keyframe's target is placed.
+const RECORDED_TARGET_DISTANCE: f32 = 5.;
+
 pub fn dev_editor_plugin(app: &mut App) {
     app.init_resource::<DevEditorState>()
         .add_editor_window::<DevEditorWindow>()
+        .add_editor_window::<SpawnPaletteWindow>()
+        .add_editor_window::<ConsoleWindow>()
+        .add_editor_window::<ObjectMetadataWindow>()
+        .add_editor_window::<BrushWindow>()
+        .add_editor_window::<SplineWindow>()
+        .add_editor_window::<VolumeWindow>()
+        .add_editor_window::<WaypointWindow>()
+        .add_editor_window::<CutsceneWindow>()
+        .add_editor_window::<EditorFlagsWindow>()
+        .add_editor_window::<LevelValidationWindow>()
         .add_systems(
             (
                 handle_debug_render,
                 handle_navmesh_render,
                 set_cursor_grab_mode,
+                resume_on_editor_open,
             )
                 .in_set(OnUpdate(GameState::Playing)),
-        );
+        )
+        .add_state_transition_guard(no_menu_while_editing);
+}
+
+/// The editor only works on a running level, so it has to be closed before quitting to the menu.
+fn no_menu_while_editing(world: &World, _from: &GameState, to: &GameState) -> Result<()> {
+    let editing = world.get_resource::<Editor>().map_or(false, Editor::active);
+    if *to == GameState::Menu && editing {
+        bail!("The editor is open");
+    }
+    Ok(())
 }
 
 pub struct DevEditorWindow;
@@ -52,6 +109,24 @@ impl EditorWindow for DevEditorWindow {
         ui.checkbox(&mut state.navmesh_render_enabled, "Navmeshes");
         ui.separator();
 
+        ui.heading("Transform Gizmo");
+        ui.horizontal(|ui| {
+            for mode in [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale] {
+                ui.radio_value(&mut state.gizmo_mode, mode, format!("{mode:?}"));
+            }
+        });
+        ui.separator();
+
+        ui.heading("Determinism");
+        match world.resource::<WorldHashHistory>().latest() {
+            Some(latest) => ui.monospace(format!(
+                "World hash: {:016x} (frame {})",
+                latest.hash, latest.frame
+            )),
+            None => ui.label("World hash: not computed yet"),
+        };
+        ui.separator();
+
         ui.heading("Scene Control");
         ui.horizontal(|ui| {
             ui.label("Level name: ");
@@ -63,23 +138,41 @@ impl EditorWindow for DevEditorWindow {
                 if ui.button("Save").clicked() {
                     world.send_event(WorldSaveRequest {
                         filename: state.level_name.clone(),
+                        overwrite: false,
+                        skip_validation: false,
                     })
                 }
                 if ui.button("Load").clicked() {
                     world.send_event(WorldLoadRequest {
                         filename: state.level_name.clone(),
+                        player_transform: Some(Transform::from_xyz(0., 1.5, 0.)),
+                    });
+                }
+                if ui.button("View").clicked() {
+                    world.send_event(WorldLoadRequest {
+                        filename: state.level_name.clone(),
+                        player_transform: None,
+                    });
+                    world.init_resource::<SceneViewer>();
+                }
+                if ui.button("Export glTF").clicked() {
+                    world.send_event(GltfExportRequest {
+                        filename: state.level_name.clone(),
                     });
-                    // Make sure the player is spawned after the level
-                    world.send_event(
-                        SpawnEvent::with_data(
-                            GameObject::Player,
-                            Transform::from_translation((0., 1.5, 0.).into()),
-                        )
-                        .delay_frames(2),
-                    );
                 }
             });
         });
+        if let Some(latest) = world.resource::<Autosaves>().latest.clone() {
+            ui.horizontal(|ui| {
+                ui.label(format!("Latest autosave: {latest}"));
+                if ui.button("Load").clicked() {
+                    world.send_event(WorldLoadRequest {
+                        filename: latest,
+                        player_transform: Some(Transform::from_xyz(0., 1.5, 0.)),
+                    });
+                }
+            });
+        }
         ui.horizontal(|ui| {
             ui.label("Save name: ");
             ui.text_edit_singleline(&mut state.save_name);
@@ -93,30 +186,21 @@ impl EditorWindow for DevEditorWindow {
                 })
             }
             if ui.button("Load").clicked() {
+                world.send_event(GameLoadRequest {
+                    filename: filename.clone(),
+                });
+            }
+            if ui.button("View").clicked() {
                 world.send_event(GameLoadRequest { filename });
+                world.init_resource::<SceneViewer>();
             }
         });
-
-        ui.add_space(10.);
-        ui.label("Spawning");
-        if ui.button("Spawn").clicked() {
-            world.send_event(SpawnEvent::with_data(
-                state.spawn_item,
-                Transform::default(),
-            ));
+        if ui.button("Spawn demo scene").clicked() {
+            world.send_event(DemoSceneRequest);
+        }
+        if world.contains_resource::<SceneViewer>() && ui.button("Exit scene viewer").clicked() {
+            world.remove_resource::<SceneViewer>();
         }
-
-        ui.add_space(3.);
-
-        ScrollArea::vertical()
-            .auto_shrink([false; 2])
-            .show(ui, |ui| {
-                ui.vertical(|ui| {
-                    for item in GameObject::iter() {
-                        ui.radio_value(&mut state.spawn_item, item, format!("{item:?}"));
-                    }
-                });
-            });
     }
 }
 
@@ -126,9 +210,9 @@ pub struct DevEditorState {
     pub open: bool,
     pub level_name: String,
     pub save_name: String,
-    pub spawn_item: GameObject,
     pub collider_render_enabled: bool,
     pub navmesh_render_enabled: bool,
+    pub gizmo_mode: GizmoMode,
 }
 
 impl Default for DevEditorState {
@@ -136,14 +220,1000 @@ impl Default for DevEditorState {
         Self {
             level_name: "old_town".to_owned(),
             save_name: default(),
-            spawn_item: default(),
             collider_render_enabled: false,
             navmesh_render_enabled: false,
+            gizmo_mode: default(),
             open: false,
         }
     }
 }
 
+pub struct SpawnPaletteWindow;
+
+impl EditorWindow for SpawnPaletteWindow {
+    type State = SpawnPaletteState;
+    const NAME: &'static str = "Spawn Palette";
+    const DEFAULT_SIZE: (f32, f32) = (200., 300.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let selected: Vec<_> = cx
+            .state::<HierarchyWindow>()
+            .map(|hierarchy| hierarchy.selected.iter().collect())
+            .unwrap_or_default();
+        let state = cx
+            .state_mut::<SpawnPaletteWindow>()
+            .expect("Failed to get spawn palette window state");
+
+        ui.horizontal(|ui| {
+            if ui.button("Place").clicked() {
+                world.insert_resource(Placement {
+                    object: state.spawn_item.clone(),
+                });
+            }
+            if ui.button("Spawn at origin").clicked() {
+                state.spawn_item.spawn(world, Transform::default());
+            }
+        });
+        if world.contains_resource::<Placement>() {
+            ui.label("Click to place, hold shift to place multiple, right click to cancel");
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Prefab name: ");
+            ui.text_edit_singleline(&mut state.prefab_name);
+        });
+        let can_save = !state.prefab_name.is_empty() && !selected.is_empty();
+        if ui
+            .add_enabled(can_save, egui::Button::new("Save selection as prefab"))
+            .clicked()
+        {
+            world.send_event(PrefabSaveRequest {
+                name: state.prefab_name.clone(),
+                entities: selected.clone(),
+            });
+        }
+        ui.add_enabled_ui(!selected.is_empty(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Despawn selection").clicked() {
+                    for &entity in &selected {
+                        world.send_event(DespawnEvent(ObjectSelector::Entity(entity)));
+                    }
+                }
+                if ui.button("Respawn selection").clicked() {
+                    for &entity in &selected {
+                        world.send_event(RespawnEvent(ObjectSelector::Entity(entity)));
+                    }
+                }
+            });
+        });
+
+        ui.add_space(3.);
+
+        let items: Vec<_> = GameObject::iter()
+            .map(ObjectKind::Builtin)
+            .chain(
+                world
+                    .resource::<CustomObjects>()
+                    .names()
+                    .map(|name| ObjectKind::Custom(name.to_owned())),
+            )
+            .chain(
+                world
+                    .resource::<Prefabs>()
+                    .names()
+                    .map(|name| ObjectKind::Prefab(name.to_owned())),
+            )
+            .collect();
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                ui.vertical(|ui| {
+                    for item in items {
+                        let label = item.to_string();
+                        ui.radio_value(&mut state.spawn_item, item, label);
+                    }
+                });
+            });
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct SpawnPaletteState {
+    pub spawn_item: ObjectKind,
+    pub prefab_name: String,
+}
+
+pub struct ConsoleWindow;
+
+impl EditorWindow for ConsoleWindow {
+    type State = ConsoleWindowState;
+    const NAME: &'static str = "Console";
+    const DEFAULT_SIZE: (f32, f32) = (400., 200.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let state = cx
+            .state_mut::<ConsoleWindow>()
+            .expect("Failed to get console window state");
+
+        let input_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y * 2.;
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .max_height(ui.available_height() - input_height)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in world.resource::<ConsoleHistory>().lines.iter() {
+                    let text = match line {
+                        ConsoleLine::Input(input) => RichText::new(format!("> {input}")).strong(),
+                        ConsoleLine::Output(output) => RichText::new(output),
+                        ConsoleLine::Error(error) => RichText::new(error).color(Color32::RED),
+                    };
+                    ui.label(text.monospace());
+                }
+            });
+
+        ui.separator();
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut state.input)
+                .desired_width(f32::INFINITY)
+                .hint_text("Type \"help\" for a list of commands"),
+        );
+        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            let input = std::mem::take(&mut state.input);
+            if !input.trim().is_empty() {
+                world.send_event(ConsoleCommandEvent::new(input, CommandSource::Console));
+            }
+            response.request_focus();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct ConsoleWindowState {
+    pub input: String,
+}
+
+pub struct ObjectMetadataWindow;
+
+impl EditorWindow for ObjectMetadataWindow {
+    type State = ObjectMetadataWindowState;
+    const NAME: &'static str = "Object Metadata";
+    const DEFAULT_SIZE: (f32, f32) = (250., 200.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let selected = cx
+            .state::<HierarchyWindow>()
+            .and_then(|hierarchy| hierarchy.selected.iter().next());
+        let state = cx
+            .state_mut::<ObjectMetadataWindow>()
+            .expect("Failed to get object metadata window state");
+
+        // Selecting a part of an object, e.g. a mesh of an NPC, edits the whole object
+        let mut root = selected;
+        while let Some(entity) = root {
+            if world.get::<GameObject>(entity).is_some()
+                || world.get::<CustomObject>(entity).is_some()
+            {
+                break;
+            }
+            root = world.get::<Parent>(entity).map(|parent| parent.get());
+        }
+        let Some(root) = root else {
+            ui.label("Select a spawned object to edit its metadata");
+            return;
+        };
+        let name = world
+            .get::<Name>(root)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("{root:?}"));
+        ui.label(RichText::new(name).strong());
+
+        let mut metadata = world
+            .get::<ObjectMetadata>(root)
+            .cloned()
+            .unwrap_or_default();
+        let original = metadata.clone();
+        let mut removed = None;
+        egui::Grid::new("object_metadata").show(ui, |ui| {
+            for (index, (key, value)) in metadata.0.iter_mut().enumerate() {
+                ui.text_edit_singleline(key);
+                ui.text_edit_singleline(value);
+                if ui.small_button("x").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+            ui.text_edit_singleline(&mut state.new_key);
+            ui.text_edit_singleline(&mut state.new_value);
+            if ui
+                .add_enabled(!state.new_key.is_empty(), egui::Button::new("Add"))
+                .clicked()
+            {
+                metadata.insert(
+                    std::mem::take(&mut state.new_key),
+                    std::mem::take(&mut state.new_value),
+                );
+            }
+            ui.end_row();
+        });
+        if let Some(index) = removed {
+            metadata.0.remove(index);
+        }
+        if metadata != original {
+            world.entity_mut(root).insert(metadata);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct ObjectMetadataWindowState {
+    pub new_key: String,
+    pub new_value: String,
+}
+
+pub struct BrushWindow;
+
+impl EditorWindow for BrushWindow {
+    type State = BrushWindowState;
+    const NAME: &'static str = "Brush";
+    const DEFAULT_SIZE: (f32, f32) = (220., 350.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let state = cx
+            .state_mut::<BrushWindow>()
+            .expect("Failed to get brush window state");
+        let brush = &mut state.brush;
+
+        let active = world.contains_resource::<Brush>();
+        if active {
+            if ui.button("Stop").clicked() {
+                world.remove_resource::<Brush>();
+            }
+            ui.label("Drag over surfaces to use the brush, right click to stop");
+        } else if ui
+            .add_enabled(!brush.objects.is_empty(), egui::Button::new("Start"))
+            .clicked()
+        {
+            world.insert_resource(brush.clone());
+        }
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut brush.mode, BrushMode::Paint, "Paint");
+            ui.radio_value(&mut brush.mode, BrushMode::Erase, "Erase");
+        });
+        ui.add(egui::Slider::new(&mut brush.radius, 0.5..=20.0).text("Radius"));
+        ui.add(
+            egui::Slider::new(&mut brush.density, 0.01..=2.0)
+                .logarithmic(true)
+                .text("Objects per m²"),
+        );
+        ui.add(egui::Slider::new(&mut brush.max_slope, 0.0..=90.0).text("Max slope"));
+        ui.checkbox(&mut brush.random_rotation, "Random rotation");
+
+        ui.add_space(3.);
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for object in GameObject::iter() {
+                    let mut selected = brush.objects.contains(&object);
+                    if ui.checkbox(&mut selected, object.name()).changed() {
+                        if selected {
+                            brush.objects.push(object);
+                        } else {
+                            brush.objects.retain(|other| *other != object);
+                        }
+                    }
+                }
+            });
+
+        // Settings apply to the active brush right away
+        if let Some(mut active_brush) = world.get_resource_mut::<Brush>() {
+            if *active_brush != *brush {
+                *active_brush = brush.clone();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct BrushWindowState {
+    pub brush: Brush,
+}
+
+pub struct SplineWindow;
+
+impl EditorWindow for SplineWindow {
+    type State = SplineWindowState;
+    const NAME: &'static str = "Spline";
+    const DEFAULT_SIZE: (f32, f32) = (250., 200.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let selected = cx
+            .state::<HierarchyWindow>()
+            .and_then(|hierarchy| hierarchy.selected.iter().next());
+        let state = cx
+            .state_mut::<SplineWindow>()
+            .expect("Failed to get spline window state");
+
+        let mut root = selected;
+        while let Some(entity) = root {
+            if world.get::<Spline>(entity).is_some() {
+                break;
+            }
+            root = world.get::<Parent>(entity).map(|parent| parent.get());
+        }
+        let Some(spline) = root.and_then(|root| world.get::<Spline>(root)) else {
+            ui.label("Select a spline to edit it");
+            return;
+        };
+        let mut spline = spline.clone();
+        let original = spline.clone();
+        state.selected_point = state
+            .selected_point
+            .filter(|index| *index < spline.points.len());
+
+        ui.checkbox(&mut spline.closed, "Closed");
+        ui.add(egui::Slider::new(&mut spline.thickness, 0.0..=0.5).text("Thickness"));
+        ui.horizontal(|ui| {
+            if ui.button("Add point").clicked() {
+                let after = state
+                    .selected_point
+                    .unwrap_or(spline.points.len().saturating_sub(1));
+                state.selected_point = Some(insert_spline_point(&mut spline, after));
+            }
+            let removable = state.selected_point.filter(|_| spline.points.len() > 2);
+            if ui
+                .add_enabled(removable.is_some(), egui::Button::new("Remove point"))
+                .clicked()
+            {
+                if let Some(index) = removable {
+                    spline.points.remove(index);
+                    state.selected_point = None;
+                }
+            }
+        });
+        ui.separator();
+
+        match state.selected_point {
+            Some(index) => {
+                ui.label(format!("Point {} of {}", index + 1, spline.points.len()));
+                let point = &mut spline.points[index];
+                egui::Grid::new("spline_point").show(ui, |ui| {
+                    for (label, vector) in [
+                        ("Position", &mut point.position),
+                        ("In handle", &mut point.in_handle),
+                        ("Out handle", &mut point.out_handle),
+                    ] {
+                        ui.label(label);
+                        ui.add(egui::DragValue::new(&mut vector.x).speed(0.05));
+                        ui.add(egui::DragValue::new(&mut vector.y).speed(0.05));
+                        ui.add(egui::DragValue::new(&mut vector.z).speed(0.05));
+                        ui.end_row();
+                    }
+                });
+            }
+            None => {
+                ui.label("Click a point in the viewport to select it");
+            }
+        }
+
+        if spline != original {
+            if let Some(root) = root {
+                world.entity_mut(root).insert(spline);
+            }
+        }
+    }
+}
+
+/// Inserts a point after the one at `after`, halfway along the curve to the next one or past the end, and returns its index.
+fn insert_spline_point(spline: &mut Spline, after: usize) -> usize {
+    let Some(&previous) = spline.points.get(after) else {
+        spline.points.push(SplinePoint::smooth(Vec3::ZERO, Vec3::Z));
+        return spline.points.len() - 1;
+    };
+    let is_last = after + 1 >= spline.points.len();
+    let point = if is_last && !spline.closed {
+        let direction = previous.out_handle.try_normalize().unwrap_or(Vec3::Z);
+        SplinePoint::smooth(previous.position + direction * 4., direction)
+    } else {
+        const STEP: f32 = 0.01;
+        let t = after as f32 + 0.5;
+        let sample = |t: f32| spline.sample(t).unwrap_or(previous.position);
+        // Splitting a Bézier curve in half leaves handles of a sixth of its derivative there
+        let derivative = (sample(t + STEP) - sample(t - STEP)) / (2. * STEP);
+        SplinePoint::smooth(sample(t), derivative / 6.)
+    };
+    spline.points.insert(after + 1, point);
+    after + 1
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct SplineWindowState {
+    pub selected_point: Option<usize>,
+    /// Set by the [`spline_editor_plugin`](crate::dev::spline_editor::spline_editor_plugin) while a point is dragged
+    pub drag: Option<SplinePointDrag>,
+}
+
+pub struct VolumeWindow;
+
+impl EditorWindow for VolumeWindow {
+    type State = VolumeWindowState;
+    const NAME: &'static str = "Volume";
+    const DEFAULT_SIZE: (f32, f32) = (250., 150.);
+    fn ui(
+        world: &mut World,
+        cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let selected = cx
+            .state::<HierarchyWindow>()
+            .and_then(|hierarchy| hierarchy.selected.iter().next());
+        let Some((entity, volume)) =
+            selected.and_then(|entity| Some((entity, *world.get::<Volume>(entity)?)))
+        else {
+            ui.label("Select a volume to edit it");
+            return;
+        };
+        let mut edited = volume;
+
+        egui::Grid::new("volume").show(ui, |ui| {
+            ui.label("Kind");
+            egui::ComboBox::from_id_source("volume_kind")
+                .selected_text(edited.kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in VolumeKind::ALL {
+                        ui.selectable_value(&mut edited.kind, kind, kind.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Shape");
+            let mut shape_name = edited.shape.name();
+            egui::ComboBox::from_id_source("volume_shape")
+                .selected_text(shape_name)
+                .show_ui(ui, |ui| {
+                    for name in ["box", "sphere", "cylinder"] {
+                        ui.selectable_value(&mut shape_name, name, name);
+                    }
+                });
+            if shape_name != edited.shape.name() {
+                if let Ok(shape) = edited.shape.converted(shape_name) {
+                    edited.shape = shape;
+                }
+            }
+            ui.end_row();
+
+            let mut extent = |ui: &mut egui::Ui, label: &str, value: &mut f32| {
+                ui.label(label);
+                ui.add(egui::DragValue::new(value).speed(0.05).suffix(" m"));
+                ui.end_row();
+            };
+            match &mut edited.shape {
+                VolumeShape::Box { size } => {
+                    extent(ui, "Width", &mut size.x);
+                    extent(ui, "Height", &mut size.y);
+                    extent(ui, "Depth", &mut size.z);
+                }
+                VolumeShape::Sphere { radius } => extent(ui, "Radius", radius),
+                VolumeShape::Cylinder { height, radius } => {
+                    extent(ui, "Height", height);
+                    extent(ui, "Radius", radius);
+                }
+            }
+        });
+        edited.shape = edited.shape.clamped();
+
+        if edited != volume {
+            world.entity_mut(entity).insert(edited);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct VolumeWindowState {
+    /// Set by the [`volume_editor_plugin`](crate::dev::volume_editor::volume_editor_plugin) while a face is dragged
+    pub drag: Option<VolumeFaceDrag>,
+}
+
+pub struct WaypointWindow;
+
+impl EditorWindow for WaypointWindow {
+    type State = WaypointWindowState;
+    const NAME: &'static str = "Waypoints";
+    const DEFAULT_SIZE: (f32, f32) = (250., 200.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let selected = cx
+            .state::<HierarchyWindow>()
+            .and_then(|hierarchy| hierarchy.selected.iter().next());
+        let state = cx
+            .state_mut::<WaypointWindow>()
+            .expect("Failed to get waypoint window state");
+
+        ui.horizontal(|ui| {
+            ui.label("Path");
+            ui.text_edit_singleline(&mut state.path);
+        });
+        ui.add_enabled_ui(!state.path.is_empty(), |ui| {
+            ui.checkbox(&mut state.chaining, "Chain waypoints by clicking them");
+        });
+        ui.separator();
+
+        let selected = selected.and_then(|entity| {
+            let waypoint = world.get::<Waypoint>(entity)?.clone();
+            let transform = *world.get::<Transform>(entity)?;
+            Some((entity, waypoint, transform))
+        });
+        let Some((entity, waypoint, transform)) = selected else {
+            ui.label("Select a waypoint to edit it");
+            return;
+        };
+        let mut edited = waypoint.clone();
+        egui::Grid::new("waypoint").show(ui, |ui| {
+            ui.label("Path");
+            ui.text_edit_singleline(&mut edited.path);
+            ui.end_row();
+            ui.label("Index");
+            ui.add(egui::DragValue::new(&mut edited.index));
+            ui.end_row();
+        });
+        if edited != waypoint {
+            world.entity_mut(entity).insert(edited.clone());
+        }
+        if ui.button("Use this path").clicked() {
+            state.path = edited.path.clone();
+        }
+        if ui
+            .add_enabled(
+                !edited.path.is_empty(),
+                egui::Button::new("Add waypoint after"),
+            )
+            .clicked()
+        {
+            let added = insert_waypoint(world, &edited, transform);
+            if let Some(hierarchy) = cx.state_mut::<HierarchyWindow>() {
+                hierarchy.selected.select_replace(added);
+            }
+        }
+    }
+}
+
+/// Spawns a waypoint right after `previous` in its path, continuing in the direction the path came from,
+/// and returns it. Later waypoints of the path move back by one.
+fn insert_waypoint(world: &mut World, previous: &Waypoint, transform: Transform) -> Entity {
+    let mut before = None;
+    let mut waypoints = world.query::<(&mut Waypoint, &Transform)>();
+    for (mut waypoint, waypoint_transform) in waypoints.iter_mut(world) {
+        if waypoint.path != previous.path {
+            continue;
+        }
+        if waypoint.index > previous.index {
+            waypoint.index += 1;
+        } else if waypoint.index + 1 == previous.index {
+            before = Some(waypoint_transform.translation);
+        }
+    }
+    let direction = before
+        .and_then(|before| (transform.translation - before).try_normalize())
+        .unwrap_or(Vec3::X);
+    let waypoint = Waypoint {
+        path: previous.path.clone(),
+        index: previous.index + 1,
+    };
+    let transform = Transform::from_translation(transform.translation + direction * 2.);
+    world.spawn(waypoint::bundle(transform, waypoint)).id()
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct WaypointWindowState {
+    /// Name of the path that waypoints are chained into
+    pub path: String,
+    /// Read by the [`waypoint_editor_plugin`](crate::dev::waypoint_editor::waypoint_editor_plugin)
+    pub chaining: bool,
+}
+
+pub struct CutsceneWindow;
+
+impl EditorWindow for CutsceneWindow {
+    type State = CutsceneWindowState;
+    const NAME: &'static str = "Cutscene";
+    const DEFAULT_SIZE: (f32, f32) = (350., 300.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let state = cx
+            .state_mut::<CutsceneWindow>()
+            .expect("Failed to get cutscene window state");
+
+        ui.horizontal(|ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut state.name);
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!state.name.is_empty(), egui::Button::new("Load"))
+                .clicked()
+            {
+                let path = get_rail_asset_path(&state.name);
+                let rail = world
+                    .get_resource::<CutsceneAssets>()
+                    .and_then(|assets| assets.rails.get(&path))
+                    .and_then(|handle| world.resource::<Assets<CameraRail>>().get(handle));
+                match rail {
+                    Some(rail) => state.rail = rail.clone(),
+                    None => error!(
+                        "Failed to load cutscene \"{}\": No such cutscene",
+                        state.name
+                    ),
+                }
+            }
+            if ui
+                .add_enabled(!state.name.is_empty(), egui::Button::new("Save"))
+                .clicked()
+            {
+                match save_rail(&state.name, &state.rail) {
+                    Ok(()) => info!("Saved cutscene \"{}\"", state.name),
+                    Err(e) => error!("{e:?}"),
+                }
+            }
+            if ui
+                .add_enabled(
+                    !state.rail.keyframes.is_empty(),
+                    egui::Button::new("Preview"),
+                )
+                .on_hover_text("Plays once the editor is closed")
+                .clicked()
+            {
+                let rail = world
+                    .resource_mut::<Assets<CameraRail>>()
+                    .add(state.rail.clone());
+                world.send_event(PlayCutsceneEvent { rail });
+            }
+        });
+        ui.separator();
+
+        let mut removed = None;
+        ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+            egui::Grid::new("cutscene_keyframes").show(ui, |ui| {
+                ui.label("");
+                ui.label("Duration");
+                ui.label("Hold");
+                ui.label("Easing");
+                ui.label("Dialog");
+                ui.end_row();
+                for (index, keyframe) in state.rail.keyframes.iter_mut().enumerate() {
+                    ui.label(format!("{}", index + 1));
+                    ui.add(
+                        egui::DragValue::new(&mut keyframe.duration)
+                            .speed(0.05)
+                            .clamp_range(0.0..=f32::MAX),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut keyframe.hold)
+                            .speed(0.05)
+                            .clamp_range(0.0..=f32::MAX),
+                    );
+                    egui::ComboBox::from_id_source(("cutscene_easing", index))
+                        .selected_text(format!("{:?}", keyframe.easing))
+                        .show_ui(ui, |ui| {
+                            for easing in Easing::ALL {
+                                ui.selectable_value(
+                                    &mut keyframe.easing,
+                                    easing,
+                                    format!("{easing:?}"),
+                                );
+                            }
+                        });
+                    let mut dialog = keyframe
+                        .dialog
+                        .as_ref()
+                        .map(|dialog| dialog.0.clone())
+                        .unwrap_or_default();
+                    if ui.text_edit_singleline(&mut dialog).changed() {
+                        keyframe.dialog = (!dialog.is_empty()).then(|| DialogId(dialog));
+                    }
+                    if ui.button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(index) = removed {
+            state.rail.keyframes.remove(index);
+        }
+
+        if ui.button("Record keyframe").clicked() {
+            let mut cameras = world.query_filtered::<(&Camera, &GlobalTransform), With<Camera3d>>();
+            let pose = cameras
+                .iter(world)
+                .find(|(camera, _)| camera.is_active)
+                .map(|(_, transform)| transform.compute_transform());
+            match pose {
+                Some(pose) => state.rail.keyframes.push(CameraKeyframe {
+                    position: pose.translation,
+                    target: pose.translation + pose.forward() * RECORDED_TARGET_DISTANCE,
+                    duration: 2.,
+                    ..default()
+                }),
+                None => error!("Failed to record keyframe: No active camera"),
+            }
+        }
+    }
+}
+
+fn save_rail(name: &str, rail: &CameraRail) -> Result<()> {
+    let serialized =
+        ron::ser::to_string_pretty(rail, default()).context("Failed to serialize cutscene")?;
+    let path = get_rail_path(name);
+    let dir = path.parent().context("Failed to get cutscene directory")?;
+    fs::create_dir_all(dir).context("Failed to create cutscene directory")?;
+    fs::write(&path, serialized).context("Failed to write cutscene")?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct CutsceneWindowState {
+    /// Name of the rail, which is saved to `assets/cutscenes/<name>.rail.ron`
+    pub name: String,
+    /// The rail being edited
+    pub rail: CameraRail,
+}
+
+pub struct EditorFlagsWindow;
+
+impl EditorWindow for EditorFlagsWindow {
+    type State = ();
+    const NAME: &'static str = "Lock & Hide";
+    const DEFAULT_SIZE: (f32, f32) = (250., 250.);
+    fn ui(
+        world: &mut World,
+        cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        let selected = cx
+            .state::<HierarchyWindow>()
+            .and_then(|hierarchy| hierarchy.selected.iter().next());
+        let mut root = selected;
+        while let Some(entity) = root {
+            if world.get::<GameObject>(entity).is_some()
+                || world.get::<CustomObject>(entity).is_some()
+            {
+                break;
+            }
+            root = world.get::<Parent>(entity).map(|parent| parent.get());
+        }
+        let mut changed = false;
+        match root {
+            Some(root) => {
+                ui.label(RichText::new(display_name(world, root)).strong());
+                let object = world.get::<GameObject>(root).copied();
+                let layer = world.get::<Layer>(root).cloned().unwrap_or_default();
+                let mut edited = layer.clone();
+                egui::ComboBox::from_label("Layer")
+                    .selected_text(&edited.0)
+                    .show_ui(ui, |ui| {
+                        for name in LAYERS {
+                            ui.selectable_value(&mut edited, Layer(name.to_owned()), name);
+                        }
+                    });
+                if edited != layer {
+                    let mut metadata = world
+                        .get::<ObjectMetadata>(root)
+                        .cloned()
+                        .unwrap_or_default();
+                    edited.write_metadata(object.as_ref(), &mut metadata);
+                    world.entity_mut(root).insert(metadata);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Lock").clicked() {
+                        flag_object(world, root, EditorLocked);
+                        changed = true;
+                    }
+                    if ui.button("Hide").clicked() {
+                        flag_object(world, root, EditorHidden);
+                        changed = true;
+                    }
+                });
+            }
+            None => {
+                ui.label("Select a spawned object to lock or hide it");
+            }
+        }
+
+        ui.separator();
+        changed |= show_layers(ui, world);
+        ui.separator();
+        let locked: Vec<_> = world
+            .query_filtered::<Entity, With<EditorLocked>>()
+            .iter(world)
+            .collect();
+        let hidden: Vec<_> = world
+            .query_filtered::<Entity, With<EditorHidden>>()
+            .iter(world)
+            .collect();
+        ScrollArea::vertical().show(ui, |ui| {
+            changed |= show_flagged::<EditorLocked>(ui, world, "Locked", "Unlock", &locked);
+            changed |= show_flagged::<EditorHidden>(ui, world, "Hidden", "Show", &hidden);
+        });
+        if changed {
+            world.send_event(EditorFlagsChanged);
+        }
+    }
+}
+
+pub struct LevelValidationWindow;
+
+impl EditorWindow for LevelValidationWindow {
+    type State = ();
+    const NAME: &'static str = "Level Validation";
+    const DEFAULT_SIZE: (f32, f32) = (300., 200.);
+    fn ui(
+        world: &mut World,
+        mut cx: bevy_editor_pls::editor_window::EditorWindowContext,
+        ui: &mut egui::Ui,
+    ) {
+        if ui.button("Validate").clicked() {
+            world.send_event(LevelValidationRequest);
+        }
+        let report = world.resource::<LevelValidationReport>().clone();
+        if let Some(save) = &report.blocked_save {
+            ui.separator();
+            ui.colored_label(
+                Color32::RED,
+                format!("Saving \"{}\" was blocked by errors", save.filename),
+            );
+            ui.horizontal(|ui| {
+                if ui.button("Save anyway").clicked() {
+                    world.send_event(WorldSaveRequest {
+                        skip_validation: true,
+                        ..save.clone()
+                    });
+                    world.resource_mut::<LevelValidationReport>().blocked_save = None;
+                }
+                if ui.button("Validate again").clicked() {
+                    world.send_event(save.clone());
+                }
+            });
+        }
+        ui.separator();
+        if !report.validated {
+            ui.label("Saving a level validates it first");
+            return;
+        }
+        if report.issues.is_empty() {
+            ui.label("No issues found");
+            return;
+        }
+        ScrollArea::vertical().show(ui, |ui| {
+            for issue in &report.issues {
+                ui.horizontal(|ui| {
+                    let (label, color) = match issue.severity {
+                        IssueSeverity::Error => ("Error", Color32::RED),
+                        IssueSeverity::Warning => ("Warning", Color32::YELLOW),
+                    };
+                    ui.colored_label(color, label);
+                    if let Some(entity) = issue.entity {
+                        let exists = world.get_entity(entity).is_some();
+                        if ui
+                            .add_enabled(exists, egui::Button::new("Select"))
+                            .clicked()
+                        {
+                            if let Some(hierarchy) = cx.state_mut::<HierarchyWindow>() {
+                                hierarchy.selected.select_replace(entity);
+                            }
+                        }
+                    }
+                    ui.label(&issue.message);
+                });
+            }
+        });
+    }
+}
+
+/// Lists the offered layers and those in use with their number of objects and lets each of them be hidden or locked.
+/// Returns whether [`EditorLayers`] changed.
+fn show_layers(ui: &mut egui::Ui, world: &mut World) -> bool {
+    let mut counts: Vec<(String, usize)> =
+        LAYERS.iter().map(|name| (name.to_string(), 0)).collect();
+    for layer in world.query::<&Layer>().iter(world) {
+        match counts.iter_mut().find(|(name, _)| *name == layer.0) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((layer.0.clone(), 1)),
+        }
+    }
+    let layers = world.resource::<EditorLayers>().clone();
+    let mut edited = layers.clone();
+    egui::Grid::new("editor_layers").show(ui, |ui| {
+        for (name, count) in counts {
+            ui.label(format!("{name} ({count})"));
+            let mut toggle = |ui: &mut egui::Ui, set: &mut HashSet<String>, label: &str| {
+                let mut enabled = set.contains(&name);
+                if ui.checkbox(&mut enabled, label).changed() {
+                    if enabled {
+                        set.insert(name.clone());
+                    } else {
+                        set.remove(&name);
+                    }
+                }
+            };
+            toggle(ui, &mut edited.hidden, "Hidden");
+            toggle(ui, &mut edited.locked, "Locked");
+            ui.end_row();
+        }
+    });
+    let changed = edited != layers;
+    if changed {
+        *world.resource_mut::<EditorLayers>() = edited;
+    }
+    changed
+}
+
+/// Lists the objects with the flag `T` and lets it be removed from each of them or from all at once.
+/// Returns whether a flag was removed.
+fn show_flagged<T: Component>(
+    ui: &mut egui::Ui,
+    world: &mut World,
+    heading: &str,
+    remove_label: &str,
+    flagged: &[Entity],
+) -> bool {
+    let mut removed = Vec::new();
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(format!("{heading} ({})", flagged.len())).strong());
+        let remove_all = egui::Button::new(format!("{remove_label} all"));
+        if ui.add_enabled(!flagged.is_empty(), remove_all).clicked() {
+            removed.extend_from_slice(flagged);
+        }
+    });
+    for entity in flagged {
+        ui.horizontal(|ui| {
+            if ui.small_button(remove_label).clicked() {
+                removed.push(*entity);
+            }
+            ui.label(display_name(world, *entity));
+        });
+    }
+    for entity in &removed {
+        world.entity_mut(*entity).remove::<T>();
+    }
+    !removed.is_empty()
+}
+
+fn display_name(world: &World, entity: Entity) -> String {
+    world
+        .get::<Name>(entity)
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("{entity:?}"))
+}
+
 #[sysfail(log(level = "error"))]
 fn handle_debug_render(
     state: Res<Editor>,
@@ -171,6 +1241,16 @@ fn set_cursor_grab_mode(
     }
 }
 
+/// The pause menu covers the whole screen, so it would hide the editor.
+fn resume_on_editor_open(mut commands: Commands, mut events: EventReader<EditorEvent>) {
+    let opened = events
+        .iter()
+        .any(|event| matches!(event, EditorEvent::Toggle { now_active: true }));
+    if opened {
+        commands.remove_resource::<Paused>();
+    }
+}
+
 #[sysfail(log(level = "error"))]
 fn handle_navmesh_render(
     state: Res<Editor>,
@@ -0,0 +1,283 @@
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::level_instantiation::spawning::layer::Layer;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_editor_pls::default_windows::hierarchy::HierarchyWindow;
+use bevy_editor_pls::Editor;
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::{fs, iter};
+
+/// Handles editor-only flags on spawned objects, which never end up in the level itself:
+/// - [`EditorLocked`] objects cannot be selected, so finished parts of a level are not moved by accident while placing new props.
+/// - [`EditorHidden`] objects are invisible and cannot be selected while the editor is active.
+/// - [`EditorLayers`] does the same for every object on a [`Layer`] at once.
+///
+/// The flags are toggled in the "Lock & Hide" window, which sends an [`EditorFlagsChanged`],
+/// and are stored per level in `editor/<level>.editor.ron` under the [`EDITOR_ID_KEY`] of each object.
+/// When a level is loaded, its objects get their flags back as soon as they are spawned with their metadata.
+pub fn editor_flags_plugin(app: &mut App) {
+    app.register_type::<EditorLocked>()
+        .register_type::<EditorHidden>()
+        .register_type::<EditorLayers>()
+        .init_resource::<EditorLayers>()
+        .add_event::<EditorFlagsChanged>()
+        .init_resource::<PendingEditorFlags>()
+        .add_systems(
+            (
+                load_editor_flags.run_if(resource_exists_and_changed::<CurrentLevel>()),
+                attach_editor_flags,
+                save_editor_flags.run_if(on_event::<EditorFlagsChanged>()),
+                deselect_flagged_objects,
+                hide_flagged_objects,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+/// Metadata key of the id under which the editor flags of an object are stored, e.g. `7f3a09c2d41e5b86`.
+/// It is given to an object when it is first flagged and saved with the level like the rest of its metadata.
+pub const EDITOR_ID_KEY: &str = "editor_id";
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct EditorLocked;
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct EditorHidden;
+
+/// Names of the [`Layer`]s whose objects are hidden or locked in the editor.
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct EditorLayers {
+    pub hidden: HashSet<String>,
+    pub locked: HashSet<String>,
+}
+
+impl EditorLayers {
+    pub fn is_hidden(&self, layer: Option<&Layer>) -> bool {
+        layer.map_or(false, |layer| self.hidden.contains(&layer.0))
+    }
+
+    pub fn is_locked(&self, layer: Option<&Layer>) -> bool {
+        layer.map_or(false, |layer| self.locked.contains(&layer.0))
+    }
+}
+
+/// Send after adding or removing [`EditorLocked`] or [`EditorHidden`] or changing [`EditorLayers`] to write the flags of the current level to disk.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct EditorFlagsChanged;
+
+/// Contents of an editor sidecar file. Objects are referred to by their [`EDITOR_ID_KEY`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+struct EditorFlags {
+    #[serde(default)]
+    locked: HashSet<String>,
+    #[serde(default)]
+    hidden: HashSet<String>,
+    #[serde(default)]
+    layers: EditorLayers,
+}
+
+/// Inserts an editor flag like [`EditorLocked`] into an object, first giving it an [`EDITOR_ID_KEY`] if it has none.
+/// Since the id is part of the object's metadata, the level has to be saved for the flag to be kept when the level is loaded again.
+pub fn flag_object(world: &mut World, entity: Entity, flag: impl Component) {
+    let mut metadata = world
+        .get::<ObjectMetadata>(entity)
+        .cloned()
+        .unwrap_or_default();
+    if metadata.get(EDITOR_ID_KEY).is_none() {
+        metadata.insert(EDITOR_ID_KEY, format!("{:016x}", rand::random::<u64>()));
+        world.entity_mut(entity).insert(metadata);
+    }
+    world.entity_mut(entity).insert(flag);
+}
+
+/// Flags of objects of the current level that have not been spawned yet.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+struct PendingEditorFlags(EditorFlags);
+
+/// Visibility of an [`EditorHidden`] object from before the editor hid it.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+struct HiddenByEditor(Visibility);
+
+#[sysfail(log(level = "error"))]
+fn load_editor_flags(
+    current_level: Res<CurrentLevel>,
+    mut pending: ResMut<PendingEditorFlags>,
+    mut layers: ResMut<EditorLayers>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("load_editor_flags").entered();
+    pending.0 = default();
+    let path = get_editor_flags_path(&current_level.scene);
+    if path.exists() {
+        let serialized = fs::read_to_string(&path).with_context(|| {
+            format!("Failed to read editor flags at {}", path.to_string_lossy())
+        })?;
+        pending.0 = ron::from_str(&serialized).context("Failed to deserialize editor flags")?;
+    }
+    let loaded = std::mem::take(&mut pending.0.layers);
+    if *layers != loaded {
+        *layers = loaded;
+    }
+    Ok(())
+}
+
+fn attach_editor_flags(
+    mut commands: Commands,
+    mut pending: ResMut<PendingEditorFlags>,
+    spawned: Query<(Entity, &ObjectMetadata), Changed<ObjectMetadata>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("attach_editor_flags").entered();
+    if pending.0 == default() {
+        return;
+    }
+    for (entity, metadata) in spawned.iter() {
+        let Some(id) = metadata.get(EDITOR_ID_KEY) else {
+            continue;
+        };
+        if pending.0.locked.remove(id) {
+            commands.entity(entity).insert(EditorLocked);
+        }
+        if pending.0.hidden.remove(id) {
+            commands.entity(entity).insert(EditorHidden);
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn save_editor_flags(
+    current_level: Option<Res<CurrentLevel>>,
+    layers: Res<EditorLayers>,
+    objects: Query<
+        (
+            &ObjectMetadata,
+            Option<&EditorLocked>,
+            Option<&EditorHidden>,
+        ),
+        Or<(With<EditorLocked>, With<EditorHidden>)>,
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_editor_flags").entered();
+    let Some(current_level) = current_level else {
+        return Ok(());
+    };
+    let mut flags = EditorFlags {
+        layers: layers.clone(),
+        ..default()
+    };
+    for (metadata, locked, hidden) in objects.iter() {
+        let Some(id) = metadata.get(EDITOR_ID_KEY) else {
+            continue;
+        };
+        if locked.is_some() {
+            flags.locked.insert(id.to_owned());
+        }
+        if hidden.is_some() {
+            flags.hidden.insert(id.to_owned());
+        }
+    }
+    let serialized = ron::ser::to_string_pretty(&flags, default())
+        .context("Failed to serialize editor flags")?;
+    let path = get_editor_flags_path(&current_level.scene);
+    let dir = path
+        .parent()
+        .context("Failed to get editor flags directory")?;
+    fs::create_dir_all(dir).context("Failed to create editor flags directory")?;
+    fs::write(&path, serialized).context("Failed to write editor flags")?;
+    Ok(())
+}
+
+/// Removes flagged objects and their parts, e.g. the meshes of a locked house, from the selection.
+/// Objects take the [`Layer`] of their root.
+#[sysfail(log(level = "error"))]
+fn deselect_flagged_objects(
+    mut editor: ResMut<Editor>,
+    layers: Res<EditorLayers>,
+    flagged: Query<(Option<&EditorLocked>, Option<&EditorHidden>, Option<&Layer>)>,
+    parents: Query<&Parent>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("deselect_flagged_objects").entered();
+    let is_flagged = |entity: Entity| {
+        iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .filter_map(|entity| flagged.get(entity).ok())
+            .any(|(locked, hidden, layer)| {
+                locked.is_some()
+                    || hidden.is_some()
+                    || layers.is_locked(layer)
+                    || layers.is_hidden(layer)
+            })
+    };
+    let deselected: Vec<_> = editor
+        .window_state::<HierarchyWindow>()
+        .context("Failed to read hierarchy window state")?
+        .selected
+        .iter()
+        .filter(|entity| is_flagged(*entity))
+        .collect();
+    if deselected.is_empty() {
+        return Ok(());
+    }
+    let selected = &mut editor
+        .window_state_mut::<HierarchyWindow>()
+        .context("Failed to read hierarchy window state")?
+        .selected;
+    for entity in deselected {
+        selected.remove(entity);
+    }
+    Ok(())
+}
+
+/// Hides [`EditorHidden`] objects and those on hidden [`EditorLayers`] while the editor is active
+/// and restores their visibility otherwise.
+fn hide_flagged_objects(
+    mut commands: Commands,
+    editor: Res<Editor>,
+    layers: Res<EditorLayers>,
+    mut objects: Query<
+        (
+            Entity,
+            &mut Visibility,
+            Option<&EditorHidden>,
+            Option<&Layer>,
+            Option<&HiddenByEditor>,
+        ),
+        Or<(With<EditorHidden>, With<Layer>, With<HiddenByEditor>)>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("hide_flagged_objects").entered();
+    for (entity, mut visibility, hidden, layer, hidden_by_editor) in objects.iter_mut() {
+        let should_hide = editor.active() && (hidden.is_some() || layers.is_hidden(layer));
+        match (should_hide, hidden_by_editor) {
+            (true, None) => {
+                commands.entity(entity).insert(HiddenByEditor(*visibility));
+                *visibility = Visibility::Hidden;
+            }
+            (false, Some(HiddenByEditor(previous))) => {
+                *visibility = *previous;
+                commands.entity(entity).remove::<HiddenByEditor>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn get_editor_flags_path(level: &str) -> PathBuf {
+    Path::new("editor").join(level).with_extension("editor.ron")
+}
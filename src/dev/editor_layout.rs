@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const LAYOUT_PATH: &str = "editor/layout.ron";
+
+/// Persists the egui memory, which includes the positions and sizes of all editor windows,
+/// so that the editor layout survives restarts.
+pub fn editor_layout_plugin(app: &mut App) {
+    app.add_systems((
+        load_layout.run_if(run_once()),
+        save_layout.run_if(on_timer(Duration::from_secs(5))),
+    ));
+}
+
+#[sysfail(log(level = "error"))]
+fn load_layout(mut egui_contexts: EguiContexts) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("load_layout").entered();
+    let path = Path::new(LAYOUT_PATH);
+    if !path.exists() {
+        return Ok(());
+    }
+    let serialized = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read editor layout at {}", path.display()))?;
+    let memory: egui::Memory = ron::from_str(&serialized)
+        .with_context(|| format!("Failed to deserialize editor layout at {}", path.display()))?;
+    egui_contexts
+        .ctx_mut()
+        .memory_mut(|current| *current = memory);
+    info!("Successfully loaded editor layout at {}", path.display());
+    Ok(())
+}
+
+#[sysfail(log(level = "error"))]
+fn save_layout(mut egui_contexts: EguiContexts, mut last_saved: Local<String>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_layout").entered();
+    let serialized = egui_contexts
+        .ctx_mut()
+        .memory(ron::to_string)
+        .context("Failed to serialize editor layout")?;
+    if serialized == *last_saved {
+        return Ok(());
+    }
+    let path = Path::new(LAYOUT_PATH);
+    let dir = path
+        .parent()
+        .context("Failed to get editor layout directory")?;
+    fs::create_dir_all(dir).context("Failed to create editor layout directory")?;
+    fs::write(path, &serialized)
+        .with_context(|| format!("Failed to write editor layout at {}", path.display()))?;
+    *last_saved = serialized;
+    Ok(())
+}
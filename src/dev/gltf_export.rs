@@ -0,0 +1,393 @@
+use crate::console::AddConsoleCommandExt;
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::GameObject;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::render::mesh::skinning::SkinnedMesh;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::tasks::AsyncComputeTaskPool;
+use bevy::utils::HashMap;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::f32::consts::PI;
+use std::fs;
+use std::iter;
+use std::path::{Path, PathBuf};
+
+/// Writes the static geometry and lights of the current level to `exports/<filename>.gltf` with its data
+/// in a `.bin` file next to it, so the layout can be reviewed in Blender or a browser-based viewer without the game.
+/// Only visible meshes of spawned objects that do not move on their own are exported, so hiding a layer in the editor
+/// leaves it out of the export. Materials are reduced to their colors and lights use the `KHR_lights_punctual` extension.
+pub fn gltf_export_plugin(app: &mut App) {
+    app.add_event::<GltfExportRequest>()
+        .add_system(
+            export_gltf
+                .run_if(on_event::<GltfExportRequest>())
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "export_gltf",
+            "Exports the static geometry and lights of the current level to exports/<name>.gltf",
+            run_export_gltf_command,
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct GltfExportRequest {
+    pub filename: String,
+}
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Collects the JSON parts and the binary buffer of a glTF file.
+#[derive(Debug, Default)]
+struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    materials: Vec<Value>,
+    lights: Vec<Value>,
+    nodes: Vec<Value>,
+    mesh_indices: HashMap<(Handle<Mesh>, Option<Handle<StandardMaterial>>), usize>,
+    material_indices: HashMap<Handle<StandardMaterial>, usize>,
+}
+
+impl GltfBuilder {
+    /// Appends the data to the buffer and returns the index of its accessor.
+    fn push_accessor(
+        &mut self,
+        bytes: impl IntoIterator<Item = u8>,
+        count: usize,
+        component_type: u32,
+        accessor_type: &str,
+        target: u32,
+        bounds: Option<([f32; 3], [f32; 3])>,
+    ) -> usize {
+        let offset = self.buffer.len();
+        self.buffer.extend(bytes);
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": self.buffer.len() - offset,
+            "target": target,
+        }));
+        let mut accessor = json!({
+            "bufferView": self.buffer_views.len() - 1,
+            "componentType": component_type,
+            "count": count,
+            "type": accessor_type,
+        });
+        // glTF requires the bounds of positions
+        if let Some((min, max)) = bounds {
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_vec3s(&mut self, values: &[[f32; 3]], target: u32, with_bounds: bool) -> usize {
+        let bounds = with_bounds.then(|| {
+            values.iter().fold(
+                ([f32::INFINITY; 3], [f32::NEG_INFINITY; 3]),
+                |(min, max), value| {
+                    (
+                        Vec3::from(min).min(Vec3::from(*value)).to_array(),
+                        Vec3::from(max).max(Vec3::from(*value)).to_array(),
+                    )
+                },
+            )
+        });
+        let bytes = values
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes());
+        self.push_accessor(bytes, values.len(), FLOAT, "VEC3", target, bounds)
+    }
+
+    fn material_index(
+        &mut self,
+        handle: &Handle<StandardMaterial>,
+        materials: &Assets<StandardMaterial>,
+    ) -> Option<usize> {
+        if let Some(index) = self.material_indices.get(handle) {
+            return Some(*index);
+        }
+        let material = materials.get(handle)?;
+        let emissive = material.emissive.as_linear_rgba_f32();
+        let alpha_mode = if material.base_color.a() < 1. {
+            "BLEND"
+        } else {
+            "OPAQUE"
+        };
+        self.materials.push(json!({
+            "pbrMetallicRoughness": {
+                "baseColorFactor": material.base_color.as_linear_rgba_f32(),
+                "metallicFactor": material.metallic,
+                "roughnessFactor": material.perceptual_roughness,
+            },
+            "emissiveFactor": [emissive[0], emissive[1], emissive[2]],
+            "alphaMode": alpha_mode,
+            "doubleSided": material.double_sided,
+        }));
+        let index = self.materials.len() - 1;
+        self.material_indices.insert(handle.clone_weak(), index);
+        Some(index)
+    }
+
+    /// Returns `None` for meshes that cannot be exported, e.g. lines.
+    fn mesh_index(
+        &mut self,
+        handle: &Handle<Mesh>,
+        material: Option<&Handle<StandardMaterial>>,
+        meshes: &Assets<Mesh>,
+        materials: &Assets<StandardMaterial>,
+    ) -> Option<usize> {
+        let key = (handle.clone_weak(), material.map(Handle::clone_weak));
+        if let Some(index) = self.mesh_indices.get(&key) {
+            return Some(*index);
+        }
+        let mesh = meshes.get(handle)?;
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+        let mut attributes = json!({
+            "POSITION": self.push_vec3s(positions, ARRAY_BUFFER, true),
+        });
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            attributes["NORMAL"] = json!(self.push_vec3s(normals, ARRAY_BUFFER, false));
+        }
+        let mut primitive = json!({ "attributes": attributes });
+        if let Some(indices) = mesh.indices() {
+            // Every accessor stays aligned to four bytes by widening 16 bit indices
+            let bytes = indices
+                .iter()
+                .flat_map(|index| (index as u32).to_le_bytes());
+            let count = match indices {
+                Indices::U16(indices) => indices.len(),
+                Indices::U32(indices) => indices.len(),
+            };
+            primitive["indices"] = json!(self.push_accessor(
+                bytes,
+                count,
+                UNSIGNED_INT,
+                "SCALAR",
+                ELEMENT_ARRAY_BUFFER,
+                None,
+            ));
+        }
+        if let Some(material) =
+            material.and_then(|material| self.material_index(material, materials))
+        {
+            primitive["material"] = json!(material);
+        }
+        self.meshes.push(json!({ "primitives": [primitive] }));
+        let index = self.meshes.len() - 1;
+        self.mesh_indices.insert(key, index);
+        Some(index)
+    }
+
+    fn push_node(&mut self, name: Option<&Name>, transform: &GlobalTransform, mut node: Value) {
+        node["matrix"] = json!(transform.compute_matrix().to_cols_array());
+        if let Some(name) = name {
+            node["name"] = json!(name.as_str());
+        }
+        self.nodes.push(node);
+    }
+
+    fn push_light(
+        &mut self,
+        name: Option<&Name>,
+        transform: &GlobalTransform,
+        mut light: Value,
+        color: Color,
+    ) {
+        let color = color.as_linear_rgba_f32();
+        light["color"] = json!([color[0], color[1], color[2]]);
+        self.lights.push(light);
+        let node = json!({
+            "extensions": { "KHR_lights_punctual": { "light": self.lights.len() - 1 } },
+        });
+        self.push_node(name, transform, node);
+    }
+
+    /// The glTF JSON and the buffer, which is empty when there are no meshes.
+    fn build(self, bin_uri: &str) -> (Value, Vec<u8>) {
+        let root_nodes: Vec<_> = (0..self.nodes.len()).collect();
+        let mut gltf = json!({
+            "asset": { "version": "2.0", "generator": "Foxtrot glTF export" },
+            "scene": 0,
+            "scenes": [{ "nodes": root_nodes }],
+            "nodes": self.nodes,
+        });
+        // glTF does not allow empty arrays, e.g. when only lights are exported
+        let arrays = [
+            ("meshes", self.meshes),
+            ("materials", self.materials),
+            ("accessors", self.accessors),
+            ("bufferViews", self.buffer_views),
+        ];
+        for (key, array) in arrays {
+            if !array.is_empty() {
+                gltf[key] = Value::Array(array);
+            }
+        }
+        if !self.buffer.is_empty() {
+            gltf["buffers"] = json!([{ "uri": bin_uri, "byteLength": self.buffer.len() }]);
+        }
+        if !self.lights.is_empty() {
+            gltf["extensionsUsed"] = json!(["KHR_lights_punctual"]);
+            gltf["extensions"] = json!({ "KHR_lights_punctual": { "lights": self.lights } });
+        }
+        (gltf, self.buffer)
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn export_gltf(
+    mut export_requests: EventReader<GltfExportRequest>,
+    mesh_query: Query<
+        (
+            Entity,
+            &Handle<Mesh>,
+            Option<&Handle<StandardMaterial>>,
+            &GlobalTransform,
+            &ComputedVisibility,
+            Option<&Name>,
+        ),
+        Without<SkinnedMesh>,
+    >,
+    point_lights: Query<(Entity, &PointLight, &GlobalTransform, Option<&Name>)>,
+    spot_lights: Query<(Entity, &SpotLight, &GlobalTransform, Option<&Name>)>,
+    directional_lights: Query<(Entity, &DirectionalLight, &GlobalTransform, Option<&Name>)>,
+    objects: Query<(), Or<(With<GameObject>, With<CustomObject>)>>,
+    rigid_bodies: Query<&RigidBody>,
+    parents: Query<&Parent>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("export_gltf").entered();
+    // Parts of spawned objects that are not simulated, e.g. the meshes of a house but not those of a crate
+    let is_static_object_part = |entity: Entity| {
+        let mut belongs_to_object = false;
+        for entity in iter::once(entity).chain(parents.iter_ancestors(entity)) {
+            if matches!(rigid_bodies.get(entity), Ok(body) if *body != RigidBody::Fixed) {
+                return false;
+            }
+            belongs_to_object |= objects.contains(entity);
+        }
+        belongs_to_object
+    };
+
+    for request in export_requests.iter() {
+        let mut builder = GltfBuilder::default();
+        for (entity, mesh, material, transform, visibility, name) in mesh_query.iter() {
+            if !visibility.is_visible_in_hierarchy() || !is_static_object_part(entity) {
+                continue;
+            }
+            if let Some(mesh) = builder.mesh_index(mesh, material, &meshes, &materials) {
+                builder.push_node(name, transform, json!({ "mesh": mesh }));
+            }
+        }
+        // glTF uses candela for point and spot lights, while Bevy uses lumen
+        for (entity, light, transform, name) in point_lights.iter() {
+            if is_static_object_part(entity) {
+                let light_json = json!({
+                    "type": "point",
+                    "intensity": light.intensity / (4. * PI),
+                    "range": light.range,
+                });
+                builder.push_light(name, transform, light_json, light.color);
+            }
+        }
+        for (entity, light, transform, name) in spot_lights.iter() {
+            if is_static_object_part(entity) {
+                let light_json = json!({
+                    "type": "spot",
+                    "intensity": light.intensity / (4. * PI),
+                    "range": light.range,
+                    "spot": {
+                        "innerConeAngle": light.inner_angle,
+                        "outerConeAngle": light.outer_angle,
+                    },
+                });
+                builder.push_light(name, transform, light_json, light.color);
+            }
+        }
+        for (entity, light, transform, name) in directional_lights.iter() {
+            if is_static_object_part(entity) {
+                let light_json = json!({
+                    "type": "directional",
+                    "intensity": light.illuminance,
+                });
+                builder.push_light(name, transform, light_json, light.color);
+            }
+        }
+        if builder.nodes.is_empty() {
+            bail!("Nothing to export to \"{}\"", request.filename);
+        }
+
+        let path = get_export_path(&request.filename);
+        let bin_path = path.with_extension("bin");
+        let bin_uri = bin_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .context("Failed to get name of glTF buffer file")?
+            .to_owned();
+        let (gltf, buffer) = builder.build(&bin_uri);
+        let serialized = serde_json::to_string_pretty(&gltf).context("Failed to serialize glTF")?;
+        // Like level saves, only the export needs access to the world
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                match write_export(&path, &serialized, &bin_path, &buffer) {
+                    Ok(()) => info!("Exported glTF to {}", path.to_string_lossy()),
+                    Err(e) => error!("Failed to export glTF: {e:?}"),
+                }
+            })
+            .detach();
+    }
+    Ok(())
+}
+
+fn write_export(path: &Path, serialized: &str, bin_path: &Path, buffer: &[u8]) -> Result<()> {
+    let dir = path.parent().context("Failed to get export directory")?;
+    fs::create_dir_all(dir).context("Failed to create export directory")?;
+    if !buffer.is_empty() {
+        fs::write(bin_path, buffer).context("Failed to write glTF buffer")?;
+    }
+    fs::write(path, serialized).context("Failed to write glTF")?;
+    Ok(())
+}
+
+fn get_export_path(filename: &str) -> PathBuf {
+    Path::new("exports").join(filename).with_extension("gltf")
+}
+
+fn run_export_gltf_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let [filename] = args else {
+        bail!("Usage: export_gltf <name>");
+    };
+    world.send_event(GltfExportRequest {
+        filename: filename.to_string(),
+    });
+    Ok(format!(
+        "Exporting to {}",
+        get_export_path(filename).to_string_lossy()
+    ))
+}
@@ -0,0 +1,57 @@
+use crate::movement::gravity::Gravity;
+use crate::world_interaction::bounce_pad::BouncePad;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_editor_pls::Editor;
+use bevy_prototype_debug_lines::DebugLines;
+
+/// Length in m of the arrow per m/s of launch speed.
+const ARROW_SCALE: f32 = 0.1;
+/// Time in s of flight that the predicted trajectory covers.
+const TRAJECTORY_DURATION: f32 = 3.;
+const TRAJECTORY_SEGMENTS: usize = 30;
+
+/// Draws an arrow along the launch direction of every [`BouncePad`] while the editor is active,
+/// followed by the path a body launched by it would fly without drag or obstacles.
+pub fn launch_preview_plugin(app: &mut App) {
+    app.add_system(draw_launch_previews.in_set(OnUpdate(GameState::Playing)));
+}
+
+fn draw_launch_previews(
+    editor: Res<Editor>,
+    pads: Query<(&BouncePad, &GlobalTransform)>,
+    gravity: Res<Gravity>,
+    mut lines: ResMut<DebugLines>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("draw_launch_previews").entered();
+    if !editor.active() {
+        return;
+    }
+    for (bounce_pad, transform) in pads.iter() {
+        let velocity = bounce_pad.launch_velocity(transform);
+        let Some(direction) = velocity.try_normalize() else {
+            continue;
+        };
+        let start = transform.translation();
+        let tip = start + velocity * ARROW_SCALE;
+        lines.line_colored(start, tip, 0.0, Color::GREEN);
+        let side = direction.any_orthonormal_vector() * 0.15;
+        let back = tip - direction * 0.3;
+        lines.line_colored(tip, back + side, 0.0, Color::GREEN);
+        lines.line_colored(tip, back - side, 0.0, Color::GREEN);
+
+        let position_at = |segment: usize| {
+            let time = segment as f32 / TRAJECTORY_SEGMENTS as f32 * TRAJECTORY_DURATION;
+            start + velocity * time + 0.5 * gravity.0 * time * time
+        };
+        for segment in 0..TRAJECTORY_SEGMENTS {
+            lines.line_colored(
+                position_at(segment),
+                position_at(segment + 1),
+                0.0,
+                Color::GRAY,
+            );
+        }
+    }
+}
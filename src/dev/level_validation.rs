@@ -0,0 +1,179 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::level_serialization::{save_world, WorldSaveRequest};
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::GameObject;
+use crate::GameState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Objects the player can appear on, either when the level starts or when teleporting.
+const SPAWN_POINTS: [GameObject; 2] = [GameObject::Player, GameObject::Teleporter];
+
+/// Checks levels for common mistakes before they are saved:
+/// - Objects below the kill plane of the [`GameConfig`], which have usually fallen through the terrain.
+/// - Entities whose parent was despawned without them.
+/// - Spawn points so close to each other that the player would appear inside another character.
+/// - More lights than the budget allows.
+///
+/// Every [`WorldSaveRequest`] that does not skip validation and every [`LevelValidationRequest`] fill the
+/// [`LevelValidationReport`], which the "Level Validation" window shows with buttons that select the offending objects.
+/// Saves with errors are held back if the config says so until they are saved anyway from that window.
+/// Autosaves skip validation so they never lose work.
+pub fn level_validation_plugin(app: &mut App) {
+    app.add_event::<LevelValidationRequest>()
+        .init_resource::<LevelValidationReport>()
+        .add_system(
+            validate_level
+                .run_if(in_state(GameState::Playing))
+                .before(save_world)
+                .in_base_set(CoreSet::PostUpdate),
+        );
+}
+
+/// Validates the level without saving it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub struct LevelValidationRequest;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum IssueSeverity {
+    Warning,
+    /// Blocks saving if [`LevelValidation::block_save_on_errors`](crate::file_system_interaction::config::LevelValidation::block_save_on_errors) is set
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+    /// The object the issue is about, if it concerns a single one
+    pub entity: Option<Entity>,
+}
+
+/// Result of the latest validation.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct LevelValidationReport {
+    /// Whether a validation ran since the game started
+    pub validated: bool,
+    /// Errors first
+    pub issues: Vec<ValidationIssue>,
+    /// Save that was held back because of errors. Skipped by [`save_world`].
+    pub blocked_save: Option<WorldSaveRequest>,
+}
+
+impl LevelValidationReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Error)
+    }
+}
+
+fn validate_level(
+    mut requests: EventReader<LevelValidationRequest>,
+    mut save_requests: EventReader<WorldSaveRequest>,
+    mut report: ResMut<LevelValidationReport>,
+    config: Res<GameConfig>,
+    objects: Query<
+        (Entity, &GlobalTransform, Option<&GameObject>, Option<&Name>),
+        Or<(With<GameObject>, With<CustomObject>)>,
+    >,
+    children: Query<(Entity, &Parent)>,
+    entities: Query<()>,
+    lights: Query<(), Or<(With<PointLight>, With<SpotLight>)>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("validate_level").entered();
+    let validation_requested = requests.iter().count() > 0;
+    let saves: Vec<_> = save_requests
+        .iter()
+        .filter(|save| !save.skip_validation)
+        .cloned()
+        .collect();
+    if !validation_requested && saves.is_empty() {
+        return;
+    }
+    // Objects below the height at which the player dies have fallen out of the level
+    let kill_plane_height = config.health.kill_plane_height;
+    let config = &config.level_validation;
+    let mut issues = Vec::new();
+    let name = |entity: Entity, name: Option<&Name>| {
+        name.map(|name| name.to_string())
+            .unwrap_or_else(|| format!("{entity:?}"))
+    };
+
+    for (entity, transform, _, object_name) in objects.iter() {
+        let height = transform.translation().y;
+        if height < kill_plane_height {
+            issues.push(ValidationIssue {
+                severity: IssueSeverity::Error,
+                message: format!(
+                    "{} is below the kill plane at a height of {height:.1} m",
+                    name(entity, object_name)
+                ),
+                entity: Some(entity),
+            });
+        }
+    }
+
+    for (entity, parent) in children.iter() {
+        if !entities.contains(parent.get()) {
+            issues.push(ValidationIssue {
+                severity: IssueSeverity::Error,
+                message: format!("{entity:?} has a parent that no longer exists"),
+                entity: Some(entity),
+            });
+        }
+    }
+
+    let spawn_points: Vec<_> = objects
+        .iter()
+        .filter(|(.., object, _)| object.map_or(false, |object| SPAWN_POINTS.contains(object)))
+        .collect();
+    for (index, (entity, transform, _, object_name)) in spawn_points.iter().enumerate() {
+        for (other, other_transform, _, other_name) in &spawn_points[index + 1..] {
+            let distance = transform
+                .translation()
+                .distance(other_transform.translation());
+            if distance < config.min_spawn_point_distance {
+                issues.push(ValidationIssue {
+                    severity: IssueSeverity::Warning,
+                    message: format!(
+                        "Spawn points {} and {} overlap",
+                        name(*entity, *object_name),
+                        name(*other, *other_name)
+                    ),
+                    entity: Some(*entity),
+                });
+            }
+        }
+    }
+
+    let light_count = lights.iter().count();
+    if light_count > config.max_lights {
+        issues.push(ValidationIssue {
+            severity: IssueSeverity::Warning,
+            message: format!(
+                "{light_count} point and spot lights exceed the budget of {}",
+                config.max_lights
+            ),
+            entity: None,
+        });
+    }
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+
+    *report = LevelValidationReport {
+        validated: true,
+        issues,
+        blocked_save: None,
+    };
+    if !config.block_save_on_errors || !report.has_errors() {
+        return;
+    }
+    for save in saves {
+        warn!(
+            "Not saving level \"{}\" because it failed validation",
+            save.filename
+        );
+        report.blocked_save = Some(save);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::level_instantiation::spawning::custom::{CustomSpawnEvent, ObjectKind};
+use crate::level_instantiation::spawning::placement::{get_placement_position, ghost_bundle};
+use crate::level_instantiation::spawning::prefab::PrefabSpawnEvent;
+use crate::level_instantiation::spawning::spawner::SpawnEvent;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::EguiContexts;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Places objects from the spawn palette by clicking into the world.
+/// While a [`Placement`] is active, a [`PlacementGhost`] follows the point under the cursor, found by a raycast against all
+/// non-sensor colliders and, if that misses, against the ground plane. Left click spawns the object there,
+/// holding shift keeps placing the same object afterwards. Right click cancels.
+pub fn placement_plugin(app: &mut App) {
+    app.register_type::<Placement>()
+        .register_type::<PlacementGhost>()
+        .add_systems(
+            (
+                spawn_ghost.run_if(resource_added::<Placement>()),
+                despawn_ghost.run_if(resource_removed::<Placement>()),
+                update_placement.run_if(resource_exists::<Placement>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Placement {
+    pub object: ObjectKind,
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct PlacementGhost;
+
+fn spawn_ghost(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_ghost").entered();
+    commands.spawn((
+        ghost_bundle(&mut meshes, &mut materials, Color::rgba(0.3, 0.8, 1.0, 0.5)),
+        Name::new("Placement Ghost"),
+        PlacementGhost,
+    ));
+}
+
+fn despawn_ghost(mut commands: Commands, ghosts: Query<Entity, With<PlacementGhost>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("despawn_ghost").entered();
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_placement(
+    mut commands: Commands,
+    placement: Res<Placement>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), With<PlacementGhost>>,
+    mut spawn_requests: EventWriter<SpawnEvent>,
+    mut custom_spawn_requests: EventWriter<CustomSpawnEvent>,
+    mut prefab_spawn_requests: EventWriter<PrefabSpawnEvent>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_placement").entered();
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        commands.remove_resource::<Placement>();
+        return;
+    }
+    let position = windows.get_single().ok().and_then(|window| {
+        let cursor = window.cursor_position()?;
+        cameras
+            .iter()
+            .filter(|(camera, _)| camera.is_active)
+            .find_map(|(camera, camera_transform)| {
+                let viewport_position = to_viewport_position(window, camera, cursor)?;
+                let ray = camera.viewport_to_world(camera_transform, viewport_position)?;
+                get_placement_position(
+                    &rapier_context,
+                    ray.origin,
+                    ray.direction,
+                    config.editor.max_placement_distance,
+                    default(),
+                )
+            })
+    });
+    for (mut transform, mut visibility) in ghosts.iter_mut() {
+        match position {
+            Some(position) => {
+                transform.translation = position;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    let Some(position) = position else {
+        return;
+    };
+    if !mouse_buttons.just_pressed(MouseButton::Left) || egui_contexts.ctx_mut().is_using_pointer()
+    {
+        return;
+    }
+    let transform = Transform::from_translation(position);
+    match &placement.object {
+        ObjectKind::Builtin(object) => spawn_requests.send(SpawnEvent::new(*object, transform)),
+        ObjectKind::Custom(name) => custom_spawn_requests.send(CustomSpawnEvent {
+            name: name.clone(),
+            transform,
+        }),
+        ObjectKind::Prefab(name) => {
+            prefab_spawn_requests.send(PrefabSpawnEvent::new(name.clone(), transform))
+        }
+    }
+    if !keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+        commands.remove_resource::<Placement>();
+    }
+}
+
+/// Converts a cursor position in window coordinates to the coordinates expected by [`Camera::viewport_to_world`].
+/// Returns `None` if the cursor is outside of the camera's viewport, e.g. over one of the editor's panels.
+pub(crate) fn to_viewport_position(window: &Window, camera: &Camera, cursor: Vec2) -> Option<Vec2> {
+    // The cursor's origin is at the bottom left, while the viewport's origin is at the top left
+    let (min, max) = camera.logical_viewport_rect()?;
+    let from_top_left = Vec2::new(cursor.x, window.height() - cursor.y) - min;
+    let size = max - min;
+    let is_inside = from_top_left.cmpge(Vec2::ZERO).all() && from_top_left.cmplt(size).all();
+    is_inside.then(|| Vec2::new(from_top_left.x, size.y - from_top_left.y))
+}
@@ -0,0 +1,200 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::game_state_serialization::GameLoadRequest;
+use crate::file_system_interaction::level_serialization::WorldLoadRequest;
+use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::camera::IngameCamera;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Read-only spectator mode for reviewing levels and saves.
+/// While the [`SceneViewer`] resource exists, physics and NPC navigation are disabled, player input is frozen
+/// and the view is rendered through a free flying [`SceneViewerCamera`] instead of the [`IngameCamera`].
+/// Hold the right mouse button to look around, move with WASD, ascend with space, descend with control and go faster with shift.
+pub fn scene_viewer_plugin(app: &mut App) {
+    app.register_type::<SceneViewerCamera>()
+        .add_systems(
+            (
+                enter_scene_viewer.run_if(resource_added::<SceneViewer>()),
+                exit_scene_viewer.run_if(resource_removed::<SceneViewer>()),
+                (deactivate_ingame_cameras, fly_camera).run_if(resource_exists::<SceneViewer>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "view_level",
+            "Opens the given level in the scene viewer",
+            view_level,
+        )
+        .add_console_command(
+            "view_save",
+            "Opens the given save in the scene viewer",
+            view_save,
+        )
+        .add_console_command(
+            "exit_viewer",
+            "Closes the scene viewer and resumes gameplay",
+            exit_viewer,
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct SceneViewer;
+
+/// Exists while the scene viewer holds a freeze on the [`ActionsFrozen`], so that it only releases the freeze it took.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+struct ViewerFrozeActions;
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct SceneViewerCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+fn view_level(world: &mut World, args: &[&str]) -> Result<String> {
+    let filename = args.first().context("Usage: view_level <level name>")?;
+    world.send_event(WorldLoadRequest {
+        filename: filename.to_string(),
+        player_transform: None,
+    });
+    world.init_resource::<SceneViewer>();
+    Ok(format!("Viewing level \"{filename}\""))
+}
+
+fn view_save(world: &mut World, args: &[&str]) -> Result<String> {
+    let filename = args.first().map(|filename| filename.to_string());
+    let message = match &filename {
+        Some(filename) => format!("Viewing save \"{filename}\""),
+        None => "Viewing most recent save".to_owned(),
+    };
+    world.send_event(GameLoadRequest { filename });
+    world.init_resource::<SceneViewer>();
+    Ok(message)
+}
+
+fn exit_viewer(world: &mut World, _args: &[&str]) -> Result<String> {
+    world
+        .remove_resource::<SceneViewer>()
+        .context("Scene viewer is not active")?;
+    Ok(String::new())
+}
+
+fn enter_scene_viewer(
+    mut commands: Commands,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    viewer_froze_actions: Option<Res<ViewerFrozeActions>>,
+    ingame_cameras: Query<&GlobalTransform, With<IngameCamera>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("enter_scene_viewer").entered();
+    rapier_config.physics_pipeline_active = false;
+    if viewer_froze_actions.is_none() {
+        actions_frozen.freeze();
+        commands.insert_resource(ViewerFrozeActions);
+    }
+
+    let transform = ingame_cameras
+        .iter()
+        .next()
+        .map(|global_transform| global_transform.compute_transform())
+        .unwrap_or_else(|| Transform::from_xyz(0., 10., 10.).looking_at(Vec3::ZERO, Vec3::Y));
+    let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
+    commands.spawn((
+        SceneViewerCamera { yaw, pitch },
+        Camera3dBundle {
+            transform,
+            ..default()
+        },
+        Name::new("Scene Viewer Camera"),
+    ));
+    info!("Entered scene viewer");
+}
+
+fn exit_scene_viewer(
+    mut commands: Commands,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    viewer_froze_actions: Option<Res<ViewerFrozeActions>>,
+    mut ingame_cameras: Query<&mut Camera, With<IngameCamera>>,
+    viewer_cameras: Query<Entity, With<SceneViewerCamera>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("exit_scene_viewer").entered();
+    rapier_config.physics_pipeline_active = true;
+    // The viewer may be closed without having been entered, e.g. when it was opened outside of gameplay
+    if viewer_froze_actions.is_some() {
+        actions_frozen.unfreeze();
+        commands.remove_resource::<ViewerFrozeActions>();
+    }
+    for mut camera in ingame_cameras.iter_mut() {
+        camera.is_active = true;
+    }
+    for entity in viewer_cameras.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    info!("Exited scene viewer");
+}
+
+/// Also catches cameras spawned while the viewer is active, e.g. by loading a save.
+fn deactivate_ingame_cameras(mut ingame_cameras: Query<&mut Camera, With<IngameCamera>>) {
+    for mut camera in ingame_cameras.iter_mut() {
+        if camera.is_active {
+            camera.is_active = false;
+        }
+    }
+}
+
+fn fly_camera(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut camera_query: Query<(&mut SceneViewerCamera, &mut Transform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("fly_camera").entered();
+    const MOUSE_SENSITIVITY: f32 = 0.003;
+    const SPEED: f32 = 10.;
+    const SPRINT_MULTIPLIER: f32 = 4.;
+
+    let mouse_delta: Vec2 = mouse_motion.iter().map(|motion| motion.delta).sum();
+    let rotating = mouse_buttons.pressed(MouseButton::Right);
+    let direction = [
+        (KeyCode::W, Vec3::NEG_Z),
+        (KeyCode::S, Vec3::Z),
+        (KeyCode::A, Vec3::NEG_X),
+        (KeyCode::D, Vec3::X),
+        (KeyCode::Space, Vec3::Y),
+        (KeyCode::LControl, Vec3::NEG_Y),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.pressed(*key))
+    .map(|(_, direction)| direction)
+    .sum::<Vec3>()
+    .normalize_or_zero();
+    let speed = if keys.pressed(KeyCode::LShift) {
+        SPEED * SPRINT_MULTIPLIER
+    } else {
+        SPEED
+    };
+    // Use the raw delta so that the camera keeps working when the game time is paused.
+    let dt = time.raw_delta_seconds();
+
+    for (mut camera, mut transform) in camera_query.iter_mut() {
+        if rotating {
+            camera.yaw -= mouse_delta.x * MOUSE_SENSITIVITY;
+            camera.pitch = (camera.pitch - mouse_delta.y * MOUSE_SENSITIVITY)
+                .clamp(-89_f32.to_radians(), 89_f32.to_radians());
+            transform.rotation = Quat::from_euler(EulerRot::YXZ, camera.yaw, camera.pitch, 0.);
+        }
+        let movement = transform.rotation * direction * speed * dt;
+        transform.translation += movement;
+    }
+}
@@ -0,0 +1,221 @@
+use crate::dev::dev_editor::SplineWindow;
+use crate::dev::placement::to_viewport_position;
+use crate::file_system_interaction::config::GameConfig;
+use crate::movement::spline::Spline;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_editor_pls::default_windows::hierarchy::HierarchyWindow;
+use bevy_editor_pls::Editor;
+use bevy_egui::EguiContexts;
+use bevy_mod_sysfail::macros::*;
+use bevy_prototype_debug_lines::DebugLines;
+use serde::{Deserialize, Serialize};
+
+/// Size of the markers of control points and handles as a fraction of their distance to the camera.
+const MARKER_SCALE: f32 = 0.01;
+
+/// Edits the [`Spline`] selected in the editor's hierarchy while the editor is active.
+/// The curve is drawn together with its control points and their handles, which can be dragged with the left mouse button
+/// in the plane facing the camera. Dragging a handle turns the opposite one with it so the curve stays smooth,
+/// unless alt is held. Points are added, removed and typed in precisely in the "Spline" window.
+/// The transform gizmo leaves the spline alone while one of its points is dragged.
+pub fn spline_editor_plugin(app: &mut App) {
+    app.register_type::<SplinePointDrag>()
+        .register_type::<SplinePart>()
+        .add_system(update_spline_editor.in_set(OnUpdate(GameState::Playing)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct SplinePointDrag {
+    pub spline: Entity,
+    pub point: usize,
+    pub part: SplinePart,
+    /// Normal of the plane the point is moved in
+    pub plane_normal: Vec3,
+    /// Global position of the grabbed part when the drag started
+    pub start: Vec3,
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum SplinePart {
+    #[default]
+    Position,
+    InHandle,
+    OutHandle,
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn update_spline_editor(
+    mut editor: ResMut<Editor>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    config: Res<GameConfig>,
+    mut splines: Query<(&mut Spline, &GlobalTransform)>,
+    parents: Query<&Parent>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut lines: ResMut<DebugLines>,
+    mut egui_contexts: EguiContexts,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_spline_editor").entered();
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        editor
+            .window_state_mut::<SplineWindow>()
+            .context("Failed to read spline window state")?
+            .drag = None;
+    }
+    if !editor.active() {
+        return Ok(());
+    }
+    let mut selected = editor
+        .window_state::<HierarchyWindow>()
+        .context("Failed to read hierarchy window state")?
+        .selected
+        .iter();
+    let (Some(mut entity), None) = (selected.next(), selected.next()) else {
+        return Ok(());
+    };
+    // Selecting the tube of a spline edits the spline
+    while !splines.contains(entity) {
+        let Ok(parent) = parents.get(entity) else {
+            return Ok(());
+        };
+        entity = parent.get();
+    }
+    let (mut spline, global_transform) = splines.get_mut(entity)?;
+    let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return Ok(());
+    };
+    let window_state = editor
+        .window_state_mut::<SplineWindow>()
+        .context("Failed to read spline window state")?;
+    if window_state
+        .selected_point
+        .map_or(false, |point| point >= spline.points.len())
+    {
+        window_state.selected_point = None;
+    }
+    // The selection or the number of points changed while dragging
+    if window_state.drag.map_or(false, |drag| {
+        drag.spline != entity || drag.point >= spline.points.len()
+    }) {
+        window_state.drag = None;
+    }
+
+    let to_global = global_transform.affine();
+    let to_local = to_global.inverse();
+    let parts = |spline: &Spline| -> Vec<(usize, SplinePart, Vec3)> {
+        spline
+            .points
+            .iter()
+            .enumerate()
+            .flat_map(|(index, point)| {
+                [
+                    (index, SplinePart::Position, point.position),
+                    (
+                        index,
+                        SplinePart::InHandle,
+                        point.position + point.in_handle,
+                    ),
+                    (
+                        index,
+                        SplinePart::OutHandle,
+                        point.position + point.out_handle,
+                    ),
+                ]
+            })
+            .map(|(index, part, local)| (index, part, to_global.transform_point3(local)))
+            .collect()
+    };
+    let cursor = windows.get_single().ok().and_then(|window| {
+        let cursor = window.cursor_position()?;
+        to_viewport_position(window, camera, cursor)
+    });
+
+    if let Some(cursor) = cursor {
+        match window_state.drag {
+            Some(current) => {
+                let ray = camera.viewport_to_world(camera_transform, cursor);
+                let denominator = ray.map_or(0., |ray| ray.direction.dot(current.plane_normal));
+                if let (Some(ray), true) = (ray, denominator.abs() > 1e-4) {
+                    let toi = (current.start - ray.origin).dot(current.plane_normal) / denominator;
+                    let local = to_local.transform_point3(ray.origin + ray.direction * toi);
+                    let point = &mut spline.points[current.point];
+                    let mirror = !keys.any_pressed([KeyCode::LAlt, KeyCode::RAlt]);
+                    match current.part {
+                        SplinePart::Position => point.position = local,
+                        SplinePart::InHandle => {
+                            point.in_handle = local - point.position;
+                            if mirror {
+                                point.out_handle = -point.in_handle.normalize_or_zero()
+                                    * point.out_handle.length();
+                            }
+                        }
+                        SplinePart::OutHandle => {
+                            point.out_handle = local - point.position;
+                            if mirror {
+                                point.in_handle = -point.out_handle.normalize_or_zero()
+                                    * point.in_handle.length();
+                            }
+                        }
+                    }
+                }
+            }
+            None => {
+                if mouse_buttons.just_pressed(MouseButton::Left)
+                    && !egui_contexts.ctx_mut().is_using_pointer()
+                {
+                    let grabbed = parts(&*spline)
+                        .into_iter()
+                        .filter_map(|(index, part, global)| {
+                            let viewport = camera.world_to_viewport(camera_transform, global)?;
+                            let distance = viewport.distance(cursor);
+                            (distance <= config.editor.grab_distance)
+                                .then_some((index, part, global, distance))
+                        })
+                        .min_by(|(_, _, _, a), (_, _, _, b)| a.total_cmp(b));
+                    if let Some((point, part, start, _)) = grabbed {
+                        window_state.selected_point = Some(point);
+                        window_state.drag = Some(SplinePointDrag {
+                            spline: entity,
+                            point,
+                            part,
+                            plane_normal: camera_transform.forward(),
+                            start,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let path = spline.path(&global_transform.compute_transform());
+    for piece in path.points().windows(2) {
+        lines.line_colored(piece[0], piece[1], 0.0, Color::ORANGE);
+    }
+    let camera_position = camera_transform.translation();
+    for (index, part, global) in parts(&*spline) {
+        let size = camera_position.distance(global) * MARKER_SCALE;
+        let color = match part {
+            _ if window_state.selected_point == Some(index) => Color::YELLOW,
+            SplinePart::Position => Color::WHITE,
+            SplinePart::InHandle | SplinePart::OutHandle => Color::GRAY,
+        };
+        if part != SplinePart::Position {
+            let position = to_global.transform_point3(spline.points[index].position);
+            lines.line_colored(position, global, 0.0, Color::GRAY);
+        }
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            lines.line_colored(global - axis * size, global + axis * size, 0.0, color);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use crate::dev::dev_editor::{DevEditorWindow, SplineWindow, VolumeWindow};
+use crate::dev::placement::to_viewport_position;
+use crate::dev::spline_editor::update_spline_editor;
+use crate::dev::volume_editor::update_volume_editor;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_editor_pls::default_windows::hierarchy::HierarchyWindow;
+use bevy_editor_pls::Editor;
+use bevy_egui::EguiContexts;
+use bevy_mod_sysfail::macros::*;
+use bevy_prototype_debug_lines::DebugLines;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Size of the gizmo as a fraction of its distance to the camera, so that it always appears equally large on screen.
+const GIZMO_SCALE: f32 = 0.15;
+/// How close the cursor has to be to a handle to grab it, as a fraction of the gizmo's size.
+const GRAB_TOLERANCE: f32 = 0.08;
+const RING_SEGMENTS: usize = 32;
+const MIN_SCALE: f32 = 0.01;
+
+/// Draws a translate, rotate or scale gizmo on the entity selected in the editor's hierarchy while the editor is active.
+/// Dragging one of the gizmo's handles with the left mouse button moves, rotates or scales the entity along that axis.
+/// Translation and rotation happen in world space, scaling happens along the entity's own axes.
+/// The result is written to the entity's [`Transform`], so saving the level afterwards persists it.
+/// The mode is chosen in the "Foxtrot Dev" window. Dragging a point of a spline or a face of a volume takes precedence over the gizmo.
+pub fn transform_gizmo_plugin(app: &mut App) {
+    app.register_type::<GizmoMode>().add_system(
+        update_transform_gizmo
+            .after(update_spline_editor)
+            .after(update_volume_editor)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GizmoDrag {
+    entity: Entity,
+    axis: usize,
+    /// Where the drag started, see [`grab_parameter`].
+    start: Vec3,
+    /// Global transform of the entity when the drag started.
+    start_transform: Transform,
+    /// Transforms from global space into the space of the entity's parent.
+    parent_inverse: Affine3A,
+}
+
+#[sysfail(log(level = "error"))]
+fn update_transform_gizmo(
+    editor: Res<Editor>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut transforms: Query<(&mut Transform, &GlobalTransform, Option<&Parent>)>,
+    global_transforms: Query<&GlobalTransform>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut lines: ResMut<DebugLines>,
+    mut egui_contexts: EguiContexts,
+    mut drag: Local<Option<GizmoDrag>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_transform_gizmo").entered();
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        *drag = None;
+    }
+    if !editor.active() {
+        return Ok(());
+    }
+    // Spline points and volume faces are dragged by their own editors
+    let other_dragged = editor
+        .window_state::<SplineWindow>()
+        .context("Failed to read spline window state")?
+        .drag
+        .is_some()
+        || editor
+            .window_state::<VolumeWindow>()
+            .context("Failed to read volume window state")?
+            .drag
+            .is_some();
+    if other_dragged {
+        *drag = None;
+    }
+    let mode = editor
+        .window_state::<DevEditorWindow>()
+        .context("Failed to read dev window state")?
+        .gizmo_mode;
+    let mut selected = editor
+        .window_state::<HierarchyWindow>()
+        .context("Failed to read hierarchy window state")?
+        .selected
+        .iter();
+    // Manipulating multiple entities at once is not supported
+    let (Some(entity), None) = (selected.next(), selected.next()) else {
+        *drag = None;
+        return Ok(());
+    };
+    let Ok((mut transform, global_transform, parent)) = transforms.get_mut(entity) else {
+        return Ok(());
+    };
+    let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return Ok(());
+    };
+
+    let global = global_transform.compute_transform();
+    let origin = global.translation;
+    let size = camera_transform.translation().distance(origin) * GIZMO_SCALE;
+    let axes = match mode {
+        GizmoMode::Translate | GizmoMode::Rotate => [Vec3::X, Vec3::Y, Vec3::Z],
+        GizmoMode::Scale => [global.right(), global.up(), global.back()],
+    };
+    let ray = windows.get_single().ok().and_then(|window| {
+        let cursor = window.cursor_position()?;
+        let viewport_position = to_viewport_position(window, camera, cursor)?;
+        camera.viewport_to_world(camera_transform, viewport_position)
+    });
+
+    if let (Some(ray), false) = (ray, other_dragged) {
+        match *drag {
+            Some(current) if current.entity == entity => {
+                let axis = axes[current.axis];
+                if let Some(grab) =
+                    grab_parameter(mode, ray, current.start_transform.translation, axis)
+                {
+                    let mut new_global = current.start_transform;
+                    match mode {
+                        GizmoMode::Translate => {
+                            new_global.translation += axis * (grab.x - current.start.x);
+                        }
+                        GizmoMode::Rotate => {
+                            let from = current.start.normalize_or_zero();
+                            let to = grab.normalize_or_zero();
+                            let angle = axis.dot(from.cross(to)).atan2(from.dot(to));
+                            new_global.rotation =
+                                Quat::from_axis_angle(axis, angle) * new_global.rotation;
+                        }
+                        GizmoMode::Scale => {
+                            let factor = 1. + (grab.x - current.start.x) / size;
+                            new_global.scale[current.axis] =
+                                (current.start_transform.scale[current.axis] * factor)
+                                    .max(MIN_SCALE);
+                        }
+                    }
+                    *transform = Transform::from_matrix(
+                        Mat4::from(current.parent_inverse) * new_global.compute_matrix(),
+                    );
+                }
+            }
+            _ => {
+                *drag = None;
+                if mouse_buttons.just_pressed(MouseButton::Left)
+                    && !egui_contexts.ctx_mut().is_using_pointer()
+                {
+                    if let Some((axis, start)) = hovered_axis(mode, ray, origin, axes, size) {
+                        let parent_inverse = parent
+                            .and_then(|parent| global_transforms.get(parent.get()).ok())
+                            .map(|parent| parent.affine().inverse())
+                            .unwrap_or_default();
+                        *drag = Some(GizmoDrag {
+                            entity,
+                            axis,
+                            start,
+                            start_transform: global,
+                            parent_inverse,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let highlighted = match *drag {
+        Some(current) => Some(current.axis),
+        None => ray
+            .and_then(|ray| hovered_axis(mode, ray, origin, axes, size))
+            .map(|(axis, _)| axis),
+    };
+    for (index, axis) in axes.into_iter().enumerate() {
+        let color = if highlighted == Some(index) {
+            Color::YELLOW
+        } else {
+            [Color::RED, Color::GREEN, Color::BLUE][index]
+        };
+        match mode {
+            GizmoMode::Translate => draw_arrow(&mut lines, origin, axis, size, color),
+            GizmoMode::Rotate => draw_ring(&mut lines, origin, axis, size, color),
+            GizmoMode::Scale => draw_scale_handle(&mut lines, origin, axis, size, color),
+        }
+    }
+    Ok(())
+}
+
+/// Finds the handle under the cursor and where it is grabbed.
+fn hovered_axis(
+    mode: GizmoMode,
+    ray: Ray,
+    origin: Vec3,
+    axes: [Vec3; 3],
+    size: f32,
+) -> Option<(usize, Vec3)> {
+    axes.into_iter()
+        .enumerate()
+        .filter_map(|(index, axis)| {
+            let grab = grab_parameter(mode, ray, origin, axis)?;
+            let distance = match mode {
+                GizmoMode::Translate | GizmoMode::Scale => {
+                    if !(0. ..=size).contains(&grab.x) {
+                        return None;
+                    }
+                    distance_to_ray(ray, origin + axis * grab.x)
+                }
+                GizmoMode::Rotate => (grab.length() - size).abs(),
+            };
+            (distance <= size * GRAB_TOLERANCE).then_some((index, grab, distance))
+        })
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .map(|(index, grab, _)| (index, grab))
+}
+
+/// For translating and scaling, the x component is the distance along the axis of the point on the axis closest to the ray.
+/// For rotating, this is the offset from the gizmo's origin of the point where the ray hits the plane of the ring.
+fn grab_parameter(mode: GizmoMode, ray: Ray, origin: Vec3, axis: Vec3) -> Option<Vec3> {
+    match mode {
+        GizmoMode::Translate | GizmoMode::Scale => {
+            closest_on_axis(ray, origin, axis).map(|along_axis| Vec3::X * along_axis)
+        }
+        GizmoMode::Rotate => {
+            let denominator = ray.direction.dot(axis);
+            if denominator.abs() < 1e-4 {
+                // The ring is seen edge-on
+                return None;
+            }
+            let toi = (origin - ray.origin).dot(axis) / denominator;
+            (toi > 0.).then(|| ray.origin + ray.direction * toi - origin)
+        }
+    }
+}
+
+/// Distance from `origin` along the normalized `axis` of the point on the axis closest to the ray.
+/// Returns `None` if the axis points straight at the camera.
+pub(crate) fn closest_on_axis(ray: Ray, origin: Vec3, axis: Vec3) -> Option<f32> {
+    // Closest points between two lines
+    let offset = origin - ray.origin;
+    let alignment = axis.dot(ray.direction);
+    let denominator = 1. - alignment * alignment;
+    if denominator < 1e-4 {
+        return None;
+    }
+    Some((alignment * offset.dot(ray.direction) - offset.dot(axis)) / denominator)
+}
+
+fn distance_to_ray(ray: Ray, point: Vec3) -> f32 {
+    let offset = point - ray.origin;
+    (offset - ray.direction * offset.dot(ray.direction)).length()
+}
+
+fn draw_arrow(lines: &mut DebugLines, origin: Vec3, axis: Vec3, size: f32, color: Color) {
+    let tip = origin + axis * size;
+    lines.line_colored(origin, tip, 0.0, color);
+    let side = axis.any_orthonormal_vector() * size * 0.08;
+    let back = tip - axis * size * 0.2;
+    for side in [side, -side, axis.cross(side), -axis.cross(side)] {
+        lines.line_colored(tip, back + side, 0.0, color);
+    }
+}
+
+fn draw_ring(lines: &mut DebugLines, origin: Vec3, axis: Vec3, size: f32, color: Color) {
+    let start = axis.any_orthonormal_vector() * size;
+    let points: Vec<_> = (0..=RING_SEGMENTS)
+        .map(|index| {
+            let angle = TAU * index as f32 / RING_SEGMENTS as f32;
+            origin + Quat::from_axis_angle(axis, angle) * start
+        })
+        .collect();
+    for segment in points.windows(2) {
+        lines.line_colored(segment[0], segment[1], 0.0, color);
+    }
+}
+
+fn draw_scale_handle(lines: &mut DebugLines, origin: Vec3, axis: Vec3, size: f32, color: Color) {
+    let tip = origin + axis * size;
+    lines.line_colored(origin, tip, 0.0, color);
+    let side = axis.any_orthonormal_vector() * size * 0.06;
+    let other_side = axis.cross(side);
+    let corners = [
+        tip + side + other_side,
+        tip + side - other_side,
+        tip - side - other_side,
+        tip - side + other_side,
+    ];
+    for index in 0..corners.len() {
+        lines.line_colored(
+            corners[index],
+            corners[(index + 1) % corners.len()],
+            0.0,
+            color,
+        );
+    }
+}
@@ -0,0 +1,306 @@
+use crate::dev::dev_editor::VolumeWindow;
+use crate::dev::placement::to_viewport_position;
+use crate::dev::transform_gizmo::closest_on_axis;
+use crate::file_system_interaction::config::GameConfig;
+use crate::world_interaction::volume::{Volume, VolumeKind, VolumeShape, MIN_VOLUME_EXTENT};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::math::Affine3A;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_editor_pls::default_windows::hierarchy::HierarchyWindow;
+use bevy_editor_pls::Editor;
+use bevy_egui::EguiContexts;
+use bevy_mod_sysfail::macros::*;
+use bevy_prototype_debug_lines::DebugLines;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Size of the face handles as a fraction of their distance to the camera.
+const HANDLE_SCALE: f32 = 0.015;
+const CIRCLE_SEGMENTS: usize = 32;
+
+/// Draws every [`Volume`] as a wireframe while the editor is active and lets the one selected in the editor's hierarchy be resized.
+/// Each face of the selected volume has a handle that can be dragged with the left mouse button along the face's normal.
+/// Boxes and the caps of cylinders keep the opposite face in place, while spheres and the sides of cylinders grow around their center.
+/// The shape, its exact size and the kind of the volume are set in the "Volume" window.
+/// The transform gizmo leaves the volume alone while one of its faces is dragged.
+pub fn volume_editor_plugin(app: &mut App) {
+    app.register_type::<VolumeFaceDrag>()
+        .add_system(update_volume_editor.in_set(OnUpdate(GameState::Playing)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct VolumeFaceDrag {
+    pub volume: Entity,
+    /// See [`face_normal`]
+    pub face: usize,
+    pub start_shape: VolumeShape,
+    /// Translation of the volume when the drag started
+    pub start_translation: Vec3,
+    /// Global position of the handle when the drag started
+    pub start_position: Vec3,
+    /// Distance along the face's normal from `start_position` at which the handle was grabbed
+    pub start_grab: f32,
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn update_volume_editor(
+    mut editor: ResMut<Editor>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    config: Res<GameConfig>,
+    mut volumes: Query<(
+        Entity,
+        &mut Volume,
+        &mut Transform,
+        &GlobalTransform,
+        Option<&Parent>,
+    )>,
+    global_transforms: Query<&GlobalTransform>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut lines: ResMut<DebugLines>,
+    mut egui_contexts: EguiContexts,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_volume_editor").entered();
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        editor
+            .window_state_mut::<VolumeWindow>()
+            .context("Failed to read volume window state")?
+            .drag = None;
+    }
+    if !editor.active() {
+        return Ok(());
+    }
+    let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return Ok(());
+    };
+    let mut selected = editor
+        .window_state::<HierarchyWindow>()
+        .context("Failed to read hierarchy window state")?
+        .selected
+        .iter();
+    let selected = match (selected.next(), selected.next()) {
+        (Some(entity), None) if volumes.contains(entity) => Some(entity),
+        _ => None,
+    };
+    let window_state = editor
+        .window_state_mut::<VolumeWindow>()
+        .context("Failed to read volume window state")?;
+    if window_state
+        .drag
+        .map_or(false, |drag| Some(drag.volume) != selected)
+    {
+        window_state.drag = None;
+    }
+    let ray = windows.get_single().ok().and_then(|window| {
+        let cursor = window.cursor_position()?;
+        let viewport_position = to_viewport_position(window, camera, cursor)?;
+        Some((
+            viewport_position,
+            camera.viewport_to_world(camera_transform, viewport_position)?,
+        ))
+    });
+
+    let mut hovered_face = None;
+    if let (Some(entity), Some((cursor, ray))) = (selected, ray) {
+        let (_, mut volume, mut transform, global_transform, parent) = volumes.get_mut(entity)?;
+        match window_state.drag {
+            Some(current) => {
+                hovered_face = Some(current.face);
+                let global = global_transform.compute_transform();
+                let normal = global.rotation * face_normal(current.face);
+                if let Some(grab) = closest_on_axis(ray, current.start_position, normal) {
+                    let axis = current.face / 2;
+                    let delta = (grab - current.start_grab) / global.scale[axis];
+                    let (shape, shift) = resize(current.start_shape, current.face, delta);
+                    let parent_inverse = parent
+                        .and_then(|parent| global_transforms.get(parent.get()).ok())
+                        .map(|parent| parent.affine().inverse())
+                        .unwrap_or_default();
+                    let translation = current.start_translation
+                        + parent_inverse.transform_vector3(normal * shift * global.scale[axis]);
+                    if volume.shape != shape {
+                        volume.shape = shape;
+                    }
+                    if transform.translation != translation {
+                        transform.translation = translation;
+                    }
+                }
+            }
+            None => {
+                let to_global = global_transform.affine();
+                hovered_face = (0..6)
+                    .filter_map(|face| {
+                        let position = to_global
+                            .transform_point3(face_position(volume.shape, face_normal(face)));
+                        let viewport = camera.world_to_viewport(camera_transform, position)?;
+                        let distance = viewport.distance(cursor);
+                        (distance <= config.editor.grab_distance)
+                            .then_some((face, position, distance))
+                    })
+                    .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+                    .map(|(face, position, _)| {
+                        if mouse_buttons.just_pressed(MouseButton::Left)
+                            && !egui_contexts.ctx_mut().is_using_pointer()
+                        {
+                            let normal =
+                                global_transform.compute_transform().rotation * face_normal(face);
+                            window_state.drag = Some(VolumeFaceDrag {
+                                volume: entity,
+                                face,
+                                start_shape: volume.shape,
+                                start_translation: transform.translation,
+                                start_position: position,
+                                start_grab: closest_on_axis(ray, position, normal)
+                                    .unwrap_or_default(),
+                            });
+                        }
+                        face
+                    });
+            }
+        }
+    }
+
+    let camera_position = camera_transform.translation();
+    for (entity, volume, _, global_transform, _) in volumes.iter() {
+        let to_global = global_transform.affine();
+        let color = kind_color(volume.kind);
+        draw_shape(&mut lines, to_global, volume.shape, color);
+        if Some(entity) != selected {
+            continue;
+        }
+        for face in 0..6 {
+            let position =
+                to_global.transform_point3(face_position(volume.shape, face_normal(face)));
+            let size = camera_position.distance(position) * HANDLE_SCALE;
+            let color = if hovered_face == Some(face) {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                lines.line_colored(position - axis * size, position + axis * size, 0.0, color);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Local normal of each face, in the order +x, -x, +y, -y, +z, -z.
+fn face_normal(face: usize) -> Vec3 {
+    let sign = if face % 2 == 0 { 1. } else { -1. };
+    Vec3::AXES[face / 2] * sign
+}
+
+fn face_position(shape: VolumeShape, normal: Vec3) -> Vec3 {
+    match shape {
+        VolumeShape::Box { size } => normal * size / 2.,
+        VolumeShape::Sphere { radius } => normal * radius,
+        VolumeShape::Cylinder { height, .. } if normal.y != 0. => normal * height / 2.,
+        VolumeShape::Cylinder { radius, .. } => normal * radius,
+    }
+}
+
+/// Moves a face of the shape outwards by `delta`. Returns the new shape and how far its center has to move along the face's normal
+/// to keep the opposite face in place.
+fn resize(shape: VolumeShape, face: usize, delta: f32) -> (VolumeShape, f32) {
+    let grow = |extent: f32| (extent + delta).max(MIN_VOLUME_EXTENT);
+    let axis = face / 2;
+    match shape {
+        VolumeShape::Box { mut size } => {
+            let old = size[axis];
+            size[axis] = grow(old);
+            (VolumeShape::Box { size }, (size[axis] - old) / 2.)
+        }
+        VolumeShape::Sphere { radius } => (
+            VolumeShape::Sphere {
+                radius: grow(radius),
+            },
+            0.,
+        ),
+        VolumeShape::Cylinder { height, radius } if axis == 1 => {
+            let new_height = grow(height);
+            (
+                VolumeShape::Cylinder {
+                    height: new_height,
+                    radius,
+                },
+                (new_height - height) / 2.,
+            )
+        }
+        VolumeShape::Cylinder { height, radius } => (
+            VolumeShape::Cylinder {
+                height,
+                radius: grow(radius),
+            },
+            0.,
+        ),
+    }
+}
+
+fn kind_color(kind: VolumeKind) -> Color {
+    match kind {
+        VolumeKind::Trigger => Color::GREEN,
+        VolumeKind::Water => Color::CYAN,
+        VolumeKind::Wind => Color::GRAY,
+        VolumeKind::CameraRoom => Color::ORANGE,
+        VolumeKind::Launch => Color::GREEN,
+        VolumeKind::Hazard => Color::RED,
+        VolumeKind::Challenge => Color::YELLOW,
+    }
+}
+
+fn draw_shape(lines: &mut DebugLines, to_global: Affine3A, shape: VolumeShape, color: Color) {
+    let mut line = |start: Vec3, end: Vec3| {
+        lines.line_colored(
+            to_global.transform_point3(start),
+            to_global.transform_point3(end),
+            0.0,
+            color,
+        );
+    };
+    match shape {
+        VolumeShape::Box { size } => {
+            let corner = |index: usize| {
+                let sign = |bit: usize| if index & bit == 0 { -0.5 } else { 0.5 };
+                Vec3::new(sign(1), sign(2), sign(4)) * size
+            };
+            // Edges connect corners that differ in exactly one coordinate
+            for index in 0..8 {
+                for bit in [1, 2, 4] {
+                    if index & bit == 0 {
+                        line(corner(index), corner(index | bit));
+                    }
+                }
+            }
+        }
+        VolumeShape::Sphere { radius } => {
+            for (u, v) in [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)] {
+                draw_circle(&mut line, Vec3::ZERO, u * radius, v * radius);
+            }
+        }
+        VolumeShape::Cylinder { height, radius } => {
+            let top = Vec3::Y * height / 2.;
+            for center in [top, -top] {
+                draw_circle(&mut line, center, Vec3::X * radius, Vec3::Z * radius);
+            }
+            for side in [Vec3::X, Vec3::NEG_X, Vec3::Z, Vec3::NEG_Z] {
+                line(side * radius + top, side * radius - top);
+            }
+        }
+    }
+}
+
+fn draw_circle(line: &mut impl FnMut(Vec3, Vec3), center: Vec3, u: Vec3, v: Vec3) {
+    let point = |index: usize| {
+        let angle = index as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+        center + u * angle.cos() + v * angle.sin()
+    };
+    for index in 0..CIRCLE_SEGMENTS {
+        line(point(index), point(index + 1));
+    }
+}
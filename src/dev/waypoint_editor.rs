@@ -0,0 +1,125 @@
+use crate::dev::dev_editor::WaypointWindow;
+use crate::dev::placement::to_viewport_position;
+use crate::file_system_interaction::config::GameConfig;
+use crate::movement::patrol_path::Waypoint;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy::window::PrimaryWindow;
+use bevy_editor_pls::default_windows::hierarchy::HierarchyWindow;
+use bevy_editor_pls::Editor;
+use bevy_egui::EguiContexts;
+use bevy_mod_sysfail::macros::*;
+use bevy_prototype_debug_lines::DebugLines;
+
+/// Size of the waypoint markers as a fraction of their distance to the camera.
+const MARKER_SCALE: f32 = 0.015;
+
+/// Draws every patrol path as a closed loop through its [`Waypoint`]s while the editor is active,
+/// highlighting the path of the selected waypoint. Waypoints without a path are drawn in gray.
+/// While chaining is enabled in the "Waypoints" window, clicking a waypoint in the viewport appends it to the path named there.
+pub fn waypoint_editor_plugin(app: &mut App) {
+    app.add_system(update_waypoint_editor.in_set(OnUpdate(GameState::Playing)));
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn update_waypoint_editor(
+    editor: Res<Editor>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    config: Res<GameConfig>,
+    mut waypoints: Query<(Entity, &mut Waypoint, &GlobalTransform)>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut lines: ResMut<DebugLines>,
+    mut egui_contexts: EguiContexts,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_waypoint_editor").entered();
+    if !editor.active() {
+        return Ok(());
+    }
+    let Some((camera, camera_transform)) = cameras.iter().find(|(camera, _)| camera.is_active)
+    else {
+        return Ok(());
+    };
+    let selected_path = editor
+        .window_state::<HierarchyWindow>()
+        .context("Failed to read hierarchy window state")?
+        .selected
+        .iter()
+        .find_map(|entity| waypoints.get(entity).ok())
+        .map(|(_, waypoint, _)| waypoint.path.clone());
+    let window_state = editor
+        .window_state::<WaypointWindow>()
+        .context("Failed to read waypoint window state")?;
+
+    if window_state.chaining
+        && !window_state.path.is_empty()
+        && mouse_buttons.just_pressed(MouseButton::Left)
+        && !egui_contexts.ctx_mut().is_using_pointer()
+    {
+        let cursor = windows.get_single().ok().and_then(|window| {
+            let cursor = window.cursor_position()?;
+            to_viewport_position(window, camera, cursor)
+        });
+        let clicked = cursor.and_then(|cursor| {
+            waypoints
+                .iter()
+                .filter_map(|(entity, _, transform)| {
+                    let viewport =
+                        camera.world_to_viewport(camera_transform, transform.translation())?;
+                    let distance = viewport.distance(cursor);
+                    (distance <= config.editor.grab_distance).then_some((entity, distance))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(entity, _)| entity)
+        });
+        let next_index = waypoints
+            .iter()
+            .filter(|(_, waypoint, _)| waypoint.path == window_state.path)
+            .map(|(_, waypoint, _)| waypoint.index + 1)
+            .max()
+            .unwrap_or_default();
+        if let Some(entity) = clicked {
+            let (_, mut waypoint, _) = waypoints.get_mut(entity)?;
+            // Clicking a waypoint that is already part of the path would reorder it, which is rarely intended
+            if waypoint.path != window_state.path {
+                *waypoint = Waypoint {
+                    path: window_state.path.clone(),
+                    index: next_index,
+                };
+            }
+        }
+    }
+
+    let mut paths: HashMap<&str, Vec<(usize, Vec3)>> = HashMap::new();
+    for (_, waypoint, transform) in waypoints.iter() {
+        paths
+            .entry(waypoint.path.as_str())
+            .or_default()
+            .push((waypoint.index, transform.translation()));
+    }
+    let camera_position = camera_transform.translation();
+    for (path, mut points) in paths {
+        points.sort_by_key(|(index, _)| *index);
+        let color = if path.is_empty() {
+            Color::GRAY
+        } else if selected_path.as_deref() == Some(path) {
+            Color::YELLOW
+        } else {
+            Color::WHITE
+        };
+        for (index, (_, position)) in points.iter().enumerate() {
+            if !path.is_empty() {
+                let (_, next) = points[(index + 1) % points.len()];
+                lines.line_colored(*position, next, 0.0, color);
+            }
+            let size = camera_position.distance(*position) * MARKER_SCALE;
+            for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                lines.line_colored(*position - axis * size, *position + axis * size, 0.0, color);
+            }
+        }
+    }
+    Ok(())
+}
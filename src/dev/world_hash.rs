@@ -0,0 +1,134 @@
+use crate::console::AddConsoleCommandExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::condition::ActiveConditions;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+use bevy::utils::FixedState;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// Number of frames between two world hashes.
+pub const HASH_INTERVAL: u32 = 10;
+
+/// Periodically hashes the serializable world state, i.e. all [`GameObject`]s with their [`Transform`]s
+/// and the [`ActiveConditions`], and records the result in [`WorldHashHistory`].
+/// Comparing these hashes between two runs shows the frame at which a supposedly deterministic simulation diverged.
+pub fn world_hash_plugin(app: &mut App) {
+    app.register_type::<WorldHashHistory>()
+        .init_resource::<WorldHashHistory>()
+        .add_system(
+            hash_world
+                .run_if(in_state(GameState::Playing))
+                .in_base_set(CoreSet::PostUpdate),
+        )
+        .add_console_command(
+            "world_hash",
+            "Prints the most recent world hashes. Takes the number of hashes to print as an optional argument",
+            print_world_hashes,
+        );
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub struct WorldHash {
+    pub frame: u32,
+    pub hash: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct WorldHashHistory {
+    pub hashes: VecDeque<WorldHash>,
+}
+
+impl WorldHashHistory {
+    const MAX_LEN: usize = 600;
+
+    pub fn push(&mut self, hash: WorldHash) {
+        self.hashes.push_back(hash);
+        while self.hashes.len() > Self::MAX_LEN {
+            self.hashes.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<WorldHash> {
+        self.hashes.back().copied()
+    }
+}
+
+fn hash_world(
+    frame_count: Res<FrameCount>,
+    game_objects: Query<(&GameObject, Option<&Transform>)>,
+    conditions: Option<Res<ActiveConditions>>,
+    mut history: ResMut<WorldHashHistory>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("hash_world").entered();
+    if frame_count.0 % HASH_INTERVAL != 0 {
+        return;
+    }
+
+    // Query iteration order is not stable, so the entries are sorted before combining them
+    let mut entries: Vec<_> = game_objects
+        .iter()
+        .map(|(game_object, transform)| {
+            let mut hasher = FixedState.build_hasher();
+            game_object.hash(&mut hasher);
+            if let Some(transform) = transform {
+                hash_transform(transform, &mut hasher);
+            }
+            hasher.finish()
+        })
+        .collect();
+    entries.sort_unstable();
+
+    let mut conditions: Vec<_> = conditions
+        .iter()
+        .flat_map(|conditions| conditions.0.iter().map(|condition| &condition.0))
+        .collect();
+    conditions.sort_unstable();
+
+    let mut hasher = FixedState.build_hasher();
+    entries.hash(&mut hasher);
+    conditions.hash(&mut hasher);
+    history.push(WorldHash {
+        frame: frame_count.0,
+        hash: hasher.finish(),
+    });
+}
+
+fn hash_transform(transform: &Transform, hasher: &mut impl Hasher) {
+    let floats = transform
+        .translation
+        .to_array()
+        .into_iter()
+        .chain(transform.rotation.to_array())
+        .chain(transform.scale.to_array());
+    for float in floats {
+        float.to_bits().hash(hasher);
+    }
+}
+
+fn print_world_hashes(world: &mut World, args: &[&str]) -> Result<String> {
+    let count = args
+        .first()
+        .map(|count| count.parse::<usize>())
+        .transpose()
+        .context("Failed to parse number of hashes")?
+        .unwrap_or(10);
+    let history = world.resource::<WorldHashHistory>();
+    Ok(history
+        .hashes
+        .iter()
+        .rev()
+        .take(count)
+        .rev()
+        .map(|WorldHash { frame, hash }| format!("frame {frame}: {hash:016x}"))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
@@ -1,15 +1,25 @@
 pub mod asset_loading;
 pub mod audio;
+pub mod bug_report;
 pub mod config;
 pub mod game_state_serialization;
 pub mod level_serialization;
+pub mod localization;
+#[cfg(all(feature = "native", not(feature = "tracing")))]
+pub mod log_history;
+pub mod player_profile;
+pub mod scene_interchange;
 
 use bevy::prelude::*;
 
 use crate::file_system_interaction::asset_loading::loading_plugin;
 use crate::file_system_interaction::audio::internal_audio_plugin;
+use crate::file_system_interaction::bug_report::bug_report_plugin;
 use crate::file_system_interaction::game_state_serialization::game_state_serialization_plugin;
 use crate::file_system_interaction::level_serialization::level_serialization_plugin;
+use crate::file_system_interaction::localization::localization_plugin;
+use crate::file_system_interaction::player_profile::player_profile_plugin;
+use crate::file_system_interaction::scene_interchange::scene_interchange_plugin;
 use seldom_fn_plugin::FnPluginExt;
 
 /// Handles loading and saving of levels and save states to disk.
@@ -17,10 +27,18 @@ use seldom_fn_plugin::FnPluginExt;
 /// - [`loading_plugin`] handles loading of assets.
 /// - [`game_state_serialization_plugin`] handles saving and loading of game states.
 /// - [`level_serialization_plugin`] handles saving and loading of levels.
-/// - [`internal_audio_plugin`]: Handles audio initialization
+/// - [`internal_audio_plugin`]: Handles audio initialization, spatial sounds and sounds requested by gameplay systems
+/// - [`bug_report_plugin`] handles exporting bug reports.
+/// - [`player_profile_plugin`] handles progress that is shared between save states.
+/// - [`localization_plugin`] handles translations of texts into the chosen language.
+/// - [`scene_interchange_plugin`] handles converting levels to and from Bevy scenes.
 pub fn file_system_interaction_plugin(app: &mut App) {
     app.fn_plugin(loading_plugin)
         .fn_plugin(game_state_serialization_plugin)
         .fn_plugin(level_serialization_plugin)
-        .fn_plugin(internal_audio_plugin);
+        .fn_plugin(internal_audio_plugin)
+        .fn_plugin(bug_report_plugin)
+        .fn_plugin(player_profile_plugin)
+        .fn_plugin(localization_plugin)
+        .fn_plugin(scene_interchange_plugin);
 }
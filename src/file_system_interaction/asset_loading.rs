@@ -1,6 +1,17 @@
-use crate::file_system_interaction::config::GameConfig;
-use crate::file_system_interaction::level_serialization::SerializedLevel;
+use crate::file_system_interaction::config::{CameraConfig, GameConfig};
+use crate::file_system_interaction::level_serialization::{LevelLoader, SerializedLevel};
+use crate::file_system_interaction::localization::Translations;
+use crate::level_instantiation::spawning::data_spawner::DataSpawner;
+use crate::level_instantiation::spawning::spawn_queue::SpawnBudget;
+use crate::movement::general_movement::CharacterControllerConfig;
+use crate::movement::surface::SurfaceDefinition;
+use crate::world_interaction::consumable::Consumable;
+use crate::world_interaction::crafting::Recipe;
+use crate::world_interaction::cutscene::CameraRail;
 use crate::world_interaction::dialog::Dialog;
+use crate::world_interaction::quest::Quest;
+use crate::world_interaction::tutorial::Tutorials;
+use crate::world_interaction::world_event::WorldEvent;
 use crate::GameState;
 use anyhow::{Context, Result};
 use bevy::prelude::*;
@@ -15,8 +26,20 @@ use bevy_mod_sysfail::macros::*;
 use iyes_progress::{ProgressCounter, ProgressPlugin};
 
 pub fn loading_plugin(app: &mut App) {
-    app.add_plugin(RonAssetPlugin::<SerializedLevel>::new(&["lvl.ron"]))
+    app.register_type::<GameConfig>()
+        .register_type::<CameraConfig>()
+        .add_asset::<SerializedLevel>()
+        .add_asset_loader(LevelLoader)
         .add_plugin(RonAssetPlugin::<Dialog>::new(&["dlg.ron"]))
+        .add_plugin(RonAssetPlugin::<Translations>::new(&["lang.ron"]))
+        .add_plugin(RonAssetPlugin::<Quest>::new(&["quest.ron"]))
+        .add_plugin(RonAssetPlugin::<Tutorials>::new(&["tut.ron"]))
+        .add_plugin(RonAssetPlugin::<DataSpawner>::new(&["spawner.ron"]))
+        .add_plugin(RonAssetPlugin::<SurfaceDefinition>::new(&["surface.ron"]))
+        .add_plugin(RonAssetPlugin::<CameraRail>::new(&["rail.ron"]))
+        .add_plugin(RonAssetPlugin::<Recipe>::new(&["recipe.ron"]))
+        .add_plugin(RonAssetPlugin::<Consumable>::new(&["consumable.ron"]))
+        .add_plugin(RonAssetPlugin::<WorldEvent>::new(&["event.ron"]))
         .add_plugin(TomlAssetPlugin::<GameConfig>::new(&["game.toml"]))
         .add_plugin(ProgressPlugin::new(GameState::Loading).continue_to(GameState::Menu))
         .add_loading_state(LoadingState::new(GameState::Loading).continue_to_state(GameState::Menu))
@@ -25,8 +48,17 @@ pub fn loading_plugin(app: &mut App) {
         .add_collection_to_loading_state::<_, AnimationAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, LevelAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, DialogAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, LocalizationAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, QuestAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, TutorialAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, TextureAssets>(GameState::Loading)
         .add_collection_to_loading_state::<_, ConfigAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, SpawnerAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, SurfaceAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, CutsceneAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, RecipeAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, ConsumableAssets>(GameState::Loading)
+        .add_collection_to_loading_state::<_, WorldEventAssets>(GameState::Loading)
         .add_system(show_progress.in_set(OnUpdate(GameState::Loading)))
         .add_system(update_config);
 }
@@ -38,6 +70,18 @@ pub fn loading_plugin(app: &mut App) {
 pub struct AudioAssets {
     #[asset(path = "audio/walking.ogg")]
     pub walking: Handle<AudioSource>,
+    #[asset(path = "audio/flying.ogg")]
+    pub flying: Handle<AudioSource>,
+    #[asset(path = "audio/jump.wav")]
+    pub jump: Handle<AudioSource>,
+    #[asset(path = "audio/teleport.wav")]
+    pub teleport: Handle<AudioSource>,
+    #[asset(path = "audio/item_pickup.wav")]
+    pub item_pickup: Handle<AudioSource>,
+    #[asset(path = "audio/dialog_advance.wav")]
+    pub dialog_advance: Handle<AudioSource>,
+    #[asset(path = "audio/orb_hum.wav")]
+    pub orb_hum: Handle<AudioSource>,
 }
 
 #[derive(AssetCollection, Resource, Clone)]
@@ -78,6 +122,134 @@ pub struct DialogAssets {
     pub dialogs: HashMap<String, Handle<Dialog>>,
 }
 
+#[derive(AssetCollection, Resource, Clone)]
+pub struct LocalizationAssets {
+    #[cfg_attr(
+        feature = "native",
+        asset(path = "localization", collection(typed, mapped))
+    )]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(
+            paths("localization/en.lang.ron", "localization/de.lang.ron"),
+            collection(typed, mapped)
+        )
+    )]
+    pub translations: HashMap<String, Handle<Translations>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct QuestAssets {
+    #[cfg_attr(feature = "native", asset(path = "quests", collection(typed, mapped)))]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(paths("quests/fox_rest.quest.ron"), collection(typed, mapped))
+    )]
+    pub quests: HashMap<String, Handle<Quest>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct SpawnerAssets {
+    #[cfg_attr(
+        feature = "native",
+        asset(path = "spawners", collection(typed, mapped))
+    )]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(paths("spawners/lamp.spawner.ron"), collection(typed, mapped))
+    )]
+    pub spawners: HashMap<String, Handle<DataSpawner>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct SurfaceAssets {
+    #[cfg_attr(
+        feature = "native",
+        asset(path = "surfaces", collection(typed, mapped))
+    )]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(
+            paths(
+                "surfaces/ice.surface.ron",
+                "surfaces/mud.surface.ron",
+                "surfaces/rubber.surface.ron"
+            ),
+            collection(typed, mapped)
+        )
+    )]
+    pub surfaces: HashMap<String, Handle<SurfaceDefinition>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct CutsceneAssets {
+    #[cfg_attr(
+        feature = "native",
+        asset(path = "cutscenes", collection(typed, mapped))
+    )]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(paths("cutscenes/intro.rail.ron"), collection(typed, mapped))
+    )]
+    pub rails: HashMap<String, Handle<CameraRail>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct RecipeAssets {
+    #[cfg_attr(feature = "native", asset(path = "recipes", collection(typed, mapped)))]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(paths("recipes/key.recipe.ron"), collection(typed, mapped))
+    )]
+    pub recipes: HashMap<String, Handle<Recipe>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct ConsumableAssets {
+    #[cfg_attr(
+        feature = "native",
+        asset(path = "consumables", collection(typed, mapped))
+    )]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(
+            paths(
+                "consumables/speed_berry.consumable.ron",
+                "consumables/jump_mushroom.consumable.ron",
+                "consumables/healing_herb.consumable.ron"
+            ),
+            collection(typed, mapped)
+        )
+    )]
+    pub consumables: HashMap<String, Handle<Consumable>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct WorldEventAssets {
+    #[cfg_attr(
+        feature = "native",
+        asset(path = "world_events", collection(typed, mapped))
+    )]
+    #[cfg_attr(
+        feature = "wasm",
+        asset(
+            paths(
+                "world_events/merchant_arrives.event.ron",
+                "world_events/storm.event.ron",
+                "world_events/clear_skies.event.ron"
+            ),
+            collection(typed, mapped)
+        )
+    )]
+    pub events: HashMap<String, Handle<WorldEvent>>,
+}
+
+#[derive(AssetCollection, Resource, Clone)]
+pub struct TutorialAssets {
+    #[asset(path = "tutorials/tutorials.tut.ron")]
+    pub tutorials: Handle<Tutorials>,
+}
+
 #[derive(AssetCollection, Resource, Clone)]
 pub struct TextureAssets {
     #[asset(path = "textures/stone_alley_2.jpg")]
@@ -101,8 +273,20 @@ fn show_progress(
     animation_assets: Option<Res<AnimationAssets>>,
     level_assets: Option<Res<LevelAssets>>,
     dialog_assets: Option<Res<DialogAssets>>,
+    localization_assets: Option<Res<LocalizationAssets>>,
+    quest_assets: Option<Res<QuestAssets>>,
+    tutorial_assets: Option<Res<TutorialAssets>>,
     texture_assets: Option<Res<TextureAssets>>,
     config_assets: Option<Res<ConfigAssets>>,
+    spawner_assets: Option<Res<SpawnerAssets>>,
+    surface_assets: Option<Res<SurfaceAssets>>,
+    // Grouped because systems take at most 16 parameters
+    (cutscene_assets, recipe_assets, consumable_assets, world_event_assets): (
+        Option<Res<CutsceneAssets>>,
+        Option<Res<RecipeAssets>>,
+        Option<Res<ConsumableAssets>>,
+        Option<Res<WorldEventAssets>>,
+    ),
 ) {
     if let Some(progress) = progress.map(|counter| counter.progress()) {
         if progress.done > *last_done {
@@ -124,8 +308,17 @@ fn show_progress(
                     ui.checkbox(&mut animation_assets.is_some(), "Animations");
                     ui.checkbox(&mut level_assets.is_some(), "Levels");
                     ui.checkbox(&mut dialog_assets.is_some(), "Dialogs");
+                    ui.checkbox(&mut localization_assets.is_some(), "Translations");
+                    ui.checkbox(&mut quest_assets.is_some(), "Quests");
+                    ui.checkbox(&mut tutorial_assets.is_some(), "Tutorials");
                     ui.checkbox(&mut texture_assets.is_some(), "Textures");
                     ui.checkbox(&mut config_assets.is_some(), "Config");
+                    ui.checkbox(&mut spawner_assets.is_some(), "Spawners");
+                    ui.checkbox(&mut surface_assets.is_some(), "Surfaces");
+                    ui.checkbox(&mut cutscene_assets.is_some(), "Cutscenes");
+                    ui.checkbox(&mut recipe_assets.is_some(), "Recipes");
+                    ui.checkbox(&mut consumable_assets.is_some(), "Consumables");
+                    ui.checkbox(&mut world_event_assets.is_some(), "World Events");
                 });
             });
         });
@@ -147,6 +340,16 @@ fn update_config(
                 let config = config
                     .get(handle)
                     .context("Failed to get config even though it was just created")?;
+                commands.insert_resource(config.camera.clone());
+                commands.insert_resource(SpawnBudget {
+                    objects_per_frame: config.spawning.objects_per_frame,
+                });
+                commands.insert_resource(CharacterControllerConfig {
+                    ground_distance: config.characters.ground_distance,
+                    rotation_smoothing: config.characters.rotation_smoothing,
+                    model_sync_smoothing: config.characters.model_sync_smoothing,
+                    skin_width: config.characters.skin_width,
+                });
                 commands.insert_resource(config.clone());
             }
             AssetEvent::Removed { .. } => {}
@@ -1,25 +1,482 @@
 use crate::file_system_interaction::asset_loading::AudioAssets;
-use crate::GameState;
+use crate::game_settings::GameSettings;
 use bevy::prelude::*;
-use bevy_kira_audio::prelude::{Audio, *};
+use bevy::utils::HashMap;
+use bevy_kira_audio::prelude::*;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
-/// Handles initialization of all sounds.
+/// Distance in m at which spatial sounds become inaudible.
+const MAX_SPATIAL_DISTANCE: f32 = 25.;
+/// Time in seconds over which a [`Sound::excerpt`] fades out at its end.
+const EXCERPT_FADE_TIME: f32 = 0.05;
+
+/// Handles initialization of all sounds and playing the [`Sound`]s requested by gameplay systems.
+/// Sounds are played on the [`MusicAudio`], [`EffectAudio`], [`UiAudio`] and [`DialogAudio`] channels, whose volumes follow the
+/// [`GameSettings`](crate::game_settings::GameSettings).
+/// Gameplay systems send a [`PlaySoundEvent`] instead of holding asset handles. If it names an emitter, the sound
+/// fades and pans with the distance and direction of that entity to the ingame camera, which holds the [`AudioReceiver`].
+/// Entities with a [`SoundEmitter`] play a looped sound for as long as they exist.
+/// The volume of each [`Sound`] is mixed from the volume of its channel, its own volume and its distance to the receiver,
+/// which is why `bevy_kira_audio`'s own spatial audio, which overwrites the volume, is not used.
 pub fn internal_audio_plugin(app: &mut App) {
-    app.add_plugin(AudioPlugin)
-        .add_system(init_audio.in_schedule(OnExit(GameState::Loading)));
+    app.register_type::<SoundEmitter>()
+        .register_type::<Sound>()
+        .add_plugin(AudioPlugin)
+        .add_audio_channel::<DialogAudio>()
+        .add_audio_channel::<EffectAudio>()
+        .add_audio_channel::<MusicAudio>()
+        .add_audio_channel::<UiAudio>()
+        .init_resource::<LoopingSounds>()
+        .init_resource::<PlayingSounds>()
+        .init_resource::<PlayingExcerpts>()
+        .add_event::<PlaySoundEvent>()
+        .add_systems(
+            (
+                start_sound_emitters,
+                stop_removed_sound_emitters,
+                play_sounds,
+                end_excerpts,
+                mix_sounds,
+            )
+                .chain()
+                .distributive_run_if(resource_exists::<AudioAssets>()),
+        );
+}
+
+/// Audio channel for voice-overs played during dialogs.
+#[derive(Debug, Clone, Copy, Resource, Default)]
+pub struct DialogAudio;
+
+/// Audio channel for one-shot sound effects.
+#[derive(Debug, Clone, Copy, Resource, Default)]
+pub struct EffectAudio;
+
+/// Audio channel for background music.
+#[derive(Debug, Clone, Copy, Resource, Default)]
+pub struct MusicAudio;
+
+/// Audio channel for feedback of menus and dialog windows.
+#[derive(Debug, Clone, Copy, Resource, Default)]
+pub struct UiAudio;
+
+/// A sound the game can play, see [`PlaySoundEvent`].
+/// Steps and landings share a recording that they are cut out of, see [`Sound::excerpt`], and differ by their playback rate.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum Sound {
+    Jump,
+    Launch,
+    Teleport,
+    ItemPickup,
+    DialogAdvance,
+    /// Looped by [`SoundEmitter`]s on orbs
+    OrbHum,
+    Footstep(FootstepSet),
+    Landing(FootstepSet),
+}
+
+/// The sounds steps make on a kind of ground, named by the footsteps of a
+/// [`SurfaceDefinition`](crate::movement::surface::SurfaceDefinition).
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum FootstepSet {
+    #[default]
+    Default,
+    Ice,
+    Mud,
+    Rubber,
+}
+
+impl FootstepSet {
+    /// Set of the surface's footsteps, falling back to [`FootstepSet::Default`] for surfaces without or with unknown ones.
+    pub fn from_surface(footsteps: Option<&str>) -> Self {
+        match footsteps {
+            Some("ice") => FootstepSet::Ice,
+            Some("mud") => FootstepSet::Mud,
+            Some("rubber") => FootstepSet::Rubber,
+            _ => FootstepSet::Default,
+        }
+    }
+
+    fn volume(self) -> f64 {
+        match self {
+            FootstepSet::Default => 0.5,
+            FootstepSet::Ice => 0.4,
+            FootstepSet::Mud => 0.6,
+            FootstepSet::Rubber => 0.45,
+        }
+    }
+
+    fn playback_rate(self) -> f64 {
+        match self {
+            FootstepSet::Default => 1.,
+            FootstepSet::Ice => 1.4,
+            FootstepSet::Mud => 0.7,
+            FootstepSet::Rubber => 1.2,
+        }
+    }
+}
+
+/// The channel a [`Sound`] is played on.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SoundChannel {
+    Effect,
+    Ui,
+}
+
+impl SoundChannel {
+    /// Volume of the channel as set in the [`GameSettings`].
+    fn volume(self, settings: &GameSettings) -> f64 {
+        let master = settings.master_volume as f64;
+        match self {
+            SoundChannel::Effect => master * settings.sfx_volume as f64,
+            SoundChannel::Ui => master * settings.ui_volume as f64,
+        }
+    }
+}
+
+impl Sound {
+    pub fn channel(self) -> SoundChannel {
+        match self {
+            Sound::DialogAdvance => SoundChannel::Ui,
+            Sound::Jump
+            | Sound::Launch
+            | Sound::Teleport
+            | Sound::ItemPickup
+            | Sound::OrbHum
+            | Sound::Footstep(_)
+            | Sound::Landing(_) => SoundChannel::Effect,
+        }
+    }
+
+    fn source(self, audio_assets: &AudioAssets) -> Handle<AudioSource> {
+        match self {
+            Sound::Jump => audio_assets.jump.clone(),
+            Sound::Launch => audio_assets.flying.clone(),
+            Sound::Teleport => audio_assets.teleport.clone(),
+            Sound::ItemPickup => audio_assets.item_pickup.clone(),
+            Sound::DialogAdvance => audio_assets.dialog_advance.clone(),
+            Sound::OrbHum => audio_assets.orb_hum.clone(),
+            Sound::Footstep(_) | Sound::Landing(_) => audio_assets.walking.clone(),
+        }
+    }
+
+    /// Length in seconds of the piece of the clip that is played, starting at a random point of it.
+    /// Lets short sounds be cut out of a longer recording, so that consecutive ones don't sound the same.
+    fn excerpt(self) -> Option<f64> {
+        match self {
+            Sound::Footstep(_) => Some(0.25),
+            Sound::Landing(_) => Some(0.4),
+            _ => None,
+        }
+    }
+
+    fn volume(self) -> f64 {
+        match self {
+            Sound::Launch | Sound::Teleport => 1.,
+            Sound::Jump | Sound::ItemPickup => 0.6,
+            Sound::DialogAdvance => 0.4,
+            Sound::OrbHum => 0.3,
+            Sound::Footstep(set) => set.volume(),
+            Sound::Landing(set) => (set.volume() * 1.5).min(1.),
+        }
+    }
+
+    fn playback_rate(self) -> f64 {
+        match self {
+            Sound::Jump
+            | Sound::Launch
+            | Sound::Teleport
+            | Sound::ItemPickup
+            | Sound::DialogAdvance
+            | Sound::OrbHum => 1.,
+            Sound::Footstep(set) => set.playback_rate(),
+            // Heavier than a step
+            Sound::Landing(set) => set.playback_rate() * 0.8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PlaySoundEvent {
+    pub sound: Sound,
+    /// Entity the sound comes from. Without one, the sound is heard at full volume everywhere.
+    pub emitter: Option<Entity>,
+}
+
+impl PlaySoundEvent {
+    pub fn new(sound: Sound) -> Self {
+        Self {
+            sound,
+            emitter: None,
+        }
+    }
+
+    pub fn at(sound: Sound, emitter: Entity) -> Self {
+        Self {
+            sound,
+            emitter: Some(emitter),
+        }
+    }
+}
+
+/// Plays its sound in a loop for as long as the entity exists.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct SoundEmitter {
+    pub sound: Sound,
+}
+
+impl Default for SoundEmitter {
+    fn default() -> Self {
+        Self {
+            sound: Sound::OrbHum,
+        }
+    }
+}
+
+/// The looped instances of all [`SoundEmitter`]s, so they can be stopped once their entity is gone.
+#[derive(Debug, Clone, Resource, Default)]
+struct LoopingSounds(HashMap<Entity, Handle<AudioInstance>>);
+
+/// Instances playing a [`Sound::excerpt`] with the time in seconds until they are stopped.
+#[derive(Debug, Clone, Resource, Default)]
+struct PlayingExcerpts(Vec<(Handle<AudioInstance>, f32)>);
+
+/// All sounds that are still playing, so that [`mix_sounds`] can keep their volume and panning up to date.
+#[derive(Debug, Clone, Resource, Default)]
+struct PlayingSounds(Vec<PlayingSound>);
+
+#[derive(Debug, Clone)]
+struct PlayingSound {
+    instance: Handle<AudioInstance>,
+    sound: Sound,
+    emitter: Option<Entity>,
+}
+
+/// Volume and panning of a sound. Sounds with an emitter fade out over [`MAX_SPATIAL_DISTANCE`]
+/// and are panned towards the side of the receiver they are on.
+fn mix_sound(
+    sound: Sound,
+    emitter: Option<&GlobalTransform>,
+    receiver: Option<&GlobalTransform>,
+    settings: &GameSettings,
+) -> (f64, f64) {
+    let (attenuation, panning) = match (emitter, receiver) {
+        (Some(emitter), Some(receiver)) => {
+            let path = emitter.translation() - receiver.translation();
+            let attenuation = (1. - path.length() / MAX_SPATIAL_DISTANCE)
+                .clamp(0., 1.)
+                .powi(2);
+            let panning = if path.length_squared() > 0. {
+                (receiver.right().angle_between(path).cos() + 1.) / 2.
+            } else {
+                0.5
+            };
+            (attenuation, panning)
+        }
+        _ => (1., 0.5),
+    };
+    let volume = sound.channel().volume(settings) * sound.volume() * attenuation as f64;
+    (volume, panning as f64)
+}
+
+fn play_sound(
+    sound: Sound,
+    looped: bool,
+    start_from: f64,
+    mix: (f64, f64),
+    audio_assets: &AudioAssets,
+    effect_audio: &AudioChannel<EffectAudio>,
+    ui_audio: &AudioChannel<UiAudio>,
+) -> Handle<AudioInstance> {
+    match sound.channel() {
+        SoundChannel::Effect => {
+            play_on_channel(effect_audio, sound, looped, start_from, mix, audio_assets)
+        }
+        SoundChannel::Ui => play_on_channel(ui_audio, sound, looped, start_from, mix, audio_assets),
+    }
+}
+
+fn play_on_channel(
+    channel: &impl AudioControl,
+    sound: Sound,
+    looped: bool,
+    start_from: f64,
+    (volume, panning): (f64, f64),
+    audio_assets: &AudioAssets,
+) -> Handle<AudioInstance> {
+    let mut command = channel.play(sound.source(audio_assets));
+    if looped {
+        command.looped();
+    }
+    command
+        .start_from(start_from)
+        .with_volume(volume)
+        .with_panning(panning)
+        .with_playback_rate(sound.playback_rate())
+        .handle()
+}
+
+fn start_sound_emitters(
+    emitters: Query<(Entity, &SoundEmitter), Added<SoundEmitter>>,
+    mut looping_sounds: ResMut<LoopingSounds>,
+    mut playing_sounds: ResMut<PlayingSounds>,
+    audio_assets: Res<AudioAssets>,
+    effect_audio: Res<AudioChannel<EffectAudio>>,
+    ui_audio: Res<AudioChannel<UiAudio>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_sound_emitters").entered();
+    for (entity, emitter) in emitters.iter() {
+        // New emitters have not been moved into place yet, so they start silent until they are mixed
+        let instance = play_sound(
+            emitter.sound,
+            true,
+            0.,
+            (0., 0.5),
+            &audio_assets,
+            &effect_audio,
+            &ui_audio,
+        );
+        playing_sounds.0.push(PlayingSound {
+            instance: instance.clone(),
+            sound: emitter.sound,
+            emitter: Some(entity),
+        });
+        looping_sounds.0.insert(entity, instance);
+    }
+}
+
+fn stop_removed_sound_emitters(
+    mut removed: RemovedComponents<SoundEmitter>,
+    mut looping_sounds: ResMut<LoopingSounds>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("stop_removed_sound_emitters").entered();
+    for entity in removed.iter() {
+        let Some(instance) = looping_sounds.0.remove(&entity) else {
+            continue;
+        };
+        if let Some(instance) = audio_instances.get_mut(&instance) {
+            instance.stop(default());
+        }
+    }
+}
+
+fn play_sounds(
+    mut sound_events: EventReader<PlaySoundEvent>,
+    transforms: Query<&GlobalTransform>,
+    receivers: Query<&GlobalTransform, With<AudioReceiver>>,
+    settings: Res<GameSettings>,
+    mut playing_sounds: ResMut<PlayingSounds>,
+    mut excerpts: ResMut<PlayingExcerpts>,
+    audio_assets: Res<AudioAssets>,
+    audio_sources: Res<Assets<AudioSource>>,
+    effect_audio: Res<AudioChannel<EffectAudio>>,
+    ui_audio: Res<AudioChannel<UiAudio>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_sounds").entered();
+    let mut rng = SmallRng::from_entropy();
+    let receiver = receivers.iter().next();
+    for event in sound_events.iter() {
+        let excerpt = event.sound.excerpt();
+        let start_from = excerpt.map_or(0., |length| {
+            let duration = audio_sources
+                .get(&event.sound.source(&audio_assets))
+                .map_or(0., |source| source.sound.duration().as_secs_f64());
+            rng.gen_range(0.0..=(duration - length).max(0.))
+        });
+        // Sounds of emitters that no longer exist are heard everywhere
+        let emitter = event
+            .emitter
+            .and_then(|emitter| Some((emitter, transforms.get(emitter).ok()?)));
+        let instance = play_sound(
+            event.sound,
+            false,
+            start_from,
+            mix_sound(
+                event.sound,
+                emitter.map(|(_, transform)| transform),
+                receiver,
+                &settings,
+            ),
+            &audio_assets,
+            &effect_audio,
+            &ui_audio,
+        );
+        if let Some(length) = excerpt {
+            let remaining = length / event.sound.playback_rate();
+            excerpts.0.push((instance.clone(), remaining as f32));
+        }
+        playing_sounds.0.push(PlayingSound {
+            instance,
+            sound: event.sound,
+            emitter: emitter.map(|(emitter, _)| emitter),
+        });
+    }
 }
 
-#[derive(Debug, Clone, Resource)]
-pub struct AudioHandles {
-    pub walking: Handle<AudioInstance>,
+/// Stops the instances playing a [`Sound::excerpt`] once the excerpt is over.
+fn end_excerpts(
+    time: Res<Time>,
+    mut excerpts: ResMut<PlayingExcerpts>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("end_excerpts").entered();
+    let dt = time.delta_seconds();
+    excerpts.0.retain_mut(|(instance, remaining)| {
+        *remaining -= dt;
+        if *remaining > 0. {
+            return true;
+        }
+        if let Some(instance) = audio_instances.get_mut(instance) {
+            instance.stop(AudioTween::linear(Duration::from_secs_f32(
+                EXCERPT_FADE_TIME,
+            )));
+        }
+        false
+    });
 }
 
-fn init_audio(mut commands: Commands, audio_assets: Res<AudioAssets>, audio: Res<Audio>) {
-    audio.pause();
-    let handle = audio
-        .play(audio_assets.walking.clone())
-        .looped()
-        .with_volume(0.8)
-        .handle();
-    commands.insert_resource(AudioHandles { walking: handle });
+/// Keeps the volume and panning of the playing sounds up to date with the settings and the positions of their emitters.
+/// Drops the handles of sounds that are done playing, so that their instances can be freed.
+fn mix_sounds(
+    settings: Res<GameSettings>,
+    mut playing_sounds: ResMut<PlayingSounds>,
+    transforms: Query<&GlobalTransform>,
+    receivers: Query<&GlobalTransform, With<AudioReceiver>>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("mix_sounds").entered();
+    let receiver = receivers.iter().next();
+    playing_sounds.0.retain(|playing| {
+        // The instance only exists once the audio plugin has started the sound
+        let Some(instance) = audio_instances.get_mut(&playing.instance) else {
+            return true;
+        };
+        if matches!(instance.state(), PlaybackState::Stopped) {
+            return false;
+        }
+        let emitter = match playing.emitter {
+            Some(emitter) => match transforms.get(emitter) {
+                Ok(transform) => Some(transform),
+                // The rest of the sound stays as it was last heard
+                Err(_) => return true,
+            },
+            None => None,
+        };
+        let (volume, panning) = mix_sound(playing.sound, emitter, receiver, &settings);
+        instance.set_volume(volume, default());
+        instance.set_panning(panning, default());
+        true
+    });
 }
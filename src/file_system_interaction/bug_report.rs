@@ -0,0 +1,176 @@
+use crate::console::{AddConsoleCommandExt, ConsoleHistory, ConsoleLine, PermissionLevel};
+use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::game_state_serialization::{serialize_save, SaveModel};
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+#[cfg(all(feature = "native", not(feature = "tracing")))]
+use crate::file_system_interaction::log_history::LogHistory;
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::GameObject;
+use crate::level_instantiation::terrain::TerrainDeformations;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::building::{Built, BuiltObjects};
+use crate::world_interaction::condition::ActiveConditions;
+use crate::world_interaction::dialog::{CurrentDialog, DialogContext};
+use crate::world_interaction::level_stats::LevelStats;
+use crate::world_interaction::npc_memory::{NpcMemories, NpcMemory};
+use crate::world_interaction::quest::QuestLog;
+use crate::world_interaction::weather::Weather;
+use crate::world_interaction::world_event::{WorldClock, WorldEventObject, WorldEventObjects};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use chrono::prelude::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Exports the current game state into a single zip file in `bug_reports/` so that playtesters
+/// can attach reproducible state to issues. The report contains the current save, the console log,
+/// the recent log output, the loaded [`GameConfig`] and some general information about the build.
+/// Reports are requested via [`BugReportRequest`] or the `bug_report` console command.
+pub fn bug_report_plugin(app: &mut App) {
+    app.add_event::<BugReportRequest>()
+        .add_system(
+            handle_bug_report_requests
+                .run_if(resource_exists::<CurrentLevel>())
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command_with_permission(
+            "bug_report",
+            "Exports the current game state to bug_reports/. All arguments are used as the description",
+            PermissionLevel::Player,
+            request_bug_report,
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Serialize, Deserialize, Default)]
+pub struct BugReportRequest {
+    pub description: String,
+}
+
+fn request_bug_report(world: &mut World, args: &[&str]) -> Result<String> {
+    world.send_event(BugReportRequest {
+        description: args.join(" "),
+    });
+    Ok(String::new())
+}
+
+#[sysfail(log(level = "error"))]
+fn handle_bug_report_requests(
+    mut requests: EventReader<BugReportRequest>,
+    conditions: Res<ActiveConditions>,
+    dialog_context: Res<DialogContext>,
+    quest_log: Res<QuestLog>,
+    dialog: Option<Res<CurrentDialog>>,
+    level_stats: Res<LevelStats>,
+    terrain_deformations: Res<TerrainDeformations>,
+    npcs: Query<(Option<&Name>, Option<&ObjectMetadata>, &NpcMemory)>,
+    built: Query<(&GameObject, &Transform), With<Built>>,
+    // Grouped because systems take at most 16 parameters
+    (world_clock, weather): (Res<WorldClock>, Res<Weather>),
+    world_event_objects: Query<(
+        &Transform,
+        &WorldEventObject,
+        Option<&GameObject>,
+        Option<&CustomObject>,
+    )>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    current_level: Res<CurrentLevel>,
+    config: Res<GameConfig>,
+    mut console_history: ResMut<ConsoleHistory>,
+    #[cfg(all(feature = "native", not(feature = "tracing")))] log_history: Res<LogHistory>,
+) -> Result<()> {
+    for request in requests.iter() {
+        let timestamp = Local::now().to_rfc2822();
+        let mut files = vec![
+            (
+                "info.txt",
+                format!(
+                    "Version: {}\nTimestamp: {timestamp}\nLevel: {}\nDescription: {}\n",
+                    env!("CARGO_PKG_VERSION"),
+                    current_level.scene,
+                    request.description
+                ),
+            ),
+            (
+                "config.ron",
+                ron::ser::to_string_pretty(&*config, default())
+                    .context("Failed to serialize game config")?,
+            ),
+            ("console.log", format_console_history(&console_history)),
+        ];
+        #[cfg(all(feature = "native", not(feature = "tracing")))]
+        files.push(("game.log", log_history.lines().join("\n")));
+        if let Some(player) = player_query.iter().next() {
+            let save_model = SaveModel::new(
+                &current_level,
+                &conditions,
+                &dialog_context,
+                &quest_log,
+                dialog.as_deref(),
+                &level_stats,
+                NpcMemories::collect(&npcs),
+                &terrain_deformations,
+                BuiltObjects::collect(&built),
+                &world_clock,
+                &weather,
+                WorldEventObjects::collect(&world_event_objects),
+                player.compute_transform(),
+            );
+            files.push(("save.sav.ron", serialize_save(&save_model)?));
+        }
+
+        let path = get_bug_report_path(timestamp.replace(':', "-"));
+        write_zip(&path, &files)?;
+        let message = format!(
+            "Successfully exported bug report at {}",
+            path.to_string_lossy()
+        );
+        info!("{message}");
+        console_history.push(ConsoleLine::Output(message));
+    }
+    Ok(())
+}
+
+fn format_console_history(console_history: &ConsoleHistory) -> String {
+    console_history
+        .lines
+        .iter()
+        .map(|line| match line {
+            ConsoleLine::Input(input) => format!("> {input}"),
+            ConsoleLine::Output(output) => output.clone(),
+            ConsoleLine::Error(error) => format!("error: {error}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn write_zip(path: &Path, files: &[(&str, String)]) -> Result<()> {
+    let dir = path
+        .parent()
+        .context("Failed to get bug report directory")?;
+    fs::create_dir_all(dir).context("Failed to create bug report directory")?;
+    let file = File::create(path).context("Failed to create bug report file")?;
+    let mut zip = ZipWriter::new(file);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(*name, options)
+            .with_context(|| format!("Failed to add {name} to bug report"))?;
+        zip.write_all(contents.as_bytes())
+            .with_context(|| format!("Failed to write {name} to bug report"))?;
+    }
+    zip.finish()
+        .context("Failed to finish writing bug report")?;
+    Ok(())
+}
+
+fn get_bug_report_path(filename: String) -> PathBuf {
+    Path::new("bug_reports")
+        .join(filename)
+        .with_extension("zip")
+}
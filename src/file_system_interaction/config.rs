@@ -1,3 +1,4 @@
+use crate::level_instantiation::spawning::GameObject;
 use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
 use serde::{Deserialize, Serialize};
@@ -17,18 +18,33 @@ use serde::{Deserialize, Serialize};
 #[reflect(Serialize, Deserialize, Resource)]
 #[uuid = "93a7c64b-4d6e-4420-b8c1-dfca481d9387"]
 pub struct GameConfig {
-    pub camera: Camera,
+    pub camera: CameraConfig,
     pub characters: Characters,
+    pub navigation: Navigation,
     pub player: Player,
+    pub health: Health,
     pub dialog: Dialog,
+    pub collectibles: Collectibles,
+    pub autosave: Autosave,
+    pub ambient_population: AmbientPopulation,
+    pub building: Building,
+    pub level_validation: LevelValidation,
+    pub spawning: Spawning,
+    /// Only used with the `dev` feature
+    pub editor: Editor,
 }
 
-#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
-#[reflect(Serialize, Deserialize)]
-pub struct Camera {
+/// Copied into its own resource whenever the [`GameConfig`] is loaded, so the camera can be tuned in the editor while playing.
+/// Reloading the config file overwrites such changes.
+#[derive(
+    Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default, Resource,
+)]
+#[reflect(Serialize, Deserialize, Resource)]
+pub struct CameraConfig {
     pub fixed_angle: FixedAngle,
     pub first_person: FirstPerson,
     pub third_person: ThirdPerson,
+    pub side_view: SideView,
     pub mouse_sensitivity_x: f32,
     pub mouse_sensitivity_y: f32,
 }
@@ -72,11 +88,42 @@ pub struct ThirdPerson {
     pub zoom_out_smoothing: f32,
 }
 
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct SideView {
+    /// Distance in m between the camera and the plane of its target
+    pub distance: f32,
+    /// Width in m of the area around the focus in which the target can move without the camera following
+    pub dead_zone_width: f32,
+    /// Height in m of the dead zone
+    pub dead_zone_height: f32,
+    pub translation_smoothing: f32,
+    pub rotation_smoothing: f32,
+    pub zoom_smoothing: f32,
+}
+
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
 #[reflect(Serialize, Deserialize)]
 pub struct Characters {
     pub model_sync_smoothing: f32,
     pub rotation_smoothing: f32,
+    /// Distance in m that fast characters keep to the first obstacle in their way, see [`prevent_tunneling`](crate::movement::general_movement::prevent_tunneling)
+    pub skin_width: f32,
+    /// Depth in m up to which characters may overlap other colliders before being pushed out, see [`depenetration_plugin`](crate::movement::depenetration::depenetration_plugin)
+    pub penetration_tolerance: f32,
+    /// Combined depth in m of colliders pressing into a character from opposite sides at which it counts as crushed
+    pub crush_tolerance: f32,
+    /// Distance in m between a character's collider and the ground up to which it counts as grounded
+    pub ground_distance: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Navigation {
+    /// Navigating characters steer away from other characters closer than this in m
+    pub avoidance_radius: f32,
+    /// How strongly steering away from other characters counts against walking towards the destination
+    pub avoidance_weight: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
@@ -87,6 +134,60 @@ pub struct Player {
     pub fov_saturation_speed: f32,
     pub min_fov: f32,
     pub max_fov: f32,
+    /// Only read when the player is spawned, so changes apply to the next spawned player.
+    pub body: Body,
+    /// Applied to the player whenever the config changes, so it can be tuned while playing.
+    pub movement: Movement,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Body {
+    /// Height of the cylindrical part of the capsule collider
+    pub height: f32,
+    pub radius: f32,
+    pub mass: f32,
+    pub model_scale: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Movement {
+    pub ground_acceleration: f32,
+    pub sprinting_acceleration: f32,
+    pub aerial_acceleration: f32,
+    pub jump_speed: f32,
+    /// See [`Jumping::coyote_time`](crate::movement::general_movement::Jumping::coyote_time)
+    pub coyote_time: f32,
+    /// See [`Jumping::buffer_time`](crate::movement::general_movement::Jumping::buffer_time)
+    pub jump_buffer_time: f32,
+    /// See [`Jumping::cut_factor`](crate::movement::general_movement::Jumping::cut_factor)
+    pub jump_cut_factor: f32,
+    /// See [`WallJumping::max_slide_speed`](crate::movement::wall_jump::WallJumping::max_slide_speed)
+    pub wall_slide_speed: f32,
+    /// See [`WallJumping::push_speed`](crate::movement::wall_jump::WallJumping::push_speed)
+    pub wall_jump_push_speed: f32,
+    /// See [`Dash::speed`](crate::movement::dash::Dash::speed)
+    pub dash_speed: f32,
+    /// Duration of a dash in s
+    pub dash_duration: f32,
+    /// Time in s after a dash before the next one is possible
+    pub dash_cooldown: f32,
+    /// Time in s it takes to pull up onto a ledge
+    pub mantle_duration: f32,
+    /// Whether the player stands on the ground below them instead of against gravity, see [`AlignToSurface`](crate::movement::general_movement::AlignToSurface)
+    pub align_to_surface: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Health {
+    /// Health the player spawns and respawns with
+    pub player_max_health: f32,
+    /// Time in s a character cannot be hurt again after taking damage or respawning
+    pub invulnerability_duration: f32,
+    /// The player dies when falling below this height in m
+    pub kill_plane_height: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
@@ -94,3 +195,90 @@ pub struct Player {
 pub struct Dialog {
     pub base_letters_per_second: f32,
 }
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Collectibles {
+    /// Distance in m at which collectibles start being pulled towards the player
+    pub magnet_radius: f32,
+    /// Distance in m at which collectibles are collected
+    pub pickup_radius: f32,
+    /// Acceleration in m/s² towards the player
+    pub magnet_acceleration: f32,
+    pub max_speed: f32,
+    pub tween_smoothing: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Autosave {
+    /// Only has an effect in builds with the `dev` feature
+    pub enabled: bool,
+    /// Time in seconds between two autosaves
+    pub interval: f32,
+    /// Number of autosaves to keep
+    pub slots: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct AmbientPopulation {
+    /// Upper bound for the number of ambient NPCs, regardless of density
+    pub max_npcs: usize,
+    /// Distance in m from the player up to which ambient NPCs are spawned
+    pub spawn_radius: f32,
+    /// Ambient NPCs are never spawned closer than this in m to the player
+    pub min_spawn_distance: f32,
+    /// Ambient NPCs farther than this in m from the player are recycled
+    pub despawn_radius: f32,
+    /// NPCs per 100 m² outside of population zones
+    pub default_density: f32,
+    pub spawns_per_frame: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Building {
+    /// Distance in m from the player up to which objects can be placed
+    pub max_distance: f32,
+    /// The only objects the player can build, in the order they are listed in the build menu
+    pub buildables: Vec<Buildable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct LevelValidation {
+    /// Spawn points closer than this in m to each other overlap
+    pub min_spawn_point_distance: f32,
+    /// Upper bound for the number of point and spot lights in a level
+    pub max_lights: usize,
+    /// Whether errors hold back saves from the editor until they are saved anyway
+    pub block_save_on_errors: bool,
+}
+
+/// Copied into the [`SpawnBudget`](crate::level_instantiation::spawning::spawn_queue::SpawnBudget) whenever the [`GameConfig`] is loaded.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Spawning {
+    /// Number of objects taken from the spawn queue per frame while levels and prefabs are spawned
+    pub objects_per_frame: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Editor {
+    /// Distance in m from the camera up to which objects can be placed
+    pub max_placement_distance: f32,
+    /// Distance in m from the camera up to which the brush reaches surfaces
+    pub max_brush_distance: f32,
+    /// How close in pixels the cursor has to be to a spline point, volume face or waypoint to grab it
+    pub grab_distance: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct Buildable {
+    pub object: GameObject,
+    /// Coins it takes to build this
+    pub cost: u32,
+}
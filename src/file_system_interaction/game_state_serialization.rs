@@ -1,8 +1,20 @@
-use crate::file_system_interaction::level_serialization::{CurrentLevel, WorldLoadRequest};
+use crate::console::{AddConsoleCommandExt, PermissionLevel};
+use crate::file_system_interaction::level_serialization::{
+    CurrentLevel, WorldLoadProgress, WorldLoadRequest,
+};
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
 use crate::level_instantiation::spawning::GameObject;
+use crate::level_instantiation::terrain::TerrainDeformations;
 use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::building::{Built, BuiltObjects};
 use crate::world_interaction::condition::ActiveConditions;
-use crate::world_interaction::dialog::{CurrentDialog, DialogEvent};
+use crate::world_interaction::dialog::{CurrentDialog, DialogContext, DialogEvent, DialogTarget};
+use crate::world_interaction::level_stats::LevelStats;
+use crate::world_interaction::npc_memory::{NpcMemories, NpcMemory};
+use crate::world_interaction::quest::QuestLog;
+use crate::world_interaction::weather::Weather;
+use crate::world_interaction::world_event::{WorldClock, WorldEventObject, WorldEventObjects};
 use crate::GameState;
 use anyhow::{Context, Result};
 use bevy::prelude::*;
@@ -10,21 +22,50 @@ use bevy_mod_sysfail::macros::*;
 use chrono::prelude::Local;
 use glob::glob;
 use serde::{Deserialize, Serialize};
-use spew::prelude::*;
 use std::borrow::Cow;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Number of save slots offered in the pause menu.
+pub const SAVE_SLOT_COUNT: usize = 3;
+
+/// Handles save games, which are stored in `saves/` separately from the levels they were made in.
+/// A save stores the name of the level and the runtime state on top of it, i.e. the player's position,
+/// the [`ActiveConditions`], the current dialog and its [`DialogContext`], the [`QuestLog`], the [`LevelStats`], the [`NpcMemories`], the [`TerrainDeformations`],
+/// the [`BuiltObjects`], the [`WorldClock`], the [`Weather`] and the [`WorldEventObjects`].
+/// Loading a save sends a [`WorldLoadRequest`] and restores the rest of the state once the level has been spawned,
+/// after which a [`GameLoaded`] is sent.
+/// Saves are written and read via [`GameSaveRequest`] and [`GameLoadRequest`], the `save` and `load` console commands
+/// or the save slots in the pause menu. The existing saves are listed in [`SaveSlots`].
 pub fn game_state_serialization_plugin(app: &mut App) {
     app.add_event::<GameSaveRequest>()
         .add_event::<GameLoadRequest>()
+        .add_event::<GameLoaded>()
+        .init_resource::<SaveSlots>()
+        .add_startup_system(refresh_save_slots)
         .add_systems(
             (
                 handle_load_requests,
                 handle_save_requests.run_if(resource_exists::<CurrentLevel>()),
+                refresh_save_slots.run_if(on_event::<GameSaveRequest>()),
+                restore_game_state
+                    .run_if(resource_exists::<PendingGameLoad>())
+                    .run_if(resource_removed::<WorldLoadProgress>()),
             )
                 .chain()
                 .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command_with_permission(
+            "save",
+            "Saves the game, e.g. \"save slot_1\". Without arguments, the save is named after the current time",
+            PermissionLevel::Player,
+            request_save,
+        )
+        .add_console_command_with_permission(
+            "load",
+            "Loads a save, e.g. \"load slot_1\". Without arguments, loads the most recent save",
+            PermissionLevel::Player,
+            request_load,
         );
 }
 
@@ -38,14 +79,161 @@ pub struct GameLoadRequest {
     pub filename: Option<String>,
 }
 
+/// Sent once the state of a save game has been restored, e.g. for objects that depend on the restored [`DialogContext`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GameLoaded {
+    pub scene: String,
+}
+
+/// The saves found in `saves/`, sorted from newest to oldest.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct SaveSlots(pub Vec<SaveSlot>);
+
+impl SaveSlots {
+    pub fn get(&self, filename: &str) -> Option<&SaveSlot> {
+        self.0.iter().find(|slot| slot.filename == filename)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSlot {
+    pub filename: String,
+    pub scene: String,
+    pub saved_at: String,
+}
+
+/// Name of the numbered slot shown in the pause menu, starting at 1.
+pub fn slot_filename(slot: usize) -> String {
+    format!("slot_{slot}")
+}
+
 #[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize, Default)]
-struct SaveModel {
+pub struct SaveModel {
     scene: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    saved_at: String,
     #[serde(default, skip_serializing_if = "ActiveConditions::is_empty")]
     conditions: ActiveConditions,
+    #[serde(default, skip_serializing_if = "DialogContext::is_empty")]
+    dialog_context: DialogContext,
+    #[serde(default, skip_serializing_if = "QuestLog::is_empty")]
+    quest_log: QuestLog,
     player_transform: Transform,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     dialog_event: Option<DialogEvent>,
+    #[serde(default)]
+    level_stats: LevelStats,
+    #[serde(default, skip_serializing_if = "NpcMemories::is_empty")]
+    npc_memories: NpcMemories,
+    #[serde(default, skip_serializing_if = "TerrainDeformations::is_empty")]
+    terrain_deformations: TerrainDeformations,
+    #[serde(default, skip_serializing_if = "BuiltObjects::is_empty")]
+    built_objects: BuiltObjects,
+    #[serde(default)]
+    world_clock: WorldClock,
+    #[serde(default)]
+    weather: Weather,
+    #[serde(default, skip_serializing_if = "WorldEventObjects::is_empty")]
+    world_event_objects: WorldEventObjects,
+}
+
+impl SaveModel {
+    pub(crate) fn new(
+        current_level: &CurrentLevel,
+        conditions: &ActiveConditions,
+        dialog_context: &DialogContext,
+        quest_log: &QuestLog,
+        dialog: Option<&CurrentDialog>,
+        level_stats: &LevelStats,
+        npc_memories: NpcMemories,
+        terrain_deformations: &TerrainDeformations,
+        built_objects: BuiltObjects,
+        world_clock: &WorldClock,
+        weather: &Weather,
+        world_event_objects: WorldEventObjects,
+        player_transform: Transform,
+    ) -> Self {
+        let dialog_event = dialog.map(|dialog| DialogEvent {
+            dialog: dialog.id.clone(),
+            source: dialog.source,
+            page: Some(dialog.current_page.clone()),
+            resumed: dialog.entered_page.as_ref() == Some(&dialog.current_page),
+        });
+        Self {
+            scene: current_level.scene.clone(),
+            saved_at: Local::now().to_rfc2822(),
+            conditions: conditions.clone(),
+            dialog_context: dialog_context.clone(),
+            quest_log: quest_log.clone(),
+            dialog_event,
+            level_stats: level_stats.clone(),
+            npc_memories,
+            terrain_deformations: terrain_deformations.clone(),
+            built_objects,
+            world_clock: *world_clock,
+            weather: *weather,
+            world_event_objects,
+            player_transform,
+        }
+    }
+}
+
+impl SaveModel {
+    /// Name of the level the save was made in.
+    pub fn scene(&self) -> &str {
+        &self.scene
+    }
+}
+
+/// Serializes a save the way it is stored in `saves/`.
+pub fn serialize_save(save_model: &SaveModel) -> Result<String> {
+    ron::to_string(save_model).context("Failed to serialize save")
+}
+
+pub fn deserialize_save(serialized: &str) -> Result<SaveModel> {
+    ron::from_str(serialized).context("Failed to deserialize save")
+}
+
+/// A loaded save whose level is still being spawned.
+#[derive(Debug, Clone, PartialEq, Resource)]
+struct PendingGameLoad(SaveModel);
+
+fn request_save(world: &mut World, args: &[&str]) -> Result<String> {
+    let filename = args.first().map(|filename| filename.to_string());
+    world.send_event(GameSaveRequest { filename });
+    Ok(String::new())
+}
+
+fn request_load(world: &mut World, args: &[&str]) -> Result<String> {
+    let filename = args.first().map(|filename| filename.to_string());
+    world.send_event(GameLoadRequest { filename });
+    Ok(String::new())
+}
+
+#[sysfail(log(level = "error"))]
+fn refresh_save_slots(mut save_slots: ResMut<SaveSlots>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("refresh_save_slots").entered();
+    let mut slots = Vec::new();
+    for path in list_saves()?.into_iter().rev() {
+        let Some(filename) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".sav.ron"))
+        else {
+            continue;
+        };
+        match read_save(&path) {
+            Ok(save_model) => slots.push(SaveSlot {
+                filename: filename.to_owned(),
+                scene: save_model.scene,
+                saved_at: save_model.saved_at,
+            }),
+            Err(e) => warn!("Skipping unreadable save {}: {e:#}", path.to_string_lossy()),
+        }
+    }
+    save_slots.0 = slots;
+    Ok(())
 }
 
 #[sysfail(log(level = "error"))]
@@ -53,98 +241,121 @@ fn handle_load_requests(
     mut commands: Commands,
     mut load_events: EventReader<GameLoadRequest>,
     mut loader: EventWriter<WorldLoadRequest>,
-    mut spawner: EventWriter<SpawnEvent<GameObject, Transform>>,
-    mut dialog_event_writer: EventWriter<DialogEvent>,
 ) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_load_requests").entered();
     for load in load_events.iter() {
-        let path = match load
-            .filename
-            .as_ref()
-            .map(|filename| anyhow::Ok(Some(get_save_path(filename.clone()))))
-            .unwrap_or_else(|| {
-                let mut saves: Vec<_> = glob("./saves/*.sav.ron")
-                    .context("Failed to read glob pattern")?
-                    .filter_map(|entry| entry.ok())
-                    .filter(|entry| entry.is_file())
-                    .collect();
-                saves.sort_by_cached_key(|f| {
-                    f.metadata()
-                        .expect("Failed to read file metadata")
-                        .modified()
-                        .expect("Failed to read file modified time")
-                });
-                Ok(saves.last().map(|entry| entry.to_owned()))
-            })? {
-            Some(path) => path,
-            None => {
-                error!("Failed to load save: No filename provided and no saves found on disk");
-                continue;
-            }
+        let path = match &load.filename {
+            Some(filename) => get_save_path(filename.clone()),
+            None => match list_saves()?.pop() {
+                Some(path) => path,
+                None => {
+                    error!("Failed to load save: No filename provided and no saves found on disk");
+                    continue;
+                }
+            },
         };
-        let serialized = match fs::read_to_string(&path) {
-            Ok(serialized) => {
+        let save_model = match read_save(&path) {
+            Ok(save_model) => {
                 info!("Successfully read save at {}", path.to_string_lossy());
-                serialized
+                save_model
             }
             Err(e) => {
-                error!(
-                    "Failed to read save {:?} at {:?}: {}",
-                    &load.filename, path, e
-                );
-                continue;
-            }
-        };
-        let save_model: SaveModel = match ron::from_str(&serialized) {
-            Ok(save_model) => save_model,
-            Err(e) => {
-                error!(
-                    "Failed to deserialize save {:?} at {:?}: {}",
-                    &load.filename, path, e
-                );
+                error!("Failed to load save {:?}: {e:#}", &load.filename);
                 continue;
             }
         };
         loader.send(WorldLoadRequest {
-            filename: save_model.scene,
+            filename: save_model.scene.clone(),
+            player_transform: Some(save_model.player_transform),
         });
-        if let Some(dialog_event) = save_model.dialog_event {
-            dialog_event_writer.send(dialog_event);
-        }
-        commands.insert_resource(save_model.conditions);
-
-        spawner.send(
-            SpawnEvent::with_data(GameObject::Player, save_model.player_transform).delay_frames(2),
-        );
+        // Loading the level resets the conditions and the dialog, so the rest has to wait until it is spawned
+        commands.insert_resource(PendingGameLoad(save_model));
     }
     Ok(())
 }
 
+fn restore_game_state(
+    mut commands: Commands,
+    pending: Res<PendingGameLoad>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut dialog_event_writer: EventWriter<DialogEvent>,
+    npcs: Query<(Entity, Option<&Name>, Option<&ObjectMetadata>), With<DialogTarget>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("restore_game_state").entered();
+    let save_model = &pending.0;
+    // Another level may have finished loading before the one of the save
+    if current_level.map(|level| level.scene.clone()) != Some(save_model.scene.clone()) {
+        return;
+    }
+    commands.insert_resource(save_model.conditions.clone());
+    commands.insert_resource(save_model.dialog_context.clone());
+    commands.insert_resource(save_model.quest_log.clone());
+    commands.insert_resource(save_model.level_stats.clone());
+    save_model.npc_memories.restore(&mut commands, &npcs);
+    commands.insert_resource(save_model.terrain_deformations.clone());
+    save_model.built_objects.restore(&mut commands);
+    commands.insert_resource(save_model.world_clock);
+    commands.insert_resource(save_model.weather);
+    save_model.world_event_objects.restore(&mut commands);
+    if let Some(dialog_event) = save_model.dialog_event.clone() {
+        dialog_event_writer.send(dialog_event);
+    }
+    commands.remove_resource::<PendingGameLoad>();
+    let loaded = GameLoaded {
+        scene: save_model.scene.clone(),
+    };
+    // Sent as a command so that it is only read once the state above has been inserted
+    commands.add(move |world: &mut World| world.send_event(loaded));
+    info!("Successfully restored save of \"{}\"", save_model.scene);
+}
+
 #[sysfail(log(level = "error"))]
 fn handle_save_requests(
     mut save_events: EventReader<GameSaveRequest>,
     conditions: Res<ActiveConditions>,
+    dialog_context: Res<DialogContext>,
+    quest_log: Res<QuestLog>,
     dialog: Option<Res<CurrentDialog>>,
+    level_stats: Res<LevelStats>,
+    terrain_deformations: Res<TerrainDeformations>,
+    npcs: Query<(Option<&Name>, Option<&ObjectMetadata>, &NpcMemory)>,
+    built: Query<(&GameObject, &Transform), With<Built>>,
+    world_clock: Res<WorldClock>,
+    weather: Res<Weather>,
+    world_event_objects: Query<(
+        &Transform,
+        &WorldEventObject,
+        Option<&GameObject>,
+        Option<&CustomObject>,
+    )>,
     player_query: Query<&GlobalTransform, With<Player>>,
     current_level: Res<CurrentLevel>,
 ) -> Result<()> {
-    let dialog = dialog.map(|dialog| dialog.clone());
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_save_requests").entered();
     for save in save_events.iter() {
         for player in &player_query {
-            let dialog_event = dialog.clone().map(|dialog| DialogEvent {
-                dialog: dialog.id,
-                source: dialog.source,
-                page: Some(dialog.current_page),
-            });
-            let save_model = SaveModel {
-                scene: current_level.scene.clone(),
-                conditions: conditions.clone(),
-                dialog_event,
-                player_transform: player.compute_transform(),
-            };
-            let serialized = match ron::to_string(&save_model) {
+            let save_model = SaveModel::new(
+                &current_level,
+                &conditions,
+                &dialog_context,
+                &quest_log,
+                dialog.as_deref(),
+                &level_stats,
+                NpcMemories::collect(&npcs),
+                &terrain_deformations,
+                BuiltObjects::collect(&built),
+                &world_clock,
+                &weather,
+                WorldEventObjects::collect(&world_event_objects),
+                player.compute_transform(),
+            );
+            let serialized = match serialize_save(&save_model) {
                 Ok(string) => string,
                 Err(e) => {
-                    error!("Failed to save world: {}", e);
+                    error!("Failed to save world: {e:?}");
                     continue;
                 }
             };
@@ -164,6 +375,29 @@ fn handle_save_requests(
     Ok(())
 }
 
+/// Returns the paths of all saves, sorted from oldest to newest.
+fn list_saves() -> Result<Vec<PathBuf>> {
+    let mut saves: Vec<_> = glob("./saves/*.sav.ron")
+        .context("Failed to read glob pattern")?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.is_file())
+        .collect();
+    saves.sort_by_cached_key(|f| {
+        f.metadata()
+            .expect("Failed to read file metadata")
+            .modified()
+            .expect("Failed to read file modified time")
+    });
+    Ok(saves)
+}
+
+fn read_save(path: &Path) -> Result<SaveModel> {
+    let serialized = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read save at {}", path.to_string_lossy()))?;
+    deserialize_save(&serialized)
+        .with_context(|| format!("Failed to read save at {}", path.to_string_lossy()))
+}
+
 fn get_save_path(filename: impl Into<Cow<'static, str>>) -> PathBuf {
     let filename = filename.into().to_string();
     Path::new("saves").join(filename).with_extension("sav.ron")
@@ -1,17 +1,49 @@
+#[cfg(feature = "dev")]
+use crate::dev::level_validation::LevelValidationReport;
 use crate::file_system_interaction::asset_loading::LevelAssets;
+use crate::level_instantiation::spawning::custom::{CustomObject, ObjectKind};
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::spawn_queue::{process_spawn_queue, SpawnQueue};
+use crate::level_instantiation::spawning::spawner::SpawnEvent;
 use crate::level_instantiation::spawning::GameObject;
+use crate::movement::moving_platform::MovingPlatform;
 use crate::world_interaction::condition::ActiveConditions;
 use crate::world_interaction::dialog::CurrentDialog;
 use crate::world_interaction::interactions_ui::InteractionOpportunities;
 use anyhow::{Context, Result};
+use bevy::asset::{AssetLoader, LoadContext, LoadState, LoadedAsset};
 use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
+use bevy::tasks::AsyncComputeTaskPool;
+use bevy::utils::BoxedFuture;
 use bevy_mod_sysfail::macros::*;
+use foxtrot_world_serialization::{
+    object_names, stored_objects, write_numbered, Migration, VersionedFormat,
+};
 use serde::{Deserialize, Serialize};
-use spew::prelude::*;
-use std::path::Path;
-use std::{fs, iter};
+use std::path::{Path, PathBuf};
 
+/// Version of the level format written by [`WorldSaveRequest`]s.
+/// Bump this whenever a change to [`SerializedLevel`], [`GameObject`] or the data of a spawned object would break
+/// existing levels, and append a migration from the previous version to [`LEVEL_MIGRATIONS`].
+pub const LEVEL_FORMAT_VERSION: u32 = 1;
+
+/// The migration at index `i` converts levels of version `i` to the version after it.
+/// Migrations work on text, since [`ron::Value`]s cannot represent the enum variants used by version 0.
+const LEVEL_MIGRATIONS: &[Migration] = &[migrate_level_from_v0];
+
+/// Levels without a `version` field are version 0.
+const LEVEL_FORMAT: VersionedFormat = VersionedFormat {
+    name: "level",
+    current_version: LEVEL_FORMAT_VERSION,
+    oldest_version: 0,
+    migrations: LEVEL_MIGRATIONS,
+};
+
+/// Saves and loads levels. Levels are written to disk in the background.
+/// Loading a level puts its objects into the [`SpawnQueue`], which spawns them over multiple frames
+/// according to the [`SpawnBudget`](crate::level_instantiation::spawning::spawn_queue::SpawnBudget).
+/// A [`WorldLoadProgress`] exists until all of them are spawned.
 pub fn level_serialization_plugin(app: &mut App) {
     app.add_event::<WorldSaveRequest>()
         .add_event::<WorldLoadRequest>()
@@ -19,7 +51,11 @@ pub fn level_serialization_plugin(app: &mut App) {
             (
                 save_world,
                 load_world.run_if(resource_exists::<LevelAssets>()),
+                track_world_load_progress
+                    .run_if(resource_exists::<WorldLoadProgress>())
+                    .after(process_spawn_queue),
             )
+                .chain()
                 .in_base_set(CoreSet::PostUpdate),
         );
 }
@@ -28,12 +64,55 @@ pub fn level_serialization_plugin(app: &mut App) {
 #[reflect(Serialize, Deserialize)]
 pub struct WorldSaveRequest {
     pub filename: String,
+    /// Replace an existing level with the same name instead of saving under a new name like `<filename>-1`.
+    #[serde(default)]
+    pub overwrite: bool,
+    /// Save even if the level fails validation, which only exists with the `dev` feature.
+    #[serde(default)]
+    pub skip_validation: bool,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Reflect, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize, Default)]
 #[reflect(Serialize, Deserialize)]
 pub struct WorldLoadRequest {
     pub filename: String,
+    /// Where to spawn the player once all objects of the level are spawned.
+    /// `None` if the level is loaded without a player, e.g. for the scene viewer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_transform: Option<Transform>,
+}
+
+/// Exists while the objects of a level requested with a [`WorldLoadRequest`] are being spawned.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct WorldLoadProgress {
+    pub filename: String,
+    pub total: usize,
+    pub spawned: usize,
+    player_transform: Option<Transform>,
+}
+
+impl WorldLoadProgress {
+    fn new(filename: String, level: &SerializedLevel, player_transform: Option<Transform>) -> Self {
+        Self {
+            filename,
+            total: level.objects.len() + level.custom_objects.len(),
+            spawned: 0,
+            player_transform,
+        }
+    }
+
+    /// Fraction of spawned objects between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.
+        } else {
+            self.spawned as f32 / self.total as f32
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.spawned >= self.total
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
@@ -43,51 +122,52 @@ pub struct CurrentLevel {
 }
 
 #[sysfail(log(level = "error"))]
-fn save_world(
+pub(crate) fn save_world(
     mut save_requests: EventReader<WorldSaveRequest>,
-    spawn_query: Query<(&GameObject, Option<&Transform>)>,
+    spawn_query: Query<(
+        &GameObject,
+        Option<&Transform>,
+        Option<&ObjectMetadata>,
+        Option<&MovingPlatform>,
+    )>,
+    custom_query: Query<(&CustomObject, Option<&Transform>, Option<&ObjectMetadata>)>,
+    #[cfg(feature = "dev")] validation_report: Option<Res<LevelValidationReport>>,
 ) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_world").entered();
     for save in save_requests.iter() {
-        let scene = save.filename.clone();
-        let valid_candidates: Vec<_> = iter::once(scene.clone())
-            .chain((1..).map(|n| format!("{0}-{n}", scene.clone())))
-            .map(|filename| {
-                Path::new("assets")
-                    .join("levels")
-                    .join(filename)
-                    .with_extension("lvl.ron")
-            })
-            .map(|path| (path.clone(), fs::try_exists(path).ok()))
-            .take(10)
-            .filter_map(|(path, maybe_exists)| maybe_exists.map(|exists| (path, exists)))
-            .collect();
-        if valid_candidates.is_empty() {
-            error!("Failed to save scene \"{}\": Invalid path", scene);
-        } else if let Some(path) = valid_candidates
-            .iter()
-            .filter_map(|(path, exists)| (!exists).then_some(path))
-            .next()
+        #[cfg(feature = "dev")]
+        if validation_report
+            .as_ref()
+            .map_or(false, |report| report.blocked_save.as_ref() == Some(save))
         {
-            let serialized_world = serialize_world(&spawn_query)?;
-            let dir = path.parent().context("Failed to get level directory")?;
-            fs::create_dir_all(dir).context("Failed to create level directory")?;
-            fs::write(path, serialized_world)
-                .unwrap_or_else(|e| error!("Failed to save level \"{}\": {}", scene, e));
-            info!(
-                "Successfully saved level \"{}\" at {}",
-                scene,
-                path.to_string_lossy()
-            );
-        } else {
-            error!(
-                "Failed to save level \"{}\": Already got too many saves with this name",
-                scene
-            );
+            continue;
         }
+        let scene = save.filename.clone();
+        let overwrite = save.overwrite;
+        let serialized_world = serialize_world(&spawn_query, &custom_query)?;
+        // Only the serialization needs access to the world, so the file system is accessed in the background
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                match write_level(&scene, &serialized_world, overwrite) {
+                    Ok(path) => info!(
+                        "Successfully saved level \"{}\" at {}",
+                        scene,
+                        path.to_string_lossy()
+                    ),
+                    Err(e) => error!("Failed to save level \"{scene}\": {e:?}"),
+                }
+            })
+            .detach();
     }
     Ok(())
 }
 
+pub(crate) fn write_level(scene: &str, serialized_world: &str, overwrite: bool) -> Result<PathBuf> {
+    let dir = Path::new("assets").join("levels");
+    write_numbered(&dir, scene, "lvl.ron", serialized_world, overwrite)
+}
+
 #[derive(Debug, Component, Clone, PartialEq, Default, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
 pub struct Protected;
@@ -96,11 +176,15 @@ pub struct Protected;
 fn load_world(
     mut commands: Commands,
     mut load_requests: EventReader<WorldLoadRequest>,
-    current_spawn_query: Query<Entity, With<GameObject>>,
-    mut spawn_requests: EventWriter<SpawnEvent<GameObject, Transform>>,
+    current_spawn_query: Query<Entity, Or<(With<GameObject>, With<CustomObject>)>>,
     levels: Res<Assets<SerializedLevel>>,
     level_handles: Res<LevelAssets>,
+    asset_server: Res<AssetServer>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+    mut pending_loads: Local<Vec<(WorldLoadRequest, Handle<SerializedLevel>)>>,
 ) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("load_world").entered();
     for load in load_requests.iter() {
         let path = Path::new("levels")
             .join(load.filename.clone())
@@ -114,77 +198,217 @@ fn load_world(
             })?
             .to_string();
         let handle = match level_handles.levels.get(&path) {
-            Some(handle) => handle,
-            None => {
-                error!(
-                    "Failed to load scene \"{}\": No such level. Available levels: {:?}",
-                    path,
-                    level_handles.levels.keys()
-                );
-                continue;
-            }
+            Some(handle) => handle.clone(),
+            // Levels saved after startup, e.g. autosaves, are not part of the level assets
+            None => asset_server.load(path.as_str()),
+        };
+        pending_loads.push((load.clone(), handle));
+    }
+
+    for (load, handle) in std::mem::take(&mut *pending_loads) {
+        if asset_server.get_load_state(&handle) == LoadState::Failed {
+            error!(
+                "Failed to load scene \"{}\": No such level. Available levels: {:?}",
+                load.filename,
+                level_handles.levels.keys()
+            );
+            continue;
+        }
+        let Some(level) = levels.get(&handle) else {
+            pending_loads.push((load, handle));
+            continue;
         };
-        let spawn_events = &levels
-            .get(handle)
-            .context("Failed to get level from handle in level assets")?;
-        let spawn_events = Vec::<SpawnEvent<GameObject, Transform>>::from(*spawn_events);
         for entity in &current_spawn_query {
             commands
                 .get_entity(entity)
                 .context("Failed to get entity while loading")?
                 .despawn_recursive();
         }
-        for event in spawn_events.into_iter() {
-            spawn_requests.send(event);
+        // Objects still queued from before belong to the level that is being replaced
+        spawn_queue.0.clear();
+        for (object, transform, metadata) in &level.objects {
+            spawn_queue.push_with_metadata(
+                ObjectKind::Builtin(*object),
+                *transform,
+                metadata.clone(),
+            );
+        }
+        for (name, transform, metadata) in &level.custom_objects {
+            spawn_queue.push_with_metadata(
+                ObjectKind::Custom(name.clone()),
+                *transform,
+                metadata.clone(),
+            );
         }
+        commands.insert_resource(WorldLoadProgress::new(
+            load.filename.clone(),
+            level,
+            load.player_transform,
+        ));
         commands.insert_resource(CurrentLevel {
             scene: load.filename.clone(),
         });
         commands.insert_resource(InteractionOpportunities::default());
         commands.insert_resource(ActiveConditions::default());
         commands.remove_resource::<CurrentDialog>();
-
-        info!("Successfully loaded scene \"{}\"", load.filename,)
     }
     Ok(())
 }
 
-fn serialize_world(spawn_query: &Query<(&GameObject, Option<&Transform>)>) -> Result<String> {
-    let objects: Vec<_> = spawn_query
+fn track_world_load_progress(
+    mut commands: Commands,
+    mut progress: ResMut<WorldLoadProgress>,
+    spawn_queue: Res<SpawnQueue>,
+    mut spawn_requests: EventWriter<SpawnEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("track_world_load_progress").entered();
+    progress.spawned = progress.total.saturating_sub(spawn_queue.len());
+    if !spawn_queue.is_empty() {
+        return;
+    }
+    if let Some(transform) = progress.player_transform {
+        // Make sure the player is spawned after the last objects of the level
+        spawn_requests.send(SpawnEvent::new(GameObject::Player, transform).delay_frames(2));
+    }
+    commands.remove_resource::<WorldLoadProgress>();
+    info!("Successfully loaded scene \"{}\"", progress.filename);
+}
+
+fn serialize_world(
+    spawn_query: &Query<(
+        &GameObject,
+        Option<&Transform>,
+        Option<&ObjectMetadata>,
+        Option<&MovingPlatform>,
+    )>,
+    custom_query: &Query<(&CustomObject, Option<&Transform>, Option<&ObjectMetadata>)>,
+) -> Result<String> {
+    let objects = spawn_query
         .iter()
-        .filter(|(game_object, _)| **game_object != GameObject::Player)
-        .map(|(game_object, transform)| {
-            SpawnEvent::with_data(
+        .filter(|(game_object, ..)| **game_object != GameObject::Player)
+        .map(|(game_object, transform, metadata, platform)| {
+            let mut transform = transform.map(Clone::clone).unwrap_or_default();
+            // Platforms are stored where they started, as their waypoints are relative to that
+            if let Some(origin) = platform.and_then(|platform| platform.origin) {
+                transform.translation = origin;
+            }
+            (
                 *game_object,
+                transform,
+                metadata.cloned().unwrap_or_default(),
+            )
+        })
+        .collect();
+    let custom_objects = custom_query
+        .iter()
+        .map(|(custom_object, transform, metadata)| {
+            (
+                custom_object.name.clone(),
                 transform.map(Clone::clone).unwrap_or_default(),
+                metadata.cloned().unwrap_or_default(),
             )
         })
         .collect();
-    let serialized_level = SerializedLevel::from(objects);
-    ron::ser::to_string_pretty(&serialized_level, default()).context("Failed to serialize world")
+    let serialized_level = SerializedLevel {
+        version: LEVEL_FORMAT_VERSION,
+        objects,
+        custom_objects,
+    };
+    serialize_level(&serialized_level)
+}
+
+/// Serializes a level the way it is stored in `assets/levels`.
+pub fn serialize_level(level: &SerializedLevel) -> Result<String> {
+    ron::ser::to_string_pretty(level, default()).context("Failed to serialize level")
 }
 
-#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize, TypeUuid, Deref, DerefMut)]
+#[derive(Debug, Clone, PartialEq, Reflect, Serialize, Deserialize, TypeUuid)]
 #[uuid = "eb7cc7bc-5a97-41ed-b0c3-0d4e2137b73b"]
 #[reflect(Serialize, Deserialize)]
-pub struct SerializedLevel(pub Vec<(GameObject, Transform)>);
-
-impl From<Vec<SpawnEvent<GameObject, Transform>>> for SerializedLevel {
-    fn from(events: Vec<SpawnEvent<GameObject, Transform>>) -> Self {
-        Self(
-            events
-                .into_iter()
-                .map(|event| (event.object, event.data))
-                .collect(),
-        )
-    }
+pub struct SerializedLevel {
+    pub version: u32,
+    /// Objects are stored by name, since [`ron::Value`] cannot represent enum variants.
+    #[serde(with = "object_names")]
+    pub objects: Vec<(GameObject, Transform, ObjectMetadata)>,
+    /// Objects registered with [`AddCustomObjectExt`](crate::level_instantiation::spawning::custom::AddCustomObjectExt), stored by name.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "stored_objects"
+    )]
+    pub custom_objects: Vec<(String, Transform, ObjectMetadata)>,
 }
 
-impl From<&SerializedLevel> for Vec<SpawnEvent<GameObject, Transform>> {
+impl From<&SerializedLevel> for Vec<SpawnEvent> {
     fn from(level: &SerializedLevel) -> Self {
         level
+            .objects
             .iter()
-            .map(|(object, transform)| SpawnEvent::with_data(*object, *transform))
+            .map(|(object, transform, _)| SpawnEvent::new(*object, *transform))
             .collect()
     }
 }
+
+/// Loads levels, migrating them to the current [`LEVEL_FORMAT_VERSION`] if needed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = deserialize_level(bytes).with_context(|| {
+                format!("Failed to load level at {}", load_context.path().display())
+            })?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lvl.ron"]
+    }
+}
+
+/// Levels from before the format was versioned, which stored objects as enum variants.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct UnversionedLevel {
+    objects: Vec<(GameObject, Transform)>,
+    #[serde(default)]
+    custom_objects: Vec<(String, Transform)>,
+}
+
+/// Stores objects by name instead of as enum variants and adds the `version` field.
+fn migrate_level_from_v0(level: &str) -> Result<String> {
+    let level: UnversionedLevel = ron::from_str(level).context("Failed to deserialize level")?;
+    serialize_level(&SerializedLevel {
+        version: 1,
+        objects: level
+            .objects
+            .into_iter()
+            .map(|(object, transform)| (object, transform, default()))
+            .collect(),
+        custom_objects: level
+            .custom_objects
+            .into_iter()
+            .map(|(name, transform)| (name, transform, default()))
+            .collect(),
+    })
+}
+
+/// Deserializes a level of any supported format version, migrating it to the current [`LEVEL_FORMAT_VERSION`].
+/// Levels without a `version` field are treated as version 0.
+pub fn deserialize_level(bytes: &[u8]) -> Result<SerializedLevel> {
+    let (mut level, version): (SerializedLevel, _) = LEVEL_FORMAT.deserialize(bytes)?;
+    if version < LEVEL_FORMAT_VERSION {
+        warn!(
+            "Loaded a level of format version {version}, save it again to upgrade it to version {LEVEL_FORMAT_VERSION}"
+        );
+    }
+    level.version = LEVEL_FORMAT_VERSION;
+    Ok(level)
+}
@@ -0,0 +1,109 @@
+use crate::file_system_interaction::asset_loading::LocalizationAssets;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Language whose strings fill in for those missing from other languages.
+pub const DEFAULT_LANGUAGE: &str = "en";
+/// Key of the name of a language in its own translation file, e.g. `"Deutsch"`.
+pub const LANGUAGE_NAME_KEY: &str = "language.name";
+
+/// Handles translations. Each language has a file in `assets/localization/` named after its code, e.g. `de.lang.ron`,
+/// which maps string keys to the text in that language. Texts shown to the player, e.g. dialog lines, are written as keys
+/// and resolved through the [`Localization`] of the current [`Language`], which is chosen in the settings.
+/// Keys without a translation fall back to [`DEFAULT_LANGUAGE`] and then to the key itself, so untranslated text still shows up.
+/// Editing a translation file while the game runs applies the change right away in builds that watch their assets.
+pub fn localization_plugin(app: &mut App) {
+    app.register_type::<Language>()
+        .init_resource::<Language>()
+        .init_resource::<Localization>()
+        .add_system(update_localization.run_if(resource_exists::<LocalizationAssets>()));
+}
+
+/// Code of the language the game is shown in, e.g. `"de"`.
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Language(pub String);
+
+impl Default for Language {
+    fn default() -> Self {
+        Self(DEFAULT_LANGUAGE.to_owned())
+    }
+}
+
+/// Contents of a translation file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid, Default)]
+#[uuid = "5c0e3f6a-7a4e-4b8e-9a71-3f0d2c8b6e14"]
+pub struct Translations(pub HashMap<String, String>);
+
+/// The strings of the current [`Language`].
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct Localization {
+    /// Codes and names of all languages with a translation file, sorted by code
+    pub languages: Vec<(String, String)>,
+    strings: HashMap<String, String>,
+}
+
+impl Localization {
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map_or(key, String::as_str)
+    }
+}
+
+fn update_localization(
+    language: Res<Language>,
+    localization_assets: Res<LocalizationAssets>,
+    translations: Res<Assets<Translations>>,
+    mut translation_events: EventReader<AssetEvent<Translations>>,
+    mut localization: ResMut<Localization>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_localization").entered();
+    // Any event means that a file was loaded or edited
+    let files_changed = translation_events.iter().count() > 0;
+    if !files_changed && !language.is_changed() && !localization_assets.is_added() {
+        return;
+    }
+    let by_code: HashMap<_, _> = localization_assets
+        .translations
+        .iter()
+        .filter_map(|(path, handle)| Some((language_code(path)?, translations.get(handle)?)))
+        .collect();
+    if !by_code.contains_key(&language.0) {
+        warn!(
+            "No translation file for language \"{}\", falling back to \"{DEFAULT_LANGUAGE}\"",
+            language.0
+        );
+    }
+
+    let mut languages: Vec<_> = by_code
+        .iter()
+        .map(|(code, translations)| {
+            let name = translations
+                .0
+                .get(LANGUAGE_NAME_KEY)
+                .cloned()
+                .unwrap_or_else(|| code.clone());
+            (code.clone(), name)
+        })
+        .collect();
+    languages.sort();
+    let mut strings = HashMap::new();
+    for code in [DEFAULT_LANGUAGE, language.0.as_str()] {
+        if let Some(translations) = by_code.get(code) {
+            strings.extend(translations.0.clone());
+        }
+    }
+    let updated = Localization { languages, strings };
+    if updated != *localization {
+        *localization = updated;
+    }
+}
+
+/// Extracts `de` from `localization/de.lang.ron`.
+fn language_code(path: &str) -> Option<String> {
+    let file_name = Path::new(path).file_name()?.to_str()?;
+    file_name.strip_suffix(".lang.ron").map(str::to_owned)
+}
@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy::utils::tracing::field::{Field, Visit};
+use bevy::utils::tracing::{Event, Subscriber};
+use chrono::prelude::Local;
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Same as the default filter of Bevy's `LogPlugin`.
+const DEFAULT_LOG_FILTER: &str = "info,wgpu=error,naga=warn";
+
+/// The most recent log output, so that it can be attached to bug reports.
+#[derive(Debug, Clone, Resource, Default)]
+pub struct LogHistory(Arc<Mutex<VecDeque<String>>>);
+
+impl LogHistory {
+    const MAX_LINES: usize = 1000;
+
+    pub fn lines(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    fn push(&self, line: String) {
+        let Ok(mut lines) = self.0.lock() else {
+            return;
+        };
+        lines.push_back(line);
+        while lines.len() > Self::MAX_LINES {
+            lines.pop_front();
+        }
+    }
+}
+
+/// Sets up logging in place of Bevy's `LogPlugin`, which cannot be extended.
+/// Logs to stderr like it does and additionally records into the returned [`LogHistory`].
+pub fn init_logging() -> LogHistory {
+    let history = LogHistory::default();
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(LogHistoryLayer(history.clone()))
+        .try_init();
+    if let Err(e) = result {
+        eprintln!("Failed to set up logging: {e}");
+    }
+    history
+}
+
+struct LogHistoryLayer(LogHistory);
+
+impl<S: Subscriber> Layer<S> for LogHistoryLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {} {}:",
+            Local::now().format("%H:%M:%S%.3f"),
+            metadata.level(),
+            metadata.target()
+        );
+        event.record(&mut LineVisitor(&mut line));
+        self.0.push(line);
+    }
+}
+
+struct LineVisitor<'a>(&'a mut String);
+
+impl Visit for LineVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // Writing to a string cannot fail
+        let _ = if field.name() == "message" {
+            write!(self.0, " {value:?}")
+        } else {
+            write!(self.0, " {}={value:?}", field.name())
+        };
+    }
+}
@@ -0,0 +1,122 @@
+use crate::console::{AddConsoleCommandExt, PermissionLevel};
+use crate::world_interaction::currency::Wallet;
+use crate::world_interaction::dialog::DialogJournal;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Handles the [`PlayerProfile`], which holds progress that is shared between all save states,
+/// e.g. which tutorials have already been shown, the best results per level, the [`DialogJournal`] and the [`Wallet`]. It is loaded at startup and written back to
+/// `saves/profile.ron` whenever it changes.
+pub fn player_profile_plugin(app: &mut App) {
+    app.register_type::<PlayerProfile>()
+        .register_type::<LevelRecord>()
+        .init_resource::<PlayerProfile>()
+        .add_startup_system(load_player_profile)
+        .add_system(save_player_profile)
+        .add_console_command_with_permission(
+            "reset_tutorials",
+            "Forgets which tutorials have been shown so that they trigger again",
+            PermissionLevel::Player,
+            reset_tutorials,
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    #[serde(default)]
+    pub seen_tutorials: HashSet<String>,
+    /// Best results of all finished levels by level name.
+    #[serde(default)]
+    pub best_results: HashMap<String, LevelRecord>,
+    #[serde(default, skip_serializing_if = "DialogJournal::is_empty")]
+    pub journal: DialogJournal,
+    #[serde(default, skip_serializing_if = "Wallet::is_empty")]
+    pub wallet: Wallet,
+}
+
+/// The best results achieved in a level. Each value is tracked separately, so they can stem from different runs.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct LevelRecord {
+    /// Fastest time in seconds
+    pub best_time: f32,
+    pub most_collected: u32,
+    pub fewest_deaths: u32,
+    /// Fastest time in seconds per timed challenge
+    #[serde(default)]
+    pub challenge_times: HashMap<String, f32>,
+}
+
+impl LevelRecord {
+    pub fn merge(&self, other: &LevelRecord) -> LevelRecord {
+        LevelRecord {
+            best_time: self.best_time.min(other.best_time),
+            most_collected: self.most_collected.max(other.most_collected),
+            fewest_deaths: self.fewest_deaths.min(other.fewest_deaths),
+            challenge_times: merge_challenge_times(&self.challenge_times, &other.challenge_times),
+        }
+    }
+}
+
+fn merge_challenge_times(
+    times: &HashMap<String, f32>,
+    other: &HashMap<String, f32>,
+) -> HashMap<String, f32> {
+    let mut merged = times.clone();
+    for (challenge, time) in other {
+        merged
+            .entry(challenge.clone())
+            .and_modify(|best| *best = best.min(*time))
+            .or_insert(*time);
+    }
+    merged
+}
+
+impl PlayerProfile {
+    pub fn has_seen_tutorial(&self, id: &str) -> bool {
+        self.seen_tutorials.contains(id)
+    }
+}
+
+fn reset_tutorials(world: &mut World, _args: &[&str]) -> Result<String> {
+    world.resource_mut::<PlayerProfile>().seen_tutorials.clear();
+    Ok("Tutorials have been reset".to_owned())
+}
+
+#[sysfail(log(level = "error"))]
+fn load_player_profile(mut profile: ResMut<PlayerProfile>) -> Result<()> {
+    let path = get_profile_path();
+    if !path.exists() {
+        return Ok(());
+    }
+    let serialized = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read profile at {}", path.to_string_lossy()))?;
+    *profile = ron::from_str(&serialized).context("Failed to deserialize profile")?;
+    Ok(())
+}
+
+#[sysfail(log(level = "error"))]
+fn save_player_profile(profile: Res<PlayerProfile>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_player_profile").entered();
+    // The profile is inserted at startup, which also counts as a change
+    if !profile.is_changed() || profile.is_added() {
+        return Ok(());
+    }
+    let serialized = ron::to_string(&*profile).context("Failed to serialize profile")?;
+    let path = get_profile_path();
+    let dir = path.parent().context("Failed to get profile directory")?;
+    fs::create_dir_all(dir).context("Failed to create profile directory")?;
+    fs::write(&path, serialized).context("Failed to write profile")?;
+    Ok(())
+}
+
+fn get_profile_path() -> PathBuf {
+    Path::new("saves").join("profile.ron")
+}
@@ -0,0 +1,164 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::level_serialization::{
+    deserialize_level, serialize_level, write_level, SerializedLevel, LEVEL_FORMAT_VERSION,
+};
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::GameObject;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::scene::serde::SceneDeserializer;
+use bevy::scene::DynamicEntity;
+use serde::de::DeserializeSeed;
+use std::fs;
+use std::path::Path;
+
+/// Converts levels to and from Bevy [`DynamicScene`]s stored as `.scn.ron`, so they can be opened by other Bevy tooling.
+/// Each object becomes an entity with its [`GameObject`] or [`CustomObject`], its [`Transform`] and its [`ObjectMetadata`].
+/// Since imported scenes always become levels of the current [`LEVEL_FORMAT_VERSION`], scenes can also carry levels
+/// across changes to the level format that are too large for a migration.
+/// The conversion is available as the `export_scene` and `import_scene` console commands.
+pub fn scene_interchange_plugin(app: &mut App) {
+    app.add_console_command(
+        "export_scene",
+        "Converts the level with the given name to exports/<name>.scn.ron",
+        run_export_scene_command,
+    )
+    .add_console_command(
+        "import_scene",
+        "Converts a .scn.ron file into a level named after the file, e.g. \"import_scene exports/old_town.scn.ron\"",
+        run_import_scene_command,
+    );
+}
+
+pub fn level_to_dynamic_scene(level: &SerializedLevel) -> DynamicScene {
+    let builtin = level.objects.iter().map(|(object, transform, metadata)| {
+        (Box::new(*object) as Box<dyn Reflect>, transform, metadata)
+    });
+    let custom = level
+        .custom_objects
+        .iter()
+        .map(|(name, transform, metadata)| {
+            let object = CustomObject { name: name.clone() };
+            (Box::new(object) as Box<dyn Reflect>, transform, metadata)
+        });
+    let entities = builtin
+        .chain(custom)
+        .enumerate()
+        .map(|(index, (object, transform, metadata))| {
+            let mut components = vec![object, Box::new(*transform) as Box<dyn Reflect>];
+            if !metadata.is_empty() {
+                components.push(Box::new(metadata.clone()));
+            }
+            DynamicEntity {
+                entity: index as u32,
+                components,
+            }
+        })
+        .collect();
+    DynamicScene { entities }
+}
+
+/// Entities that are neither a [`GameObject`] nor a [`CustomObject`], e.g. cameras added by another tool, are skipped.
+pub fn dynamic_scene_to_level(scene: &DynamicScene) -> Result<SerializedLevel> {
+    let mut level = SerializedLevel {
+        version: LEVEL_FORMAT_VERSION,
+        objects: default(),
+        custom_objects: default(),
+    };
+    let mut skipped = 0;
+    for entity in &scene.entities {
+        let context = || format!("Failed to read scene entity {}", entity.entity);
+        let object = get_component::<GameObject>(entity).with_context(context)?;
+        let custom = get_component::<CustomObject>(entity).with_context(context)?;
+        let transform = get_component::<Transform>(entity)
+            .with_context(context)?
+            .unwrap_or_default();
+        let metadata = get_component::<ObjectMetadata>(entity)
+            .with_context(context)?
+            .unwrap_or_default();
+        match (object, custom) {
+            // Levels never contain the player, see `WorldLoadRequest::player_transform`
+            (Some(GameObject::Player), _) => skipped += 1,
+            (Some(object), _) => level.objects.push((object, transform, metadata)),
+            (None, Some(custom)) => level
+                .custom_objects
+                .push((custom.name, transform, metadata)),
+            (None, None) => skipped += 1,
+        }
+    }
+    if skipped > 0 {
+        warn!("Skipped {skipped} scene entities that are not level objects");
+    }
+    Ok(level)
+}
+
+fn get_component<T: FromReflect>(entity: &DynamicEntity) -> Result<Option<T>> {
+    let type_name = std::any::type_name::<T>();
+    let Some(component) = entity
+        .components
+        .iter()
+        .find(|component| component.type_name() == type_name)
+    else {
+        return Ok(None);
+    };
+    T::from_reflect(component.as_ref())
+        .map(Some)
+        .with_context(|| format!("Failed to convert component to {type_name}"))
+}
+
+fn run_export_scene_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let [name] = args else {
+        bail!("Usage: export_scene <level>");
+    };
+    let level_path = Path::new("assets")
+        .join("levels")
+        .join(name)
+        .with_extension("lvl.ron");
+    let bytes = fs::read(&level_path)
+        .with_context(|| format!("Failed to read level at {}", level_path.to_string_lossy()))?;
+    let level = deserialize_level(&bytes)?;
+    let type_registry = world.resource::<AppTypeRegistry>();
+    let serialized = level_to_dynamic_scene(&level)
+        .serialize_ron(type_registry)
+        .context("Failed to serialize scene")?;
+    let scene_path = Path::new("exports").join(name).with_extension("scn.ron");
+    let dir = scene_path
+        .parent()
+        .context("Failed to get export directory")?;
+    fs::create_dir_all(dir).context("Failed to create export directory")?;
+    fs::write(&scene_path, serialized).context("Failed to write scene")?;
+    Ok(format!(
+        "Exported level \"{name}\" to {}",
+        scene_path.to_string_lossy()
+    ))
+}
+
+fn run_import_scene_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let [path] = args else {
+        bail!("Usage: import_scene <path>");
+    };
+    let path = Path::new(path);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".scn.ron"))
+        .context("Scenes must have the extension .scn.ron")?;
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read scene at {}", path.to_string_lossy()))?;
+    let mut deserializer =
+        ron::de::Deserializer::from_bytes(&bytes).context("Failed to read scene")?;
+    let scene = SceneDeserializer {
+        type_registry: &world.resource::<AppTypeRegistry>().read(),
+    }
+    .deserialize(&mut deserializer)
+    .context("Failed to deserialize scene")?;
+    let level = dynamic_scene_to_level(&scene)?;
+    let serialized = serialize_level(&level)?;
+    let level_path = write_level(name, &serialized, false)?;
+    Ok(format!(
+        "Imported {} objects as level {}",
+        level.objects.len() + level.custom_objects.len(),
+        level_path.to_string_lossy()
+    ))
+}
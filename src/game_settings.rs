@@ -0,0 +1,396 @@
+use crate::file_system_interaction::audio::{DialogAudio, MusicAudio};
+use crate::file_system_interaction::localization::{Language, DEFAULT_LANGUAGE};
+use anyhow::{Context, Result};
+use bevy::pbr::{DirectionalLightShadowMap, PointLightShadowMap};
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use bevy_egui::egui;
+use bevy_kira_audio::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Window sizes offered in the settings. The window can still be resized freely.
+const RESOLUTIONS: [(f32, f32); 5] = [
+    (800., 600.),
+    (1280., 720.),
+    (1600., 900.),
+    (1920., 1080.),
+    (2560., 1440.),
+];
+
+/// Handles the [`GameSettings`], i.e. the graphics, audio, input, text speed and language options chosen by the player.
+/// They are loaded at startup and written back to `settings/settings.ron` whenever they change.
+/// Changes are applied right away to the primary window, the shadows of all lights, the volumes of all audio channels and the [`Language`].
+/// The main menu and the pause menu show them via [`show_game_settings`].
+pub fn game_settings_plugin(app: &mut App) {
+    app.register_type::<GameSettings>()
+        .register_type::<DisplayMode>()
+        .register_type::<ShadowQuality>()
+        .init_resource::<GameSettings>()
+        .add_startup_system(load_game_settings)
+        .add_system(save_game_settings)
+        .add_systems(
+            (
+                apply_window_settings,
+                apply_audio_settings,
+                apply_language_settings,
+            )
+                .distributive_run_if(resource_changed::<GameSettings>()),
+        )
+        .add_system(apply_shadow_settings);
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    /// Window size in logical pixels
+    pub resolution: (f32, f32),
+    pub vsync: bool,
+    pub display_mode: DisplayMode,
+    pub shadow_quality: ShadowQuality,
+    /// Scales all other volumes, between 0 and 1
+    pub master_volume: f32,
+    pub music_volume: f32,
+    /// Volume of sound effects, including footsteps
+    pub sfx_volume: f32,
+    /// Volume of menu and dialog sounds
+    pub ui_volume: f32,
+    /// Multiplies the camera sensitivities of the [`GameConfig`](crate::file_system_interaction::config::GameConfig)
+    pub mouse_sensitivity: f32,
+    /// Code of the [`Language`]
+    pub language: String,
+    /// Multiplies how fast the text of dialogs is revealed
+    pub text_speed: f32,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            display_mode: default(),
+            shadow_quality: default(),
+            master_volume: 1.,
+            music_volume: 0.8,
+            sfx_volume: 1.,
+            ui_volume: 1.,
+            mouse_sensitivity: 1.,
+            language: DEFAULT_LANGUAGE.to_owned(),
+            text_speed: 1.,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// Covers the whole screen without changing the monitor's resolution
+    BorderlessFullscreen,
+    Fullscreen,
+}
+
+impl From<DisplayMode> for WindowMode {
+    fn from(mode: DisplayMode) -> Self {
+        match mode {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    /// Sizes in pixels of the shadow maps of directional lights and point lights.
+    fn shadow_map_sizes(self) -> Option<(usize, usize)> {
+        match self {
+            ShadowQuality::Off => None,
+            ShadowQuality::Low => Some((1024, 512)),
+            ShadowQuality::Medium => Some((2048, 1024)),
+            ShadowQuality::High => Some((4096, 2048)),
+        }
+    }
+}
+
+/// Marks lights whose shadows were turned off by the [`ShadowQuality`], so that only they are turned back on.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Component, Default)]
+pub struct ShadowsDisabledBySettings;
+
+/// Shows the settings as a form. Changes are written to `settings` right away.
+/// `languages` are the codes and names of the languages to choose from.
+pub fn show_game_settings(
+    ui: &mut egui::Ui,
+    settings: &mut GameSettings,
+    languages: &[(String, String)],
+) {
+    egui::Grid::new("game_settings")
+        .num_columns(2)
+        .spacing([20., 8.])
+        .show(ui, |ui| {
+            ui.label("Resolution");
+            let (width, height) = settings.resolution;
+            egui::ComboBox::from_id_source("resolution")
+                .selected_text(format!("{width} x {height}"))
+                .show_ui(ui, |ui| {
+                    for resolution @ (width, height) in RESOLUTIONS {
+                        ui.selectable_value(
+                            &mut settings.resolution,
+                            resolution,
+                            format!("{width} x {height}"),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label("Display mode");
+            egui::ComboBox::from_id_source("display_mode")
+                .selected_text(display_mode_name(settings.display_mode))
+                .show_ui(ui, |ui| {
+                    for mode in [
+                        DisplayMode::Windowed,
+                        DisplayMode::BorderlessFullscreen,
+                        DisplayMode::Fullscreen,
+                    ] {
+                        ui.selectable_value(
+                            &mut settings.display_mode,
+                            mode,
+                            display_mode_name(mode),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label("VSync");
+            ui.checkbox(&mut settings.vsync, "");
+            ui.end_row();
+
+            ui.label("Shadows");
+            ui.horizontal(|ui| {
+                for quality in [
+                    ShadowQuality::Off,
+                    ShadowQuality::Low,
+                    ShadowQuality::Medium,
+                    ShadowQuality::High,
+                ] {
+                    ui.radio_value(
+                        &mut settings.shadow_quality,
+                        quality,
+                        format!("{quality:?}"),
+                    );
+                }
+            });
+            ui.end_row();
+
+            for (label, volume) in [
+                ("Master volume", &mut settings.master_volume),
+                ("Music volume", &mut settings.music_volume),
+                ("Effects volume", &mut settings.sfx_volume),
+                ("Interface volume", &mut settings.ui_volume),
+            ] {
+                ui.label(label);
+                ui.add(egui::Slider::new(volume, 0.0..=1.0).show_value(false));
+                ui.end_row();
+            }
+
+            ui.label("Mouse sensitivity");
+            ui.add(egui::Slider::new(
+                &mut settings.mouse_sensitivity,
+                0.1..=3.0,
+            ));
+            ui.end_row();
+
+            ui.label("Text speed");
+            ui.add(egui::Slider::new(&mut settings.text_speed, 0.25..=4.0));
+            ui.end_row();
+
+            ui.label("Language");
+            let selected_name = languages
+                .iter()
+                .find(|(code, _)| *code == settings.language)
+                .map_or(settings.language.as_str(), |(_, name)| name.as_str());
+            egui::ComboBox::from_id_source("language")
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (code, name) in languages {
+                        ui.selectable_value(&mut settings.language, code.clone(), name);
+                    }
+                });
+            ui.end_row();
+        });
+}
+
+fn display_mode_name(mode: DisplayMode) -> &'static str {
+    match mode {
+        DisplayMode::Windowed => "Windowed",
+        DisplayMode::BorderlessFullscreen => "Borderless fullscreen",
+        DisplayMode::Fullscreen => "Fullscreen",
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<GameSettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_window_settings").entered();
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+    let (width, height) = settings.resolution;
+    if window.resolution.width() != width || window.resolution.height() != height {
+        window.resolution.set(width, height);
+    }
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+    let mode = settings.display_mode.into();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+}
+
+fn apply_audio_settings(
+    settings: Res<GameSettings>,
+    music_audio: Res<AudioChannel<MusicAudio>>,
+    dialog_audio: Res<AudioChannel<DialogAudio>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_audio_settings").entered();
+    let master = settings.master_volume as f64;
+    music_audio.set_volume(master * settings.music_volume as f64);
+    dialog_audio.set_volume(master);
+    // Effects and UI sounds are mixed per sound, see `internal_audio_plugin`
+}
+
+fn apply_language_settings(settings: Res<GameSettings>, mut language: ResMut<Language>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_language_settings").entered();
+    if language.0 != settings.language {
+        language.0 = settings.language.clone();
+    }
+}
+
+fn apply_shadow_settings(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    mut directional_shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut point_shadow_map: ResMut<PointLightShadowMap>,
+    mut directional_lights: Query<(
+        Entity,
+        &mut DirectionalLight,
+        Option<&ShadowsDisabledBySettings>,
+    )>,
+    mut point_lights: Query<(Entity, &mut PointLight, Option<&ShadowsDisabledBySettings>)>,
+    mut spot_lights: Query<(Entity, &mut SpotLight, Option<&ShadowsDisabledBySettings>)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_shadow_settings").entered();
+    let sizes = settings.shadow_quality.shadow_map_sizes();
+    if settings.is_changed() {
+        if let Some((directional_size, point_size)) = sizes {
+            directional_shadow_map.size = directional_size;
+            point_shadow_map.size = point_size;
+        }
+    }
+    // Returns the new value of `shadows_enabled` if it needs to change.
+    // Only lights spawned since the last run are updated, unless the settings changed.
+    let update = |added: bool, disabled: bool, shadows_enabled: bool| {
+        if !added && !settings.is_changed() {
+            return None;
+        }
+        match (sizes.is_some(), shadows_enabled, disabled) {
+            (false, true, _) => Some(false),
+            (true, false, true) => Some(true),
+            _ => None,
+        }
+    };
+    let mut mark = |entity: Entity, shadows_enabled: bool| {
+        if shadows_enabled {
+            commands
+                .entity(entity)
+                .remove::<ShadowsDisabledBySettings>();
+        } else {
+            commands.entity(entity).insert(ShadowsDisabledBySettings);
+        }
+    };
+    for (entity, mut light, disabled) in directional_lights.iter_mut() {
+        if let Some(shadows_enabled) =
+            update(light.is_added(), disabled.is_some(), light.shadows_enabled)
+        {
+            light.shadows_enabled = shadows_enabled;
+            mark(entity, shadows_enabled);
+        }
+    }
+    for (entity, mut light, disabled) in point_lights.iter_mut() {
+        if let Some(shadows_enabled) =
+            update(light.is_added(), disabled.is_some(), light.shadows_enabled)
+        {
+            light.shadows_enabled = shadows_enabled;
+            mark(entity, shadows_enabled);
+        }
+    }
+    for (entity, mut light, disabled) in spot_lights.iter_mut() {
+        if let Some(shadows_enabled) =
+            update(light.is_added(), disabled.is_some(), light.shadows_enabled)
+        {
+            light.shadows_enabled = shadows_enabled;
+            mark(entity, shadows_enabled);
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn load_game_settings(mut settings: ResMut<GameSettings>) -> Result<()> {
+    let path = get_game_settings_path();
+    if !path.exists() {
+        return Ok(());
+    }
+    let serialized = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read settings at {}", path.to_string_lossy()))?;
+    *settings = ron::from_str(&serialized).context("Failed to deserialize settings")?;
+    Ok(())
+}
+
+#[sysfail(log(level = "error"))]
+fn save_game_settings(settings: Res<GameSettings>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_game_settings").entered();
+    // The settings are inserted at startup, which also counts as a change
+    if !settings.is_changed() || settings.is_added() {
+        return Ok(());
+    }
+    let serialized = ron::ser::to_string_pretty(&*settings, default())
+        .context("Failed to serialize settings")?;
+    let path = get_game_settings_path();
+    let dir = path.parent().context("Failed to get settings directory")?;
+    fs::create_dir_all(dir).context("Failed to create settings directory")?;
+    fs::write(&path, serialized).context("Failed to write settings")?;
+    Ok(())
+}
+
+fn get_game_settings_path() -> PathBuf {
+    Path::new("settings").join("settings.ron")
+}
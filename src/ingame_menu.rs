@@ -1,49 +1,236 @@
+use crate::file_system_interaction::bug_report::BugReportRequest;
+use crate::file_system_interaction::game_state_serialization::{
+    slot_filename, GameLoadRequest, GameSaveRequest, SaveSlots, SAVE_SLOT_COUNT,
+};
+use crate::file_system_interaction::localization::Localization;
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::game_settings::{show_game_settings, GameSettings};
 use crate::player_control::actions::{ActionsFrozen, UiAction};
+use crate::player_control::input_bindings::{show_input_bindings, InputBindings, Rebinding};
+use crate::state_transition::RequestStateChange;
+use crate::world_interaction::cutscene::ActiveCutscene;
+use crate::world_interaction::dialog::DialogJournal;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
 
-/// Handles the pause menu accessed while playing the game via ESC.
+/// Handles pausing the game via the pause action, ESC by default. The game is paused while the [`Paused`] resource exists,
+/// so other systems can check for it and anything can resume the game by removing it, e.g. the editor when it is opened.
+/// The game cannot be paused during a cutscene.
+/// Pausing stops the virtual [`Time`], which freezes physics, animations and everything else driven by it, and freezes the player's actions.
+/// Systems keep running in [`GameState::Playing`], as leaving it would stop the UI and rerun the level setup when coming back.
+///
+/// The pause menu offers [`SAVE_SLOT_COUNT`] save slots, a journal tab listing the conversations recorded in the [`DialogJournal`],
+/// a controls tab for rebinding the [`InputBindings`], a settings tab for the [`GameSettings`] and buttons to resume or quit to the main menu.
 pub fn ingame_menu_plugin(app: &mut App) {
-    app.add_system(handle_pause.in_set(OnUpdate(GameState::Playing)));
+    app.register_type::<Paused>()
+        .add_systems(
+            (
+                toggle_pause,
+                pause_time.run_if(resource_added::<Paused>()),
+                resume_time.run_if(resource_removed::<Paused>()),
+                show_pause_menu.run_if(resource_exists::<Paused>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(unpause_on_exit.in_schedule(OnExit(GameState::Playing)));
 }
 
-fn handle_pause(
-    mut time: ResMut<Time>,
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Paused;
+
+fn toggle_pause(
+    mut commands: Commands,
     actions: Query<&ActionState<UiAction>>,
-    mut actions_frozen: ResMut<ActionsFrozen>,
+    paused: Option<Res<Paused>>,
+    rebinding: Option<Res<Rebinding>>,
+    cutscene: Option<Res<ActiveCutscene>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("toggle_pause").entered();
+    // ESC cancels rebinding instead of closing the menu
+    let toggled = rebinding.is_none()
+        && (paused.is_some() || cutscene.is_none())
+        && actions
+            .iter()
+            .any(|action| action.just_pressed(UiAction::TogglePause));
+    if !toggled {
+        return;
+    }
+    if paused.is_some() {
+        commands.remove_resource::<Paused>();
+    } else {
+        commands.insert_resource(Paused);
+    }
+}
+
+fn pause_time(mut time: ResMut<Time>, mut actions_frozen: ResMut<ActionsFrozen>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("pause_time").entered();
+    time.pause();
+    actions_frozen.freeze();
+}
+
+fn resume_time(mut time: ResMut<Time>, mut actions_frozen: ResMut<ActionsFrozen>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("resume_time").entered();
+    time.unpause();
+    actions_frozen.unfreeze();
+}
+
+/// Quitting to the menu leaves [`GameState::Playing`] while paused, so [`resume_time`] only runs once the game is entered again.
+/// Time is needed in the menu and is resumed right away, while the actions stay frozen until [`resume_time`] releases them.
+fn unpause_on_exit(mut commands: Commands, paused: Option<Res<Paused>>, mut time: ResMut<Time>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("unpause_on_exit").entered();
+    if paused.is_none() {
+        return;
+    }
+    commands.remove_resource::<Paused>();
+    time.unpause();
+}
+
+fn show_pause_menu(
+    mut commands: Commands,
     mut egui_contexts: EguiContexts,
-    mut paused: Local<bool>,
+    mut bug_report_requests: EventWriter<BugReportRequest>,
+    mut save_requests: EventWriter<GameSaveRequest>,
+    mut load_requests: EventWriter<GameLoadRequest>,
+    mut state_change_requests: EventWriter<RequestStateChange>,
+    save_slots: Res<SaveSlots>,
+    profile: Res<PlayerProfile>,
+    mut input_bindings: ResMut<InputBindings>,
+    rebinding: Option<Res<Rebinding>>,
+    mut settings: ResMut<GameSettings>,
+    localization: Res<Localization>,
+    mut tab: Local<PauseMenuTab>,
 ) {
-    for action in actions.iter() {
-        let toggled = action.just_pressed(UiAction::TogglePause);
-        if *paused {
-            if toggled {
-                *paused = false;
-                time.unpause();
-                actions_frozen.unfreeze();
-            } else {
-                egui::CentralPanel::default()
-                    .frame(egui::Frame {
-                        fill: egui::Color32::from_black_alpha(240),
-                        ..default()
-                    })
-                    .show(egui_contexts.ctx_mut(), |ui| {
-                        ui.vertical_centered_justified(|ui| {
-                            ui.visuals_mut().override_text_color =
-                                Some(egui::Color32::from_gray(240));
-                            ui.add_space(100.0);
-                            ui.heading("Game Paused");
-                            ui.separator();
-                            ui.label("Press ESC to resume");
-                        });
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_pause_menu").entered();
+    let mut resume = false;
+    egui::CentralPanel::default()
+        .frame(egui::Frame {
+            fill: egui::Color32::from_black_alpha(240),
+            ..default()
+        })
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.vertical_centered_justified(|ui| {
+                ui.visuals_mut().override_text_color = Some(egui::Color32::from_gray(240));
+                ui.add_space(100.0);
+                ui.heading("Game Paused");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Resume").clicked() {
+                        resume = true;
+                    }
+                    if ui.button("Quit to menu").clicked() {
+                        state_change_requests.send(RequestStateChange(GameState::Menu));
+                    }
+                });
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut *tab, PauseMenuTab::Game, "Game");
+                    ui.selectable_value(&mut *tab, PauseMenuTab::Journal, "Journal");
+                    ui.selectable_value(&mut *tab, PauseMenuTab::Controls, "Controls");
+                    ui.selectable_value(&mut *tab, PauseMenuTab::Settings, "Settings");
+                });
+                ui.add_space(10.0);
+                if *tab == PauseMenuTab::Journal {
+                    show_journal(ui, &profile.journal);
+                    return;
+                }
+                if *tab == PauseMenuTab::Settings {
+                    let mut edited = settings.clone();
+                    show_game_settings(ui, &mut edited, &localization.languages);
+                    if edited != *settings {
+                        *settings = edited;
+                    }
+                    return;
+                }
+                if *tab == PauseMenuTab::Controls {
+                    // Only touch the resource on actual edits, as every change is written to disk
+                    let mut edited = input_bindings.clone();
+                    if let Some(requested) =
+                        show_input_bindings(ui, &mut edited, rebinding.as_deref())
+                    {
+                        commands.insert_resource(requested);
+                    }
+                    if edited != *input_bindings {
+                        *input_bindings = edited;
+                    }
+                    return;
+                }
+                for slot in 1..=SAVE_SLOT_COUNT {
+                    let filename = slot_filename(slot);
+                    let existing = save_slots.get(&filename);
+                    ui.horizontal(|ui| {
+                        match existing {
+                            Some(save) => {
+                                ui.label(format!("Slot {slot}: {} ({})", save.scene, save.saved_at))
+                            }
+                            None => ui.label(format!("Slot {slot}: empty")),
+                        };
+                        if ui.button("Save").clicked() {
+                            save_requests.send(GameSaveRequest {
+                                filename: Some(filename.clone()),
+                            });
+                        }
+                        if ui
+                            .add_enabled(existing.is_some(), egui::Button::new("Load"))
+                            .clicked()
+                        {
+                            load_requests.send(GameLoadRequest {
+                                filename: Some(filename.clone()),
+                            });
+                            resume = true;
+                        }
                     });
-            }
-        } else if toggled {
-            *paused = true;
-            time.pause();
-            actions_frozen.freeze();
-        }
+                }
+                ui.add_space(20.0);
+                if ui.button("Export bug report").clicked() {
+                    bug_report_requests.send(default());
+                }
+            });
+        });
+    if resume {
+        commands.remove_resource::<Paused>();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+enum PauseMenuTab {
+    #[default]
+    Game,
+    Journal,
+    Controls,
+    Settings,
+}
+
+fn show_journal(ui: &mut egui::Ui, journal: &DialogJournal) {
+    if journal.is_empty() {
+        ui.label("You have not talked to anyone yet");
+        return;
     }
+    let mut entries: Vec<_> = journal.0.iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.0.cmp(&b.0));
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for (dialog, entry) in entries {
+            let title = entry.speaker.as_deref().unwrap_or(&dialog.0);
+            egui::CollapsingHeader::new(title)
+                .id_source(&dialog.0)
+                .show(ui, |ui| {
+                    ui.label(format!("Pages seen: {}", entry.visited_pages.len()));
+                    if entry.choices.is_empty() {
+                        ui.label("No answers given yet");
+                    }
+                    for choice in &entry.choices {
+                        ui.label(format!("> {}", choice.text));
+                    }
+                });
+        }
+    });
 }
@@ -1,10 +1,16 @@
+pub mod ambient_population;
+pub mod demo_scene;
 pub mod grass;
 pub mod map;
 pub mod spawning;
+pub mod terrain;
 
+use crate::level_instantiation::ambient_population::ambient_population_plugin;
+use crate::level_instantiation::demo_scene::demo_scene_plugin;
 use crate::level_instantiation::grass::grass_plugin;
 use crate::level_instantiation::map::map_plugin;
 use crate::level_instantiation::spawning::spawning_plugin;
+use crate::level_instantiation::terrain::terrain_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
@@ -12,8 +18,14 @@ use seldom_fn_plugin::FnPluginExt;
 /// - [`map_plugin`] handles loading of level files and orchestrates the spawning of the objects therein.
 /// - [`spawning_plugin`] handles the spawning of objects in general.
 /// - [`grass_plugin`] handles the spawning of grass on top of marked meshes.
+/// - [`demo_scene_plugin`] handles spawning a sandbox with one of each basic building block.
+/// - [`ambient_population_plugin`] handles crowds of background NPCs around the player.
+/// - [`terrain_plugin`] handles ground patches that can be dug into.
 pub fn level_instantiation_plugin(app: &mut App) {
     app.fn_plugin(map_plugin)
         .fn_plugin(spawning_plugin)
-        .fn_plugin(grass_plugin);
+        .fn_plugin(grass_plugin)
+        .fn_plugin(demo_scene_plugin)
+        .fn_plugin(ambient_population_plugin)
+        .fn_plugin(terrain_plugin);
 }
@@ -0,0 +1,222 @@
+use crate::file_system_interaction::asset_loading::{AnimationAssets, SceneAssets};
+use crate::file_system_interaction::config::{AmbientPopulation, GameConfig};
+use crate::file_system_interaction::level_serialization::WorldLoadRequest;
+use crate::level_instantiation::spawning::objects::npc;
+use crate::movement::general_movement::{CharacterControllerSystemSet, Model, Walking};
+use crate::player_control::player_embodiment::Player;
+use crate::util::trait_extension::{F32Ext, Vec3Ext};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::sync::LazyLock;
+
+/// Keeps a crowd of [`AmbientNpc`]s wandering around the player, e.g. for busy streets.
+/// How many there are depends on the [`PopulationZone`] the player is in, which is placed in the level via the glTF node name marker
+/// `[population: <density>]`. It turns everything inside the node's cube into a zone with the given number of NPCs per 100 m².
+/// Outside of zones, the default density from the config applies. The total number of NPCs never exceeds the configured budget.
+/// NPCs that get too far away from the player are moved to a new spot near the player instead of being despawned,
+/// so the crowd is only spawned once.
+pub fn ambient_population_plugin(app: &mut App) {
+    app.register_type::<PopulationZone>()
+        .register_type::<AmbientNpc>()
+        .add_systems(
+            (
+                read_population_markers,
+                clear_population.run_if(on_event::<WorldLoadRequest>()),
+                update_population,
+                wander,
+            )
+                .chain()
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct PopulationZone {
+    /// NPCs per 100 m²
+    pub density: f32,
+}
+
+impl PopulationZone {
+    fn contains(transform: &GlobalTransform, point: Vec3) -> bool {
+        let local = transform.affine().inverse().transform_point3(point);
+        local.abs().cmple(Vec3::ONE).all()
+    }
+}
+
+/// A background NPC that walks to random spots near where it is.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct AmbientNpc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<Vec3>,
+    /// Time in s until the NPC walks on
+    pub idle_time: f32,
+}
+
+static POPULATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[population:\s*(\d+(?:\.\d+)?)\]").expect("Failed to compile population regex")
+});
+
+/// Distance in m around its current position within which an NPC picks its next target.
+const WANDER_RADIUS: f32 = 8.;
+
+/// NPCs closer than this in m to their target have arrived.
+const ARRIVAL_DISTANCE: f32 = 1.;
+
+#[sysfail(log(level = "error"))]
+fn read_population_markers(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name), Added<Name>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_population_markers").entered();
+    for (entity, name) in added_name.iter() {
+        let name = name.to_lowercase();
+        let Some(captures) = POPULATION_REGEX.captures(&name) else {
+            continue;
+        };
+        let density = captures[1]
+            .parse::<f32>()
+            .with_context(|| format!("Failed to parse population zone: {name}"))?;
+        commands.entity(entity).insert(PopulationZone { density });
+    }
+    Ok(())
+}
+
+fn clear_population(mut commands: Commands, npcs: Query<Entity, With<AmbientNpc>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("clear_population").entered();
+    for entity in npcs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_population(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    rapier_context: Res<RapierContext>,
+    animations: Res<AnimationAssets>,
+    scene_handles: Res<SceneAssets>,
+    players: Query<&Transform, (With<Player>, Without<AmbientNpc>)>,
+    zones: Query<(&PopulationZone, &GlobalTransform)>,
+    mut npcs: Query<(Entity, &mut Transform, &mut Velocity, &mut AmbientNpc), Without<Player>>,
+    mut models: Query<(&Model, &mut Transform), (Without<Player>, Without<AmbientNpc>)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_population").entered();
+    let Some(player) = players.iter().next() else {
+        return;
+    };
+    let config = &config.ambient_population;
+    let density = zones
+        .iter()
+        .find(|(_, transform)| PopulationZone::contains(transform, player.translation))
+        .map_or(config.default_density, |(zone, _)| zone.density);
+    let area = TAU / 2. * config.spawn_radius.squared();
+    let target_count = ((density * area / 100.).round() as usize).min(config.max_npcs);
+
+    let mut rng = SmallRng::from_entropy();
+    let mut count = npcs.iter().len();
+    for (entity, mut transform, mut velocity, mut npc) in npcs.iter_mut() {
+        let distance = (transform.translation - player.translation).length();
+        if distance <= config.despawn_radius {
+            continue;
+        }
+        if count > target_count {
+            commands.entity(entity).despawn_recursive();
+            count -= 1;
+            continue;
+        }
+        let Some(spot) = find_spawn_spot(&mut rng, &rapier_context, player.translation, config)
+        else {
+            continue;
+        };
+        transform.translation = spot;
+        velocity.linvel = Vec3::ZERO;
+        *npc = default();
+        // Keeps the model from sliding across the map to the new spot
+        for (model, mut model_transform) in models.iter_mut() {
+            if model.target == entity {
+                model_transform.translation = spot;
+            }
+        }
+    }
+
+    for _ in count..target_count.min(count + config.spawns_per_frame) {
+        let Some(spot) = find_spawn_spot(&mut rng, &rapier_context, player.translation, config)
+        else {
+            break;
+        };
+        let rotation = Quat::from_rotation_y(rng.gen_range(0.0..TAU));
+        let entity = npc::spawn_ambient(
+            &mut commands,
+            Transform::from_translation(spot).with_rotation(rotation),
+            &animations,
+            &scene_handles,
+        );
+        commands.entity(entity).insert(AmbientNpc::default());
+    }
+}
+
+/// Picks a random spot on the ground that is between the configured minimum distance and the spawn radius away from the player,
+/// so that NPCs do not appear right in front of them. Finds none if the minimum distance is not below the spawn radius.
+fn find_spawn_spot(
+    rng: &mut SmallRng,
+    rapier_context: &RapierContext,
+    center: Vec3,
+    config: &AmbientPopulation,
+) -> Option<Vec3> {
+    // The config can be edited while the game runs, so an empty range must not reach `gen_range`
+    if config.min_spawn_distance >= config.spawn_radius {
+        return None;
+    }
+    let angle = rng.gen_range(0.0..TAU);
+    let distance = rng.gen_range(config.min_spawn_distance..config.spawn_radius);
+    let offset = Vec3::new(angle.cos(), 0., angle.sin()) * distance;
+    let origin = center + offset + Vec3::Y * 20.;
+    let (_, toi) = rapier_context.cast_ray(
+        origin,
+        Vec3::NEG_Y,
+        60.,
+        true,
+        QueryFilter::only_fixed().exclude_sensors(),
+    )?;
+    let ground = origin + Vec3::NEG_Y * toi;
+    Some(ground + Vec3::Y * (npc::HEIGHT / 2. + npc::RADIUS + 0.1))
+}
+
+fn wander(time: Res<Time>, mut npcs: Query<(&Transform, &mut Walking, &mut AmbientNpc)>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("wander").entered();
+    let dt = time.delta_seconds();
+    let mut rng = SmallRng::from_entropy();
+    for (transform, mut walking, mut npc) in npcs.iter_mut() {
+        if npc.idle_time > 0. {
+            npc.idle_time -= dt;
+            continue;
+        }
+        let target = *npc.target.get_or_insert_with(|| {
+            let angle = rng.gen_range(0.0..TAU);
+            let distance = rng.gen_range(ARRIVAL_DISTANCE..WANDER_RADIUS);
+            transform.translation + Vec3::new(angle.cos(), 0., angle.sin()) * distance
+        });
+        let direction = (target - transform.translation)
+            .split(transform.up())
+            .horizontal;
+        if direction.length() < ARRIVAL_DISTANCE {
+            npc.target = None;
+            npc.idle_time = rng.gen_range(1.0..5.0);
+            continue;
+        }
+        walking.direction = direction.try_normalize();
+    }
+}
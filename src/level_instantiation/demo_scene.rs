@@ -0,0 +1,164 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::objects::platform;
+use crate::level_instantiation::spawning::spawner::SpawnEvent;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::condition::ActiveConditions;
+use crate::world_interaction::dialog::{CurrentDialog, DialogContext};
+use crate::world_interaction::interactions_ui::InteractionOpportunities;
+use crate::world_interaction::quest::QuestLog;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+pub const DEMO_SCENE_NAME: &str = "demo";
+
+/// Replaces the current world with a small sandbox that showcases the basic building blocks of the game:
+/// platforms to jump on, an NPC to talk to, an orb, coins, a crate, a pressure plate that opens a gate and a goal portal behind it.
+/// Requested via [`DemoSceneRequest`], the `demo_scene` console command or the dev editor.
+///
+/// The plate and the gate are linked via name markers, see [`puzzle_plugin`](crate::world_interaction::puzzle::puzzle_plugin).
+/// Since levels only store [`GameObject`]s and their [`Transform`]s, saving the sandbox as a level keeps
+/// the platforms but loses these links.
+pub fn demo_scene_plugin(app: &mut App) {
+    app.add_event::<DemoSceneRequest>()
+        .add_system(spawn_demo_scene.in_set(OnUpdate(GameState::Playing)))
+        .add_console_command(
+            "demo_scene",
+            "Replaces the current world with a sandbox containing one of each basic building block",
+            request_demo_scene,
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Default)]
+pub struct DemoSceneRequest;
+
+fn request_demo_scene(world: &mut World, _args: &[&str]) -> Result<String> {
+    world.send_event(DemoSceneRequest);
+    Ok("Spawning demo scene".to_owned())
+}
+
+#[sysfail(log(level = "error"))]
+fn spawn_demo_scene(
+    mut commands: Commands,
+    mut requests: EventReader<DemoSceneRequest>,
+    current_spawn_query: Query<Entity, Or<(With<GameObject>, With<CustomObject>)>>,
+    mut spawn_requests: EventWriter<SpawnEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_demo_scene").entered();
+    if requests.is_empty() {
+        return Ok(());
+    }
+    // Multiple requests in the same frame would just spawn the same scene on top of each other
+    requests.clear();
+    for entity in &current_spawn_query {
+        commands
+            .get_entity(entity)
+            .context("Failed to get entity while clearing the world")?
+            .despawn_recursive();
+    }
+    commands.insert_resource(CurrentLevel {
+        scene: DEMO_SCENE_NAME.to_owned(),
+    });
+    commands.insert_resource(InteractionOpportunities::default());
+    commands.insert_resource(ActiveConditions::default());
+    commands.insert_resource(DialogContext::default());
+    commands.insert_resource(QuestLog::default());
+    commands.remove_resource::<CurrentDialog>();
+
+    let objects = [
+        (
+            GameObject::Sunlight,
+            Transform::from_rotation(Quat::from_rotation_x(-PI / 4.)),
+        ),
+        (GameObject::Skydome, Transform::default()),
+        (
+            GameObject::Camera,
+            Transform::from_xyz(0., 3., 10.).looking_at(Vec3::ZERO, Vec3::Y),
+        ),
+        (
+            GameObject::Npc,
+            Transform::from_xyz(-4., 1.5, -3.).with_rotation(Quat::from_rotation_y(PI / 4.)),
+        ),
+        (GameObject::Orb, Transform::from_xyz(4., 3., -5.)),
+        (GameObject::Crate, Transform::from_xyz(-1.5, 0.5, 4.)),
+        (GameObject::GoalPortal, Transform::from_xyz(0., 0., 10.)),
+        (GameObject::Item, Transform::from_xyz(3., 0.5, 3.)),
+    ];
+    for (object, transform) in objects {
+        spawn_requests.send(SpawnEvent::new(object, transform));
+    }
+    for index in 0..5 {
+        spawn_requests.send(SpawnEvent::new(
+            GameObject::Coin,
+            Transform::from_xyz(-6. + 1.5 * index as f32, 0.5, -6.),
+        ));
+    }
+
+    let mut spawn_platform = |name: &'static str, translation: Vec3, half_extents: Vec3| {
+        platform::spawn_named(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            Transform::from_translation(translation).with_scale(half_extents),
+            name,
+        )
+        .id()
+    };
+    spawn_platform(
+        "Demo Ground",
+        Vec3::new(0., -0.5, 0.),
+        Vec3::new(12., 0.5, 12.),
+    );
+    for (index, name) in ["Demo Step 1", "Demo Step 2", "Demo Step 3"]
+        .into_iter()
+        .enumerate()
+    {
+        let height = 0.4 * (index + 1) as f32;
+        spawn_platform(
+            name,
+            Vec3::new(4., height / 2., -2.5 * index as f32),
+            Vec3::new(1., height / 2., 1.),
+        );
+    }
+
+    let plate_translation = Vec3::new(-3., 0.05, 4.);
+    let plate_half_extents = Vec3::new(0.75, 0.05, 0.75);
+    let plate = spawn_platform("Demo Pressure Plate", plate_translation, plate_half_extents);
+    let gate = spawn_platform(
+        "Demo Gate [door: demo_gate, 0, -3, 0]",
+        Vec3::new(0., 1.5, 8.),
+        Vec3::new(2., 1.5, 0.2),
+    );
+
+    // The trigger volume is a child so that it is cleaned up together with the plate.
+    // Its transform is relative to the plate's, so the plate's scale has to be undone.
+    let volume_half_extents = Vec3::new(0.75, 0.5, 0.75);
+    commands.entity(plate).with_children(|parent| {
+        parent.spawn((
+            TransformBundle::from_transform(
+                Transform::from_translation(Vec3::Y * volume_half_extents.y / plate_half_extents)
+                    .with_scale(volume_half_extents / plate_half_extents),
+            ),
+            Name::new("Demo Pressure Plate Volume [pressure_plate: demo_gate, 4]"),
+        ));
+    });
+    commands
+        .entity(gate)
+        .insert(RigidBody::KinematicPositionBased);
+
+    // Make sure the player is spawned after the platforms
+    spawn_requests.send(
+        SpawnEvent::new(GameObject::Player, Transform::from_xyz(0., 1.5, 0.)).delay_frames(2),
+    );
+    info!("Successfully spawned demo scene");
+    Ok(())
+}
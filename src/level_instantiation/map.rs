@@ -1,10 +1,12 @@
-use crate::file_system_interaction::level_serialization::{CurrentLevel, WorldLoadRequest};
-use crate::level_instantiation::spawning::GameObject;
+#[cfg(feature = "dev")]
+use crate::dev::scene_viewer::SceneViewer;
+use crate::file_system_interaction::level_serialization::{
+    CurrentLevel, WorldLoadProgress, WorldLoadRequest,
+};
 use crate::player_control::player_embodiment::Player;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
-use spew::prelude::*;
 
 pub fn map_plugin(app: &mut App) {
     app.add_system(
@@ -21,11 +23,7 @@ pub fn map_plugin(app: &mut App) {
     app.add_system(show_wasm_loader.in_set(OnUpdate(GameState::Playing)));
 }
 
-fn setup(
-    mut commands: Commands,
-    mut loader: EventWriter<WorldLoadRequest>,
-    mut delayed_spawner: EventWriter<SpawnEvent<GameObject, Transform>>,
-) {
+fn setup(mut commands: Commands, mut loader: EventWriter<WorldLoadRequest>) {
     commands.insert_resource(AmbientLight {
         color: Color::WHITE,
         brightness: 0.3,
@@ -33,20 +31,28 @@ fn setup(
 
     loader.send(WorldLoadRequest {
         filename: "old_town".to_string(),
+        player_transform: Some(Transform::from_xyz(0., 1.5, 0.)),
     });
-
-    // Make sure the player is spawned after the level
-    delayed_spawner.send(
-        SpawnEvent::with_data(GameObject::Player, Transform::from_xyz(0., 1.5, 0.)).delay_frames(2),
-    );
 }
 
-fn show_loading_screen(mut egui_contexts: EguiContexts) {
+fn show_loading_screen(
+    mut egui_contexts: EguiContexts,
+    progress: Option<Res<WorldLoadProgress>>,
+    #[cfg(feature = "dev")] scene_viewer: Option<Res<SceneViewer>>,
+) {
+    // Levels opened in the scene viewer don't spawn a player
+    #[cfg(feature = "dev")]
+    if scene_viewer.is_some() {
+        return;
+    }
     egui::CentralPanel::default().show(egui_contexts.ctx_mut(), |ui| {
         ui.vertical_centered(|ui| {
             ui.add_space(100.0);
             ui.heading("Loading");
             ui.label("Spawning level...");
+            if let Some(progress) = progress {
+                ui.add(egui::ProgressBar::new(progress.fraction()).show_percentage());
+            }
             ui.add_space(10.0);
             #[cfg(feature = "wasm")]
             ui.add_space(40.0); // Spinner from CSS (build/web/styles.css) goes here.
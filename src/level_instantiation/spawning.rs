@@ -1,44 +1,156 @@
+use crate::console::AddConsoleCommandExt;
 use crate::level_instantiation::spawning::animation_link::link_animations;
-use crate::level_instantiation::spawning::despawn::{despawn, Despawn};
+use crate::level_instantiation::spawning::custom::{
+    spawn_custom_objects, CustomObject, CustomObjects, CustomSpawnEvent, ObjectKind,
+};
+#[cfg(feature = "native")]
+use crate::level_instantiation::spawning::data_spawner::register_added_data_spawners;
+use crate::level_instantiation::spawning::data_spawner::{
+    register_data_spawners, respawn_modified_data_spawners, DataSpawnerMeshes,
+};
+use crate::level_instantiation::spawning::despawn::{
+    despawn, despawn_by_name, handle_despawn_events, handle_respawn_events, respawn_by_name,
+    Despawn, DespawnEvent, RespawnEvent,
+};
+use crate::level_instantiation::spawning::layer::{update_layers, Layer};
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
 use crate::level_instantiation::spawning::post_spawn_modification::{
     despawn_removed, set_color, set_hidden, set_shadows,
 };
+use crate::level_instantiation::spawning::prefab::{
+    apply_material_variants, load_prefabs, save_prefabs, spawn_prefabs, tint_objects,
+    PrefabSaveRequest, PrefabSpawnEvent, Prefabs, TintedMaterials,
+};
+use crate::level_instantiation::spawning::spawn_queue::{
+    process_spawn_queue, SpawnBudget, SpawnQueue,
+};
 use crate::GameState;
 pub use animation_link::AnimationEntityLink;
+use anyhow::{bail, Context, Result};
 use bevy::prelude::*;
+use foxtrot_spawning::AddObjectSpawnerExt;
+use foxtrot_world_serialization::ObjectName;
+use seldom_fn_plugin::FnPluginExt;
 use serde::{Deserialize, Serialize};
-use spew::prelude::*;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 mod animation_link;
-mod despawn;
+pub mod custom;
+pub mod data_spawner;
+pub mod despawn;
+pub mod layer;
+pub mod metadata;
 pub mod objects;
+pub mod placement;
 mod post_spawn_modification;
+pub mod prefab;
+pub mod spawn_queue;
+pub mod spawner;
 
+/// Handles spawning of objects. Every spawnable object has a name, which is used to store it in levels:
+/// - Built-in objects are variants of [`GameObject`] with a spawner registered below in the [`foxtrot_spawning`] registry.
+/// - Other crates register their own objects by name with [`AddCustomObjectExt::add_custom_object`](custom::AddCustomObjectExt::add_custom_object),
+/// without touching the enum.
+/// - [`DataSpawner`](data_spawner::DataSpawner)s describe simple objects in `assets/spawners/` and are registered as custom objects.
+/// They are respawned when their file changes, and files added while the game runs are picked up as well.
+/// - [`Prefabs`] are groups of the above, loaded from data files. Each stamped copy can vary in scale, color and parts.
+///
+/// [`ObjectKind::from_name`] resolves a name to any of these, e.g. for the `spawn` console command.
+/// Levels and prefabs are spawned through the [`SpawnQueue`], which spawns at most [`SpawnBudget`] objects per frame.
+/// Objects with meshes share their mesh handles between all instances.
+/// Spawned objects are removed or reset with [`DespawnEvent`] and [`RespawnEvent`],
+/// which are also available as the `despawn` and `respawn` console commands.
+/// Objects can carry [`ObjectMetadata`] edited in the editor, which is saved with them and reattached when they are spawned.
+/// Spawners return the root entity they spawned, so the metadata is inserted on exactly that entity.
+/// Every object belongs to a [`Layer`], e.g. `lighting`, which the editor uses to hide or lock groups of objects at once.
 pub fn spawning_plugin(app: &mut App) {
-    app.add_plugin(SpewPlugin::<GameObject, Transform>::default())
+    app.fn_plugin(foxtrot_spawning::spawning_plugin::<GameObject>)
         .register_type::<Despawn>()
         .register_type::<AnimationEntityLink>()
-        .add_spawners((
-            (GameObject::Empty, objects::primitives::spawn_empty),
-            (GameObject::Box, objects::primitives::spawn_box),
-            (GameObject::Triangle, objects::primitives::spawn_triangle),
-            (GameObject::Sphere, objects::primitives::spawn_sphere),
-            (GameObject::Capsule, objects::primitives::spawn_capsule),
-            (GameObject::Sunlight, objects::sunlight::spawn),
-            (GameObject::PointLight, objects::point_light::spawn),
-            (GameObject::Npc, objects::npc::spawn),
-            (GameObject::Player, objects::player::spawn),
-            (GameObject::Level, objects::level::spawn),
-            (GameObject::Orb, objects::orb::spawn),
-            (GameObject::Camera, objects::camera::spawn),
-            (GameObject::Skydome, objects::skydome::spawn),
-        ))
+        .register_type::<GameObject>()
+        .register_type::<CustomObject>()
+        .init_resource::<CustomObjects>()
+        .add_event::<CustomSpawnEvent>()
+        .add_system(spawn_custom_objects)
+        .register_type::<SpawnBudget>()
+        .init_resource::<SpawnBudget>()
+        .init_resource::<SpawnQueue>()
+        .add_system(process_spawn_queue.in_base_set(CoreSet::PostUpdate))
+        .register_type::<ObjectMetadata>()
+        .register_type::<Layer>()
+        .add_system(update_layers)
+        .init_resource::<Prefabs>()
+        .init_resource::<TintedMaterials>()
+        .add_event::<PrefabSpawnEvent>()
+        .add_event::<PrefabSaveRequest>()
+        .add_startup_system(load_prefabs)
+        .add_systems((spawn_prefabs, save_prefabs, apply_material_variants, tint_objects))
+        .init_resource::<DataSpawnerMeshes>()
+        .add_system(register_data_spawners.in_schedule(OnExit(GameState::Loading)))
+        .add_system(respawn_modified_data_spawners.in_set(OnUpdate(GameState::Playing)))
+        .add_event::<DespawnEvent>()
+        .add_event::<RespawnEvent>()
+        .add_systems((handle_despawn_events, handle_respawn_events))
+        .add_console_command(
+            "despawn",
+            "Despawns all entities with the given name and their children, e.g. \"despawn Coin\"",
+            despawn_by_name,
+        )
+        .add_console_command(
+            "respawn",
+            "Spawns the objects with the given name again where they are, e.g. \"respawn Crate\"",
+            respawn_by_name,
+        )
+        .add_console_command(
+            "spawn",
+            "Spawns an object by name, e.g. \"spawn Coin 0 1 0\". Without arguments, lists all spawnable objects",
+            spawn_object,
+        )
+        .add_object_spawner(GameObject::Empty, objects::primitives::spawn_empty)
+        .add_object_spawner(GameObject::Box, objects::primitives::spawn_box)
+        .add_object_spawner(GameObject::Triangle, objects::primitives::spawn_triangle)
+        .add_object_spawner(GameObject::Sphere, objects::primitives::spawn_sphere)
+        .add_object_spawner(GameObject::Capsule, objects::primitives::spawn_capsule)
+        .add_object_spawner(GameObject::Sunlight, objects::sunlight::spawn)
+        .add_object_spawner(GameObject::PointLight, objects::point_light::spawn)
+        .add_object_spawner(GameObject::Npc, objects::npc::spawn)
+        .add_object_spawner(GameObject::Player, objects::player::spawn)
+        .add_object_spawner(GameObject::Level, objects::level::spawn)
+        .add_object_spawner(GameObject::Orb, objects::orb::spawn)
+        .add_object_spawner(GameObject::Camera, objects::camera::spawn)
+        .add_object_spawner(GameObject::Skydome, objects::skydome::spawn)
+        .add_object_spawner(GameObject::Crate, objects::wooden_crate::spawn)
+        .add_object_spawner(GameObject::Rope, objects::rope::spawn)
+        .add_object_spawner(GameObject::ZiplineAnchor, objects::zipline_anchor::spawn)
+        .add_object_spawner(GameObject::Teleporter, objects::teleporter::spawn)
+        .add_object_spawner(GameObject::Platform, objects::platform::spawn)
+        .add_object_spawner(GameObject::Coin, objects::coin::spawn)
+        .add_object_spawner(GameObject::GoalPortal, objects::goal_portal::spawn)
+        .add_object_spawner(GameObject::MovingPlatform, objects::moving_platform::spawn)
+        .add_object_spawner(GameObject::Rabbit, objects::critter::spawn_rabbit)
+        .add_object_spawner(GameObject::Bird, objects::critter::spawn_bird)
+        .add_object_spawner(GameObject::TerrainPatch, objects::terrain_patch::spawn)
+        .add_object_spawner(GameObject::Spline, objects::spline::spawn)
+        .add_object_spawner(GameObject::Volume, objects::volume::spawn)
+        .add_object_spawner(GameObject::Item, objects::item::spawn)
+        .add_object_spawner(GameObject::Waypoint, objects::waypoint::spawn)
+        .add_object_spawner(GameObject::BouncePad, objects::bounce_pad::spawn)
+        .add_object_spawner(GameObject::Hazard, objects::hazard::spawn)
+        .add_object_spawner(GameObject::Checkpoint, objects::checkpoint::spawn)
+        .add_object_spawner(GameObject::Conveyor, objects::conveyor::spawn)
+        .add_object_spawner(GameObject::ChallengeStart, objects::challenge_gate::spawn_start)
+        .add_object_spawner(GameObject::ChallengeFinish, objects::challenge_gate::spawn_finish)
+        .add_object_spawner(GameObject::Key, objects::key::spawn)
+        .add_object_spawner(GameObject::LockedDoor, objects::locked_door::spawn)
+        .add_object_spawner(GameObject::Shrine, objects::shrine::spawn)
         .add_systems((despawn, link_animations).in_set(OnUpdate(GameState::Playing)))
         .add_systems(
             (set_hidden, despawn_removed, set_color, set_shadows)
                 .in_set(OnUpdate(GameState::Playing)),
         );
+    #[cfg(feature = "native")]
+    app.add_system(register_added_data_spawners.in_set(OnUpdate(GameState::Playing)));
 }
 
 #[derive(
@@ -72,4 +184,76 @@ pub enum GameObject {
     Orb,
     Camera,
     Skydome,
+    Crate,
+    Rope,
+    ZiplineAnchor,
+    Teleporter,
+    Platform,
+    Coin,
+    GoalPortal,
+    MovingPlatform,
+    Rabbit,
+    Bird,
+    TerrainPatch,
+    Spline,
+    Volume,
+    Item,
+    Waypoint,
+    BouncePad,
+    Hazard,
+    Checkpoint,
+    Conveyor,
+    ChallengeStart,
+    ChallengeFinish,
+    Key,
+    LockedDoor,
+    Shrine,
+}
+
+fn spawn_object(world: &mut World, args: &[&str]) -> Result<String> {
+    let Some((name, coordinates)) = args.split_first() else {
+        let names: Vec<_> = GameObject::iter()
+            .map(|object| object.name())
+            .chain(world.resource::<CustomObjects>().names().map(str::to_owned))
+            .chain(world.resource::<Prefabs>().names().map(str::to_owned))
+            .collect();
+        return Ok(format!("Spawnable objects: {}", names.join(", ")));
+    };
+    let translation = match coordinates {
+        [] => Vec3::ZERO,
+        [x, y, z] => {
+            let parse = |value: &str| {
+                value
+                    .parse::<f32>()
+                    .with_context(|| format!("Failed to parse coordinate \"{value}\""))
+            };
+            Vec3::new(parse(x)?, parse(y)?, parse(z)?)
+        }
+        _ => bail!("Usage: spawn <name> [<x> <y> <z>]"),
+    };
+    let object = ObjectKind::from_name(world, name)
+        .with_context(|| format!("No spawnable object named \"{name}\""))?;
+    object.spawn(world, Transform::from_translation(translation));
+    Ok(format!("Spawning {object} at {translation}"))
+}
+
+impl GameObject {
+    /// The name under which the object is stored in levels and spawned by the `spawn` console command.
+    pub fn name(&self) -> String {
+        format!("{self:?}")
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::iter().find(|object| object.name() == name)
+    }
+}
+
+impl ObjectName for GameObject {
+    fn name(&self) -> String {
+        GameObject::name(self)
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        GameObject::from_name(name)
+    }
 }
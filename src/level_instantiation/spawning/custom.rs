@@ -0,0 +1,198 @@
+use crate::level_instantiation::spawning::prefab::{PrefabSpawnEvent, Prefabs};
+use crate::level_instantiation::spawning::GameObject;
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use foxtrot_spawning::spawn_object;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// Spawner of a custom object. Receives the object's transform and returns the root entity it spawned.
+pub type CustomObjectSpawner = foxtrot_spawning::ObjectSpawner;
+
+#[derive(Resource, Default)]
+pub struct CustomObjects(pub BTreeMap<String, CustomObjectSpawner>);
+
+impl CustomObjects {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
+/// Marks the root entity of a custom object so that it can be saved in levels.
+#[derive(
+    Debug, Clone, Eq, PartialEq, Component, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct CustomObject {
+    pub name: String,
+}
+
+/// The equivalent of a [`SpawnEvent`](crate::level_instantiation::spawning::spawner::SpawnEvent) for custom objects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomSpawnEvent {
+    pub name: String,
+    pub transform: Transform,
+}
+
+/// Either a built-in [`GameObject`], the name of a custom object or the name of a [`Prefab`](crate::level_instantiation::spawning::prefab::Prefabs),
+/// e.g. for letting the user choose what to spawn.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum ObjectKind {
+    Builtin(GameObject),
+    Custom(String),
+    Prefab(String),
+}
+
+impl Default for ObjectKind {
+    fn default() -> Self {
+        Self::Builtin(default())
+    }
+}
+
+impl ObjectKind {
+    /// Resolves a name as stored in levels. Built-in objects take precedence over custom objects, which take precedence over prefabs.
+    pub fn from_name(world: &World, name: &str) -> Option<Self> {
+        if let Some(object) = GameObject::from_name(name) {
+            return Some(Self::Builtin(object));
+        }
+        if world
+            .get_resource::<CustomObjects>()
+            .map_or(false, |custom_objects| custom_objects.0.contains_key(name))
+        {
+            return Some(Self::Custom(name.to_owned()));
+        }
+        world
+            .get_resource::<Prefabs>()
+            .filter(|prefabs| prefabs.0.contains_key(name))
+            .map(|_| Self::Prefab(name.to_owned()))
+    }
+
+    /// Spawns built-in and custom objects right away and returns their root entity.
+    /// Prefabs consist of several objects, so they are only requested through a [`PrefabSpawnEvent`] and `None` is returned.
+    pub fn spawn(&self, world: &mut World, transform: Transform) -> Option<Entity> {
+        match self {
+            ObjectKind::Builtin(object) => spawn_object(world, *object, transform),
+            ObjectKind::Custom(name) => spawn_custom_object(world, name, transform),
+            ObjectKind::Prefab(name) => {
+                world.send_event(PrefabSpawnEvent::new(name.clone(), transform));
+                None
+            }
+        }
+    }
+
+    /// Spawns the object through a command and inserts the bundle into its root entity in the same command,
+    /// e.g. a marker to find the object again. Prefabs have no single root entity, so they cannot be spawned this way.
+    pub fn spawn_with(&self, commands: &mut Commands, transform: Transform, bundle: impl Bundle) {
+        let object = self.clone();
+        commands.add(move |world: &mut World| {
+            if let ObjectKind::Prefab(name) = &object {
+                error!("Failed to spawn prefab \"{name}\" with extra components: Prefabs have no root entity");
+                return;
+            }
+            if let Some(entity) = object.spawn(world, transform) {
+                world.entity_mut(entity).insert(bundle);
+            }
+        });
+    }
+}
+
+impl Display for ObjectKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjectKind::Builtin(object) => write!(f, "{object:?}"),
+            ObjectKind::Custom(name) => write!(f, "{name}"),
+            ObjectKind::Prefab(name) => write!(f, "{name} (prefab)"),
+        }
+    }
+}
+
+pub trait AddCustomObjectExt {
+    /// Registers a spawnable object that is not part of [`GameObject`], e.g. from another crate.
+    /// The name is used to refer to the object in level files and shown in the editor,
+    /// so it must not be the name of a [`GameObject`].
+    /// The spawner receives the object's transform and returns the root entity it spawned.
+    fn add_custom_object<M>(
+        &mut self,
+        name: &str,
+        spawner: impl IntoSystem<Transform, Entity, M>,
+    ) -> &mut Self;
+}
+
+impl AddCustomObjectExt for App {
+    fn add_custom_object<M>(
+        &mut self,
+        name: &str,
+        spawner: impl IntoSystem<Transform, Entity, M>,
+    ) -> &mut Self {
+        self.world.add_custom_object(name, spawner);
+        self
+    }
+}
+
+/// Allows registering objects after startup, e.g. from data files.
+impl AddCustomObjectExt for World {
+    fn add_custom_object<M>(
+        &mut self,
+        name: &str,
+        spawner: impl IntoSystem<Transform, Entity, M>,
+    ) -> &mut Self {
+        if GameObject::from_name(name).is_some() {
+            error!(
+                "Failed to register custom object \"{name}\": A built-in object has the same name"
+            );
+            return self;
+        }
+        let mut spawner = IntoSystem::into_system(spawner);
+        spawner.initialize(self);
+        let previous = self
+            .get_resource_or_insert_with(CustomObjects::default)
+            .0
+            .insert(name.to_owned(), Box::new(spawner));
+        if previous.is_some() {
+            warn!("Custom object \"{name}\" was registered twice, only the last spawner is used");
+        }
+        self
+    }
+}
+
+pub(crate) fn spawn_custom_objects(
+    world: &mut World,
+    spawn_events: &mut SystemState<EventReader<CustomSpawnEvent>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_custom_objects").entered();
+    let requests: Vec<_> = spawn_events.get_mut(world).iter().cloned().collect();
+    for CustomSpawnEvent { name, transform } in requests {
+        spawn_custom_object(world, &name, transform);
+    }
+}
+
+/// Runs the spawner of a custom object right away and returns the entity it spawned.
+pub fn spawn_custom_object(world: &mut World, name: &str, transform: Transform) -> Option<Entity> {
+    let entity = world.resource_scope(|world, mut custom_objects: Mut<CustomObjects>| {
+        let Some(spawner) = custom_objects.0.get_mut(name) else {
+            error!(
+                "Failed to spawn custom object \"{name}\": No such object. Available objects: {:?}",
+                custom_objects.0.keys()
+            );
+            return None;
+        };
+        let entity = spawner.run(transform, world);
+        spawner.apply_buffers(world);
+        Some(entity)
+    })?;
+    match world.get_entity_mut(entity) {
+        Some(mut entity_mut) => {
+            entity_mut.insert(CustomObject {
+                name: name.to_owned(),
+            });
+            Some(entity)
+        }
+        None => {
+            error!("Spawner of custom object \"{name}\" returned an invalid entity");
+            None
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use crate::file_system_interaction::asset_loading::SpawnerAssets;
+use crate::level_instantiation::spawning::custom::{
+    AddCustomObjectExt, CustomObject, CustomSpawnEvent,
+};
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use bevy::reflect::{DynamicStruct, DynamicTupleStruct, TypeInfo, TypeUuid};
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Describes a simple object in `assets/spawners/<name>.spawner.ron`, e.g. a lamp or a prop,
+/// so that it can be spawned without writing a spawner in Rust.
+/// Every file is registered as a custom object named after the file, see [`AddCustomObjectExt`].
+/// Files added while the game is running are registered as well, and when a file changes,
+/// all objects spawned from it are respawned with the new description.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid)]
+#[uuid = "5f0b8d6e-1c2a-4e9b-9a47-3d6c2b8e7f10"]
+pub struct DataSpawner {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<DataMesh>,
+    /// Only used if there is a [`DataSpawner::mesh`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<DataMaterial>,
+    /// Asset path of a scene that is spawned as a child, e.g. `"scenes/lamp.glb#Scene0"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scene: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collider: Option<DataCollider>,
+    /// Objects with a collider but without a rigid body are static.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rigid_body: Option<DataRigidBody>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light: Option<DataLight>,
+    /// Names of registered marker components to insert via reflection, e.g. `["GoalPortal"]`.
+    /// The components are created with their default values.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DataMesh {
+    Box { size: Vec3 },
+    Sphere { radius: f32 },
+    Capsule { radius: f32, depth: f32 },
+    Cylinder { radius: f32, height: f32 },
+    Plane { size: f32 },
+}
+
+impl From<DataMesh> for Mesh {
+    fn from(mesh: DataMesh) -> Self {
+        match mesh {
+            DataMesh::Box { size } => shape::Box::new(size.x, size.y, size.z).into(),
+            DataMesh::Sphere { radius } => shape::UVSphere {
+                radius,
+                ..default()
+            }
+            .into(),
+            DataMesh::Capsule { radius, depth } => shape::Capsule {
+                radius,
+                depth,
+                ..default()
+            }
+            .into(),
+            DataMesh::Cylinder { radius, height } => shape::Cylinder {
+                radius,
+                height,
+                ..default()
+            }
+            .into(),
+            DataMesh::Plane { size } => shape::Plane::from_size(size).into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataMaterial {
+    pub base_color: Color,
+    /// Asset path of the base color texture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+    #[serde(default = "default_emissive")]
+    pub emissive: Color,
+    #[serde(default)]
+    pub metallic: f32,
+    #[serde(default = "default_roughness")]
+    pub perceptual_roughness: f32,
+}
+
+fn default_emissive() -> Color {
+    Color::BLACK
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DataCollider {
+    Cuboid { half_extents: Vec3 },
+    Ball { radius: f32 },
+    Capsule { half_height: f32, radius: f32 },
+    Cylinder { half_height: f32, radius: f32 },
+}
+
+impl From<DataCollider> for Collider {
+    fn from(collider: DataCollider) -> Self {
+        match collider {
+            DataCollider::Cuboid { half_extents } => {
+                Collider::cuboid(half_extents.x, half_extents.y, half_extents.z)
+            }
+            DataCollider::Ball { radius } => Collider::ball(radius),
+            DataCollider::Capsule {
+                half_height,
+                radius,
+            } => Collider::capsule_y(half_height, radius),
+            DataCollider::Cylinder {
+                half_height,
+                radius,
+            } => Collider::cylinder(half_height, radius),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DataRigidBody {
+    Fixed,
+    Dynamic,
+    KinematicPositionBased,
+}
+
+impl From<DataRigidBody> for RigidBody {
+    fn from(rigid_body: DataRigidBody) -> Self {
+        match rigid_body {
+            DataRigidBody::Fixed => RigidBody::Fixed,
+            DataRigidBody::Dynamic => RigidBody::Dynamic,
+            DataRigidBody::KinematicPositionBased => RigidBody::KinematicPositionBased,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DataLight {
+    pub color: Color,
+    /// Luminous power in lumens
+    pub intensity: f32,
+    pub range: f32,
+    #[serde(default)]
+    pub shadows: bool,
+    #[serde(default)]
+    pub offset: Vec3,
+}
+
+/// Time in seconds between checks of the spawner folder for new files.
+#[cfg(feature = "native")]
+const SPAWNER_FOLDER_POLL_INTERVAL: f32 = 1.0;
+
+/// Mesh and material of each [`DataSpawner`] by name, shared by all objects spawned from it.
+#[derive(Debug, Clone, Resource, Default)]
+pub(crate) struct DataSpawnerMeshes(HashMap<String, (Handle<Mesh>, Handle<StandardMaterial>)>);
+
+/// Registers every loaded [`DataSpawner`] as a custom object.
+pub(crate) fn register_data_spawners(world: &mut World) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("register_data_spawners").entered();
+    let spawners = world.resource::<SpawnerAssets>().spawners.clone();
+    for (path, handle) in spawners {
+        register_data_spawner(world, &path, handle);
+    }
+}
+
+/// Registers [`DataSpawner`]s whose files were added to `assets/spawners` after loading.
+#[cfg(feature = "native")]
+pub(crate) fn register_added_data_spawners(world: &mut World, mut since_poll: Local<f32>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("register_added_data_spawners").entered();
+    *since_poll += world.resource::<Time>().delta_seconds();
+    if *since_poll < SPAWNER_FOLDER_POLL_INTERVAL {
+        return;
+    }
+    *since_poll = 0.;
+    let asset_server = world.resource::<AssetServer>().clone();
+    // Files that are already loaded keep their handle, so this only starts loading the new ones
+    let handles = match asset_server.load_folder("spawners") {
+        Ok(handles) => handles,
+        Err(e) => {
+            error!("Failed to check for new data spawners: {e}");
+            return;
+        }
+    };
+    let known = &world.resource::<SpawnerAssets>().spawners;
+    let added: Vec<_> = handles
+        .into_iter()
+        .filter_map(|handle| {
+            let path = asset_server.get_handle_path(&handle)?;
+            let path = path.path().to_str()?.to_owned();
+            let is_new = get_spawner_name(&path).is_some() && !known.contains_key(&path);
+            is_new.then(|| (path, handle.typed::<DataSpawner>()))
+        })
+        .collect();
+    for (path, handle) in added {
+        world
+            .resource_mut::<SpawnerAssets>()
+            .spawners
+            .insert(path.clone(), handle.clone());
+        register_data_spawner(world, &path, handle);
+        info!("Registered new data spawner at {path}");
+    }
+}
+
+fn register_data_spawner(world: &mut World, path: &str, handle: Handle<DataSpawner>) {
+    let Some(name) = get_spawner_name(path) else {
+        error!("Failed to register data spawner at {path}: Invalid file name");
+        return;
+    };
+    let spawner_name = name.clone();
+    world.add_custom_object(
+        &name,
+        move |In(transform): In<Transform>,
+              mut commands: Commands,
+              spawners: Res<Assets<DataSpawner>>,
+              asset_server: Res<AssetServer>,
+              mut spawner_meshes: ResMut<DataSpawnerMeshes>,
+              mut meshes: ResMut<Assets<Mesh>>,
+              mut materials: ResMut<Assets<StandardMaterial>>| {
+            let entity = commands
+                .spawn((
+                    SpatialBundle::from_transform(transform),
+                    Name::new(spawner_name.clone()),
+                ))
+                .id();
+            let Some(spawner) = spawners.get(&handle) else {
+                error!("Failed to spawn \"{spawner_name}\": The spawner file failed to load");
+                return entity;
+            };
+            let mesh = spawner.mesh.map(|mesh| {
+                spawner_meshes
+                    .0
+                    .entry(spawner_name.clone())
+                    .or_insert_with(|| {
+                        let material = create_material(spawner.material.as_ref(), &asset_server);
+                        (meshes.add(mesh.into()), materials.add(material))
+                    })
+                    .clone()
+            });
+            spawn_data_object(&mut commands, entity, spawner, mesh, &asset_server);
+            entity
+        },
+    );
+}
+
+fn create_material(
+    material: Option<&DataMaterial>,
+    asset_server: &AssetServer,
+) -> StandardMaterial {
+    material
+        .map(|material| StandardMaterial {
+            base_color: material.base_color,
+            base_color_texture: material
+                .texture
+                .as_ref()
+                .map(|texture| asset_server.load(texture.as_str())),
+            emissive: material.emissive,
+            metallic: material.metallic,
+            perceptual_roughness: material.perceptual_roughness,
+            ..default()
+        })
+        .unwrap_or_default()
+}
+
+fn spawn_data_object(
+    commands: &mut Commands,
+    entity: Entity,
+    spawner: &DataSpawner,
+    mesh: Option<(Handle<Mesh>, Handle<StandardMaterial>)>,
+    asset_server: &AssetServer,
+) {
+    let mut entity_commands = commands.entity(entity);
+    if let Some(mesh) = mesh {
+        entity_commands.insert(mesh);
+    }
+    if let Some(collider) = spawner.collider {
+        entity_commands.insert(Collider::from(collider));
+    }
+    if let Some(rigid_body) = spawner.rigid_body {
+        entity_commands.insert(RigidBody::from(rigid_body));
+    }
+    let scene = spawner.scene.clone();
+    let light = spawner.light;
+    entity_commands.with_children(|parent| {
+        if let Some(scene) = scene {
+            parent.spawn(SceneBundle {
+                scene: asset_server.load(scene.as_str()),
+                ..default()
+            });
+        }
+        if let Some(light) = light {
+            parent.spawn(PointLightBundle {
+                point_light: PointLight {
+                    color: light.color,
+                    intensity: light.intensity,
+                    range: light.range,
+                    shadows_enabled: light.shadows,
+                    ..default()
+                },
+                transform: Transform::from_translation(light.offset),
+                ..default()
+            });
+        }
+    });
+
+    let components = spawner.components.clone();
+    if components.is_empty() {
+        return;
+    }
+    commands.add(move |world: &mut World| {
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let Some(mut entity) = world.get_entity_mut(entity) else {
+            return;
+        };
+        for name in components {
+            let Some((registration, reflect_component)) = registry
+                .get_with_short_name(&name)
+                .or_else(|| registry.get_with_name(&name))
+                .and_then(|registration| {
+                    Some((registration, registration.data::<ReflectComponent>()?))
+                })
+            else {
+                error!("Failed to insert component \"{name}\": No such registered component");
+                continue;
+            };
+            // The component is created from its default and then overwritten by a value without fields
+            match registration.type_info() {
+                TypeInfo::Struct(_) => {
+                    reflect_component.insert(&mut entity, &DynamicStruct::default())
+                }
+                TypeInfo::TupleStruct(_) => {
+                    reflect_component.insert(&mut entity, &DynamicTupleStruct::default())
+                }
+                _ => error!("Failed to insert component \"{name}\": Only structs are supported"),
+            }
+        }
+    });
+}
+
+/// Respawns all objects of a [`DataSpawner`] that was changed on disk.
+pub(crate) fn respawn_modified_data_spawners(
+    world: &mut World,
+    state: &mut SystemState<(
+        EventReader<AssetEvent<DataSpawner>>,
+        Res<SpawnerAssets>,
+        Query<(Entity, &CustomObject, &Transform)>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("respawn_modified_data_spawners").entered();
+    let (mut events, spawner_assets, objects) = state.get_mut(world);
+    let modified_names: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { handle } => spawner_assets
+                .spawners
+                .iter()
+                .find(|(_, spawner)| spawner == &handle)
+                .and_then(|(path, _)| get_spawner_name(path)),
+            _ => None,
+        })
+        .collect();
+    if modified_names.is_empty() {
+        return;
+    }
+    // The mesh or material may have changed, so they are created anew on the next spawn
+    let mut spawner_meshes = world.resource_mut::<DataSpawnerMeshes>();
+    for name in &modified_names {
+        spawner_meshes.0.remove(name);
+    }
+    let respawns: Vec<_> = objects
+        .iter()
+        .filter(|(_, object, _)| modified_names.contains(&object.name))
+        .map(|(entity, object, transform)| (entity, object.name.clone(), *transform))
+        .collect();
+    for (entity, name, transform) in respawns {
+        world.entity_mut(entity).despawn_recursive();
+        world.send_event(CustomSpawnEvent { name, transform });
+    }
+    info!("Reloaded data spawners {modified_names:?}");
+}
+
+/// `spawners/lamp.spawner.ron` is registered as `lamp`.
+fn get_spawner_name(path: &str) -> Option<String> {
+    Path::new(path)
+        .file_name()?
+        .to_str()?
+        .strip_suffix(".spawner.ron")
+        .map(str::to_owned)
+}
@@ -1,5 +1,12 @@
+use crate::level_instantiation::spawning::custom::{CustomObject, ObjectKind};
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::spawn_queue::SpawnQueue;
+use crate::level_instantiation::spawning::GameObject;
+use anyhow::{bail, Result};
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 use serde::{Deserialize, Serialize};
+use std::iter;
 
 #[derive(Debug, Component, Clone, PartialEq, Default, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
@@ -7,6 +14,35 @@ pub struct Despawn {
     pub recursive: bool,
 }
 
+/// Despawns the selected entities together with their children.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DespawnEvent(pub ObjectSelector);
+
+/// Despawns the objects the selected entities belong to and spawns them again at their current transform,
+/// i.e. the one that would be saved in the level. Useful for resetting an object's runtime state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RespawnEvent(pub ObjectSelector);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ObjectSelector {
+    /// All entities with exactly this [`Name`].
+    Name(String),
+    Entity(Entity),
+}
+
+impl ObjectSelector {
+    fn select(&self, names: &Query<(Entity, &Name)>) -> Vec<Entity> {
+        match self {
+            ObjectSelector::Name(name) => names
+                .iter()
+                .filter(|(_, entity_name)| entity_name.as_str() == name)
+                .map(|(entity, _)| entity)
+                .collect(),
+            ObjectSelector::Entity(entity) => vec![*entity],
+        }
+    }
+}
+
 pub fn despawn(mut commands: Commands, despawn_query: Query<(Entity, &Despawn, &Children)>) {
     for (entity, despawn, children) in despawn_query.iter() {
         if despawn.recursive {
@@ -18,3 +54,101 @@ pub fn despawn(mut commands: Commands, despawn_query: Query<(Entity, &Despawn, &
         }
     }
 }
+
+pub(crate) fn handle_despawn_events(
+    mut commands: Commands,
+    mut despawn_events: EventReader<DespawnEvent>,
+    names: Query<(Entity, &Name)>,
+    parents: Query<&Parent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_despawn_events").entered();
+    for DespawnEvent(selector) in despawn_events.iter() {
+        let selected: HashSet<_> = selector.select(&names).into_iter().collect();
+        if selected.is_empty() {
+            warn!("Nothing to despawn for {selector:?}");
+            continue;
+        }
+        // Children of selected entities are already despawned together with their parent
+        for &entity in selected.iter().filter(|&&entity| {
+            !parents
+                .iter_ancestors(entity)
+                .any(|ancestor| selected.contains(&ancestor))
+        }) {
+            if let Some(entity_commands) = commands.get_entity(entity) {
+                entity_commands.despawn_recursive();
+            }
+        }
+    }
+}
+
+pub(crate) fn handle_respawn_events(
+    mut commands: Commands,
+    mut respawn_events: EventReader<RespawnEvent>,
+    names: Query<(Entity, &Name)>,
+    objects: Query<(
+        Option<&GameObject>,
+        Option<&CustomObject>,
+        Option<&Transform>,
+        Option<&ObjectMetadata>,
+    )>,
+    parents: Query<&Parent>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_respawn_events").entered();
+    let mut respawned = HashSet::new();
+    for RespawnEvent(selector) in respawn_events.iter() {
+        for entity in selector.select(&names) {
+            // Selecting a part of an object, e.g. a mesh of an NPC, refers to the whole object
+            let root = iter::once(entity)
+                .chain(parents.iter_ancestors(entity))
+                .find(|&ancestor| {
+                    objects
+                        .get(ancestor)
+                        .map_or(false, |(object, custom, _, _)| {
+                            object.is_some() || custom.is_some()
+                        })
+                });
+            let Some(root) = root else {
+                warn!("Cannot respawn {selector:?}: {entity:?} is not part of a spawned object");
+                continue;
+            };
+            if !respawned.insert(root) {
+                continue;
+            }
+            let Ok((object, custom, transform, metadata)) = objects.get(root) else {
+                continue;
+            };
+            let kind = match (object, custom) {
+                (Some(object), _) => ObjectKind::Builtin(*object),
+                (None, Some(custom)) => ObjectKind::Custom(custom.name.clone()),
+                (None, None) => continue,
+            };
+            spawn_queue.push_with_metadata(
+                kind,
+                transform.copied().unwrap_or_default(),
+                metadata.cloned().unwrap_or_default(),
+            );
+            commands.entity(root).despawn_recursive();
+        }
+    }
+}
+
+pub(crate) fn despawn_by_name(world: &mut World, args: &[&str]) -> Result<String> {
+    if args.is_empty() {
+        bail!("Usage: despawn <name>");
+    }
+    let name = args.join(" ");
+    world.send_event(DespawnEvent(ObjectSelector::Name(name.clone())));
+    Ok(format!("Despawning \"{name}\""))
+}
+
+pub(crate) fn respawn_by_name(world: &mut World, args: &[&str]) -> Result<String> {
+    if args.is_empty() {
+        bail!("Usage: respawn <name>");
+    }
+    let name = args.join(" ");
+    world.send_event(RespawnEvent(ObjectSelector::Name(name.clone())));
+    Ok(format!("Respawning \"{name}\""))
+}
@@ -0,0 +1,86 @@
+use crate::level_instantiation::spawning::custom::CustomObject;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::GameObject;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the layer an object belongs to, e.g. `props`.
+pub const LAYER_KEY: &str = "layer";
+
+/// Layers offered in the editor. Objects can also be put on layers with other names by editing their metadata.
+pub const LAYERS: [&str; 4] = ["terrain", "props", "gameplay", "lighting"];
+
+/// Named group of objects in a level, e.g. all lights, used to organize large levels.
+/// An object's layer is stored in its [`ObjectMetadata`] under [`LAYER_KEY`] only if it differs from [`Layer::default_for`],
+/// so levels saved before layers existed keep their format.
+#[derive(
+    Debug, Clone, Eq, PartialEq, Hash, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Layer(pub String);
+
+impl Layer {
+    /// The layer of an object whose metadata does not name one.
+    pub fn default_for(object: Option<&GameObject>) -> Self {
+        let name = match object {
+            Some(GameObject::Level | GameObject::TerrainPatch | GameObject::Skydome) => "terrain",
+            Some(GameObject::Sunlight | GameObject::PointLight) => "lighting",
+            Some(
+                GameObject::Empty
+                | GameObject::Box
+                | GameObject::Triangle
+                | GameObject::Sphere
+                | GameObject::Capsule
+                | GameObject::Crate
+                | GameObject::Rope
+                | GameObject::Platform,
+            )
+            | None => "props",
+            Some(_) => "gameplay",
+        };
+        Self(name.to_owned())
+    }
+
+    /// Puts the object on this layer by editing its metadata.
+    pub fn write_metadata(&self, object: Option<&GameObject>, metadata: &mut ObjectMetadata) {
+        if *self == Self::default_for(object) {
+            metadata.remove(LAYER_KEY);
+        } else {
+            metadata.insert(LAYER_KEY, self.0.clone());
+        }
+    }
+}
+
+pub(crate) fn update_layers(
+    mut commands: Commands,
+    objects: Query<
+        (
+            Entity,
+            Option<&GameObject>,
+            Option<&ObjectMetadata>,
+            Option<&Layer>,
+        ),
+        (
+            Or<(With<GameObject>, With<CustomObject>)>,
+            Or<(
+                Changed<ObjectMetadata>,
+                Added<GameObject>,
+                Added<CustomObject>,
+            )>,
+        ),
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_layers").entered();
+    for (entity, object, metadata, layer) in objects.iter() {
+        let read = metadata
+            .and_then(|metadata| metadata.get(LAYER_KEY))
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| Layer(name.to_owned()))
+            .unwrap_or_else(|| Layer::default_for(object));
+        if layer != Some(&read) {
+            commands.entity(entity).insert(read);
+        }
+    }
+}
@@ -0,0 +1 @@
+pub use foxtrot_world_serialization::ObjectMetadata;
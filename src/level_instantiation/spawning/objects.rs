@@ -1,17 +1,183 @@
+use crate::file_system_interaction::asset_loading::AnimationAssets;
+use crate::movement::animation_graph::{AnimationGraph, AnimationNode};
+use crate::movement::footsteps::FOOTSTEP_MARKER;
+use crate::movement::general_movement::{Emote, EmoteAnimations};
+use bevy::animation::{EntityPath, Keyframes, VariableCurve};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
 use bevy_rapier3d::prelude::*;
 use bitflags::bitflags;
 
+pub mod bounce_pad;
 pub mod camera;
+pub mod challenge_gate;
+pub mod checkpoint;
+pub mod coin;
+pub mod conveyor;
+pub mod critter;
+pub mod goal_portal;
+pub mod hazard;
+pub mod item;
+pub mod key;
 pub mod level;
+pub mod locked_door;
+pub mod moving_platform;
 pub mod npc;
 pub mod orb;
+pub mod platform;
 pub mod player;
 pub mod point_light;
 pub mod primitives;
+pub mod rope;
+pub mod shrine;
 pub mod skydome;
+pub mod spline;
 pub mod sunlight;
+pub mod teleporter;
+pub mod terrain_patch;
+pub mod volume;
+pub mod waypoint;
+pub mod wooden_crate;
+pub mod zipline_anchor;
 mod util;
 
+/// Bone of the character model below which an [`UpperBodyAnimation`](crate::movement::general_movement::UpperBodyAnimation) takes over.
+pub const CHARACTER_UPPER_BODY_BONE: &str = "b_Spine01_02";
+/// Horizontal speed in m/s at which the walk animation of the character model matches its movement.
+const CHARACTER_WALK_SPEED: f32 = 5.;
+
+/// Animation graph of the character model shared by the player and NPCs.
+/// The fox model only ships with locomotion clips, so it has no one-shots until dedicated clips exist.
+pub(crate) fn create_character_animation_graph(animations: &AnimationAssets) -> AnimationGraph {
+    AnimationGraph::new(
+        AnimationNode::new(animations.character_idle.clone()),
+        AnimationNode::new(animations.character_walking.clone())
+            .with_reference_speed(CHARACTER_WALK_SPEED)
+            // The paws touch the ground in pairs
+            .with_marker(0., FOOTSTEP_MARKER)
+            .with_marker(0.5, FOOTSTEP_MARKER),
+        AnimationNode::new(animations.character_running.clone()),
+    )
+}
+
+/// Bones from the animated root of the character model down to where its arms and neck branch off.
+const CHARACTER_SHOULDER_PATH: [&str; 6] = [
+    "root",
+    "_rootJoint",
+    "b_Root_00",
+    "b_Hip_01",
+    "b_Spine01_02",
+    "b_Spine02_03",
+];
+const CHARACTER_RIGHT_UPPER_ARM_REST: Quat =
+    Quat::from_xyzw(0.000467, -0.000446, -0.712179, 0.701997);
+const CHARACTER_RIGHT_FORE_ARM_REST: Quat = Quat::from_xyzw(0., 0., 0.037126, 0.999311);
+const CHARACTER_HEAD_REST: Quat = Quat::from_xyzw(0., 0., -0.400285, 0.916391);
+
+const WAVE_CLIP_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(AnimationClip::TYPE_UUID, 0x6c1e_52a7_93d4_0b18);
+const NOD_CLIP_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(AnimationClip::TYPE_UUID, 0x1f87_c3e0_5a29_d64b);
+const POINT_CLIP_HANDLE: HandleUntyped =
+    HandleUntyped::weak_from_u64(AnimationClip::TYPE_UUID, 0xa4d9_0e6b_27f1_8c35);
+
+/// Gestures of the character model shared by the player and NPCs.
+/// The fox model only ships with locomotion clips, so the gestures are keyed on top of its rest pose.
+pub(crate) fn create_character_emote_animations(
+    animation_clips: &mut Assets<AnimationClip>,
+) -> EmoteAnimations {
+    let clips: [(Emote, HandleUntyped, fn() -> AnimationClip); 3] = [
+        (Emote::Wave, WAVE_CLIP_HANDLE, create_wave_clip),
+        (Emote::Nod, NOD_CLIP_HANDLE, create_nod_clip),
+        (Emote::Point, POINT_CLIP_HANDLE, create_point_clip),
+    ];
+    EmoteAnimations(
+        clips
+            .into_iter()
+            .map(|(emote, handle, create_clip)| {
+                let handle = handle.typed();
+                animation_clips.get_or_insert_with(handle.clone_weak(), create_clip);
+                (emote, handle)
+            })
+            .collect(),
+    )
+}
+
+fn create_wave_clip() -> AnimationClip {
+    let mut clip = AnimationClip::default();
+    clip.add_curve_to_path(
+        character_bone_path(&["b_RightUpperArm_06"]),
+        bend_curve(
+            CHARACTER_RIGHT_UPPER_ARM_REST,
+            0.2,
+            &[0., 1.2, 1.2, 1.2, 1.2, 1.2, 0.],
+        ),
+    );
+    clip.add_curve_to_path(
+        character_bone_path(&["b_RightUpperArm_06", "b_RightForeArm_07"]),
+        bend_curve(
+            CHARACTER_RIGHT_FORE_ARM_REST,
+            0.2,
+            &[0., 0.6, -0.2, 0.6, -0.2, 0.6, 0.],
+        ),
+    );
+    clip
+}
+
+fn create_nod_clip() -> AnimationClip {
+    let mut clip = AnimationClip::default();
+    clip.add_curve_to_path(
+        character_bone_path(&["b_Neck_04", "b_Head_05"]),
+        bend_curve(CHARACTER_HEAD_REST, 0.2, &[0., 0.35, 0., 0.35, 0.]),
+    );
+    clip
+}
+
+fn create_point_clip() -> AnimationClip {
+    let mut clip = AnimationClip::default();
+    clip.add_curve_to_path(
+        character_bone_path(&["b_RightUpperArm_06"]),
+        bend_curve(
+            CHARACTER_RIGHT_UPPER_ARM_REST,
+            0.3,
+            &[0., 0.9, 0.9, 0.9, 0.],
+        ),
+    );
+    clip.add_curve_to_path(
+        character_bone_path(&["b_RightUpperArm_06", "b_RightForeArm_07"]),
+        // Stretch the arm out fully
+        bend_curve(
+            CHARACTER_RIGHT_FORE_ARM_REST,
+            0.3,
+            &[0., -0.07, -0.07, -0.07, 0.],
+        ),
+    );
+    clip
+}
+
+fn character_bone_path(bones: &[&'static str]) -> EntityPath {
+    EntityPath {
+        parts: CHARACTER_SHOULDER_PATH
+            .iter()
+            .chain(bones)
+            .map(|&bone| Name::new(bone))
+            .collect(),
+    }
+}
+
+/// Keys the bone bending around its local Z axis by the given angles in radians, one keyframe every `step` seconds.
+fn bend_curve(rest: Quat, step: f32, angles: &[f32]) -> VariableCurve {
+    VariableCurve {
+        keyframe_timestamps: (0..angles.len()).map(|index| index as f32 * step).collect(),
+        keyframes: Keyframes::Rotation(
+            angles
+                .iter()
+                .map(|&angle| rest * Quat::from_rotation_z(angle))
+                .collect(),
+        ),
+    }
+}
+
 bitflags! {
     pub struct GameCollisionGroup: u32 {
         const PLAYER = 1 << 0;
@@ -0,0 +1,75 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::bounce_pad::{BouncePad, BouncePadModel};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const RADIUS: f32 = 0.8;
+pub const HEIGHT: f32 = 0.2;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0xc81f3e05a7d2946b);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Cylinder {
+            radius: RADIUS,
+            height: HEIGHT,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x5e9a20d7c4b1f836);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.3, 0.8, 0.3),
+        emissive: Color::rgb(0.1, 0.4, 0.1),
+        perceptual_roughness: 0.6,
+        ..default()
+    });
+    handle
+}
+
+/// A pad that launches everything touching it straight up, see [`bounce_pad_plugin`](crate::world_interaction::bounce_pad::bounce_pad_plugin).
+/// Its speed and direction are set in its metadata.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Bounce Pad"),
+            Collider::cylinder(HEIGHT / 2., RADIUS),
+            BouncePad::default(),
+            ObjectMetadata::default(),
+            GameObject::BouncePad,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_mesh_handle(&mut meshes),
+                    material: get_or_add_material_handle(&mut materials),
+                    ..default()
+                },
+                Name::new("Bounce Pad Model"),
+                BouncePadModel::default(),
+            ));
+            parent.spawn((
+                Name::new("Bounce Pad Trigger"),
+                TransformBundle::from_transform(Transform::from_translation(Vec3::Y * HEIGHT)),
+                Collider::cylinder(HEIGHT / 2., RADIUS * 0.9),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+            ));
+        })
+        .id()
+}
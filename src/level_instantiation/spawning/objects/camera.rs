@@ -1,25 +1,30 @@
 use crate::level_instantiation::spawning::GameObject;
 use crate::player_control::actions::create_camera_action_input_manager_bundle;
-use crate::player_control::camera::IngameCamera;
+use crate::player_control::camera::{IngameCamera, SideView};
 use bevy::prelude::*;
 use bevy_dolly::prelude::*;
+use bevy_kira_audio::prelude::AudioReceiver;
 
-pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) {
-    commands.spawn((
-        IngameCamera::default(),
-        Camera3dBundle {
-            transform,
-            ..default()
-        },
-        Rig::builder()
-            .with(Position::new(default()))
-            .with(YawPitch::new())
-            .with(Smooth::new_position_rotation(default(), default()))
-            .with(Arm::new(default()))
-            .with(LookAt::new(default()).tracking_predictive(true))
-            .build(),
-        create_camera_action_input_manager_bundle(),
-        Name::new("Main Camera"),
-        GameObject::Camera,
-    ));
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    commands
+        .spawn((
+            IngameCamera::default(),
+            SideView::default(),
+            Camera3dBundle {
+                transform,
+                ..default()
+            },
+            AudioReceiver,
+            Rig::builder()
+                .with(Position::new(default()))
+                .with(YawPitch::new())
+                .with(Smooth::new_position_rotation(default(), default()))
+                .with(Arm::new(default()))
+                .with(LookAt::new(default()).tracking_predictive(true))
+                .build(),
+            create_camera_action_input_manager_bundle(),
+            Name::new("Main Camera"),
+            GameObject::Camera,
+        ))
+        .id()
 }
@@ -0,0 +1,140 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::challenge::{ChallengeGate, ChallengeGateKind};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+/// Inner width of the gate in m
+pub const WIDTH: f32 = 3.;
+pub const HEIGHT: f32 = 2.5;
+pub const POST_SIZE: f32 = 0.2;
+/// Depth of the area in which walking through the gate is detected
+pub const TRIGGER_DEPTH: f32 = 0.5;
+
+fn get_or_add_post_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x5e93b1c7a20d4f68);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(POST_SIZE, HEIGHT, POST_SIZE))
+    })
+}
+
+fn get_or_add_banner_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0xa1c64e08f3b9d275);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(WIDTH + 2. * POST_SIZE, 0.4, POST_SIZE))
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+    kind: ChallengeGateKind,
+) -> Handle<StandardMaterial> {
+    let (id, color) = match kind {
+        ChallengeGateKind::Start => (0x7f20c95d13e6ab84, Color::rgb(0.2, 0.8, 0.3)),
+        ChallengeGateKind::Finish => (0x2c8e5b4f90d137a6, Color::rgb(0.9, 0.9, 0.9)),
+    };
+    let handle = HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, id).typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: color,
+        emissive: color * 0.2,
+        ..default()
+    });
+    handle
+}
+
+/// The gate that starts the countdown of a timed challenge, see [`challenge_plugin`](crate::world_interaction::challenge::challenge_plugin).
+/// It is paired with a finish gate by the challenge's name in its metadata.
+pub(crate) fn spawn_start(
+    In(transform): In<Transform>,
+    commands: Commands,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    spawn(
+        transform,
+        ChallengeGateKind::Start,
+        commands,
+        meshes,
+        materials,
+    )
+}
+
+/// The gate that stops the timer of a running challenge with the same name.
+pub(crate) fn spawn_finish(
+    In(transform): In<Transform>,
+    commands: Commands,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    spawn(
+        transform,
+        ChallengeGateKind::Finish,
+        commands,
+        meshes,
+        materials,
+    )
+}
+
+fn spawn(
+    transform: Transform,
+    kind: ChallengeGateKind,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    let (name, game_object) = match kind {
+        ChallengeGateKind::Start => ("Challenge Start", GameObject::ChallengeStart),
+        ChallengeGateKind::Finish => ("Challenge Finish", GameObject::ChallengeFinish),
+    };
+    let post_mesh = get_or_add_post_mesh_handle(&mut meshes);
+    let material = get_or_add_material_handle(&mut materials, kind);
+    let post_offset = (WIDTH + POST_SIZE) / 2.;
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new(name),
+            ChallengeGate { kind, ..default() },
+            ObjectMetadata::default(),
+            game_object,
+        ))
+        .with_children(|parent| {
+            for x in [-post_offset, post_offset] {
+                parent.spawn((
+                    PbrBundle {
+                        mesh: post_mesh.clone(),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(x, HEIGHT / 2., 0.),
+                        ..default()
+                    },
+                    Name::new("Challenge Gate Post"),
+                    Collider::cuboid(POST_SIZE / 2., HEIGHT / 2., POST_SIZE / 2.),
+                ));
+            }
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_banner_mesh_handle(&mut meshes),
+                    material,
+                    transform: Transform::from_xyz(0., HEIGHT, 0.),
+                    ..default()
+                },
+                Name::new("Challenge Gate Banner"),
+            ));
+            parent.spawn((
+                Name::new("Challenge Gate Trigger"),
+                TransformBundle::from_transform(Transform::from_xyz(0., HEIGHT / 2., 0.)),
+                Collider::cuboid(WIDTH / 2., HEIGHT / 2., TRIGGER_DEPTH / 2.),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
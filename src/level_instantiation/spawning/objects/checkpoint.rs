@@ -0,0 +1,117 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::checkpoint::{Checkpoint, CheckpointFlag};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const POLE_HEIGHT: f32 = 2.;
+pub const POLE_RADIUS: f32 = 0.05;
+pub const FLAG_SIZE: Vec3 = Vec3::new(0.6, 0.4, 0.04);
+/// Radius of the area in which the player activates the checkpoint
+pub const TRIGGER_RADIUS: f32 = 1.;
+
+fn get_or_add_pole_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x6c0e83b5f29d4a17);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Cylinder {
+            radius: POLE_RADIUS,
+            height: POLE_HEIGHT,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_flag_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x91f4a7d3c58e0b26);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(FLAG_SIZE.x, FLAG_SIZE.y, FLAG_SIZE.z))
+    })
+}
+
+fn get_or_add_pole_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x2d8b5e61a4f3c970);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.8, 0.8, 0.85),
+        metallic: 0.8,
+        perceptual_roughness: 0.3,
+        ..default()
+    });
+    handle
+}
+
+fn get_or_add_flag_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xb37a0c9e5d162f48);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.9, 0.2, 0.15),
+        emissive: Color::rgb(0.3, 0.05, 0.02),
+        ..default()
+    });
+    handle
+}
+
+/// A flag pole that the player respawns at after touching it, see [`checkpoint_plugin`](crate::world_interaction::checkpoint::checkpoint_plugin).
+/// The flag is raised while the checkpoint is activated.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    let lowered = FLAG_SIZE.y / 2.;
+    let raised = POLE_HEIGHT - FLAG_SIZE.y / 2.;
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Checkpoint"),
+            Checkpoint::default(),
+            GameObject::Checkpoint,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_pole_mesh_handle(&mut meshes),
+                    material: get_or_add_pole_material_handle(&mut materials),
+                    transform: Transform::from_translation(Vec3::Y * POLE_HEIGHT / 2.),
+                    ..default()
+                },
+                Name::new("Checkpoint Pole"),
+                Collider::cylinder(POLE_HEIGHT / 2., POLE_RADIUS),
+            ));
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_flag_mesh_handle(&mut meshes),
+                    material: get_or_add_flag_material_handle(&mut materials),
+                    transform: Transform::from_xyz(FLAG_SIZE.x / 2. + POLE_RADIUS, lowered, 0.),
+                    ..default()
+                },
+                Name::new("Checkpoint Flag"),
+                CheckpointFlag { lowered, raised },
+            ));
+            parent.spawn((
+                Name::new("Checkpoint Trigger"),
+                TransformBundle::from_transform(Transform::from_translation(
+                    Vec3::Y * POLE_HEIGHT / 2.,
+                )),
+                Collider::cylinder(POLE_HEIGHT / 2., TRIGGER_RADIUS),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
@@ -0,0 +1,68 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::collectible::Collectible;
+use bevy::pbr::NotShadowReceiver;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+
+pub const RADIUS: f32 = 0.2;
+pub const THICKNESS: f32 = 0.04;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x6e2d8a4f0b71c935);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Cylinder {
+            radius: RADIUS,
+            height: THICKNESS,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xc48f1e6a9d2b7053);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(1.0, 0.8, 0.2),
+        emissive: Color::rgb(0.4, 0.3, 0.0),
+        metallic: 0.9,
+        perceptual_roughness: 0.3,
+        ..default()
+    });
+    handle
+}
+
+/// A coin standing upright that is pulled towards the player, see [`collectible_plugin`](crate::world_interaction::collectible::collectible_plugin).
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Coin"),
+            Collectible { value: 1 },
+            GameObject::Coin,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_mesh_handle(&mut meshes),
+                    material: get_or_add_material_handle(&mut materials),
+                    // The cylinder's flat sides face up by default
+                    transform: Transform::from_rotation(Quat::from_rotation_x(
+                        std::f32::consts::FRAC_PI_2,
+                    )),
+                    ..default()
+                },
+                NotShadowReceiver,
+            ));
+        })
+        .id()
+}
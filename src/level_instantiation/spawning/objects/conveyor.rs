@@ -0,0 +1,89 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::movement::conveyor::Conveyor;
+use crate::shader::{ConveyorBelt, ConveyorMaterial};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+/// Size of the conveyor in m, the belt runs along the z axis
+pub const SIZE: Vec3 = Vec3::new(1.5, 0.4, 4.);
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x7e2b904fd1a6c835);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(SIZE.x, SIZE.y, SIZE.z))
+    })
+}
+
+fn get_or_add_belt_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x0c5d7a3e9f21b684);
+    // Scaled to the belt's size, so the belt material can map its uvs to m
+    mesh_assets.get_or_add(MESH_HANDLE, || Mesh::from(shape::Plane::from_size(1.)))
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x48f1c0b7e62d9a35);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.35, 0.35, 0.38),
+        metallic: 0.6,
+        perceptual_roughness: 0.5,
+        ..default()
+    });
+    handle
+}
+
+/// A conveyor belt that moves everything on top of it, see [`conveyor_plugin`](crate::movement::conveyor::conveyor_plugin).
+/// Its speed and direction are set in its metadata.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut conveyor_materials: ResMut<Assets<ConveyorMaterial>>,
+) -> Entity {
+    // Every belt scrolls on its own
+    let belt_material = conveyor_materials.add(ConveyorMaterial {
+        belt: ConveyorBelt {
+            color: Color::rgb(0.15, 0.15, 0.15),
+            size: Vec2::new(SIZE.x, SIZE.z),
+            ..default()
+        },
+    });
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: get_or_add_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(&mut materials),
+                transform,
+                ..default()
+            },
+            Name::new("Conveyor"),
+            RigidBody::Fixed,
+            Collider::cuboid(SIZE.x / 2., SIZE.y / 2., SIZE.z / 2.),
+            Conveyor::default(),
+            ObjectMetadata::default(),
+            GameObject::Conveyor,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                MaterialMeshBundle {
+                    mesh: get_or_add_belt_mesh_handle(&mut meshes),
+                    material: belt_material,
+                    // Slightly above the box to avoid z-fighting
+                    transform: Transform::from_xyz(0., SIZE.y / 2. + 0.005, 0.)
+                        .with_scale(Vec3::new(SIZE.x, 1., SIZE.z)),
+                    ..default()
+                },
+                Name::new("Conveyor Belt"),
+            ));
+        })
+        .id()
+}
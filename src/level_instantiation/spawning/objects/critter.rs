@@ -0,0 +1,121 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::movement::critter::{Critter, Flocking};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+pub const RABBIT_RADIUS: f32 = 0.12;
+pub const RABBIT_HEIGHT: f32 = 0.15;
+pub const BIRD_RADIUS: f32 = 0.07;
+pub const BIRD_LENGTH: f32 = 0.16;
+
+fn get_or_add_rabbit_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x2b9e64d1a7f0c385);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Capsule {
+            radius: RABBIT_RADIUS,
+            depth: RABBIT_HEIGHT,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_bird_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x8d03f5a6c1e27b49);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Capsule {
+            radius: BIRD_RADIUS,
+            depth: BIRD_LENGTH,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+    id: u64,
+    color: Color,
+) -> Handle<StandardMaterial> {
+    let handle = HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, id).typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: color,
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    handle
+}
+
+/// A small animal that hops around on the ground and runs away from the player, see [`Critter`].
+pub(crate) fn spawn_rabbit(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: get_or_add_rabbit_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(
+                    &mut materials,
+                    0x5c7a19e3f4b2068d,
+                    Color::rgb(0.55, 0.42, 0.3),
+                ),
+                transform,
+                ..default()
+            },
+            Name::new("Rabbit"),
+            RigidBody::Dynamic,
+            Collider::capsule_y(RABBIT_HEIGHT / 2., RABBIT_RADIUS),
+            LockedAxes::ROTATION_LOCKED,
+            Velocity::default(),
+            Critter::default(),
+            GameObject::Rabbit,
+        ))
+        .id()
+}
+
+/// A small bird that flies around in flocks with other birds, see [`Flocking`].
+/// It is not affected by gravity and stays at the height it was spawned at.
+pub(crate) fn spawn_bird(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Bird"),
+            RigidBody::KinematicVelocityBased,
+            Collider::capsule_z(BIRD_LENGTH / 2., BIRD_RADIUS),
+            Velocity::default(),
+            Critter {
+                wander_speed: 2.5,
+                flee_speed: 6.,
+                flee_radius: 6.,
+                home_radius: 15.,
+                ..default()
+            },
+            Flocking::default(),
+            GameObject::Bird,
+        ))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: get_or_add_bird_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(
+                    &mut materials,
+                    0xe1486b07d92c5fa3,
+                    Color::rgb(0.25, 0.3, 0.45),
+                ),
+                // The capsule is upright by default, but birds fly nose first
+                transform: Transform::from_rotation(Quat::from_rotation_x(FRAC_PI_2)),
+                ..default()
+            });
+        })
+        .id()
+}
@@ -0,0 +1,79 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::level_stats::GoalPortal;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+use std::f32::consts::FRAC_PI_2;
+
+pub const RADIUS: f32 = 1.2;
+pub const RING_RADIUS: f32 = 0.1;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x5fa2c8193e7b046d);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Torus {
+            radius: RADIUS,
+            ring_radius: RING_RADIUS,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xa03d7e5b61c9f824);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.3, 0.9, 0.5),
+        emissive: Color::rgb(0.2, 1.0, 0.4),
+        ..default()
+    });
+    handle
+}
+
+/// An upright ring that ends the level when the player walks through it,
+/// see [`level_stats_plugin`](crate::world_interaction::level_stats::level_stats_plugin).
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Goal Portal"),
+            GoalPortal,
+            GameObject::GoalPortal,
+        ))
+        .with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: get_or_add_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(&mut materials),
+                // The torus lies flat by default
+                transform: Transform::from_xyz(0., RADIUS, 0.)
+                    .with_rotation(Quat::from_rotation_x(FRAC_PI_2)),
+                ..default()
+            });
+            parent.spawn((
+                Name::new("Goal Portal Trigger"),
+                TransformBundle::from_transform(
+                    Transform::from_xyz(0., RADIUS, 0.)
+                        .with_rotation(Quat::from_rotation_x(FRAC_PI_2)),
+                ),
+                Collider::cylinder(0.2, RADIUS - RING_RADIUS),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
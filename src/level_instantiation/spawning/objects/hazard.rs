@@ -0,0 +1,61 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::health::Damage;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const WIDTH: f32 = 1.5;
+pub const HEIGHT: f32 = 0.3;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x3a6fd21c9e804b57);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(WIDTH, HEIGHT, WIDTH))
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xd47b9e2a1f0c6835);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.5, 0.1, 0.1),
+        emissive: Color::rgb(0.3, 0.02, 0.02),
+        perceptual_roughness: 0.4,
+        metallic: 0.6,
+        ..default()
+    });
+    handle
+}
+
+/// A patch of spikes that hurts every character touching it, see [`health_plugin`](crate::world_interaction::health::health_plugin).
+/// Its damage is set in its metadata. Larger hazards like lava are volumes of the kind `hazard`.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: get_or_add_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(&mut materials),
+                transform,
+                ..default()
+            },
+            Name::new("Hazard"),
+            // Taller than the mesh so that characters standing on top are hurt too
+            Collider::cuboid(WIDTH / 2., HEIGHT, WIDTH / 2.),
+            Sensor,
+            Damage::default(),
+            ObjectMetadata::default(),
+            GameObject::Hazard,
+        ))
+        .id()
+}
@@ -0,0 +1,76 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::inventory::Item;
+use bevy::pbr::NotShadowReceiver;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const RADIUS: f32 = 0.15;
+/// Radius of the sensor that picks the item up when the player touches it.
+pub const PICKUP_RADIUS: f32 = 0.5;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x51c9e07a3b8d26f4);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::try_from(shape::Icosphere {
+            radius: RADIUS,
+            subdivisions: 0,
+        })
+        .expect("Failed to create item mesh")
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xa2f7d4610e95b83c);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.6, 0.3, 0.9),
+        emissive: Color::rgb(0.3, 0.1, 0.5),
+        perceptual_roughness: 0.2,
+        ..default()
+    });
+    handle
+}
+
+/// A floating gem the player picks up by touching it, see [`inventory_plugin`](crate::world_interaction::inventory::inventory_plugin).
+/// Which item it stands for is set in its metadata.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: get_or_add_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(&mut materials),
+                transform,
+                ..default()
+            },
+            Name::new("Item"),
+            Item::default(),
+            NotShadowReceiver,
+            GameObject::Item,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Item Pickup Collider"),
+                TransformBundle::default(),
+                Collider::ball(PICKUP_RADIUS),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
@@ -0,0 +1,106 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::inventory::Item;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const RING_RADIUS: f32 = 0.08;
+pub const SHAFT_LENGTH: f32 = 0.25;
+/// Radius of the sensor that picks the key up when the player touches it.
+pub const PICKUP_RADIUS: f32 = 0.5;
+
+fn get_or_add_ring_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x9b3e6d10c4a7f258);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Torus {
+            radius: RING_RADIUS,
+            ring_radius: 0.02,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_shaft_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x3f71a8c9e25d0b46);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(SHAFT_LENGTH, 0.03, 0.03))
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xe08d5b27a61c94f3);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.95, 0.75, 0.2),
+        emissive: Color::rgb(0.3, 0.2, 0.02),
+        metallic: 0.9,
+        perceptual_roughness: 0.25,
+        ..default()
+    });
+    handle
+}
+
+/// A key the player picks up by touching it, see [`lock_plugin`](crate::world_interaction::lock::lock_plugin).
+/// Like any [`Item`], which lock it opens is set in its metadata, e.g. `item: key_red`.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    let material = get_or_add_material_handle(&mut materials);
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Key"),
+            Item {
+                id: "key".to_owned(),
+                amount: 1,
+            },
+            ObjectMetadata::default(),
+            GameObject::Key,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_ring_mesh_handle(&mut meshes),
+                    material: material.clone(),
+                    // Stand the ring up
+                    transform: Transform::from_rotation(Quat::from_rotation_x(
+                        std::f32::consts::FRAC_PI_2,
+                    )),
+                    ..default()
+                },
+                Name::new("Key Ring"),
+            ));
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_shaft_mesh_handle(&mut meshes),
+                    material,
+                    transform: Transform::from_xyz(RING_RADIUS + SHAFT_LENGTH / 2., 0., 0.),
+                    ..default()
+                },
+                Name::new("Key Shaft"),
+            ));
+            parent.spawn((
+                Name::new("Key Pickup Collider"),
+                TransformBundle::default(),
+                Collider::ball(PICKUP_RADIUS),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
@@ -6,17 +6,19 @@ pub(crate) fn spawn(
     In(transform): In<Transform>,
     mut commands: Commands,
     scene_handles: Res<SceneAssets>,
-) {
-    commands.spawn((
-        SceneBundle {
-            scene: scene_handles.level.clone(),
-            transform,
-            ..default()
-        },
-        Name::new("Level"),
-        Imported,
-        GameObject::Level,
-    ));
+) -> Entity {
+    commands
+        .spawn((
+            SceneBundle {
+                scene: scene_handles.level.clone(),
+                transform,
+                ..default()
+            },
+            Name::new("Level"),
+            Imported,
+            GameObject::Level,
+        ))
+        .id()
 }
 
 #[derive(Component)]
@@ -0,0 +1,77 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::interactions_ui::Interactable;
+use crate::world_interaction::lock::{Lock, LockedDoorHinge};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const SIZE: Vec3 = Vec3::new(1.2, 2.2, 0.15);
+/// Radius in m of the area in which the player can try to unlock the door
+pub const INTERACTION_RADIUS: f32 = 1.5;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x64c2f0e9b81a3d57);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Box::new(SIZE.x, SIZE.y, SIZE.z))
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xc7a1538d0f2e6b94);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.45, 0.28, 0.15),
+        perceptual_roughness: 0.8,
+        ..default()
+    });
+    handle
+}
+
+/// A door that swings open once the player uses the right key on it, see [`lock_plugin`](crate::world_interaction::lock::lock_plugin).
+/// The key it needs is set in its metadata. The door swings around its left edge.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    let mut interactable = Interactable::new("interaction.unlock", INTERACTION_RADIUS);
+    interactable.prompt_height = SIZE.y / 2.;
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Locked Door"),
+            Lock::default(),
+            interactable,
+            ObjectMetadata::default(),
+            GameObject::LockedDoor,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    SpatialBundle::from_transform(Transform::from_xyz(-SIZE.x / 2., 0., 0.)),
+                    Name::new("Locked Door Hinge"),
+                    LockedDoorHinge,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        PbrBundle {
+                            mesh: get_or_add_mesh_handle(&mut meshes),
+                            material: get_or_add_material_handle(&mut materials),
+                            transform: Transform::from_xyz(SIZE.x / 2., SIZE.y / 2., 0.),
+                            ..default()
+                        },
+                        Name::new("Locked Door Panel"),
+                        RigidBody::KinematicPositionBased,
+                        Collider::cuboid(SIZE.x / 2., SIZE.y / 2., SIZE.z / 2.),
+                    ));
+                });
+        })
+        .id()
+}
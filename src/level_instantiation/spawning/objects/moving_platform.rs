@@ -0,0 +1,27 @@
+use crate::level_instantiation::spawning::objects::platform;
+use crate::level_instantiation::spawning::GameObject;
+use crate::movement::moving_platform::MovingPlatform;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// A [`platform`] that moves along the waypoints of its [`MovingPlatform`], which are set via the object's metadata.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    platform::spawn_named(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        transform,
+        "Moving Platform",
+    )
+    .insert((
+        RigidBody::KinematicPositionBased,
+        MovingPlatform::default(),
+        GameObject::MovingPlatform,
+    ))
+    .id()
+}
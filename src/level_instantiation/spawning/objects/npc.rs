@@ -1,11 +1,15 @@
 use crate::file_system_interaction::asset_loading::{AnimationAssets, SceneAssets};
-use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::objects::{
+    create_character_animation_graph, create_character_emote_animations, CHARACTER_UPPER_BODY_BONE,
+};
 use crate::level_instantiation::spawning::GameObject;
-use crate::movement::general_movement::{CharacterAnimations, CharacterControllerBundle, Model};
-use crate::movement::navigation::Follower;
+use crate::movement::behavior::{Behavior, BehaviorTarget};
+use crate::movement::general_movement::{CharacterControllerBundle, Model, UpperBodyAnimation};
+use crate::movement::ledge_grab::LedgeGrabbing;
+use crate::movement::surface::GroundSurface;
 use crate::world_interaction::dialog::{DialogId, DialogTarget};
+use crate::world_interaction::interactions_ui::Interactable;
 use bevy::prelude::*;
-use bevy_rapier3d::prelude::*;
 use std::f32::consts::TAU;
 
 pub const HEIGHT: f32 = 0.4;
@@ -16,7 +20,8 @@ pub(crate) fn spawn(
     mut commands: Commands,
     animations: Res<AnimationAssets>,
     scene_handles: Res<SceneAssets>,
-) {
+    mut animation_clips: ResMut<Assets<AnimationClip>>,
+) -> Entity {
     let entity = commands
         .spawn((
             PbrBundle {
@@ -25,36 +30,63 @@ pub(crate) fn spawn(
             },
             Name::new("NPC"),
             CharacterControllerBundle::capsule(HEIGHT, RADIUS),
-            Follower,
-            CharacterAnimations {
-                idle: animations.character_idle.clone(),
-                walk: animations.character_walking.clone(),
-                aerial: animations.character_running.clone(),
+            GroundSurface::default(),
+            Behavior::Follow {
+                target: BehaviorTarget::Player,
+                distance: 3.,
+            },
+            create_character_animation_graph(&animations),
+            create_character_emote_animations(&mut animation_clips),
+            UpperBodyAnimation::new(CHARACTER_UPPER_BODY_BONE),
+            LedgeGrabbing {
+                animation: Some(animations.character_running.clone()),
+                ..default()
             },
             DialogTarget {
                 dialog_id: DialogId::new("follower"),
             },
+            Interactable::new("interaction.talk", RADIUS * 5.).with_sensor_height(HEIGHT),
             GameObject::Npc,
         ))
-        .with_children(|parent| {
-            parent.spawn((
-                Name::new("NPC Dialog Collider"),
-                Collider::cylinder(HEIGHT / 2., RADIUS * 5.),
-                Sensor,
-                ActiveEvents::COLLISION_EVENTS,
-                ActiveCollisionTypes::DYNAMIC_DYNAMIC,
-                CollisionGroups::new(
-                    GameCollisionGroup::OTHER.into(),
-                    GameCollisionGroup::PLAYER.into(),
-                ),
-            ));
-        })
         .id();
+    spawn_model(&mut commands, entity, transform, &scene_handles);
+    entity
+}
 
+/// Spawns an NPC without dialog that does not follow the player, for background crowds.
+/// It is not a [`GameObject`], so it is not saved in levels.
+pub(crate) fn spawn_ambient(
+    commands: &mut Commands,
+    transform: Transform,
+    animations: &AnimationAssets,
+    scene_handles: &SceneAssets,
+) -> Entity {
+    let entity = commands
+        .spawn((
+            PbrBundle {
+                transform,
+                ..default()
+            },
+            Name::new("Ambient NPC"),
+            CharacterControllerBundle::capsule(HEIGHT, RADIUS),
+            GroundSurface::default(),
+            create_character_animation_graph(animations),
+        ))
+        .id();
+    spawn_model(commands, entity, transform, scene_handles);
+    entity
+}
+
+fn spawn_model(
+    commands: &mut Commands,
+    target: Entity,
+    transform: Transform,
+    scene_handles: &SceneAssets,
+) {
     commands
         .spawn((
-            Model { target: entity },
-            SpatialBundle::default(),
+            Model { target },
+            SpatialBundle::from_transform(transform),
             Name::new("NPC Model Parent"),
         ))
         .with_children(|parent| {
@@ -1,3 +1,4 @@
+use crate::file_system_interaction::audio::{Sound, SoundEmitter};
 use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
 use crate::level_instantiation::spawning::GameObject;
 use crate::shader::Materials;
@@ -21,7 +22,7 @@ pub(crate) fn spawn(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     materials: Res<Materials>,
-) {
+) -> Entity {
     let mesh_handle = get_or_add_mesh_handle(&mut meshes);
     commands
         .spawn((
@@ -34,6 +35,9 @@ pub(crate) fn spawn(
             Name::new("Orb"),
             NotShadowCaster,
             NotShadowReceiver,
+            SoundEmitter {
+                sound: Sound::OrbHum,
+            },
             GameObject::Orb,
         ))
         .with_children(|parent| {
@@ -47,5 +51,6 @@ pub(crate) fn spawn(
                 },
                 ..default()
             },));
-        });
+        })
+        .id()
 }
@@ -0,0 +1,67 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+use std::borrow::Cow;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x4c0a7e29f13b58d6);
+    // Same extents as the box collider, so that the scale works the same way for both
+    mesh_assets.get_or_add(MESH_HANDLE, || Mesh::from(shape::Cube { size: 2.0 }))
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x91d3b6e05a2c47f8);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.6, 0.62, 0.65),
+        perceptual_roughness: 0.8,
+        ..default()
+    });
+    handle
+}
+
+/// A visible box with a collider. Use the transform's scale to set its half extents.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    spawn_named(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        transform,
+        "Platform",
+    )
+    .id()
+}
+
+/// Spawns a platform under a custom name, e.g. to give it a marker such as `[door: ...]`.
+pub(crate) fn spawn_named<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    transform: Transform,
+    name: impl Into<Cow<'static, str>>,
+) -> EntityCommands<'w, 's, 'a> {
+    commands.spawn((
+        PbrBundle {
+            mesh: get_or_add_mesh_handle(meshes),
+            material: get_or_add_material_handle(materials),
+            transform,
+            ..default()
+        },
+        Name::new(name),
+        RigidBody::Fixed,
+        Collider::cuboid(1., 1., 1.),
+        GameObject::Platform,
+    ))
+}
@@ -1,24 +1,37 @@
 use crate::file_system_interaction::asset_loading::{AnimationAssets, SceneAssets};
-use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::file_system_interaction::config::GameConfig;
+use crate::level_instantiation::spawning::objects::{
+    create_character_animation_graph, create_character_emote_animations, GameCollisionGroup,
+    CHARACTER_UPPER_BODY_BONE,
+};
 use crate::level_instantiation::spawning::GameObject;
-use crate::movement::general_movement::{CharacterAnimations, CharacterControllerBundle, Model};
+use crate::movement::dash::Dash;
+use crate::movement::general_movement::{
+    AlignToSurface, CharacterControllerBundle, Jumping, Model, UpperBodyAnimation, Walking,
+};
+use crate::movement::ledge_grab::LedgeGrabbing;
+use crate::movement::surface::GroundSurface;
+use crate::movement::wall_jump::{MovementState, WallJumping};
 use crate::player_control::actions::{
     create_player_action_input_manager_bundle, create_ui_action_input_manager_bundle,
 };
 use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::health::Health;
+use crate::world_interaction::inventory::Inventory;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use std::f32::consts::TAU;
 
-pub const HEIGHT: f32 = 0.4;
-pub const RADIUS: f32 = 0.3;
-
 pub(crate) fn spawn(
     In(transform): In<Transform>,
     mut commands: Commands,
     animations: Res<AnimationAssets>,
     scene_handles: Res<SceneAssets>,
-) {
+    config: Res<GameConfig>,
+    mut animation_clips: ResMut<Assets<AnimationClip>>,
+) -> Entity {
+    let body = &config.player.body;
+    let movement = &config.player.movement;
     let entity = commands
         .spawn((
             PbrBundle {
@@ -28,11 +41,43 @@ pub(crate) fn spawn(
             Player,
             Name::new("Player"),
             Ccd::enabled(),
-            CharacterControllerBundle::capsule(HEIGHT, RADIUS),
-            CharacterAnimations {
-                idle: animations.character_idle.clone(),
-                walk: animations.character_walking.clone(),
-                aerial: animations.character_running.clone(),
+            CharacterControllerBundle {
+                mass: ColliderMassProperties::Mass(body.mass),
+                walking: Walking {
+                    ground_acceleration: movement.ground_acceleration,
+                    sprinting_acceleration: movement.sprinting_acceleration,
+                    aerial_acceleration: movement.aerial_acceleration,
+                    ..default()
+                },
+                jumping: Jumping {
+                    speed: movement.jump_speed,
+                    coyote_time: movement.coyote_time,
+                    buffer_time: movement.jump_buffer_time,
+                    cut_factor: movement.jump_cut_factor,
+                    ..default()
+                },
+                ..CharacterControllerBundle::capsule(body.height, body.radius)
+            },
+            create_character_animation_graph(&animations),
+            create_character_emote_animations(&mut animation_clips),
+            UpperBodyAnimation::new(CHARACTER_UPPER_BODY_BONE),
+            WallJumping {
+                max_slide_speed: movement.wall_slide_speed,
+                push_speed: movement.wall_jump_push_speed,
+                jump_speed: movement.jump_speed,
+                ..default()
+            },
+            MovementState::default(),
+            Dash::new(
+                movement.dash_speed,
+                movement.dash_duration,
+                movement.dash_cooldown,
+            ),
+            LedgeGrabbing {
+                mantle_duration: movement.mantle_duration,
+                // The fox model has no climbing clip, so the running one stands in
+                animation: Some(animations.character_running.clone()),
+                ..default()
             },
             CollisionGroups::new(
                 GameCollisionGroup::PLAYER.into(),
@@ -43,6 +88,15 @@ pub(crate) fn spawn(
             GameObject::Player,
         ))
         .id();
+    // Bundles have a maximum length
+    commands.entity(entity).insert((
+        Inventory::default(),
+        GroundSurface::default(),
+        Health::new(config.health.player_max_health),
+    ));
+    if movement.align_to_surface {
+        commands.entity(entity).insert(AlignToSurface);
+    }
 
     commands
         .spawn((
@@ -55,13 +109,14 @@ pub(crate) fn spawn(
                 SceneBundle {
                     scene: scene_handles.character.clone(),
                     transform: Transform {
-                        translation: Vec3::new(0., -HEIGHT / 2. - RADIUS, 0.),
+                        translation: Vec3::new(0., -body.height / 2. - body.radius, 0.),
                         rotation: Quat::from_rotation_y(TAU / 2.),
-                        scale: Vec3::splat(0.01),
+                        scale: Vec3::splat(body.model_scale),
                     },
                     ..default()
                 },
                 Name::new("Player Model"),
             ));
         });
+    entity
 }
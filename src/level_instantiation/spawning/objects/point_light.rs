@@ -2,21 +2,23 @@ use crate::level_instantiation::spawning::GameObject;
 
 use bevy::prelude::*;
 
-pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) {
-    commands.spawn((
-        PointLightBundle {
-            point_light: PointLight {
-                color: Color::WHITE,
-                intensity: 1.0,
-                range: 1.0,
-                radius: 1.0,
-                shadows_enabled: true,
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    commands
+        .spawn((
+            PointLightBundle {
+                point_light: PointLight {
+                    color: Color::WHITE,
+                    intensity: 1.0,
+                    range: 1.0,
+                    radius: 1.0,
+                    shadows_enabled: true,
+                    ..default()
+                },
+                transform,
                 ..default()
             },
-            transform,
-            ..default()
-        },
-        Name::new("Light"),
-        GameObject::PointLight,
-    ));
+            Name::new("Light"),
+            GameObject::PointLight,
+        ))
+        .id()
 }
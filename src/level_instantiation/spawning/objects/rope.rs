@@ -0,0 +1,15 @@
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::rope::Rope;
+use bevy::prelude::*;
+
+/// Spawns the anchor of a rope. The segments hanging from it are built by the [`rope_plugin`](crate::world_interaction::rope::rope_plugin).
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Rope"),
+            Rope::default(),
+            GameObject::Rope,
+        ))
+        .id()
+}
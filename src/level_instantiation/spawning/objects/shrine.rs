@@ -0,0 +1,113 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::consumable::{Shrine, ShrineCrystal};
+use crate::world_interaction::interactions_ui::Interactable;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const PEDESTAL_RADIUS: f32 = 0.35;
+pub const PEDESTAL_HEIGHT: f32 = 1.;
+pub const CRYSTAL_RADIUS: f32 = 0.2;
+/// Radius of the area in which the player can pray at the shrine
+pub const INTERACTION_RADIUS: f32 = 1.5;
+
+fn get_or_add_pedestal_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x4e92c1a7d3b85f06);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Cylinder {
+            radius: PEDESTAL_RADIUS,
+            height: PEDESTAL_HEIGHT,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_crystal_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0xa15f7e3c09d2b684);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        // Few faces make the sphere look cut like a crystal
+        Mesh::from(shape::UVSphere {
+            radius: CRYSTAL_RADIUS,
+            sectors: 6,
+            stacks: 4,
+        })
+    })
+}
+
+fn get_or_add_pedestal_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x7c3d09b5e8a1f264);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.55, 0.55, 0.5),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    handle
+}
+
+fn get_or_add_crystal_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xd28a64f1b0c7e359);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.4, 0.8, 1.),
+        emissive: Color::rgb(0.2, 0.6, 0.9),
+        perceptual_roughness: 0.1,
+        ..default()
+    });
+    handle
+}
+
+/// A stone pedestal with a floating crystal that grants a consumable's effects to the player praying at it,
+/// see [`consumable_plugin`](crate::world_interaction::consumable::consumable_plugin).
+/// The consumable and cooldown are set in its metadata, e.g. `shrine: jump_mushroom` and `cooldown: 60`.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    let mut interactable = Interactable::new("interaction.pray", INTERACTION_RADIUS);
+    interactable.prompt_height = PEDESTAL_HEIGHT + CRYSTAL_RADIUS * 3.;
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Shrine"),
+            Shrine::default(),
+            interactable,
+            ObjectMetadata::default(),
+            GameObject::Shrine,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_pedestal_mesh_handle(&mut meshes),
+                    material: get_or_add_pedestal_material_handle(&mut materials),
+                    transform: Transform::from_xyz(0., PEDESTAL_HEIGHT / 2., 0.),
+                    ..default()
+                },
+                Name::new("Shrine Pedestal"),
+                Collider::cylinder(PEDESTAL_HEIGHT / 2., PEDESTAL_RADIUS),
+            ));
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_crystal_mesh_handle(&mut meshes),
+                    material: get_or_add_crystal_material_handle(&mut materials),
+                    transform: Transform::from_xyz(0., PEDESTAL_HEIGHT + CRYSTAL_RADIUS * 1.5, 0.),
+                    ..default()
+                },
+                Name::new("Shrine Crystal"),
+                ShrineCrystal,
+            ));
+        })
+        .id()
+}
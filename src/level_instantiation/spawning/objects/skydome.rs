@@ -27,19 +27,21 @@ pub(crate) fn spawn(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     materials: Res<Materials>,
-) {
+) -> Entity {
     let mesh_handle = get_or_add_mesh_handle(&mut meshes);
-    commands.spawn((
-        Name::new("Skydome"),
-        NotShadowCaster,
-        NotShadowReceiver,
-        Skydome,
-        MaterialMeshBundle {
-            mesh: mesh_handle,
-            material: materials.skydome.clone(),
-            transform,
-            ..default()
-        },
-        GameObject::Skydome,
-    ));
+    commands
+        .spawn((
+            Name::new("Skydome"),
+            NotShadowCaster,
+            NotShadowReceiver,
+            Skydome,
+            MaterialMeshBundle {
+                mesh: mesh_handle,
+                material: materials.skydome.clone(),
+                transform,
+                ..default()
+            },
+            GameObject::Skydome,
+        ))
+        .id()
 }
@@ -0,0 +1,17 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::GameObject;
+use crate::movement::spline::Spline;
+use bevy::prelude::*;
+
+/// An invisible [`Spline`] whose control points are read from and written to its metadata.
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Spline"),
+            Spline::default(),
+            ObjectMetadata::default(),
+            GameObject::Spline,
+        ))
+        .id()
+}
@@ -3,24 +3,26 @@ use crate::level_instantiation::spawning::GameObject;
 use bevy::pbr::CascadeShadowConfigBuilder;
 use bevy::prelude::*;
 
-pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) {
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
     // directional 'sun' light
-    commands.spawn((
-        DirectionalLightBundle {
-            directional_light: DirectionalLight {
-                shadows_enabled: true,
+    commands
+        .spawn((
+            DirectionalLightBundle {
+                directional_light: DirectionalLight {
+                    shadows_enabled: true,
+                    ..default()
+                },
+                cascade_shadow_config: CascadeShadowConfigBuilder {
+                    first_cascade_far_bound: 7.0,
+                    maximum_distance: 100.0,
+                    ..default()
+                }
+                .into(),
+                transform,
                 ..default()
             },
-            cascade_shadow_config: CascadeShadowConfigBuilder {
-                first_cascade_far_bound: 7.0,
-                maximum_distance: 100.0,
-                ..default()
-            }
-            .into(),
-            transform,
-            ..default()
-        },
-        Name::new("Light"),
-        GameObject::Sunlight,
-    ));
+            Name::new("Light"),
+            GameObject::Sunlight,
+        ))
+        .id()
 }
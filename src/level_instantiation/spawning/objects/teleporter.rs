@@ -0,0 +1,71 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const RADIUS: f32 = 0.6;
+pub const HEIGHT: f32 = 0.1;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x3b7e91c4d0a6f285);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Cylinder {
+            radius: RADIUS,
+            height: HEIGHT,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x8d24f6a1b95e0c37);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.2, 0.3, 0.6),
+        emissive: Color::rgb(0.1, 0.4, 1.0),
+        ..default()
+    });
+    handle
+}
+
+/// Spawns a pad linked to all other pads spawned this way. Rename it in the editor to link it to a different group,
+/// see [`teleporter_plugin`](crate::world_interaction::teleporter::teleporter_plugin).
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: get_or_add_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(&mut materials),
+                transform,
+                ..default()
+            },
+            Name::new("Teleporter [teleporter: default]"),
+            Collider::cylinder(HEIGHT / 2., RADIUS),
+            GameObject::Teleporter,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Teleporter Trigger"),
+                TransformBundle::from_transform(Transform::from_translation(Vec3::Y * 0.5)),
+                Collider::cylinder(0.5, RADIUS * 0.8),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
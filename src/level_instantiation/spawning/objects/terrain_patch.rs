@@ -0,0 +1,41 @@
+use crate::level_instantiation::spawning::GameObject;
+use crate::level_instantiation::terrain::TerrainPatch;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x37f0b8c2d64e159a);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.45, 0.36, 0.25),
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+    handle
+}
+
+/// A flat patch of ground that can be dug into. Use the transform's scale to set its half extents.
+/// Every patch gets its own mesh and collider, which are built by the [`terrain_plugin`](crate::level_instantiation::terrain::terrain_plugin).
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                material: get_or_add_material_handle(&mut materials),
+                transform,
+                ..default()
+            },
+            Name::new("Terrain Patch"),
+            RigidBody::Fixed,
+            TerrainPatch::default(),
+            GameObject::TerrainPatch,
+        ))
+        .id()
+}
@@ -0,0 +1,22 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::volume::Volume;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// An invisible sensor [`Volume`] whose shape and kind are read from and written to its metadata.
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    let volume = Volume::default();
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Volume"),
+            volume.shape.collider(),
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            volume,
+            ObjectMetadata::default(),
+            GameObject::Volume,
+        ))
+        .id()
+}
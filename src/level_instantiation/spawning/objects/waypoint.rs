@@ -0,0 +1,20 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::GameObject;
+use crate::movement::patrol_path::Waypoint;
+use bevy::prelude::*;
+
+/// An invisible point of a patrol path, see [`patrol_path_plugin`](crate::movement::patrol_path::patrol_path_plugin).
+pub(crate) fn spawn(In(transform): In<Transform>, mut commands: Commands) -> Entity {
+    commands.spawn(bundle(transform, Waypoint::default())).id()
+}
+
+/// Lets the editor add waypoints that already belong to a path.
+pub(crate) fn bundle(transform: Transform, waypoint: Waypoint) -> impl Bundle {
+    (
+        SpatialBundle::from_transform(transform),
+        Name::new("Waypoint"),
+        waypoint,
+        ObjectMetadata::default(),
+        GameObject::Waypoint,
+    )
+}
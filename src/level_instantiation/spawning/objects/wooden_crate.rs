@@ -0,0 +1,70 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::carrying::Carryable;
+use crate::world_interaction::puzzle::SocketKey;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+pub const SIZE: f32 = 0.4;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x6d1e5c6a0b3f2e41);
+    mesh_assets.get_or_add(MESH_HANDLE, || Mesh::from(shape::Cube { size: SIZE }))
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x2b9f0c4e7d1a8c35);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgb(0.55, 0.36, 0.2),
+        perceptual_roughness: 0.9,
+        ..default()
+    });
+    handle
+}
+
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            PbrBundle {
+                mesh: get_or_add_mesh_handle(&mut meshes),
+                material: get_or_add_material_handle(&mut materials),
+                transform,
+                ..default()
+            },
+            Name::new("Crate"),
+            RigidBody::Dynamic,
+            Collider::cuboid(SIZE / 2., SIZE / 2., SIZE / 2.),
+            ColliderMassProperties::Mass(5.0),
+            ReadMassProperties::default(),
+            Velocity::default(),
+            Carryable::default(),
+            SocketKey("crate".to_owned()),
+            GameObject::Crate,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Crate Interaction Collider"),
+                Collider::ball(SIZE * 1.5),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                ActiveCollisionTypes::DYNAMIC_DYNAMIC,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
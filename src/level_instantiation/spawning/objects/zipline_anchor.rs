@@ -0,0 +1,75 @@
+use crate::level_instantiation::spawning::objects::util::MeshAssetsExt;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::level_instantiation::spawning::GameObject;
+use crate::world_interaction::zipline::ZiplineAnchor;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+
+/// Height in m of the pole below the point where the cable is attached.
+pub const POLE_HEIGHT: f32 = 2.5;
+pub const POLE_RADIUS: f32 = 0.1;
+
+fn get_or_add_mesh_handle(mesh_assets: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0xe5019a7c32b4d86f);
+    mesh_assets.get_or_add(MESH_HANDLE, || {
+        Mesh::from(shape::Cylinder {
+            radius: POLE_RADIUS,
+            height: POLE_HEIGHT,
+            ..default()
+        })
+    })
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x17c6e0b9a4f2d358);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || Color::rgb(0.4, 0.3, 0.2).into());
+    handle
+}
+
+/// Spawns a pole whose top is at the given transform.
+pub(crate) fn spawn(
+    In(transform): In<Transform>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Name::new("Zipline Anchor"),
+            ZiplineAnchor,
+            GameObject::ZiplineAnchor,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: get_or_add_mesh_handle(&mut meshes),
+                    material: get_or_add_material_handle(&mut materials),
+                    transform: Transform::from_translation(-Vec3::Y * POLE_HEIGHT / 2.),
+                    ..default()
+                },
+                Name::new("Zipline Pole"),
+                Collider::cylinder(POLE_HEIGHT / 2., POLE_RADIUS),
+            ));
+            parent.spawn((
+                Name::new("Zipline Interaction Collider"),
+                TransformBundle::from_transform(Transform::from_translation(
+                    -Vec3::Y * POLE_HEIGHT / 2.,
+                )),
+                Collider::cylinder(POLE_HEIGHT / 2., 1.0),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        })
+        .id()
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+const GHOST_RADIUS: f32 = 0.25;
+
+/// A translucent sphere that previews where an object will be placed. It starts out hidden until a position is found.
+/// Used by both the editor's placement and the player's build mode.
+pub fn ghost_bundle(
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    color: Color,
+) -> PbrBundle {
+    PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: GHOST_RADIUS,
+            ..default()
+        })),
+        material: materials.add(StandardMaterial {
+            base_color: color,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        visibility: Visibility::Hidden,
+        ..default()
+    }
+}
+
+/// Snaps a ray to the first non-sensor surface it hits within `max_distance` meters.
+/// If that misses, the ray is intersected with the ground plane so that objects can be placed in empty worlds.
+pub fn get_placement_position(
+    rapier_context: &RapierContext,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    filter: QueryFilter,
+) -> Option<Vec3> {
+    if let Some((_entity, toi)) = rapier_context.cast_ray(
+        origin,
+        direction,
+        max_distance,
+        true,
+        filter.exclude_sensors(),
+    ) {
+        return Some(origin + direction * toi);
+    }
+    let toi = -origin.y / direction.y;
+    (toi.is_finite() && toi > 0. && toi <= max_distance).then(|| origin + direction * toi)
+}
@@ -0,0 +1,447 @@
+use crate::file_system_interaction::level_serialization::{
+    deserialize_level, SerializedLevel, LEVEL_FORMAT_VERSION,
+};
+use crate::level_instantiation::spawning::custom::{CustomObject, ObjectKind};
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::level_instantiation::spawning::spawn_queue::SpawnQueue;
+use crate::level_instantiation::spawning::GameObject;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_mod_sysfail::macros::*;
+use glob::glob;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{fs, iter};
+
+/// Prefabs are groups of objects that are spawned together, e.g. a house with its walls, lights and inhabitant.
+/// They are stored in the level format in `assets/prefabs/` with transforms relative to the prefab's origin.
+/// Spawning a prefab queues copies of its objects in the [`SpawnQueue`], which are saved in levels like any other object.
+///
+/// Each copy can vary through the [`ObjectMetadata`] of the prefab's objects, which is resolved when the prefab is stamped:
+/// - [`SPAWN_CHANCE_KEY`], e.g. `0.5`: Probability that the object is part of the copy at all
+/// - [`SCALE_JITTER_KEY`], e.g. `0.2`: The object's scale is multiplied by a random factor between 0.8 and 1.2
+/// - [`COLORS_KEY`], e.g. `200, 180, 120; 120, 90, 60`: One of the colors is picked and stored as [`COLOR_KEY`],
+/// which tints all materials of the object
+/// - [`MATERIALS_KEY`], e.g. `scenes/rock.glb#Material0; scenes/rock.glb#Material1`: One of the material asset paths is picked
+/// and stored as [`MATERIAL_KEY`], which replaces all materials of the object. Cannot be combined with [`COLORS_KEY`].
+///
+/// The variation keys are replaced by their outcome, so a stamped copy keeps its look when the level is saved and loaded again.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct Prefabs(pub BTreeMap<String, SerializedLevel>);
+
+impl Prefabs {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+}
+
+/// The equivalent of a [`SpawnEvent`](crate::level_instantiation::spawning::spawner::SpawnEvent) for prefabs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrefabSpawnEvent {
+    pub name: String,
+    pub transform: Transform,
+    /// Seed for the variation of the copy, so that the same seed stamps the same copy.
+    pub seed: u64,
+}
+
+impl PrefabSpawnEvent {
+    /// Seeds the variation with the position of the copy, so that stamping a prefab at the same spot again yields the same copy.
+    pub fn new(name: impl Into<String>, transform: Transform) -> Self {
+        let [x, y, z] = transform.translation.to_array().map(f32::to_bits);
+        Self {
+            name: name.into(),
+            transform,
+            seed: u64::from(x) ^ u64::from(y).rotate_left(21) ^ u64::from(z).rotate_left(42),
+        }
+    }
+}
+
+pub const SPAWN_CHANCE_KEY: &str = "spawn_chance";
+pub const SCALE_JITTER_KEY: &str = "scale_jitter";
+pub const COLORS_KEY: &str = "colors";
+pub const COLOR_KEY: &str = "color";
+pub const MATERIALS_KEY: &str = "materials";
+pub const MATERIAL_KEY: &str = "material";
+
+/// Saves the given objects and all objects parented to them as a prefab.
+/// The origin of the prefab is the position of the first entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrefabSaveRequest {
+    pub name: String,
+    pub entities: Vec<Entity>,
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn load_prefabs(mut prefabs: ResMut<Prefabs>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("load_prefabs").entered();
+    for path in glob("assets/prefabs/*.prefab.ron")
+        .context("Failed to read glob pattern")?
+        .filter_map(|entry| entry.ok())
+    {
+        let Some(name) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".prefab.ron"))
+        else {
+            continue;
+        };
+        let prefab = fs::read(&path)
+            .context("Failed to read prefab")
+            .and_then(|bytes| deserialize_level(&bytes));
+        match prefab {
+            Ok(prefab) => {
+                prefabs.0.insert(name.to_owned(), prefab);
+            }
+            Err(e) => error!("Failed to load prefab {}: {e:#}", path.to_string_lossy()),
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn spawn_prefabs(
+    mut prefab_spawn_events: EventReader<PrefabSpawnEvent>,
+    prefabs: Res<Prefabs>,
+    mut spawn_queue: ResMut<SpawnQueue>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_prefabs").entered();
+    for PrefabSpawnEvent {
+        name,
+        transform,
+        seed,
+    } in prefab_spawn_events.iter()
+    {
+        let Some(prefab) = prefabs.0.get(name) else {
+            error!(
+                "Failed to spawn prefab \"{name}\": No such prefab. Available prefabs: {:?}",
+                prefabs.0.keys()
+            );
+            continue;
+        };
+        let mut rng = SmallRng::seed_from_u64(*seed);
+        let objects = prefab
+            .objects
+            .iter()
+            .map(|(object, object_transform, metadata)| {
+                (ObjectKind::Builtin(*object), object_transform, metadata)
+            })
+            .chain(prefab.custom_objects.iter().map(
+                |(custom_name, object_transform, metadata)| {
+                    (
+                        ObjectKind::Custom(custom_name.clone()),
+                        object_transform,
+                        metadata,
+                    )
+                },
+            ));
+        for (object, object_transform, metadata) in objects {
+            let mut object_transform = *object_transform;
+            let mut metadata = metadata.clone();
+            match vary(&mut object_transform, &mut metadata, &mut rng) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => error!("Failed to vary {object} in prefab \"{name}\": {e:#}"),
+            }
+            spawn_queue.push_with_metadata(
+                object,
+                transform.mul_transform(object_transform),
+                metadata,
+            );
+        }
+    }
+}
+
+/// Resolves the variation keys of a prefab object. Returns whether the object is part of the copy.
+fn vary(
+    transform: &mut Transform,
+    metadata: &mut ObjectMetadata,
+    rng: &mut SmallRng,
+) -> Result<bool> {
+    if let Some(chance) = metadata.remove(SPAWN_CHANCE_KEY) {
+        let chance: f32 = parse_value(&chance, SPAWN_CHANCE_KEY)?;
+        if rng.gen::<f32>() >= chance {
+            return Ok(false);
+        }
+    }
+    if let Some(jitter) = metadata.remove(SCALE_JITTER_KEY) {
+        let jitter: f32 = parse_value(&jitter, SCALE_JITTER_KEY)?;
+        if jitter > 0. {
+            transform.scale *= 1. + rng.gen_range(-jitter..=jitter);
+        }
+    }
+    if metadata.get(COLORS_KEY).is_some() && metadata.get(MATERIALS_KEY).is_some() {
+        // Tints copy the object's own materials, which are about to be replaced
+        bail!("Cannot combine {COLORS_KEY} with {MATERIALS_KEY}");
+    }
+    if let Some(colors) = metadata.remove(COLORS_KEY) {
+        if let Some(color) = pick(&colors, rng) {
+            parse_color(color)?;
+            metadata.insert(COLOR_KEY, color);
+        }
+    }
+    if let Some(materials) = metadata.remove(MATERIALS_KEY) {
+        if let Some(material) = pick(&materials, rng) {
+            metadata.insert(MATERIAL_KEY, material);
+        }
+    }
+    Ok(true)
+}
+
+/// Picks one of the options separated by `;`.
+fn pick<'a>(options: &'a str, rng: &mut SmallRng) -> Option<&'a str> {
+    let options: Vec<_> = options
+        .split(';')
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+        .collect();
+    if options.is_empty() {
+        return None;
+    }
+    Some(options[rng.gen_range(0..options.len())])
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str, key: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .with_context(|| format!("Failed to parse {key} \"{value}\""))
+}
+
+/// Parses colors in the format `r, g, b` with components from 0 to 255.
+pub fn parse_color(value: &str) -> Result<Color> {
+    let components = value
+        .split(',')
+        .map(|component| parse_value::<u8>(component, COLOR_KEY))
+        .collect::<Result<Vec<_>>>()?;
+    let [r, g, b] = components[..] else {
+        bail!("Expected three components in color \"{value}\"");
+    };
+    Ok(Color::rgb_u8(r, g, b))
+}
+
+/// Replaces the materials of objects with a [`MATERIAL_KEY`] in their metadata by the material at that asset path.
+/// Meshes that are added later, e.g. when a scene finished loading, get the material as well.
+pub(crate) fn apply_material_variants(
+    changed_metadata: Query<(Entity, &ObjectMetadata), Changed<ObjectMetadata>>,
+    metadata: Query<&ObjectMetadata>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    mut material_handles: ParamSet<(
+        Query<Entity, Added<Handle<StandardMaterial>>>,
+        Query<&mut Handle<StandardMaterial>>,
+    )>,
+    asset_server: Res<AssetServer>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_material_variants").entered();
+    let material_of =
+        |object_metadata: &ObjectMetadata| object_metadata.get(MATERIAL_KEY).map(str::to_owned);
+    let mut variants: HashMap<Entity, String> = changed_metadata
+        .iter()
+        .filter_map(|(entity, metadata)| Some((entity, material_of(metadata)?)))
+        .flat_map(|(entity, material)| {
+            iter::once(entity)
+                .chain(children.iter_descendants(entity))
+                .map(move |descendant| (descendant, material.clone()))
+        })
+        .collect();
+    variants.extend(material_handles.p0().iter().filter_map(|entity| {
+        let material = iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .find_map(|ancestor| metadata.get(ancestor).ok().and_then(material_of))?;
+        Some((entity, material))
+    }));
+
+    let mut material_handles = material_handles.p1();
+    for (entity, material) in variants {
+        if let Ok(mut handle) = material_handles.get_mut(entity) {
+            *handle = asset_server.load(material.as_str());
+        }
+    }
+}
+
+/// The material a mesh had before it was tinted, so that tinting it again starts from the original.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct UntintedMaterial(pub Handle<StandardMaterial>);
+
+/// Tinted copies of materials by their original and their color as RGBA, shared by all meshes with the same tint.
+/// The handles are weak, so copies are freed once no mesh uses them anymore.
+#[derive(Debug, Clone, Resource, Default)]
+pub struct TintedMaterials(pub HashMap<(Handle<StandardMaterial>, u32), Handle<StandardMaterial>>);
+
+/// Tints the materials of objects with a [`COLOR_KEY`] in their metadata.
+/// Meshes that are added later, e.g. when a scene finished loading, are tinted as well.
+/// Tinted meshes get a copy of their material, as the original is usually shared with other objects.
+/// The copies are kept in the [`TintedMaterials`], so meshes with the same material and tint share one.
+pub(crate) fn tint_objects(
+    mut commands: Commands,
+    changed_metadata: Query<(Entity, &ObjectMetadata), Changed<ObjectMetadata>>,
+    metadata: Query<&ObjectMetadata>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    mut material_handles: ParamSet<(
+        Query<Entity, Added<Handle<StandardMaterial>>>,
+        Query<(&mut Handle<StandardMaterial>, Option<&UntintedMaterial>)>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted_materials: ResMut<TintedMaterials>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("tint_objects").entered();
+    let color_of = |object_metadata: &ObjectMetadata| {
+        let color = object_metadata.get(COLOR_KEY)?;
+        parse_color(color)
+            .map_err(|e| error!("Failed to tint object: {e:#}"))
+            .ok()
+    };
+    // Keyed by entity, as a mesh can both be new and belong to a changed object, but must only be tinted once
+    let mut tints: HashMap<Entity, Color> = changed_metadata
+        .iter()
+        .filter_map(|(entity, metadata)| Some((entity, color_of(metadata)?)))
+        .flat_map(|(entity, color)| {
+            iter::once(entity)
+                .chain(children.iter_descendants(entity))
+                .map(move |descendant| (descendant, color))
+        })
+        .collect();
+    tints.extend(material_handles.p0().iter().filter_map(|entity| {
+        let color = iter::once(entity)
+            .chain(parents.iter_ancestors(entity))
+            .find_map(|ancestor| metadata.get(ancestor).ok().and_then(color_of))?;
+        Some((entity, color))
+    }));
+
+    if tints.is_empty() {
+        return;
+    }
+    tinted_materials
+        .0
+        .retain(|_, tinted| materials.contains(tinted));
+    let mut material_handles = material_handles.p1();
+    for (entity, color) in tints {
+        let Ok((mut handle, untinted)) = material_handles.get_mut(entity) else {
+            continue;
+        };
+        let original = match untinted {
+            Some(untinted) => untinted.0.clone(),
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(UntintedMaterial(handle.clone()));
+                handle.clone()
+            }
+        };
+        let key = (original.clone_weak(), color.as_rgba_u32());
+        if let Some(tinted) = tinted_materials.0.get(&key) {
+            *handle = materials.get_handle(tinted);
+            continue;
+        }
+        let Some(material) = materials.get(&original) else {
+            continue;
+        };
+        let tinted = materials.add(StandardMaterial {
+            base_color: color,
+            ..material.clone()
+        });
+        tinted_materials.0.insert(key, tinted.clone_weak());
+        *handle = tinted;
+    }
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn save_prefabs(
+    mut prefab_save_requests: EventReader<PrefabSaveRequest>,
+    mut prefabs: ResMut<Prefabs>,
+    objects: Query<(
+        Option<&GameObject>,
+        Option<&CustomObject>,
+        &GlobalTransform,
+        Option<&ObjectMetadata>,
+    )>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_prefabs").entered();
+    for PrefabSaveRequest { name, entities } in prefab_save_requests.iter() {
+        if name.is_empty() {
+            bail!("Failed to save prefab: The name must not be empty");
+        }
+        let is_object = |entity: Entity| {
+            objects.get(entity).map_or(false, |(object, custom, _, _)| {
+                object.is_some() || custom.is_some()
+            })
+        };
+        // Selecting a part of an object, e.g. a mesh of an NPC, refers to the whole object
+        let roots: Vec<_> = entities
+            .iter()
+            .filter_map(|&entity| {
+                iter::once(entity)
+                    .chain(parents.iter_ancestors(entity))
+                    .find(|&ancestor| is_object(ancestor))
+            })
+            .collect();
+        let Some(origin) = roots
+            .first()
+            .and_then(|&root| objects.get(root).ok())
+            .map(|(_, _, transform, _)| GlobalTransform::from_translation(transform.translation()))
+        else {
+            bail!("Failed to save prefab \"{name}\": No spawned objects selected");
+        };
+
+        let mut saved = HashSet::new();
+        let mut prefab = SerializedLevel {
+            version: LEVEL_FORMAT_VERSION,
+            objects: default(),
+            custom_objects: default(),
+        };
+        for root in roots {
+            for entity in iter::once(root).chain(children.iter_descendants(root)) {
+                if !saved.insert(entity) {
+                    continue;
+                }
+                let Ok((object, custom, transform, metadata)) = objects.get(entity) else {
+                    continue;
+                };
+                let transform = transform.reparented_to(&origin);
+                let metadata = metadata.cloned().unwrap_or_default();
+                match (object, custom) {
+                    (Some(GameObject::Player), _) => {}
+                    (Some(object), _) => prefab.objects.push((*object, transform, metadata)),
+                    (None, Some(custom)) => {
+                        prefab
+                            .custom_objects
+                            .push((custom.name.clone(), transform, metadata))
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+
+        let serialized =
+            ron::ser::to_string_pretty(&prefab, default()).context("Failed to serialize prefab")?;
+        let path = get_prefab_path(name);
+        let dir = path.parent().context("Failed to get prefab directory")?;
+        fs::create_dir_all(dir).context("Failed to create prefab directory")?;
+        fs::write(&path, serialized).context("Failed to write prefab")?;
+        info!(
+            "Successfully saved prefab \"{name}\" with {} objects at {}",
+            prefab.objects.len() + prefab.custom_objects.len(),
+            path.to_string_lossy()
+        );
+        prefabs.0.insert(name.clone(), prefab);
+    }
+    Ok(())
+}
+
+fn get_prefab_path(name: &str) -> PathBuf {
+    Path::new("assets")
+        .join("prefabs")
+        .join(name)
+        .with_extension("prefab.ron")
+}
@@ -0,0 +1,69 @@
+use crate::level_instantiation::spawning::custom::ObjectKind;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Maximum number of objects taken from the [`SpawnQueue`] per frame.
+/// Lower values keep the frame rate smooth while big levels load, higher values load them faster.
+/// A budget of 0 pauses spawning from the queue.
+/// Set from the `spawning` section of the [`GameConfig`](crate::file_system_interaction::config::GameConfig) whenever it is loaded.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct SpawnBudget {
+    pub objects_per_frame: usize,
+}
+
+impl Default for SpawnBudget {
+    fn default() -> Self {
+        Self {
+            objects_per_frame: 8,
+        }
+    }
+}
+
+/// Objects waiting to be spawned. Use this instead of sending spawn events directly
+/// when spawning many objects at once, e.g. for levels and prefabs.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct SpawnQueue(pub VecDeque<(ObjectKind, Transform, ObjectMetadata)>);
+
+impl SpawnQueue {
+    pub fn push(&mut self, object: ObjectKind, transform: Transform) {
+        self.push_with_metadata(object, transform, default());
+    }
+
+    pub fn push_with_metadata(
+        &mut self,
+        object: ObjectKind,
+        transform: Transform,
+        metadata: ObjectMetadata,
+    ) {
+        self.0.push_back((object, transform, metadata));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Spawns the next objects of the [`SpawnQueue`] that fit into the [`SpawnBudget`] and attaches their metadata.
+pub(crate) fn process_spawn_queue(world: &mut World) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("process_spawn_queue").entered();
+    let budget = world.resource::<SpawnBudget>().objects_per_frame;
+    let mut queue = world.resource_mut::<SpawnQueue>();
+    let count = budget.min(queue.len());
+    let batch: Vec<_> = queue.0.drain(..count).collect();
+    for (object, transform, metadata) in batch {
+        let Some(entity) = object.spawn(world, transform) else {
+            continue;
+        };
+        if !metadata.is_empty() {
+            world.entity_mut(entity).insert(metadata);
+        }
+    }
+}
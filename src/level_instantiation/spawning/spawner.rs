@@ -0,0 +1,4 @@
+use crate::level_instantiation::spawning::GameObject;
+
+/// Requests spawning a built-in [`GameObject`].
+pub type SpawnEvent = foxtrot_spawning::SpawnEvent<GameObject>;
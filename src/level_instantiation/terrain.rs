@@ -0,0 +1,299 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::level_serialization::WorldLoadRequest;
+use crate::player_control::player_embodiment::Player;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Handles ground patches that can be dug into, e.g. by tools or explosions.
+/// A [`TerrainPatch`] is a heightfield covering its transform's cube, i.e. the scale's x and z are its half extents, just like the platforms.
+/// Send a [`DeformTerrain`] event to carve a crater into every patch it overlaps with. Only the mesh and collider
+/// of those patches are rebuilt. The `dig` console command does this at the player's feet.
+///
+/// All deformations of the current level are recorded in [`TerrainDeformations`], which is part of save games.
+/// Patches replay them when they are spawned or when a save is restored.
+pub fn terrain_plugin(app: &mut App) {
+    app.register_type::<TerrainPatch>()
+        .register_type::<TerrainDeformations>()
+        .init_resource::<TerrainDeformations>()
+        .add_event::<DeformTerrain>()
+        .add_systems(
+            (
+                reset_deformations.run_if(on_event::<WorldLoadRequest>()),
+                rebuild_all_patches.run_if(resource_changed::<TerrainDeformations>()),
+                rebuild_added_patches,
+                deform_patches,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "dig",
+            "Carves a crater into the terrain at the player's feet, e.g. \"dig 2 0.5\" for a radius of 2 m and a depth of 0.5 m",
+            dig,
+        );
+}
+
+/// Number of height samples along each side of a patch.
+pub const PATCH_RESOLUTION: usize = 33;
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct TerrainPatch {
+    /// Samples per side
+    pub resolution: usize,
+    /// Local heights in column-major order, i.e. rows go along z and columns along x.
+    /// Derived from the recorded [`TerrainDeformations`], so they are not saved with the level.
+    #[serde(skip)]
+    pub heights: Vec<f32>,
+}
+
+impl Default for TerrainPatch {
+    fn default() -> Self {
+        Self::new(PATCH_RESOLUTION)
+    }
+}
+
+impl TerrainPatch {
+    pub fn new(resolution: usize) -> Self {
+        Self {
+            resolution,
+            heights: vec![0.; resolution * resolution],
+        }
+    }
+
+    fn flatten(&mut self) {
+        self.heights = vec![0.; self.resolution * self.resolution];
+    }
+
+    /// Position of the sample in local space without its height, where the patch covers -1 to 1 in x and z.
+    fn sample_position(&self, row: usize, column: usize) -> Vec3 {
+        let step = 2. / (self.resolution - 1) as f32;
+        Vec3::new(-1. + column as f32 * step, 0., -1. + row as f32 * step)
+    }
+
+    /// Lowers all samples within the deformation's radius. Returns whether any sample changed.
+    fn apply(&mut self, transform: &GlobalTransform, deformation: &DeformTerrain) -> bool {
+        let scale = transform.compute_transform().scale;
+        let mut changed = false;
+        for column in 0..self.resolution {
+            for row in 0..self.resolution {
+                let index = row + column * self.resolution;
+                let local = self.sample_position(row, column) + Vec3::Y * self.heights[index];
+                let world = transform.transform_point(local);
+                let offset = Vec2::new(
+                    world.x - deformation.center.x,
+                    world.z - deformation.center.z,
+                );
+                let distance = offset.length() / deformation.radius;
+                if distance >= 1. {
+                    continue;
+                }
+                // Smooth bowl that is deepest in the center
+                let depth = deformation.depth * (1. - distance * distance);
+                self.heights[index] -= depth / scale.y;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn collider(&self) -> Collider {
+        Collider::heightfield(
+            self.heights.clone(),
+            self.resolution,
+            self.resolution,
+            Vec3::new(2., 1., 2.),
+        )
+    }
+
+    fn mesh(&self) -> Mesh {
+        let resolution = self.resolution;
+        let step = 2. / (resolution - 1) as f32;
+        let height = |row: usize, column: usize| {
+            self.heights[row.min(resolution - 1) + column.min(resolution - 1) * resolution]
+        };
+        let mut positions = Vec::with_capacity(resolution * resolution);
+        let mut normals = Vec::with_capacity(resolution * resolution);
+        let mut uvs = Vec::with_capacity(resolution * resolution);
+        for row in 0..resolution {
+            for column in 0..resolution {
+                let position = self.sample_position(row, column) + Vec3::Y * height(row, column);
+                positions.push(position.to_array());
+                let dx = height(row, column + 1) - height(row, column.saturating_sub(1));
+                let dz = height(row + 1, column) - height(row.saturating_sub(1), column);
+                normals.push(Vec3::new(-dx, 2. * step, -dz).normalize().to_array());
+                uvs.push([
+                    column as f32 / (resolution - 1) as f32,
+                    row as f32 / (resolution - 1) as f32,
+                ]);
+            }
+        }
+        let mut indices = Vec::with_capacity((resolution - 1) * (resolution - 1) * 6);
+        for row in 0..resolution - 1 {
+            for column in 0..resolution - 1 {
+                let top_left = (row * resolution + column) as u32;
+                let top_right = top_left + 1;
+                let bottom_left = top_left + resolution as u32;
+                let bottom_right = bottom_left + 1;
+                indices.extend([
+                    top_left,
+                    bottom_left,
+                    top_right,
+                    top_right,
+                    bottom_left,
+                    bottom_right,
+                ]);
+            }
+        }
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+/// Carves a crater with the given radius and depth in m, centered below `center`.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct DeformTerrain {
+    pub center: Vec3,
+    pub radius: f32,
+    pub depth: f32,
+}
+
+/// Every [`DeformTerrain`] applied in the current level, in order.
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct TerrainDeformations(pub Vec<DeformTerrain>);
+
+impl TerrainDeformations {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn dig(world: &mut World, args: &[&str]) -> Result<String> {
+    let parse = |index: usize, default: f32| {
+        args.get(index).map_or(Ok(default), |value| {
+            value
+                .parse::<f32>()
+                .with_context(|| format!("Failed to parse \"{value}\""))
+        })
+    };
+    let radius = parse(0, 1.5)?;
+    let depth = parse(1, 0.5)?;
+    if radius <= 0. {
+        bail!("The radius must be positive");
+    }
+    let Some(center) = world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(world)
+        .next()
+        .map(|transform| transform.translation)
+    else {
+        bail!("There is no player to dig at");
+    };
+    world.send_event(DeformTerrain {
+        center,
+        radius,
+        depth,
+    });
+    Ok(format!("Digging at {center}"))
+}
+
+fn reset_deformations(mut commands: Commands) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reset_deformations").entered();
+    commands.insert_resource(TerrainDeformations::default());
+}
+
+fn rebuild(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    entity: Entity,
+    patch: &TerrainPatch,
+    mesh_handle: Option<&Handle<Mesh>>,
+) {
+    let mesh = patch.mesh();
+    match mesh_handle.and_then(|handle| meshes.get_mut(handle)) {
+        Some(existing) => *existing = mesh,
+        None => {
+            commands.entity(entity).insert(meshes.add(mesh));
+        }
+    }
+    commands.entity(entity).insert(patch.collider());
+}
+
+/// Replays all deformations onto flat patches whenever they were replaced, e.g. when a save was restored.
+/// Inserting the resource over an existing one only counts as a change, not as an addition.
+fn rebuild_all_patches(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    deformations: Res<TerrainDeformations>,
+    mut patches: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut TerrainPatch,
+        Option<&Handle<Mesh>>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("rebuild_all_patches").entered();
+    for (entity, transform, mut patch, mesh_handle) in patches.iter_mut() {
+        patch.flatten();
+        for deformation in deformations.0.iter() {
+            patch.apply(transform, deformation);
+        }
+        rebuild(&mut commands, &mut meshes, entity, &patch, mesh_handle);
+    }
+}
+
+fn rebuild_added_patches(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    deformations: Res<TerrainDeformations>,
+    mut patches: Query<(Entity, &Transform, &mut TerrainPatch), Added<TerrainPatch>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("rebuild_added_patches").entered();
+    for (entity, transform, mut patch) in patches.iter_mut() {
+        // The global transform has not been propagated yet for new patches
+        let transform = GlobalTransform::from(*transform);
+        patch.flatten();
+        for deformation in deformations.0.iter() {
+            patch.apply(&transform, deformation);
+        }
+        rebuild(&mut commands, &mut meshes, entity, &patch, None);
+    }
+}
+
+fn deform_patches(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut deform_events: EventReader<DeformTerrain>,
+    mut deformations: ResMut<TerrainDeformations>,
+    mut patches: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut TerrainPatch,
+        Option<&Handle<Mesh>>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("deform_patches").entered();
+    for deformation in deform_events.iter() {
+        // The patches are deformed right here, so this must not trigger rebuilding all of them
+        deformations.bypass_change_detection().0.push(*deformation);
+        for (entity, transform, mut patch, mesh_handle) in patches.iter_mut() {
+            if patch.apply(transform, deformation) {
+                rebuild(&mut commands, &mut meshes, entity, &patch, mesh_handle);
+            }
+        }
+    }
+}
@@ -13,12 +13,17 @@
 //! This is an organizational measure and not meant to be imply that you can turn them on or off at will,
 //! since the plugins are interdependent.  
 //! Instead, decide for yourself which features you like and which one's you don't and simply trim the code accordingly.
+//! The subsystems that other games can use on their own are split out into the library crates
+//! [`foxtrot_spawning`], [`foxtrot_world_serialization`], [`foxtrot_character_controller`] and [`foxtrot_dialog`].
 //! Feel free to [file an issue](https://github.com/janhohenheim/foxtrot/issues/new) if you need help!
 //! The docs are organized such that you can click through the plugins to explore the systems at play.
 pub mod bevy_config;
+pub mod chat;
+pub mod console;
 #[cfg(feature = "dev")]
 pub mod dev;
 pub mod file_system_interaction;
+pub mod game_settings;
 pub mod ingame_menu;
 pub mod level_instantiation;
 pub mod menu;
@@ -27,13 +32,17 @@ pub mod movement;
 pub mod particles;
 pub mod player_control;
 pub mod shader;
+pub mod state_transition;
 pub mod util;
 pub mod world_interaction;
 
 use crate::bevy_config::bevy_config_plugin;
+use crate::chat::chat_plugin;
+use crate::console::console_plugin;
 #[cfg(feature = "dev")]
 use crate::dev::dev_plugin;
 use crate::file_system_interaction::file_system_interaction_plugin;
+use crate::game_settings::game_settings_plugin;
 use crate::ingame_menu::ingame_menu_plugin;
 use crate::level_instantiation::level_instantiation_plugin;
 use crate::menu::menu_plugin;
@@ -42,12 +51,13 @@ use crate::movement::movement_plugin;
 use crate::particles::particle_plugin;
 use crate::player_control::player_control_plugin;
 use crate::shader::shader_plugin;
+use crate::state_transition::state_transition_plugin;
 use crate::world_interaction::world_interaction_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
 #[derive(States, Default, Clone, Eq, PartialEq, Debug, Hash)]
-enum GameState {
+pub enum GameState {
     /// During the loading State the loading_plugin will load our assets
     #[default]
     Loading,
@@ -61,6 +71,7 @@ enum GameState {
 ///
 /// The top-level plugins are:
 /// - [`bevy_config_plugin`]: Sets up the bevy configuration.
+/// - [`state_transition_plugin`]: Handles guarded changes of the game state.
 /// - [`menu_plugin`]: Handles the menu.
 /// - [`movement_plugin`]: Handles the movement of entities.
 /// - [`player_control_plugin`]: Handles the player's control.
@@ -70,6 +81,9 @@ enum GameState {
 /// - [`shader_plugin`]: Handles the shaders.
 /// - [`dev_plugin`]: Handles the dev tools.
 /// - [`ingame_menu_plugin`]: Handles the ingame menu accessed via ESC.
+/// - [`game_settings_plugin`]: Handles the graphics, audio and input settings chosen by the player.
+/// - [`console_plugin`]: Handles text commands entered through a console.
+/// - [`chat_plugin`]: Handles the text chat and its slash commands.
 /// - [`particle_plugin`]: Handles the particle system. Since [bevy_hanabi](https://github.com/djeedai/bevy_hanabi) does not support wasm, this plugin is only available on native.
 ///
 /// Because Foxtrot uses `seldom_fn_plugin`, these are all functions.
@@ -88,6 +102,7 @@ impl Plugin for GamePlugin {
 
         app.add_state::<GameState>()
             .fn_plugin(bevy_config_plugin)
+            .fn_plugin(state_transition_plugin)
             .fn_plugin(menu_plugin)
             .fn_plugin(movement_plugin)
             .fn_plugin(player_control_plugin)
@@ -95,7 +110,10 @@ impl Plugin for GamePlugin {
             .fn_plugin(level_instantiation_plugin)
             .fn_plugin(file_system_interaction_plugin)
             .fn_plugin(shader_plugin)
-            .fn_plugin(ingame_menu_plugin);
+            .fn_plugin(ingame_menu_plugin)
+            .fn_plugin(game_settings_plugin)
+            .fn_plugin(console_plugin)
+            .fn_plugin(chat_plugin);
         #[cfg(feature = "dev")]
         app.fn_plugin(dev_plugin);
         #[cfg(feature = "native")]
@@ -1,3 +1,6 @@
+use crate::file_system_interaction::localization::Localization;
+use crate::game_settings::{show_game_settings, GameSettings};
+use crate::state_transition::RequestStateChange;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_egui::egui::FontFamily::Proportional;
@@ -7,11 +10,18 @@ use bevy_egui::{egui, EguiContexts};
 
 /// This plugin is responsible for the game menu
 /// The menu is only drawn during the State `GameState::Menu` and is removed when that state is exited.
+/// Besides starting the game, it leads to the [`GameSettings`].
 pub fn menu_plugin(app: &mut App) {
     app.add_system(setup_menu.in_set(OnUpdate(GameState::Menu)));
 }
 
-fn setup_menu(mut egui_contexts: EguiContexts, mut next_state: ResMut<NextState<GameState>>) {
+fn setup_menu(
+    mut egui_contexts: EguiContexts,
+    mut state_change_requests: EventWriter<RequestStateChange>,
+    mut settings: ResMut<GameSettings>,
+    localization: Res<Localization>,
+    mut showing_settings: Local<bool>,
+) {
     get_menu_panel().show(egui_contexts.ctx_mut(), |ui| {
         set_menu_style(ui.style_mut());
         ui.vertical_centered_justified(|ui| {
@@ -19,10 +29,26 @@ fn setup_menu(mut egui_contexts: EguiContexts, mut next_state: ResMut<NextState<
             ui.heading("Foxtrot");
             ui.separator();
             ui.add_space(50.);
+            if *showing_settings {
+                // Only touch the resource on actual edits, as every change is written to disk
+                let mut edited = settings.clone();
+                show_game_settings(ui, &mut edited, &localization.languages);
+                if edited != *settings {
+                    *settings = edited;
+                }
+                ui.add_space(20.);
+                if ui.button("Back").clicked() {
+                    *showing_settings = false;
+                }
+                return;
+            }
             if ui.button("Play").clicked() {
-                next_state.set(GameState::Playing);
+                state_change_requests.send(RequestStateChange(GameState::Playing));
+            }
+            if ui.button("Settings").clicked() {
+                *showing_settings = true;
             }
-        })
+        });
     });
 }
 
@@ -1,10 +1,38 @@
+pub mod animation_graph;
+pub mod behavior;
+pub mod conveyor;
+pub mod critter;
+pub mod dash;
+pub mod depenetration;
+pub mod footsteps;
 pub mod general_movement;
+pub mod gravity;
+pub mod ledge_grab;
+pub mod moving_platform;
 pub mod navigation;
+pub mod patrol_path;
 pub mod physics;
+pub mod spline;
+pub mod surface;
+pub mod wall_jump;
 
+use crate::movement::animation_graph::animation_graph_plugin;
+use crate::movement::behavior::behavior_plugin;
+use crate::movement::conveyor::conveyor_plugin;
+use crate::movement::critter::critter_plugin;
+use crate::movement::dash::dash_plugin;
+use crate::movement::depenetration::depenetration_plugin;
+use crate::movement::footsteps::footsteps_plugin;
 use crate::movement::general_movement::general_movement_plugin;
+use crate::movement::gravity::gravity_plugin;
+use crate::movement::ledge_grab::ledge_grab_plugin;
+use crate::movement::moving_platform::moving_platform_plugin;
 use crate::movement::navigation::navigation_plugin;
+use crate::movement::patrol_path::patrol_path_plugin;
 use crate::movement::physics::physics_plugin;
+use crate::movement::spline::spline_plugin;
+use crate::movement::surface::surface_plugin;
+use crate::movement::wall_jump::wall_jump_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
@@ -14,9 +42,37 @@ use seldom_fn_plugin::FnPluginExt;
 /// - [`general_movement_plugin`]: Handles kinematic character controller movement. A "character" in
 /// this sense is anything that behaves in a not-quite completely physical way, like a player, an npc, an elevator, a moving platform, etc.
 /// Contrast this with pure rigidbodies like a ball, a crate, etc.
+/// - [`animation_graph_plugin`]: Blends the locomotion animations of characters and plays one-shots on top of them.
+/// - [`gravity_plugin`]: Handles the global gravity and areas with their own gravity.
+/// - [`depenetration_plugin`]: Pushes characters out of colliders that moved into them.
+/// - [`surface_plugin`]: Gives colliders friction, bounciness and slipperiness from surface definitions.
+/// - [`footsteps_plugin`]: Plays footstep and landing sounds that fit the surface characters walk on.
+/// - [`wall_jump_plugin`]: Lets characters slide down walls and jump off them.
+/// - [`dash_plugin`]: Lets characters dash horizontally with a cooldown.
+/// - [`ledge_grab_plugin`]: Lets characters grab ledges and pull themselves up.
+/// - [`moving_platform_plugin`]: Moves platforms along waypoints and carries the characters standing on them.
+/// - [`conveyor_plugin`]: Moves characters and props along conveyor belts.
+/// - [`critter_plugin`]: Lets small animals wander, flee from the player and flock together.
+/// - [`behavior_plugin`]: Decides where NPCs walk based on their configurable behaviors.
+/// - [`patrol_path_plugin`]: Chains waypoints placed in a level into named paths that NPCs can patrol.
 /// - [`navigation_plugin`]: Handles npc pathfinding via bevy_pathmesh integration.
+/// - [`spline_plugin`]: Handles curves that platforms, characters and cameras can follow and that can be rendered as ropes.
 pub fn movement_plugin(app: &mut App) {
     app.fn_plugin(physics_plugin)
         .fn_plugin(general_movement_plugin)
-        .fn_plugin(navigation_plugin);
+        .fn_plugin(animation_graph_plugin)
+        .fn_plugin(gravity_plugin)
+        .fn_plugin(depenetration_plugin)
+        .fn_plugin(surface_plugin)
+        .fn_plugin(footsteps_plugin)
+        .fn_plugin(wall_jump_plugin)
+        .fn_plugin(dash_plugin)
+        .fn_plugin(ledge_grab_plugin)
+        .fn_plugin(moving_platform_plugin)
+        .fn_plugin(conveyor_plugin)
+        .fn_plugin(critter_plugin)
+        .fn_plugin(behavior_plugin)
+        .fn_plugin(patrol_path_plugin)
+        .fn_plugin(navigation_plugin)
+        .fn_plugin(spline_plugin);
 }
@@ -0,0 +1,412 @@
+use crate::level_instantiation::spawning::AnimationEntityLink;
+use crate::movement::general_movement::{
+    apply_upper_body_animations, blend_curve, collect_masked_bones, CharacterControllerSystemSet,
+    CharacterJumped, CharacterLanded, Grounded,
+};
+use crate::movement::ledge_grab::Mantling;
+use crate::util::trait_extension::Vec3Ext;
+use crate::world_interaction::interactions_ui::InteractionEvent;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::animation::animation_player;
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy::utils::HashMap;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Slowest and fastest a locomotion clip is played when scaling it with the character's speed.
+const PLAYBACK_SPEED_RANGE: (f32, f32) = (0.5, 2.);
+/// Time in seconds it takes a one-shot to fade in over the locomotion and out again.
+const ONE_SHOT_FADE_TIME: f32 = 0.15;
+
+/// Drives the animations of characters through their [`AnimationGraph`].
+/// The locomotion state is picked from whether the character is grounded and moving, and cross-fades into the next
+/// state over [`AnimationGraph::transition_time`]. Clips with a [`AnimationNode::reference_speed`] play faster or slower
+/// with the character's horizontal speed, so that the paws don't slide over the ground.
+/// Jumping, landing and interacting play the graph's [`OneShot`]s once on top of the locomotion, fading in and out of it.
+/// [`AnimationMarker`]s send an [`AnimationMarkerReached`] whenever playback passes them, which is where sounds and
+/// effects hook into the animations.
+pub fn animation_graph_plugin(app: &mut App) {
+    app.register_type::<AnimationGraph>()
+        .register_type::<AnimationNode>()
+        .register_type::<AnimationMarker>()
+        .register_type::<LocomotionState>()
+        .register_type::<OneShot>()
+        .register_type::<PlayingOneShot>()
+        .add_event::<AnimationMarkerReached>()
+        .add_systems(
+            (update_locomotion, start_one_shots, update_one_shots)
+                .chain()
+                .after(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            apply_one_shot_animations
+                .after(animation_player)
+                .before(apply_upper_body_animations)
+                .before(TransformSystem::TransformPropagate)
+                .in_base_set(CoreSet::PostUpdate),
+        );
+}
+
+/// The animations a character plays and how they are blended into each other.
+#[derive(Debug, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct AnimationGraph {
+    pub idle: AnimationNode,
+    pub walk: AnimationNode,
+    pub aerial: AnimationNode,
+    /// Clips played once on top of the locomotion. One-shots without a clip are not played.
+    pub one_shots: HashMap<OneShot, AnimationNode>,
+    /// Time in seconds it takes to cross-fade from one locomotion state to the next
+    pub transition_time: f32,
+    pub state: LocomotionState,
+    /// Fraction of the current locomotion clip that had been played at the last update
+    pub phase: Option<f32>,
+    pub one_shot: Option<PlayingOneShot>,
+}
+
+impl Default for AnimationGraph {
+    fn default() -> Self {
+        Self {
+            idle: default(),
+            walk: default(),
+            aerial: default(),
+            one_shots: default(),
+            transition_time: 0.2,
+            state: default(),
+            phase: None,
+            one_shot: None,
+        }
+    }
+}
+
+impl AnimationGraph {
+    pub fn new(idle: AnimationNode, walk: AnimationNode, aerial: AnimationNode) -> Self {
+        Self {
+            idle,
+            walk,
+            aerial,
+            ..default()
+        }
+    }
+
+    pub fn with_one_shot(mut self, one_shot: OneShot, node: AnimationNode) -> Self {
+        self.one_shots.insert(one_shot, node);
+        self
+    }
+
+    pub fn node(&self, state: LocomotionState) -> &AnimationNode {
+        match state {
+            LocomotionState::Idle => &self.idle,
+            LocomotionState::Walk => &self.walk,
+            LocomotionState::Aerial => &self.aerial,
+        }
+    }
+
+    /// Plays the one-shot on top of the locomotion, replacing the one that is playing.
+    /// Returns whether the graph has a clip for it.
+    pub fn play_one_shot(&mut self, one_shot: OneShot) -> bool {
+        if !self.one_shots.contains_key(&one_shot) {
+            return false;
+        }
+        self.one_shot = Some(PlayingOneShot {
+            one_shot,
+            elapsed: 0.,
+        });
+        true
+    }
+}
+
+/// A clip in an [`AnimationGraph`].
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Default)]
+pub struct AnimationNode {
+    pub clip: Handle<AnimationClip>,
+    /// Horizontal speed in m/s at which the clip plays at its authored speed. Without one, the speed is not scaled.
+    pub reference_speed: Option<f32>,
+    pub markers: Vec<AnimationMarker>,
+}
+
+impl AnimationNode {
+    pub fn new(clip: Handle<AnimationClip>) -> Self {
+        Self { clip, ..default() }
+    }
+
+    pub fn with_reference_speed(mut self, speed: f32) -> Self {
+        self.reference_speed = Some(speed);
+        self
+    }
+
+    pub fn with_marker(mut self, time: f32, name: impl Into<String>) -> Self {
+        self.markers.push(AnimationMarker {
+            time,
+            name: name.into(),
+        });
+        self
+    }
+
+    /// Speed at which the clip plays for a character moving horizontally at `speed` m/s.
+    pub fn playback_speed(&self, speed: f32) -> f32 {
+        let (min, max) = PLAYBACK_SPEED_RANGE;
+        self.reference_speed
+            .filter(|reference_speed| *reference_speed > 0.)
+            .map_or(1., |reference_speed| {
+                (speed / reference_speed).clamp(min, max)
+            })
+    }
+
+    /// Markers passed when playback went from the fraction `previous` of the clip to `current`, wrapping around at its end.
+    fn passed_markers(
+        &self,
+        previous: f32,
+        current: f32,
+    ) -> impl Iterator<Item = &AnimationMarker> {
+        self.markers.iter().filter(move |marker| {
+            if current >= previous {
+                previous < marker.time && marker.time <= current
+            } else {
+                marker.time > previous || marker.time <= current
+            }
+        })
+    }
+}
+
+/// A named point in a clip at which an [`AnimationMarkerReached`] is sent.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct AnimationMarker {
+    /// Fraction of the clip's duration at which the marker is reached
+    pub time: f32,
+    pub name: String,
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum LocomotionState {
+    #[default]
+    Idle,
+    Walk,
+    Aerial,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum OneShot {
+    JumpStart,
+    Land,
+    Interact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct PlayingOneShot {
+    pub one_shot: OneShot,
+    /// Time in seconds since the one-shot was started
+    pub elapsed: f32,
+}
+
+impl PlayingOneShot {
+    /// Blend weight over the locomotion for a clip of the given duration, fading in at its start and out at its end.
+    pub fn weight(&self, duration: f32) -> f32 {
+        let fade_in = self.elapsed / ONE_SHOT_FADE_TIME;
+        let fade_out = (duration - self.elapsed) / ONE_SHOT_FADE_TIME;
+        fade_in.min(fade_out).clamp(0., 1.)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AnimationMarkerReached {
+    pub entity: Entity,
+    /// Name of the [`AnimationMarker`]
+    pub marker: String,
+}
+
+#[sysfail(log(level = "error"))]
+fn update_locomotion(
+    mut animation_players: Query<&mut AnimationPlayer>,
+    mut characters: Query<
+        (
+            Entity,
+            &Velocity,
+            &Transform,
+            &Grounded,
+            &AnimationEntityLink,
+            &mut AnimationGraph,
+        ),
+        Without<Mantling>,
+    >,
+    animation_clips: Res<Assets<AnimationClip>>,
+    mut marker_events: EventWriter<AnimationMarkerReached>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_locomotion").entered();
+    for (entity, velocity, transform, grounded, animation_entity_link, mut graph) in
+        characters.iter_mut()
+    {
+        let mut animation_player = animation_players
+            .get_mut(animation_entity_link.0)
+            .context("animation_entity_link held entity without animation player")?;
+
+        let horizontal_movement = velocity.linvel.split(transform.up()).horizontal;
+        let state = if !grounded.0 {
+            LocomotionState::Aerial
+        } else if !horizontal_movement.is_approx_zero() {
+            LocomotionState::Walk
+        } else {
+            LocomotionState::Idle
+        };
+        if state != graph.state {
+            graph.state = state;
+            graph.phase = None;
+        }
+        let node = graph.node(state);
+        animation_player
+            .play_with_transition(
+                node.clip.clone_weak(),
+                Duration::from_secs_f32(graph.transition_time),
+            )
+            .repeat();
+        animation_player.set_speed(node.playback_speed(horizontal_movement.length()));
+
+        let Some(duration) = animation_clips
+            .get(&node.clip)
+            .map(|clip| clip.duration())
+            .filter(|duration| *duration > 0.)
+        else {
+            continue;
+        };
+        let phase = (animation_player.elapsed() / duration).fract();
+        if let Some(previous) = graph.phase {
+            for marker in node.passed_markers(previous, phase) {
+                marker_events.send(AnimationMarkerReached {
+                    entity,
+                    marker: marker.name.clone(),
+                });
+            }
+        }
+        graph.phase = Some(phase);
+    }
+    Ok(())
+}
+
+fn start_one_shots(
+    mut jumped_events: EventReader<CharacterJumped>,
+    mut landed_events: EventReader<CharacterLanded>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut characters: Query<&mut AnimationGraph>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_one_shots").entered();
+    let jumps = jumped_events
+        .iter()
+        .map(|event| (event.entity, OneShot::JumpStart));
+    let landings = landed_events
+        .iter()
+        .map(|event| (event.entity, OneShot::Land));
+    let interactions = interaction_events
+        .iter()
+        .map(|event| (event.player, OneShot::Interact));
+    for (entity, one_shot) in jumps.chain(landings).chain(interactions) {
+        if let Ok(mut graph) = characters.get_mut(entity) {
+            graph.play_one_shot(one_shot);
+        }
+    }
+}
+
+fn update_one_shots(
+    time: Res<Time>,
+    mut characters: Query<(Entity, &mut AnimationGraph)>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    mut marker_events: EventWriter<AnimationMarkerReached>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_one_shots").entered();
+    let dt = time.delta_seconds();
+    for (entity, mut graph) in characters.iter_mut() {
+        let Some(playing) = graph.one_shot else {
+            continue;
+        };
+        let Some(node) = graph.one_shots.get(&playing.one_shot) else {
+            graph.one_shot = None;
+            continue;
+        };
+        let Some(duration) = animation_clips
+            .get(&node.clip)
+            .map(|clip| clip.duration().max(1e-5))
+        else {
+            continue;
+        };
+        let elapsed = playing.elapsed + dt;
+        // Markers at the very start are reached as soon as the one-shot starts playing
+        let previous = if playing.elapsed > 0. {
+            playing.elapsed / duration
+        } else {
+            -1.
+        };
+        for marker in node.passed_markers(previous, (elapsed / duration).min(1.)) {
+            marker_events.send(AnimationMarkerReached {
+                entity,
+                marker: marker.name.clone(),
+            });
+        }
+        graph.one_shot = (elapsed < duration).then_some(PlayingOneShot { elapsed, ..playing });
+    }
+}
+
+/// Runs after Bevy's own [`animation_player`] and blends the playing [`OneShot`]s over the whole body.
+fn apply_one_shot_animations(
+    animation_clips: Res<Assets<AnimationClip>>,
+    characters: Query<(&AnimationEntityLink, &AnimationGraph)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    mut transforms: Query<&mut Transform>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_one_shot_animations").entered();
+    for (animation_entity_link, graph) in characters.iter() {
+        let Some(playing) = &graph.one_shot else {
+            continue;
+        };
+        let Some(clip) = graph
+            .one_shots
+            .get(&playing.one_shot)
+            .and_then(|node| animation_clips.get(&node.clip))
+        else {
+            continue;
+        };
+        let weight = playing.weight(clip.duration());
+        if weight <= 0. {
+            continue;
+        }
+        let root = animation_entity_link.0;
+        let Ok(root_name) = names.get(root) else {
+            continue;
+        };
+        let mut bones = Vec::new();
+        collect_masked_bones(
+            root,
+            vec![root_name.clone()],
+            root_name,
+            true,
+            &children,
+            &names,
+            &mut bones,
+        );
+        for (bone, path) in bones {
+            let Some(curves) = clip.get_curves_by_path(&path) else {
+                continue;
+            };
+            let Ok(mut transform) = transforms.get_mut(bone) else {
+                continue;
+            };
+            for curve in curves {
+                blend_curve(curve, playing.elapsed, weight, &mut transform);
+            }
+        }
+    }
+}
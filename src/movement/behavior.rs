@@ -0,0 +1,334 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::general_movement::CharacterControllerSystemSet;
+use crate::movement::navigation::NavigationIntent;
+use crate::movement::patrol_path::{collect_patrol_paths, Waypoint};
+use crate::player_control::player_embodiment::Player;
+use crate::util::trait_extension::Vec3Ext;
+use crate::world_interaction::dialog::CurrentDialog;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of an object's [`Behavior`] in RON, e.g. `Patrol(waypoints: [(0, 0, 0), (5, 0, 0)])`.
+pub const BEHAVIOR_KEY: &str = "behavior";
+/// Metadata key of an object's [`BehaviorTransitions`] in RON, e.g. `[(on: PlayerNear(4), to: Flee(target: Player))]`.
+pub const BEHAVIOR_TRANSITIONS_KEY: &str = "behavior_transitions";
+/// Metadata key of the name of a patrol path, shorthand for a [`Behavior::PatrolPath`].
+pub const PATROL_PATH_KEY: &str = "patrol_path";
+
+/// How close in m a character has to get to a waypoint or scripted destination to count as having reached it.
+const ARRIVAL_DISTANCE: f32 = 0.5;
+
+/// Decides where NPCs walk. Each [`Behavior`] sets the [`NavigationIntent`] that the
+/// [`navigation_plugin`](crate::movement::navigation::navigation_plugin) then finds a path for.
+/// [`BehaviorTransitions`] switch to another behavior when one of their [`BehaviorTrigger`]s fires,
+/// e.g. when the player comes close, a dialog with the NPC ends or an [`NpcDamaged`] is sent for it.
+/// Both are read from the object's metadata, so levels can configure how each NPC behaves.
+pub fn behavior_plugin(app: &mut App) {
+    app.register_type::<Behavior>()
+        .register_type::<BehaviorTransitions>()
+        .register_type::<BehaviorProgress>()
+        .add_event::<NpcDamaged>()
+        .add_systems(
+            (
+                read_behavior_metadata,
+                apply_behavior_transitions,
+                reset_behavior_progress,
+                update_navigation_intents,
+            )
+                .chain()
+                .in_set(BehaviorSystemSet)
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct BehaviorSystemSet;
+
+#[derive(
+    Debug, Clone, PartialEq, Component, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub enum Behavior {
+    /// Stands still
+    #[default]
+    Idle,
+    /// Walks from waypoint to waypoint, starting over after the last one
+    Patrol { waypoints: Vec<Vec3> },
+    /// Like [`Behavior::Patrol`], but along the [`Waypoint`]s placed in the level for the named path
+    PatrolPath { path: String },
+    /// Walks towards the target until it is `distance` m away
+    Follow {
+        target: BehaviorTarget,
+        #[serde(default = "default_follow_distance")]
+        distance: f32,
+    },
+    /// Walks away from the target until it is `distance` m away
+    Flee {
+        target: BehaviorTarget,
+        #[serde(default = "default_flee_distance")]
+        distance: f32,
+    },
+    /// Runs through the steps once and then fires [`BehaviorTrigger::ScriptFinished`]
+    Scripted { steps: Vec<ScriptStep> },
+}
+
+fn default_follow_distance() -> f32 {
+    3.
+}
+
+fn default_flee_distance() -> f32 {
+    8.
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+pub enum BehaviorTarget {
+    Player,
+    /// The first entity with this [`Name`]
+    Named(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+pub enum ScriptStep {
+    WalkTo(Vec3),
+    /// Waits for the given number of seconds
+    Wait(f32),
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+pub enum BehaviorTrigger {
+    /// The player is closer than the given distance in m
+    PlayerNear(f32),
+    /// The player is farther away than the given distance in m
+    PlayerFar(f32),
+    /// A dialog started with this NPC was closed
+    DialogFinished,
+    /// An [`NpcDamaged`] was sent for this NPC
+    Damaged,
+    /// The NPC ran through all steps of its [`Behavior::Scripted`]
+    ScriptFinished,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+pub struct BehaviorTransition {
+    pub on: BehaviorTrigger,
+    pub to: Behavior,
+}
+
+/// Checked in order, the first transition whose trigger fires and that leads to a different behavior wins.
+#[derive(
+    Debug, Clone, PartialEq, Component, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct BehaviorTransitions(pub Vec<BehaviorTransition>);
+
+/// Runtime state of the current [`Behavior`], reset whenever it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct BehaviorProgress {
+    /// Index of the current waypoint or script step
+    pub step: usize,
+    /// Time in s spent on the current script step
+    pub elapsed: f32,
+    pub script_finished: bool,
+}
+
+/// Sent by whatever hurts an NPC, so it can react with a [`BehaviorTrigger::Damaged`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct NpcDamaged {
+    pub npc: Entity,
+}
+
+#[sysfail(log(level = "error"))]
+fn read_behavior_metadata(
+    mut commands: Commands,
+    objects: Query<(Entity, &ObjectMetadata), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_behavior_metadata").entered();
+    for (entity, metadata) in objects.iter() {
+        if let Some(behavior) = metadata.get(BEHAVIOR_KEY) {
+            let behavior: Behavior = ron::from_str(behavior)
+                .with_context(|| format!("Failed to parse behavior \"{behavior}\""))?;
+            commands.entity(entity).insert(behavior);
+        } else if let Some(path) = metadata.get(PATROL_PATH_KEY) {
+            commands.entity(entity).insert(Behavior::PatrolPath {
+                path: path.to_owned(),
+            });
+        }
+        if let Some(transitions) = metadata.get(BEHAVIOR_TRANSITIONS_KEY) {
+            let transitions: Vec<BehaviorTransition> =
+                ron::from_str(transitions).with_context(|| {
+                    format!("Failed to parse behavior transitions \"{transitions}\"")
+                })?;
+            commands
+                .entity(entity)
+                .insert(BehaviorTransitions(transitions));
+        }
+    }
+    Ok(())
+}
+
+fn reset_behavior_progress(
+    mut commands: Commands,
+    mut behaviors: Query<
+        (
+            Entity,
+            Option<&mut BehaviorProgress>,
+            Option<&NavigationIntent>,
+        ),
+        Changed<Behavior>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reset_behavior_progress").entered();
+    for (entity, progress, intent) in behaviors.iter_mut() {
+        match progress {
+            Some(mut progress) => *progress = default(),
+            None => {
+                commands.entity(entity).insert(BehaviorProgress::default());
+            }
+        }
+        if intent.is_none() {
+            commands.entity(entity).insert(NavigationIntent::default());
+        }
+    }
+}
+
+fn apply_behavior_transitions(
+    mut npcs: Query<(
+        Entity,
+        &Transform,
+        &mut Behavior,
+        &BehaviorTransitions,
+        Option<&BehaviorProgress>,
+    )>,
+    players: Query<&Transform, With<Player>>,
+    current_dialog: Option<Res<CurrentDialog>>,
+    mut last_dialog_source: Local<Option<Entity>>,
+    mut damaged_events: EventReader<NpcDamaged>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_behavior_transitions").entered();
+    let dialog_source = current_dialog.map(|dialog| dialog.source);
+    let finished_dialog_source = last_dialog_source.filter(|_| dialog_source.is_none());
+    *last_dialog_source = dialog_source;
+    let damaged: Vec<_> = damaged_events.iter().map(|event| event.npc).collect();
+    let player = players.iter().next();
+
+    for (entity, transform, mut behavior, transitions, progress) in npcs.iter_mut() {
+        let player_distance =
+            player.map(|player| transform.translation.distance(player.translation));
+        let fired = |trigger: &BehaviorTrigger| match trigger {
+            BehaviorTrigger::PlayerNear(radius) => {
+                player_distance.map_or(false, |distance| distance < *radius)
+            }
+            BehaviorTrigger::PlayerFar(radius) => {
+                player_distance.map_or(false, |distance| distance > *radius)
+            }
+            BehaviorTrigger::DialogFinished => finished_dialog_source == Some(entity),
+            BehaviorTrigger::Damaged => damaged.contains(&entity),
+            BehaviorTrigger::ScriptFinished => {
+                progress.map_or(false, |progress| progress.script_finished)
+            }
+        };
+        // Without the comparison, a trigger that keeps firing would restart the behavior every frame
+        let transition = transitions
+            .0
+            .iter()
+            .find(|transition| transition.to != *behavior && fired(&transition.on));
+        if let Some(transition) = transition {
+            *behavior = transition.to.clone();
+        }
+    }
+}
+
+fn update_navigation_intents(
+    time: Res<Time>,
+    mut npcs: Query<(
+        &Transform,
+        &Behavior,
+        &mut BehaviorProgress,
+        &mut NavigationIntent,
+    )>,
+    players: Query<&GlobalTransform, With<Player>>,
+    named: Query<(&Name, &GlobalTransform)>,
+    waypoints: Query<(&Waypoint, &GlobalTransform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_navigation_intents").entered();
+    let patrol_paths = collect_patrol_paths(&waypoints);
+    let find_target = |target: &BehaviorTarget| match target {
+        BehaviorTarget::Player => players
+            .iter()
+            .next()
+            .map(|transform| transform.translation()),
+        BehaviorTarget::Named(target_name) => named
+            .iter()
+            .find(|(name, _)| name.as_str() == target_name)
+            .map(|(_, transform)| transform.translation()),
+    };
+    let reached =
+        |from: Vec3, to: Vec3| (to - from).split(Vec3::Y).horizontal.length() < ARRIVAL_DISTANCE;
+    let patrol = |position: Vec3, waypoints: &[Vec3], progress: &mut BehaviorProgress| {
+        if waypoints.is_empty() {
+            return NavigationIntent::default();
+        }
+        let mut waypoint = waypoints[progress.step % waypoints.len()];
+        if reached(position, waypoint) {
+            progress.step = (progress.step + 1) % waypoints.len();
+            waypoint = waypoints[progress.step];
+        }
+        NavigationIntent::towards(waypoint, 0.)
+    };
+
+    for (transform, behavior, mut progress, mut intent) in npcs.iter_mut() {
+        let position = transform.translation;
+        *intent = match behavior {
+            Behavior::Idle => NavigationIntent::default(),
+            Behavior::Patrol { waypoints } => patrol(position, waypoints, &mut progress),
+            Behavior::PatrolPath { path } => {
+                let waypoints = patrol_paths.get(path).map_or(&[][..], Vec::as_slice);
+                patrol(position, waypoints, &mut progress)
+            }
+            Behavior::Follow { target, distance } => find_target(target)
+                .map(|target| NavigationIntent::towards(target, *distance))
+                .unwrap_or_default(),
+            Behavior::Flee { target, distance } => find_target(target)
+                .map(|target| (position - target).split(Vec3::Y).horizontal)
+                .filter(|away| away.length() < *distance)
+                .map(|away| {
+                    // Aim for a point at the safe distance, the navmesh finds a way around obstacles
+                    let direction = away.try_normalize().unwrap_or(Vec3::X);
+                    let destination = position + direction * (*distance - away.length());
+                    NavigationIntent::towards(destination, 0.)
+                })
+                .unwrap_or_default(),
+            Behavior::Scripted { steps } => match steps.get(progress.step) {
+                None => {
+                    progress.script_finished = true;
+                    NavigationIntent::default()
+                }
+                Some(ScriptStep::WalkTo(destination)) => {
+                    if reached(position, *destination) {
+                        progress.step += 1;
+                        NavigationIntent::default()
+                    } else {
+                        NavigationIntent::towards(*destination, 0.)
+                    }
+                }
+                Some(ScriptStep::Wait(duration)) => {
+                    progress.elapsed += time.delta_seconds();
+                    if progress.elapsed >= *duration {
+                        progress.step += 1;
+                        progress.elapsed = 0.;
+                    }
+                    NavigationIntent::default()
+                }
+            },
+        };
+    }
+}
@@ -0,0 +1,198 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::general_movement::{
+    prevent_tunneling, update_grounded, CharacterControllerSystemSet, Grounded, Walking,
+};
+use crate::shader::ConveyorMaterial;
+use crate::world_interaction::bounce_pad::parse_direction;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the speed in m/s a conveyor moves things with, e.g. `2`. Negative speeds reverse the belt.
+pub const CONVEYOR_SPEED_KEY: &str = "conveyor_speed";
+/// Metadata key of the direction in the conveyor's local space that the belt moves in, e.g. `1, 0, 0`.
+pub const CONVEYOR_DIRECTION_KEY: &str = "conveyor_direction";
+
+/// Moves everything standing on a [`Conveyor`] along its belt. Characters are carried like on moving platforms,
+/// while dynamic bodies are sped up to the belt's speed and otherwise keep their momentum.
+/// The speed and direction are read from the metadata keys [`CONVEYOR_SPEED_KEY`] and [`CONVEYOR_DIRECTION_KEY`].
+/// The ridges of a [`ConveyorMaterial`] on a child of a conveyor scroll along with the belt.
+pub fn conveyor_plugin(app: &mut App) {
+    app.register_type::<Conveyor>()
+        .add_systems(
+            (read_conveyor_metadata, scroll_conveyor_belts)
+                .chain()
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (convey_characters, convey_bodies)
+                .after(update_grounded)
+                .before(prevent_tunneling)
+                .in_set(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Conveyor {
+    /// Speed in m/s of the belt
+    pub speed: f32,
+    /// Direction in the conveyor's local space, only the horizontal part is used
+    pub direction: Vec3,
+}
+
+impl Default for Conveyor {
+    fn default() -> Self {
+        Self {
+            speed: 2.,
+            direction: Vec3::NEG_Z,
+        }
+    }
+}
+
+impl Conveyor {
+    /// The direction along the belt in the conveyor's local space.
+    pub fn local_direction(&self) -> Vec3 {
+        Vec3::new(self.direction.x, 0., self.direction.z).normalize_or_zero()
+    }
+
+    /// The velocity of the belt in world space for a conveyor with the given transform.
+    pub fn velocity(&self, transform: &GlobalTransform) -> Vec3 {
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        rotation * self.local_direction() * self.speed
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn read_conveyor_metadata(
+    mut conveyors: Query<(&ObjectMetadata, &mut Conveyor), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_conveyor_metadata").entered();
+    for (metadata, mut conveyor) in conveyors.iter_mut() {
+        let mut read = *conveyor;
+        if let Some(speed) = metadata.get(CONVEYOR_SPEED_KEY) {
+            read.speed = speed
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse conveyor speed \"{speed}\""))?;
+        }
+        if let Some(direction) = metadata.get(CONVEYOR_DIRECTION_KEY) {
+            read.direction = parse_direction(direction)
+                .with_context(|| format!("Failed to parse conveyor direction \"{direction}\""))?;
+        }
+        if read != *conveyor {
+            *conveyor = read;
+        }
+    }
+    Ok(())
+}
+
+fn scroll_conveyor_belts(
+    time: Res<Time>,
+    conveyors: Query<&Conveyor>,
+    belts: Query<(&Parent, &Handle<ConveyorMaterial>)>,
+    mut materials: ResMut<Assets<ConveyorMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("scroll_conveyor_belts").entered();
+    for (parent, handle) in belts.iter() {
+        let Ok(conveyor) = conveyors.get(parent.get()) else {
+            continue;
+        };
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        let direction = conveyor.local_direction();
+        // The v coordinate of a plane mesh grows towards -z
+        material.belt.direction = Vec2::new(direction.x, -direction.z);
+        // Wrapping keeps the offset precise, the pattern repeats every meter
+        material.belt.offset = (material.belt.offset + conveyor.speed * time.delta_seconds()) % 1.;
+    }
+}
+
+fn find_conveyor_velocity(
+    entity: Entity,
+    conveyors: &Query<(&Conveyor, &GlobalTransform)>,
+    parents: &Query<&Parent>,
+) -> Option<Vec3> {
+    std::iter::once(entity)
+        .chain(parents.iter_ancestors(entity))
+        .find_map(|entity| conveyors.get(entity).ok())
+        .map(|(conveyor, transform)| conveyor.velocity(transform))
+}
+
+fn convey_characters(
+    time: Res<Time>,
+    rapier_context: Res<RapierContext>,
+    conveyors: Query<(&Conveyor, &GlobalTransform)>,
+    parents: Query<&Parent>,
+    mut characters: Query<(Entity, &Grounded, &Collider, &mut Transform), With<Walking>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("convey_characters").entered();
+    for (entity, grounded, collider, mut transform) in characters.iter_mut() {
+        if !grounded.0 {
+            continue;
+        }
+        // Same ray as in `update_grounded`, with some leeway like for moving platforms
+        let height = collider.raw.compute_local_aabb().maxs.y;
+        let Some((ground, _)) = rapier_context.cast_ray(
+            transform.translation,
+            transform.down(),
+            height * 1.5,
+            true,
+            QueryFilter::new()
+                .exclude_collider(entity)
+                .exclude_sensors(),
+        ) else {
+            continue;
+        };
+        let Some(velocity) = find_conveyor_velocity(ground, &conveyors, &parents) else {
+            continue;
+        };
+        transform.translation += velocity * time.delta_seconds();
+    }
+}
+
+fn convey_bodies(
+    rapier_context: Res<RapierContext>,
+    conveyors: Query<(Entity, &Conveyor, &GlobalTransform)>,
+    mut bodies: Query<(&mut Velocity, &RigidBody), Without<Walking>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("convey_bodies").entered();
+    for (entity, conveyor, transform) in conveyors.iter() {
+        let belt_velocity = conveyor.velocity(transform);
+        let Some(direction) = belt_velocity.try_normalize() else {
+            continue;
+        };
+        for contact_pair in rapier_context.contacts_with(entity) {
+            if !contact_pair.has_any_active_contacts() {
+                continue;
+            }
+            let other = if contact_pair.collider1() == entity {
+                contact_pair.collider2()
+            } else {
+                contact_pair.collider1()
+            };
+            let Ok((mut velocity, rigid_body)) = bodies.get_mut(other) else {
+                continue;
+            };
+            if *rigid_body != RigidBody::Dynamic {
+                continue;
+            }
+            // Bodies that are already faster, e.g. because they were thrown, are not slowed down
+            let along = velocity.linvel.dot(direction);
+            let missing = conveyor.speed.abs() - along;
+            if missing > 0. {
+                velocity.linvel += direction * missing;
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::player_control::player_embodiment::Player;
+use crate::util::trait_extension::{F32Ext, Vec3Ext};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Steers small animals with a [`Critter`] without any pathfinding, so there can be lots of them:
+/// - They wander in a random direction that changes every few seconds and stay close to where they were spawned.
+/// - They flee from the player when they come too close.
+/// - Critters with [`Flocking`] also stay together with, fly in the same direction as and keep some distance from nearby flockmates.
+/// They fly at the height they were spawned at instead of walking on the ground.
+pub fn critter_plugin(app: &mut App) {
+    app.register_type::<Critter>()
+        .register_type::<Flocking>()
+        .add_systems(
+            (steer_critters, flock, apply_critter_velocity)
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Critter {
+    /// Speed in m/s when not fleeing
+    pub wander_speed: f32,
+    /// Speed in m/s when fleeing
+    pub flee_speed: f32,
+    /// The critter flees when the player is closer than this in m
+    pub flee_radius: f32,
+    /// Distance in m from the home the critter wanders at most
+    pub home_radius: f32,
+    /// Set to the critter's translation on its first update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub home: Option<Vec3>,
+    pub wander_direction: Vec3,
+    /// Time in s until a new wander direction is picked
+    pub wander_time: f32,
+    /// Velocity the critter wants to reach this frame
+    #[serde(skip)]
+    pub desired_velocity: Vec3,
+}
+
+impl Default for Critter {
+    fn default() -> Self {
+        Self {
+            wander_speed: 0.8,
+            flee_speed: 4.,
+            flee_radius: 4.,
+            home_radius: 8.,
+            home: None,
+            wander_direction: Vec3::ZERO,
+            wander_time: 0.,
+            desired_velocity: Vec3::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Flocking {
+    /// Flockmates within this distance in m influence each other
+    pub neighbor_radius: f32,
+    /// Flockmates closer than this in m push each other away
+    pub separation_distance: f32,
+    pub cohesion_weight: f32,
+    pub alignment_weight: f32,
+    pub separation_weight: f32,
+}
+
+impl Default for Flocking {
+    fn default() -> Self {
+        Self {
+            neighbor_radius: 5.,
+            separation_distance: 1.,
+            cohesion_weight: 0.5,
+            alignment_weight: 0.8,
+            separation_weight: 2.,
+        }
+    }
+}
+
+/// Fraction of the difference to the desired velocity that is made up per second.
+const STEERING_RATE: f32 = 4.;
+
+/// How strongly flying critters are pulled back to their home height.
+const HEIGHT_CORRECTION: f32 = 0.5;
+
+fn steer_critters(
+    time: Res<Time>,
+    players: Query<&Transform, (With<Player>, Without<Critter>)>,
+    mut critters: Query<(&Transform, &mut Critter)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("steer_critters").entered();
+    let dt = time.delta_seconds();
+    let mut rng = SmallRng::from_entropy();
+    for (transform, mut critter) in critters.iter_mut() {
+        let home = *critter.home.get_or_insert(transform.translation);
+        let position = transform.translation;
+
+        let threat = players
+            .iter()
+            .map(|player| position - player.translation)
+            .map(|away| away.split(Vec3::Y).horizontal)
+            .filter(|away| away.length_squared() < critter.flee_radius.squared())
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        if let Some(away) = threat {
+            critter.desired_velocity = away.normalize_or_zero() * critter.flee_speed;
+            continue;
+        }
+
+        critter.wander_time -= dt;
+        let to_home = (home - position).split(Vec3::Y).horizontal;
+        if to_home.length_squared() > critter.home_radius.squared() {
+            critter.wander_direction = to_home.normalize_or_zero();
+        } else if critter.wander_time <= 0. {
+            critter.wander_time = rng.gen_range(1.0..4.0);
+            // Sometimes, critters just sit still for a while
+            critter.wander_direction = if rng.gen_bool(0.3) {
+                Vec3::ZERO
+            } else {
+                let angle = rng.gen_range(0.0..TAU);
+                Vec3::new(angle.cos(), 0., angle.sin())
+            };
+        }
+        critter.desired_velocity = critter.wander_direction * critter.wander_speed;
+    }
+}
+
+fn flock(mut critters: Query<(Entity, &Transform, &Velocity, &Flocking, &mut Critter)>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("flock").entered();
+    let flockmates: Vec<_> = critters
+        .iter()
+        .map(|(entity, transform, velocity, ..)| (entity, transform.translation, velocity.linvel))
+        .collect();
+    for (entity, transform, _, flocking, mut critter) in critters.iter_mut() {
+        let position = transform.translation;
+        let neighbors: Vec<_> = flockmates
+            .iter()
+            .filter(|(other, other_position, _)| {
+                *other != entity
+                    && position.distance_squared(*other_position)
+                        < flocking.neighbor_radius.squared()
+            })
+            .collect();
+        if neighbors.is_empty() {
+            continue;
+        }
+        let count = neighbors.len() as f32;
+        let center = neighbors
+            .iter()
+            .map(|(_, position, _)| *position)
+            .sum::<Vec3>()
+            / count;
+        let heading = neighbors
+            .iter()
+            .map(|(_, _, velocity)| *velocity)
+            .sum::<Vec3>()
+            / count;
+        let separation: Vec3 = neighbors
+            .iter()
+            .map(|(_, other_position, _)| position - *other_position)
+            .filter(|away| away.length_squared() < flocking.separation_distance.squared())
+            .map(|away| away.normalize_or_zero())
+            .sum();
+
+        critter.desired_velocity += (center - position) * flocking.cohesion_weight
+            + heading * flocking.alignment_weight
+            + separation * flocking.separation_weight;
+    }
+}
+
+fn apply_critter_velocity(
+    time: Res<Time>,
+    mut critters: Query<(&Critter, Option<&Flocking>, &mut Transform, &mut Velocity)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_critter_velocity").entered();
+    let factor = (STEERING_RATE * time.delta_seconds()).min(1.);
+    for (critter, flocking, mut transform, mut velocity) in critters.iter_mut() {
+        let speed_limit = critter.flee_speed.max(critter.wander_speed);
+        let mut desired = critter.desired_velocity.clamp_length_max(speed_limit);
+        desired.y = if flocking.is_some() {
+            // Flyers ignore gravity, so the vertical velocity is steered as well
+            let home_height = critter.home.map_or(transform.translation.y, |home| home.y);
+            (home_height - transform.translation.y) * HEIGHT_CORRECTION
+        } else {
+            // Walkers keep falling as the physics engine sees fit
+            velocity.linvel.y
+        };
+        velocity.linvel = velocity.linvel.lerp(desired, factor);
+
+        let heading = velocity.linvel.split(Vec3::Y).horizontal;
+        if !heading.is_approx_zero() {
+            let target = transform.translation + heading;
+            transform.look_at(target, Vec3::Y);
+        }
+    }
+}
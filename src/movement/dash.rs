@@ -0,0 +1,129 @@
+use crate::movement::general_movement::{
+    apply_jumping, apply_walking, CharacterControllerSystemSet, CharacterUp, Walking,
+};
+use crate::movement::gravity::{Gravity, LocalGravity};
+use crate::util::trait_extension::Vec3Ext;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Lets characters with a [`Dash`] burst forward horizontally for a short time, ignoring gravity while doing so.
+/// A dash requested during the cooldown is remembered for a moment and executed as soon as the cooldown ends.
+pub fn dash_plugin(app: &mut App) {
+    app.register_type::<Dash>().add_system(
+        apply_dash
+            .after(apply_jumping)
+            .after(apply_walking)
+            .in_set(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Dash {
+    /// Horizontal speed in m/s during the dash
+    pub speed: f32,
+    /// Runs while dashing
+    pub duration: Timer,
+    /// Runs after a dash ends. Dashing is possible again once it has finished.
+    pub cooldown: Timer,
+    /// Time in s a dash requested during the cooldown is remembered
+    pub buffer_time: f32,
+    /// Was a dash requested?
+    pub requested: bool,
+    /// Time in s since the last dash request that was not executed yet
+    pub time_since_request: Option<f32>,
+    /// Direction of the current dash, if any
+    pub direction: Option<Vec3>,
+}
+
+impl Default for Dash {
+    fn default() -> Self {
+        Self::new(12., 0.2, 0.8)
+    }
+}
+
+impl Dash {
+    pub fn new(speed: f32, duration: f32, cooldown: f32) -> Self {
+        let mut cooldown = Timer::from_seconds(cooldown, TimerMode::Once);
+        // The first dash does not have to wait
+        let cooldown_duration = cooldown.duration();
+        cooldown.set_elapsed(cooldown_duration);
+        Self {
+            speed,
+            duration: Timer::from_seconds(duration, TimerMode::Once),
+            cooldown,
+            buffer_time: 0.15,
+            requested: false,
+            time_since_request: None,
+            direction: None,
+        }
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        self.direction.is_some()
+    }
+}
+
+fn apply_dash(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    mut characters: Query<(
+        &mut Dash,
+        &Walking,
+        &Transform,
+        &CharacterUp,
+        &ReadMassProperties,
+        Option<&LocalGravity>,
+        &mut ExternalForce,
+        &mut Velocity,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_dash").entered();
+    let dt = time.delta();
+    for (mut dash, walking, transform, up, mass, local_gravity, mut force, mut velocity) in
+        characters.iter_mut()
+    {
+        dash.time_since_request = if dash.requested {
+            Some(0.)
+        } else {
+            let buffer_time = dash.buffer_time;
+            dash.time_since_request
+                .map(|time_since_request| time_since_request + dt.as_secs_f32())
+                .filter(|&time_since_request| time_since_request <= buffer_time)
+        };
+        dash.requested = false;
+
+        if dash.is_dashing() {
+            dash.duration.tick(dt);
+            if dash.duration.finished() {
+                dash.direction = None;
+                dash.cooldown.reset();
+            }
+        } else {
+            dash.cooldown.tick(dt);
+            if dash.time_since_request.is_some() && dash.cooldown.finished() {
+                dash.time_since_request = None;
+                dash.duration.reset();
+                // Without movement input, the character dashes the way it is facing
+                let direction = walking
+                    .direction
+                    .map(|direction| direction.split(up.0).horizontal)
+                    .filter(|direction| !direction.is_approx_zero())
+                    .unwrap_or_else(|| transform.forward().split(up.0).horizontal)
+                    .normalize_or_zero();
+                dash.direction = Some(direction);
+            }
+        }
+
+        let Some(direction) = dash.direction else {
+            continue;
+        };
+        velocity.linvel = direction * dash.speed;
+        let gravity = local_gravity.map(|gravity| gravity.0).unwrap_or(gravity.0);
+        force.force -= gravity * mass.0.mass;
+    }
+}
@@ -0,0 +1,123 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::movement::general_movement::{prevent_tunneling, CharacterControllerSystemSet, Walking};
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::level_stats::PlayerDied;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier3d::prelude::*;
+
+/// Pushes characters out of colliders they overlap with, e.g. because a moving platform drove into them.
+/// Rapier only resolves such overlaps gradually, which lets fast platforms push characters through walls.
+/// Instead, characters are moved out along the shortest way and lose their velocity towards the obstacles.
+/// A character that is squeezed from opposite sides deeper than the configured crush tolerance has no way to escape.
+/// In that case, a [`Crushed`] event is sent once, e.g. for death traps and moving walls. Crushing the player kills them.
+pub fn depenetration_plugin(app: &mut App) {
+    app.add_event::<Crushed>().add_systems(
+        (depenetrate_characters, kill_crushed_players)
+            .chain()
+            .before(prevent_tunneling)
+            .in_set(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crushed {
+    pub character: Entity,
+    /// Sum of the penetration depths of the opposing colliders in m.
+    pub depth: f32,
+}
+
+/// Pushes whose directions are less aligned than this are considered opposing.
+const OPPOSING_ALIGNMENT: f32 = -0.5;
+
+fn depenetrate_characters(
+    config: Res<GameConfig>,
+    rapier_context: Res<RapierContext>,
+    mut characters: Query<(Entity, &mut Transform, &mut Velocity), With<Walking>>,
+    mut crushed_events: EventWriter<Crushed>,
+    mut crushed: Local<HashSet<Entity>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("depenetrate_characters").entered();
+    let tolerance = config.characters.penetration_tolerance;
+    for (entity, mut transform, mut velocity) in characters.iter_mut() {
+        // Each push is the direction out of an obstacle and the penetration depth
+        let pushes: Vec<(Vec3, f32)> = rapier_context
+            .contacts_with(entity)
+            .filter(|contact_pair| contact_pair.has_any_active_contacts())
+            .filter_map(|contact_pair| {
+                let (manifold, contact) = contact_pair.find_deepest_contact()?;
+                let depth = -contact.dist();
+                if depth <= tolerance {
+                    return None;
+                }
+                // The manifold's normal points from the first collider to the second one
+                let direction = if contact_pair.collider1() == entity {
+                    -manifold.normal()
+                } else {
+                    manifold.normal()
+                };
+                Some((direction, depth))
+            })
+            .collect();
+
+        let squeeze_depth = pushes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, (direction, depth))| {
+                pushes[index + 1..]
+                    .iter()
+                    .filter(|(other, _)| direction.dot(*other) < OPPOSING_ALIGNMENT)
+                    .map(move |(_, other_depth)| depth + other_depth)
+            })
+            .max_by(f32::total_cmp);
+        if let Some(squeeze_depth) = squeeze_depth {
+            if squeeze_depth > config.characters.crush_tolerance {
+                // Only the first frame of being stuck counts
+                if crushed.insert(entity) {
+                    crushed_events.send(Crushed {
+                        character: entity,
+                        depth: squeeze_depth,
+                    });
+                }
+            } else {
+                crushed.remove(&entity);
+            }
+            // Pushing out of one side would only push deeper into the other one
+            continue;
+        }
+        crushed.remove(&entity);
+
+        let mut correction = Vec3::ZERO;
+        for (direction, depth) in pushes {
+            let remaining = depth - correction.dot(direction);
+            if remaining > 0. {
+                correction += direction * remaining;
+            }
+            let approach_speed = velocity.linvel.dot(direction);
+            if approach_speed < 0. {
+                velocity.linvel -= direction * approach_speed;
+            }
+        }
+        transform.translation += correction;
+    }
+}
+
+fn kill_crushed_players(
+    mut crushed_events: EventReader<Crushed>,
+    players: Query<(), With<Player>>,
+    mut player_died_events: EventWriter<PlayerDied>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("kill_crushed_players").entered();
+    for crushed in crushed_events.iter() {
+        if players.contains(crushed.character) {
+            info!("Player was crushed");
+            player_died_events.send(PlayerDied {
+                player: crushed.character,
+            });
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::file_system_interaction::audio::{FootstepSet, PlaySoundEvent, Sound};
+use crate::movement::animation_graph::AnimationMarkerReached;
+use crate::movement::general_movement::{CharacterControllerSystemSet, CharacterLanded};
+use crate::movement::surface::GroundSurface;
+use crate::GameState;
+use bevy::prelude::*;
+
+/// Name of the [`AnimationMarker`](crate::movement::animation_graph::AnimationMarker) at which a paw hits the ground.
+pub const FOOTSTEP_MARKER: &str = "footstep";
+
+/// Plays footstep and landing sounds for characters, following the state of their character controller.
+/// A footstep is heard whenever an animation reaches a [`FOOTSTEP_MARKER`], so the sounds keep in time
+/// with the paws no matter how fast the animation plays. A [`CharacterLanded`] is heard as a landing.
+/// Both are taken from the [`FootstepSet`] named by the footsteps of the [`GroundSurface`] below the character.
+pub fn footsteps_plugin(app: &mut App) {
+    app.add_system(
+        play_footsteps
+            .after(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+fn play_footsteps(
+    mut marker_events: EventReader<AnimationMarkerReached>,
+    mut landed_events: EventReader<CharacterLanded>,
+    characters: Query<&GroundSurface>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_footsteps").entered();
+    let steps = marker_events
+        .iter()
+        .filter(|event| event.marker == FOOTSTEP_MARKER)
+        .map(|event| (event.entity, false));
+    let landings = landed_events.iter().map(|event| (event.entity, true));
+    for (entity, is_landing) in steps.chain(landings) {
+        let Ok(ground_surface) = characters.get(entity) else {
+            continue;
+        };
+        let set = FootstepSet::from_surface(ground_surface.definition.footsteps.as_deref());
+        let sound = if is_landing {
+            Sound::Landing(set)
+        } else {
+            Sound::Footstep(set)
+        };
+        sound_events.send(PlaySoundEvent::at(sound, entity));
+    }
+}
@@ -1,256 +1,281 @@
-use anyhow::{Context, Result};
+use bevy::animation::{animation_player, EntityPath, Keyframes, VariableCurve};
 use bevy::prelude::*;
-use std::time::Duration;
+use bevy::transform::TransformSystem;
 
-use bevy_rapier3d::prelude::*;
 mod components;
-use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::audio::{PlaySoundEvent, Sound};
 use crate::level_instantiation::spawning::AnimationEntityLink;
-use crate::util::smoothness_to_lerp_factor;
-use crate::util::trait_extension::{TransformExt, Vec3Ext};
+use crate::movement::surface::{update_ground_surfaces, GroundSurface};
+use crate::world_interaction::status_effect::{StatusEffectKind, StatusEffects};
 use crate::GameState;
-use bevy_mod_sysfail::macros::*;
 pub use components::*;
+pub use foxtrot_character_controller::*;
+use seldom_fn_plugin::FnPluginExt;
 
-/// Handles movement of character controllers, i.e. entities with the [`CharacterControllerBundle`].
-/// The default forces on a character going right are:  
-/// ```text
-/// ┌──────────────────────────────┐
-/// │            Gravity           │
-/// │               ↓              │
-/// │              ╔═╗             │
-/// │   Walking ─► ║ ║ ◄─ Damping  │
-/// │              ╚═╝             │  
-/// │                              │
-/// └──────────────────────────────┘
-/// ```
-/// All physics values are assumed to be in SI units, e.g. forces are measured in N and acceleration in m/s².
+/// Runs the [`foxtrot_character_controller`] for the player and NPCs while playing, see [`character_controller_plugin`] for how characters move.
+/// On top of it, the game scales movement through [`MovementModifiers`] from [`StatusEffects`] and the [`GroundSurface`],
+/// and plays a sound whenever a character jumps.
 ///
-/// The [`Walking`] and [`Jumping`] components are user friendly ways of influencing the corresponding forces.
-/// There is no explicit maximum speed since the damping counteracts all other forces until reaching an equilibrium.
-/// The [`Grounded`] component is used to determine whether the character is on the ground or not.
-/// To influence movement, apply your force by adding it to the character's total [`ExternalForce`] or [`ExternalImpulse`]. This is usually done like this:
-/// - A continuous force like walking: `external_force.force += acceleration * read_mass_properties.0.mass`, with `external_force`: [`ExternalForce`], `read_mass_properties`: [`ReadMassProperties`], and a user-defined `acceleration`: [`Vec3`]
-/// - An instantaneous force (i.e. an impulse) like jumping: `external_impulse.impulse += velocity * read_mass_properties.0.mass`, with `external_impulse`: [`ExternalImpulse`], `read_mass_properties`: [`ReadMassProperties`], and a user-defined `velocity`: [`Vec3`]
-///
-/// Note: you might notice that the normal force is not included in the above diagram. This is because rapier emulates it by moving penetrating colliders out of each other.
+/// Characters with an [`UpperBodyAnimation`] can play a second animation on their upper body on top of the locomotion.
+/// Characters that additionally have [`EmoteAnimations`] play one-shot gestures on it when receiving an [`EmoteEvent`].
 pub fn general_movement_plugin(app: &mut App) {
-    app.register_type::<Grounded>()
-        .register_type::<Jumping>()
-        .register_type::<Velocity>()
-        .register_type::<Walking>()
-        .register_type::<CharacterAnimations>()
+    app.fn_plugin(character_controller_plugin)
+        .configure_set(CharacterControllerSystemSet.in_set(OnUpdate(GameState::Playing)))
+        .register_type::<AlignToSurface>()
+        .register_type::<EmoteAnimations>()
+        .register_type::<PlayingEmote>()
+        .register_type::<UpperBodyAnimation>()
+        .add_event::<EmoteEvent>()
         .add_systems(
             (
-                reset_forces_and_impulses,
-                update_grounded,
-                apply_jumping,
-                apply_walking,
-                rotate_characters,
-                play_animations,
-                sync_models,
-                reset_movement_components,
+                update_movement_modifiers
+                    .after(update_ground_surfaces)
+                    .before(apply_jumping),
+                play_jump_sounds.after(apply_jumping),
+                update_emotes.after(rotate_characters).before(sync_models),
+                start_emotes.after(update_emotes).before(sync_models),
             )
-                .chain()
-                .in_set(GeneralMovementSystemSet)
-                .in_set(OnUpdate(GameState::Playing)),
+                .in_set(CharacterControllerSystemSet),
+        )
+        .add_system(
+            apply_upper_body_animations
+                .after(animation_player)
+                .before(TransformSystem::TransformPropagate)
+                .in_base_set(CoreSet::PostUpdate),
         );
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
-pub struct GeneralMovementSystemSet;
+/// Time in seconds it takes an [`UpperBodyAnimation`] to fade in or out.
+const UPPER_BODY_FADE_TIME: f32 = 0.2;
 
-fn update_grounded(
-    mut query: Query<(Entity, &Transform, &Collider, &mut Grounded)>,
-    rapier_context: Res<RapierContext>,
+fn update_movement_modifiers(
+    mut characters: Query<(
+        &mut MovementModifiers,
+        Option<&StatusEffects>,
+        Option<&GroundSurface>,
+    )>,
 ) {
     #[cfg(feature = "tracing")]
-    let _span = info_span!("update_grounded").entered();
-    for (entity, transform, collider, mut grounded) in &mut query {
-        let height = collider.raw.compute_local_aabb().maxs.y;
-        grounded.0 = rapier_context
-            .cast_ray(
-                transform.translation,
-                transform.down(),
-                height + 0.1,
-                true,
-                QueryFilter::new()
-                    .exclude_collider(entity)
-                    .exclude_sensors(),
-            )
-            .is_some();
+    let _span = info_span!("update_movement_modifiers").entered();
+    for (mut modifiers, status_effects, ground_surface) in characters.iter_mut() {
+        *modifiers = MovementModifiers {
+            speed: status_effects.map_or(1., |effects| effects.factor(StatusEffectKind::Speed)),
+            jump: status_effects.map_or(1., |effects| effects.factor(StatusEffectKind::JumpBoost)),
+            grip: ground_surface.map_or(1., |ground_surface| ground_surface.definition.grip),
+        };
     }
 }
 
-pub fn reset_forces_and_impulses(
-    mut forces: Query<&mut ExternalForce>,
-    mut impulses: Query<&mut ExternalImpulse>,
+fn play_jump_sounds(
+    mut jumped_events: EventReader<CharacterJumped>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
 ) {
     #[cfg(feature = "tracing")]
-    let _span = info_span!("reset_forces_and_impulses").entered();
-    for mut force in &mut forces {
-        *force = default();
-    }
-    for mut impulse in &mut impulses {
-        *impulse = default();
+    let _span = info_span!("play_jump_sounds").entered();
+    for CharacterJumped { entity } in jumped_events.iter() {
+        sound_events.send(PlaySoundEvent::at(Sound::Jump, *entity));
     }
 }
 
-pub fn reset_movement_components(
-    mut walking: Query<&mut Walking>,
-    mut jumpers: Query<&mut Jumping>,
+fn start_emotes(
+    mut commands: Commands,
+    mut emote_events: EventReader<EmoteEvent>,
+    mut characters: Query<(&EmoteAnimations, &mut UpperBodyAnimation)>,
+    animation_clips: Res<Assets<AnimationClip>>,
 ) {
     #[cfg(feature = "tracing")]
-    let _span = info_span!("reset_movement_components").entered();
-    for mut walk in &mut walking {
-        walk.direction = None;
-    }
-    for mut jumper in &mut jumpers {
-        jumper.requested = false;
+    let _span = info_span!("start_emotes").entered();
+    for EmoteEvent { entity, emote } in emote_events.iter() {
+        let Ok((emote_animations, mut upper_body)) = characters.get_mut(*entity) else {
+            warn!("Tried to play emote {emote} on an entity that cannot play emotes");
+            continue;
+        };
+        let Some(clip) = emote_animations.0.get(emote) else {
+            warn!("Character has no animation for emote {emote}");
+            continue;
+        };
+        let Some(duration) = animation_clips.get(clip).map(|clip| clip.duration()) else {
+            continue;
+        };
+        upper_body.play(clip.clone_weak());
+        commands.entity(*entity).insert(PlayingEmote {
+            emote: *emote,
+            remaining: duration,
+        });
     }
 }
 
-pub fn apply_jumping(
-    mut character_query: Query<(
-        &Grounded,
-        &mut ExternalImpulse,
-        &mut Velocity,
-        &ReadMassProperties,
-        &Jumping,
-        &Transform,
-    )>,
+fn update_emotes(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut characters: Query<(Entity, &mut PlayingEmote, &mut UpperBodyAnimation)>,
 ) {
     #[cfg(feature = "tracing")]
-    let _span = info_span!("apply_jumping").entered();
-    for (grounded, mut impulse, mut velocity, mass, jump, transform) in &mut character_query {
-        if jump.requested && grounded.0 {
-            let up = transform.up();
-            impulse.impulse += up * mass.0.mass * jump.speed;
-
-            // Kill any downward velocity. This ensures that repeated jumps are always the same height.
-            // Otherwise the falling velocity from the last tick would dampen the jump velocity.
-            let velocity_components = velocity.linvel.split(up);
-            velocity.linvel = velocity_components.horizontal;
+    let _span = info_span!("update_emotes").entered();
+    let dt = time.delta_seconds();
+    for (entity, mut playing_emote, mut upper_body) in characters.iter_mut() {
+        playing_emote.remaining -= dt;
+        if playing_emote.remaining <= 0.0 {
+            upper_body.stop();
+            commands.entity(entity).remove::<PlayingEmote>();
         }
     }
 }
 
-fn rotate_characters(
+/// Runs after Bevy's own [`animation_player`] and blends the [`UpperBodyAnimation`]s over the bones it has just animated.
+pub(crate) fn apply_upper_body_animations(
     time: Res<Time>,
-    mut player_query: Query<(&Velocity, &mut Transform)>,
-    config: Res<GameConfig>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    mut characters: Query<(&AnimationEntityLink, &mut UpperBodyAnimation)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    mut transforms: Query<&mut Transform>,
 ) {
     #[cfg(feature = "tracing")]
-    let _span = info_span!("rotate_characters").entered();
+    let _span = info_span!("apply_upper_body_animations").entered();
     let dt = time.delta_seconds();
-    for (velocity, mut transform) in player_query.iter_mut() {
-        let up = transform.up();
-        let horizontal_movement = velocity.linvel.split(up).horizontal;
-        if horizontal_movement.is_approx_zero() {
+    for (animation_entity_link, mut upper_body) in characters.iter_mut() {
+        let fade = dt / UPPER_BODY_FADE_TIME;
+        upper_body.weight = if upper_body.active {
+            (upper_body.weight + fade).min(1.0)
+        } else {
+            (upper_body.weight - fade).max(0.0)
+        };
+        if upper_body.weight <= 0.0 {
             continue;
         }
-        let target_transform =
-            transform.looking_at(transform.translation + horizontal_movement, up);
-        // Asymptotic averaging
-        let smoothness = config.characters.rotation_smoothing;
-        let factor = smoothness_to_lerp_factor(smoothness, dt);
-        let rotation = transform.rotation.slerp(target_transform.rotation, factor);
-        transform.rotation = rotation;
-    }
-}
-
-#[sysfail(log(level = "error"))]
-fn play_animations(
-    mut animation_player: Query<&mut AnimationPlayer>,
-    characters: Query<(
-        &Velocity,
-        &Transform,
-        &Grounded,
-        &AnimationEntityLink,
-        &CharacterAnimations,
-    )>,
-) -> Result<()> {
-    #[cfg(feature = "tracing")]
-    let _span = info_span!("play_animations").entered();
-    for (velocity, transform, grounded, animation_entity_link, animations) in characters.iter() {
-        let mut animation_player = animation_player
-            .get_mut(animation_entity_link.0)
-            .context("animation_entity_link held entity without animation player")?;
-
-        let has_horizontal_movement = !velocity
-            .linvel
-            .split(transform.up())
-            .horizontal
-            .is_approx_zero();
-
-        if !grounded.0 {
-            animation_player
-                .play_with_transition(animations.aerial.clone_weak(), Duration::from_secs_f32(0.2))
-                .repeat();
-        } else if has_horizontal_movement {
-            animation_player
-                .play_with_transition(animations.walk.clone_weak(), Duration::from_secs_f32(0.2))
-                .repeat();
+        let Some(clip) = animation_clips.get(&upper_body.clip) else {
+            continue;
+        };
+        upper_body.elapsed += dt;
+        let duration = clip.duration().max(1e-5);
+        let elapsed = if upper_body.repeat {
+            upper_body.elapsed % duration
         } else {
-            animation_player
-                .play_with_transition(animations.idle.clone_weak(), Duration::from_secs_f32(0.2))
-                .repeat();
+            upper_body.elapsed.min(duration)
+        };
+
+        let root = animation_entity_link.0;
+        let Ok(root_name) = names.get(root) else {
+            continue;
+        };
+        let mut masked_bones = Vec::new();
+        collect_masked_bones(
+            root,
+            vec![root_name.clone()],
+            &upper_body.mask_root,
+            false,
+            &children,
+            &names,
+            &mut masked_bones,
+        );
+        for (bone, path) in masked_bones {
+            let Some(curves) = clip.get_curves_by_path(&path) else {
+                continue;
+            };
+            let Ok(mut transform) = transforms.get_mut(bone) else {
+                continue;
+            };
+            for curve in curves {
+                blend_curve(curve, elapsed, upper_body.weight, &mut transform);
+            }
         }
     }
-    Ok(())
 }
 
-pub fn apply_walking(
-    mut character_query: Query<(
-        &mut ExternalForce,
-        &Walking,
-        &mut Velocity,
-        &Grounded,
-        &ReadMassProperties,
-        &Transform,
-    )>,
+/// Collects all bones below `entity` that are part of the mask, together with their path from the animation root.
+pub(crate) fn collect_masked_bones(
+    entity: Entity,
+    path: Vec<Name>,
+    mask_root: &Name,
+    is_masked: bool,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+    masked_bones: &mut Vec<(Entity, EntityPath)>,
 ) {
-    #[cfg(feature = "tracing")]
-    let _span = info_span!("apply_walking").entered();
-    for (mut force, walking, mut velocity, grounded, mass, transform) in &mut character_query {
-        let mass = mass.0.mass;
-        if let Some(acceleration) = walking.get_acceleration(grounded.0) {
-            let walking_force = acceleration * mass;
-            force.force += walking_force;
-        } else if grounded.0 {
-            let velocity_components = velocity.linvel.split(transform.up());
-            if velocity_components.horizontal.length_squared()
-                < walking.stopping_speed * walking.stopping_speed
-            {
-                velocity.linvel = velocity_components.vertical;
-            } else if let Some(braking_direction) =
-                velocity_components.horizontal.try_normalize().map(|v| -v)
-            {
-                let braking_force = walking.braking_acceleration * braking_direction * mass;
-                force.force += braking_force;
-            }
+    let is_masked = is_masked || path.last() == Some(mask_root);
+    let Ok(entity_children) = children.get(entity) else {
+        if is_masked {
+            masked_bones.push((entity, EntityPath { parts: path }));
+        }
+        return;
+    };
+    for &child in entity_children.iter() {
+        if let Ok(name) = names.get(child) {
+            let mut child_path = path.clone();
+            child_path.push(name.clone());
+            collect_masked_bones(
+                child,
+                child_path,
+                mask_root,
+                is_masked,
+                children,
+                names,
+                masked_bones,
+            );
         }
     }
+    if is_masked {
+        masked_bones.push((entity, EntityPath { parts: path }));
+    }
 }
 
-#[sysfail(log(level = "error"))]
-fn sync_models(
-    time: Res<Time>,
-    mut commands: Commands,
-    without_model: Query<(&Transform, &Visibility), Without<Model>>,
-    mut with_model: Query<(Entity, &mut Transform, &mut Visibility, &Model)>,
-    game_config: Res<GameConfig>,
-) -> Result<()> {
-    let dt = time.delta_seconds();
-    for (model_entity, mut model_transform, mut visibility, model) in with_model.iter_mut() {
-        if let Ok((target_transform, target_visibility)) = without_model.get(model.target) {
-            let smoothness = game_config.characters.model_sync_smoothing;
-            let factor = smoothness_to_lerp_factor(smoothness, dt);
-            *model_transform = model_transform.lerp(*target_transform, factor);
-            *visibility = *target_visibility;
-        } else {
-            commands.entity(model_entity).despawn_recursive();
+/// Samples the curve the same way [`animation_player`] does and blends the result into the transform.
+pub(crate) fn blend_curve(
+    curve: &VariableCurve,
+    elapsed: f32,
+    weight: f32,
+    transform: &mut Transform,
+) {
+    let timestamps = &curve.keyframe_timestamps;
+    let (start, end, lerp) =
+        match timestamps.binary_search_by(|timestamp| timestamp.total_cmp(&elapsed)) {
+            _ if timestamps.len() < 2 => (0, 0, 0.0),
+            Ok(index) => (index, index, 0.0),
+            Err(0) => (0, 0, 0.0),
+            Err(index) if index >= timestamps.len() => {
+                (timestamps.len() - 1, timestamps.len() - 1, 0.0)
+            }
+            Err(index) => {
+                let (start, end) = (index - 1, index);
+                let lerp = (elapsed - timestamps[start]) / (timestamps[end] - timestamps[start]);
+                (start, end, lerp)
+            }
+        };
+    match &curve.keyframes {
+        Keyframes::Rotation(keyframes) => {
+            let (Some(&rotation_start), Some(&rotation_end)) =
+                (keyframes.get(start), keyframes.get(end))
+            else {
+                return;
+            };
+            // Choose the smallest angle for the rotation
+            let rotation_end = if rotation_end.dot(rotation_start) < 0.0 {
+                -rotation_end
+            } else {
+                rotation_end
+            };
+            let rotation = rotation_start
+                .normalize()
+                .slerp(rotation_end.normalize(), lerp);
+            transform.rotation = transform.rotation.slerp(rotation, weight);
+        }
+        Keyframes::Translation(keyframes) => {
+            let (Some(translation_start), Some(translation_end)) =
+                (keyframes.get(start), keyframes.get(end))
+            else {
+                return;
+            };
+            let translation = translation_start.lerp(*translation_end, lerp);
+            transform.translation = transform.translation.lerp(translation, weight);
+        }
+        Keyframes::Scale(keyframes) => {
+            let (Some(scale_start), Some(scale_end)) = (keyframes.get(start), keyframes.get(end))
+            else {
+                return;
+            };
+            let scale = scale_start.lerp(*scale_end, lerp);
+            transform.scale = transform.scale.lerp(scale, weight);
         }
     }
-    Ok(())
 }
@@ -1,137 +1,102 @@
 use bevy::prelude::*;
-use bevy_rapier3d::prelude::*;
+use bevy::utils::HashMap;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use strum_macros::{Display, EnumIter};
 
-#[derive(Debug, Clone, Bundle)]
-pub struct CharacterControllerBundle {
-    pub gravity_scale: GravityScale,
-    pub mass: ColliderMassProperties,
-    pub read_mass: ReadMassProperties,
-    pub walking: Walking,
-    pub jumping: Jumping,
-    pub grounded: Grounded,
-    pub damping: Damping,
-    pub rigid_body: RigidBody,
-    pub locked_axes: LockedAxes,
-    pub collider: Collider,
-    pub force: ExternalForce,
-    pub impulse: ExternalImpulse,
-    pub velocity: Velocity,
-    pub dominance: Dominance,
-}
+/// Lets a character walk on curved terrain like spherical planets or loops by standing perpendicular to the ground
+/// and being pulled towards it instead of in the direction of gravity.
+/// While in the air, the character keeps the up direction of the last ground it stood on.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct AlignToSurface;
 
-impl Default for CharacterControllerBundle {
-    fn default() -> Self {
-        Self {
-            read_mass: default(),
-            gravity_scale: GravityScale(1.0),
-            force: default(),
-            mass: ColliderMassProperties::Mass(3.0),
-            walking: default(),
-            jumping: default(),
-            grounded: default(),
-            damping: Damping {
-                linear_damping: 1.5,
-                ..default()
-            },
-            collider: default(),
-            rigid_body: RigidBody::Dynamic,
-            locked_axes: LockedAxes::ROTATION_LOCKED,
-            impulse: default(),
-            velocity: default(),
-            dominance: default(),
-        }
-    }
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Reflect,
+    FromReflect,
+    Serialize,
+    Deserialize,
+    EnumIter,
+    Display,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum Emote {
+    Wave,
+    Nod,
+    Point,
 }
 
-impl CharacterControllerBundle {
-    pub fn capsule(height: f32, radius: f32) -> Self {
-        Self {
-            collider: Collider::capsule_y(height / 2., radius),
-            ..default()
-        }
-    }
-}
-
-#[derive(Debug, Clone, Eq, PartialEq, Component, Serialize, Deserialize)]
-pub struct Model {
-    pub(crate) target: Entity,
-}
+/// One-shot gesture clips of a character. Characters without a clip for an emote ignore requests to play it.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct EmoteAnimations(pub HashMap<Emote, Handle<AnimationClip>>);
 
+/// Present on characters while they are playing an emote on their [`UpperBodyAnimation`].
 #[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
-pub struct Walking {
-    /// Acceleration on the ground
-    pub ground_acceleration: f32,
-    /// Acceleration on the ground when[`Walking::sprinting`] is `true`
-    pub sprinting_acceleration: f32,
-    /// Acceleration in the air
-    pub aerial_acceleration: f32,
-    /// Acceleration in opposide direction of velocity when not explicitely walking, i.e. [`Walking::direction`] is [`Option::None`]
-    pub braking_acceleration: f32,
-    /// Speed at which we stop braking and just set the horizontal velocity to 0
-    pub stopping_speed: f32,
-    /// Direction in which we want to walk this tick. When not normalized, the acceleration will be scaled accordingly.
-    pub direction: Option<Vec3>,
-    /// Whether we are sprinting this tick
-    pub sprinting: bool,
+pub struct PlayingEmote {
+    pub emote: Emote,
+    /// Time in seconds until the emote has finished playing.
+    pub remaining: f32,
 }
 
-impl Walking {
-    pub fn get_acceleration(&self, grounded: bool) -> Option<Vec3> {
-        let acceleration = if grounded {
-            if self.sprinting {
-                self.sprinting_acceleration
-            } else {
-                self.ground_acceleration
-            }
-        } else {
-            self.aerial_acceleration
-        };
-        self.direction.map(|dir| dir * acceleration)
-    }
+/// An animation layered on top of the locomotion animations from the
+/// [`AnimationGraph`](crate::movement::animation_graph::AnimationGraph).
+/// It only drives the bones below [`UpperBodyAnimation::mask_root`], so the character can
+/// gesture, aim or carry something while the legs keep walking.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct UpperBodyAnimation {
+    /// Name of the bone that, together with all of its descendants, is driven by this layer.
+    pub mask_root: Name,
+    pub clip: Handle<AnimationClip>,
+    pub active: bool,
+    pub repeat: bool,
+    /// Time in seconds since the clip was started.
+    pub elapsed: f32,
+    /// Blend weight in `[0, 1]`. Fades in while the layer is active and out otherwise.
+    pub weight: f32,
 }
 
-impl Default for Walking {
-    fn default() -> Self {
+impl UpperBodyAnimation {
+    pub fn new(mask_root: impl Into<Cow<'static, str>>) -> Self {
         Self {
-            ground_acceleration: 14.,
-            sprinting_acceleration: 19.,
-            aerial_acceleration: 9.,
-            braking_acceleration: 5.,
-            stopping_speed: 0.1,
-            direction: None,
-            sprinting: false,
+            mask_root: Name::new(mask_root),
+            ..default()
         }
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Component, Reflect, Default, Serialize, Deserialize)]
-#[reflect(Component, Serialize, Deserialize)]
-pub struct Grounded(pub bool);
+    pub fn play(&mut self, clip: Handle<AnimationClip>) -> &mut Self {
+        if !self.active || self.clip != clip {
+            self.elapsed = 0.0;
+        }
+        self.clip = clip;
+        self.active = true;
+        self.repeat = false;
+        self
+    }
 
-#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
-#[reflect(Component, Serialize, Deserialize)]
-pub struct Jumping {
-    /// Speed of the jump in m/s
-    pub speed: f32,
-    /// Was jump requested?
-    pub requested: bool,
-}
+    pub fn repeat(&mut self) -> &mut Self {
+        self.repeat = true;
+        self
+    }
 
-impl Default for Jumping {
-    fn default() -> Self {
-        Self {
-            speed: 3.5,
-            requested: false,
-        }
+    /// Fades the layer out, handing the upper body back to the locomotion animations.
+    pub fn stop(&mut self) {
+        self.active = false;
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
-#[reflect(Component)]
-pub struct CharacterAnimations {
-    pub idle: Handle<AnimationClip>,
-    pub walk: Handle<AnimationClip>,
-    pub aerial: Handle<AnimationClip>,
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EmoteEvent {
+    pub entity: Entity,
+    pub emote: Emote,
 }
@@ -0,0 +1,294 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::config::GameConfig;
+use crate::movement::general_movement::{
+    update_grounded, AlignToSurface, CharacterControllerSystemSet, CharacterUp, Grounded,
+};
+use crate::util::smoothness_to_lerp_factor;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Handles the direction and strength of gravity.
+/// The global [`Gravity`] resource is passed on to rapier and can be changed with the `gravity` console command.
+/// Areas with a different gravity are placed in the level via the glTF node name marker `[gravity: <x>, <y>, <z>]`,
+/// which turns everything inside the node's cube into a [`GravityZone`], e.g. `[gravity: 0, -1.6, 0]` for moon gravity.
+/// Dynamic bodies inside a zone get a [`LocalGravity`] and are pushed by the difference to the global gravity.
+/// Characters turn towards their [`CharacterUp`], which points against the gravity acting on them,
+/// so walking and jumping keep working in zones with sideways or inverted gravity.
+/// Characters with [`AlignToSurface`] instead stand on the ground below them and are pulled towards it,
+/// e.g. for walking around a spherical planet.
+/// Overlapping zones are not supported.
+pub fn gravity_plugin(app: &mut App) {
+    app.register_type::<Gravity>()
+        .register_type::<GravityZone>()
+        .register_type::<LocalGravity>()
+        .init_resource::<Gravity>()
+        .add_system(sync_rapier_gravity.run_if(resource_changed::<Gravity>()))
+        .add_systems(
+            (read_gravity_markers, update_local_gravity)
+                .chain()
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                update_character_up,
+                apply_gravity,
+                apply_surface_gravity,
+                align_characters_to_up,
+            )
+                .chain()
+                .after(update_grounded)
+                .in_set(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "gravity",
+            "Sets the global gravity in m/s², e.g. \"gravity 0 -1.6 0\". Without arguments, prints the current gravity",
+            set_gravity,
+        );
+}
+
+/// The gravity outside of any [`GravityZone`] in m/s².
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct Gravity(pub Vec3);
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self(Vec3::Y * -9.81)
+    }
+}
+
+/// Overrides the gravity of all dynamic bodies inside the cube of this entity's transform.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct GravityZone {
+    pub gravity: Vec3,
+}
+
+/// The gravity acting on a body that is inside a [`GravityZone`]. Removed when the body leaves the zone.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct LocalGravity(pub Vec3);
+
+static GRAVITY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[gravity:\s*(-?\d+(?:\.\d+)?),\s*(-?\d+(?:\.\d+)?),\s*(-?\d+(?:\.\d+)?)\]")
+        .expect("Failed to compile gravity regex")
+});
+
+fn set_gravity(world: &mut World, args: &[&str]) -> Result<String> {
+    if args.is_empty() {
+        return Ok(format!("Gravity: {}", world.resource::<Gravity>().0));
+    }
+    let [x, y, z] = args else {
+        bail!("Usage: gravity <x> <y> <z>");
+    };
+    let parse = |value: &str| {
+        value
+            .parse::<f32>()
+            .with_context(|| format!("Failed to parse gravity component \"{value}\""))
+    };
+    let gravity = Vec3::new(parse(x)?, parse(y)?, parse(z)?);
+    world.resource_mut::<Gravity>().0 = gravity;
+    Ok(format!("Set gravity to {gravity}"))
+}
+
+fn sync_rapier_gravity(gravity: Res<Gravity>, mut rapier_config: ResMut<RapierConfiguration>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("sync_rapier_gravity").entered();
+    rapier_config.gravity = gravity.0;
+}
+
+#[sysfail(log(level = "error"))]
+fn read_gravity_markers(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name), Added<Name>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_gravity_markers").entered();
+    for (entity, name) in added_name.iter() {
+        let name = name.to_lowercase();
+        let Some(captures) = GRAVITY_REGEX.captures(&name) else {
+            continue;
+        };
+        let parse = |index: usize| {
+            captures[index]
+                .parse::<f32>()
+                .with_context(|| format!("Failed to parse gravity zone: {name}"))
+        };
+        commands.entity(entity).insert(GravityZone {
+            gravity: Vec3::new(parse(1)?, parse(2)?, parse(3)?),
+        });
+    }
+    Ok(())
+}
+
+fn update_local_gravity(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    zones: Query<(&GravityZone, &GlobalTransform)>,
+    affected: Query<(Entity, &LocalGravity)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_local_gravity").entered();
+    let mut bodies_in_zones = HashMap::new();
+    for (zone, transform) in zones.iter() {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let shape = Collider::cuboid(scale.x, scale.y, scale.z);
+        rapier_context.intersections_with_shape(
+            translation,
+            rotation,
+            &shape,
+            QueryFilter::only_dynamic().exclude_sensors(),
+            |collider| {
+                let body = rapier_context.collider_parent(collider).unwrap_or(collider);
+                bodies_in_zones.insert(body, zone.gravity);
+                true
+            },
+        );
+    }
+    for (entity, local_gravity) in affected.iter() {
+        match bodies_in_zones.get(&entity) {
+            None => {
+                commands.entity(entity).remove::<LocalGravity>();
+            }
+            Some(gravity) if *gravity == local_gravity.0 => {
+                bodies_in_zones.remove(&entity);
+            }
+            Some(_) => {}
+        }
+    }
+    for (body, gravity) in bodies_in_zones {
+        if let Some(mut entity) = commands.get_entity(body) {
+            entity.insert(LocalGravity(gravity));
+        }
+    }
+}
+
+/// How far below its collider a character with [`AlignToSurface`] looks for the ground, in m.
+/// Larger values let characters follow tighter convex curves.
+const SURFACE_PROBE_DISTANCE: f32 = 0.5;
+
+/// Rapier already applies the global gravity, so only the difference to it needs to be added.
+fn apply_gravity(
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    mut bodies: Query<
+        (
+            Entity,
+            &LocalGravity,
+            &ReadMassProperties,
+            Option<&GravityScale>,
+            Option<&mut ExternalForce>,
+        ),
+        Without<AlignToSurface>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_gravity").entered();
+    for (entity, local_gravity, mass, gravity_scale, force) in bodies.iter_mut() {
+        let gravity_scale = gravity_scale.map(|scale| scale.0).unwrap_or(1.);
+        let additional_force = (local_gravity.0 - gravity.0) * mass.0.mass * gravity_scale;
+        match force {
+            Some(mut force) => force.force += additional_force,
+            None => {
+                commands.entity(entity).insert(ExternalForce {
+                    force: additional_force,
+                    ..default()
+                });
+            }
+        }
+    }
+}
+
+fn update_character_up(
+    gravity: Res<Gravity>,
+    rapier_context: Res<RapierContext>,
+    mut characters: Query<(
+        Entity,
+        &Transform,
+        &Collider,
+        &Grounded,
+        &mut CharacterUp,
+        Option<&LocalGravity>,
+        Option<&AlignToSurface>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_character_up").entered();
+    for (entity, transform, collider, grounded, mut up, local_gravity, align_to_surface) in
+        characters.iter_mut()
+    {
+        let target_up = if align_to_surface.is_some() {
+            if !grounded.0 {
+                continue;
+            }
+            let height = collider.raw.compute_local_aabb().maxs.y;
+            rapier_context
+                .cast_ray_and_get_normal(
+                    transform.translation,
+                    transform.down(),
+                    height + SURFACE_PROBE_DISTANCE,
+                    true,
+                    QueryFilter::new()
+                        .exclude_collider(entity)
+                        .exclude_sensors(),
+                )
+                .and_then(|(_, intersection)| intersection.normal.try_normalize())
+        } else {
+            let gravity = local_gravity.map(|gravity| gravity.0).unwrap_or(gravity.0);
+            (-gravity).try_normalize()
+        };
+        if let Some(target_up) = target_up {
+            up.0 = target_up;
+        }
+    }
+}
+
+/// Replaces the gravity of [`AlignToSurface`] characters by one of the same strength that points into the ground.
+fn apply_surface_gravity(
+    gravity: Res<Gravity>,
+    mut characters: Query<
+        (
+            &CharacterUp,
+            Option<&LocalGravity>,
+            &ReadMassProperties,
+            Option<&GravityScale>,
+            &mut ExternalForce,
+        ),
+        With<AlignToSurface>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_surface_gravity").entered();
+    for (up, local_gravity, mass, gravity_scale, mut force) in characters.iter_mut() {
+        let strength = local_gravity
+            .map(|gravity| gravity.0)
+            .unwrap_or(gravity.0)
+            .length();
+        let gravity_scale = gravity_scale.map(|scale| scale.0).unwrap_or(1.);
+        force.force += (-up.0 * strength - gravity.0) * mass.0.mass * gravity_scale;
+    }
+}
+
+fn align_characters_to_up(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut characters: Query<(&mut Transform, &CharacterUp)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("align_characters_to_up").entered();
+    let dt = time.delta_seconds();
+    let factor = smoothness_to_lerp_factor(config.characters.rotation_smoothing, dt);
+    for (mut transform, up) in characters.iter_mut() {
+        let target_rotation = Quat::from_rotation_arc(transform.up(), up.0) * transform.rotation;
+        transform.rotation = transform.rotation.slerp(target_rotation, factor);
+    }
+}
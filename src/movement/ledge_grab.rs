@@ -0,0 +1,232 @@
+use crate::level_instantiation::spawning::AnimationEntityLink;
+use crate::movement::general_movement::{
+    apply_jumping, update_grounded, CharacterControllerSystemSet, CharacterUp, Grounded, Jumping,
+    Walking,
+};
+use crate::util::trait_extension::Vec3Ext;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Lets airborne characters with an enabled [`LedgeGrabbing`] grab ledges they are walking towards and pull themselves up.
+/// A ledge is found by casting a ray forward to find a wall and then casting a small sphere down from above the character
+/// just behind the wall's surface. If the top is within reach and there is room to stand on it, the character starts [`Mantling`]:
+/// it is moved up and onto the ledge over [`LedgeGrabbing::mantle_duration`] while playing [`LedgeGrabbing::animation`].
+pub fn ledge_grab_plugin(app: &mut App) {
+    app.register_type::<LedgeGrabbing>()
+        .register_type::<Mantling>()
+        .add_systems(
+            (detect_ledges, mantle)
+                .chain()
+                .after(update_grounded)
+                .before(apply_jumping)
+                .in_set(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            play_mantle_animations
+                .after(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct LedgeGrabbing {
+    pub enabled: bool,
+    /// Distance in m between the character's collider and a wall at which its ledge can be grabbed
+    pub reach: f32,
+    /// Highest ledge in m above the character's center that can be grabbed
+    pub max_height: f32,
+    /// Time in s it takes to pull up
+    pub mantle_duration: f32,
+    /// Played while mantling instead of the usual locomotion animations
+    pub animation: Option<Handle<AnimationClip>>,
+}
+
+impl Default for LedgeGrabbing {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            reach: 0.3,
+            max_height: 1.0,
+            mantle_duration: 0.4,
+            animation: None,
+        }
+    }
+}
+
+/// Present on characters while they are pulling themselves up onto a ledge.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Mantling {
+    pub start: Vec3,
+    /// Where the character stands on the ledge afterwards
+    pub target: Vec3,
+    pub up: Vec3,
+    pub elapsed: f32,
+    pub duration: f32,
+}
+
+impl Mantling {
+    /// The character first climbs up and then moves forward onto the ledge.
+    fn position(&self) -> Vec3 {
+        const CLIMB_PORTION: f32 = 0.6;
+        let progress = (self.elapsed / self.duration).clamp(0., 1.);
+        let climb = (self.target - self.start).dot(self.up) * self.up;
+        let top = self.start + climb;
+        if progress < CLIMB_PORTION {
+            self.start.lerp(top, progress / CLIMB_PORTION)
+        } else {
+            top.lerp(
+                self.target,
+                (progress - CLIMB_PORTION) / (1. - CLIMB_PORTION),
+            )
+        }
+    }
+}
+
+/// Radius in m of the sphere that is cast down to find the top of a ledge.
+const PROBE_RADIUS: f32 = 0.1;
+
+/// Gap in m between the character and the ledge after mantling.
+const STANDING_CLEARANCE: f32 = 0.05;
+
+fn detect_ledges(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    characters: Query<
+        (
+            Entity,
+            &LedgeGrabbing,
+            &Transform,
+            &Collider,
+            &Grounded,
+            &CharacterUp,
+            &Walking,
+        ),
+        Without<Mantling>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("detect_ledges").entered();
+    for (entity, ledge_grabbing, transform, collider, grounded, up, walking) in characters.iter() {
+        if !ledge_grabbing.enabled || grounded.0 {
+            continue;
+        }
+        // Only grab ledges the character is moving towards
+        let Some(forward) = walking
+            .direction
+            .map(|direction| direction.split(up.0).horizontal)
+            .filter(|direction| !direction.is_approx_zero())
+            .map(Vec3::normalize)
+        else {
+            continue;
+        };
+        let extents = collider.raw.compute_local_aabb().maxs;
+        let (radius, half_height) = (extents.x, extents.y);
+        let filter = QueryFilter::new()
+            .exclude_collider(entity)
+            .exclude_sensors()
+            .exclude_dynamic();
+
+        let Some((_, wall_distance)) = rapier_context.cast_ray(
+            transform.translation,
+            forward,
+            radius + ledge_grabbing.reach,
+            true,
+            filter,
+        ) else {
+            continue;
+        };
+
+        let probe_origin = transform.translation
+            + up.0 * (ledge_grabbing.max_height + PROBE_RADIUS)
+            + forward * (wall_distance + PROBE_RADIUS * 2.);
+        let Some((_, toi)) = rapier_context.cast_shape(
+            probe_origin,
+            Quat::IDENTITY,
+            -up.0,
+            &Collider::ball(PROBE_RADIUS),
+            ledge_grabbing.max_height,
+            filter,
+        ) else {
+            continue;
+        };
+        // The probe started inside the wall, so the ledge is out of reach
+        if toi.toi <= 0. {
+            continue;
+        }
+        let ledge_top = probe_origin - up.0 * (toi.toi + PROBE_RADIUS);
+        if (ledge_top - transform.translation).dot(up.0) < 0. {
+            continue;
+        }
+
+        let target = ledge_top
+            + up.0 * (half_height + STANDING_CLEARANCE)
+            + forward * (radius + PROBE_RADIUS);
+        let blocked = rapier_context
+            .intersection_with_shape(target, transform.rotation, collider, filter)
+            .is_some();
+        if blocked {
+            continue;
+        }
+        commands.entity(entity).insert(Mantling {
+            start: transform.translation,
+            target,
+            up: up.0,
+            elapsed: 0.,
+            duration: ledge_grabbing.mantle_duration,
+        });
+    }
+}
+
+fn mantle(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut characters: Query<(
+        Entity,
+        &mut Mantling,
+        &mut Transform,
+        &mut Velocity,
+        &mut Jumping,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("mantle").entered();
+    let dt = time.delta_seconds();
+    for (entity, mut mantling, mut transform, mut velocity, mut jumping) in characters.iter_mut() {
+        mantling.elapsed += dt;
+        transform.translation = mantling.position();
+        velocity.linvel = Vec3::ZERO;
+        // Jumping off a ledge halfway through pulling up would fling the character through it
+        jumping.requested = false;
+        jumping.time_since_request = None;
+        if mantling.elapsed >= mantling.duration {
+            commands.entity(entity).remove::<Mantling>();
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn play_mantle_animations(
+    mut animation_player: Query<&mut AnimationPlayer>,
+    characters: Query<(&AnimationEntityLink, &LedgeGrabbing), With<Mantling>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_mantle_animations").entered();
+    for (animation_entity_link, ledge_grabbing) in characters.iter() {
+        let Some(animation) = &ledge_grabbing.animation else {
+            continue;
+        };
+        let mut animation_player = animation_player
+            .get_mut(animation_entity_link.0)
+            .context("animation_entity_link held entity without animation player")?;
+        animation_player.play_with_transition(animation.clone_weak(), Duration::from_secs_f32(0.1));
+    }
+    Ok(())
+}
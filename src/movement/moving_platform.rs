@@ -0,0 +1,159 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::general_movement::{
+    prevent_tunneling, update_grounded, CharacterControllerSystemSet, Grounded, Walking,
+};
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Moves kinematic bodies with a [`MovingPlatform`] along their waypoints and carries the characters standing on them.
+/// Without this, characters would stay in place while the platform slides away beneath them.
+/// Platforms spawned as objects read their waypoints and speed from their [`ObjectMetadata`]:
+/// - `waypoints`: offsets from the spawn position separated by `;`, e.g. `0, 0, 0; 0, 4, 0`
+/// - `speed`: in m/s
+pub fn moving_platform_plugin(app: &mut App) {
+    app.register_type::<MovingPlatform>().add_systems(
+        (read_platform_metadata, move_platforms, carry_characters)
+            .chain()
+            .after(update_grounded)
+            .before(prevent_tunneling)
+            .in_set(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct MovingPlatform {
+    /// Offsets from the platform's starting position. After the last one, the platform returns to the first one.
+    pub waypoints: Vec<Vec3>,
+    /// Speed in m/s
+    pub speed: f32,
+    pub next_waypoint: usize,
+    /// Set to the platform's translation on its first update. Saved levels store the platform at this position.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Vec3>,
+    /// Distance the platform moved in the last frame
+    #[serde(skip)]
+    pub delta: Vec3,
+}
+
+impl Default for MovingPlatform {
+    fn default() -> Self {
+        Self {
+            waypoints: vec![Vec3::ZERO, Vec3::Y * 3.],
+            speed: 1.5,
+            next_waypoint: 0,
+            origin: None,
+            delta: Vec3::ZERO,
+        }
+    }
+}
+
+fn parse_waypoints(value: &str) -> Result<Vec<Vec3>> {
+    value
+        .split(';')
+        .map(|waypoint| {
+            let coordinates = waypoint
+                .split(',')
+                .map(|coordinate| {
+                    coordinate
+                        .trim()
+                        .parse::<f32>()
+                        .with_context(|| format!("Failed to parse coordinate \"{coordinate}\""))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            match coordinates[..] {
+                [x, y, z] => Ok(Vec3::new(x, y, z)),
+                _ => bail!("Waypoint \"{waypoint}\" does not have three coordinates"),
+            }
+        })
+        .collect()
+}
+
+#[sysfail(log(level = "error"))]
+fn read_platform_metadata(
+    mut platforms: Query<(&ObjectMetadata, &mut MovingPlatform), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_platform_metadata").entered();
+    for (metadata, mut platform) in platforms.iter_mut() {
+        if let Some(waypoints) = metadata.get("waypoints") {
+            let waypoints = parse_waypoints(waypoints)
+                .with_context(|| format!("Failed to parse platform waypoints \"{waypoints}\""))?;
+            if waypoints.is_empty() {
+                continue;
+            }
+            platform.next_waypoint %= waypoints.len();
+            platform.waypoints = waypoints;
+        }
+        if let Some(speed) = metadata.get("speed") {
+            platform.speed = speed
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse platform speed \"{speed}\""))?;
+        }
+    }
+    Ok(())
+}
+
+fn move_platforms(time: Res<Time>, mut platforms: Query<(&mut MovingPlatform, &mut Transform)>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("move_platforms").entered();
+    let dt = time.delta_seconds();
+    for (mut platform, mut transform) in platforms.iter_mut() {
+        let origin = *platform.origin.get_or_insert(transform.translation);
+        let Some(&waypoint) = platform.waypoints.get(platform.next_waypoint) else {
+            platform.delta = Vec3::ZERO;
+            continue;
+        };
+        let to_target = origin + waypoint - transform.translation;
+        let max_distance = platform.speed * dt;
+        if to_target.length() <= max_distance {
+            platform.next_waypoint = (platform.next_waypoint + 1) % platform.waypoints.len();
+        }
+        platform.delta = to_target.clamp_length_max(max_distance);
+        if platform.delta != Vec3::ZERO {
+            transform.translation += platform.delta;
+        }
+    }
+}
+
+fn carry_characters(
+    rapier_context: Res<RapierContext>,
+    platforms: Query<&MovingPlatform>,
+    mut characters: Query<
+        (Entity, &Grounded, &Collider, &mut Transform),
+        (With<Walking>, Without<MovingPlatform>),
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("carry_characters").entered();
+    for (entity, grounded, collider, mut transform) in characters.iter_mut() {
+        if !grounded.0 {
+            continue;
+        }
+        // Same ray as in `update_grounded`, with some leeway for platforms that move down
+        let height = collider.raw.compute_local_aabb().maxs.y;
+        let Some((ground, _)) = rapier_context.cast_ray(
+            transform.translation,
+            transform.down(),
+            height * 1.5,
+            true,
+            QueryFilter::new()
+                .exclude_collider(entity)
+                .exclude_sensors(),
+        ) else {
+            continue;
+        };
+        let Ok(platform) = platforms.get(ground) else {
+            continue;
+        };
+        if platform.delta != Vec3::ZERO {
+            transform.translation += platform.delta;
+        }
+    }
+}
@@ -1,8 +1,11 @@
 #[cfg(feature = "dev")]
 use crate::dev::dev_editor::DevEditorWindow;
+#[cfg(feature = "dev")]
+use crate::dev::scene_viewer::SceneViewer;
+use crate::file_system_interaction::config::GameConfig;
 use crate::level_instantiation::spawning::objects::npc;
-use crate::movement::general_movement::{GeneralMovementSystemSet, Walking};
-use crate::player_control::player_embodiment::Player;
+use crate::movement::behavior::BehaviorSystemSet;
+use crate::movement::general_movement::{CharacterControllerSystemSet, Walking};
 use crate::util::trait_extension::{F32Ext, Vec3Ext};
 use crate::GameState;
 #[cfg(feature = "dev")]
@@ -21,9 +24,12 @@ use serde::{Deserialize, Serialize};
 /// Manually tweaked
 const CELL_WIDTH: f32 = 0.4 * npc::RADIUS;
 
-/// Handles NPC pathfinding. Currently, all entities with the [`Follower`] component will follow the [`Player`].
+/// Handles NPC pathfinding. Entities with a [`NavigationIntent`] walk along the navmesh towards its destination,
+/// which is usually set by their [`Behavior`](crate::movement::behavior::Behavior).
+/// On the way, they steer away from other characters nearby so that groups of them do not end up inside each other.
 pub fn navigation_plugin(app: &mut App) {
-    app.add_plugin(OxidizedNavigationPlugin)
+    app.register_type::<NavigationIntent>()
+        .add_plugin(OxidizedNavigationPlugin)
         // consts manually tweaked
         .insert_resource(NavMeshSettings {
             cell_width: CELL_WIDTH,
@@ -40,34 +46,53 @@ pub fn navigation_plugin(app: &mut App) {
             max_contour_simplification_error: 1.3,
             max_edge_length: 100,
         })
-        .add_system(
-            query_mesh
-                .before(GeneralMovementSystemSet)
+        .add_systems(
+            (query_mesh, avoid_other_characters)
+                .chain()
+                .after(BehaviorSystemSet)
+                .before(CharacterControllerSystemSet)
                 .in_set(OnUpdate(GameState::Playing)),
         );
 }
 
-#[derive(Debug, Component, Clone, PartialEq, Default, Reflect, Serialize, Deserialize)]
+#[derive(Debug, Component, Clone, Copy, PartialEq, Default, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
-pub struct Follower;
+pub struct NavigationIntent {
+    /// `None` to stand still
+    pub destination: Option<Vec3>,
+    /// Distance in m from the destination at which the character stops
+    pub stop_distance: f32,
+}
+
+impl NavigationIntent {
+    pub fn towards(destination: Vec3, stop_distance: f32) -> Self {
+        Self {
+            destination: Some(destination),
+            stop_distance,
+        }
+    }
+}
 
 #[sysfail(log(level = "error"))]
 fn query_mesh(
-    mut with_follower: Query<(&Transform, &mut Walking), (With<Follower>, Without<Player>)>,
-    with_player: Query<&Transform, (With<Player>, Without<Follower>)>,
+    mut navigators: Query<(&Transform, &NavigationIntent, &mut Walking)>,
     nav_mesh_settings: Res<NavMeshSettings>,
     nav_mesh: Res<NavMesh>,
     #[cfg(feature = "dev")] mut lines: ResMut<DebugLines>,
     #[cfg(feature = "dev")] editor_state: Res<bevy_editor_pls::Editor>,
+    #[cfg(feature = "dev")] scene_viewer: Option<Res<SceneViewer>>,
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
     let _span = info_span!("query_mesh").entered();
+    #[cfg(feature = "dev")]
+    if scene_viewer.is_some() {
+        return Ok(());
+    }
     if let Ok(nav_mesh) = nav_mesh.get().read() {
-        for (follower_transform, mut walking) in &mut with_follower {
-            for player_transform in &with_player {
-                let from = follower_transform.translation;
-                let to = player_transform.translation;
-                if (to - from).length_squared() < 3.0f32.squared() {
+        for (navigator_transform, intent, mut walking) in &mut navigators {
+            if let Some(to) = intent.destination {
+                let from = navigator_transform.translation;
+                if (to - from).length_squared() < intent.stop_distance.squared() {
                     continue;
                 }
 
@@ -86,7 +111,7 @@ fn query_mesh(
                         .into_iter()
                         .map(|next_point| {
                             (next_point - from)
-                                .split(follower_transform.up())
+                                .split(navigator_transform.up())
                                 .horizontal
                         })
                         .filter(|dir| dir.length_squared() > 1e-3f32.squared())
@@ -101,6 +126,52 @@ fn query_mesh(
     Ok(())
 }
 
+/// Separation steering: the closer another character is, the more a navigating character is pushed away from it.
+fn avoid_other_characters(
+    config: Res<GameConfig>,
+    characters: Query<(Entity, &Transform), With<Walking>>,
+    mut navigators: Query<(Entity, &Transform, &mut Walking), With<NavigationIntent>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("avoid_other_characters").entered();
+    let config = &config.navigation;
+    if config.avoidance_radius <= 0. {
+        return;
+    }
+    let positions: Vec<_> = characters
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+    for (entity, transform, mut walking) in navigators.iter_mut() {
+        let separation: Vec3 = positions
+            .iter()
+            .filter(|(other, _)| *other != entity)
+            .filter_map(|(other, position)| {
+                let away = (transform.translation - *position)
+                    .split(transform.up())
+                    .horizontal;
+                let distance = away.length();
+                if distance >= config.avoidance_radius {
+                    return None;
+                }
+                // Characters standing exactly on top of each other still need to be split up somehow
+                let direction = away.try_normalize().unwrap_or_else(|| {
+                    let angle = (entity.index() as f32 - other.index() as f32).signum();
+                    transform.right() * angle
+                });
+                Some(direction * (1. - distance / config.avoidance_radius))
+            })
+            .sum();
+        if separation.is_approx_zero() {
+            continue;
+        }
+        let direction =
+            walking.direction.unwrap_or_default() + separation * config.avoidance_weight;
+        // The direction scales the acceleration, so it must not get longer than walking normally
+        walking.direction = Some(direction.clamp_length_max(1.));
+    }
+}
+
 #[cfg(feature = "dev")]
 fn draw_path(path: &[Vec3], lines: &mut DebugLines, color: Color) {
     for (a, b) in path.iter().zip(path.iter().skip(1)) {
@@ -0,0 +1,104 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::behavior::BehaviorSystemSet;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the name of the patrol path a [`Waypoint`] belongs to.
+pub const WAYPOINT_PATH_KEY: &str = "path";
+/// Metadata key of a [`Waypoint`]'s place in its path. Waypoints are visited in ascending order.
+pub const WAYPOINT_INDEX_KEY: &str = "index";
+
+/// Handles [`Waypoint`]s, invisible markers that form a named patrol path together with all other waypoints of the same path name.
+/// Their path and index are stored in their [`ObjectMetadata`] under [`WAYPOINT_PATH_KEY`] and [`WAYPOINT_INDEX_KEY`]
+/// and kept in sync in both directions like those of volumes, so paths are saved with the level.
+/// NPCs walk along a path with [`Behavior::PatrolPath`](crate::movement::behavior::Behavior::PatrolPath).
+pub fn patrol_path_plugin(app: &mut App) {
+    app.register_type::<Waypoint>().add_systems(
+        (read_waypoint_metadata, write_waypoint_metadata)
+            .chain()
+            .before(BehaviorSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub path: String,
+    pub index: usize,
+}
+
+impl Waypoint {
+    fn read_metadata(&mut self, metadata: &ObjectMetadata) -> Result<()> {
+        if let Some(path) = metadata.get(WAYPOINT_PATH_KEY) {
+            self.path = path.to_owned();
+        }
+        if let Some(index) = metadata.get(WAYPOINT_INDEX_KEY) {
+            self.index = index
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse waypoint index \"{index}\""))?;
+        }
+        Ok(())
+    }
+
+    fn write_metadata(&self, metadata: &mut ObjectMetadata) {
+        metadata.insert(WAYPOINT_PATH_KEY, self.path.as_str());
+        metadata.insert(WAYPOINT_INDEX_KEY, self.index.to_string());
+    }
+}
+
+/// Collects the global positions of the waypoints of each path in the order they are visited.
+pub fn collect_patrol_paths<'a>(
+    waypoints: impl IntoIterator<Item = (&'a Waypoint, &'a GlobalTransform)>,
+) -> HashMap<String, Vec<Vec3>> {
+    let mut paths: HashMap<String, Vec<(usize, Vec3)>> = HashMap::new();
+    for (waypoint, transform) in waypoints {
+        paths
+            .entry(waypoint.path.clone())
+            .or_default()
+            .push((waypoint.index, transform.translation()));
+    }
+    paths
+        .into_iter()
+        .map(|(name, mut waypoints)| {
+            waypoints.sort_by_key(|(index, _)| *index);
+            let positions = waypoints.into_iter().map(|(_, position)| position);
+            (name, positions.collect())
+        })
+        .collect()
+}
+
+#[sysfail(log(level = "error"))]
+fn read_waypoint_metadata(
+    mut waypoints: Query<(&ObjectMetadata, &mut Waypoint), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_waypoint_metadata").entered();
+    for (metadata, mut waypoint) in waypoints.iter_mut() {
+        let mut read = waypoint.clone();
+        read.read_metadata(metadata)?;
+        if read != *waypoint {
+            *waypoint = read;
+        }
+    }
+    Ok(())
+}
+
+fn write_waypoint_metadata(
+    mut waypoints: Query<(&Waypoint, &mut ObjectMetadata), Changed<Waypoint>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("write_waypoint_metadata").entered();
+    for (waypoint, mut metadata) in waypoints.iter_mut() {
+        let mut written = metadata.clone();
+        waypoint.write_metadata(&mut written);
+        if written != *metadata {
+            *metadata = written;
+        }
+    }
+}
@@ -0,0 +1,690 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::behavior::Behavior;
+use crate::movement::general_movement::{CharacterControllerSystemSet, Walking};
+use crate::movement::moving_platform::MovingPlatform;
+use crate::movement::navigation::NavigationIntent;
+use crate::util::trait_extension::Vec3Ext;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Metadata key of a spline's control points, formatted as `position | in handle | out handle` separated by `;`,
+/// e.g. `0, 0, 0 | 0, 0, -1 | 0, 0, 1; 0, 0, 4 | 0, 0, -1 | 0, 0, 1`. The handles may be left out for straight segments.
+pub const SPLINE_POINTS_KEY: &str = "points";
+/// Metadata key of whether the last control point connects back to the first one.
+pub const SPLINE_CLOSED_KEY: &str = "closed";
+/// Metadata key of the radius in m of the tube rendered along a spline, e.g. for ropes and cables.
+pub const SPLINE_THICKNESS_KEY: &str = "thickness";
+/// Metadata key by which other objects refer to a spline. Its `id` metadata, or its [`Name`] if it has none.
+pub const SPLINE_PATH_KEY: &str = "path";
+
+/// Number of straight pieces each curved segment of a spline is approximated with.
+const SAMPLES_PER_SEGMENT: usize = 16;
+/// Number of vertices around the tube of a spline with a thickness.
+const TUBE_SEGMENTS: usize = 8;
+/// How close in m a walking [`SplineFollower`] has to get to its target before moving it further along the spline.
+const ARRIVAL_DISTANCE: f32 = 1.;
+
+/// Handles [`Spline`]s, which are chains of cubic Bézier curves through control points in the spline's local space.
+/// Their data is stored in their [`ObjectMetadata`] under [`SPLINE_POINTS_KEY`], [`SPLINE_CLOSED_KEY`] and [`SPLINE_THICKNESS_KEY`],
+/// and kept in sync in both directions, so editing either one updates the other and saving the level persists it.
+/// Splines with a thickness are rendered as tubes, e.g. for ropes and cables.
+///
+/// Other objects refer to a spline by putting its `id` under [`SPLINE_PATH_KEY`] in their metadata:
+/// - [`MovingPlatform`]s move along the spline instead of their waypoints.
+/// - Any other object becomes a [`SplineFollower`]. Characters walk along the spline, e.g. as a patrol route,
+/// while everything else, e.g. a camera on a rail, is moved along it at the `speed` from its metadata.
+pub fn spline_plugin(app: &mut App) {
+    app.register_type::<Spline>()
+        .register_type::<SplinePoint>()
+        .register_type::<SplineFollower>()
+        .register_type::<SplineFollowMode>()
+        .add_systems(
+            (
+                read_spline_metadata,
+                write_spline_metadata,
+                build_spline_tubes,
+                read_follower_metadata,
+                apply_platform_paths,
+                follow_splines,
+            )
+                .chain()
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Spline {
+    pub points: Vec<SplinePoint>,
+    /// Whether the last point connects back to the first one
+    pub closed: bool,
+    /// Radius in m of the rendered tube. Not rendered if 0.
+    pub thickness: f32,
+}
+
+impl Default for Spline {
+    fn default() -> Self {
+        Self {
+            points: vec![
+                SplinePoint::smooth(Vec3::ZERO, Vec3::Z),
+                SplinePoint::smooth(Vec3::Z * 4., Vec3::Z),
+            ],
+            closed: false,
+            thickness: 0.,
+        }
+    }
+}
+
+/// A control point of a [`Spline`]. The handles are offsets from the position that the curve bends towards.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct SplinePoint {
+    pub position: Vec3,
+    /// Shapes the curve arriving at this point
+    pub in_handle: Vec3,
+    /// Shapes the curve leaving this point
+    pub out_handle: Vec3,
+}
+
+impl SplinePoint {
+    /// A point the curve passes through without a kink, as both handles lie on the same line.
+    pub fn smooth(position: Vec3, out_handle: Vec3) -> Self {
+        Self {
+            position,
+            in_handle: -out_handle,
+            out_handle,
+        }
+    }
+}
+
+impl Spline {
+    pub fn segment_count(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            count if self.closed => count,
+            count => count - 1,
+        }
+    }
+
+    /// The control points of the Bézier curve between the point at `index` and the next one.
+    fn segment(&self, index: usize) -> [Vec3; 4] {
+        let start = self.points[index];
+        let end = self.points[(index + 1) % self.points.len()];
+        [
+            start.position,
+            start.position + start.out_handle,
+            end.position + end.in_handle,
+            end.position,
+        ]
+    }
+
+    /// The point in local space at `t`, which runs from 0 at the first control point to [`Spline::segment_count`] at the end.
+    pub fn sample(&self, t: f32) -> Option<Vec3> {
+        let count = self.segment_count();
+        if count == 0 {
+            return self.points.first().map(|point| point.position);
+        }
+        let t = t.clamp(0., count as f32);
+        let index = (t.floor() as usize).min(count - 1);
+        let [p0, p1, p2, p3] = self.segment(index);
+        let t = t - index as f32;
+        let u = 1. - t;
+        Some(p0 * u * u * u + p1 * 3. * u * u * t + p2 * 3. * u * t * t + p3 * t * t * t)
+    }
+
+    /// Approximates the curve with straight lines in local space.
+    pub fn polyline(&self) -> Vec<Vec3> {
+        let count = self.segment_count();
+        if count == 0 {
+            return self.points.iter().map(|point| point.position).collect();
+        }
+        (0..=count * SAMPLES_PER_SEGMENT)
+            .filter_map(|index| self.sample(index as f32 / SAMPLES_PER_SEGMENT as f32))
+            .collect()
+    }
+
+    /// Approximates the curve with straight lines in the space `transform` leads into, usually world space.
+    pub fn path(&self, transform: &Transform) -> SplinePath {
+        SplinePath::new(
+            self.polyline()
+                .into_iter()
+                .map(|point| transform.transform_point(point))
+                .collect(),
+        )
+    }
+
+    fn read_metadata(&mut self, metadata: &ObjectMetadata) -> Result<()> {
+        if let Some(points) = metadata.get(SPLINE_POINTS_KEY) {
+            self.points = parse_points(points)
+                .with_context(|| format!("Failed to parse spline points \"{points}\""))?;
+        }
+        if let Some(closed) = metadata.get(SPLINE_CLOSED_KEY) {
+            self.closed = closed
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse spline closedness \"{closed}\""))?;
+        }
+        if let Some(thickness) = metadata.get(SPLINE_THICKNESS_KEY) {
+            self.thickness = thickness
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse spline thickness \"{thickness}\""))?;
+        }
+        Ok(())
+    }
+
+    fn write_metadata(&self, metadata: &mut ObjectMetadata) {
+        let format = |vector: Vec3| format!("{}, {}, {}", vector.x, vector.y, vector.z);
+        let points = self
+            .points
+            .iter()
+            .map(|point| {
+                format!(
+                    "{} | {} | {}",
+                    format(point.position),
+                    format(point.in_handle),
+                    format(point.out_handle)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        metadata.insert(SPLINE_POINTS_KEY, points);
+        metadata.insert(SPLINE_CLOSED_KEY, self.closed.to_string());
+        metadata.insert(SPLINE_THICKNESS_KEY, self.thickness.to_string());
+    }
+}
+
+/// A [`Spline`] approximated by straight lines, measured by the distance travelled along it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SplinePath {
+    points: Vec<Vec3>,
+    /// Distance along the path to each point
+    distances: Vec<f32>,
+}
+
+impl SplinePath {
+    fn new(points: Vec<Vec3>) -> Self {
+        let mut total = 0.;
+        let distances = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| {
+                if index > 0 {
+                    total += point.distance(points[index - 1]);
+                }
+                total
+            })
+            .collect();
+        Self { points, distances }
+    }
+
+    pub fn points(&self) -> &[Vec3] {
+        &self.points
+    }
+
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or_default()
+    }
+
+    /// The point at `distance` along the path, clamped to its ends.
+    pub fn point_at(&self, distance: f32) -> Option<Vec3> {
+        let (start, end, ratio) = self.piece_at(distance)?;
+        Some(start.lerp(end, ratio))
+    }
+
+    /// The normalized direction of the path at `distance`.
+    pub fn direction_at(&self, distance: f32) -> Option<Vec3> {
+        let (start, end, _) = self.piece_at(distance)?;
+        (end - start).try_normalize()
+    }
+
+    fn piece_at(&self, distance: f32) -> Option<(Vec3, Vec3, f32)> {
+        match self.points[..] {
+            [] => None,
+            [point] => Some((point, point, 0.)),
+            _ => {
+                let index = self
+                    .distances
+                    .partition_point(|&point_distance| point_distance < distance)
+                    .clamp(1, self.points.len() - 1);
+                let (start_distance, end_distance) =
+                    (self.distances[index - 1], self.distances[index]);
+                let piece_length = end_distance - start_distance;
+                let ratio = if piece_length > 0. {
+                    ((distance - start_distance) / piece_length).clamp(0., 1.)
+                } else {
+                    0.
+                };
+                Some((self.points[index - 1], self.points[index], ratio))
+            }
+        }
+    }
+}
+
+/// Moves an entity along the [`Spline`] referenced by [`SplineFollower::path`].
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct SplineFollower {
+    /// `id` metadata or [`Name`] of the spline
+    pub path: String,
+    /// Speed in m/s. Characters walk at their own pace instead.
+    pub speed: f32,
+    pub mode: SplineFollowMode,
+    /// Distance in m travelled along the spline
+    pub distance: f32,
+    /// Whether the follower currently moves from the end of the spline towards its start
+    pub reversed: bool,
+}
+
+impl Default for SplineFollower {
+    fn default() -> Self {
+        Self {
+            path: default(),
+            speed: 2.,
+            mode: default(),
+            distance: 0.,
+            reversed: false,
+        }
+    }
+}
+
+impl SplineFollower {
+    fn advance(&mut self, step: f32, length: f32) {
+        let distance = if self.reversed {
+            self.distance - step
+        } else {
+            self.distance + step
+        };
+        self.distance = match self.mode {
+            SplineFollowMode::Loop => distance.rem_euclid(length),
+            SplineFollowMode::PingPong if distance > length => {
+                self.reversed = true;
+                (2. * length - distance).max(0.)
+            }
+            SplineFollowMode::PingPong if distance < 0. => {
+                self.reversed = false;
+                (-distance).min(length)
+            }
+            SplineFollowMode::PingPong => distance,
+            SplineFollowMode::Once => distance.clamp(0., length),
+        };
+    }
+}
+
+/// What a [`SplineFollower`] does at the end of its spline. Set with the `path_mode` metadata as `loop`, `ping_pong` or `once`.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum SplineFollowMode {
+    /// Starts over at the beginning, which is seamless for closed splines
+    #[default]
+    Loop,
+    /// Turns around
+    PingPong,
+    /// Stops
+    Once,
+}
+
+/// The tube rendered along a [`Spline`] with a thickness.
+#[derive(Debug, Clone, Eq, PartialEq, Component)]
+pub struct SplineTube {
+    pub entity: Entity,
+    /// Replaced in place when the spline changes
+    pub mesh: Handle<Mesh>,
+}
+
+fn parse_points(value: &str) -> Result<Vec<SplinePoint>> {
+    value
+        .split(';')
+        .filter(|point| !point.trim().is_empty())
+        .map(|point| {
+            let vectors = point
+                .split('|')
+                .map(parse_vec3)
+                .collect::<Result<Vec<_>>>()?;
+            match vectors[..] {
+                [position] => Ok(SplinePoint {
+                    position,
+                    ..default()
+                }),
+                [position, in_handle, out_handle] => Ok(SplinePoint {
+                    position,
+                    in_handle,
+                    out_handle,
+                }),
+                _ => {
+                    bail!("Point \"{point}\" needs either a position or a position and two handles")
+                }
+            }
+        })
+        .collect()
+}
+
+fn parse_vec3(value: &str) -> Result<Vec3> {
+    let coordinates = value
+        .split(',')
+        .map(|coordinate| {
+            coordinate
+                .trim()
+                .parse::<f32>()
+                .with_context(|| format!("Failed to parse coordinate \"{coordinate}\""))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    match coordinates[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => bail!("\"{value}\" does not have three coordinates"),
+    }
+}
+
+/// Finds the spline that objects refer to with `path`, see [`SPLINE_PATH_KEY`].
+fn find_spline<'a>(
+    splines: impl IntoIterator<
+        Item = (
+            &'a Spline,
+            &'a Transform,
+            Option<&'a ObjectMetadata>,
+            Option<&'a Name>,
+        ),
+    >,
+    path: &str,
+) -> Option<(&'a Spline, &'a Transform)> {
+    splines
+        .into_iter()
+        .find(
+            |(_, _, metadata, name)| match metadata.and_then(|metadata| metadata.get("id")) {
+                Some(id) => id == path,
+                None => name.map_or(false, |name| name.as_str() == path),
+            },
+        )
+        .map(|(spline, transform, _, _)| (spline, transform))
+}
+
+#[sysfail(log(level = "error"))]
+fn read_spline_metadata(
+    mut splines: Query<(&ObjectMetadata, &mut Spline), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_spline_metadata").entered();
+    for (metadata, mut spline) in splines.iter_mut() {
+        let mut read = spline.clone();
+        read.read_metadata(metadata)?;
+        // Only touch the spline on actual changes, as that writes it back to the metadata
+        if read != *spline {
+            *spline = read;
+        }
+    }
+    Ok(())
+}
+
+fn write_spline_metadata(mut splines: Query<(&Spline, &mut ObjectMetadata), Changed<Spline>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("write_spline_metadata").entered();
+    for (spline, mut metadata) in splines.iter_mut() {
+        let mut written = metadata.clone();
+        spline.write_metadata(&mut written);
+        if written != *metadata {
+            *metadata = written;
+        }
+    }
+}
+
+fn get_or_add_tube_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x2b7c94e1d03f5a68);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || Color::rgb(0.6, 0.5, 0.3).into());
+    handle
+}
+
+fn build_spline_tubes(
+    mut commands: Commands,
+    splines: Query<(Entity, &Spline, Option<&SplineTube>), Changed<Spline>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("build_spline_tubes").entered();
+    for (entity, spline, old_tube) in splines.iter() {
+        let polyline = spline.polyline();
+        if spline.thickness <= 0. || polyline.len() < 2 {
+            if let Some(tube) = old_tube.and_then(|tube| commands.get_entity(tube.entity)) {
+                tube.despawn_recursive();
+            }
+            commands.entity(entity).remove::<SplineTube>();
+            continue;
+        }
+        let mesh = tube_mesh(&polyline, spline.thickness);
+        // Editing a spline, e.g. while dragging one of its points, would otherwise add a mesh every frame
+        if let Some(old_tube) = old_tube {
+            meshes.set_untracked(&old_tube.mesh, mesh);
+            continue;
+        }
+        let mesh = meshes.add(mesh);
+        let tube = commands
+            .spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: get_or_add_tube_material_handle(&mut materials),
+                    ..default()
+                },
+                Name::new("Spline Tube"),
+            ))
+            .id();
+        commands
+            .entity(entity)
+            .add_child(tube)
+            .insert(SplineTube { entity: tube, mesh });
+    }
+}
+
+/// Sweeps a circle along the points. Its orientation is carried from point to point so that the tube does not twist.
+fn tube_mesh(points: &[Vec3], radius: f32) -> Mesh {
+    let ring_size = TUBE_SEGMENTS + 1;
+    let mut positions = Vec::with_capacity(points.len() * ring_size);
+    let mut normals = Vec::with_capacity(points.len() * ring_size);
+    let mut uvs = Vec::with_capacity(points.len() * ring_size);
+    let mut side = None;
+    let mut length = 0.;
+    for (index, point) in points.iter().enumerate() {
+        let previous = points[index.saturating_sub(1)];
+        let next = points[(index + 1).min(points.len() - 1)];
+        let tangent = (next - previous).try_normalize().unwrap_or(Vec3::Y);
+        let carried: Vec3 = side.unwrap_or_else(|| tangent.any_orthonormal_vector());
+        let current = (carried - tangent * carried.dot(tangent))
+            .try_normalize()
+            .unwrap_or_else(|| tangent.any_orthonormal_vector());
+        side = Some(current);
+        length += point.distance(previous);
+        for segment in 0..ring_size {
+            let angle = TAU * segment as f32 / TUBE_SEGMENTS as f32;
+            let normal = Quat::from_axis_angle(tangent, angle) * current;
+            positions.push((*point + normal * radius).to_array());
+            normals.push(normal.to_array());
+            uvs.push([segment as f32 / TUBE_SEGMENTS as f32, length]);
+        }
+    }
+    let mut indices = Vec::with_capacity((points.len() - 1) * TUBE_SEGMENTS * 6);
+    for index in 0..points.len() - 1 {
+        for segment in 0..TUBE_SEGMENTS {
+            let current = (index * ring_size + segment) as u32;
+            let next_segment = current + 1;
+            let next_ring = current + ring_size as u32;
+            let next_both = next_ring + 1;
+            indices.extend([
+                current,
+                next_ring,
+                next_segment,
+                next_segment,
+                next_ring,
+                next_both,
+            ]);
+        }
+    }
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[sysfail(log(level = "error"))]
+fn read_follower_metadata(
+    mut commands: Commands,
+    objects: Query<
+        (Entity, &ObjectMetadata, Option<&SplineFollower>),
+        (
+            Changed<ObjectMetadata>,
+            Without<Spline>,
+            Without<MovingPlatform>,
+        ),
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_follower_metadata").entered();
+    for (entity, metadata, follower) in objects.iter() {
+        let Some(path) = metadata.get(SPLINE_PATH_KEY) else {
+            if follower.is_some() {
+                commands.entity(entity).remove::<SplineFollower>();
+            }
+            continue;
+        };
+        let mut follower = follower.cloned().unwrap_or_default();
+        follower.path = path.trim().to_owned();
+        if let Some(speed) = metadata.get("speed") {
+            follower.speed = speed
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse spline follower speed \"{speed}\""))?;
+        }
+        if let Some(mode) = metadata.get("path_mode") {
+            follower.mode = match mode.trim() {
+                "loop" => SplineFollowMode::Loop,
+                "ping_pong" => SplineFollowMode::PingPong,
+                "once" => SplineFollowMode::Once,
+                _ => {
+                    bail!("Unknown spline follow mode \"{mode}\", expected loop, ping_pong or once")
+                }
+            };
+        }
+        // Navigating on behalf of a behavior would fight with following the spline
+        commands
+            .entity(entity)
+            .remove::<(Behavior, NavigationIntent)>()
+            .insert(follower);
+    }
+    Ok(())
+}
+
+fn apply_platform_paths(
+    mut platforms: Query<(Ref<ObjectMetadata>, &Transform, &mut MovingPlatform)>,
+    splines: Query<(
+        Ref<Spline>,
+        Ref<Transform>,
+        Option<&ObjectMetadata>,
+        Option<&Name>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_platform_paths").entered();
+    let splines_changed = splines
+        .iter()
+        .any(|(spline, transform, _, _)| spline.is_changed() || transform.is_changed());
+    for (metadata, transform, mut platform) in platforms.iter_mut() {
+        if !splines_changed && !metadata.is_changed() {
+            continue;
+        }
+        let Some(path) = metadata.get(SPLINE_PATH_KEY) else {
+            continue;
+        };
+        let Some((spline, spline_transform)) = find_spline(
+            splines.iter().map(|(spline, transform, metadata, name)| {
+                (spline.into_inner(), transform.into_inner(), metadata, name)
+            }),
+            path.trim(),
+        ) else {
+            continue;
+        };
+        let origin = platform.origin.unwrap_or(transform.translation);
+        let waypoints: Vec<_> = spline
+            .path(spline_transform)
+            .points()
+            .iter()
+            .map(|point| *point - origin)
+            .collect();
+        if waypoints.is_empty() || waypoints == platform.waypoints {
+            continue;
+        }
+        platform.next_waypoint %= waypoints.len();
+        platform.waypoints = waypoints;
+    }
+}
+
+fn follow_splines(
+    time: Res<Time>,
+    mut followers: Query<
+        (&mut SplineFollower, &mut Transform, Option<&mut Walking>),
+        Without<Spline>,
+    >,
+    splines: Query<(&Spline, &Transform, Option<&ObjectMetadata>, Option<&Name>)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("follow_splines").entered();
+    let dt = time.delta_seconds();
+    for (mut follower, mut transform, walking) in followers.iter_mut() {
+        let Some((spline, spline_transform)) = find_spline(&splines, &follower.path) else {
+            continue;
+        };
+        let path = spline.path(spline_transform);
+        let length = path.length();
+        if length <= 0. {
+            continue;
+        }
+        match walking {
+            Some(mut walking) => {
+                let Some(target) = path.point_at(follower.distance) else {
+                    continue;
+                };
+                let mut offset = (target - transform.translation)
+                    .split(transform.up())
+                    .horizontal;
+                if offset.length() < ARRIVAL_DISTANCE {
+                    let before = follower.distance;
+                    follower.advance(ARRIVAL_DISTANCE, length);
+                    if follower.distance == before {
+                        // Reached the end of a spline that is only followed once
+                        offset = Vec3::ZERO;
+                    }
+                }
+                walking.direction = (!offset.is_approx_zero()).then(|| offset.normalize());
+            }
+            None => {
+                follower.advance(follower.speed * dt, length);
+                let Some(position) = path.point_at(follower.distance) else {
+                    continue;
+                };
+                transform.translation = position;
+                let direction = path.direction_at(follower.distance).map(|direction| {
+                    if follower.reversed {
+                        -direction
+                    } else {
+                        direction
+                    }
+                });
+                if let Some(direction) = direction {
+                    transform.look_at(position + direction, Vec3::Y);
+                }
+            }
+        }
+    }
+}
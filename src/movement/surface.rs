@@ -0,0 +1,303 @@
+use crate::file_system_interaction::asset_loading::SurfaceAssets;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::general_movement::{
+    apply_walking, prevent_tunneling, update_grounded, CharacterControllerSystemSet, Grounded,
+};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Metadata key of the name of the surface an object's colliders are made of, e.g. `ice`.
+pub const SURFACE_KEY: &str = "surface";
+
+/// Gives colliders the friction and bounciness of a [`SurfaceDefinition`] from `assets/surfaces/<name>.surface.ron`.
+/// Objects get their [`Surface`] from the metadata key [`SURFACE_KEY`], glTF nodes from the name marker `[surface: <name>]`.
+/// A surface applies to all colliders below it in the hierarchy, so dynamic props bounce off a rubber floor
+/// while those that are made of rubber themselves bounce everywhere.
+/// Characters remember the surface they stand on in their [`GroundSurface`], which scales how well they can walk and brake
+/// and lets them slide down steep slopes.
+pub fn surface_plugin(app: &mut App) {
+    app.register_type::<Surface>()
+        .register_type::<GroundSurface>()
+        .register_type::<SurfaceDefinition>()
+        .add_systems(
+            (
+                read_surface_metadata,
+                read_surface_markers,
+                apply_surface_physics,
+            )
+                .chain()
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                update_ground_surfaces
+                    .after(update_grounded)
+                    .before(apply_walking),
+                apply_sliding.after(apply_walking).before(prevent_tunneling),
+            )
+                .in_set(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, TypeUuid)]
+#[uuid = "8d3f2a61-4c7e-4b0a-9e15-6a2d9c4b7f38"]
+#[reflect(Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurfaceDefinition {
+    /// Friction coefficient of the colliders, where `0` is frictionless
+    pub friction: f32,
+    /// Restitution of the colliders, where `0` does not bounce at all and `1` bounces back at full speed
+    pub bounce: f32,
+    /// Fraction of their usual acceleration and braking that characters have on the surface
+    pub grip: f32,
+    /// Name of the set of footstep sounds played while walking on the surface, see [`FootstepSet`](crate::file_system_interaction::audio::FootstepSet)
+    pub footsteps: Option<String>,
+    pub slide: Option<SurfaceSlide>,
+}
+
+/// What colliders without a [`Surface`] are made of, also used for fields missing in a definition.
+impl Default for SurfaceDefinition {
+    fn default() -> Self {
+        Self {
+            friction: 0.5,
+            bounce: 0.,
+            grip: 1.,
+            footsteps: None,
+            slide: None,
+        }
+    }
+}
+
+/// Lets characters slide down the surface where it is steeper than `min_angle`.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct SurfaceSlide {
+    /// Slope in degrees from which on characters slide
+    pub min_angle: f32,
+    /// Acceleration in m/s² down the slope
+    pub acceleration: f32,
+}
+
+/// Name of the [`SurfaceDefinition`] of this entity's colliders and those of its descendants.
+#[derive(Debug, Clone, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Surface(pub String);
+
+/// The surface a character is standing on, or was last standing on while in the air.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct GroundSurface {
+    pub name: Option<String>,
+    pub definition: SurfaceDefinition,
+    /// Normal of the ground below the character
+    pub normal: Vec3,
+}
+
+impl Default for GroundSurface {
+    fn default() -> Self {
+        Self {
+            name: None,
+            definition: default(),
+            normal: Vec3::Y,
+        }
+    }
+}
+
+static SURFACE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[surface:\s*([\w-]+)\]").expect("Failed to compile surface regex")
+});
+
+pub fn get_surface_definition<'a>(
+    definitions: &'a Assets<SurfaceDefinition>,
+    surface_assets: &SurfaceAssets,
+    name: &str,
+) -> Option<&'a SurfaceDefinition> {
+    let path = format!("surfaces/{name}.surface.ron");
+    definitions.get(surface_assets.surfaces.get(&path)?)
+}
+
+/// Returns the [`Surface`] of the entity or of its closest ancestor that has one.
+fn find_surface<'a>(
+    entity: Entity,
+    surfaces: &'a Query<&Surface>,
+    parents: &Query<&Parent>,
+) -> Option<&'a Surface> {
+    let mut current = entity;
+    loop {
+        if let Ok(surface) = surfaces.get(current) {
+            return Some(surface);
+        }
+        current = parents.get(current).ok()?.get();
+    }
+}
+
+fn read_surface_metadata(
+    mut commands: Commands,
+    objects: Query<(Entity, &ObjectMetadata, Option<&Surface>), Changed<ObjectMetadata>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_surface_metadata").entered();
+    for (entity, metadata, surface) in objects.iter() {
+        match metadata.get(SURFACE_KEY) {
+            Some(name) if surface.map(|surface| surface.0.as_str()) != Some(name) => {
+                commands.entity(entity).insert(Surface(name.to_owned()));
+            }
+            None if surface.is_some() => {
+                commands.entity(entity).remove::<Surface>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_surface_markers(mut commands: Commands, added_name: Query<(Entity, &Name), Added<Name>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_surface_markers").entered();
+    for (entity, name) in added_name.iter() {
+        let name = name.to_lowercase();
+        if let Some(captures) = SURFACE_REGEX.captures(&name) {
+            commands
+                .entity(entity)
+                .insert(Surface(captures[1].to_owned()));
+        }
+    }
+}
+
+/// Colliders are updated when they are created, e.g. by [`read_colliders`](crate::movement::physics::read_colliders)
+/// after the surface marker was read, when a surface changes and when a definition is reloaded.
+fn apply_surface_physics(
+    mut commands: Commands,
+    mut definition_events: EventReader<AssetEvent<SurfaceDefinition>>,
+    definitions: Res<Assets<SurfaceDefinition>>,
+    surface_assets: Res<SurfaceAssets>,
+    changed_surfaces: Query<Entity, Changed<Surface>>,
+    mut removed_surfaces: RemovedComponents<Surface>,
+    added_colliders: Query<Entity, Added<Collider>>,
+    colliders: Query<Entity, With<Collider>>,
+    children: Query<&Children>,
+    surfaces: Query<&Surface>,
+    parents: Query<&Parent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_surface_physics").entered();
+    let reloaded = definition_events
+        .iter()
+        .any(|event| matches!(event, AssetEvent::Modified { .. }));
+    let mut outdated: Vec<Entity> = if reloaded {
+        colliders.iter().collect()
+    } else {
+        added_colliders.iter().collect()
+    };
+    for entity in changed_surfaces.iter().chain(removed_surfaces.iter()) {
+        outdated.extend(
+            std::iter::once(entity)
+                .chain(children.iter_descendants(entity))
+                .filter(|entity| colliders.contains(*entity)),
+        );
+    }
+
+    for entity in outdated {
+        let Some(mut entity_commands) = commands.get_entity(entity) else {
+            continue;
+        };
+        let Some(Surface(name)) = find_surface(entity, &surfaces, &parents) else {
+            // Rapier's defaults match those of the default surface
+            entity_commands.remove::<(Friction, Restitution)>();
+            continue;
+        };
+        // A typo in one surface name should not keep the other colliders from being updated
+        let Some(definition) = get_surface_definition(&definitions, &surface_assets, name) else {
+            error!("Failed to find surface \"{name}\"");
+            continue;
+        };
+        entity_commands.insert((
+            Friction::coefficient(definition.friction),
+            // Taking the larger restitution lets a bouncy surface bounce everything that lands on it
+            Restitution {
+                coefficient: definition.bounce,
+                combine_rule: CoefficientCombineRule::Max,
+            },
+        ));
+    }
+}
+
+pub(crate) fn update_ground_surfaces(
+    mut characters: Query<(Entity, &Transform, &Collider, &Grounded, &mut GroundSurface)>,
+    rapier_context: Res<RapierContext>,
+    definitions: Res<Assets<SurfaceDefinition>>,
+    surface_assets: Res<SurfaceAssets>,
+    surfaces: Query<&Surface>,
+    parents: Query<&Parent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_ground_surfaces").entered();
+    for (entity, transform, collider, grounded, mut ground_surface) in characters.iter_mut() {
+        if !grounded.0 {
+            continue;
+        }
+        // Reaches a bit further than `update_grounded` so that the ground is still found on slopes
+        let distance = collider.raw.compute_local_aabb().maxs.y * 2.;
+        let Some((ground, intersection)) = rapier_context.cast_ray_and_get_normal(
+            transform.translation,
+            transform.down(),
+            distance,
+            true,
+            QueryFilter::new()
+                .exclude_collider(entity)
+                .exclude_sensors(),
+        ) else {
+            continue;
+        };
+        let name = find_surface(ground, &surfaces, &parents).map(|surface| surface.0.clone());
+        let definition = name
+            .as_deref()
+            .and_then(|name| get_surface_definition(&definitions, &surface_assets, name))
+            .cloned()
+            .unwrap_or_default();
+        let updated = GroundSurface {
+            name,
+            definition,
+            normal: intersection.normal,
+        };
+        if updated != *ground_surface {
+            *ground_surface = updated;
+        }
+    }
+}
+
+fn apply_sliding(
+    mut characters: Query<(
+        &mut ExternalForce,
+        &Grounded,
+        &GroundSurface,
+        &ReadMassProperties,
+        &Transform,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_sliding").entered();
+    for (mut force, grounded, ground_surface, mass, transform) in characters.iter_mut() {
+        let Some(slide) = ground_surface.definition.slide else {
+            continue;
+        };
+        if !grounded.0 {
+            continue;
+        }
+        let up = transform.up();
+        let slope = ground_surface.normal.angle_between(up).to_degrees();
+        if slope < slide.min_angle {
+            continue;
+        }
+        let Some(downhill) = (-up).reject_from(ground_surface.normal).try_normalize() else {
+            continue;
+        };
+        force.force += downhill * slide.acceleration * mass.0.mass;
+    }
+}
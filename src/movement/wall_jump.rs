@@ -0,0 +1,185 @@
+use crate::movement::general_movement::{
+    apply_jumping, update_grounded, CharacterControllerSystemSet, CharacterUp, Grounded, Jumping,
+    Walking,
+};
+use crate::movement::gravity::{Gravity, LocalGravity};
+use crate::util::trait_extension::Vec3Ext;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Lets characters with [`WallJumping`] slide down walls they touch while falling and jump off them.
+/// Their [`MovementState`] tells whether they are on the ground, in the air or sliding down a wall.
+/// While sliding, only a fraction of gravity pulls the character down and the fall speed is capped.
+/// A jump requested while sliding pushes the character away from the wall, bent towards the direction they are walking in.
+pub fn wall_jump_plugin(app: &mut App) {
+    app.register_type::<WallJumping>()
+        .register_type::<MovementState>()
+        .add_systems(
+            (update_movement_state, apply_wall_slide, apply_wall_jump)
+                .chain()
+                .after(update_grounded)
+                .before(apply_jumping)
+                .in_set(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct WallJumping {
+    /// Fraction of gravity that still acts on the character while sliding
+    pub gravity_factor: f32,
+    /// Maximum speed in m/s at which the character slides down
+    pub max_slide_speed: f32,
+    /// Speed in m/s away from the wall
+    pub push_speed: f32,
+    /// Speed in m/s upwards
+    pub jump_speed: f32,
+}
+
+impl Default for WallJumping {
+    fn default() -> Self {
+        Self {
+            gravity_factor: 0.3,
+            max_slide_speed: 2.0,
+            push_speed: 4.0,
+            jump_speed: 3.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub enum MovementState {
+    #[default]
+    Grounded,
+    Airborne,
+    WallSliding {
+        /// Points out of the wall
+        normal: Vec3,
+    },
+}
+
+/// Contacts whose normals are more aligned with the character's up direction than this are floors or ceilings.
+const MAX_WALL_ALIGNMENT: f32 = 0.3;
+
+fn update_movement_state(
+    rapier_context: Res<RapierContext>,
+    mut characters: Query<
+        (
+            Entity,
+            &Grounded,
+            &CharacterUp,
+            &Velocity,
+            &mut MovementState,
+        ),
+        With<WallJumping>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_movement_state").entered();
+    for (entity, grounded, up, velocity, mut state) in characters.iter_mut() {
+        let new_state = if grounded.0 {
+            MovementState::Grounded
+        } else if velocity.linvel.dot(up.0) > 0. {
+            MovementState::Airborne
+        } else {
+            rapier_context
+                .contacts_with(entity)
+                .filter(|contact_pair| contact_pair.has_any_active_contacts())
+                .filter_map(|contact_pair| {
+                    let (manifold, _) = contact_pair.find_deepest_contact()?;
+                    // The manifold's normal points from the first collider to the second one
+                    let normal = if contact_pair.collider1() == entity {
+                        -manifold.normal()
+                    } else {
+                        manifold.normal()
+                    };
+                    (normal.dot(up.0).abs() < MAX_WALL_ALIGNMENT).then_some(normal)
+                })
+                .next()
+                .map_or(MovementState::Airborne, |normal| {
+                    MovementState::WallSliding { normal }
+                })
+        };
+        if *state != new_state {
+            *state = new_state;
+        }
+    }
+}
+
+fn apply_wall_slide(
+    gravity: Res<Gravity>,
+    mut characters: Query<(
+        &WallJumping,
+        &MovementState,
+        &CharacterUp,
+        &ReadMassProperties,
+        Option<&LocalGravity>,
+        &mut ExternalForce,
+        &mut Velocity,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_wall_slide").entered();
+    for (wall_jumping, state, up, mass, local_gravity, mut force, mut velocity) in
+        characters.iter_mut()
+    {
+        if !matches!(state, MovementState::WallSliding { .. }) {
+            continue;
+        }
+        let strength = local_gravity
+            .map(|gravity| gravity.0)
+            .unwrap_or(gravity.0)
+            .length();
+        force.force += up.0 * strength * mass.0.mass * (1. - wall_jumping.gravity_factor);
+
+        let fall_speed = -velocity.linvel.dot(up.0);
+        if fall_speed > wall_jumping.max_slide_speed {
+            velocity.linvel += up.0 * (fall_speed - wall_jumping.max_slide_speed);
+        }
+    }
+}
+
+fn apply_wall_jump(
+    mut characters: Query<(
+        &WallJumping,
+        &mut MovementState,
+        &CharacterUp,
+        &Walking,
+        &ReadMassProperties,
+        &mut Jumping,
+        &mut ExternalImpulse,
+        &mut Velocity,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_wall_jump").entered();
+    for (wall_jumping, mut state, up, walking, mass, mut jump, mut impulse, mut velocity) in
+        characters.iter_mut()
+    {
+        let MovementState::WallSliding { normal } = *state else {
+            continue;
+        };
+        if !jump.requested {
+            continue;
+        }
+        // The jump is handled here, so it must not be buffered for landing
+        jump.requested = false;
+        jump.time_since_request = None;
+        jump.rising = true;
+
+        let away = normal.split(up.0).horizontal.normalize_or_zero();
+        let direction = walking
+            .direction
+            .map(|direction| direction.split(up.0).horizontal.normalize_or_zero())
+            .filter(|direction| direction.dot(away) > 0.)
+            .map_or(away, |direction| (away + direction).normalize_or_zero());
+        velocity.linvel = Vec3::ZERO;
+        impulse.impulse +=
+            (direction * wall_jumping.push_speed + up.0 * wall_jumping.jump_speed) * mass.0.mass;
+        *state = MovementState::Airborne;
+    }
+}
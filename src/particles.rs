@@ -1,5 +1,4 @@
 use crate::file_system_interaction::config::GameConfig;
-use crate::level_instantiation::spawning::objects::player;
 use crate::movement::general_movement::Grounded;
 use crate::particles::init::init_effects;
 use crate::util::trait_extension::{F32Ext, Vec3Ext};
@@ -36,8 +35,9 @@ fn play_sprinting_effect(
         for (mut particle_transform, mut effect) in with_particle.iter_mut() {
             let threshold = config.player.sprint_effect_speed_threshold;
             if grounded.0 && horizontal_speed_squared > threshold.squared() {
+                let body = &config.player.body;
                 let translation = player_transform.translation
-                    - player_transform.up() * (player::HEIGHT / 2. + player::RADIUS);
+                    - player_transform.up() * (body.height / 2. + body.radius);
                 *particle_transform = player_transform.with_translation(translation);
                 effect.maybe_spawner().unwrap().set_active(true);
             } else {
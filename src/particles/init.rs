@@ -1,9 +1,11 @@
-use crate::level_instantiation::spawning::objects::player;
 use crate::particles::SprintingParticle;
 use bevy::pbr::NotShadowReceiver;
 use bevy::prelude::*;
 use bevy_hanabi::prelude::*;
 
+/// Radius of the area around the player's feet in which sprinting particles appear.
+const SPRINTING_EMITTER_RADIUS: f32 = 0.15;
+
 pub fn init_effects(mut commands: Commands, mut effects: ResMut<Assets<EffectAsset>>) {
     let sprinting = create_sprinting_effect(&mut effects);
     commands.spawn((
@@ -40,7 +42,7 @@ fn create_sprinting_effect(effects: &mut Assets<EffectAsset>) -> ParticleEffect
             }
             .init(InitPositionCircleModifier {
                 dimension: ShapeDimension::Volume,
-                radius: player::RADIUS * 0.5,
+                radius: SPRINTING_EMITTER_RADIUS,
                 center: Vec3::ZERO,
                 axis: Vec3::Y,
             })
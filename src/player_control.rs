@@ -1,20 +1,36 @@
 pub mod actions;
 pub mod camera;
+pub mod emote_wheel;
+pub mod input_bindings;
+pub mod noclip;
 pub mod player_embodiment;
+pub mod warp;
 
 pub use crate::player_control::actions::actions_plugin;
 pub use crate::player_control::camera::camera_plugin;
+pub use crate::player_control::emote_wheel::emote_wheel_plugin;
+pub use crate::player_control::input_bindings::input_bindings_plugin;
+pub use crate::player_control::noclip::noclip_plugin;
 pub use crate::player_control::player_embodiment::player_embodiment_plugin;
+pub use crate::player_control::warp::warp_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
 /// Handles systems exclusive to the player's control. Is split into the following sub-plugins:
 /// - [`actions_plugin`]: Handles player input such as mouse and keyboard and neatly packs it into an [`actions::Actions`] resource.
+/// - [`input_bindings_plugin`]: Lets the player rebind the keys and buttons of their actions.
 /// - [`camera_plugin`]: Handles camera movement.
 /// - [`player_embodiment_plugin`]: Tells the components from [`super::movement_plugin`] about the desired player [`actions::Actions`].
 /// Also handles other systems that change how the player is physically represented in the world.
+/// - [`emote_wheel_plugin`]: Lets the player pick an emote to play.
+/// - [`noclip_plugin`]: Lets the player fly through walls for debugging.
+/// - [`warp_plugin`]: Lets testers teleport the player to named entities or positions.
 pub fn player_control_plugin(app: &mut App) {
     app.fn_plugin(actions_plugin)
+        .fn_plugin(input_bindings_plugin)
         .fn_plugin(camera_plugin)
-        .fn_plugin(player_embodiment_plugin);
+        .fn_plugin(player_embodiment_plugin)
+        .fn_plugin(emote_wheel_plugin)
+        .fn_plugin(noclip_plugin)
+        .fn_plugin(warp_plugin);
 }
@@ -1,5 +1,8 @@
+use crate::player_control::input_bindings::InputBindings;
 use crate::util::criteria::is_frozen;
+use bevy::input::gamepad::GamepadButton;
 use bevy::prelude::*;
+use bevy_egui::{egui, EguiInput, EguiSet};
 use leafwing_input_manager::axislike::DualAxisData;
 use leafwing_input_manager::plugin::InputManagerSystem;
 use leafwing_input_manager::prelude::*;
@@ -23,6 +26,7 @@ impl ActionsFrozen {
 }
 
 /// Configures [`Actions`], the resource that holds all player input.
+/// Gamepads can also navigate the UI, see [`navigate_ui_with_gamepad`].
 /// Add new input in [`set_actions`] and in [`game_control::generate_bindings!`](game_control).
 
 pub fn actions_plugin(app: &mut App) {
@@ -39,10 +43,16 @@ pub fn actions_plugin(app: &mut App) {
                 .run_if(is_frozen)
                 .after(InputManagerSystem::ManualControl)
                 .in_base_set(CoreSet::PreUpdate),
+        )
+        .add_system(
+            navigate_ui_with_gamepad
+                .after(EguiSet::ProcessInput)
+                .before(EguiSet::BeginFrame)
+                .in_base_set(CoreSet::PreUpdate),
         );
 }
 
-#[derive(Debug, Clone, Copy, Actionlike, Reflect, FromReflect, Default)]
+#[derive(Debug, Clone, Copy, Actionlike, Reflect, FromReflect, Serialize, Deserialize, Default)]
 pub enum PlayerAction {
     #[default]
     Move,
@@ -50,6 +60,12 @@ pub enum PlayerAction {
     Jump,
     Interact,
     SpeedUpDialog,
+    SkipDialogLine,
+    EmoteWheel,
+    Attack,
+    Dash,
+    Build,
+    ToggleInventory,
     NumberedChoice1,
     NumberedChoice2,
     NumberedChoice3,
@@ -88,34 +104,20 @@ pub enum CameraAction {
     #[default]
     Orbit,
     Zoom,
+    ToggleFirstPerson,
 }
 
 #[derive(Debug, Clone, Actionlike, Reflect, FromReflect, Default)]
 pub enum UiAction {
     #[default]
     TogglePause,
+    ToggleChat,
 }
 
+/// The bindings are replaced by the player's [`InputBindings`] as soon as the bundle is spawned.
 pub fn create_player_action_input_manager_bundle() -> InputManagerBundle<PlayerAction> {
     InputManagerBundle {
-        input_map: InputMap::new([
-            (QwertyScanCode::Space, PlayerAction::Jump),
-            (QwertyScanCode::LShift, PlayerAction::Sprint),
-            (QwertyScanCode::E, PlayerAction::Interact),
-            (QwertyScanCode::Space, PlayerAction::SpeedUpDialog),
-            (QwertyScanCode::Key1, PlayerAction::NumberedChoice1),
-            (QwertyScanCode::Key2, PlayerAction::NumberedChoice2),
-            (QwertyScanCode::Key3, PlayerAction::NumberedChoice3),
-            (QwertyScanCode::Key4, PlayerAction::NumberedChoice4),
-            (QwertyScanCode::Key5, PlayerAction::NumberedChoice5),
-            (QwertyScanCode::Key6, PlayerAction::NumberedChoice6),
-            (QwertyScanCode::Key7, PlayerAction::NumberedChoice7),
-            (QwertyScanCode::Key8, PlayerAction::NumberedChoice8),
-            (QwertyScanCode::Key9, PlayerAction::NumberedChoice9),
-            (QwertyScanCode::Key0, PlayerAction::NumberedChoice0),
-        ])
-        .insert(VirtualDPad::wasd(), PlayerAction::Move)
-        .build(),
+        input_map: InputBindings::default().player_input_map(),
         ..default()
     }
 }
@@ -125,6 +127,11 @@ pub fn create_camera_action_input_manager_bundle() -> InputManagerBundle<CameraA
         input_map: InputMap::default()
             .insert(DualAxis::mouse_motion(), CameraAction::Orbit)
             .insert(SingleAxis::mouse_wheel_y(), CameraAction::Zoom)
+            .insert(KeyCode::V, CameraAction::ToggleFirstPerson)
+            .insert(
+                GamepadButtonType::RightThumb,
+                CameraAction::ToggleFirstPerson,
+            )
             .build(),
         ..default()
     }
@@ -132,7 +139,7 @@ pub fn create_camera_action_input_manager_bundle() -> InputManagerBundle<CameraA
 
 pub fn create_ui_action_input_manager_bundle() -> InputManagerBundle<UiAction> {
     InputManagerBundle {
-        input_map: InputMap::new([(QwertyScanCode::Escape, UiAction::TogglePause)]),
+        input_map: InputBindings::default().ui_input_map(),
         ..default()
     }
 }
@@ -146,12 +153,56 @@ pub fn remove_actions_when_frozen(
         player_actions.release(PlayerAction::Jump);
         player_actions.release(PlayerAction::Interact);
         player_actions.release(PlayerAction::Sprint);
+        player_actions.release(PlayerAction::Attack);
+        player_actions.release(PlayerAction::Dash);
+        player_actions.release(PlayerAction::Build);
     }
     for mut camera_actions in camera_actions_query.iter_mut() {
         camera_actions
             .action_data_mut(CameraAction::Orbit)
             .axis_pair = Some(default());
         camera_actions.action_data_mut(CameraAction::Zoom).value = default();
+        camera_actions.release(CameraAction::ToggleFirstPerson);
+    }
+}
+
+/// Lets the d-pad move the focus between UI widgets like the keyboard's tab key and the south button press the focused one,
+/// so that menus and dialogs can be used with a gamepad alone.
+fn navigate_ui_with_gamepad(
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut egui_inputs: Query<&mut EguiInput>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("navigate_ui_with_gamepad").entered();
+    let mut keys = Vec::new();
+    for button in gamepad_buttons.get_just_pressed() {
+        let key = match button.button_type {
+            GamepadButtonType::DPadDown | GamepadButtonType::DPadRight => {
+                (egui::Key::Tab, egui::Modifiers::NONE)
+            }
+            GamepadButtonType::DPadUp | GamepadButtonType::DPadLeft => {
+                (egui::Key::Tab, egui::Modifiers::SHIFT)
+            }
+            GamepadButtonType::South => (egui::Key::Enter, egui::Modifiers::NONE),
+            _ => continue,
+        };
+        keys.push(key);
+    }
+    if keys.is_empty() {
+        return;
+    }
+    for mut egui_input in egui_inputs.iter_mut() {
+        for (key, modifiers) in keys.iter().copied() {
+            // Release right away so that egui does not consider the key held down
+            for pressed in [true, false] {
+                egui_input.events.push(egui::Event::Key {
+                    key,
+                    pressed,
+                    repeat: false,
+                    modifiers,
+                });
+            }
+        }
     }
 }
 
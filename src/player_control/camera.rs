@@ -1,19 +1,23 @@
 use crate::player_control::camera::kind::update_drivers;
 use crate::player_control::camera::{
-    cursor::grab_cursor, focus::set_camera_focus, kind::update_kind, rig::update_rig,
+    cursor::grab_cursor, focus::set_camera_focus, kind::update_kind, rig::align_to_target_up,
+    rig::update_rig, side_view::update_side_view_focus, side_view::update_side_view_room,
     skydome::move_skydome,
 };
+use crate::world_interaction::cutscene::ActiveCutscene;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_dolly::prelude::*;
 pub use cursor::ForceCursorGrabMode;
 use serde::{Deserialize, Serialize};
+pub use side_view::SideView;
 use ui::*;
 
 mod cursor;
 pub mod focus;
 mod kind;
 mod rig;
+mod side_view;
 mod skydome;
 mod ui;
 
@@ -44,25 +48,46 @@ pub enum IngameCameraKind {
     ThirdPerson,
     FirstPerson,
     FixedAngle,
+    /// Looks at the target from the side while it is inside a camera room, see [`SideView`]
+    SideView,
 }
 
 /// Handles the main ingame camera, i.e. not the UI camera in the menu.
 /// Cameras are controlled with [`CameraActions`]. Depending on the distance, a first person,
-/// third person or fixed angle camera is used.
+/// third person or fixed angle camera is used. [`CameraAction::ToggleFirstPerson`](crate::player_control::actions::CameraAction::ToggleFirstPerson) switches straight to and from first person.
+/// The third person camera sits on a smoothed arm that is shortened whenever level geometry is in the way.
+/// All parameters are read from the [`CameraConfig`](crate::file_system_interaction::config::CameraConfig) resource, which can be edited in the editor.
+/// While the target is inside a [`VolumeKind::CameraRoom`](crate::world_interaction::volume::VolumeKind::CameraRoom),
+/// the camera switches to a side view that only follows the target once it leaves a dead zone
+/// and never shows anything outside the room.
+/// The camera is tilted along with its target, so it stays upright relative to a character walking on walls or ceilings.
+/// While an [`ActiveCutscene`] plays, the rig is left alone and the cutscene moves the camera instead.
 pub fn camera_plugin(app: &mut App) {
     app.register_type::<UiCamera>()
         .register_type::<IngameCamera>()
         .register_type::<IngameCameraKind>()
+        .register_type::<SideView>()
         .init_resource::<ForceCursorGrabMode>()
-        .add_system(Dolly::<IngameCamera>::update_active)
+        .configure_set(CameraUpdateSystemSet.run_if(not(resource_exists::<ActiveCutscene>())))
+        .add_system(
+            Dolly::<IngameCamera>::update_active.run_if(not(resource_exists::<ActiveCutscene>())),
+        )
         .add_system(spawn_ui_camera.on_startup())
         .add_system(despawn_ui_camera.in_schedule(OnEnter(GameState::Playing)))
         .add_system(grab_cursor.in_set(OnUpdate(GameState::Playing)))
+        .add_system(
+            align_to_target_up
+                .after(Dolly::<IngameCamera>::update_active)
+                .run_if(not(resource_exists::<ActiveCutscene>()))
+                .in_set(OnUpdate(GameState::Playing)),
+        )
         .add_systems(
             (
                 update_kind,
+                update_side_view_room,
                 update_drivers,
                 set_camera_focus,
+                update_side_view_focus,
                 update_rig,
                 move_skydome,
             )
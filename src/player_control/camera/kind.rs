@@ -1,4 +1,4 @@
-use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::config::CameraConfig;
 use crate::player_control::actions::CameraAction;
 use crate::player_control::camera::{IngameCamera, IngameCameraKind};
 use bevy::prelude::*;
@@ -7,28 +7,39 @@ use leafwing_input_manager::prelude::*;
 
 pub fn update_kind(
     mut camera_query: Query<(&mut IngameCamera, &ActionState<CameraAction>)>,
-    config: Res<GameConfig>,
+    config: Res<CameraConfig>,
 ) {
     for (mut camera, actions) in camera_query.iter_mut() {
         let zoom = actions.clamped_value(CameraAction::Zoom);
         let zoomed_out = zoom < -1e-5;
         let zoomed_in = zoom > 1e-5;
+        let toggled = actions.just_pressed(CameraAction::ToggleFirstPerson);
         let new_kind = match camera.kind {
+            IngameCameraKind::FirstPerson if toggled => {
+                // Zooming out of first person starts close to the target, toggling should not
+                let third_person = &config.third_person;
+                camera.desired_distance =
+                    (third_person.min_distance + third_person.max_distance) / 2.;
+                Some(IngameCameraKind::ThirdPerson)
+            }
+            IngameCameraKind::ThirdPerson | IngameCameraKind::FixedAngle if toggled => {
+                Some(IngameCameraKind::FirstPerson)
+            }
             IngameCameraKind::FirstPerson if zoomed_out => Some(IngameCameraKind::ThirdPerson),
             IngameCameraKind::ThirdPerson
-                if camera.desired_distance < config.camera.third_person.min_distance + 1e-5
+                if camera.desired_distance < config.third_person.min_distance + 1e-5
                     && zoomed_in =>
             {
                 Some(IngameCameraKind::FirstPerson)
             }
             IngameCameraKind::ThirdPerson
-                if camera.desired_distance > config.camera.third_person.max_distance - 1e-5
+                if camera.desired_distance > config.third_person.max_distance - 1e-5
                     && zoomed_out =>
             {
                 Some(IngameCameraKind::FixedAngle)
             }
             IngameCameraKind::FixedAngle
-                if camera.desired_distance < config.camera.fixed_angle.min_distance + 1e-5
+                if camera.desired_distance < config.fixed_angle.min_distance + 1e-5
                     && zoomed_in =>
             {
                 Some(IngameCameraKind::ThirdPerson)
@@ -49,7 +60,9 @@ pub fn update_drivers(mut camera_query: Query<(&IngameCamera, &mut Rig)>) {
                 Some(_) => set_first_person_drivers_with_target(&mut rig),
                 None => set_first_person_drivers_without_target(&mut rig),
             },
-            IngameCameraKind::FixedAngle => set_fixed_angle_drivers(&mut rig),
+            IngameCameraKind::FixedAngle | IngameCameraKind::SideView => {
+                set_fixed_angle_drivers(&mut rig)
+            }
         };
     }
 }
@@ -1,7 +1,8 @@
-use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::config::CameraConfig;
+use crate::game_settings::GameSettings;
 use crate::player_control::actions::CameraAction;
 use crate::player_control::camera::rig::arm::{get_arm_distance, get_zoom_smoothness, set_arm};
-use crate::player_control::camera::{IngameCamera, IngameCameraKind};
+use crate::player_control::camera::{IngameCamera, IngameCameraKind, SideView};
 use crate::util::trait_extension::Vec2Ext;
 use anyhow::{Context, Result};
 use bevy::prelude::*;
@@ -20,20 +21,26 @@ pub fn update_rig(
         &mut Rig,
         &ActionState<CameraAction>,
         &Transform,
+        &SideView,
     )>,
     rapier_context: Res<RapierContext>,
-    config: Res<GameConfig>,
+    config: Res<CameraConfig>,
+    settings: Res<GameSettings>,
 ) -> Result<()> {
     let dt = time.delta_seconds();
-    for (mut camera, mut rig, actions, transform) in camera_query.iter_mut() {
+    for (mut camera, mut rig, actions, transform, side_view) in camera_query.iter_mut() {
         set_look_at(&mut rig, &camera);
-        set_position(&mut rig, &camera);
+        set_position(&mut rig, &camera, side_view);
         if camera.kind == IngameCameraKind::FixedAngle {
             let yaw_pitch = rig.driver_mut::<YawPitch>();
             yaw_pitch.yaw_degrees = 0.;
-            yaw_pitch.pitch_degrees = config.camera.fixed_angle.pitch;
+            yaw_pitch.pitch_degrees = config.fixed_angle.pitch;
+        } else if camera.kind == IngameCameraKind::SideView {
+            let yaw_pitch = rig.driver_mut::<YawPitch>();
+            yaw_pitch.yaw_degrees = side_view.yaw;
+            yaw_pitch.pitch_degrees = 0.;
         } else {
-            let camera_movement = get_camera_movement(actions)?;
+            let camera_movement = get_camera_movement(actions)? * settings.mouse_sensitivity;
             if !camera_movement.is_approx_zero() {
                 set_yaw_pitch(&mut rig, &camera, camera_movement, &config);
             }
@@ -51,6 +58,17 @@ pub fn update_rig(
     Ok(())
 }
 
+/// The rig itself always works with the world's up direction.
+/// Rotating its result around the target turns it into the target's frame of reference.
+pub fn align_to_target_up(mut camera_query: Query<(&IngameCamera, &mut Transform)>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("align_to_target_up").entered();
+    for (camera, mut transform) in camera_query.iter_mut() {
+        let rotation = Quat::from_rotation_arc(Vec3::Y, camera.target.up());
+        transform.rotate_around(camera.target.translation, rotation);
+    }
+}
+
 fn get_camera_movement(actions: &ActionState<CameraAction>) -> Result<Vec2> {
     actions
         .axis_pair(CameraAction::Orbit)
@@ -58,10 +76,15 @@ fn get_camera_movement(actions: &ActionState<CameraAction>) -> Result<Vec2> {
         .map(|pair| pair.xy())
 }
 
-fn set_yaw_pitch(rig: &mut Rig, camera: &IngameCamera, camera_movement: Vec2, config: &GameConfig) {
+fn set_yaw_pitch(
+    rig: &mut Rig,
+    camera: &IngameCamera,
+    camera_movement: Vec2,
+    config: &CameraConfig,
+) {
     let yaw_pitch = rig.driver_mut::<YawPitch>();
-    let yaw = -camera_movement.x * config.camera.mouse_sensitivity_x;
-    let pitch = -camera_movement.y * config.camera.mouse_sensitivity_y;
+    let yaw = -camera_movement.x * config.mouse_sensitivity_x;
+    let pitch = -camera_movement.y * config.mouse_sensitivity_y;
     yaw_pitch.rotate_yaw_pitch(yaw.to_degrees(), pitch.to_degrees());
     let (min_pitch, max_pitch) = get_pitch_extrema(config, camera);
     yaw_pitch.pitch_degrees = yaw_pitch.pitch_degrees.clamp(min_pitch, max_pitch);
@@ -77,8 +100,12 @@ fn set_look_at(rig: &mut Rig, camera: &IngameCamera) {
     };
 }
 
-fn set_position(rig: &mut Rig, camera: &IngameCamera) {
-    let target = if camera.kind != IngameCameraKind::FirstPerson && let Some(secondary_target) = camera.secondary_target {
+fn set_position(rig: &mut Rig, camera: &IngameCamera, side_view: &SideView) {
+    let target = if camera.kind == IngameCameraKind::SideView {
+        side_view.focus
+    } else if camera.kind != IngameCameraKind::FirstPerson
+        && let Some(secondary_target) = camera.secondary_target
+    {
         secondary_target.translation
     } else {
         camera.target.translation
@@ -86,16 +113,14 @@ fn set_position(rig: &mut Rig, camera: &IngameCamera) {
     rig.driver_mut::<Position>().position = target;
 }
 
-fn get_pitch_extrema(config: &GameConfig, camera: &IngameCamera) -> (f32, f32) {
+fn get_pitch_extrema(config: &CameraConfig, camera: &IngameCamera) -> (f32, f32) {
     match camera.kind {
-        IngameCameraKind::ThirdPerson => (
-            config.camera.third_person.min_pitch,
-            config.camera.third_person.max_pitch,
-        ),
-        IngameCameraKind::FirstPerson => (
-            config.camera.first_person.min_pitch,
-            config.camera.first_person.max_pitch,
-        ),
+        IngameCameraKind::ThirdPerson => {
+            (config.third_person.min_pitch, config.third_person.max_pitch)
+        }
+        IngameCameraKind::FirstPerson => {
+            (config.first_person.min_pitch, config.first_person.max_pitch)
+        }
         _ => unreachable!(),
     }
 }
@@ -103,46 +128,48 @@ fn get_pitch_extrema(config: &GameConfig, camera: &IngameCamera) -> (f32, f32) {
 fn set_desired_distance(
     camera: &mut IngameCamera,
     actions: &ActionState<CameraAction>,
-    config: &GameConfig,
+    config: &CameraConfig,
 ) {
-    let zoom = actions.clamped_value(CameraAction::Zoom) * config.camera.third_person.zoom_speed;
+    let zoom = actions.clamped_value(CameraAction::Zoom) * config.third_person.zoom_speed;
     let (min_distance, max_distance) = match camera.kind {
         IngameCameraKind::ThirdPerson => (
-            config.camera.third_person.min_distance,
-            config.camera.third_person.max_distance,
+            config.third_person.min_distance,
+            config.third_person.max_distance,
         ),
         IngameCameraKind::FixedAngle => (
-            config.camera.fixed_angle.min_distance,
-            config.camera.fixed_angle.max_distance,
+            config.fixed_angle.min_distance,
+            config.fixed_angle.max_distance,
         ),
         IngameCameraKind::FirstPerson => (0.0, 0.0),
+        IngameCameraKind::SideView => (config.side_view.distance, config.side_view.distance),
     };
     camera.desired_distance = (camera.desired_distance - zoom).clamp(min_distance, max_distance);
 }
 
-fn set_smoothness(rig: &mut Rig, config: &GameConfig, camera: &IngameCamera) {
+fn set_smoothness(rig: &mut Rig, config: &CameraConfig, camera: &IngameCamera) {
     match camera.kind {
         IngameCameraKind::ThirdPerson => {
             rig.driver_mut::<Smooth>().position_smoothness =
-                config.camera.third_person.translation_smoothing;
-            rig.driver_mut::<Smooth>().rotation_smoothness =
-                config.camera.third_person.rotation_smoothing;
-            rig.driver_mut::<LookAt>().smoothness = config.camera.third_person.tracking_smoothing;
+                config.third_person.translation_smoothing;
+            rig.driver_mut::<Smooth>().rotation_smoothness = config.third_person.rotation_smoothing;
+            rig.driver_mut::<LookAt>().smoothness = config.third_person.tracking_smoothing;
         }
         IngameCameraKind::FirstPerson => {
             rig.driver_mut::<Smooth>().position_smoothness =
-                config.camera.first_person.translation_smoothing;
-            rig.driver_mut::<Smooth>().rotation_smoothness =
-                config.camera.first_person.rotation_smoothing;
+                config.first_person.translation_smoothing;
+            rig.driver_mut::<Smooth>().rotation_smoothness = config.first_person.rotation_smoothing;
             if let Some(look_at) = rig.try_driver_mut::<LookAt>() {
-                look_at.smoothness = config.camera.first_person.tracking_smoothing;
+                look_at.smoothness = config.first_person.tracking_smoothing;
             }
         }
         IngameCameraKind::FixedAngle => {
             rig.driver_mut::<Smooth>().position_smoothness =
-                config.camera.fixed_angle.translation_smoothing;
-            rig.driver_mut::<Smooth>().rotation_smoothness =
-                config.camera.fixed_angle.rotation_smoothing;
+                config.fixed_angle.translation_smoothing;
+            rig.driver_mut::<Smooth>().rotation_smoothness = config.fixed_angle.rotation_smoothing;
+        }
+        IngameCameraKind::SideView => {
+            rig.driver_mut::<Smooth>().position_smoothness = config.side_view.translation_smoothing;
+            rig.driver_mut::<Smooth>().rotation_smoothness = config.side_view.rotation_smoothing;
         }
     }
 }
@@ -1,4 +1,4 @@
-use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::config::CameraConfig;
 use crate::player_control::camera::{IngameCamera, IngameCameraKind};
 use crate::util::smoothness_to_lerp_factor;
 use crate::util::trait_extension::F32Ext;
@@ -10,7 +10,7 @@ pub fn get_arm_distance(
     camera: &IngameCamera,
     transform: &Transform,
     rapier_context: &RapierContext,
-    config: &GameConfig,
+    config: &CameraConfig,
 ) -> Option<f32> {
     match camera.kind {
         IngameCameraKind::ThirdPerson => Some(get_distance_to_collision(
@@ -19,13 +19,13 @@ pub fn get_arm_distance(
             camera,
             transform,
         )),
-        IngameCameraKind::FixedAngle => Some(camera.desired_distance),
+        IngameCameraKind::FixedAngle | IngameCameraKind::SideView => Some(camera.desired_distance),
         _ => None,
     }
 }
 
 pub fn get_zoom_smoothness(
-    config: &GameConfig,
+    config: &CameraConfig,
     camera: &IngameCamera,
     rig: &Rig,
     new_distance: f32,
@@ -33,14 +33,16 @@ pub fn get_zoom_smoothness(
     let current_distance = rig.driver::<Arm>().offset.z;
     if new_distance < current_distance - 1e-4 {
         match camera.kind {
-            IngameCameraKind::ThirdPerson => config.camera.third_person.zoom_in_smoothing,
-            IngameCameraKind::FixedAngle => config.camera.fixed_angle.zoom_in_smoothing,
+            IngameCameraKind::ThirdPerson => config.third_person.zoom_in_smoothing,
+            IngameCameraKind::FixedAngle => config.fixed_angle.zoom_in_smoothing,
+            IngameCameraKind::SideView => config.side_view.zoom_smoothing,
             _ => unreachable!(),
         }
     } else {
         match camera.kind {
-            IngameCameraKind::ThirdPerson => config.camera.third_person.zoom_out_smoothing,
-            IngameCameraKind::FixedAngle => config.camera.fixed_angle.zoom_out_smoothing,
+            IngameCameraKind::ThirdPerson => config.third_person.zoom_out_smoothing,
+            IngameCameraKind::FixedAngle => config.fixed_angle.zoom_out_smoothing,
+            IngameCameraKind::SideView => config.side_view.zoom_smoothing,
             _ => unreachable!(),
         }
     }
@@ -54,7 +56,7 @@ pub fn set_arm(rig: &mut Rig, distance: f32, zoom_smoothness: f32, dt: f32) {
 
 fn get_distance_to_collision(
     rapier_context: &RapierContext,
-    config: &GameConfig,
+    config: &CameraConfig,
     camera: &IngameCamera,
     camera_transform: &Transform,
 ) -> f32 {
@@ -67,7 +69,7 @@ fn get_distance_to_collision(
     filter.flags |= QueryFilterFlags::EXCLUDE_SENSORS;
 
     let min_distance = match camera.kind {
-        IngameCameraKind::ThirdPerson => config.camera.third_person.min_distance_to_objects,
+        IngameCameraKind::ThirdPerson => config.third_person.min_distance_to_objects,
         _ => unreachable!(),
     };
 
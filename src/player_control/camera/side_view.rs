@@ -0,0 +1,118 @@
+use crate::file_system_interaction::config::CameraConfig;
+use crate::player_control::camera::{IngameCamera, IngameCameraKind};
+use crate::world_interaction::volume::{Volume, VolumeKind};
+use bevy::prelude::*;
+
+/// State of the side view that is used while the camera's target is inside a [`VolumeKind::CameraRoom`].
+#[derive(Debug, Clone, PartialEq, Component, Reflect, FromReflect, Default)]
+#[reflect(Component)]
+pub struct SideView {
+    /// The camera room volume the target is in
+    pub room: Option<Entity>,
+    /// The kind to go back to when leaving all rooms
+    pub previous_kind: IngameCameraKind,
+    /// The point the camera looks at, which only follows the target once it leaves the dead zone
+    pub focus: Vec3,
+    /// Yaw in degrees of the current room, the camera looks along the room's forward direction
+    pub yaw: f32,
+}
+
+pub fn update_side_view_room(
+    mut camera_query: Query<(&mut IngameCamera, &mut SideView)>,
+    volumes: Query<(Entity, &Volume, &GlobalTransform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_side_view_room").entered();
+    for (mut camera, mut side_view) in camera_query.iter_mut() {
+        let target = camera.target.translation;
+        let rooms: Vec<_> = volumes
+            .iter()
+            .filter(|(_, volume, _)| volume.kind == VolumeKind::CameraRoom)
+            .filter(|(_, volume, transform)| {
+                let local_target = transform.affine().inverse().transform_point3(target);
+                volume.shape.contains(local_target)
+            })
+            .map(|(entity, ..)| entity)
+            .collect();
+        // Where rooms overlap, stay in the current one so the camera does not jump back and forth
+        let room = side_view
+            .room
+            .filter(|room| rooms.contains(room))
+            .or_else(|| rooms.first().copied());
+
+        match room {
+            Some(_) if camera.kind != IngameCameraKind::SideView => {
+                side_view.previous_kind = camera.kind.clone();
+                side_view.focus = target;
+                camera.kind = IngameCameraKind::SideView;
+            }
+            None if camera.kind == IngameCameraKind::SideView => {
+                camera.kind = side_view.previous_kind.clone();
+            }
+            _ => {}
+        }
+        side_view.room = room;
+    }
+}
+
+/// Moves the focus as little as needed to keep the target inside the dead zone, then moves it back towards the room's
+/// center until the visible area lies within the room. Switching rooms moves the focus at once, the rig's [`Smooth`]
+/// driver turns that into a transition.
+///
+/// [`Smooth`]: bevy_dolly::prelude::Smooth
+pub fn update_side_view_focus(
+    mut camera_query: Query<(&IngameCamera, &mut SideView, &Projection)>,
+    volumes: Query<(&Volume, &GlobalTransform)>,
+    config: Res<CameraConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_side_view_focus").entered();
+    for (camera, mut side_view, projection) in camera_query.iter_mut() {
+        let target = camera.target.translation;
+        let Some((volume, room_transform)) = side_view
+            .room
+            .filter(|_| camera.kind == IngameCameraKind::SideView)
+            .and_then(|room| volumes.get(room).ok())
+        else {
+            side_view.focus = target;
+            continue;
+        };
+        let (scale, rotation, center) = room_transform.to_scale_rotation_translation();
+        let right = rotation * Vec3::X;
+        let up = rotation * Vec3::Y;
+        let back = rotation * Vec3::Z;
+        side_view.yaw = rotation.to_euler(EulerRot::YXZ).0.to_degrees();
+
+        let dead_zone = Vec2::new(
+            config.side_view.dead_zone_width,
+            config.side_view.dead_zone_height,
+        ) / 2.;
+        let offset = target - side_view.focus;
+        let offset = Vec2::new(offset.dot(right), offset.dot(up));
+        let outside_dead_zone = offset - offset.clamp(-dead_zone, dead_zone);
+        let focus = side_view.focus + right * outside_dead_zone.x + up * outside_dead_zone.y;
+
+        let room_half_extents = volume.shape.half_extents() * scale;
+        let visible_half_extents = get_visible_half_extents(projection, &config);
+        // A room smaller than the visible area keeps the camera at its center
+        let max_offset = (room_half_extents.truncate() - visible_half_extents).max(Vec2::ZERO);
+        let from_center = focus - center;
+        let clamped =
+            Vec2::new(from_center.dot(right), from_center.dot(up)).clamp(-max_offset, max_offset);
+        // The depth follows the target so it stays in the plane the camera is looking at
+        let depth = (target - center).dot(back);
+        side_view.focus = center + right * clamped.x + up * clamped.y + back * depth;
+    }
+}
+
+fn get_visible_half_extents(projection: &Projection, config: &CameraConfig) -> Vec2 {
+    match projection {
+        Projection::Perspective(perspective) => {
+            let half_height = config.side_view.distance * (perspective.fov / 2.).tan();
+            Vec2::new(half_height * perspective.aspect_ratio, half_height)
+        }
+        Projection::Orthographic(orthographic) => {
+            Vec2::new(orthographic.area.width(), orthographic.area.height()) / 2.
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::movement::general_movement::{Emote, EmoteEvent};
+use crate::player_control::actions::{ActionsFrozen, PlayerAction};
+use crate::player_control::player_embodiment::Player;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use leafwing_input_manager::prelude::ActionState;
+use std::f32::consts::TAU;
+use strum::IntoEnumIterator;
+
+const WHEEL_RADIUS: f32 = 90.;
+
+/// Shows a radial menu of [`Emote`]s while G is held. Releasing G while hovering an emote,
+/// or clicking it, makes the player play it.
+pub fn emote_wheel_plugin(app: &mut App) {
+    app.init_resource::<EmoteWheel>()
+        .add_system(show_emote_wheel.in_set(OnUpdate(GameState::Playing)));
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Default)]
+pub struct EmoteWheel {
+    pub open: bool,
+    pub hovered: Option<Emote>,
+}
+
+fn show_emote_wheel(
+    player_query: Query<(Entity, &ActionState<PlayerAction>), With<Player>>,
+    mut emote_wheel: ResMut<EmoteWheel>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut emote_events: EventWriter<EmoteEvent>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_emote_wheel").entered();
+    let Some((player, actions)) = player_query.iter().next() else {
+        return;
+    };
+    if !emote_wheel.open {
+        if actions.just_pressed(PlayerAction::EmoteWheel) && !actions_frozen.is_frozen() {
+            emote_wheel.open = true;
+            actions_frozen.freeze();
+        }
+        return;
+    }
+    if !actions.pressed(PlayerAction::EmoteWheel) {
+        if let Some(emote) = emote_wheel.hovered {
+            emote_events.send(EmoteEvent {
+                entity: player,
+                emote,
+            });
+        }
+        close(&mut emote_wheel, &mut actions_frozen);
+        return;
+    }
+
+    let ctx = egui_contexts.ctx_mut();
+    let center = ctx.screen_rect().center();
+    let emote_count = Emote::iter().count() as f32;
+    let mut hovered = None;
+    let mut clicked = None;
+    for (index, emote) in Emote::iter().enumerate() {
+        let angle = index as f32 / emote_count * TAU;
+        let position = center + WHEEL_RADIUS * egui::Vec2::new(angle.sin(), -angle.cos());
+        egui::Area::new(format!("Emote wheel {emote}"))
+            .fixed_pos(position)
+            .pivot(egui::Align2::CENTER_CENTER)
+            .show(ctx, |ui| {
+                let response = ui.button(emote.to_string());
+                if response.hovered() {
+                    hovered = Some(emote);
+                }
+                if response.clicked() {
+                    clicked = Some(emote);
+                }
+            });
+    }
+    emote_wheel.hovered = hovered;
+    if let Some(emote) = clicked {
+        emote_events.send(EmoteEvent {
+            entity: player,
+            emote,
+        });
+        close(&mut emote_wheel, &mut actions_frozen);
+    }
+}
+
+fn close(emote_wheel: &mut EmoteWheel, actions_frozen: &mut ActionsFrozen) {
+    *emote_wheel = default();
+    actions_frozen.unfreeze();
+}
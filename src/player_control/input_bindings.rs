@@ -0,0 +1,621 @@
+use crate::player_control::actions::{PlayerAction, UiAction};
+use anyhow::{Context, Result};
+use bevy::input::gamepad::GamepadButton;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui;
+use bevy_mod_sysfail::macros::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// Handles the [`InputBindings`], which map the rebindable [`BindableAction`]s to keys, mouse buttons and gamepad buttons.
+/// The player always moves with the left stick of a gamepad in addition to the bound keys.
+/// Keys are bound by their position on the keyboard, see [`Binding::Key`].
+/// They are loaded at startup and written back to `settings/input_bindings.ron` whenever they change.
+/// Changes are applied to the input maps of all [`PlayerAction`]s and [`UiAction`]s immediately.
+///
+/// The pause menu shows them via [`show_input_bindings`]. Clicking a binding inserts a [`Rebinding`] that captures
+/// the next pressed input. Inputs that are already bound to another action are rejected, so that one key never
+/// triggers two actions by accident.
+pub fn input_bindings_plugin(app: &mut App) {
+    app.register_type::<InputBindings>()
+        .init_resource::<InputBindings>()
+        .add_startup_system(load_input_bindings)
+        .add_systems((
+            save_input_bindings,
+            apply_input_bindings,
+            capture_rebinding.run_if(resource_exists::<Rebinding>()),
+        ));
+}
+
+/// Actions the player can rebind. The numbered choices in dialogs always stay on the number keys.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, EnumIter, Reflect, FromReflect, Serialize, Deserialize,
+)]
+pub enum BindableAction {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    /// Also speeds up dialogs
+    Jump,
+    /// Also skips to the full line in dialogs
+    Interact,
+    Attack,
+    Dash,
+    Build,
+    Inventory,
+    EmoteWheel,
+    TogglePause,
+    ToggleChat,
+    /// Only available in builds with the `dev` feature
+    ToggleEditor,
+}
+
+impl BindableAction {
+    /// Whether both actions can be triggered at the same time, so that they must not share an input.
+    /// Builds without the `dev` feature have no editor, so its binding does not keep other actions from using an input.
+    pub fn conflicts_with(self, other: BindableAction) -> bool {
+        let editor_exists = cfg!(feature = "dev");
+        self != other && (editor_exists || ![self, other].contains(&BindableAction::ToggleEditor))
+    }
+}
+
+impl Display for BindableAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BindableAction::MoveForward => "Move forward",
+            BindableAction::MoveBackward => "Move backward",
+            BindableAction::MoveLeft => "Move left",
+            BindableAction::MoveRight => "Move right",
+            BindableAction::Sprint => "Sprint",
+            BindableAction::Jump => "Jump",
+            BindableAction::Interact => "Interact",
+            BindableAction::Attack => "Attack",
+            BindableAction::Dash => "Dash",
+            BindableAction::Build => "Build",
+            BindableAction::Inventory => "Inventory",
+            BindableAction::EmoteWheel => "Emote wheel",
+            BindableAction::TogglePause => "Pause",
+            BindableAction::ToggleChat => "Chat",
+            BindableAction::ToggleEditor => "Toggle editor",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    /// A key by its position, so that the defaults end up in the same place on every layout.
+    /// It is named after the key at that position on a QWERTY layout.
+    Key(QwertyScanCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    fn input_kind(self) -> InputKind {
+        match self {
+            Binding::Key(key) => key.into(),
+            Binding::Mouse(button) => InputKind::Mouse(button),
+            Binding::Gamepad(button) => InputKind::GamepadButton(button),
+        }
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::Mouse(button) => write!(f, "Mouse {button:?}"),
+            Binding::Gamepad(button) => write!(f, "Gamepad {button:?}"),
+        }
+    }
+}
+
+/// Number of bindings that can be assigned to each action.
+pub const BINDING_SLOTS: usize = 2;
+
+/// The keys that can be bound, together with the key they are on a QWERTY layout.
+const BINDABLE_KEYS: [(QwertyScanCode, KeyCode); 64] = [
+    (QwertyScanCode::A, KeyCode::A),
+    (QwertyScanCode::B, KeyCode::B),
+    (QwertyScanCode::C, KeyCode::C),
+    (QwertyScanCode::D, KeyCode::D),
+    (QwertyScanCode::E, KeyCode::E),
+    (QwertyScanCode::F, KeyCode::F),
+    (QwertyScanCode::G, KeyCode::G),
+    (QwertyScanCode::H, KeyCode::H),
+    (QwertyScanCode::I, KeyCode::I),
+    (QwertyScanCode::J, KeyCode::J),
+    (QwertyScanCode::K, KeyCode::K),
+    (QwertyScanCode::L, KeyCode::L),
+    (QwertyScanCode::M, KeyCode::M),
+    (QwertyScanCode::N, KeyCode::N),
+    (QwertyScanCode::O, KeyCode::O),
+    (QwertyScanCode::P, KeyCode::P),
+    (QwertyScanCode::Q, KeyCode::Q),
+    (QwertyScanCode::R, KeyCode::R),
+    (QwertyScanCode::S, KeyCode::S),
+    (QwertyScanCode::T, KeyCode::T),
+    (QwertyScanCode::U, KeyCode::U),
+    (QwertyScanCode::V, KeyCode::V),
+    (QwertyScanCode::W, KeyCode::W),
+    (QwertyScanCode::X, KeyCode::X),
+    (QwertyScanCode::Y, KeyCode::Y),
+    (QwertyScanCode::Z, KeyCode::Z),
+    (QwertyScanCode::Key0, KeyCode::Key0),
+    (QwertyScanCode::Key1, KeyCode::Key1),
+    (QwertyScanCode::Key2, KeyCode::Key2),
+    (QwertyScanCode::Key3, KeyCode::Key3),
+    (QwertyScanCode::Key4, KeyCode::Key4),
+    (QwertyScanCode::Key5, KeyCode::Key5),
+    (QwertyScanCode::Key6, KeyCode::Key6),
+    (QwertyScanCode::Key7, KeyCode::Key7),
+    (QwertyScanCode::Key8, KeyCode::Key8),
+    (QwertyScanCode::Key9, KeyCode::Key9),
+    (QwertyScanCode::Escape, KeyCode::Escape),
+    (QwertyScanCode::Tab, KeyCode::Tab),
+    (QwertyScanCode::Space, KeyCode::Space),
+    (QwertyScanCode::Return, KeyCode::Return),
+    (QwertyScanCode::Back, KeyCode::Back),
+    (QwertyScanCode::LShift, KeyCode::LShift),
+    (QwertyScanCode::RShift, KeyCode::RShift),
+    (QwertyScanCode::LControl, KeyCode::LControl),
+    (QwertyScanCode::LAlt, KeyCode::LAlt),
+    (QwertyScanCode::Comma, KeyCode::Comma),
+    (QwertyScanCode::Period, KeyCode::Period),
+    (QwertyScanCode::Minus, KeyCode::Minus),
+    (QwertyScanCode::Up, KeyCode::Up),
+    (QwertyScanCode::Down, KeyCode::Down),
+    (QwertyScanCode::Left, KeyCode::Left),
+    (QwertyScanCode::Right, KeyCode::Right),
+    (QwertyScanCode::F1, KeyCode::F1),
+    (QwertyScanCode::F2, KeyCode::F2),
+    (QwertyScanCode::F3, KeyCode::F3),
+    (QwertyScanCode::F4, KeyCode::F4),
+    (QwertyScanCode::F5, KeyCode::F5),
+    (QwertyScanCode::F6, KeyCode::F6),
+    (QwertyScanCode::F7, KeyCode::F7),
+    (QwertyScanCode::F8, KeyCode::F8),
+    (QwertyScanCode::F9, KeyCode::F9),
+    (QwertyScanCode::F10, KeyCode::F10),
+    (QwertyScanCode::F11, KeyCode::F11),
+    (QwertyScanCode::F12, KeyCode::F12),
+];
+
+/// The key at the position of `key` on a QWERTY layout, for APIs that only take key codes.
+pub fn qwerty_key_code(key: QwertyScanCode) -> Option<KeyCode> {
+    BINDABLE_KEYS
+        .iter()
+        .find(|(scan_code, _)| *scan_code == key)
+        .map(|(_, key_code)| *key_code)
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct InputBindings {
+    // Scan codes cannot be reflected
+    #[reflect(ignore)]
+    pub bindings: HashMap<BindableAction, Vec<Binding>>,
+    /// Deflections of the left stick below this fraction are ignored when moving
+    pub gamepad_deadzone: f32,
+}
+
+/// How the bindings were saved before the gamepad deadzone was added to them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct LegacyInputBindings(HashMap<BindableAction, Vec<Binding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let keyboard = [
+            (BindableAction::MoveForward, Binding::Key(QwertyScanCode::W)),
+            (
+                BindableAction::MoveBackward,
+                Binding::Key(QwertyScanCode::S),
+            ),
+            (BindableAction::MoveLeft, Binding::Key(QwertyScanCode::A)),
+            (BindableAction::MoveRight, Binding::Key(QwertyScanCode::D)),
+            (BindableAction::Sprint, Binding::Key(QwertyScanCode::LShift)),
+            (BindableAction::Jump, Binding::Key(QwertyScanCode::Space)),
+            (BindableAction::Interact, Binding::Key(QwertyScanCode::E)),
+            (BindableAction::Attack, Binding::Mouse(MouseButton::Left)),
+            (BindableAction::Dash, Binding::Key(QwertyScanCode::C)),
+            (BindableAction::Build, Binding::Key(QwertyScanCode::B)),
+            (BindableAction::Inventory, Binding::Key(QwertyScanCode::I)),
+            (BindableAction::EmoteWheel, Binding::Key(QwertyScanCode::G)),
+            (
+                BindableAction::TogglePause,
+                Binding::Key(QwertyScanCode::Escape),
+            ),
+            (BindableAction::ToggleChat, Binding::Key(QwertyScanCode::T)),
+            (
+                BindableAction::ToggleEditor,
+                Binding::Key(QwertyScanCode::Q),
+            ),
+        ];
+        // Moving uses the left stick and the d-pad navigates menus, so they are not bound here
+        let gamepad = [
+            (
+                BindableAction::Sprint,
+                Binding::Gamepad(GamepadButtonType::LeftThumb),
+            ),
+            (
+                BindableAction::Jump,
+                Binding::Gamepad(GamepadButtonType::South),
+            ),
+            (
+                BindableAction::Interact,
+                Binding::Gamepad(GamepadButtonType::West),
+            ),
+            (
+                BindableAction::Attack,
+                Binding::Gamepad(GamepadButtonType::RightTrigger2),
+            ),
+            (
+                BindableAction::Dash,
+                Binding::Gamepad(GamepadButtonType::East),
+            ),
+            (
+                BindableAction::Build,
+                Binding::Gamepad(GamepadButtonType::Select),
+            ),
+            (
+                BindableAction::EmoteWheel,
+                Binding::Gamepad(GamepadButtonType::North),
+            ),
+            (
+                BindableAction::TogglePause,
+                Binding::Gamepad(GamepadButtonType::Start),
+            ),
+        ];
+        let mut bindings = HashMap::<_, Vec<_>>::default();
+        for (action, binding) in keyboard.into_iter().chain(gamepad) {
+            bindings.entry(action).or_default().push(binding);
+        }
+        Self {
+            bindings,
+            gamepad_deadzone: 0.15,
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: BindableAction) -> &[Binding] {
+        self.bindings
+            .get(&action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Replaces the binding in the given slot or appends it if the slot is empty.
+    pub fn set(&mut self, action: BindableAction, slot: usize, binding: Binding) {
+        let bindings = self.bindings.entry(action).or_default();
+        match bindings.get_mut(slot) {
+            Some(existing) => *existing = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    pub fn clear(&mut self, action: BindableAction, slot: usize) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            if slot < bindings.len() {
+                bindings.remove(slot);
+            }
+        }
+    }
+
+    /// Returns another action that would be triggered together with `action` if it was bound to `binding`.
+    pub fn find_conflict(
+        &self,
+        action: BindableAction,
+        binding: Binding,
+    ) -> Option<BindableAction> {
+        BindableAction::iter()
+            .find(|other| action.conflicts_with(*other) && self.get(*other).contains(&binding))
+    }
+
+    pub fn player_input_map(&self) -> InputMap<PlayerAction> {
+        let mut input_map = InputMap::default();
+        let bind = |input_map: &mut InputMap<PlayerAction>, action, player_action| {
+            for binding in self.get(action) {
+                input_map.insert(binding.input_kind(), player_action);
+            }
+        };
+        bind(&mut input_map, BindableAction::Sprint, PlayerAction::Sprint);
+        bind(&mut input_map, BindableAction::Jump, PlayerAction::Jump);
+        bind(
+            &mut input_map,
+            BindableAction::Jump,
+            PlayerAction::SpeedUpDialog,
+        );
+        bind(
+            &mut input_map,
+            BindableAction::Interact,
+            PlayerAction::SkipDialogLine,
+        );
+        bind(
+            &mut input_map,
+            BindableAction::Interact,
+            PlayerAction::Interact,
+        );
+        bind(&mut input_map, BindableAction::Attack, PlayerAction::Attack);
+        bind(&mut input_map, BindableAction::Dash, PlayerAction::Dash);
+        bind(&mut input_map, BindableAction::Build, PlayerAction::Build);
+        bind(
+            &mut input_map,
+            BindableAction::Inventory,
+            PlayerAction::ToggleInventory,
+        );
+        bind(
+            &mut input_map,
+            BindableAction::EmoteWheel,
+            PlayerAction::EmoteWheel,
+        );
+
+        input_map.insert(
+            DualAxis::symmetric(
+                GamepadAxisType::LeftStickX,
+                GamepadAxisType::LeftStickY,
+                self.gamepad_deadzone,
+            ),
+            PlayerAction::Move,
+        );
+        // Each slot gets its own dpad, e.g. one for WASD and one for the arrow keys
+        for slot in 0..BINDING_SLOTS {
+            let direction = |action| self.get(action).get(slot).copied();
+            if let (Some(up), Some(down), Some(left), Some(right)) = (
+                direction(BindableAction::MoveForward),
+                direction(BindableAction::MoveBackward),
+                direction(BindableAction::MoveLeft),
+                direction(BindableAction::MoveRight),
+            ) {
+                let dpad = VirtualDPad {
+                    up: up.input_kind(),
+                    down: down.input_kind(),
+                    left: left.input_kind(),
+                    right: right.input_kind(),
+                };
+                input_map.insert(dpad, PlayerAction::Move);
+            }
+        }
+
+        for (index, key) in [
+            QwertyScanCode::Key0,
+            QwertyScanCode::Key1,
+            QwertyScanCode::Key2,
+            QwertyScanCode::Key3,
+            QwertyScanCode::Key4,
+            QwertyScanCode::Key5,
+            QwertyScanCode::Key6,
+            QwertyScanCode::Key7,
+            QwertyScanCode::Key8,
+            QwertyScanCode::Key9,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            input_map.insert(key, PlayerAction::numbered_choice(index as u8));
+        }
+        input_map
+    }
+
+    pub fn ui_input_map(&self) -> InputMap<UiAction> {
+        let mut input_map = InputMap::default();
+        for binding in self.get(BindableAction::TogglePause) {
+            input_map.insert(binding.input_kind(), UiAction::TogglePause);
+        }
+        for binding in self.get(BindableAction::ToggleChat) {
+            input_map.insert(binding.input_kind(), UiAction::ToggleChat);
+        }
+        input_map
+    }
+}
+
+/// Exists while the next pressed input is captured as the new binding of an action.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct Rebinding {
+    pub action: BindableAction,
+    pub slot: usize,
+    /// The last rejected input and the action it is already bound to
+    pub conflict: Option<(Binding, BindableAction)>,
+}
+
+impl Rebinding {
+    pub fn new(action: BindableAction, slot: usize) -> Self {
+        Self {
+            action,
+            slot,
+            conflict: None,
+        }
+    }
+}
+
+/// Lists all bindings with buttons to rebind them. Left click a binding to rebind it, right click it to remove it.
+pub fn show_input_bindings(
+    ui: &mut egui::Ui,
+    bindings: &mut InputBindings,
+    rebinding: Option<&Rebinding>,
+) -> Option<Rebinding> {
+    let mut requested = None;
+    if let Some(rebinding) = rebinding {
+        ui.label(format!(
+            "Press a key or button for \"{}\", or ESC to cancel",
+            rebinding.action
+        ));
+        if let Some((binding, other)) = rebinding.conflict {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 120, 80),
+                format!("{binding} is already bound to \"{other}\""),
+            );
+        }
+    } else {
+        ui.label("Left click to rebind, right click to unbind");
+    }
+    ui.add_space(10.0);
+    egui::Grid::new("input_bindings")
+        .num_columns(BINDING_SLOTS + 1)
+        .striped(true)
+        .show(ui, |ui| {
+            for action in BindableAction::iter() {
+                #[cfg(not(feature = "dev"))]
+                if action == BindableAction::ToggleEditor {
+                    continue;
+                }
+                ui.label(action.to_string());
+                for slot in 0..BINDING_SLOTS {
+                    let listening = rebinding.map_or(false, |rebinding| {
+                        rebinding.action == action && rebinding.slot == slot
+                    });
+                    let text = if listening {
+                        "...".to_owned()
+                    } else {
+                        bindings
+                            .get(action)
+                            .get(slot)
+                            .map_or_else(|| "-".to_owned(), Binding::to_string)
+                    };
+                    let response = ui.add_enabled(rebinding.is_none(), egui::Button::new(text));
+                    if response.clicked() {
+                        requested = Some(Rebinding::new(action, slot));
+                    } else if response.secondary_clicked() {
+                        bindings.clear(action, slot);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    ui.add_space(10.0);
+    ui.add(
+        egui::Slider::new(&mut bindings.gamepad_deadzone, 0.0..=0.9).text("Gamepad stick deadzone"),
+    );
+    ui.add_space(10.0);
+    if ui
+        .add_enabled(rebinding.is_none(), egui::Button::new("Reset to defaults"))
+        .clicked()
+    {
+        *bindings = default();
+    }
+    requested
+}
+
+fn capture_rebinding(
+    mut commands: Commands,
+    scan_codes: Res<Input<ScanCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("capture_rebinding").entered();
+    if scan_codes.just_pressed(QwertyScanCode::Escape.into()) {
+        commands.remove_resource::<Rebinding>();
+        return;
+    }
+    let pressed = BINDABLE_KEYS
+        .iter()
+        .map(|(key, _)| *key)
+        .find(|key| scan_codes.just_pressed((*key).into()))
+        .map(Binding::Key)
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Gamepad(button.button_type))
+        });
+    let Some(binding) = pressed else {
+        return;
+    };
+    if let Some(other) = bindings.find_conflict(rebinding.action, binding) {
+        rebinding.conflict = Some((binding, other));
+        return;
+    }
+    bindings.set(rebinding.action, rebinding.slot, binding);
+    commands.remove_resource::<Rebinding>();
+}
+
+fn apply_input_bindings(
+    bindings: Res<InputBindings>,
+    mut player_input_maps: Query<&mut InputMap<PlayerAction>>,
+    mut ui_input_maps: Query<&mut InputMap<UiAction>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_input_bindings").entered();
+    for mut input_map in player_input_maps.iter_mut() {
+        if bindings.is_changed() || input_map.is_added() {
+            *input_map = bindings.player_input_map();
+        }
+    }
+    for mut input_map in ui_input_maps.iter_mut() {
+        if bindings.is_changed() || input_map.is_added() {
+            *input_map = bindings.ui_input_map();
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn load_input_bindings(mut bindings: ResMut<InputBindings>) -> Result<()> {
+    let path = get_input_bindings_path();
+    if !path.exists() {
+        return Ok(());
+    }
+    let serialized = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "Failed to read input bindings at {}",
+            path.to_string_lossy()
+        )
+    })?;
+    *bindings = deserialize_input_bindings(&serialized)?;
+    Ok(())
+}
+
+/// Also reads bindings saved in an older format, so that customized bindings survive updates.
+fn deserialize_input_bindings(serialized: &str) -> Result<InputBindings> {
+    let error = match ron::from_str(serialized) {
+        Ok(bindings) => return Ok(bindings),
+        Err(error) => error,
+    };
+    match ron::from_str(serialized) {
+        Ok(LegacyInputBindings(bindings)) => Ok(InputBindings {
+            bindings,
+            ..default()
+        }),
+        Err(_) => Err(error).context("Failed to deserialize input bindings"),
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn save_input_bindings(bindings: Res<InputBindings>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("save_input_bindings").entered();
+    // The bindings are inserted at startup, which also counts as a change
+    if !bindings.is_changed() || bindings.is_added() {
+        return Ok(());
+    }
+    let serialized = ron::ser::to_string_pretty(&*bindings, default())
+        .context("Failed to serialize input bindings")?;
+    let path = get_input_bindings_path();
+    let dir = path
+        .parent()
+        .context("Failed to get input bindings directory")?;
+    fs::create_dir_all(dir).context("Failed to create input bindings directory")?;
+    fs::write(&path, serialized).context("Failed to write input bindings")?;
+    Ok(())
+}
+
+fn get_input_bindings_path() -> PathBuf {
+    Path::new("settings").join("input_bindings.ron")
+}
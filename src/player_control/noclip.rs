@@ -0,0 +1,110 @@
+use crate::console::AddConsoleCommandExt;
+use crate::movement::general_movement::CharacterControllerSystemSet;
+use crate::player_control::actions::{DualAxisDataExt, PlayerAction};
+use crate::player_control::camera::{CameraUpdateSystemSet, IngameCamera};
+use crate::player_control::player_embodiment::Player;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+/// Flying speed in m/s.
+const NOCLIP_SPEED: f32 = 10.;
+const NOCLIP_SPRINT_SPEED: f32 = 40.;
+
+/// Lets the player fly through walls, e.g. to quickly get to the location of a bug. Toggled with the `noclip` console command.
+/// While flying, the player's collider is disabled, physics no longer moves them and they move towards where the camera looks.
+/// Jumping flies up and sprinting flies faster. Leaving noclip restores the player's previous rigid body.
+pub fn noclip_plugin(app: &mut App) {
+    app.add_system(
+        fly.after(CameraUpdateSystemSet)
+            .before(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_console_command(
+        "noclip",
+        "Toggles flying through walls. Jump to fly up and sprint to fly faster",
+        toggle_noclip,
+    );
+}
+
+/// Present on the player while flying. Holds the state to restore afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Component)]
+pub struct Noclip {
+    previous_body: RigidBody,
+}
+
+fn toggle_noclip(world: &mut World, _args: &[&str]) -> Result<String> {
+    let players: Vec<_> = world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(world)
+        .collect();
+    if players.is_empty() {
+        bail!("There is no player to toggle noclip for");
+    }
+    let mut enabled = false;
+    for player in players {
+        let mut player = world.entity_mut(player);
+        if let Some(noclip) = player.get::<Noclip>().copied() {
+            player
+                .remove::<(Noclip, ColliderDisabled)>()
+                .insert((noclip.previous_body, Velocity::zero()));
+        } else {
+            let previous_body = player
+                .get::<RigidBody>()
+                .copied()
+                .unwrap_or(RigidBody::Dynamic);
+            player.insert((
+                Noclip { previous_body },
+                ColliderDisabled,
+                RigidBody::KinematicPositionBased,
+                Velocity::zero(),
+            ));
+            enabled = true;
+        }
+    }
+    Ok(format!(
+        "Noclip {}",
+        if enabled { "enabled" } else { "disabled" }
+    ))
+}
+
+#[sysfail(log(level = "error"))]
+fn fly(
+    time: Res<Time>,
+    mut player_query: Query<
+        (&ActionState<PlayerAction>, &mut Transform),
+        (With<Player>, With<Noclip>),
+    >,
+    camera_query: Query<&Transform, (With<IngameCamera>, Without<Player>)>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("fly").entered();
+    let Some(camera_transform) = camera_query.iter().next() else {
+        return Ok(());
+    };
+    let dt = time.delta_seconds();
+    for (actions, mut transform) in player_query.iter_mut() {
+        let mut direction = Vec3::ZERO;
+        if let Some(movement) = actions
+            .axis_pair(PlayerAction::Move)
+            .context("Player movement is not an axis pair")?
+            .max_normalized()
+        {
+            direction +=
+                camera_transform.forward() * movement.y + camera_transform.right() * movement.x;
+        }
+        if actions.pressed(PlayerAction::Jump) {
+            direction += transform.up();
+        }
+        let speed = if actions.pressed(PlayerAction::Sprint) {
+            NOCLIP_SPRINT_SPEED
+        } else {
+            NOCLIP_SPEED
+        };
+        transform.translation += direction.clamp_length_max(1.) * speed * dt;
+    }
+    Ok(())
+}
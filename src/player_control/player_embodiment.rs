@@ -1,38 +1,47 @@
-use crate::file_system_interaction::audio::AudioHandles;
 use crate::file_system_interaction::config::GameConfig;
-use crate::movement::general_movement::{GeneralMovementSystemSet, Grounded, Jumping, Walking};
+use crate::movement::dash::Dash;
+use crate::movement::general_movement::{CharacterControllerSystemSet, Jumping, Walking};
+use crate::movement::ledge_grab::LedgeGrabbing;
+use crate::movement::wall_jump::WallJumping;
 use crate::player_control::actions::{DualAxisDataExt, PlayerAction};
 use crate::player_control::camera::{CameraUpdateSystemSet, IngameCamera, IngameCameraKind};
 use crate::util::smoothness_to_lerp_factor;
 use crate::util::trait_extension::{F32Ext, TransformExt, Vec3Ext};
+use crate::world_interaction::carrying::Carrying;
 use crate::world_interaction::dialog::CurrentDialog;
 use crate::GameState;
 use anyhow::{Context, Result};
 use bevy::prelude::*;
-use bevy_kira_audio::AudioInstance;
 use bevy_mod_sysfail::macros::*;
 use bevy_rapier3d::prelude::*;
 use leafwing_input_manager::prelude::ActionState;
 use serde::{Deserialize, Serialize};
 use std::ops::DerefMut;
+use std::time::Duration;
 
 /// This plugin handles everything that has to do with the player's physical representation in the world.
 /// This includes movement and rotation that differ from the way the [`MovementPlugin`] already handles characters in general.
 pub fn player_embodiment_plugin(app: &mut App) {
     app.register_type::<Timer>()
         .register_type::<Player>()
+        .add_system(
+            apply_movement_config
+                .run_if(resource_changed::<GameConfig>())
+                .before(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
         .add_systems(
             (
                 handle_jump,
+                handle_dash,
                 handle_horizontal_movement,
                 handle_speed_effects,
                 rotate_to_speaker.run_if(resource_exists::<CurrentDialog>()),
-                control_walking_sound,
                 handle_camera_kind,
             )
                 .chain()
                 .after(CameraUpdateSystemSet)
-                .before(GeneralMovementSystemSet)
+                .before(CharacterControllerSystemSet)
                 .in_set(OnUpdate(GameState::Playing)),
         );
 }
@@ -41,17 +50,72 @@ pub fn player_embodiment_plugin(app: &mut App) {
 #[reflect(Component, Serialize, Deserialize)]
 pub struct Player;
 
+fn apply_movement_config(
+    config: Res<GameConfig>,
+    mut player_query: Query<
+        (
+            &mut Walking,
+            &mut Jumping,
+            &mut WallJumping,
+            &mut Dash,
+            &mut LedgeGrabbing,
+        ),
+        With<Player>,
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_movement_config").entered();
+    let movement = &config.player.movement;
+    for (mut walking, mut jumping, mut wall_jumping, mut dash, mut ledge_grabbing) in
+        &mut player_query
+    {
+        walking.ground_acceleration = movement.ground_acceleration;
+        walking.sprinting_acceleration = movement.sprinting_acceleration;
+        walking.aerial_acceleration = movement.aerial_acceleration;
+        jumping.speed = movement.jump_speed;
+        jumping.coyote_time = movement.coyote_time;
+        jumping.buffer_time = movement.jump_buffer_time;
+        jumping.cut_factor = movement.jump_cut_factor;
+        wall_jumping.max_slide_speed = movement.wall_slide_speed;
+        wall_jumping.push_speed = movement.wall_jump_push_speed;
+        wall_jumping.jump_speed = movement.jump_speed;
+        dash.speed = movement.dash_speed;
+        dash.duration
+            .set_duration(Duration::from_secs_f32(movement.dash_duration));
+        dash.cooldown
+            .set_duration(Duration::from_secs_f32(movement.dash_cooldown));
+        ledge_grabbing.mantle_duration = movement.mantle_duration;
+    }
+}
+
 fn handle_jump(mut player_query: Query<(&ActionState<PlayerAction>, &mut Jumping), With<Player>>) {
     #[cfg(feature = "tracing")]
     let _span = info_span!("handle_jump").entered();
     for (actions, mut jump) in &mut player_query {
         jump.requested |= actions.pressed(PlayerAction::Jump);
+        jump.released |= actions.just_released(PlayerAction::Jump);
+    }
+}
+
+fn handle_dash(mut player_query: Query<(&ActionState<PlayerAction>, &mut Dash), With<Player>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_dash").entered();
+    for (actions, mut dash) in &mut player_query {
+        dash.requested |= actions.just_pressed(PlayerAction::Dash);
     }
 }
 
 #[sysfail(log(level = "error"))]
 fn handle_horizontal_movement(
-    mut player_query: Query<(&ActionState<PlayerAction>, &mut Walking, &Transform), With<Player>>,
+    mut player_query: Query<
+        (
+            &ActionState<PlayerAction>,
+            &mut Walking,
+            &Transform,
+            Option<&Carrying>,
+        ),
+        With<Player>,
+    >,
     camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
 ) -> Result<()> {
     #[cfg(feature = "tracing")]
@@ -60,7 +124,7 @@ fn handle_horizontal_movement(
         return Ok(());
     };
 
-    for (actions, mut walk, player_transform) in &mut player_query {
+    for (actions, mut walk, player_transform, carrying) in &mut player_query {
         if let Some(movement) = actions
             .axis_pair(PlayerAction::Move)
             .context("Player movement is not an axis pair")?
@@ -77,7 +141,12 @@ fn handle_horizontal_movement(
             .normalize();
 
             let sideways = forward.cross(up);
-            let forward_action = forward * movement.y;
+            // The side view only shows the plane the player can walk sideways in
+            let forward_action = if camera.kind == IngameCameraKind::SideView {
+                Vec3::ZERO
+            } else {
+                forward * movement.y
+            };
             let sideways_action = sideways * movement.x;
 
             let is_looking_backward = forward.dot(forward_action) < 0.0;
@@ -87,7 +156,8 @@ fn handle_horizontal_movement(
             } else {
                 1.
             };
-            let direction = forward_action * modifier + sideways_action;
+            let carrying_modifier = carrying.map(|carrying| carrying.speed_factor).unwrap_or(1.);
+            let direction = (forward_action * modifier + sideways_action) * carrying_modifier;
 
             walk.direction = Some(direction);
             walk.sprinting = actions.pressed(PlayerAction::Sprint);
@@ -112,7 +182,9 @@ fn handle_camera_kind(
                     player_transform.look_at(looking_target, up);
                     *visibility = Visibility::Hidden;
                 }
-                IngameCameraKind::ThirdPerson | IngameCameraKind::FixedAngle => {
+                IngameCameraKind::ThirdPerson
+                | IngameCameraKind::FixedAngle
+                | IngameCameraKind::SideView => {
                     *visibility = Visibility::Inherited;
                 }
             }
@@ -153,7 +225,7 @@ fn rotate_to_speaker(
     #[cfg(feature = "tracing")]
     let _span = info_span!("rotate_to_speaker").entered();
     let Ok(speaker_transform) = without_player.get(current_dialog.source) else {
-         return;
+        return;
     };
     let dt = time.delta_seconds();
 
@@ -171,31 +243,3 @@ fn rotate_to_speaker(
         }
     }
 }
-
-#[sysfail(log(level = "error"))]
-fn control_walking_sound(
-    time: Res<Time>,
-    character_query: Query<(&Velocity, &Transform, &Grounded), With<Player>>,
-    audio: Res<AudioHandles>,
-    mut audio_instances: ResMut<Assets<AudioInstance>>,
-) -> Result<()> {
-    #[cfg(feature = "tracing")]
-    let _span = info_span!("control_walking_sound").entered();
-    for (velocity, transform, grounded) in character_query.iter() {
-        let audio_instance = audio_instances
-            .get_mut(&audio.walking)
-            .context("Failed to get audio instance from handle")?;
-        let has_horizontal_movement = !velocity
-            .linvel
-            .split(transform.up())
-            .horizontal
-            .is_approx_zero();
-        let is_moving_on_ground = has_horizontal_movement && grounded.0;
-        if is_moving_on_ground && !time.is_paused() {
-            audio_instance.resume(default());
-        } else {
-            audio_instance.pause(default());
-        }
-    }
-    Ok(())
-}
@@ -0,0 +1,153 @@
+use crate::console::AddConsoleCommandExt;
+use crate::movement::gravity::Gravity;
+use crate::player_control::noclip::Noclip;
+use crate::player_control::player_embodiment::Player;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_rapier3d::prelude::*;
+use std::iter;
+
+/// Height in m above the destination from which the ground is searched.
+const GROUND_SEARCH_HEIGHT: f32 = 2.;
+/// Distance in m below the destination up to which the ground is searched.
+const GROUND_SEARCH_DEPTH: f32 = 100.;
+/// Gap in m between the player's collider and the ground after warping.
+const LANDING_MARGIN: f32 = 0.05;
+
+/// Lets testers move the player around big levels with the `tp` console command,
+/// either to the entity with a given [`Name`] or to coordinates.
+/// The player is placed on the ground below the destination and only if there is enough room for them.
+/// While in [noclip](crate::player_control::noclip::noclip_plugin), the player is placed exactly at the destination instead.
+pub fn warp_plugin(app: &mut App) {
+    app.add_console_command(
+        "tp",
+        "Teleports the player to an entity or a position, e.g. \"tp Zipline Anchor\" or \"tp 0 5 10\"",
+        warp_player,
+    );
+}
+
+fn warp_player(world: &mut World, args: &[&str]) -> Result<String> {
+    if args.is_empty() {
+        bail!("Usage: tp <entity name> | tp <x> <y> <z>");
+    }
+    let Some((player, collider, noclip)) = world
+        .query_filtered::<(Entity, &Collider, Option<&Noclip>), With<Player>>()
+        .iter(world)
+        .map(|(entity, collider, noclip)| (entity, collider.clone(), noclip.is_some()))
+        .next()
+    else {
+        bail!("There is no player to teleport");
+    };
+
+    let (destination, description, mut excluded) = match parse_coordinates(args) {
+        Some(coordinates) => (coordinates, coordinates.to_string(), HashSet::new()),
+        None => {
+            let name = args.join(" ");
+            let target = find_named_entity(world, &name)
+                .with_context(|| format!("No entity named \"{name}\""))?;
+            let translation = world
+                .get::<GlobalTransform>(target)
+                .context("The entity has no position")?
+                .translation();
+            // Landing on top of the target itself is not what the tester wants
+            (
+                translation,
+                format!("\"{name}\""),
+                get_hierarchy(world, target),
+            )
+        }
+    };
+    excluded.insert(player);
+
+    let landing = if noclip {
+        destination
+    } else {
+        find_landing(world, &collider, destination, &excluded)?
+    };
+    let mut player = world.entity_mut(player);
+    if let Some(mut transform) = player.get_mut::<Transform>() {
+        transform.translation = landing;
+    }
+    if let Some(mut velocity) = player.get_mut::<Velocity>() {
+        *velocity = Velocity::zero();
+    }
+    Ok(format!("Teleported player to {description} at {landing}"))
+}
+
+fn parse_coordinates(args: &[&str]) -> Option<Vec3> {
+    let [x, y, z] = args else {
+        return None;
+    };
+    Some(Vec3::new(x.parse().ok()?, y.parse().ok()?, z.parse().ok()?))
+}
+
+/// Prefers exact matches over case-insensitive ones.
+fn find_named_entity(world: &mut World, name: &str) -> Option<Entity> {
+    let named: Vec<_> = world
+        .query::<(Entity, &Name)>()
+        .iter(world)
+        .map(|(entity, entity_name)| (entity, entity_name.as_str().to_owned()))
+        .collect();
+    named
+        .iter()
+        .find(|(_, entity_name)| entity_name == name)
+        .or_else(|| {
+            named
+                .iter()
+                .find(|(_, entity_name)| entity_name.eq_ignore_ascii_case(name))
+        })
+        .map(|(entity, _)| *entity)
+}
+
+fn get_hierarchy(world: &mut World, root: Entity) -> HashSet<Entity> {
+    let mut children_query = world.query::<&Children>();
+    let mut hierarchy: HashSet<_> = iter::once(root).collect();
+    let mut unvisited = vec![root];
+    while let Some(entity) = unvisited.pop() {
+        if let Ok(children) = children_query.get(world, entity) {
+            hierarchy.extend(children.iter().copied());
+            unvisited.extend(children.iter().copied());
+        }
+    }
+    hierarchy
+}
+
+/// Finds the ground below the destination and checks that the player fits on it.
+fn find_landing(
+    world: &World,
+    collider: &Collider,
+    destination: Vec3,
+    excluded: &HashSet<Entity>,
+) -> Result<Vec3> {
+    let rapier_context = world.resource::<RapierContext>();
+    let down = world
+        .resource::<Gravity>()
+        .0
+        .try_normalize()
+        .unwrap_or(Vec3::NEG_Y);
+    let is_included = |entity: Entity| !excluded.contains(&entity);
+    let filter = QueryFilter::new().exclude_sensors().predicate(&is_included);
+
+    let origin = destination - down * GROUND_SEARCH_HEIGHT;
+    let Some((_, distance)) = rapier_context.cast_ray(
+        origin,
+        down,
+        GROUND_SEARCH_HEIGHT + GROUND_SEARCH_DEPTH,
+        true,
+        filter,
+    ) else {
+        bail!("There is no ground below {destination} to land on");
+    };
+    let ground = origin + down * distance;
+    let rotation = Quat::from_rotation_arc(Vec3::Y, -down);
+    let height = collider.raw.compute_local_aabb().maxs.y;
+    let landing = ground - down * (height + LANDING_MARGIN);
+    if rapier_context
+        .intersection_with_shape(landing, rotation, collider, filter)
+        .is_some()
+    {
+        bail!("There is not enough room for the player at {landing}");
+    }
+    Ok(landing)
+}
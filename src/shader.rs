@@ -23,6 +23,7 @@ pub fn shader_plugin(app: &mut App) {
     app.add_plugin(MaterialPlugin::<GlowyMaterial>::default())
         .add_plugin(MaterialPlugin::<RepeatedMaterial>::default())
         .add_plugin(MaterialPlugin::<SkydomeMaterial>::default())
+        .add_plugin(MaterialPlugin::<ConveyorMaterial>::default())
         .add_system(setup_shader.in_schedule(OnExit(GameState::Loading)))
         .add_system(set_texture_to_repeat.in_set(OnUpdate(GameState::Playing)));
 }
@@ -121,6 +122,36 @@ impl Material for RepeatedMaterial {
     }
 }
 
+#[repr(C, align(16))] // All WebGPU uniforms must be aligned to 16 bytes
+#[derive(Clone, Copy, ShaderType, Debug, PartialEq, Default)]
+pub struct ConveyorBelt {
+    pub color: Color,
+    /// Size of the belt mesh in m
+    pub size: Vec2,
+    /// Direction the belt moves in, in the mesh's uv space
+    pub direction: Vec2,
+    /// Distance in m the belt has moved
+    pub offset: f32,
+    pub _wasm_padding1: f32,
+    pub _wasm_padding2: f32,
+    pub _wasm_padding3: f32,
+}
+
+#[derive(AsBindGroup, Debug, Clone, TypeUuid)]
+#[uuid = "5f0a3c2e-8b71-4d96-a4e3-19c7b2d6f805"]
+/// Material for [`conveyor.wgsl`](https://github.com/janhohenheim/foxtrot/blob/main/assets/shaders/conveyor.wgsl).
+/// Every conveyor has its own, since the belt's offset is advanced by [`Conveyor`](crate::movement::conveyor::Conveyor).
+pub struct ConveyorMaterial {
+    #[uniform(0)]
+    pub belt: ConveyorBelt,
+}
+
+impl Material for ConveyorMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/conveyor.wgsl".into()
+    }
+}
+
 static REPEAT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"\[repeat:\s*(\d+),\s*(\d+)\]").expect("Failed to compile repeat regex")
 });
@@ -0,0 +1,79 @@
+use crate::GameState;
+use anyhow::{bail, Result};
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+
+/// Handles changes of the [`GameState`]. Instead of setting [`NextState`] directly, send a [`RequestStateChange`].
+/// The request is only executed if none of the registered [`StateTransitionGuard`]s object to it,
+/// e.g. the state cannot change during a cutscene and the menu cannot be entered while the editor is open.
+/// Use `OnEnter` and `OnExit` for per-state hooks as usual. Only the first accepted request per frame is executed.
+pub fn state_transition_plugin(app: &mut App) {
+    app.init_resource::<StateTransitionGuards>()
+        .add_event::<RequestStateChange>()
+        .add_system(
+            handle_state_change_requests
+                .before(apply_state_transition::<GameState>)
+                .in_base_set(CoreSet::StateTransitions),
+        )
+        .add_state_transition_guard(loading_is_one_way);
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RequestStateChange(pub GameState);
+
+/// Receives the world, the current state and the requested state.
+/// Returns an error describing why the transition is not allowed.
+pub type StateTransitionGuard = fn(&World, &GameState, &GameState) -> Result<()>;
+
+#[derive(Debug, Clone, Default, Resource)]
+pub struct StateTransitionGuards(pub Vec<StateTransitionGuard>);
+
+pub trait AddStateTransitionGuardExt {
+    fn add_state_transition_guard(&mut self, guard: StateTransitionGuard) -> &mut Self;
+}
+
+impl AddStateTransitionGuardExt for App {
+    fn add_state_transition_guard(&mut self, guard: StateTransitionGuard) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(StateTransitionGuards::default)
+            .0
+            .push(guard);
+        self
+    }
+}
+
+fn handle_state_change_requests(
+    world: &mut World,
+    requests: &mut SystemState<EventReader<RequestStateChange>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_state_change_requests").entered();
+    let requests: Vec<_> = requests.get_mut(world).iter().cloned().collect();
+    let from = world.resource::<State<GameState>>().0.clone();
+    let guards = world.resource::<StateTransitionGuards>().0.clone();
+    for RequestStateChange(to) in requests {
+        if to == from {
+            continue;
+        }
+        if let Some(error) = guards
+            .iter()
+            .find_map(|guard| guard(world, &from, &to).err())
+        {
+            warn!("Denied state change from {from:?} to {to:?}: {error}");
+            continue;
+        }
+        world.resource_mut::<NextState<GameState>>().set(to);
+        return;
+    }
+}
+
+/// [`GameState::Loading`] is entered on startup and left by the asset loader once everything is loaded.
+fn loading_is_one_way(_world: &World, from: &GameState, to: &GameState) -> Result<()> {
+    if *from == GameState::Loading {
+        bail!("Assets are still loading");
+    }
+    if *to == GameState::Loading {
+        bail!("Assets can only be loaded once");
+    }
+    Ok(())
+}
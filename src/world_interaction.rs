@@ -1,19 +1,120 @@
+pub mod bounce_pad;
+pub mod building;
+pub mod carrying;
+pub mod challenge;
+pub mod checkpoint;
+pub mod collectible;
 pub mod condition;
+pub mod consumable;
+pub mod crafting;
+pub mod currency;
+pub mod cutscene;
 pub mod dialog;
+pub mod health;
 pub mod interactions_ui;
+pub mod inventory;
+pub mod level_stats;
+pub mod lock;
+pub mod npc_memory;
+pub mod puzzle;
+pub mod quest;
+pub mod rope;
+pub mod speedrun;
+pub mod status_effect;
+pub mod teleporter;
+pub mod tutorial;
+pub mod volume;
+pub mod weather;
+pub mod world_event;
+pub mod zipline;
 
-use crate::world_interaction::condition::condition_plugin;
+use crate::world_interaction::bounce_pad::bounce_pad_plugin;
+use crate::world_interaction::building::building_plugin;
+use crate::world_interaction::carrying::carrying_plugin;
+use crate::world_interaction::challenge::challenge_plugin;
+use crate::world_interaction::checkpoint::checkpoint_plugin;
+use crate::world_interaction::collectible::collectible_plugin;
+use crate::world_interaction::consumable::consumable_plugin;
+use crate::world_interaction::crafting::crafting_plugin;
+use crate::world_interaction::currency::currency_plugin;
+use crate::world_interaction::cutscene::cutscene_plugin;
 use crate::world_interaction::dialog::dialog_plugin;
+use crate::world_interaction::health::health_plugin;
 use crate::world_interaction::interactions_ui::interactions_ui_plugin;
+use crate::world_interaction::inventory::inventory_plugin;
+use crate::world_interaction::level_stats::level_stats_plugin;
+use crate::world_interaction::lock::lock_plugin;
+use crate::world_interaction::npc_memory::npc_memory_plugin;
+use crate::world_interaction::puzzle::puzzle_plugin;
+use crate::world_interaction::quest::quest_plugin;
+use crate::world_interaction::rope::rope_plugin;
+use crate::world_interaction::speedrun::speedrun_plugin;
+use crate::world_interaction::status_effect::status_effect_plugin;
+use crate::world_interaction::teleporter::teleporter_plugin;
+use crate::world_interaction::tutorial::tutorial_plugin;
+use crate::world_interaction::volume::volume_plugin;
+use crate::world_interaction::weather::weather_plugin;
+use crate::world_interaction::world_event::world_event_plugin;
+use crate::world_interaction::zipline::zipline_plugin;
 use bevy::prelude::*;
 use seldom_fn_plugin::FnPluginExt;
 
 /// Handles player to world interactions. Split in to the following sub-plugins:
-/// - [`condition_plugin`] handles trackers of player actions such as chosen dialog options
-/// - [`dialog_plugin`] handles dialog trees
-/// - [`interactions_ui_plugin`] handles the UI for interacting with an object in front of the player.
+/// - [`dialog_plugin`] handles dialog trees and trackers of player actions such as chosen dialog options
+/// - [`cutscene_plugin`] handles cutscenes that move the camera along keyframed rails
+/// - [`interactions_ui_plugin`] handles interacting with objects in front of the player and their prompts.
+/// - [`carrying_plugin`] handles picking up, carrying and throwing physics props
+/// - [`puzzle_plugin`] handles pressure plates, sockets and the doors they open
+/// - [`rope_plugin`] handles ropes that props can be tied to and the player can swing on
+/// - [`zipline_plugin`] handles ziplines between pairs of anchors
+/// - [`teleporter_plugin`] handles teleporter pads linked by name
+/// - [`bounce_pad_plugin`] handles bounce pads and volumes that launch bodies touching them
+/// - [`health_plugin`] handles health and hazards that damage characters
+/// - [`checkpoint_plugin`] handles checkpoints and respawning the player when they die or fall out of the level
+/// - [`challenge_plugin`] handles timed challenges between start and finish gates
+/// - [`tutorial_plugin`] handles contextual tutorial popups
+/// - [`collectible_plugin`] handles pickups like coins that are pulled towards the player
+/// - [`level_stats_plugin`] handles the per-level score and the summary shown when reaching the goal
+/// - [`speedrun_plugin`] handles the optional speedrun timer, splits and ghost
+/// - [`npc_memory_plugin`] handles what NPCs remember about the player
+/// - [`building_plugin`] handles the build mode in which the player places objects for coins
+/// - [`currency_plugin`] handles the player's coins, vendors and their shops
+/// - [`volume_plugin`] handles resizable sensor volumes such as triggers and water
+/// - [`quest_plugin`] handles quests with staged objectives, started and advanced by dialogs and interactions
+/// - [`inventory_plugin`] handles item pickups and the player's inventory
+/// - [`crafting_plugin`] handles crafting items out of others following recipes
+/// - [`lock_plugin`] handles doors that are unlocked with keys from the inventory
+/// - [`status_effect_plugin`] handles temporary effects like speed boosts and their HUD icons
+/// - [`consumable_plugin`] handles items and shrines that give status effects
+/// - [`weather_plugin`] handles the weather and how it lights the level
+/// - [`world_event_plugin`] handles the in-game clock and the world events scheduled on it
 pub fn world_interaction_plugin(app: &mut App) {
-    app.fn_plugin(condition_plugin)
-        .fn_plugin(dialog_plugin)
-        .fn_plugin(interactions_ui_plugin);
+    app.fn_plugin(dialog_plugin)
+        .fn_plugin(cutscene_plugin)
+        .fn_plugin(interactions_ui_plugin)
+        .fn_plugin(carrying_plugin)
+        .fn_plugin(puzzle_plugin)
+        .fn_plugin(rope_plugin)
+        .fn_plugin(zipline_plugin)
+        .fn_plugin(teleporter_plugin)
+        .fn_plugin(bounce_pad_plugin)
+        .fn_plugin(health_plugin)
+        .fn_plugin(checkpoint_plugin)
+        .fn_plugin(challenge_plugin)
+        .fn_plugin(tutorial_plugin)
+        .fn_plugin(collectible_plugin)
+        .fn_plugin(level_stats_plugin)
+        .fn_plugin(speedrun_plugin)
+        .fn_plugin(npc_memory_plugin)
+        .fn_plugin(building_plugin)
+        .fn_plugin(currency_plugin)
+        .fn_plugin(volume_plugin)
+        .fn_plugin(quest_plugin)
+        .fn_plugin(inventory_plugin)
+        .fn_plugin(crafting_plugin)
+        .fn_plugin(lock_plugin)
+        .fn_plugin(status_effect_plugin)
+        .fn_plugin(consumable_plugin)
+        .fn_plugin(weather_plugin)
+        .fn_plugin(world_event_plugin);
 }
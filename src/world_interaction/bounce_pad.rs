@@ -0,0 +1,203 @@
+use crate::file_system_interaction::audio::{PlaySoundEvent, Sound};
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::world_interaction::volume::{Volume, VolumeKind};
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Metadata key of the speed in m/s a bounce pad launches things with, e.g. `15`.
+pub const LAUNCH_SPEED_KEY: &str = "launch_speed";
+/// Metadata key of the direction in the pad's local space that things are launched in, e.g. `0, 1, 0.5`.
+pub const LAUNCH_DIRECTION_KEY: &str = "launch_direction";
+
+const SQUASH_DURATION: f32 = 0.35;
+/// How much the model of a pad is flattened at the height of its squash.
+const SQUASH_AMOUNT: f32 = 0.5;
+
+/// Handles [`BouncePad`]s, which launch every dynamic body that touches them, characters included.
+/// The launch speed and direction are read from the metadata keys [`LAUNCH_SPEED_KEY`] and [`LAUNCH_DIRECTION_KEY`],
+/// both for bounce pad objects and for volumes of the kind [`VolumeKind::Launch`].
+/// The velocity along the launch direction is replaced, so launches are equally high no matter how something lands on the pad,
+/// while sideways momentum is kept. Pads squash their [`BouncePadModel`] and play a sound when triggered.
+pub fn bounce_pad_plugin(app: &mut App) {
+    app.register_type::<BouncePad>()
+        .register_type::<BouncePadModel>()
+        .add_event::<LaunchEvent>()
+        .add_systems(
+            (read_bounce_pad_metadata, launch_bodies, squash_bounce_pads)
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct BouncePad {
+    /// Speed in m/s along `direction` that bodies are launched with
+    pub speed: f32,
+    /// Launch direction in the pad's local space, does not need to be normalized
+    pub direction: Vec3,
+}
+
+impl Default for BouncePad {
+    fn default() -> Self {
+        Self {
+            speed: 12.,
+            direction: Vec3::Y,
+        }
+    }
+}
+
+impl BouncePad {
+    /// The launch velocity in world space for a pad with the given transform.
+    pub fn launch_velocity(&self, transform: &GlobalTransform) -> Vec3 {
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        (rotation * self.direction).normalize_or_zero() * self.speed
+    }
+}
+
+/// The part of a bounce pad that is squashed when it launches something.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct BouncePadModel {
+    /// Time in s since the last launch
+    pub since_launch: Option<f32>,
+}
+
+/// Sent whenever a bounce pad or launch volume launches a body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchEvent {
+    pub pad: Entity,
+    pub body: Entity,
+    pub velocity: Vec3,
+}
+
+#[sysfail(log(level = "error"))]
+fn read_bounce_pad_metadata(
+    mut commands: Commands,
+    objects: Query<
+        (Entity, &ObjectMetadata, Option<&BouncePad>, Option<&Volume>),
+        Or<(Changed<ObjectMetadata>, Changed<Volume>)>,
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_bounce_pad_metadata").entered();
+    for (entity, metadata, bounce_pad, volume) in objects.iter() {
+        let is_volume = volume.is_some();
+        let is_launch_volume = volume.map_or(false, |volume| volume.kind == VolumeKind::Launch);
+        if is_volume && !is_launch_volume {
+            if bounce_pad.is_some() {
+                commands.entity(entity).remove::<BouncePad>();
+            }
+            continue;
+        }
+        let Some(mut read) = bounce_pad.copied().or(is_launch_volume.then(default)) else {
+            continue;
+        };
+        if let Some(speed) = metadata.get(LAUNCH_SPEED_KEY) {
+            read.speed = speed
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse launch speed \"{speed}\""))?;
+        }
+        if let Some(direction) = metadata.get(LAUNCH_DIRECTION_KEY) {
+            read.direction = parse_direction(direction)
+                .with_context(|| format!("Failed to parse launch direction \"{direction}\""))?;
+        }
+        if bounce_pad != Some(&read) {
+            commands.entity(entity).insert(read);
+        }
+    }
+    Ok(())
+}
+
+/// Parses three comma separated components, e.g. `0, 1, 0.5`.
+pub(crate) fn parse_direction(direction: &str) -> Result<Vec3> {
+    let components = direction
+        .split(',')
+        .map(|component| component.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let [x, y, z] = components[..] else {
+        bail!("Expected three components");
+    };
+    Ok(Vec3::new(x, y, z))
+}
+
+fn launch_bodies(
+    mut collision_events: EventReader<CollisionEvent>,
+    pads: Query<(&BouncePad, &GlobalTransform)>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    mut bodies: Query<(&mut Velocity, &RigidBody)>,
+    mut models: Query<&mut BouncePadModel>,
+    mut launch_events: EventWriter<LaunchEvent>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("launch_bodies").entered();
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(a, b, _) = event else {
+            continue;
+        };
+        for (sensor, body) in [(*a, *b), (*b, *a)] {
+            // Pads detect bodies with a sensor child, volumes are sensors themselves
+            let pad = if pads.contains(sensor) {
+                sensor
+            } else if let Some(parent) = parents.get(sensor).ok().map(|parent| parent.get()) {
+                parent
+            } else {
+                continue;
+            };
+            let Ok((bounce_pad, pad_transform)) = pads.get(pad) else {
+                continue;
+            };
+            let Ok((mut velocity, rigid_body)) = bodies.get_mut(body) else {
+                continue;
+            };
+            if *rigid_body != RigidBody::Dynamic {
+                continue;
+            }
+            let launch_velocity = bounce_pad.launch_velocity(pad_transform);
+            let Some(direction) = launch_velocity.try_normalize() else {
+                continue;
+            };
+            velocity.linvel = velocity.linvel.reject_from_normalized(direction) + launch_velocity;
+            launch_events.send(LaunchEvent {
+                pad,
+                body,
+                velocity: launch_velocity,
+            });
+            sound_events.send(PlaySoundEvent::at(Sound::Launch, pad));
+            for child in children.iter_descendants(pad) {
+                if let Ok(mut model) = models.get_mut(child) {
+                    model.since_launch = Some(0.);
+                }
+            }
+        }
+    }
+}
+
+fn squash_bounce_pads(time: Res<Time>, mut models: Query<(&mut BouncePadModel, &mut Transform)>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("squash_bounce_pads").entered();
+    for (mut model, mut transform) in models.iter_mut() {
+        let Some(since_launch) = model.since_launch.as_mut() else {
+            continue;
+        };
+        *since_launch += time.delta_seconds();
+        let progress = (*since_launch / SQUASH_DURATION).min(1.);
+        // Flatten quickly, then spring back, bulging out sideways to keep the volume
+        let squash = (progress * PI).sin() * SQUASH_AMOUNT;
+        let height = 1. - squash;
+        let width = 1. / height.sqrt();
+        transform.scale = Vec3::new(width, height, width);
+        if progress >= 1. {
+            model.since_launch = None;
+            transform.scale = Vec3::ONE;
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::localization::Localization;
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::level_instantiation::spawning::custom::ObjectKind;
+use crate::level_instantiation::spawning::placement::{get_placement_position, ghost_bundle};
+use crate::level_instantiation::spawning::GameObject;
+use crate::player_control::actions::PlayerAction;
+use crate::player_control::camera::IngameCamera;
+use crate::player_control::player_embodiment::Player;
+use crate::util::criteria::is_frozen;
+use crate::util::trait_extension::Vec3Ext;
+use crate::world_interaction::currency::format_currency;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// Lets the player place objects into the world, toggled with the build action.
+/// Only the objects listed in the `building` section of the [`GameConfig`] can be built. Each costs coins,
+/// which are paid from the [`Wallet`](crate::world_interaction::currency::Wallet) in the player's [`PlayerProfile`]. While building, the number keys select an object
+/// and a preview ghost shows where the camera is aiming, using the same surface snapping as the editor's placement.
+/// Attacking places the selected object there, as long as it is within reach.
+/// Placed objects are marked with [`Built`] and stored in save games as [`BuiltObjects`].
+pub fn building_plugin(app: &mut App) {
+    app.register_type::<BuildMode>()
+        .register_type::<BuildGhost>()
+        .register_type::<Built>()
+        .add_systems(
+            (
+                toggle_build_mode.run_if(not(is_frozen)),
+                spawn_build_ghost.run_if(resource_added::<BuildMode>()),
+                despawn_build_ghost.run_if(resource_removed::<BuildMode>()),
+                update_building.run_if(resource_exists::<BuildMode>().and_then(not(is_frozen))),
+                show_build_menu.run_if(resource_exists::<BuildMode>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+/// Exists while the player is in build mode.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct BuildMode {
+    /// Index into the configured buildables
+    pub selected: usize,
+    /// Where the selected object would be placed, if the player is aiming at a spot within reach
+    pub position: Option<Vec3>,
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct BuildGhost;
+
+/// Marks objects placed by the player, so that they can be told apart from the level's own objects.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Built;
+
+/// The objects the player has built in the current level, as stored in save games.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct BuiltObjects(pub Vec<(GameObject, Transform)>);
+
+impl BuiltObjects {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Collects the built objects, i.e. those with a [`Built`] marker.
+    pub fn collect<'a>(objects: impl IntoIterator<Item = (&'a GameObject, &'a Transform)>) -> Self {
+        Self(
+            objects
+                .into_iter()
+                .map(|(object, transform)| (*object, *transform))
+                .collect(),
+        )
+    }
+
+    pub fn restore(&self, commands: &mut Commands) {
+        for (object, transform) in self.0.iter() {
+            ObjectKind::Builtin(*object).spawn_with(commands, *transform, Built);
+        }
+    }
+}
+
+fn toggle_build_mode(
+    mut commands: Commands,
+    build_mode: Option<Res<BuildMode>>,
+    players: Query<&ActionState<PlayerAction>, With<Player>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("toggle_build_mode").entered();
+    let toggled = players
+        .iter()
+        .any(|actions| actions.just_pressed(PlayerAction::Build));
+    if !toggled {
+        return;
+    }
+    if build_mode.is_some() {
+        commands.remove_resource::<BuildMode>();
+    } else {
+        commands.init_resource::<BuildMode>();
+    }
+}
+
+fn spawn_build_ghost(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("spawn_build_ghost").entered();
+    commands.spawn((
+        ghost_bundle(&mut meshes, &mut materials, Color::rgba(0.4, 1.0, 0.5, 0.5)),
+        Name::new("Build Ghost"),
+        BuildGhost,
+    ));
+}
+
+fn despawn_build_ghost(mut commands: Commands, ghosts: Query<Entity, With<BuildGhost>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("despawn_build_ghost").entered();
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_building(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    rapier_context: Res<RapierContext>,
+    mut build_mode: ResMut<BuildMode>,
+    mut profile: ResMut<PlayerProfile>,
+    players: Query<(Entity, &Transform, &ActionState<PlayerAction>), With<Player>>,
+    cameras: Query<&Transform, (With<IngameCamera>, Without<Player>)>,
+    mut ghosts: Query<
+        (&mut Transform, &mut Visibility),
+        (With<BuildGhost>, Without<Player>, Without<IngameCamera>),
+    >,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_building").entered();
+    let config = &config.building;
+    let Some((player, player_transform, actions)) = players.iter().next() else {
+        return;
+    };
+    for (index, _) in config.buildables.iter().enumerate().take(9) {
+        if actions.just_pressed(PlayerAction::numbered_choice(index as u8 + 1)) {
+            build_mode.selected = index;
+        }
+    }
+
+    let position = cameras.iter().next().and_then(|camera| {
+        let direction = camera.forward();
+        let max_distance =
+            camera.translation.distance(player_transform.translation) + config.max_distance;
+        get_placement_position(
+            &rapier_context,
+            camera.translation,
+            direction,
+            max_distance,
+            QueryFilter::new().exclude_rigid_body(player),
+        )
+    });
+    let position = position
+        .filter(|position| position.distance(player_transform.translation) <= config.max_distance);
+    if build_mode.position != position {
+        build_mode.position = position;
+    }
+    for (mut transform, mut visibility) in ghosts.iter_mut() {
+        match position {
+            Some(position) => {
+                transform.translation = position;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    let Some(position) = position else {
+        return;
+    };
+    if !actions.just_pressed(PlayerAction::Attack) {
+        return;
+    }
+    let Some(buildable) = config.buildables.get(build_mode.selected) else {
+        return;
+    };
+    if !profile.wallet.spend(buildable.cost) {
+        info!(
+            "Cannot afford {:?}: costs {}, but only {} available",
+            buildable.object, buildable.cost, profile.wallet.balance
+        );
+        return;
+    }
+    // Built objects face away from the player, like things put down in front of oneself
+    let facing = (position - player_transform.translation)
+        .split(Vec3::Y)
+        .horizontal;
+    let mut transform = Transform::from_translation(position);
+    if !facing.is_approx_zero() {
+        transform.look_at(position + facing, Vec3::Y);
+    }
+    ObjectKind::Builtin(buildable.object).spawn_with(&mut commands, transform, Built);
+}
+
+fn show_build_menu(
+    config: Res<GameConfig>,
+    build_mode: Res<BuildMode>,
+    profile: Res<PlayerProfile>,
+    localization: Res<Localization>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_build_menu").entered();
+    let balance = profile.wallet.balance;
+    egui::Window::new("Build")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10., 10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(format_currency(balance, &localization));
+            ui.separator();
+            for (index, buildable) in config.building.buildables.iter().enumerate() {
+                let text = format!(
+                    "{}. {:?} ({})",
+                    index + 1,
+                    buildable.object,
+                    format_currency(buildable.cost, &localization)
+                );
+                let text = if buildable.cost > balance {
+                    egui::RichText::new(text).weak()
+                } else {
+                    egui::RichText::new(text)
+                };
+                ui.selectable_label(index == build_mode.selected, text);
+            }
+            if build_mode.position.is_none() {
+                ui.separator();
+                ui.label("Out of reach");
+            }
+        });
+}
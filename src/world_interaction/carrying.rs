@@ -0,0 +1,144 @@
+use crate::movement::general_movement::CharacterControllerSystemSet;
+use crate::player_control::actions::PlayerAction;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::interactions_ui::InteractionUi;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// Position of a carried object relative to its carrier, i.e. slightly above and in front of it.
+const CARRY_OFFSET: Vec3 = Vec3::new(0.0, 0.3, -0.6);
+
+/// Lets the player pick up [`Carryable`] physics props with the interact button.
+/// While carrying, the player walks slower. Interacting again puts the object down,
+/// attacking throws it.
+pub fn carrying_plugin(app: &mut App) {
+    app.register_type::<Carryable>().add_systems(
+        (handle_carry_input, move_carried_objects)
+            .chain()
+            .after(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Carryable {
+    /// Factor by which the walking speed of the carrier is multiplied.
+    pub speed_factor: f32,
+    /// Speed in m/s with which the object leaves the carrier when thrown.
+    pub throw_speed: f32,
+}
+
+impl Default for Carryable {
+    fn default() -> Self {
+        Self {
+            speed_factor: 0.6,
+            throw_speed: 6.0,
+        }
+    }
+}
+
+/// Present on characters while they are carrying an object.
+#[derive(Debug, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub struct Carrying {
+    pub object: Entity,
+    pub speed_factor: f32,
+}
+
+/// Present on [`Carryable`]s while they are being carried.
+#[derive(Debug, Clone, Eq, PartialEq, Component, Serialize, Deserialize)]
+pub struct Carried {
+    pub carrier: Entity,
+}
+
+fn handle_carry_input(
+    mut commands: Commands,
+    interaction_ui: Option<Res<InteractionUi>>,
+    players: Query<
+        (
+            Entity,
+            &ActionState<PlayerAction>,
+            &Transform,
+            &Velocity,
+            Option<&Carrying>,
+        ),
+        With<Player>,
+    >,
+    carryables: Query<&Carryable>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_carry_input").entered();
+    for (player, actions, transform, velocity, carrying) in players.iter() {
+        if let Some(carrying) = carrying {
+            if actions.just_pressed(PlayerAction::Interact) {
+                release(&mut commands, player, carrying.object, velocity.linvel);
+            } else if actions.just_pressed(PlayerAction::Attack) {
+                let throw_speed = carryables
+                    .get(carrying.object)
+                    .map(|carryable| carryable.throw_speed)
+                    .unwrap_or_default();
+                let direction = (transform.forward() + transform.up() * 0.3).normalize();
+                let linvel = velocity.linvel + direction * throw_speed;
+                release(&mut commands, player, carrying.object, linvel);
+            }
+            continue;
+        }
+
+        if !actions.just_pressed(PlayerAction::Interact) {
+            continue;
+        }
+        let Some(interaction_ui) = interaction_ui.as_ref() else {
+            continue;
+        };
+        let object = interaction_ui.source;
+        let Ok(carryable) = carryables.get(object) else {
+            continue;
+        };
+        commands.entity(object).insert((
+            Carried { carrier: player },
+            RigidBody::KinematicPositionBased,
+            // Keeps the object from pushing its carrier around
+            Sensor,
+        ));
+        commands.entity(player).insert(Carrying {
+            object,
+            speed_factor: carryable.speed_factor,
+        });
+    }
+}
+
+fn release(commands: &mut Commands, carrier: Entity, object: Entity, linvel: Vec3) {
+    if let Some(mut carrier) = commands.get_entity(carrier) {
+        carrier.remove::<Carrying>();
+    }
+    if let Some(mut object) = commands.get_entity(object) {
+        object.remove::<(Carried, Sensor)>().insert((
+            RigidBody::Dynamic,
+            Velocity {
+                linvel,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn move_carried_objects(
+    mut commands: Commands,
+    mut carried_objects: Query<(Entity, &Carried, &mut Transform)>,
+    carriers: Query<&Transform, (With<Carrying>, Without<Carried>)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("move_carried_objects").entered();
+    for (entity, carried, mut transform) in carried_objects.iter_mut() {
+        let Ok(carrier_transform) = carriers.get(carried.carrier) else {
+            // The carrier was despawned or stopped carrying without releasing the object
+            release(&mut commands, carried.carrier, entity, Vec3::ZERO);
+            continue;
+        };
+        transform.translation = carrier_transform.transform_point(CARRY_OFFSET);
+        transform.rotation = carrier_transform.rotation;
+    }
+}
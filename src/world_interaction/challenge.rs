@@ -0,0 +1,353 @@
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::level_stats::{LevelStats, PlayerDied};
+use crate::world_interaction::volume::{Volume, VolumeKind};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the name that pairs the start and finish gate of a challenge, e.g. `tower climb`.
+/// Volumes of the kind [`VolumeKind::Challenge`] with the same name bound the props that are reset when it starts.
+pub const CHALLENGE_KEY: &str = "challenge";
+
+/// Time in seconds that is counted down before the timer of a challenge runs.
+const COUNTDOWN_DURATION: f32 = 3.;
+/// Time in seconds for which the result of a finished challenge is shown.
+const RESULT_DURATION: f32 = 4.;
+
+/// Handles timed challenges between a start and a finish [`ChallengeGate`] with the same name.
+/// Walking through the start gate resets all movable props inside the challenge's volumes and counts down while the player is frozen.
+/// The timer then runs until the player walks through the finish gate, or is aborted when they die.
+/// Finish times are recorded in the [`LevelStats`], so that the best ones show up in the level summary and the [`PlayerProfile`].
+pub fn challenge_plugin(app: &mut App) {
+    app.register_type::<ChallengeGate>()
+        .register_type::<ChallengeGateKind>()
+        .register_type::<ChallengeProp>()
+        .add_event::<ChallengeFinished>()
+        .add_systems(
+            (
+                cancel_challenge.run_if(resource_exists_and_changed::<CurrentLevel>()),
+                read_challenge_gate_metadata,
+                enter_challenge_gates,
+                update_challenge.run_if(resource_exists::<ActiveChallenge>()),
+                show_challenge.run_if(resource_exists::<ActiveChallenge>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct ChallengeGate {
+    /// Name of the challenge, shared by its start and finish gate
+    pub challenge: String,
+    pub kind: ChallengeGateKind,
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum ChallengeGateKind {
+    #[default]
+    Start,
+    Finish,
+}
+
+/// Where a movable prop was when the challenge first started, so that it can be put back on every attempt.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct ChallengeProp {
+    pub challenge: String,
+    pub transform: Transform,
+}
+
+/// Exists while a challenge is counted down, timed or its result is shown.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct ActiveChallenge {
+    pub challenge: String,
+    pub phase: ChallengePhase,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChallengePhase {
+    Countdown {
+        remaining: f32,
+    },
+    Running {
+        elapsed: f32,
+    },
+    Finished {
+        time: f32,
+        best: Option<f32>,
+        shown_for: f32,
+    },
+}
+
+/// Sent when the player walks through the finish gate of a running challenge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChallengeFinished {
+    pub challenge: String,
+    /// Time in seconds between the end of the countdown and reaching the finish
+    pub time: f32,
+}
+
+fn cancel_challenge(
+    mut commands: Commands,
+    challenge: Option<Res<ActiveChallenge>>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("cancel_challenge").entered();
+    let Some(challenge) = challenge else {
+        return;
+    };
+    if matches!(challenge.phase, ChallengePhase::Countdown { .. }) {
+        actions_frozen.unfreeze();
+    }
+    commands.remove_resource::<ActiveChallenge>();
+}
+
+fn read_challenge_gate_metadata(
+    mut gates: Query<(&ObjectMetadata, &mut ChallengeGate), Changed<ObjectMetadata>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_challenge_gate_metadata").entered();
+    for (metadata, mut gate) in gates.iter_mut() {
+        let Some(challenge) = metadata.get(CHALLENGE_KEY) else {
+            continue;
+        };
+        let challenge = challenge.trim();
+        if gate.challenge != challenge {
+            gate.challenge = challenge.to_owned();
+        }
+    }
+}
+
+fn enter_challenge_gates(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    players: Query<(), With<Player>>,
+    parents: Query<&Parent>,
+    gates: Query<&ChallengeGate>,
+    volumes: Query<(&Volume, &ObjectMetadata, &GlobalTransform)>,
+    mut props: Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            &RigidBody,
+            Option<&ChallengeProp>,
+            Option<&mut Velocity>,
+        ),
+        Without<Player>,
+    >,
+    mut active_challenge: Option<ResMut<ActiveChallenge>>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut finished_events: EventWriter<ChallengeFinished>,
+    stats: Res<LevelStats>,
+    profile: Res<PlayerProfile>,
+    current_level: Option<Res<CurrentLevel>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("enter_challenge_gates").entered();
+    // The player is frozen during a countdown, so a second one must not freeze them again
+    let mut counting_down = active_challenge.as_ref().map_or(false, |active| {
+        matches!(active.phase, ChallengePhase::Countdown { .. })
+    });
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(entity_a, entity_b, _) = event else {
+            continue;
+        };
+        let trigger = if players.contains(*entity_a) {
+            *entity_b
+        } else if players.contains(*entity_b) {
+            *entity_a
+        } else {
+            continue;
+        };
+        let gate = parents.get(trigger).map(Parent::get).unwrap_or(trigger);
+        let Ok(gate) = gates.get(gate) else {
+            continue;
+        };
+        match gate.kind {
+            ChallengeGateKind::Start => {
+                if counting_down {
+                    continue;
+                }
+                counting_down = true;
+                reset_props(&gate.challenge, &volumes, &mut props, &mut commands);
+                commands.insert_resource(ActiveChallenge {
+                    challenge: gate.challenge.clone(),
+                    phase: ChallengePhase::Countdown {
+                        remaining: COUNTDOWN_DURATION,
+                    },
+                });
+                actions_frozen.freeze();
+                info!("Starting challenge \"{}\"", gate.challenge);
+            }
+            ChallengeGateKind::Finish => {
+                let Some(active) = active_challenge
+                    .as_deref_mut()
+                    .filter(|active| active.challenge == gate.challenge)
+                else {
+                    continue;
+                };
+                let ChallengePhase::Running { elapsed } = active.phase else {
+                    continue;
+                };
+                let best = stats
+                    .challenge_time(&gate.challenge)
+                    .into_iter()
+                    .chain(current_level.as_ref().and_then(|level| {
+                        profile
+                            .best_results
+                            .get(&level.scene)
+                            .and_then(|record| record.challenge_times.get(&gate.challenge))
+                            .copied()
+                    }))
+                    .reduce(f32::min);
+                active.phase = ChallengePhase::Finished {
+                    time: elapsed,
+                    best,
+                    shown_for: 0.,
+                };
+                finished_events.send(ChallengeFinished {
+                    challenge: gate.challenge.clone(),
+                    time: elapsed,
+                });
+                info!(
+                    "Finished challenge \"{}\" in {elapsed:.2} s",
+                    gate.challenge
+                );
+            }
+        }
+    }
+}
+
+/// Puts every movable prop inside the challenge's volumes back where it was when the challenge was first started.
+fn reset_props(
+    challenge: &str,
+    volumes: &Query<(&Volume, &ObjectMetadata, &GlobalTransform)>,
+    props: &mut Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            &RigidBody,
+            Option<&ChallengeProp>,
+            Option<&mut Velocity>,
+        ),
+        Without<Player>,
+    >,
+    commands: &mut Commands,
+) {
+    let areas: Vec<_> = volumes
+        .iter()
+        .filter(|(volume, metadata, _)| {
+            volume.kind == VolumeKind::Challenge
+                && metadata.get(CHALLENGE_KEY).unwrap_or_default().trim() == challenge
+        })
+        .map(|(volume, _, transform)| (volume.shape, transform.affine().inverse()))
+        .collect();
+    for (entity, mut transform, global_transform, rigid_body, prop, velocity) in props.iter_mut() {
+        if *rigid_body != RigidBody::Dynamic {
+            continue;
+        }
+        match prop {
+            // Props stay registered even after being carried out of the area
+            Some(prop) if prop.challenge == challenge => {
+                *transform = prop.transform;
+                if let Some(mut velocity) = velocity {
+                    *velocity = Velocity::zero();
+                }
+            }
+            Some(_) => {}
+            None => {
+                let position = global_transform.translation();
+                let inside = areas
+                    .iter()
+                    .any(|(shape, inverse)| shape.contains(inverse.transform_point3(position)));
+                if inside {
+                    commands.entity(entity).insert(ChallengeProp {
+                        challenge: challenge.to_owned(),
+                        transform: *transform,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn update_challenge(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut active_challenge: ResMut<ActiveChallenge>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut player_died_events: EventReader<PlayerDied>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_challenge").entered();
+    let died = player_died_events.iter().count() > 0;
+    let dt = time.delta_seconds();
+    match &mut active_challenge.phase {
+        ChallengePhase::Countdown { remaining } => {
+            *remaining -= dt;
+            if *remaining <= 0. {
+                active_challenge.phase = ChallengePhase::Running { elapsed: 0. };
+                actions_frozen.unfreeze();
+            }
+        }
+        ChallengePhase::Running { elapsed } => {
+            *elapsed += dt;
+            if died {
+                info!("Challenge \"{}\" failed", active_challenge.challenge);
+                commands.remove_resource::<ActiveChallenge>();
+            }
+        }
+        ChallengePhase::Finished { shown_for, .. } => {
+            *shown_for += dt;
+            if *shown_for >= RESULT_DURATION {
+                commands.remove_resource::<ActiveChallenge>();
+            }
+        }
+    }
+}
+
+fn show_challenge(active_challenge: Res<ActiveChallenge>, mut egui_contexts: EguiContexts) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_challenge").entered();
+    egui::Area::new("challenge")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0., 100.))
+        .interactable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.label(&active_challenge.challenge);
+                match active_challenge.phase {
+                    ChallengePhase::Countdown { remaining } => {
+                        ui.heading(format!("{}", remaining.ceil().max(1.)));
+                    }
+                    ChallengePhase::Running { elapsed } => {
+                        ui.heading(format!("{elapsed:.2} s"));
+                    }
+                    ChallengePhase::Finished { time, best, .. } => {
+                        ui.heading(format!("Finished in {time:.2} s"));
+                        match best {
+                            Some(best) if best <= time => {
+                                ui.label(format!("Best: {best:.2} s"));
+                            }
+                            _ => {
+                                ui.label("New best!");
+                            }
+                        }
+                    }
+                }
+            });
+        });
+}
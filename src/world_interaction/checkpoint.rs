@@ -0,0 +1,183 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::movement::general_movement::Model;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::health::{Health, Invulnerable};
+use crate::world_interaction::level_stats::PlayerDied;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How fast in m/s the flag of a checkpoint is raised or lowered.
+const FLAG_SPEED: f32 = 1.5;
+
+/// Handles [`Checkpoint`]s and respawning the player. Touching a checkpoint records where the player is in the [`RespawnPoint`]
+/// and raises its flag, while the flag of the previously activated checkpoint is lowered again.
+/// Whenever a [`PlayerDied`] is sent, e.g. because they ran out of health or were crushed, the player is put back at the respawn point.
+/// Falling below the kill plane of the [`GameConfig`] counts as dying.
+/// Until a checkpoint is reached, the player respawns where they were spawned in the current level.
+pub fn checkpoint_plugin(app: &mut App) {
+    app.register_type::<Checkpoint>()
+        .register_type::<CheckpointFlag>()
+        .register_type::<RespawnPoint>()
+        .init_resource::<RespawnPoint>()
+        .add_systems(
+            (
+                record_spawn_point,
+                activate_checkpoints,
+                kill_fallen_players,
+                respawn_dead_players,
+                move_checkpoint_flags,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Whether this is the checkpoint the player respawns at
+    pub activated: bool,
+}
+
+/// The part of a checkpoint that is raised while it is activated.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct CheckpointFlag {
+    /// Height in m above the checkpoint while not activated
+    pub lowered: f32,
+    /// Height in m above the checkpoint while activated
+    pub raised: f32,
+}
+
+/// Where the player is put back when they die.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct RespawnPoint {
+    pub transform: Transform,
+}
+
+fn record_spawn_point(
+    players: Query<&Transform, Added<Player>>,
+    mut respawn_point: ResMut<RespawnPoint>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("record_spawn_point").entered();
+    for transform in players.iter() {
+        respawn_point.transform = *transform;
+    }
+}
+
+fn activate_checkpoints(
+    mut collision_events: EventReader<CollisionEvent>,
+    players: Query<&Transform, With<Player>>,
+    parents: Query<&Parent>,
+    mut checkpoints: Query<(Entity, &mut Checkpoint)>,
+    mut respawn_point: ResMut<RespawnPoint>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("activate_checkpoints").entered();
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(entity_a, entity_b, _) = event else {
+            continue;
+        };
+        let (player_transform, trigger) = if let Ok(transform) = players.get(*entity_a) {
+            (transform, *entity_b)
+        } else if let Ok(transform) = players.get(*entity_b) {
+            (transform, *entity_a)
+        } else {
+            continue;
+        };
+        let checkpoint = parents
+            .get(trigger)
+            .map(|parent| parent.get())
+            .unwrap_or(trigger);
+        if !checkpoints.contains(checkpoint) {
+            continue;
+        }
+        // Only the rotation around the vertical axis is kept, so that the player does not respawn tilted
+        let (yaw, ..) = player_transform.rotation.to_euler(EulerRot::YXZ);
+        respawn_point.transform = Transform::from_translation(player_transform.translation)
+            .with_rotation(Quat::from_rotation_y(yaw));
+        for (entity, mut other) in checkpoints.iter_mut() {
+            let activated = entity == checkpoint;
+            if other.activated != activated {
+                other.activated = activated;
+            }
+        }
+    }
+}
+
+fn kill_fallen_players(
+    players: Query<(Entity, &Transform), With<Player>>,
+    config: Res<GameConfig>,
+    mut player_died_events: EventWriter<PlayerDied>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("kill_fallen_players").entered();
+    for (player, transform) in players.iter() {
+        if transform.translation.y < config.health.kill_plane_height {
+            info!("Player fell out of the level");
+            player_died_events.send(PlayerDied { player });
+        }
+    }
+}
+
+fn respawn_dead_players(
+    mut commands: Commands,
+    mut player_died_events: EventReader<PlayerDied>,
+    mut players: Query<(&mut Transform, &mut Velocity, Option<&mut Health>), With<Player>>,
+    mut models: Query<(&Model, &mut Transform), Without<Player>>,
+    respawn_point: Res<RespawnPoint>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("respawn_dead_players").entered();
+    for event in player_died_events.iter() {
+        let Ok((mut transform, mut velocity, health)) = players.get_mut(event.player) else {
+            continue;
+        };
+        *transform = respawn_point.transform;
+        *velocity = Velocity::zero();
+        // Don't let the model smoothly fly over to the respawn point
+        for (model, mut model_transform) in models.iter_mut() {
+            if model.target == event.player {
+                *model_transform = *transform;
+            }
+        }
+        if let Some(mut health) = health {
+            health.current = health.max;
+        }
+        commands.entity(event.player).insert(Invulnerable {
+            remaining: config.health.invulnerability_duration,
+        });
+    }
+}
+
+fn move_checkpoint_flags(
+    time: Res<Time>,
+    checkpoints: Query<&Checkpoint>,
+    mut flags: Query<(&CheckpointFlag, &Parent, &mut Transform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("move_checkpoint_flags").entered();
+    for (flag, parent, mut transform) in flags.iter_mut() {
+        let Ok(checkpoint) = checkpoints.get(parent.get()) else {
+            continue;
+        };
+        let target = if checkpoint.activated {
+            flag.raised
+        } else {
+            flag.lowered
+        };
+        let max_step = FLAG_SPEED * time.delta_seconds();
+        let height = transform.translation.y;
+        let step = (target - height).clamp(-max_step, max_step);
+        if step != 0. {
+            transform.translation.y += step;
+        }
+    }
+}
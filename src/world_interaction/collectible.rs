@@ -0,0 +1,91 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::player_control::player_embodiment::Player;
+use crate::util::smoothness_to_lerp_factor;
+use crate::GameState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Rotation speed of idle collectibles in rad/s.
+const SPIN_SPEED: f32 = 2.;
+
+/// Handles pickups like coins. Collectibles within `magnet_radius` of the player are pulled towards them.
+/// The pull is a blend of a physical acceleration, limited by `max_speed`, and a tween towards the player that
+/// takes over the closer the collectible gets, so that it never orbits the player. Once pulled, a collectible keeps following
+/// the player until it comes within `pickup_radius`, where it is despawned and a [`CollectibleCollected`] is sent.
+/// All tunables are found in the `collectibles` section of the [`GameConfig`].
+pub fn collectible_plugin(app: &mut App) {
+    app.register_type::<Collectible>()
+        .register_type::<Attracted>()
+        .add_event::<CollectibleCollected>()
+        .add_system(attract_collectibles.in_set(OnUpdate(GameState::Playing)));
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Collectible {
+    pub value: u32,
+}
+
+/// Marks a [`Collectible`] that is being pulled towards the player.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Attracted {
+    pub velocity: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CollectibleCollected {
+    pub collector: Entity,
+    pub value: u32,
+}
+
+fn attract_collectibles(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    players: Query<(Entity, &Transform), (With<Player>, Without<Collectible>)>,
+    mut collectibles: Query<(Entity, &mut Transform, &Collectible, Option<&mut Attracted>)>,
+    mut collected_events: EventWriter<CollectibleCollected>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("attract_collectibles").entered();
+    let config = &config.collectibles;
+    let dt = time.delta_seconds();
+    for (entity, mut transform, collectible, attracted) in collectibles.iter_mut() {
+        let nearest_player = players
+            .iter()
+            .map(|(player, player_transform)| {
+                let distance = player_transform.translation.distance(transform.translation);
+                (player, player_transform.translation, distance)
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b));
+        let Some((player, target, distance)) = nearest_player else {
+            continue;
+        };
+        if distance <= config.pickup_radius {
+            commands.entity(entity).despawn_recursive();
+            collected_events.send(CollectibleCollected {
+                collector: player,
+                value: collectible.value,
+            });
+            continue;
+        }
+        let Some(mut attracted) = attracted else {
+            transform.rotate_y(SPIN_SPEED * dt);
+            if distance <= config.magnet_radius {
+                commands.entity(entity).insert(Attracted::default());
+            }
+            continue;
+        };
+        let direction = (target - transform.translation).normalize_or_zero();
+        attracted.velocity = (attracted.velocity + direction * config.magnet_acceleration * dt)
+            .clamp_length_max(config.max_speed);
+        let physical_translation = transform.translation + attracted.velocity * dt;
+        let tween_factor = smoothness_to_lerp_factor(config.tween_smoothing, dt);
+        let tweened_translation = physical_translation.lerp(target, tween_factor);
+        let closeness = 1. - (distance / config.magnet_radius).min(1.);
+        transform.translation = physical_translation.lerp(tweened_translation, closeness);
+    }
+}
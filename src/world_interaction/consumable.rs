@@ -0,0 +1,185 @@
+use crate::file_system_interaction::asset_loading::ConsumableAssets;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::world_interaction::interactions_ui::InteractionEvent;
+use crate::world_interaction::inventory::ItemUsed;
+use crate::world_interaction::status_effect::{ApplyStatusEffect, StatusEffect};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the consumable whose effects a [`Shrine`] grants, e.g. `speed_berry`.
+pub const SHRINE_KEY: &str = "shrine";
+/// Metadata key of the time in seconds until a [`Shrine`] can be used again, e.g. `30`.
+pub const SHRINE_COOLDOWN_KEY: &str = "cooldown";
+
+/// Handles items that give status effects when used from the inventory, following [`Consumable`]s authored in `assets/consumables/`.
+/// A consumable is named after its file and the item it belongs to, e.g. `speed_berry` for `consumables/speed_berry.consumable.ron`.
+/// [`Shrine`]s in levels grant the effects of a consumable to the player praying at them, without using up an item.
+/// Afterwards, the shrine's crystal goes dark until its cooldown is over.
+/// How the effects combine with ones the player already has is up to the
+/// [`status_effect_plugin`](crate::world_interaction::status_effect::status_effect_plugin).
+pub fn consumable_plugin(app: &mut App) {
+    app.register_type::<Shrine>()
+        .register_type::<ShrineCrystal>()
+        .add_systems(
+            (
+                read_shrine_metadata,
+                consume_items,
+                pray_at_shrines,
+                recharge_shrines,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid, Default)]
+#[uuid = "d41b7c2e-8f60-4a39-b5e1-07c9a3f6d218"]
+pub struct Consumable {
+    pub effects: Vec<StatusEffect>,
+}
+
+/// Grants the effects of a [`Consumable`] when the player interacts with it.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Shrine {
+    /// Name of the consumable whose effects are granted
+    pub consumable: String,
+    /// Time in seconds until the shrine can be used again after praying at it
+    pub cooldown: f32,
+    /// Time in seconds until the shrine can be used again
+    pub remaining: f32,
+}
+
+impl Default for Shrine {
+    fn default() -> Self {
+        Self {
+            consumable: "speed_berry".to_owned(),
+            cooldown: 30.,
+            remaining: 0.,
+        }
+    }
+}
+
+/// The glowing part of a [`Shrine`], which is hidden while it recharges. Its parent holds the shrine.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct ShrineCrystal;
+
+fn get_consumable<'a>(
+    name: &str,
+    consumable_assets: &ConsumableAssets,
+    consumables: &'a Assets<Consumable>,
+) -> Option<&'a Consumable> {
+    let path = format!("consumables/{name}.consumable.ron");
+    consumable_assets
+        .consumables
+        .get(&path)
+        .and_then(|handle| consumables.get(handle))
+}
+
+#[sysfail(log(level = "error"))]
+fn read_shrine_metadata(
+    mut shrines: Query<(&ObjectMetadata, &mut Shrine), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_shrine_metadata").entered();
+    for (metadata, mut shrine) in shrines.iter_mut() {
+        let mut read = shrine.clone();
+        if let Some(consumable) = metadata.get(SHRINE_KEY) {
+            read.consumable = consumable.trim().to_owned();
+        }
+        if let Some(cooldown) = metadata.get(SHRINE_COOLDOWN_KEY) {
+            read.cooldown = cooldown
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse shrine cooldown \"{cooldown}\""))?;
+        }
+        if read != *shrine {
+            *shrine = read;
+        }
+    }
+    Ok(())
+}
+
+fn consume_items(
+    mut used_events: EventReader<ItemUsed>,
+    consumable_assets: Res<ConsumableAssets>,
+    consumables: Res<Assets<Consumable>>,
+    mut status_effect_events: EventWriter<ApplyStatusEffect>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("consume_items").entered();
+    for event in used_events.iter() {
+        // Using items that are not consumables does nothing here
+        let Some(consumable) = get_consumable(&event.item, &consumable_assets, &consumables) else {
+            continue;
+        };
+        status_effect_events.send_batch(consumable.effects.iter().map(|effect| {
+            ApplyStatusEffect {
+                target: event.player,
+                effect: *effect,
+            }
+        }));
+    }
+}
+
+fn pray_at_shrines(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut shrines: Query<&mut Shrine>,
+    consumable_assets: Res<ConsumableAssets>,
+    consumables: Res<Assets<Consumable>>,
+    mut status_effect_events: EventWriter<ApplyStatusEffect>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("pray_at_shrines").entered();
+    for event in interaction_events.iter() {
+        let Ok(mut shrine) = shrines.get_mut(event.target) else {
+            continue;
+        };
+        if shrine.remaining > 0. {
+            continue;
+        }
+        let Some(consumable) = get_consumable(&shrine.consumable, &consumable_assets, &consumables)
+        else {
+            warn!("Shrine grants unknown consumable \"{}\"", shrine.consumable);
+            continue;
+        };
+        shrine.remaining = shrine.cooldown;
+        status_effect_events.send_batch(consumable.effects.iter().map(|effect| {
+            ApplyStatusEffect {
+                target: event.player,
+                effect: *effect,
+            }
+        }));
+    }
+}
+
+fn recharge_shrines(
+    time: Res<Time>,
+    mut shrines: Query<(&mut Shrine, &Children)>,
+    mut crystals: Query<&mut Visibility, With<ShrineCrystal>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("recharge_shrines").entered();
+    for (mut shrine, children) in shrines.iter_mut() {
+        if shrine.remaining > 0. {
+            shrine.remaining = (shrine.remaining - time.delta_seconds()).max(0.);
+        }
+        let visibility = if shrine.remaining > 0. {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        for child in children.iter() {
+            if let Ok(mut crystal) = crystals.get_mut(*child) {
+                if *crystal != visibility {
+                    *crystal = visibility;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::file_system_interaction::asset_loading::RecipeAssets;
+use crate::file_system_interaction::localization::Localization;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::dialog::DialogContext;
+use crate::world_interaction::inventory::{Inventory, InventoryPanel};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashMap;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+/// Handles crafting items in the player's [`Inventory`] out of other items, following [`Recipe`]s authored in `assets/recipes/`.
+/// A recipe is named after its file, e.g. `key` for `recipes/key.recipe.ron`.
+/// The crafting window is opened from the inventory panel and lists every recipe with its inputs and output,
+/// which are the same items that are picked up in levels and named by their localization `item.<id>`.
+/// Crafting uses up the inputs, adds the output and sends an [`ItemCrafted`]. It also sets the flag `crafted.<recipe>` in the
+/// [`DialogContext`], so that dialogs and quest stages can require something to have been crafted.
+pub fn crafting_plugin(app: &mut App) {
+    app.add_event::<ItemCrafted>().add_system(
+        show_crafting_window
+            .run_if(is_crafting_window_open)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid, Default)]
+#[uuid = "6a0f2e9c-5b3d-4c71-9e48-d2b7a1f0c853"]
+pub struct Recipe {
+    /// Number of each item used up by crafting
+    pub inputs: HashMap<String, u32>,
+    /// Item that is crafted
+    pub output: String,
+    #[serde(default = "get_default_amount")]
+    pub amount: u32,
+}
+
+fn get_default_amount() -> u32 {
+    1
+}
+
+impl Recipe {
+    pub fn can_craft(&self, inventory: &Inventory) -> bool {
+        self.inputs
+            .iter()
+            .all(|(item, amount)| inventory.count(item) >= *amount)
+    }
+}
+
+/// Sent after the player crafted a [`Recipe`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemCrafted {
+    pub player: Entity,
+    /// Name of the recipe
+    pub recipe: String,
+    pub item: String,
+    pub amount: u32,
+}
+
+fn is_crafting_window_open(panel: Res<InventoryPanel>) -> bool {
+    panel.open && panel.crafting
+}
+
+/// Name of a recipe asset, e.g. `key` for `recipes/key.recipe.ron`.
+fn get_recipe_name(path: &str) -> &str {
+    path.trim_start_matches("recipes/")
+        .trim_end_matches(".recipe.ron")
+}
+
+fn show_crafting_window(
+    mut players: Query<(Entity, &mut Inventory), With<Player>>,
+    mut panel: ResMut<InventoryPanel>,
+    recipe_assets: Res<RecipeAssets>,
+    recipes: Res<Assets<Recipe>>,
+    mut context: ResMut<DialogContext>,
+    localization: Res<Localization>,
+    mut crafted_events: EventWriter<ItemCrafted>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_crafting_window").entered();
+    let Some((player, mut inventory)) = players.iter_mut().next() else {
+        return;
+    };
+    let mut available: Vec<_> = recipe_assets
+        .recipes
+        .iter()
+        .filter_map(|(path, handle)| Some((get_recipe_name(path), recipes.get(handle)?)))
+        .collect();
+    available.sort_by_key(|(name, _)| *name);
+    let item_name = |item: &str| localization.get(&format!("item.{item}")).to_owned();
+
+    let mut crafted = None;
+    let mut closed = false;
+    egui::Window::new("Crafting")
+        .collapsible(false)
+        .resizable(false)
+        // Next to the inventory panel
+        .anchor(egui::Align2::RIGHT_CENTER, egui::Vec2::new(-220., 0.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if available.is_empty() {
+                ui.label("No recipes");
+            }
+            egui::Grid::new("crafting_recipes").show(ui, |ui| {
+                ui.strong("Needs");
+                ui.strong("Makes");
+                ui.end_row();
+                for (name, recipe) in &available {
+                    let mut inputs: Vec<_> = recipe
+                        .inputs
+                        .iter()
+                        .map(|(item, amount)| format!("{amount}x {}", item_name(item)))
+                        .collect();
+                    inputs.sort();
+                    ui.label(inputs.join(", "));
+                    ui.label(format!("{}x {}", recipe.amount, item_name(&recipe.output)));
+                    let craftable = recipe.can_craft(&inventory);
+                    if ui
+                        .add_enabled(craftable, egui::Button::new("Craft"))
+                        .clicked()
+                    {
+                        crafted = Some((*name, *recipe));
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            closed = ui.button("Close").clicked();
+        });
+
+    if let Some((name, recipe)) = crafted {
+        for (item, amount) in &recipe.inputs {
+            inventory.remove(item, *amount);
+        }
+        inventory.add(recipe.output.clone(), recipe.amount);
+        context.flags.insert(format!("crafted.{name}"));
+        info!("Crafted \"{name}\"");
+        crafted_events.send(ItemCrafted {
+            player,
+            recipe: name.to_owned(),
+            item: recipe.output.clone(),
+            amount: recipe.amount,
+        });
+    }
+    if closed {
+        panel.crafting = false;
+    }
+}
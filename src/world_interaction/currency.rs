@@ -0,0 +1,341 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::localization::Localization;
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::ingame_menu::Paused;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::building::BuildMode;
+use crate::world_interaction::collectible::CollectibleCollected;
+use crate::world_interaction::dialog::{DialogEffect, DialogEffectEvent, DialogTarget};
+use crate::world_interaction::interactions_ui::{Interactable, InteractionEvent};
+use crate::world_interaction::inventory::Inventory;
+use crate::world_interaction::quest::QuestCompleted;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the items a [`Vendor`] sells with their prices, e.g. `gem: 5, key: 20`.
+pub const VENDOR_KEY: &str = "vendor";
+
+/// Distance in meters from which the player can trade with a vendor that is not a character.
+const TRADE_RADIUS: f32 = 1.5;
+
+/// Handles the player's currency, which is kept in the [`Wallet`] of the [`PlayerProfile`] and thus shared by all save states.
+/// Coins are earned from [`CollectibleCollected`]s, the [`DialogEffect::GiveCurrency`] of dialogs and quest rewards,
+/// and taken by [`DialogEffect::TakeCurrency`]. They are spent on objects placed in build mode and at [`Vendor`]s,
+/// which are set up through the metadata key [`VENDOR_KEY`]. Interacting with a vendor opens its shop, where the offered items
+/// are bought into the player's [`Inventory`]. A vendor that is also a character is talked to as usual
+/// and opens its shop through the [`DialogEffect::OpenShop`] of its dialog.
+/// The balance is shown in the top right corner, formatted by [`format_currency`].
+pub fn currency_plugin(app: &mut App) {
+    app.register_type::<Wallet>()
+        .register_type::<Vendor>()
+        .register_type::<VendorOffer>()
+        .add_systems(
+            (
+                read_vendor_metadata,
+                update_wallet,
+                open_shops,
+                show_shop.run_if(resource_exists::<Shop>()),
+                show_wallet.run_if(
+                    not(resource_exists::<Paused>())
+                        .and_then(not(resource_exists::<BuildMode>()))
+                        .and_then(not(resource_exists::<Shop>())),
+                ),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "coins",
+            "Shows the player's coins or changes them by an amount, e.g. \"coins 50\" or \"coins -10\"",
+            run_coins_command,
+        );
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub struct Wallet {
+    pub balance: u32,
+}
+
+impl Wallet {
+    pub fn is_empty(&self) -> bool {
+        self.balance == 0
+    }
+
+    pub fn can_afford(&self, price: u32) -> bool {
+        self.balance >= price
+    }
+
+    pub fn earn(&mut self, amount: u32) {
+        self.balance = self.balance.saturating_add(amount);
+    }
+
+    /// Takes the price out of the wallet if it holds enough and returns whether it did.
+    pub fn spend(&mut self, price: u32) -> bool {
+        if !self.can_afford(price) {
+            return false;
+        }
+        self.balance -= price;
+        true
+    }
+
+    /// Takes up to `amount` out of the wallet, for costs the player cannot refuse.
+    pub fn take(&mut self, amount: u32) {
+        self.balance = self.balance.saturating_sub(amount);
+    }
+}
+
+/// Sells its offers for coins out of the player's [`Wallet`].
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Vendor {
+    pub offers: Vec<VendorOffer>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct VendorOffer {
+    pub item: String,
+    pub price: u32,
+}
+
+/// Exists while the shop of a [`Vendor`] is open.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Resource)]
+pub struct Shop {
+    pub vendor: Entity,
+}
+
+/// Formats an amount of coins for display, grouping the digits in threes, e.g. `12 500 Coins`.
+pub fn format_currency(amount: u32, localization: &Localization) -> String {
+    let digits = amount.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+    format!("{grouped} {}", localization.get("currency.coins"))
+}
+
+fn parse_offers(offers: &str) -> Result<Vec<VendorOffer>> {
+    offers
+        .split(',')
+        .filter(|offer| !offer.trim().is_empty())
+        .map(|offer| {
+            let Some((item, price)) = offer.split_once(':') else {
+                bail!("Failed to parse vendor offer \"{offer}\", expected <item>: <price>");
+            };
+            let price = price
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse price of vendor offer \"{offer}\""))?;
+            Ok(VendorOffer {
+                item: item.trim().to_owned(),
+                price,
+            })
+        })
+        .collect()
+}
+
+#[sysfail(log(level = "error"))]
+fn read_vendor_metadata(
+    mut commands: Commands,
+    objects: Query<
+        (
+            Entity,
+            &ObjectMetadata,
+            Option<&Interactable>,
+            Option<&DialogTarget>,
+        ),
+        Changed<ObjectMetadata>,
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_vendor_metadata").entered();
+    for (entity, metadata, interactable, dialog_target) in objects.iter() {
+        let Some(offers) = metadata.get(VENDOR_KEY) else {
+            continue;
+        };
+        let vendor = Vendor {
+            offers: parse_offers(offers)?,
+        };
+        // Characters are talked to, so they keep their prompt and open the shop through their dialog
+        if dialog_target.is_some() {
+            commands.entity(entity).insert(vendor);
+            continue;
+        }
+        let interactable = match interactable {
+            Some(interactable) => Interactable {
+                prompt: "interaction.trade".to_owned(),
+                ..interactable.clone()
+            },
+            None => Interactable::new("interaction.trade", TRADE_RADIUS),
+        };
+        commands.entity(entity).insert((vendor, interactable));
+    }
+    Ok(())
+}
+
+fn update_wallet(
+    mut collected_events: EventReader<CollectibleCollected>,
+    mut effect_events: EventReader<DialogEffectEvent>,
+    mut quest_events: EventReader<QuestCompleted>,
+    mut profile: ResMut<PlayerProfile>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_wallet").entered();
+    let collected: u32 = collected_events.iter().map(|event| event.value).sum();
+    let effects = effect_events
+        .iter()
+        .map(|event| &event.effect)
+        .chain(quest_events.iter().flat_map(|event| &event.rewards));
+    let mut wallet = profile.wallet;
+    wallet.earn(collected);
+    for effect in effects {
+        match effect {
+            DialogEffect::GiveCurrency(amount) => wallet.earn(*amount),
+            DialogEffect::TakeCurrency(amount) => wallet.take(*amount),
+            _ => {}
+        }
+    }
+    // The profile is saved on every change
+    if wallet != profile.wallet {
+        profile.wallet = wallet;
+    }
+}
+
+fn open_shops(
+    mut commands: Commands,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut effect_events: EventReader<DialogEffectEvent>,
+    vendors: Query<(), With<Vendor>>,
+    characters: Query<(), With<DialogTarget>>,
+    shop: Option<Res<Shop>>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("open_shops").entered();
+    // Characters start a dialog when interacted with, so only their dialog opens the shop
+    let interacted = interaction_events
+        .iter()
+        .map(|event| event.target)
+        .filter(|target| !characters.contains(*target));
+    let talked_to = effect_events
+        .iter()
+        .filter(|event| event.effect == DialogEffect::OpenShop)
+        .map(|event| event.source);
+    for vendor in interacted.chain(talked_to) {
+        if shop.is_some() || !vendors.contains(vendor) {
+            continue;
+        }
+        // Opening the shop frees the cursor so the offers can be clicked
+        commands.insert_resource(Shop { vendor });
+        actions_frozen.freeze();
+        return;
+    }
+}
+
+fn show_shop(
+    mut commands: Commands,
+    shop: Res<Shop>,
+    vendors: Query<(&Vendor, Option<&Name>)>,
+    mut players: Query<&mut Inventory, With<Player>>,
+    mut profile: ResMut<PlayerProfile>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    localization: Res<Localization>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_shop").entered();
+    let Ok((vendor, name)) = vendors.get(shop.vendor) else {
+        commands.remove_resource::<Shop>();
+        actions_frozen.unfreeze();
+        return;
+    };
+    let balance = profile.wallet.balance;
+    let mut bought = None;
+    let mut closed = false;
+    let title = name.map_or("Shop", |name| name.as_str());
+    egui::Window::new(title)
+        .id(egui::Id::new("shop"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("shop_offers").show(ui, |ui| {
+                for offer in &vendor.offers {
+                    ui.label(localization.get(&format!("item.{}", offer.item)));
+                    ui.label(format_currency(offer.price, &localization));
+                    let affordable = offer.price <= balance;
+                    if ui
+                        .add_enabled(affordable, egui::Button::new("Buy"))
+                        .clicked()
+                    {
+                        bought = Some(offer.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.label(format_currency(balance, &localization));
+            closed = ui.button("Close").clicked();
+        });
+    if let Some(offer) = bought {
+        if let Some(mut inventory) = players.iter_mut().next() {
+            if profile.wallet.spend(offer.price) {
+                inventory.add(offer.item.clone(), 1);
+                info!("Bought \"{}\" for {}", offer.item, offer.price);
+            }
+        }
+    }
+    if closed {
+        commands.remove_resource::<Shop>();
+        actions_frozen.unfreeze();
+    }
+}
+
+fn show_wallet(
+    profile: Res<PlayerProfile>,
+    localization: Res<Localization>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_wallet").entered();
+    egui::Area::new("Wallet")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-20., 20.))
+        .interactable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(format_currency(profile.wallet.balance, &localization));
+            });
+        });
+}
+
+fn run_coins_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let mut profile = world.resource_mut::<PlayerProfile>();
+    match args {
+        [] => {}
+        [amount] => {
+            let amount: i64 = amount
+                .parse()
+                .with_context(|| format!("Failed to parse amount \"{amount}\""))?;
+            let magnitude = u32::try_from(amount.unsigned_abs()).unwrap_or(u32::MAX);
+            if amount < 0 {
+                profile.wallet.take(magnitude);
+            } else {
+                profile.wallet.earn(magnitude);
+            }
+        }
+        _ => bail!("Usage: coins [amount]"),
+    }
+    Ok(format!("The player has {} coins", profile.wallet.balance))
+}
@@ -0,0 +1,270 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::asset_loading::CutsceneAssets;
+use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::camera::IngameCamera;
+use crate::state_transition::AddStateTransitionGuardExt;
+use crate::world_interaction::dialog::{DialogEvent, DialogId, PageId};
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Handles cutscenes, during which the ingame camera follows a [`CameraRail`] instead of its rig.
+/// A cutscene is started by a [`PlayCutsceneEvent`] or the `cutscene` console command. While it plays, player input is frozen
+/// and the camera moves from wherever it was through the keyframes of the rail, easing between them.
+/// Keyframes can start a dialog when they are reached. Once the last keyframe is done, the camera is handed back to its rig.
+/// Cutscenes cannot be interrupted by pausing or changing the [`GameState`].
+/// Rails are authored in the editor by recording the camera's current pose as keyframes.
+/// A rail is named after its file, e.g. `intro` for `cutscenes/intro.rail.ron`.
+pub fn cutscene_plugin(app: &mut App) {
+    app.register_type::<CameraRail>()
+        .register_type::<CameraKeyframe>()
+        .register_type::<Easing>()
+        .add_event::<PlayCutsceneEvent>()
+        .add_systems(
+            (
+                start_cutscenes,
+                play_cutscene.run_if(resource_exists::<ActiveCutscene>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_state_transition_guard(no_state_change_during_cutscene)
+        .add_console_command(
+            "cutscene",
+            "Plays a cutscene, e.g. \"cutscene intro\"",
+            run_cutscene_command,
+        );
+}
+
+fn no_state_change_during_cutscene(
+    world: &World,
+    _from: &GameState,
+    _to: &GameState,
+) -> Result<()> {
+    if world.contains_resource::<ActiveCutscene>() {
+        bail!("A cutscene is playing");
+    }
+    Ok(())
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, TypeUuid, Default,
+)]
+#[uuid = "3b7e0d52-9a1c-4f68-8e24-c5d1f09a6b73"]
+#[reflect(Serialize, Deserialize)]
+pub struct CameraRail {
+    pub keyframes: Vec<CameraKeyframe>,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    pub position: Vec3,
+    /// Point the camera looks at
+    pub target: Vec3,
+    /// Time in seconds it takes to get here from the previous keyframe, or from the camera's pose for the first keyframe.
+    /// A duration of zero cuts straight to this keyframe.
+    pub duration: f32,
+    #[serde(default)]
+    pub easing: Easing,
+    /// Time in seconds the camera stays here before moving on
+    #[serde(default)]
+    pub hold: f32,
+    /// Dialog that is started when the keyframe is reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialog: Option<DialogId>,
+    /// Page the dialog starts at instead of its initial page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<PageId>,
+}
+
+/// How the camera accelerates and decelerates on its way to a keyframe.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    /// Maps the linear progress `t` between 0 and 1 to the eased progress.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1. - (1. - t) * (1. - t),
+            Easing::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayCutsceneEvent {
+    pub rail: Handle<CameraRail>,
+}
+
+/// Exists while a cutscene is playing.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct ActiveCutscene {
+    pub rail: Handle<CameraRail>,
+    /// Time in seconds since the cutscene started
+    pub elapsed: f32,
+    /// Where the camera was when the cutscene started
+    pub start_position: Vec3,
+    pub start_target: Vec3,
+    /// Number of keyframes that have already been reached
+    pub reached: usize,
+}
+
+impl CameraRail {
+    /// Position and target of the camera at `elapsed` seconds into the rail when starting at the given pose,
+    /// together with the number of keyframes reached by then. Returns `None` once the rail is done.
+    pub fn sample(
+        &self,
+        elapsed: f32,
+        start_position: Vec3,
+        start_target: Vec3,
+    ) -> Option<(Vec3, Vec3, usize)> {
+        let mut remaining = elapsed;
+        let (mut position, mut target) = (start_position, start_target);
+        for (index, keyframe) in self.keyframes.iter().enumerate() {
+            if remaining < keyframe.duration {
+                let t = keyframe.easing.apply(remaining / keyframe.duration);
+                return Some((
+                    position.lerp(keyframe.position, t),
+                    target.lerp(keyframe.target, t),
+                    index,
+                ));
+            }
+            remaining -= keyframe.duration;
+            if remaining < keyframe.hold {
+                return Some((keyframe.position, keyframe.target, index + 1));
+            }
+            remaining -= keyframe.hold;
+            (position, target) = (keyframe.position, keyframe.target);
+        }
+        None
+    }
+}
+
+/// Path of the rail relative to the assets, under which it is found in the [`CutsceneAssets`].
+/// Built as a string, as replacing the extension would cut off names that contain a dot.
+pub fn get_rail_asset_path(name: &str) -> String {
+    format!("cutscenes/{name}.rail.ron")
+}
+
+pub fn get_rail_path(name: &str) -> PathBuf {
+    Path::new("assets").join(get_rail_asset_path(name))
+}
+
+fn start_cutscenes(
+    mut commands: Commands,
+    mut play_events: EventReader<PlayCutsceneEvent>,
+    cameras: Query<(&IngameCamera, &Transform)>,
+    active_cutscene: Option<Res<ActiveCutscene>>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_cutscenes").entered();
+    let Some(event) = play_events.iter().last() else {
+        return;
+    };
+    let Some((camera, transform)) = cameras.iter().next() else {
+        return;
+    };
+    // A cutscene that replaces a running one keeps the input frozen only once
+    if active_cutscene.is_none() {
+        actions_frozen.freeze();
+    }
+    commands.insert_resource(ActiveCutscene {
+        rail: event.rail.clone(),
+        elapsed: 0.,
+        start_position: transform.translation,
+        start_target: camera.target.translation,
+        reached: 0,
+    });
+}
+
+fn play_cutscene(
+    mut commands: Commands,
+    time: Res<Time>,
+    rails: Res<Assets<CameraRail>>,
+    mut active_cutscene: ResMut<ActiveCutscene>,
+    mut cameras: Query<(Entity, &mut Transform), With<IngameCamera>>,
+    mut dialog_events: EventWriter<DialogEvent>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_cutscene").entered();
+    active_cutscene.elapsed += time.delta_seconds();
+    let Some(rail) = rails.get(&active_cutscene.rail) else {
+        commands.remove_resource::<ActiveCutscene>();
+        actions_frozen.unfreeze();
+        return;
+    };
+    let sample = rail.sample(
+        active_cutscene.elapsed,
+        active_cutscene.start_position,
+        active_cutscene.start_target,
+    );
+    let reached = sample.map_or(rail.keyframes.len(), |(.., reached)| reached);
+    let Some((camera, mut transform)) = cameras.iter_mut().next() else {
+        return;
+    };
+    for keyframe in rail
+        .keyframes
+        .iter()
+        .take(reached)
+        .skip(active_cutscene.reached)
+    {
+        if let Some(dialog) = &keyframe.dialog {
+            dialog_events.send(DialogEvent {
+                dialog: dialog.clone(),
+                source: camera,
+                page: keyframe.page.clone(),
+                resumed: false,
+            });
+        }
+    }
+    active_cutscene.reached = active_cutscene.reached.max(reached);
+    match sample {
+        Some((position, target, _)) => {
+            *transform = Transform::from_translation(position).looking_at(target, Vec3::Y);
+        }
+        None => {
+            // The camera's rig takes over again from where it left off
+            commands.remove_resource::<ActiveCutscene>();
+            actions_frozen.unfreeze();
+        }
+    }
+}
+
+fn run_cutscene_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let [name] = args else {
+        bail!("Usage: cutscene <name>");
+    };
+    let path = get_rail_asset_path(name);
+    let rail = world
+        .get_resource::<CutsceneAssets>()
+        .and_then(|assets| assets.rails.get(&path))
+        .cloned()
+        .with_context(|| format!("No cutscene named \"{name}\""))?;
+    world.send_event(PlayCutsceneEvent { rail });
+    Ok(format!("Playing cutscene \"{name}\""))
+}
@@ -1,31 +1,93 @@
 use crate::file_system_interaction::asset_loading::DialogAssets;
+use crate::file_system_interaction::audio::{PlaySoundEvent, Sound};
 use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::localization::Localization;
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::ingame_menu::Paused;
+use crate::movement::general_movement::{Emote, EmoteEvent};
 use crate::player_control::actions::{ActionsFrozen, PlayerAction};
-use crate::world_interaction::condition::{ActiveConditions, ConditionAddEvent, ConditionId};
-use crate::world_interaction::dialog::resources::Page;
-pub use crate::world_interaction::dialog::resources::{
-    CurrentDialog, Dialog, DialogEvent, DialogId, InitialPage, NextPage,
+use crate::world_interaction::condition::{ActiveConditions, ConditionAddEvent};
+use crate::world_interaction::dialog::journal::{record_choices, record_visited_pages};
+use crate::world_interaction::dialog::presentation::update_dialog_presentation;
+pub use crate::world_interaction::dialog::presentation::DialogPresentation;
+use crate::world_interaction::dialog::voice_over::{
+    play_voice_over, update_voice_over_progress, VoiceOverPlayback,
 };
+use crate::world_interaction::interactions_ui::InteractionEvent;
+use crate::world_interaction::npc_memory::NpcMemory;
 use crate::GameState;
 use anyhow::{Context, Ok, Result};
 use bevy::prelude::*;
 use bevy_egui::egui::FontFamily::Proportional;
-use bevy_egui::egui::FontId;
 use bevy_egui::egui::TextStyle::{Body, Button};
+use bevy_egui::egui::{FontId, RichText};
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use bevy_mod_sysfail::macros::*;
+use foxtrot_dialog::{dialog_runtime_plugin, enter_pages, DialogLocalization, DialogSystemSet};
+pub use foxtrot_dialog::{
+    ConditionalLine, ContextRequirements, DialogChoice, DialogChoiceEvent, DialogContext,
+    DialogEffect, DialogEffectEvent, DialogEvent, DialogId, DialogJournal, InitialPage,
+    JournalChoice, JournalEntry, NextPage, PageId, PageRef, RequirementContext,
+};
 use leafwing_input_manager::prelude::ActionState;
+use seldom_fn_plugin::FnPluginExt;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use unicode_segmentation::UnicodeSegmentation;
 
-mod resources;
+mod journal;
+mod presentation;
+mod voice_over;
+
+pub type Dialog = foxtrot_dialog::Dialog<Emote>;
+pub type Page = foxtrot_dialog::Page<Emote>;
+pub type CurrentDialog = foxtrot_dialog::CurrentDialog<Emote>;
 
+/// Width and height in points of the portraits of speakers.
+const PORTRAIT_SIZE: f32 = 96.;
+
+/// Handles dialogs with NPCs on top of the [`foxtrot_dialog`] runtime, including their voice-overs, which are played on the [`DialogAudio`](crate::file_system_interaction::audio::DialogAudio) channel.
+/// A dialog starts when the player interacts with a [`DialogTarget`].
+/// Pages with an emote make the speaker play it via an [`EmoteEvent`].
+/// The texts of pages and choices are keys into the [`Localization`], so dialogs are shown in the chosen language.
+/// Seen pages and picked choices are recorded in the [`DialogJournal`] of the [`PlayerProfile`].
+/// Pages and choices can require pages to have been seen, so dialogs can react to earlier conversations.
+/// They can also be gated on the [`DialogContext`], which other systems fill with flags, held items and started quests.
+/// Pages may add lines depending on it and change it through [`DialogEffect`]s when shown, each announced by a [`DialogEffectEvent`].
+/// Each page can name its speaker and show a portrait next to its text, which is revealed letter by letter
+/// as tracked by the [`DialogPresentation`]. The player can skip to the full text.
+/// While the game is [`Paused`], the dialog is hidden behind the pause menu and resumes where it left off.
 pub fn dialog_plugin(app: &mut App) {
     app.add_plugin(EguiPlugin)
-        .register_type::<DialogId>()
-        .add_event::<DialogEvent>()
-        .add_systems((set_current_dialog, show_dialog).in_set(OnUpdate(GameState::Playing)));
+        .fn_plugin(dialog_runtime_plugin::<Emote, Localization>)
+        .configure_set(DialogSystemSet.in_set(OnUpdate(GameState::Playing)))
+        .init_resource::<DialogPresentation>()
+        .add_systems(
+            (start_dialogs_on_interaction, set_current_dialog)
+                .chain()
+                .before(enter_pages::<Emote, Localization>)
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_systems(
+            (
+                record_visited_pages,
+                play_voice_over,
+                play_page_emotes,
+                update_voice_over_progress,
+                update_dialog_presentation,
+                show_dialog.run_if(not(resource_exists::<Paused>())),
+                record_choices,
+            )
+                .chain()
+                .after(enter_pages::<Emote, Localization>)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+impl DialogLocalization for Localization {
+    fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        Localization::get(self, key)
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Component, Serialize, Deserialize, Default)]
@@ -33,10 +95,32 @@ pub struct DialogTarget {
     pub dialog_id: DialogId,
 }
 
+fn start_dialogs_on_interaction(
+    mut interaction_events: EventReader<InteractionEvent>,
+    dialog_targets: Query<&DialogTarget>,
+    mut dialog_events: EventWriter<DialogEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_dialogs_on_interaction").entered();
+    for event in interaction_events.iter() {
+        if let Ok(dialog_target) = dialog_targets.get(event.target) {
+            dialog_events.send(DialogEvent {
+                source: event.target,
+                dialog: dialog_target.dialog_id.clone(),
+                page: None,
+                resumed: false,
+            });
+        }
+    }
+}
+
 #[sysfail(log(level = "error"))]
 fn set_current_dialog(
     mut commands: Commands,
     active_conditions: Res<ActiveConditions>,
+    profile: Res<PlayerProfile>,
+    memories: Query<&NpcMemory>,
+    dialog_context: Res<DialogContext>,
     mut dialog_events: EventReader<DialogEvent>,
     dialogs: Res<Assets<Dialog>>,
     dialog_handles: Res<DialogAssets>,
@@ -69,25 +153,27 @@ fn set_current_dialog(
             .get(dialog_handle)
             .context("Failed to get dialog handle in dialog assets")?;
         let current_page = dialog_event.page.clone().or_else(|| {
-            dialog
-                .initial_page
-                .iter()
-                .find(|page| page.is_available(&active_conditions))
-                ?
-                .id
-                .clone()
-                .into()
+            let context = RequirementContext {
+                active_conditions: &active_conditions,
+                journal: &profile.journal,
+                memory: memories.get(dialog_event.source).ok(),
+                dialog_context: &dialog_context,
+            };
+            dialog.initial_page_id(&context, &dialog_event.dialog)
         }).with_context(|| {
             format!(
                 "No valid active page for dialog {dialog:?}. Current conditions: {active_conditions:?}"
             )
         })?;
+        let entered_page = dialog_event.resumed.then(|| current_page.clone());
         commands.insert_resource(CurrentDialog {
             source: dialog_event.source,
             id: dialog_event.dialog.clone(),
             dialog: dialog.clone(),
             current_page,
             last_choice: None,
+            text: String::new(),
+            entered_page,
         });
         actions_frozen.freeze();
     }
@@ -99,21 +185,41 @@ fn show_dialog(
     mut commands: Commands,
     current_dialog: Option<ResMut<CurrentDialog>>,
     active_conditions: Res<ActiveConditions>,
+    profile: Res<PlayerProfile>,
+    memories: Query<&NpcMemory>,
+    dialog_context: Res<DialogContext>,
+    localization: Res<Localization>,
     mut condition_writer: EventWriter<ConditionAddEvent>,
+    mut choice_writer: EventWriter<DialogChoiceEvent>,
     mut egui_contexts: EguiContexts,
     mut actions_frozen: ResMut<ActionsFrozen>,
     actions: Query<&ActionState<PlayerAction>>,
-    time: Res<Time>,
-    mut elapsed_time: Local<f32>,
+    presentation: Res<DialogPresentation>,
     config: Res<GameConfig>,
+    voice_over: Option<Res<VoiceOverPlayback>>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
 ) -> Result<()> {
     let Some(mut current_dialog) = current_dialog else {
-            *elapsed_time = 0.0;
-            return Ok(());
+        return Ok(());
     };
 
+    let requirement_context = RequirementContext {
+        active_conditions: &active_conditions,
+        journal: &profile.journal,
+        memory: memories.get(current_dialog.source).ok(),
+        dialog_context: &dialog_context,
+    };
     for actions in actions.iter() {
         let current_page = current_dialog.fetch_current_page()?;
+        let page_text = current_dialog.text.clone();
+        let voice_over = voice_over
+            .as_ref()
+            .filter(|voice_over| voice_over.page == current_dialog.current_page);
+        let voice_over_progress = voice_over.and_then(|voice_over| voice_over.progress);
+        let should_auto_advance = current_page.auto_advance
+            && voice_over
+                .map(|voice_over| voice_over.finished)
+                .unwrap_or_default();
         get_dialog_window()
             .show(egui_contexts.ctx_mut(), |ui| {
                 // Get current context style
@@ -122,40 +228,56 @@ fn show_dialog(
                 ui.set_width(dialog_size.x);
                 ui.set_height(dialog_size.y);
 
-                let dialog_text = create_dialog_rich_text(&current_page, *elapsed_time, &config);
-                ui.vertical(|ui| {
-                    ui.add_space(5.);
-                    ui.label(&dialog_text);
-                    if dialog_text == current_page.text {
-                        ui.add_space(3.);
-                        ui.separator();
-                        ui.add_space(8.);
-                        present_choices(
-                            ui,
-                            &mut commands,
-                            &mut current_dialog,
-                            &active_conditions,
-                            &mut condition_writer,
-                            &mut actions_frozen,
-                            actions,
-                            current_page.next_page,
-                            &mut elapsed_time,
-                        )
-                        .context("Failed to present dialog choices")?;
+                let dialog_text = create_dialog_rich_text(
+                    &page_text,
+                    current_page.talking_speed,
+                    &presentation,
+                    voice_over_progress,
+                    &config,
+                );
+                ui.horizontal_top(|ui| {
+                    if let Some(texture) = presentation.portrait_texture {
+                        ui.image(texture, egui::Vec2::splat(PORTRAIT_SIZE));
+                        ui.add_space(15.);
                     }
-                    Ok(())
+                    ui.vertical(|ui| {
+                        ui.add_space(5.);
+                        if let Some(speaker) = &current_page.speaker {
+                            ui.label(RichText::new(localization.get(speaker)).strong());
+                            ui.add_space(3.);
+                        }
+                        ui.label(&dialog_text);
+                        if dialog_text == page_text {
+                            ui.add_space(3.);
+                            ui.separator();
+                            ui.add_space(8.);
+                            present_choices(
+                                ui,
+                                &mut commands,
+                                &mut current_dialog,
+                                &requirement_context,
+                                &localization,
+                                &mut condition_writer,
+                                &mut choice_writer,
+                                &mut actions_frozen,
+                                &mut sound_events,
+                                actions,
+                                current_page.next_page,
+                            )
+                            .context("Failed to present dialog choices")?;
+                        }
+                        Ok(())
+                    })
+                    .inner
                 })
                 .inner
             })
             .context("Failed to show dialog window")?
             .inner
             .context("Failed to fetch inner result when showing dialog window")??;
-        let dt_speed_multiplier = if actions.pressed(PlayerAction::SpeedUpDialog) {
-            4.
-        } else {
-            1.
-        };
-        *elapsed_time += time.delta_seconds() * dt_speed_multiplier;
+        if should_auto_advance {
+            current_dialog.auto_advance()?;
+        }
     }
     Ok(())
 }
@@ -164,19 +286,21 @@ fn present_choices(
     ui: &mut egui::Ui,
     commands: &mut Commands,
     current_dialog: &mut CurrentDialog,
-    active_conditions: &ActiveConditions,
+    requirement_context: &RequirementContext,
+    localization: &Localization,
     condition_writer: &mut EventWriter<ConditionAddEvent>,
+    choice_writer: &mut EventWriter<DialogChoiceEvent>,
     actions_frozen: &mut ActionsFrozen,
+    sound_events: &mut EventWriter<PlaySoundEvent>,
     actions: &ActionState<PlayerAction>,
     next_page: NextPage,
-    elapsed_time: &mut f32,
 ) -> Result<()> {
     match next_page {
         NextPage::Continue(next_page_id) => {
-            let text = create_choice_rich_text(0, "Continue");
+            let text = create_choice_rich_text(0, localization.get("dialog.continue"));
             if ui.button(text).clicked() || actions.just_pressed(PlayerAction::numbered_choice(1)) {
                 current_dialog.current_page = next_page_id;
-                *elapsed_time = 0.0;
+                sound_events.send(PlaySoundEvent::new(Sound::DialogAdvance));
             }
         }
         NextPage::Choice(choices) => {
@@ -184,12 +308,12 @@ fn present_choices(
             for (index, (choice_id, choice)) in choices
                 .iter()
                 .filter(|(choice_id, choice)| {
-                    choice.is_available(active_conditions)
-                        && !was_just_picked(current_dialog, choice_id)
+                    choice.is_available(requirement_context, &current_dialog.id)
+                        && !current_dialog.was_just_picked(choice_id)
                 })
                 .enumerate()
             {
-                let text = create_choice_rich_text(index, &choice.text);
+                let text = create_choice_rich_text(index, localization.get(&choice.text));
                 if ui.button(&text).clicked()
                     || actions.just_pressed(PlayerAction::numbered_choice(index as u8 + 1))
                 {
@@ -198,9 +322,15 @@ fn present_choices(
             }
             if let Some((choice_id, choice)) = picked_choice {
                 condition_writer.send(ConditionAddEvent(choice_id.clone()));
+                choice_writer.send(DialogChoiceEvent {
+                    dialog: current_dialog.id.clone(),
+                    page: current_dialog.current_page.clone(),
+                    choice: choice_id.clone(),
+                    text: localization.get(&choice.text).to_owned(),
+                });
                 current_dialog.last_choice = Some(choice_id);
                 current_dialog.current_page = choice.next_page_id;
-                *elapsed_time = 0.0;
+                sound_events.send(PlaySoundEvent::new(Sound::DialogAdvance));
             }
         }
         NextPage::SameAs(other_page_id) => {
@@ -209,19 +339,22 @@ fn present_choices(
                 ui,
                 commands,
                 current_dialog,
-                active_conditions,
+                requirement_context,
+                localization,
                 condition_writer,
+                choice_writer,
                 actions_frozen,
+                sound_events,
                 actions,
                 next_page,
-                elapsed_time,
             )?;
         }
         NextPage::Exit => {
-            let text = create_choice_rich_text(0, "Exit");
+            let text = create_choice_rich_text(0, localization.get("dialog.exit"));
             if ui.button(text).clicked() || actions.just_pressed(PlayerAction::numbered_choice(1)) {
                 commands.remove_resource::<CurrentDialog>();
                 actions_frozen.unfreeze();
+                sound_events.send(PlaySoundEvent::new(Sound::DialogAdvance));
             }
         }
     }
@@ -252,20 +385,53 @@ fn set_dialog_style(style: &mut egui::Style) {
     style.visuals.widgets.noninteractive.fg_stroke.color = egui::Color32::from_gray(250);
 }
 
-fn create_dialog_rich_text(page: &Page, elapsed_time: f32, config: &GameConfig) -> String {
-    let base_letters_per_second = config.dialog.base_letters_per_second;
-    let letters_to_display = (base_letters_per_second * page.talking_speed * elapsed_time) as usize;
-    page.text.graphemes(true).take(letters_to_display).collect()
+#[sysfail(log(level = "error"))]
+fn play_page_emotes(
+    current_dialog: Option<Res<CurrentDialog>>,
+    mut last_page: Local<Option<(DialogId, PageId)>>,
+    mut emote_events: EventWriter<EmoteEvent>,
+) -> Result<()> {
+    let Some(current_dialog) = current_dialog else {
+        *last_page = None;
+        return Ok(());
+    };
+    let page = (
+        current_dialog.id.clone(),
+        current_dialog.current_page.clone(),
+    );
+    if last_page.as_ref() == Some(&page) {
+        return Ok(());
+    }
+    *last_page = Some(page);
+    if let Some(emote) = current_dialog.fetch_current_page()?.emote {
+        emote_events.send(EmoteEvent {
+            entity: current_dialog.source,
+            emote,
+        });
+    }
+    Ok(())
 }
 
-fn create_choice_rich_text(index: usize, text: &str) -> String {
-    format!("{}. {}", index + 1, text)
+fn create_dialog_rich_text(
+    text: &str,
+    talking_speed: f32,
+    presentation: &DialogPresentation,
+    voice_over_progress: Option<f32>,
+    config: &GameConfig,
+) -> String {
+    if presentation.skipped {
+        return text.to_owned();
+    }
+    let letters_to_display = match voice_over_progress {
+        Some(progress) => (text.graphemes(true).count() as f32 * progress).ceil() as usize,
+        None => {
+            let base_letters_per_second = config.dialog.base_letters_per_second;
+            (base_letters_per_second * talking_speed * presentation.elapsed) as usize
+        }
+    };
+    text.graphemes(true).take(letters_to_display).collect()
 }
 
-fn was_just_picked(current_dialog: &CurrentDialog, choice_id: &ConditionId) -> bool {
-    current_dialog
-        .last_choice
-        .as_ref()
-        .map(|id| id == choice_id)
-        .unwrap_or_default()
+fn create_choice_rich_text(index: usize, text: &str) -> String {
+    format!("{}. {}", index + 1, text)
 }
@@ -0,0 +1,60 @@
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::world_interaction::dialog::{CurrentDialog, DialogChoiceEvent, JournalChoice};
+use bevy::prelude::*;
+
+pub(crate) fn record_visited_pages(
+    current_dialog: Option<Res<CurrentDialog>>,
+    names: Query<&Name>,
+    mut profile: ResMut<PlayerProfile>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("record_visited_pages").entered();
+    let Some(current_dialog) = current_dialog else {
+        return;
+    };
+    // Only borrow the profile mutably when something changes, since every change is written to disk
+    if profile
+        .journal
+        .has_seen(&current_dialog.id, &current_dialog.current_page)
+    {
+        return;
+    }
+    let entry = profile
+        .journal
+        .0
+        .entry(current_dialog.id.clone())
+        .or_default();
+    entry
+        .visited_pages
+        .push(current_dialog.current_page.clone());
+    if let Ok(name) = names.get(current_dialog.source) {
+        entry.speaker = Some(name.to_string());
+    }
+}
+
+pub(crate) fn record_choices(
+    mut choice_events: EventReader<DialogChoiceEvent>,
+    mut profile: ResMut<PlayerProfile>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("record_choices").entered();
+    for DialogChoiceEvent {
+        dialog,
+        page,
+        choice,
+        text,
+    } in choice_events.iter()
+    {
+        profile
+            .journal
+            .0
+            .entry(dialog.clone())
+            .or_default()
+            .choices
+            .push(JournalChoice {
+                page: page.clone(),
+                choice: choice.clone(),
+                text: text.clone(),
+            });
+    }
+}
@@ -0,0 +1,94 @@
+use crate::game_settings::GameSettings;
+use crate::ingame_menu::Paused;
+use crate::player_control::actions::PlayerAction;
+use crate::world_interaction::dialog::{CurrentDialog, DialogId, PageId};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use leafwing_input_manager::prelude::ActionState;
+
+/// How far the line of the current page has been revealed. Reset whenever a new page is shown.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct DialogPresentation {
+    pub page: Option<(DialogId, PageId)>,
+    /// Seconds the line has been revealed for, scaled by the text speed of the [`GameSettings`]
+    /// and sped up while [`PlayerAction::SpeedUpDialog`] is held
+    pub elapsed: f32,
+    /// Whether the player skipped to the full line with [`PlayerAction::SkipDialogLine`]
+    pub skipped: bool,
+    pub portrait: Option<Handle<Image>>,
+    /// Set once the portrait is loaded and registered with egui
+    pub portrait_texture: Option<egui::TextureId>,
+}
+
+pub(crate) fn update_dialog_presentation(
+    current_dialog: Option<Res<CurrentDialog>>,
+    mut presentation: ResMut<DialogPresentation>,
+    settings: Res<GameSettings>,
+    time: Res<Time>,
+    actions: Query<&ActionState<PlayerAction>>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    mut egui_contexts: EguiContexts,
+    paused: Option<Res<Paused>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_dialog_presentation").entered();
+    let page_ref = current_dialog.as_ref().map(|current_dialog| {
+        (
+            current_dialog.id.clone(),
+            current_dialog.current_page.clone(),
+        )
+    });
+    if page_ref != presentation.page {
+        let portrait: Option<Handle<Image>> = current_dialog
+            .as_ref()
+            .and_then(|current_dialog| current_dialog.fetch_current_page().ok())
+            .and_then(|page| page.portrait)
+            .map(|path| asset_server.load(path));
+        // Consecutive pages of the same speaker usually share their portrait
+        let portrait_texture = if portrait == presentation.portrait {
+            presentation.portrait_texture
+        } else {
+            if let Some(previous) = &presentation.portrait {
+                egui_contexts.remove_image(previous);
+            }
+            None
+        };
+        // The line of a new page starts hidden even if the previous one was skipped
+        *presentation = DialogPresentation {
+            page: page_ref,
+            elapsed: 0.,
+            skipped: false,
+            portrait,
+            portrait_texture,
+        };
+        // Input is read from the next frame on, so the input that opened the dialog or picked the previous choice
+        // does not skip the new line right away
+        return;
+    }
+    if presentation.page.is_none() {
+        return;
+    }
+    if presentation.portrait_texture.is_none() {
+        if let Some(portrait) = presentation.portrait.clone() {
+            if images.contains(&portrait) {
+                presentation.portrait_texture = Some(egui_contexts.add_image(portrait));
+            }
+        }
+    }
+    // The line neither reveals nor can be skipped behind the pause menu
+    if paused.is_some() {
+        return;
+    }
+    for actions in actions.iter() {
+        if actions.just_pressed(PlayerAction::SkipDialogLine) {
+            presentation.skipped = true;
+        }
+        let speed_multiplier = if actions.pressed(PlayerAction::SpeedUpDialog) {
+            4.
+        } else {
+            1.
+        };
+        presentation.elapsed += time.delta_seconds() * speed_multiplier * settings.text_speed;
+    }
+}
@@ -0,0 +1,96 @@
+use crate::file_system_interaction::audio::DialogAudio;
+use crate::world_interaction::dialog::{CurrentDialog, DialogId, PageId};
+use anyhow::Result;
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+use bevy_mod_sysfail::macros::*;
+
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct VoiceOverPlayback {
+    pub dialog: DialogId,
+    pub page: PageId,
+    pub source: Handle<AudioSource>,
+    pub instance: Handle<AudioInstance>,
+    /// Fraction of the clip that has been played, in `[0, 1]`. `None` while the clip is still loading.
+    pub progress: Option<f32>,
+    pub finished: bool,
+}
+
+#[sysfail(log(level = "error"))]
+pub(crate) fn play_voice_over(
+    mut commands: Commands,
+    current_dialog: Option<Res<CurrentDialog>>,
+    playback: Option<Res<VoiceOverPlayback>>,
+    dialog_audio: Res<AudioChannel<DialogAudio>>,
+    asset_server: Res<AssetServer>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("play_voice_over").entered();
+    let Some(current_dialog) = current_dialog else {
+        if playback.is_some() {
+            dialog_audio.stop();
+            commands.remove_resource::<VoiceOverPlayback>();
+        }
+        return Ok(());
+    };
+    let is_playing_current_page = playback
+        .as_ref()
+        .map(|playback| {
+            playback.dialog == current_dialog.id && playback.page == current_dialog.current_page
+        })
+        .unwrap_or_default();
+    if is_playing_current_page {
+        return Ok(());
+    }
+
+    if playback.is_some() {
+        dialog_audio.stop();
+        commands.remove_resource::<VoiceOverPlayback>();
+    }
+    if let Some(path) = current_dialog.fetch_current_page()?.voice_over {
+        let source = asset_server.load(path);
+        let instance = dialog_audio.play(source.clone()).handle();
+        commands.insert_resource(VoiceOverPlayback {
+            dialog: current_dialog.id.clone(),
+            page: current_dialog.current_page.clone(),
+            source,
+            instance,
+            progress: None,
+            finished: false,
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn update_voice_over_progress(
+    playback: Option<ResMut<VoiceOverPlayback>>,
+    dialog_audio: Res<AudioChannel<DialogAudio>>,
+    audio_sources: Res<Assets<AudioSource>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_voice_over_progress").entered();
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if playback.finished {
+        return;
+    }
+    let Some(duration) = audio_sources
+        .get(&playback.source)
+        .map(|source| source.sound.duration().as_secs_f64())
+    else {
+        return;
+    };
+    match dialog_audio.state(&playback.instance) {
+        PlaybackState::Queued => {}
+        PlaybackState::Stopped => {
+            playback.progress = Some(1.0);
+            playback.finished = true;
+        }
+        state => {
+            if let Some(position) = state.position() {
+                playback.progress = Some((position / duration.max(1e-5)).clamp(0.0, 1.0) as f32);
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crate::file_system_interaction::config::GameConfig;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::behavior::{Behavior, NpcDamaged};
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::level_stats::PlayerDied;
+use crate::world_interaction::volume::{Volume, VolumeKind};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the damage a hazard deals per hit, e.g. `2`.
+pub const DAMAGE_KEY: &str = "damage";
+
+/// Handles the [`Health`] of the player and other characters. Colliders with [`Damage`], e.g. hazard objects
+/// and volumes of the kind [`VolumeKind::Hazard`], hurt everything with health that touches them by sending [`DamageEvent`]s.
+/// After being hurt, a character is [`Invulnerable`] for a while, so touching spikes or standing in lava drains health at a steady pace.
+/// When the player's health runs out, a [`PlayerDied`] is sent, which respawns them at the last
+/// [`Checkpoint`](crate::world_interaction::checkpoint::Checkpoint) with full health.
+/// Other characters are despawned when they die.
+pub fn health_plugin(app: &mut App) {
+    app.register_type::<Health>()
+        .register_type::<Damage>()
+        .register_type::<Invulnerable>()
+        .add_event::<DamageEvent>()
+        .add_systems(
+            (
+                read_damage_metadata,
+                damage_on_contact,
+                apply_damage,
+                tick_invulnerability,
+                show_health_bar,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0. {
+            (self.current / self.max).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new(1.)
+    }
+}
+
+/// Hurts everything with [`Health`] that intersects this entity's sensor collider.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Damage {
+    /// Health taken per hit
+    pub amount: f32,
+}
+
+impl Default for Damage {
+    fn default() -> Self {
+        Self { amount: 1. }
+    }
+}
+
+/// Present while a character ignores damage after being hurt or respawning.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Invulnerable {
+    /// Time in s until the character can be hurt again
+    pub remaining: f32,
+}
+
+/// Send this to hurt an entity with [`Health`]. It is ignored while the target is [`Invulnerable`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: f32,
+    /// The hazard or character that caused the damage
+    pub source: Option<Entity>,
+}
+
+#[sysfail(log(level = "error"))]
+fn read_damage_metadata(
+    mut commands: Commands,
+    objects: Query<
+        (Entity, &ObjectMetadata, Option<&Damage>, Option<&Volume>),
+        Or<(Changed<ObjectMetadata>, Changed<Volume>)>,
+    >,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_damage_metadata").entered();
+    for (entity, metadata, damage, volume) in objects.iter() {
+        let is_volume = volume.is_some();
+        let is_hazard_volume = volume.map_or(false, |volume| volume.kind == VolumeKind::Hazard);
+        if is_volume && !is_hazard_volume {
+            if damage.is_some() {
+                commands.entity(entity).remove::<Damage>();
+            }
+            continue;
+        }
+        let Some(mut read) = damage.copied().or(is_hazard_volume.then(default)) else {
+            continue;
+        };
+        if let Some(amount) = metadata.get(DAMAGE_KEY) {
+            read.amount = amount
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse damage \"{amount}\""))?;
+        }
+        if damage != Some(&read) {
+            commands.entity(entity).insert(read);
+        }
+    }
+    Ok(())
+}
+
+fn damage_on_contact(
+    rapier_context: Res<RapierContext>,
+    hazards: Query<(Entity, &Damage)>,
+    targets: Query<(), With<Health>>,
+    parents: Query<&Parent>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("damage_on_contact").entered();
+    for (hazard, damage) in hazards.iter() {
+        for (collider_a, collider_b, intersecting) in rapier_context.intersections_with(hazard) {
+            if !intersecting {
+                continue;
+            }
+            let other = if collider_a == hazard {
+                collider_b
+            } else {
+                collider_a
+            };
+            // Characters may be made of several colliders below the entity that has the health
+            let Some(target) = std::iter::once(other)
+                .chain(parents.iter_ancestors(other))
+                .find(|entity| targets.contains(*entity))
+            else {
+                continue;
+            };
+            damage_events.send(DamageEvent {
+                target,
+                amount: damage.amount,
+                source: Some(hazard),
+            });
+        }
+    }
+}
+
+fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut targets: Query<(&mut Health, Option<&Invulnerable>)>,
+    players: Query<(), With<Player>>,
+    npcs: Query<(), With<Behavior>>,
+    mut npc_damaged_events: EventWriter<NpcDamaged>,
+    mut player_died_events: EventWriter<PlayerDied>,
+    config: Res<GameConfig>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_damage").entered();
+    // The invulnerability is only inserted at the end of the frame, so several hits in one frame are caught here
+    let mut hurt = HashSet::new();
+    for event in damage_events.iter() {
+        let Ok((mut health, invulnerable)) = targets.get_mut(event.target) else {
+            continue;
+        };
+        if invulnerable.is_some() || health.is_dead() || !hurt.insert(event.target) {
+            continue;
+        }
+        health.current = (health.current - event.amount).max(0.);
+        commands.entity(event.target).insert(Invulnerable {
+            remaining: config.health.invulnerability_duration,
+        });
+        if npcs.contains(event.target) {
+            npc_damaged_events.send(NpcDamaged { npc: event.target });
+        }
+        if !health.is_dead() {
+            continue;
+        }
+        if players.contains(event.target) {
+            info!("Player died");
+            player_died_events.send(PlayerDied {
+                player: event.target,
+            });
+        } else {
+            commands.entity(event.target).despawn_recursive();
+        }
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut invulnerables: Query<(Entity, &mut Invulnerable)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("tick_invulnerability").entered();
+    for (entity, mut invulnerable) in invulnerables.iter_mut() {
+        invulnerable.remaining -= time.delta_seconds();
+        if invulnerable.remaining <= 0. {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+fn show_health_bar(
+    players: Query<(&Health, Option<&Invulnerable>), With<Player>>,
+    time: Res<Time>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_health_bar").entered();
+    let Some((health, invulnerable)) = players.iter().next() else {
+        return;
+    };
+    // Flicker while invulnerable so that getting hurt is noticeable
+    let flicker = invulnerable.is_some() && (time.elapsed_seconds() * 10.).sin() > 0.;
+    let color = if flicker {
+        egui::Color32::WHITE
+    } else {
+        egui::Color32::from_rgb(200, 40, 40)
+    };
+    egui::Area::new("Health Bar")
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(20., 20.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.add(
+                egui::ProgressBar::new(health.fraction())
+                    .desired_width(200.)
+                    .fill(color)
+                    .text(format!("{:.0} / {:.0}", health.current, health.max)),
+            );
+        });
+}
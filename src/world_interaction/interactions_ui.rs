@@ -1,8 +1,13 @@
+use crate::file_system_interaction::localization::Localization;
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
 use crate::player_control::actions::PlayerAction;
 use crate::player_control::camera::{IngameCamera, IngameCameraKind};
+use crate::player_control::input_bindings::{BindableAction, InputBindings};
 use crate::player_control::player_embodiment::Player;
 use crate::util::criteria::is_frozen;
-use crate::world_interaction::dialog::{DialogEvent, DialogTarget};
+use crate::world_interaction::carrying::{Carryable, Carrying};
+use crate::world_interaction::rope::{Grabbing, RopeSegment};
+use crate::world_interaction::zipline::{ZiplinePartner, Ziplining};
 use crate::GameState;
 use anyhow::{Context, Result};
 use bevy::prelude::*;
@@ -15,11 +20,22 @@ use leafwing_input_manager::prelude::ActionState;
 use serde::{Deserialize, Serialize};
 use std::f32::consts::TAU;
 
+/// Lets the player interact with objects near them that they are facing.
+/// Objects become interactable through an [`Interactable`], which gets a sensor of the given radius and shows
+/// its prompt above the object together with the key bound to the interact action. Interacting sends an [`InteractionEvent`]
+/// that dialogs, levers, item pickups and the like react to.
+/// Carryable objects, rope segments and ziplines predate this and still handle the interact action themselves.
 pub fn interactions_ui_plugin(app: &mut App) {
     app.register_type::<InteractionOpportunities>()
+        .register_type::<Interactable>()
         .init_resource::<InteractionOpportunities>()
+        .add_event::<InteractionEvent>()
         .add_systems(
-            (update_interaction_opportunities, update_interaction_ui)
+            (
+                add_interaction_sensors,
+                update_interaction_opportunities,
+                update_interaction_ui,
+            )
                 .chain()
                 .in_set(OnUpdate(GameState::Playing)),
         )
@@ -30,15 +46,102 @@ pub fn interactions_ui_plugin(app: &mut App) {
         );
 }
 
+/// Prompt height above the origin of an [`Interactable`] if nothing else is set.
+const DEFAULT_PROMPT_HEIGHT: f32 = 1.0;
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Interactable {
+    /// Localization key of what interacting does, e.g. `interaction.talk`.
+    pub prompt: String,
+    /// Radius in meters of the sensor the player needs to be in to interact.
+    pub radius: f32,
+    /// Height above the object's origin at which the prompt is shown.
+    pub prompt_height: f32,
+    /// Height in meters of the sensor, which makes it a cylinder around the object's origin instead of a ball.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensor_height: Option<f32>,
+}
+
+impl Default for Interactable {
+    fn default() -> Self {
+        Self::new("interaction.use", 1.0)
+    }
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>, radius: f32) -> Self {
+        Self {
+            prompt: prompt.into(),
+            radius,
+            prompt_height: DEFAULT_PROMPT_HEIGHT,
+            sensor_height: None,
+        }
+    }
+
+    /// Makes the sensor a cylinder of the given height, e.g. so that characters cannot be talked to from above.
+    pub fn with_sensor_height(mut self, height: f32) -> Self {
+        self.sensor_height = Some(height);
+        self
+    }
+}
+
+/// Sent when the player interacts with an [`Interactable`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InteractionEvent {
+    pub player: Entity,
+    pub target: Entity,
+}
+
+/// Child of an [`Interactable`] holding its sensor.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Component)]
+struct InteractionSensor;
+
 #[derive(Resource, Debug)]
 pub struct InteractionUi {
-    source: Entity,
+    pub(crate) source: Entity,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
 #[reflect(Resource, Serialize, Deserialize)]
 pub struct InteractionOpportunities(pub HashSet<Entity>);
 
+fn add_interaction_sensors(
+    mut commands: Commands,
+    interactables: Query<(Entity, &Interactable, Option<&Children>), Changed<Interactable>>,
+    sensors: Query<(), With<InteractionSensor>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("add_interaction_sensors").entered();
+    for (entity, interactable, children) in interactables.iter() {
+        // Replace the sensor so a changed radius takes effect
+        for child in children.into_iter().flatten() {
+            if sensors.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Name::new("Interaction Sensor"),
+                InteractionSensor,
+                TransformBundle::default(),
+                match interactable.sensor_height {
+                    Some(height) => Collider::cylinder(height / 2., interactable.radius),
+                    None => Collider::ball(interactable.radius),
+                },
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                // Interactables can be dynamic bodies themselves, e.g. characters
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::DYNAMIC_DYNAMIC,
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    GameCollisionGroup::PLAYER.into(),
+                ),
+            ));
+        });
+    }
+}
+
 fn update_interaction_opportunities(
     mut collision_events: EventReader<CollisionEvent>,
     player_query: Query<Entity, With<Player>>,
@@ -65,10 +168,11 @@ fn update_interaction_opportunities(
 fn update_interaction_ui(
     mut commands: Commands,
     interaction_ui: Option<ResMut<InteractionUi>>,
-    non_player_query: Query<&Transform, (Without<Player>, Without<IngameCamera>)>,
-    player_query: Query<&Transform, (With<Player>, Without<IngameCamera>)>,
+    // Global, as interactables can be nested, e.g. levers in a puzzle
+    non_player_query: Query<&GlobalTransform, (Without<Player>, Without<IngameCamera>)>,
+    player_query: Query<&GlobalTransform, (With<Player>, Without<IngameCamera>)>,
     interaction_opportunities: Res<InteractionOpportunities>,
-    camera_query: Query<(&IngameCamera, &Transform), Without<Player>>,
+    camera_query: Query<(&IngameCamera, &GlobalTransform), Without<Player>>,
 ) -> Result<()> {
     let mut valid_target = None;
     for entity in interaction_opportunities.0.iter() {
@@ -77,12 +181,8 @@ fn update_interaction_ui(
             .context("Failed to get transform of interaction target")?;
         for player_transform in player_query.iter() {
             for (camera, camera_transform) in camera_query.iter() {
-                let is_facing_target = is_facing_target(
-                    *player_transform,
-                    *target_transform,
-                    *camera_transform,
-                    camera,
-                );
+                let is_facing_target =
+                    is_facing_target(player_transform, target_transform, camera_transform, camera);
                 if is_facing_target {
                     valid_target = Some(*entity);
                     break;
@@ -138,16 +238,19 @@ fn determine_player_and_target(
 }
 
 fn is_facing_target(
-    player_transform: Transform,
-    target_transform: Transform,
-    camera_transform: Transform,
+    player_transform: &GlobalTransform,
+    target_transform: &GlobalTransform,
+    camera_transform: &GlobalTransform,
     camera: &IngameCamera,
 ) -> bool {
-    if camera.kind == IngameCameraKind::FixedAngle {
+    if matches!(
+        camera.kind,
+        IngameCameraKind::FixedAngle | IngameCameraKind::SideView
+    ) {
         return true;
     }
     let camera_to_player = camera_transform.forward();
-    let player_to_target = target_transform.translation - player_transform.translation;
+    let player_to_target = target_transform.translation() - player_transform.translation();
     let angle = camera_to_player.angle_between(player_to_target);
     angle < TAU / 8.
 }
@@ -155,32 +258,79 @@ fn is_facing_target(
 #[sysfail(log(level = "error"))]
 fn display_interaction_prompt(
     interaction_ui: Res<InteractionUi>,
-    mut dialog_event_writer: EventWriter<DialogEvent>,
+    mut interaction_events: EventWriter<InteractionEvent>,
     mut egui_contexts: EguiContexts,
-    actions: Query<&ActionState<PlayerAction>>,
+    localization: Res<Localization>,
+    input_bindings: Res<InputBindings>,
+    players: Query<(
+        Entity,
+        &ActionState<PlayerAction>,
+        Option<&Carrying>,
+        Option<&Grabbing>,
+        Option<&Ziplining>,
+    )>,
     primary_windows: Query<&Window, With<PrimaryWindow>>,
-    dialog_target_query: Query<&DialogTarget>,
+    cameras: Query<(&Camera, &GlobalTransform), With<IngameCamera>>,
+    targets: Query<(&GlobalTransform, Option<&Interactable>)>,
+    carryable_query: Query<(), With<Carryable>>,
+    rope_segment_query: Query<(), With<RopeSegment>>,
+    zipline_query: Query<(), With<ZiplinePartner>>,
 ) -> Result<()> {
-    for actions in actions.iter() {
+    let target = interaction_ui.source;
+    let (target_transform, interactable) = targets
+        .get(target)
+        .context("Failed to get interaction target")?;
+    for (player, actions, carrying, grabbing, ziplining) in players.iter() {
+        if carrying.is_some() || grabbing.is_some() || ziplining.is_some() {
+            continue;
+        }
+        let (prompt, prompt_height) = if let Some(interactable) = interactable {
+            (interactable.prompt.as_str(), interactable.prompt_height)
+        } else if carryable_query.contains(target) {
+            ("interaction.pick_up", DEFAULT_PROMPT_HEIGHT)
+        } else if rope_segment_query.contains(target) {
+            ("interaction.grab", DEFAULT_PROMPT_HEIGHT)
+        } else if zipline_query.contains(target) {
+            ("interaction.zipline", DEFAULT_PROMPT_HEIGHT)
+        } else {
+            // E.g. a teleporter, which is used by just stepping on it
+            continue;
+        };
         let window = primary_windows
             .get_single()
             .context("Failed to get primary window")?;
-        egui::Window::new("Interaction")
-            .collapsible(false)
-            .title_bar(false)
-            .auto_sized()
-            .fixed_pos(egui::Pos2::new(window.width() / 2., window.height() / 2.))
+        let prompt_position = target_transform.translation() + Vec3::Y * prompt_height;
+        // Viewport coordinates start at the bottom while egui's start at the top
+        let screen_position = cameras
+            .iter()
+            .find_map(|(camera, camera_transform)| {
+                camera.world_to_viewport(camera_transform, prompt_position)
+            })
+            .map(|viewport| egui::Pos2::new(viewport.x, window.height() - viewport.y));
+        let Some(screen_position) = screen_position else {
+            // The target is behind the camera, but it can still be interacted with
+            continue;
+        };
+        let prompt = localization.get(prompt);
+        let text = match input_bindings
+            .bindings
+            .get(&BindableAction::Interact)
+            .and_then(|bindings| bindings.first())
+        {
+            Some(binding) => format!("{binding}: {prompt}"),
+            None => prompt.to_owned(),
+        };
+        egui::Area::new("Interaction")
+            .fixed_pos(screen_position)
+            .pivot(egui::Align2::CENTER_BOTTOM)
+            .interactable(false)
             .show(egui_contexts.ctx_mut(), |ui| {
-                ui.label("E: Talk");
-            });
-        if actions.just_pressed(PlayerAction::Interact) {
-            if let Ok(dialog_target) = dialog_target_query.get(interaction_ui.source) {
-                dialog_event_writer.send(DialogEvent {
-                    source: interaction_ui.source,
-                    dialog: dialog_target.dialog_id.clone(),
-                    page: None,
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(text);
                 });
-            }
+            });
+        if interactable.is_some() && actions.just_pressed(PlayerAction::Interact) {
+            interaction_events.send(InteractionEvent { player, target });
         }
     }
     Ok(())
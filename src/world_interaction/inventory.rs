@@ -0,0 +1,342 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::audio::{PlaySoundEvent, Sound};
+use crate::file_system_interaction::localization::Localization;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::player_control::actions::{ActionsFrozen, PlayerAction};
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::dialog::DialogContext;
+use crate::world_interaction::interactions_ui::{Interactable, InteractionEvent};
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of the item an [`Item`] object stands for, e.g. `key_red`.
+pub const ITEM_KEY: &str = "item";
+/// Metadata key of how many of the item an [`Item`] object gives.
+pub const ITEM_AMOUNT_KEY: &str = "amount";
+/// Metadata key that makes an [`Item`] object wait for the player to interact with it when set to `interact`
+/// instead of being picked up on touch.
+pub const ITEM_PICKUP_KEY: &str = "pickup";
+
+/// Distance in meters from which items that need an interaction can be picked up.
+const INTERACTION_RADIUS: f32 = 1.0;
+
+/// Handles the items the player carries in their [`Inventory`].
+/// Items lie around in levels as [`Item`] objects, which are picked up by touching their sensor and send an [`ItemPickedUp`].
+/// Items can instead be set to be an [`Interactable`] that is only picked up when interacted with.
+/// The inventory panel, toggled with the inventory action, lists the held items by their localized name `item.<id>`
+/// and lets the player use them, which consumes one and sends an [`ItemUsed`] for other systems to react to.
+/// It also opens the crafting window of the [`crafting_plugin`](crate::world_interaction::crafting::crafting_plugin).
+///
+/// The inventory is mirrored into the items of the [`DialogContext`], so dialog conditions and quest stages can check
+/// what the player holds and dialog effects and quest rewards can give and take items. As the context is kept between
+/// levels and stored in save games, a respawned player gets their items back from it.
+pub fn inventory_plugin(app: &mut App) {
+    app.register_type::<Item>()
+        .register_type::<Inventory>()
+        .init_resource::<InventoryPanel>()
+        .add_event::<ItemPickedUp>()
+        .add_event::<ItemUsed>()
+        .add_systems(
+            (
+                read_item_metadata,
+                pick_up_items,
+                sync_inventory_with_dialog_context,
+                show_inventory_panel,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "give",
+            "Gives the player an item, e.g. \"give gem 3\"",
+            run_give_command,
+        );
+}
+
+/// An object in the world that gives `amount` of the item `id` when picked up.
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub amount: u32,
+}
+
+impl Default for Item {
+    fn default() -> Self {
+        Self {
+            id: "gem".to_owned(),
+            amount: 1,
+        }
+    }
+}
+
+/// Number of each item the player holds. Items that run out are removed.
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Inventory {
+    pub items: HashMap<String, u32>,
+}
+
+impl Inventory {
+    pub fn count(&self, item: &str) -> u32 {
+        self.items.get(item).copied().unwrap_or_default()
+    }
+
+    pub fn add(&mut self, item: impl Into<String>, amount: u32) {
+        if amount > 0 {
+            *self.items.entry(item.into()).or_default() += amount;
+        }
+    }
+
+    /// Removes up to `amount` of the item and returns how many were actually removed.
+    pub fn remove(&mut self, item: &str, amount: u32) -> u32 {
+        let Some(count) = self.items.get_mut(item) else {
+            return 0;
+        };
+        let removed = amount.min(*count);
+        *count -= removed;
+        if *count == 0 {
+            self.items.remove(item);
+        }
+        removed
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Default)]
+pub struct InventoryPanel {
+    pub open: bool,
+    /// Whether the crafting window is shown next to the panel
+    pub crafting: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemPickedUp {
+    pub player: Entity,
+    pub item: String,
+    pub amount: u32,
+}
+
+/// Sent after one of the item was removed from the player's [`Inventory`].
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ItemUsed {
+    pub player: Entity,
+    pub item: String,
+}
+
+#[sysfail(log(level = "error"))]
+fn read_item_metadata(
+    mut commands: Commands,
+    mut items: Query<(Entity, &ObjectMetadata, &mut Item), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_item_metadata").entered();
+    for (entity, metadata, mut item) in items.iter_mut() {
+        if let Some(id) = metadata.get(ITEM_KEY) {
+            item.id = id.trim().to_owned();
+        }
+        if let Some(amount) = metadata.get(ITEM_AMOUNT_KEY) {
+            item.amount = amount
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse item amount \"{amount}\""))?;
+        }
+        match metadata.get(ITEM_PICKUP_KEY).map(str::trim) {
+            None | Some("touch") => {
+                commands.entity(entity).remove::<Interactable>();
+            }
+            Some("interact") => {
+                commands
+                    .entity(entity)
+                    .insert(Interactable::new("interaction.pick_up", INTERACTION_RADIUS));
+            }
+            Some(pickup) => bail!("Unknown item pickup \"{pickup}\", expected touch or interact"),
+        }
+    }
+    Ok(())
+}
+
+fn pick_up_items(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut players: Query<&mut Inventory, With<Player>>,
+    items: Query<(&Item, Option<&Interactable>)>,
+    parents: Query<&Parent>,
+    mut picked_up_events: EventWriter<ItemPickedUp>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("pick_up_items").entered();
+    let touched = collision_events.iter().filter_map(|event| {
+        let CollisionEvent::Started(entity_a, entity_b, _) = event else {
+            return None;
+        };
+        let (player, collider) = if players.contains(*entity_a) {
+            (*entity_a, *entity_b)
+        } else if players.contains(*entity_b) {
+            (*entity_b, *entity_a)
+        } else {
+            return None;
+        };
+        let entity = parents
+            .get(collider)
+            .map(|parent| parent.get())
+            .unwrap_or(collider);
+        Some((player, entity, false))
+    });
+    let interacted = interaction_events
+        .iter()
+        .map(|event| (event.player, event.target, true));
+    let pickups: Vec<_> = touched.chain(interacted).collect();
+    // An item touching the player with multiple colliders must only be picked up once
+    let mut picked_up = HashSet::new();
+    for (player, entity, interacted) in pickups {
+        let Ok((item, interactable)) = items.get(entity) else {
+            continue;
+        };
+        if interactable.is_some() != interacted {
+            continue;
+        }
+        if !picked_up.insert(entity) {
+            continue;
+        }
+        let Ok(mut inventory) = players.get_mut(player) else {
+            continue;
+        };
+        inventory.add(item.id.clone(), item.amount);
+        commands.entity(entity).despawn_recursive();
+        // The item is gone, so the sound comes from whoever picked it up
+        sound_events.send(PlaySoundEvent::at(Sound::ItemPickup, player));
+        picked_up_events.send(ItemPickedUp {
+            player,
+            item: item.id.clone(),
+            amount: item.amount,
+        });
+    }
+}
+
+/// Keeps the items of the [`DialogContext`] equal to the player's [`Inventory`].
+fn sync_inventory_with_dialog_context(
+    mut players: Query<&mut Inventory, With<Player>>,
+    mut context: ResMut<DialogContext>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("sync_inventory_with_dialog_context").entered();
+    for mut inventory in players.iter_mut() {
+        if inventory.is_added() {
+            // The player was respawned, e.g. after loading a save game or changing levels
+            inventory.items = context.items.clone();
+        } else if inventory.is_changed() {
+            if context.items != inventory.items {
+                context.items = inventory.items.clone();
+            }
+        } else if context.is_changed() && context.items != inventory.items {
+            // Dialog effects and quest rewards give and take items through the context
+            inventory.items = context.items.clone();
+        }
+    }
+}
+
+fn show_inventory_panel(
+    mut players: Query<(Entity, &ActionState<PlayerAction>, &mut Inventory), With<Player>>,
+    mut panel: ResMut<InventoryPanel>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    localization: Res<Localization>,
+    mut used_events: EventWriter<ItemUsed>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_inventory_panel").entered();
+    let Some((player, actions, mut inventory)) = players.iter_mut().next() else {
+        return;
+    };
+    let toggled = actions.just_pressed(PlayerAction::ToggleInventory);
+    if !panel.open {
+        // Opening the panel frees the cursor so the items can be clicked
+        if toggled && !actions_frozen.is_frozen() {
+            panel.open = true;
+            actions_frozen.freeze();
+        }
+        return;
+    }
+    if toggled {
+        close(&mut panel, &mut actions_frozen);
+        return;
+    }
+
+    let mut items: Vec<_> = inventory
+        .items
+        .iter()
+        .map(|(item, count)| (item.clone(), *count))
+        .collect();
+    items.sort();
+    let mut used = None;
+    let mut closed = false;
+    egui::Window::new("Inventory")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_CENTER, egui::Vec2::new(-10., 0.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if items.is_empty() {
+                ui.label("Empty");
+            }
+            egui::Grid::new("inventory_items").show(ui, |ui| {
+                for (item, count) in &items {
+                    ui.label(localization.get(&format!("item.{item}")));
+                    ui.label(format!("x{count}"));
+                    if ui.button("Use").clicked() {
+                        used = Some(item.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Crafting").clicked() {
+                    panel.crafting = !panel.crafting;
+                }
+                closed = ui.button("Close").clicked();
+            });
+        });
+    if let Some(item) = used {
+        if inventory.remove(&item, 1) > 0 {
+            used_events.send(ItemUsed { player, item });
+        }
+    }
+    if closed {
+        close(&mut panel, &mut actions_frozen);
+    }
+}
+
+fn close(panel: &mut InventoryPanel, actions_frozen: &mut ActionsFrozen) {
+    panel.open = false;
+    panel.crafting = false;
+    actions_frozen.unfreeze();
+}
+
+fn run_give_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let (item, amount) = match args {
+        [item] => (*item, 1),
+        [item, amount] => (
+            *item,
+            amount
+                .parse()
+                .with_context(|| format!("Failed to parse amount \"{amount}\""))?,
+        ),
+        _ => bail!("Usage: give <item> [amount]"),
+    };
+    let mut players = world.query_filtered::<&mut Inventory, With<Player>>();
+    let mut inventory = players
+        .iter_mut(world)
+        .next()
+        .context("There is no player to give items to")?;
+    inventory.add(item, amount);
+    Ok(format!("Gave {amount} {item}"))
+}
@@ -0,0 +1,234 @@
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::file_system_interaction::player_profile::{LevelRecord, PlayerProfile};
+use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::challenge::ChallengeFinished;
+use crate::world_interaction::collectible::{Collectible, CollectibleCollected};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Tracks the [`LevelStats`] of the current level, i.e. the elapsed time, the value of all collected [`Collectible`]s
+/// the number of [`PlayerDied`] events and the best time of every finished challenge. The stats are reset whenever a new level is loaded.
+/// Walking through a [`GoalPortal`] ends the level: the stats are shown in a summary and
+/// the best results per level are recorded in the [`PlayerProfile`].
+pub fn level_stats_plugin(app: &mut App) {
+    app.register_type::<LevelStats>()
+        .register_type::<ChallengeResult>()
+        .register_type::<GoalPortal>()
+        .init_resource::<LevelStats>()
+        .add_event::<PlayerDied>()
+        .add_systems(
+            (
+                reset_level_stats.run_if(resource_exists_and_changed::<CurrentLevel>()),
+                update_level_stats.run_if(not(resource_exists::<LevelSummary>())),
+                reach_goal.run_if(not(resource_exists::<LevelSummary>())),
+                show_level_summary.run_if(resource_exists::<LevelSummary>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct LevelStats {
+    /// Time spent in the level in seconds, not counting pauses.
+    pub elapsed: f32,
+    /// Total value of the collected [`Collectible`]s.
+    pub collected: u32,
+    pub deaths: u32,
+    /// Best time of every challenge finished in this run.
+    #[serde(default)]
+    pub challenges: Vec<ChallengeResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct ChallengeResult {
+    pub challenge: String,
+    /// Time in seconds
+    pub time: f32,
+}
+
+impl LevelStats {
+    /// Best time in seconds of the challenge in this run, if it was finished.
+    pub fn challenge_time(&self, challenge: &str) -> Option<f32> {
+        self.challenges
+            .iter()
+            .find(|result| result.challenge == challenge)
+            .map(|result| result.time)
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct GoalPortal;
+
+/// Send this when the player dies so that it is counted in the [`LevelStats`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct PlayerDied {
+    pub player: Entity,
+}
+
+/// Exists while the summary of a finished level is shown.
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct LevelSummary {
+    pub level: String,
+    pub stats: LevelStats,
+    /// Total value of all collectibles in the level, including the ones that were not collected.
+    pub total_collectibles: u32,
+    /// Best results before this run.
+    pub previous_record: Option<LevelRecord>,
+}
+
+fn reset_level_stats(mut stats: ResMut<LevelStats>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reset_level_stats").entered();
+    *stats = default();
+}
+
+fn update_level_stats(
+    time: Res<Time>,
+    mut stats: ResMut<LevelStats>,
+    mut collected_events: EventReader<CollectibleCollected>,
+    mut death_events: EventReader<PlayerDied>,
+    mut challenge_events: EventReader<ChallengeFinished>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_level_stats").entered();
+    stats.elapsed += time.delta_seconds();
+    stats.collected += collected_events
+        .iter()
+        .map(|event| event.value)
+        .sum::<u32>();
+    stats.deaths += death_events.iter().count() as u32;
+    for event in challenge_events.iter() {
+        match stats
+            .challenges
+            .iter_mut()
+            .find(|result| result.challenge == event.challenge)
+        {
+            Some(result) => result.time = result.time.min(event.time),
+            None => stats.challenges.push(ChallengeResult {
+                challenge: event.challenge.clone(),
+                time: event.time,
+            }),
+        }
+    }
+}
+
+fn reach_goal(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    players: Query<(), With<Player>>,
+    portals: Query<(), With<GoalPortal>>,
+    parents: Query<&Parent>,
+    collectibles: Query<&Collectible>,
+    stats: Res<LevelStats>,
+    current_level: Option<Res<CurrentLevel>>,
+    mut profile: ResMut<PlayerProfile>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("reach_goal").entered();
+    let reached = collision_events.iter().any(|event| {
+        let CollisionEvent::Started(entity_a, entity_b, _) = event else {
+            return false;
+        };
+        let is_portal = |entity: Entity| {
+            let entity = parents.get(entity).map(Parent::get).unwrap_or(entity);
+            portals.contains(entity)
+        };
+        (players.contains(*entity_a) && is_portal(*entity_b))
+            || (players.contains(*entity_b) && is_portal(*entity_a))
+    });
+    let Some(current_level) = current_level else {
+        return;
+    };
+    if !reached {
+        return;
+    }
+    let level = current_level.scene.clone();
+    let remaining: u32 = collectibles
+        .iter()
+        .map(|collectible| collectible.value)
+        .sum();
+    let previous_record = profile.best_results.get(&level).cloned();
+    let record = LevelRecord {
+        best_time: stats.elapsed,
+        most_collected: stats.collected,
+        fewest_deaths: stats.deaths,
+        challenge_times: stats
+            .challenges
+            .iter()
+            .map(|result| (result.challenge.clone(), result.time))
+            .collect(),
+    };
+    let record = match &previous_record {
+        Some(previous) => previous.merge(&record),
+        None => record,
+    };
+    profile.best_results.insert(level.clone(), record);
+    commands.insert_resource(LevelSummary {
+        level,
+        stats: stats.clone(),
+        total_collectibles: stats.collected + remaining,
+        previous_record,
+    });
+    actions_frozen.freeze();
+}
+
+fn show_level_summary(
+    mut commands: Commands,
+    summary: Res<LevelSummary>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_level_summary").entered();
+    let stats = &summary.stats;
+    let previous = summary.previous_record.as_ref();
+    let is_better = |is_better: bool| if is_better { " (new best!)" } else { "" };
+    egui::Window::new("Level Complete")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.heading(&summary.level);
+            ui.label(format!(
+                "Time: {:.1} s{}",
+                stats.elapsed,
+                is_better(previous.map_or(true, |record| stats.elapsed < record.best_time))
+            ));
+            ui.label(format!(
+                "Collected: {} / {}{}",
+                stats.collected,
+                summary.total_collectibles,
+                is_better(previous.map_or(true, |record| stats.collected > record.most_collected))
+            ));
+            ui.label(format!(
+                "Deaths: {}{}",
+                stats.deaths,
+                is_better(previous.map_or(true, |record| stats.deaths < record.fewest_deaths))
+            ));
+            for result in &stats.challenges {
+                let previous_time =
+                    previous.and_then(|record| record.challenge_times.get(&result.challenge));
+                ui.label(format!(
+                    "{}: {:.2} s{}",
+                    result.challenge,
+                    result.time,
+                    is_better(previous_time.map_or(true, |time| result.time < *time))
+                ));
+            }
+            if ui.button("Continue").clicked() {
+                commands.remove_resource::<LevelSummary>();
+                actions_frozen.unfreeze();
+            }
+        });
+}
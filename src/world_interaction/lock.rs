@@ -0,0 +1,278 @@
+use crate::file_system_interaction::game_state_serialization::GameLoaded;
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::file_system_interaction::localization::Localization;
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::world_interaction::dialog::{DialogContext, DialogEvent, DialogId};
+use crate::world_interaction::interactions_ui::{Interactable, InteractionEvent};
+use crate::world_interaction::inventory::Inventory;
+use crate::GameState;
+use anyhow::{bail, Result};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_2;
+
+/// Metadata key of the id under which a [`Lock`] remembers being unlocked, e.g. `cellar`.
+/// Defaults to an id of the door's own, so only doors given the same id are unlocked together.
+pub const LOCK_KEY: &str = "lock";
+/// Metadata key of the item that opens a [`Lock`], e.g. `key_red`.
+pub const LOCK_ITEM_KEY: &str = "key";
+/// Metadata key of whether unlocking a [`Lock`] uses up its key, `true` or `false`.
+pub const LOCK_CONSUMES_KEY: &str = "consume_key";
+/// Metadata key of the dialog started when the player tries a [`Lock`] without its key, e.g. `locked_gate`.
+pub const LOCKED_DIALOG_KEY: &str = "locked_dialog";
+
+/// Speed in radians per second at which unlocked doors swing open.
+const SWING_SPEED: f32 = 2.;
+/// Time in seconds for which the message about a missing key is shown.
+const LOCKED_MESSAGE_DURATION: f32 = 2.5;
+
+/// Handles doors that are opened with keys from the player's [`Inventory`].
+/// Interacting with a [`Lock`] while holding its key unlocks it, which sends a [`LockUnlocked`] and swings the door open.
+/// Without the key, the lock's dialog is started or a message names the missing key, localized as `item.<key>`.
+/// Unlocked locks are remembered as the flag `unlocked.<id>` in the [`DialogContext`], so they stay open across levels
+/// and in save games, and dialogs can check them.
+pub fn lock_plugin(app: &mut App) {
+    app.register_type::<Lock>()
+        .register_type::<LockedDoorHinge>()
+        .add_event::<LockUnlocked>()
+        .add_systems(
+            (
+                read_lock_metadata,
+                try_unlocking,
+                restore_unlocked_locks,
+                swing_doors,
+                show_locked_message.run_if(resource_exists::<LockedMessage>()),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Lock {
+    /// Id under which the unlocked state is stored. Unless set in the metadata, it is made up of the level and the door's position.
+    pub id: String,
+    /// Item needed to unlock this lock
+    pub key: String,
+    pub consume_key: bool,
+    /// Dialog started when the player lacks the key instead of showing a message
+    pub locked_dialog: Option<DialogId>,
+    pub unlocked: bool,
+}
+
+impl Default for Lock {
+    fn default() -> Self {
+        Self {
+            id: default(),
+            key: "key".to_owned(),
+            consume_key: true,
+            locked_dialog: None,
+            unlocked: false,
+        }
+    }
+}
+
+impl Lock {
+    /// Flag of the [`DialogContext`] that is set once the lock is unlocked.
+    pub fn flag(&self) -> String {
+        format!("unlocked.{}", self.id)
+    }
+}
+
+/// Id of a door that was not given one in its metadata. Doors are placed in levels and never move, so it stays the same between sessions.
+fn default_lock_id(level: Option<&CurrentLevel>, transform: &Transform) -> String {
+    let level = level.map_or("", |level| level.scene.as_str());
+    let Vec3 { x, y, z } = transform.translation;
+    format!("{level}@{x:.1},{y:.1},{z:.1}")
+}
+
+/// The part of a locked door it swings around once unlocked. Its parent holds the [`Lock`].
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct LockedDoorHinge;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LockUnlocked {
+    pub lock: Entity,
+    /// Flag that was set in the [`DialogContext`]
+    pub flag: String,
+}
+
+/// Exists while the player is told which key they are missing.
+#[derive(Debug, Clone, PartialEq, Resource)]
+struct LockedMessage {
+    key: String,
+    shown_for: f32,
+}
+
+#[sysfail(log(level = "error"))]
+fn read_lock_metadata(
+    mut locks: Query<(&ObjectMetadata, &Transform, &mut Lock), Changed<ObjectMetadata>>,
+    current_level: Option<Res<CurrentLevel>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_lock_metadata").entered();
+    for (metadata, transform, mut lock) in locks.iter_mut() {
+        let mut read = lock.clone();
+        read.id = metadata
+            .get(LOCK_KEY)
+            .map(|id| id.trim().to_owned())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| default_lock_id(current_level.as_deref(), transform));
+        if let Some(key) = metadata.get(LOCK_ITEM_KEY) {
+            read.key = key.trim().to_owned();
+        }
+        if let Some(consume) = metadata.get(LOCK_CONSUMES_KEY) {
+            read.consume_key = match consume.trim() {
+                "true" => true,
+                "false" => false,
+                _ => bail!("Failed to parse \"{consume}\" as whether a lock consumes its key, expected true or false"),
+            };
+        }
+        read.locked_dialog = metadata
+            .get(LOCKED_DIALOG_KEY)
+            .map(|dialog| DialogId::new(dialog.trim()));
+        if read != *lock {
+            *lock = read;
+        }
+    }
+    Ok(())
+}
+
+fn try_unlocking(
+    mut commands: Commands,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut locks: Query<&mut Lock>,
+    mut players: Query<&mut Inventory>,
+    mut context: ResMut<DialogContext>,
+    mut unlocked_events: EventWriter<LockUnlocked>,
+    mut dialog_events: EventWriter<DialogEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("try_unlocking").entered();
+    for event in interaction_events.iter() {
+        let Ok(mut lock) = locks.get_mut(event.target) else {
+            continue;
+        };
+        let Ok(mut inventory) = players.get_mut(event.player) else {
+            continue;
+        };
+        if lock.unlocked {
+            continue;
+        }
+        if inventory.count(&lock.key) == 0 {
+            match &lock.locked_dialog {
+                Some(dialog) => dialog_events.send(DialogEvent {
+                    dialog: dialog.clone(),
+                    source: event.target,
+                    page: None,
+                    resumed: false,
+                }),
+                None => commands.insert_resource(LockedMessage {
+                    key: lock.key.clone(),
+                    shown_for: 0.,
+                }),
+            }
+            continue;
+        }
+        if lock.consume_key {
+            inventory.remove(&lock.key, 1);
+        }
+        lock.unlocked = true;
+        let flag = lock.flag();
+        info!("Unlocked \"{flag}\"");
+        context.flags.insert(flag.clone());
+        commands.entity(event.target).remove::<Interactable>();
+        unlocked_events.send(LockUnlocked {
+            lock: event.target,
+            flag,
+        });
+    }
+}
+
+/// Opens locks that were unlocked before, e.g. in a save game or an earlier visit to the level.
+/// Only new locks are checked, except right after a save game was loaded, which restores the flags after the level is spawned.
+fn restore_unlocked_locks(
+    mut commands: Commands,
+    mut locks: Query<(Entity, &mut Lock, Option<&Children>)>,
+    mut hinges: Query<&mut Transform, With<LockedDoorHinge>>,
+    context: Res<DialogContext>,
+    mut loaded_events: EventReader<GameLoaded>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("restore_unlocked_locks").entered();
+    let game_loaded = loaded_events.iter().count() > 0;
+    for (entity, mut lock, children) in locks.iter_mut() {
+        if !game_loaded && !lock.is_added() {
+            continue;
+        }
+        if lock.unlocked || !context.has_flag(&lock.flag()) {
+            continue;
+        }
+        lock.unlocked = true;
+        commands.entity(entity).remove::<Interactable>();
+        // Don't let the door swing open in front of the player again
+        for child in children.into_iter().flatten() {
+            if let Ok(mut transform) = hinges.get_mut(*child) {
+                transform.rotation = Quat::from_rotation_y(FRAC_PI_2);
+            }
+        }
+    }
+}
+
+fn swing_doors(
+    time: Res<Time>,
+    locks: Query<&Lock>,
+    mut hinges: Query<(&Parent, &mut Transform), With<LockedDoorHinge>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("swing_doors").entered();
+    for (parent, mut transform) in hinges.iter_mut() {
+        let Ok(lock) = locks.get(parent.get()) else {
+            continue;
+        };
+        let target = if lock.unlocked {
+            Quat::from_rotation_y(FRAC_PI_2)
+        } else {
+            Quat::IDENTITY
+        };
+        let angle = transform.rotation.angle_between(target);
+        if angle < f32::EPSILON {
+            continue;
+        }
+        let step = (SWING_SPEED * time.delta_seconds() / angle).min(1.);
+        transform.rotation = transform.rotation.slerp(target, step);
+    }
+}
+
+fn show_locked_message(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut message: ResMut<LockedMessage>,
+    localization: Res<Localization>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_locked_message").entered();
+    message.shown_for += time.delta_seconds();
+    if message.shown_for >= LOCKED_MESSAGE_DURATION {
+        commands.remove_resource::<LockedMessage>();
+        return;
+    }
+    let text = format!(
+        "{} {}",
+        localization.get("lock.requires"),
+        localization.get(&format!("item.{}", message.key))
+    );
+    egui::Area::new("locked_message")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0., 60.))
+        .interactable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(text);
+            });
+        });
+}
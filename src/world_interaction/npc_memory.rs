@@ -0,0 +1,175 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::player_control::player_embodiment::Player;
+use crate::util::trait_extension::F32Ext;
+use crate::world_interaction::carrying::Carryable;
+use crate::world_interaction::dialog::{CurrentDialog, DialogTarget};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+pub use foxtrot_dialog::{MemoryRequirements, NpcMemory};
+use serde::{Deserialize, Serialize};
+
+/// Gives every NPC with a [`DialogTarget`] an [`NpcMemory`] of what the player did to them:
+/// - Perception: NPCs remember where they last saw the player, if the player is within [`SIGHT_RANGE`] and not hidden behind anything.
+/// - Dialog: finished conversations are counted.
+/// - Combat: NPCs remember being hit by a thrown prop.
+///
+/// Other systems read the memory from the component, e.g. dialog requirements.
+/// Memories are part of save games, see [`NpcMemories`].
+pub fn npc_memory_plugin(app: &mut App) {
+    app.register_type::<NpcMemory>().add_systems(
+        (
+            add_npc_memories,
+            perceive_player,
+            count_conversations,
+            remember_attacks,
+        )
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+/// The memories of all NPCs in the current level by [`npc_key`], as stored in save games.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct NpcMemories(pub Vec<(String, NpcMemory)>);
+
+impl NpcMemories {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn collect<'a>(
+        npcs: impl IntoIterator<Item = (Option<&'a Name>, Option<&'a ObjectMetadata>, &'a NpcMemory)>,
+    ) -> Self {
+        Self(
+            npcs.into_iter()
+                .filter_map(|(name, metadata, memory)| {
+                    Some((npc_key(name, metadata)?, memory.clone()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Gives each NPC the first unused memory stored under its key.
+    pub fn restore<'a>(
+        &self,
+        commands: &mut Commands,
+        npcs: impl IntoIterator<Item = (Entity, Option<&'a Name>, Option<&'a ObjectMetadata>)>,
+    ) {
+        let mut memories = self.0.clone();
+        for (entity, name, metadata) in npcs {
+            let Some(key) = npc_key(name, metadata) else {
+                continue;
+            };
+            let memory = memories
+                .iter()
+                .position(|(memory_key, _)| *memory_key == key)
+                .map(|index| memories.swap_remove(index).1)
+                .unwrap_or_default();
+            commands.entity(entity).insert(memory);
+        }
+    }
+}
+
+/// Identifies an NPC between loads by its `id` [`ObjectMetadata`], or by its [`Name`] if it has none.
+/// NPCs sharing a key get the memories stored under it in arbitrary order.
+pub fn npc_key(name: Option<&Name>, metadata: Option<&ObjectMetadata>) -> Option<String> {
+    metadata
+        .and_then(|metadata| metadata.get("id"))
+        .map(str::to_owned)
+        .or_else(|| name.map(|name| name.to_string()))
+}
+
+/// Distance in m up to which NPCs notice the player.
+pub const SIGHT_RANGE: f32 = 15.;
+
+/// Props hitting an NPC faster than this in m/s count as an attack.
+const ATTACK_SPEED: f32 = 3.;
+
+fn add_npc_memories(
+    mut commands: Commands,
+    npcs: Query<Entity, (With<DialogTarget>, Without<NpcMemory>)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("add_npc_memories").entered();
+    for entity in npcs.iter() {
+        commands.entity(entity).insert(NpcMemory::default());
+    }
+}
+
+fn perceive_player(
+    rapier_context: Res<RapierContext>,
+    players: Query<(Entity, &Transform), With<Player>>,
+    mut npcs: Query<(Entity, &Transform, &mut NpcMemory), Without<Player>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("perceive_player").entered();
+    for (player, player_transform) in players.iter() {
+        for (npc, npc_transform, mut memory) in npcs.iter_mut() {
+            let to_player = player_transform.translation - npc_transform.translation;
+            let distance = to_player.length();
+            if distance > SIGHT_RANGE || distance < f32::EPSILON {
+                continue;
+            }
+            let sees_player = rapier_context
+                .cast_ray(
+                    npc_transform.translation,
+                    to_player / distance,
+                    distance,
+                    true,
+                    QueryFilter::new().exclude_collider(npc).exclude_sensors(),
+                )
+                .map_or(true, |(hit, _)| hit == player);
+            if sees_player && memory.last_seen_player_position != Some(player_transform.translation)
+            {
+                memory.last_seen_player_position = Some(player_transform.translation);
+            }
+        }
+    }
+}
+
+/// A conversation counts once it is over, so dialogs can tell the first one apart by `times_talked_to` being 0.
+fn count_conversations(
+    current_dialog: Option<Res<CurrentDialog>>,
+    mut npcs: Query<&mut NpcMemory>,
+    mut speaker: Local<Option<Entity>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("count_conversations").entered();
+    match current_dialog {
+        Some(current_dialog) => *speaker = Some(current_dialog.source),
+        None => {
+            if let Some(mut memory) = speaker.take().and_then(|npc| npcs.get_mut(npc).ok()) {
+                memory.times_talked_to += 1;
+            }
+        }
+    }
+}
+
+fn remember_attacks(
+    rapier_context: Res<RapierContext>,
+    props: Query<&Velocity, With<Carryable>>,
+    mut npcs: Query<(Entity, &mut NpcMemory)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("remember_attacks").entered();
+    for (npc, mut memory) in npcs.iter_mut() {
+        if memory.was_attacked {
+            continue;
+        }
+        let was_hit = rapier_context
+            .contacts_with(npc)
+            .filter(|contact_pair| contact_pair.has_any_active_contacts())
+            .filter_map(|contact_pair| {
+                let other = if contact_pair.collider1() == npc {
+                    contact_pair.collider2()
+                } else {
+                    contact_pair.collider1()
+                };
+                props.get(other).ok()
+            })
+            .any(|velocity| velocity.linvel.length_squared() > ATTACK_SPEED.squared());
+        if was_hit {
+            memory.was_attacked = true;
+        }
+    }
+}
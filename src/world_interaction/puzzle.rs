@@ -0,0 +1,344 @@
+use crate::world_interaction::carrying::{Carried, Carrying};
+use crate::world_interaction::interactions_ui::{Interactable, InteractionEvent};
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Speed in m/s at which doors move towards their target position.
+const DOOR_SPEED: f32 = 1.5;
+/// Distance in meters from which levers can be pulled.
+const LEVER_RADIUS: f32 = 1.5;
+
+/// Handles physics puzzle pieces that are placed in the level via markers in their glTF node names:
+/// - `[pressure_plate: <activation>, <mass>]`: Is pressed while the dynamic bodies on it weigh at least `<mass>` kg.
+/// Carried objects count towards the mass of their carrier.
+/// - `[socket: <activation>, <key>]`: Holds the first dropped object whose [`SocketKey`] equals `<key>`.
+/// - `[lever: <activation>]`: Toggles between active and inactive whenever the player interacts with it.
+/// - `[door: <activation>, <x>, <y>, <z>]`: Moves by the given offset while any of its linked plates or sockets is active.
+/// Also works for bridges, lifts, etc.
+///
+/// Plates and sockets react to everything inside the node's cube, i.e. the node's scale are the half extents of the volume,
+/// just like Blender's default cube. They and levers are linked to doors by sharing the same activation name and
+/// report their state changes as [`ActivationEvent`]s.
+pub fn puzzle_plugin(app: &mut App) {
+    app.register_type::<ActivationId>()
+        .register_type::<PressurePlate>()
+        .register_type::<Socket>()
+        .register_type::<SocketKey>()
+        .register_type::<Lever>()
+        .add_event::<ActivationEvent>()
+        .add_systems(
+            (
+                read_puzzle_markers,
+                update_pressure_plates,
+                update_sockets,
+                pull_levers,
+                move_doors,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Default, Reflect, Hash, Serialize, Deserialize, FromReflect,
+)]
+#[reflect(Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct ActivationId(pub String);
+
+impl From<String> for ActivationId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ActivationId> for String {
+    fn from(value: ActivationId) -> Self {
+        value.0
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ActivationEvent {
+    pub id: ActivationId,
+    /// The plate, socket or lever whose state changed.
+    pub source: Entity,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct PressurePlate {
+    pub activation: ActivationId,
+    /// Minimum mass in kg that needs to be on the plate for it to be pressed.
+    pub mass_threshold: f32,
+    pub pressed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Socket {
+    pub activation: ActivationId,
+    /// Only objects with an equal [`SocketKey`] fit into this socket.
+    pub key: String,
+    pub occupant: Option<Entity>,
+}
+
+/// Marks an object as fitting into [`Socket`]s with the same key.
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct SocketKey(pub String);
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct Lever {
+    pub activation: ActivationId,
+    pub pulled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct Door {
+    pub activation: ActivationId,
+    /// Offset from the closed position when open.
+    pub offset: Vec3,
+    pub closed_translation: Vec3,
+    /// Plates and sockets that are currently holding this door open.
+    pub active_sources: HashSet<Entity>,
+}
+
+static PRESSURE_PLATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[pressure_plate:\s*([^,\]]+?),\s*(\d+(?:\.\d+)?)\]")
+        .expect("Failed to compile pressure plate regex")
+});
+
+static SOCKET_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[socket:\s*([^,\]]+?),\s*([^,\]]+?)\]").expect("Failed to compile socket regex")
+});
+
+static LEVER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[lever:\s*([^,\]]+?)\]").expect("Failed to compile lever regex")
+});
+
+static DOOR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\[door:\s*([^,\]]+?),\s*(-?\d+(?:\.\d+)?),\s*(-?\d+(?:\.\d+)?),\s*(-?\d+(?:\.\d+)?)\]",
+    )
+    .expect("Failed to compile door regex")
+});
+
+#[sysfail(log(level = "error"))]
+fn read_puzzle_markers(
+    mut commands: Commands,
+    added_name: Query<(Entity, &Name, &Transform), Added<Name>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_puzzle_markers").entered();
+    for (entity, name, transform) in added_name.iter() {
+        let name = name.to_lowercase();
+        if let Some(captures) = PRESSURE_PLATE_REGEX.captures(&name) {
+            commands.entity(entity).insert(PressurePlate {
+                activation: ActivationId(captures[1].to_owned()),
+                mass_threshold: captures[2]
+                    .parse()
+                    .with_context(|| format!("Failed to parse mass in pressure plate: {name}"))?,
+                pressed: false,
+            });
+        }
+        if let Some(captures) = SOCKET_REGEX.captures(&name) {
+            commands.entity(entity).insert(Socket {
+                activation: ActivationId(captures[1].to_owned()),
+                key: captures[2].to_owned(),
+                occupant: None,
+            });
+        }
+        if let Some(captures) = LEVER_REGEX.captures(&name) {
+            commands.entity(entity).insert((
+                Lever {
+                    activation: ActivationId(captures[1].to_owned()),
+                    pulled: false,
+                },
+                Interactable::new("interaction.pull", LEVER_RADIUS),
+            ));
+        }
+        if let Some(captures) = DOOR_REGEX.captures(&name) {
+            let parse = |index: usize| {
+                captures[index]
+                    .parse::<f32>()
+                    .with_context(|| format!("Failed to parse offset in door: {name}"))
+            };
+            commands.entity(entity).insert(Door {
+                activation: ActivationId(captures[1].to_owned()),
+                offset: Vec3::new(parse(2)?, parse(3)?, parse(4)?),
+                closed_translation: transform.translation,
+                active_sources: default(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the rigid bodies of all dynamic, non-sensor colliders inside the cube of the given transform.
+fn get_bodies_in_volume(
+    rapier_context: &RapierContext,
+    transform: &GlobalTransform,
+) -> HashSet<Entity> {
+    let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+    let shape = Collider::cuboid(scale.x, scale.y, scale.z);
+    let mut bodies = HashSet::new();
+    rapier_context.intersections_with_shape(
+        translation,
+        rotation,
+        &shape,
+        QueryFilter::only_dynamic().exclude_sensors(),
+        |collider| {
+            bodies.insert(rapier_context.collider_parent(collider).unwrap_or(collider));
+            true
+        },
+    );
+    bodies
+}
+
+fn update_pressure_plates(
+    rapier_context: Res<RapierContext>,
+    mut plates: Query<(Entity, &GlobalTransform, &mut PressurePlate)>,
+    masses: Query<&ReadMassProperties>,
+    carriers: Query<&Carrying>,
+    mut activation_events: EventWriter<ActivationEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_pressure_plates").entered();
+    let get_mass = |entity: Entity| {
+        masses
+            .get(entity)
+            .map(|mass| mass.0.mass)
+            .unwrap_or_default()
+    };
+    for (entity, transform, mut plate) in plates.iter_mut() {
+        let mass: f32 = get_bodies_in_volume(&rapier_context, transform)
+            .into_iter()
+            .map(|body| {
+                let carried_mass = carriers
+                    .get(body)
+                    .map(|carrying| get_mass(carrying.object))
+                    .unwrap_or_default();
+                get_mass(body) + carried_mass
+            })
+            .sum();
+        let pressed = mass >= plate.mass_threshold;
+        if pressed != plate.pressed {
+            plate.pressed = pressed;
+            activation_events.send(ActivationEvent {
+                id: plate.activation.clone(),
+                source: entity,
+                active: pressed,
+            });
+        }
+    }
+}
+
+fn update_sockets(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut sockets: Query<(Entity, &GlobalTransform, &mut Socket)>,
+    keys: Query<&SocketKey, Without<Carried>>,
+    mut activation_events: EventWriter<ActivationEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_sockets").entered();
+    for (entity, transform, mut socket) in sockets.iter_mut() {
+        if let Some(occupant) = socket.occupant {
+            // The occupant was picked up again or despawned
+            if !keys.contains(occupant) {
+                socket.occupant = None;
+                activation_events.send(ActivationEvent {
+                    id: socket.activation.clone(),
+                    source: entity,
+                    active: false,
+                });
+            }
+            continue;
+        }
+        let occupant = get_bodies_in_volume(&rapier_context, transform)
+            .into_iter()
+            .find(|body| {
+                keys.get(*body)
+                    .map(|key| key.0.to_lowercase() == socket.key)
+                    .unwrap_or_default()
+            });
+        let Some(occupant) = occupant else {
+            continue;
+        };
+        let (_scale, rotation, translation) = transform.to_scale_rotation_translation();
+        commands.entity(occupant).insert((
+            RigidBody::KinematicPositionBased,
+            Transform::from_translation(translation).with_rotation(rotation),
+        ));
+        socket.occupant = Some(occupant);
+        activation_events.send(ActivationEvent {
+            id: socket.activation.clone(),
+            source: entity,
+            active: true,
+        });
+    }
+}
+
+fn pull_levers(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut levers: Query<&mut Lever>,
+    mut activation_events: EventWriter<ActivationEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("pull_levers").entered();
+    for event in interaction_events.iter() {
+        let Ok(mut lever) = levers.get_mut(event.target) else {
+            continue;
+        };
+        lever.pulled = !lever.pulled;
+        activation_events.send(ActivationEvent {
+            id: lever.activation.clone(),
+            source: event.target,
+            active: lever.pulled,
+        });
+    }
+}
+
+fn move_doors(
+    time: Res<Time>,
+    mut activation_events: EventReader<ActivationEvent>,
+    mut doors: Query<(&mut Door, &mut Transform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("move_doors").entered();
+    for event in activation_events.iter() {
+        for (mut door, _) in doors.iter_mut() {
+            if door.activation != event.id {
+                continue;
+            }
+            if event.active {
+                door.active_sources.insert(event.source);
+            } else {
+                door.active_sources.remove(&event.source);
+            }
+        }
+    }
+
+    let max_distance = DOOR_SPEED * time.delta_seconds();
+    for (door, mut transform) in doors.iter_mut() {
+        let target = if door.active_sources.is_empty() {
+            door.closed_translation
+        } else {
+            door.closed_translation + door.offset
+        };
+        let to_target = target - transform.translation;
+        if to_target.length_squared() > 0.0 {
+            transform.translation += to_target.clamp_length_max(max_distance);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::asset_loading::QuestAssets;
+use crate::file_system_interaction::localization::Localization;
+use crate::ingame_menu::Paused;
+use crate::world_interaction::dialog::{
+    ContextRequirements, DialogContext, DialogEffect, DialogEffectEvent,
+};
+use crate::GameState;
+use anyhow::{bail, Result};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashSet;
+use bevy_egui::egui::RichText;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+/// Handles quests, which are authored in `assets/quests/` as [`Quest`]s made of consecutive [`QuestStage`]s.
+/// A quest is named after its file, e.g. `fox_rest` for `quests/fox_rest.quest.ron`.
+/// Quests are started, advanced and completed through [`QuestEvent`]s, which interactions send directly
+/// and dialogs send through the quest [`DialogEffect`]s. A stage also completes by itself once the [`DialogContext`]
+/// meets its requirements, e.g. when the player holds enough of an item. Completing the last stage completes the quest
+/// and applies its rewards to the [`DialogContext`] before sending a [`QuestCompleted`]. The context also learns about started and completed quests so dialogs can react to them.
+/// Progress is kept in the [`QuestLog`], which is stored in save games. The objectives of active quests are listed in the top left corner.
+pub fn quest_plugin(app: &mut App) {
+    app.register_type::<QuestId>()
+        .register_type::<QuestLog>()
+        .init_resource::<QuestLog>()
+        .add_event::<QuestEvent>()
+        .add_event::<QuestCompleted>()
+        .add_systems(
+            (
+                forward_dialog_quest_effects,
+                handle_quest_events,
+                complete_quest_stages,
+                show_quest_objectives.run_if(not(resource_exists::<Paused>())),
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "quest",
+            "Starts, advances or completes a quest, e.g. \"quest start fox_rest\". Without arguments, lists the active quests",
+            run_quest_command,
+        );
+}
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub struct QuestId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid, Default)]
+#[uuid = "9d1f4b7e-2c3a-4e8f-b6d5-7a0e1c9f3b52"]
+pub struct Quest {
+    /// Key of the title in the [`Localization`]
+    pub title: String,
+    pub stages: Vec<QuestStage>,
+    /// Applied to the [`DialogContext`] when the quest is completed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rewards: Vec<DialogEffect>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct QuestStage {
+    /// Key of the objective shown while this stage is current in the [`Localization`]
+    pub objective: String,
+    /// The stage completes once these are met. Stages without requirements only complete through [`QuestEvent::Advance`].
+    #[serde(default, skip_serializing_if = "ContextRequirements::is_empty")]
+    pub completion: ContextRequirements,
+}
+
+/// Progress of all quests the player has started.
+#[derive(Debug, Clone, Eq, PartialEq, Resource, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct QuestLog {
+    /// In the order they were started
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub active: Vec<ActiveQuest>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub completed: HashSet<QuestId>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Reflect, FromReflect, Serialize, Deserialize, Default)]
+#[reflect(Serialize, Deserialize)]
+pub struct ActiveQuest {
+    pub id: QuestId,
+    /// Index of the current [`QuestStage`]
+    pub stage: usize,
+}
+
+impl QuestLog {
+    pub fn is_empty(&self) -> bool {
+        self == &default()
+    }
+
+    /// Index of the current stage of an active quest.
+    pub fn stage(&self, id: &QuestId) -> Option<usize> {
+        self.active
+            .iter()
+            .find(|active| active.id == *id)
+            .map(|active| active.stage)
+    }
+
+    pub fn is_completed(&self, id: &QuestId) -> bool {
+        self.completed.contains(id)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum QuestEvent {
+    /// Ignored if the quest is already active or completed
+    Start(QuestId),
+    /// Completes the current stage of an active quest regardless of its requirements
+    Advance(QuestId),
+    /// Completes the quest even if it was never started
+    Complete(QuestId),
+}
+
+/// Sent after a quest was completed and its rewards were applied to the [`DialogContext`],
+/// so that systems keeping state outside of it can apply the rewards meant for them.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QuestCompleted {
+    pub quest: QuestId,
+    pub rewards: Vec<DialogEffect>,
+}
+
+impl QuestEvent {
+    pub fn quest(&self) -> &QuestId {
+        match self {
+            QuestEvent::Start(id) | QuestEvent::Advance(id) | QuestEvent::Complete(id) => id,
+        }
+    }
+}
+
+fn get_quest<'a>(
+    quests: &'a Assets<Quest>,
+    quest_assets: &QuestAssets,
+    id: &QuestId,
+) -> Option<&'a Quest> {
+    let path = format!("quests/{}.quest.ron", id.0);
+    quests.get(quest_assets.quests.get(&path)?)
+}
+
+fn forward_dialog_quest_effects(
+    mut effect_events: EventReader<DialogEffectEvent>,
+    mut quest_events: EventWriter<QuestEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("forward_dialog_quest_effects").entered();
+    for event in effect_events.iter() {
+        let quest_event = match &event.effect {
+            DialogEffect::StartQuest(quest) => QuestEvent::Start(QuestId(quest.clone())),
+            DialogEffect::AdvanceQuest(quest) => QuestEvent::Advance(QuestId(quest.clone())),
+            DialogEffect::CompleteQuest(quest) => QuestEvent::Complete(QuestId(quest.clone())),
+            _ => continue,
+        };
+        quest_events.send(quest_event);
+    }
+}
+
+fn handle_quest_events(
+    mut quest_events: EventReader<QuestEvent>,
+    mut log: ResMut<QuestLog>,
+    mut context: ResMut<DialogContext>,
+    quest_assets: Res<QuestAssets>,
+    quests: Res<Assets<Quest>>,
+    mut completed_events: EventWriter<QuestCompleted>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_quest_events").entered();
+    for event in quest_events.iter() {
+        let id = event.quest();
+        let Some(quest) = get_quest(&quests, &quest_assets, id) else {
+            error!(
+                "Failed to handle {event:?}: No such quest. Available quests: {:?}",
+                quest_assets.quests.keys()
+            );
+            continue;
+        };
+        match event {
+            QuestEvent::Start(id) => {
+                if log.stage(id).is_some() || log.is_completed(id) {
+                    continue;
+                }
+                log.active.push(ActiveQuest {
+                    id: id.clone(),
+                    stage: 0,
+                });
+                context.started_quests.insert(id.0.clone());
+                info!("Started quest \"{}\"", id.0);
+                if quest.stages.is_empty() {
+                    complete_quest(&mut log, &mut context, &mut completed_events, id, quest);
+                }
+            }
+            QuestEvent::Advance(id) => {
+                advance_quest(&mut log, &mut context, &mut completed_events, id, quest)
+            }
+            QuestEvent::Complete(id) => {
+                complete_quest(&mut log, &mut context, &mut completed_events, id, quest)
+            }
+        }
+    }
+}
+
+/// Advances every active quest whose current stage has its requirements met.
+/// Consecutive stages that are already met complete one per frame.
+fn complete_quest_stages(
+    mut log: ResMut<QuestLog>,
+    mut context: ResMut<DialogContext>,
+    quest_assets: Res<QuestAssets>,
+    quests: Res<Assets<Quest>>,
+    mut completed_events: EventWriter<QuestCompleted>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("complete_quest_stages").entered();
+    if !log.is_changed() && !context.is_changed() {
+        return;
+    }
+    for active in log.active.clone() {
+        let Some(quest) = get_quest(&quests, &quest_assets, &active.id) else {
+            continue;
+        };
+        let is_met = quest.stages.get(active.stage).map_or(false, |stage| {
+            !stage.completion.is_empty() && stage.completion.is_met(&context)
+        });
+        if is_met {
+            advance_quest(
+                &mut log,
+                &mut context,
+                &mut completed_events,
+                &active.id,
+                quest,
+            );
+        }
+    }
+}
+
+/// Moves an active quest to its next stage, completing it after the last one.
+fn advance_quest(
+    log: &mut QuestLog,
+    context: &mut DialogContext,
+    completed_events: &mut EventWriter<QuestCompleted>,
+    id: &QuestId,
+    quest: &Quest,
+) {
+    let Some(active) = log.active.iter_mut().find(|active| active.id == *id) else {
+        return;
+    };
+    active.stage += 1;
+    if active.stage >= quest.stages.len() {
+        complete_quest(log, context, completed_events, id, quest);
+    }
+}
+
+fn complete_quest(
+    log: &mut QuestLog,
+    context: &mut DialogContext,
+    completed_events: &mut EventWriter<QuestCompleted>,
+    id: &QuestId,
+    quest: &Quest,
+) {
+    if log.is_completed(id) {
+        return;
+    }
+    log.active.retain(|active| active.id != *id);
+    log.completed.insert(id.clone());
+    context.completed_quests.insert(id.0.clone());
+    for reward in &quest.rewards {
+        context.apply(reward);
+    }
+    completed_events.send(QuestCompleted {
+        quest: id.clone(),
+        rewards: quest.rewards.clone(),
+    });
+    info!("Completed quest \"{}\"", id.0);
+}
+
+fn show_quest_objectives(
+    log: Res<QuestLog>,
+    quest_assets: Res<QuestAssets>,
+    quests: Res<Assets<Quest>>,
+    localization: Res<Localization>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_quest_objectives").entered();
+    if log.active.is_empty() {
+        return;
+    }
+    egui::Area::new("Quest Objectives")
+        // Below the health bar
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(20., 60.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            for active in &log.active {
+                let Some(quest) = get_quest(&quests, &quest_assets, &active.id) else {
+                    continue;
+                };
+                let Some(stage) = quest.stages.get(active.stage) else {
+                    continue;
+                };
+                ui.label(RichText::new(localization.get(&quest.title)).strong());
+                ui.label(format!("- {}", localization.get(&stage.objective)));
+                ui.add_space(6.);
+            }
+        });
+}
+
+fn run_quest_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let (action, id) = match args {
+        [] => {
+            let log = world.resource::<QuestLog>();
+            let active: Vec<_> = log
+                .active
+                .iter()
+                .map(|active| format!("{} (stage {})", active.id.0, active.stage + 1))
+                .collect();
+            return Ok(format!("Active quests: {}", active.join(", ")));
+        }
+        [action, id] => (*action, QuestId(id.to_string())),
+        _ => bail!("Usage: quest [start|advance|complete <id>]"),
+    };
+    let event = match action {
+        "start" => QuestEvent::Start(id),
+        "advance" => QuestEvent::Advance(id),
+        "complete" => QuestEvent::Complete(id),
+        _ => bail!("Unknown quest action \"{action}\", expected start, advance or complete"),
+    };
+    let message = format!("Sending {event:?}");
+    world.send_event(event);
+    Ok(message)
+}
@@ -0,0 +1,229 @@
+use crate::level_instantiation::spawning::objects::GameCollisionGroup;
+use crate::movement::general_movement::Jumping;
+use crate::player_control::actions::PlayerAction;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::interactions_ui::InteractionUi;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+const SEGMENT_RADIUS: f32 = 0.03;
+
+/// Handles ropes, which hang from the [`Rope`] entity as a chain of capsules connected by spherical joints.
+/// Changing a [`Rope`], e.g. its length in the editor's inspector, rebuilds its segments.
+/// The player can grab the end of a rope with the interact button to swing on it and let go by jumping.
+pub fn rope_plugin(app: &mut App) {
+    app.register_type::<Rope>().add_systems(
+        (build_ropes, despawn_orphaned_segments, handle_rope_input)
+            .chain()
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Rope {
+    /// Total length in m.
+    pub length: f32,
+    /// Length of a single segment in m. Shorter segments look smoother but are more expensive to simulate.
+    pub segment_length: f32,
+    /// A dynamic body tied to the end of the rope.
+    #[serde(skip)]
+    pub tether: Option<Entity>,
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Self {
+            length: 3.0,
+            segment_length: 0.25,
+            tether: None,
+        }
+    }
+}
+
+/// The segments a [`Rope`] was built with, from top to bottom.
+#[derive(Debug, Clone, PartialEq, Component, Default)]
+pub struct RopeSegments {
+    pub segments: Vec<Entity>,
+    /// Shared by all segments and replaced in place when the rope is rebuilt
+    pub mesh: Handle<Mesh>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Component)]
+pub struct RopeSegment {
+    pub rope: Entity,
+}
+
+/// Present on characters while they hang on a rope.
+#[derive(Debug, Clone, Eq, PartialEq, Component)]
+pub struct Grabbing {
+    pub segment: Entity,
+}
+
+fn get_or_add_material_handle(
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x5d2e8f71a4c96b03);
+    let handle = MATERIAL_HANDLE.typed();
+    material_assets.get_or_insert_with(handle.clone_weak(), || Color::rgb(0.6, 0.5, 0.3).into());
+    handle
+}
+
+fn build_ropes(
+    mut commands: Commands,
+    ropes: Query<(Entity, &Rope, &Transform, Option<&RopeSegments>), Changed<Rope>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("build_ropes").entered();
+    for (rope_entity, rope, transform, old_segments) in ropes.iter() {
+        for segment in old_segments.iter().flat_map(|old| old.segments.iter()) {
+            if let Some(segment) = commands.get_entity(*segment) {
+                segment.despawn_recursive();
+            }
+        }
+
+        let segment_length = rope.segment_length.max(SEGMENT_RADIUS * 2.);
+        let segment_count = (rope.length / segment_length).ceil().max(1.) as usize;
+        let half_length = segment_length / 2.;
+        let capsule = Mesh::from(shape::Capsule {
+            radius: SEGMENT_RADIUS,
+            depth: segment_length,
+            ..default()
+        });
+        // Rebuilding a rope, e.g. while dragging its length in the inspector, would otherwise add a mesh every frame
+        let mesh = match old_segments {
+            Some(old) => meshes.set(&old.mesh, capsule),
+            None => meshes.add(capsule),
+        };
+        let material = get_or_add_material_handle(&mut materials);
+
+        let mut segments = Vec::with_capacity(segment_count);
+        let mut previous = rope_entity;
+        let mut previous_anchor = Vec3::ZERO;
+        for index in 0..segment_count {
+            let translation =
+                transform.translation - Vec3::Y * segment_length * (index as f32 + 0.5);
+            let joint = SphericalJointBuilder::new()
+                .local_anchor1(previous_anchor)
+                .local_anchor2(Vec3::Y * half_length);
+            let mut segment = commands.spawn((
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+                Name::new("Rope Segment"),
+                RigidBody::Dynamic,
+                Collider::capsule_y(half_length, SEGMENT_RADIUS),
+                ColliderMassProperties::Mass(0.2),
+                // Ropes don't collide with the player so that it can hang on them without getting pushed around
+                CollisionGroups::new(
+                    GameCollisionGroup::OTHER.into(),
+                    (GameCollisionGroup::ALL ^ GameCollisionGroup::PLAYER).into(),
+                ),
+                ImpulseJoint::new(previous, joint),
+                RopeSegment { rope: rope_entity },
+            ));
+            if index == segment_count - 1 {
+                segment.with_children(|parent| {
+                    parent.spawn((
+                        Name::new("Rope Interaction Collider"),
+                        Collider::ball(0.5),
+                        Sensor,
+                        ActiveEvents::COLLISION_EVENTS,
+                        ActiveCollisionTypes::DYNAMIC_DYNAMIC,
+                        CollisionGroups::new(
+                            GameCollisionGroup::OTHER.into(),
+                            GameCollisionGroup::PLAYER.into(),
+                        ),
+                    ));
+                });
+            }
+            previous = segment.id();
+            previous_anchor = -Vec3::Y * half_length;
+            segments.push(previous);
+        }
+
+        if let Some(mut tether) = rope.tether.and_then(|tether| commands.get_entity(tether)) {
+            let joint = SphericalJointBuilder::new().local_anchor1(previous_anchor);
+            tether.insert(ImpulseJoint::new(previous, joint));
+        }
+        commands
+            .entity(rope_entity)
+            .insert((RigidBody::Fixed, RopeSegments { segments, mesh }));
+    }
+}
+
+fn despawn_orphaned_segments(
+    mut commands: Commands,
+    segments: Query<(Entity, &RopeSegment)>,
+    ropes: Query<(), With<Rope>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("despawn_orphaned_segments").entered();
+    for (entity, segment) in segments.iter() {
+        if !ropes.contains(segment.rope) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn handle_rope_input(
+    mut commands: Commands,
+    interaction_ui: Option<Res<InteractionUi>>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<PlayerAction>,
+            &Jumping,
+            &mut Velocity,
+            Option<&Grabbing>,
+        ),
+        With<Player>,
+    >,
+    segments: Query<&Collider, With<RopeSegment>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("handle_rope_input").entered();
+    for (player, actions, jumping, mut velocity, grabbing) in players.iter_mut() {
+        if let Some(grabbing) = grabbing {
+            let segment_exists = segments.contains(grabbing.segment);
+            if actions.just_pressed(PlayerAction::Jump) || !segment_exists {
+                commands.entity(player).remove::<(Grabbing, ImpulseJoint)>();
+                if segment_exists {
+                    velocity.linvel += Vec3::Y * jumping.speed;
+                }
+            }
+            continue;
+        }
+
+        if !actions.just_pressed(PlayerAction::Interact) {
+            continue;
+        }
+        let Some(segment) = interaction_ui.as_ref().map(|ui| ui.source) else {
+            continue;
+        };
+        let Ok(collider) = segments.get(segment) else {
+            continue;
+        };
+        let half_length = collider
+            .as_capsule()
+            .map(|capsule| capsule.half_height())
+            .unwrap_or_default();
+        let joint = SphericalJointBuilder::new()
+            .local_anchor1(-Vec3::Y * half_length)
+            // Roughly where the hands are
+            .local_anchor2(Vec3::Y * 0.3);
+        commands
+            .entity(player)
+            .insert((Grabbing { segment }, ImpulseJoint::new(segment, joint)));
+    }
+}
@@ -0,0 +1,368 @@
+use crate::console::{AddConsoleCommandExt, PermissionLevel};
+use crate::file_system_interaction::config::GameConfig;
+use crate::file_system_interaction::level_serialization::CurrentLevel;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::level_stats::LevelSummary;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Seconds between two recorded transforms of the player.
+const GHOST_SAMPLE_INTERVAL: f32 = 0.05;
+
+/// Handles the optional speedrun mode, which is toggled with the `speedrun` console command.
+/// While it is active, an on-screen timer measures the time from loading a level or enabling the mode until reaching the goal portal.
+/// Entering a node with the glTF name marker `[split: <name>]` records a split, which is compared to the split of the same name in the best run.
+/// The player's transforms are recorded during the run. The best run per level is stored in `saves/ghosts/`
+/// and replayed as a translucent [`Ghost`] in subsequent runs.
+pub fn speedrun_plugin(app: &mut App) {
+    app.register_type::<SplitMarker>()
+        .register_type::<Ghost>()
+        .add_system(read_split_markers.in_set(OnUpdate(GameState::Playing)))
+        .add_systems(
+            (
+                start_speedrun,
+                update_speedrun.run_if(not(resource_exists::<LevelSummary>())),
+                finish_speedrun.run_if(resource_added::<LevelSummary>()),
+                update_ghost,
+                show_speedrun_timer,
+            )
+                .chain()
+                .distributive_run_if(resource_exists::<Speedrun>())
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(despawn_ghosts.run_if(resource_removed::<Speedrun>()))
+        .add_console_command_with_permission(
+            "speedrun",
+            "Toggles speedrun mode with a timer, splits and a ghost of the best run. Accepts \"on\" or \"off\" as argument",
+            PermissionLevel::Player,
+            toggle_speedrun,
+        );
+}
+
+/// Exists while speedrun mode is active. Holds the current run and the best run of the current level.
+#[derive(Debug, Clone, PartialEq, Resource, Default)]
+pub struct Speedrun {
+    pub current: SpeedrunRecording,
+    pub best: Option<SpeedrunRecording>,
+    pub finished: bool,
+    since_last_sample: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct SpeedrunRecording {
+    pub time: f32,
+    pub splits: Vec<Split>,
+    pub frames: Vec<GhostFrame>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Split {
+    pub name: String,
+    pub time: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GhostFrame {
+    pub time: f32,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+impl SpeedrunRecording {
+    pub fn split(&self, name: &str) -> Option<&Split> {
+        self.splits.iter().find(|split| split.name == name)
+    }
+
+    /// Interpolates the recorded transform at the given time.
+    pub fn sample(&self, time: f32) -> Option<Transform> {
+        let next_index = self.frames.partition_point(|frame| frame.time < time);
+        let next = self.frames.get(next_index).or_else(|| self.frames.last())?;
+        let previous = next_index
+            .checked_sub(1)
+            .and_then(|index| self.frames.get(index))
+            .unwrap_or(next);
+        let duration = next.time - previous.time;
+        let factor = if duration > f32::EPSILON {
+            ((time - previous.time) / duration).clamp(0., 1.)
+        } else {
+            1.
+        };
+        Some(
+            Transform::from_translation(previous.translation.lerp(next.translation, factor))
+                .with_rotation(previous.rotation.slerp(next.rotation, factor)),
+        )
+    }
+}
+
+/// Entering the cube of this entity's transform records a split.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct SplitMarker {
+    pub name: String,
+}
+
+/// Replays the best run of the current level.
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Ghost;
+
+static SPLIT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[split:\s*([^\]]+?)\s*\]").expect("Failed to compile split regex")
+});
+
+fn toggle_speedrun(world: &mut World, args: &[&str]) -> Result<String> {
+    let enable = match args {
+        [] => !world.contains_resource::<Speedrun>(),
+        ["on"] => true,
+        ["off"] => false,
+        _ => bail!("Usage: speedrun [on|off]"),
+    };
+    if enable {
+        world.insert_resource(Speedrun::default());
+        Ok("Speedrun mode enabled".to_owned())
+    } else {
+        world.remove_resource::<Speedrun>();
+        Ok("Speedrun mode disabled".to_owned())
+    }
+}
+
+fn read_split_markers(mut commands: Commands, added_name: Query<(Entity, &Name), Added<Name>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_split_markers").entered();
+    for (entity, name) in added_name.iter() {
+        if let Some(captures) = SPLIT_REGEX.captures(&name.to_lowercase()) {
+            commands.entity(entity).insert(SplitMarker {
+                name: captures[1].to_owned(),
+            });
+        }
+    }
+}
+
+/// The ghost has the shape of the player, which may change with the config, so its mesh is replaced on every spawn.
+fn set_ghost_mesh(meshes: &mut Assets<Mesh>, config: &GameConfig) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x7b20c6f4e81a935d);
+    let handle = MESH_HANDLE.typed();
+    let body = &config.player.body;
+    meshes.set_untracked(
+        handle.clone_weak(),
+        Mesh::from(shape::Capsule {
+            radius: body.radius,
+            depth: body.height,
+            ..default()
+        }),
+    );
+    handle
+}
+
+fn get_or_add_ghost_material(materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0xc58e1d3a0f7b2649);
+    let handle = MATERIAL_HANDLE.typed();
+    materials.get_or_insert_with(handle.clone_weak(), || StandardMaterial {
+        base_color: Color::rgba(0.6, 0.8, 1.0, 0.3),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    handle
+}
+
+#[sysfail(log(level = "error"))]
+fn start_speedrun(
+    mut commands: Commands,
+    mut speedrun: ResMut<Speedrun>,
+    current_level: Option<Res<CurrentLevel>>,
+    ghosts: Query<Entity, With<Ghost>>,
+    config: Res<GameConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_speedrun").entered();
+    let Some(current_level) = current_level else {
+        return Ok(());
+    };
+    if !current_level.is_changed() && !speedrun.is_added() {
+        return Ok(());
+    }
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let path = get_ghost_path(&current_level.scene);
+    let best = if path.exists() {
+        let serialized = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read ghost at {}", path.to_string_lossy()))?;
+        Some(ron::from_str(&serialized).context("Failed to deserialize ghost")?)
+    } else {
+        None
+    };
+    *speedrun = Speedrun { best, ..default() };
+    if speedrun.best.is_none() {
+        return Ok(());
+    }
+    commands.spawn((
+        PbrBundle {
+            mesh: set_ghost_mesh(&mut meshes, &config),
+            material: get_or_add_ghost_material(&mut materials),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        NotShadowCaster,
+        Ghost,
+        Name::new("Speedrun Ghost"),
+    ));
+    Ok(())
+}
+
+fn update_speedrun(
+    time: Res<Time>,
+    mut speedrun: ResMut<Speedrun>,
+    players: Query<&GlobalTransform, With<Player>>,
+    split_markers: Query<(&SplitMarker, &GlobalTransform)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_speedrun").entered();
+    let Some(player) = players.iter().next() else {
+        // The run starts once the player has been spawned
+        return;
+    };
+    if speedrun.finished {
+        return;
+    }
+    let dt = time.delta_seconds();
+    speedrun.current.time += dt;
+    let now = speedrun.current.time;
+    speedrun.since_last_sample += dt;
+    if speedrun.since_last_sample >= GHOST_SAMPLE_INTERVAL || speedrun.current.frames.is_empty() {
+        speedrun.since_last_sample = 0.;
+        let (_scale, rotation, translation) = player.to_scale_rotation_translation();
+        speedrun.current.frames.push(GhostFrame {
+            time: now,
+            translation,
+            rotation,
+        });
+    }
+    let position = player.translation();
+    for (marker, transform) in split_markers.iter() {
+        if speedrun.current.split(&marker.name).is_some() {
+            continue;
+        }
+        let local = transform.affine().inverse().transform_point3(position);
+        if local.abs().max_element() <= 1. {
+            speedrun.current.splits.push(Split {
+                name: marker.name.clone(),
+                time: now,
+            });
+        }
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn finish_speedrun(mut speedrun: ResMut<Speedrun>, summary: Res<LevelSummary>) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("finish_speedrun").entered();
+    if speedrun.finished || speedrun.current.frames.is_empty() {
+        return Ok(());
+    }
+    speedrun.finished = true;
+    let is_best = speedrun
+        .best
+        .as_ref()
+        .map_or(true, |best| speedrun.current.time < best.time);
+    if !is_best {
+        return Ok(());
+    }
+    let serialized = ron::to_string(&speedrun.current).context("Failed to serialize ghost")?;
+    let path = get_ghost_path(&summary.level);
+    let dir = path.parent().context("Failed to get ghost directory")?;
+    fs::create_dir_all(dir).context("Failed to create ghost directory")?;
+    fs::write(&path, serialized).context("Failed to write ghost")?;
+    info!(
+        "New best run of {:.2} s saved at {}",
+        speedrun.current.time,
+        path.to_string_lossy()
+    );
+    Ok(())
+}
+
+fn update_ghost(
+    speedrun: Res<Speedrun>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), With<Ghost>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_ghost").entered();
+    let Some(best) = &speedrun.best else {
+        return;
+    };
+    for (mut transform, mut visibility) in ghosts.iter_mut() {
+        // The ghost disappears at the goal, or right away if the run has not started yet
+        let time = speedrun.current.time;
+        match best.sample(time) {
+            Some(sample) if time > 0. && time <= best.time => {
+                *transform = sample;
+                *visibility = Visibility::Inherited;
+            }
+            _ => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn show_speedrun_timer(speedrun: Res<Speedrun>, mut egui_contexts: EguiContexts) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_speedrun_timer").entered();
+    let best = speedrun.best.as_ref();
+    egui::Area::new("Speedrun Timer")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-20., 20.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.heading(format_time(speedrun.current.time));
+            if let Some(best) = best {
+                ui.label(format!("Best: {}", format_time(best.time)));
+            }
+            for split in &speedrun.current.splits {
+                let delta = best
+                    .and_then(|best| best.split(&split.name))
+                    .map(|best_split| {
+                        let delta = split.time - best_split.time;
+                        let sign = if delta > 0. { "+" } else { "-" };
+                        format!(" ({sign}{:.2})", delta.abs())
+                    })
+                    .unwrap_or_default();
+                ui.label(format!(
+                    "{}: {}{delta}",
+                    split.name,
+                    format_time(split.time)
+                ));
+            }
+        });
+}
+
+fn despawn_ghosts(mut commands: Commands, ghosts: Query<Entity, With<Ghost>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("despawn_ghosts").entered();
+    for entity in ghosts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    let minutes = (seconds / 60.).floor();
+    format!("{minutes:02}:{:05.2}", seconds - minutes * 60.)
+}
+
+fn get_ghost_path(level: &str) -> PathBuf {
+    Path::new("saves")
+        .join("ghosts")
+        .join(level)
+        .with_extension("ghost.ron")
+}
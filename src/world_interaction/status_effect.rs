@@ -0,0 +1,320 @@
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::health::Health;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::{egui, EguiContexts};
+use serde::{Deserialize, Serialize};
+
+/// Number of effects of the same kind a character can have at once when they [`Stacking::Stack`].
+const MAX_STACKS: usize = 3;
+/// Side length in points of the icons of the player's effects.
+const ICON_SIZE: f32 = 28.;
+
+/// Handles temporary effects on characters, which are kept in their [`StatusEffects`].
+/// Effects are given by sending an [`ApplyStatusEffect`], e.g. by consumables and shrines, and wear off once their duration is over.
+/// Getting an effect of a kind the character already has follows the effect's [`Stacking`] rule.
+/// [`StatusEffectKind::Speed`] scales how fast characters accelerate, [`StatusEffectKind::JumpBoost`] how fast they jump off,
+/// and [`StatusEffectKind::Regeneration`] heals their [`Health`] every second.
+/// The player's effects are shown as icons below the health bar, each filling up a ring with its remaining time.
+pub fn status_effect_plugin(app: &mut App) {
+    app.register_type::<StatusEffects>()
+        .register_type::<ActiveStatusEffect>()
+        .register_type::<StatusEffect>()
+        .register_type::<StatusEffectKind>()
+        .register_type::<Stacking>()
+        .add_event::<ApplyStatusEffect>()
+        .add_systems(
+            (
+                apply_status_effects,
+                tick_status_effects,
+                show_status_effects,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    /// Multiplies the walking acceleration by the strength
+    Speed,
+    /// Multiplies the jump speed by the strength
+    JumpBoost,
+    /// Heals the strength in health per second
+    Regeneration,
+}
+
+/// What happens when a character gets an effect of a kind they already have.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum Stacking {
+    /// Keeps a single effect with the higher strength and restarts its duration
+    #[default]
+    Refresh,
+    /// Keeps a single effect with the higher strength and adds the durations up
+    Extend,
+    /// Keeps the effects side by side so their strengths add up, up to [`MAX_STACKS`] of them.
+    /// A further one replaces the one closest to wearing off.
+    Stack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub strength: f32,
+    /// Time in seconds the effect lasts
+    pub duration: f32,
+    #[serde(default)]
+    pub stacking: Stacking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub struct ActiveStatusEffect {
+    pub effect: StatusEffect,
+    /// Time in seconds until the effect wears off
+    pub remaining: f32,
+}
+
+impl ActiveStatusEffect {
+    /// Fraction of the effect's duration that is left.
+    pub fn fraction(&self) -> f32 {
+        if self.effect.duration > 0. {
+            (self.remaining / self.effect.duration).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+}
+
+/// The effects a character is currently under.
+#[derive(Debug, Clone, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct StatusEffects {
+    pub active: Vec<ActiveStatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        if effect.stacking == Stacking::Stack {
+            self.stack(effect);
+            return;
+        }
+        // Stacked effects of the kind are left to wear off on their own
+        let Some(active) = self.active.iter_mut().find(|active| {
+            active.effect.kind == effect.kind && active.effect.stacking != Stacking::Stack
+        }) else {
+            self.push(effect);
+            return;
+        };
+        active.effect.strength = active.effect.strength.max(effect.strength);
+        active.remaining = match effect.stacking {
+            Stacking::Extend => active.remaining + effect.duration,
+            _ => active.remaining.max(effect.duration),
+        };
+        // The icon shows the renewed effect as fresh
+        active.effect.duration = active.remaining;
+    }
+
+    fn stack(&mut self, effect: StatusEffect) {
+        let is_stack = |active: &ActiveStatusEffect| {
+            active.effect.kind == effect.kind && active.effect.stacking == Stacking::Stack
+        };
+        let stacks = self.active.iter().filter(|active| is_stack(active)).count();
+        if stacks >= MAX_STACKS {
+            let closest_to_wearing_off = self
+                .active
+                .iter()
+                .enumerate()
+                .filter(|(_, active)| is_stack(active))
+                .min_by(|(_, a), (_, b)| a.remaining.total_cmp(&b.remaining))
+                .map(|(index, _)| index);
+            if let Some(index) = closest_to_wearing_off {
+                self.active.remove(index);
+            }
+        }
+        self.push(effect);
+    }
+
+    fn push(&mut self, effect: StatusEffect) {
+        self.active.push(ActiveStatusEffect {
+            effect,
+            remaining: effect.duration,
+        });
+    }
+
+    /// Factor by which the effects of the kind scale a value, `1` without any.
+    /// What the effects add on top of `1` adds up, so two effects of strength `1.5` make a factor of `2`.
+    pub fn factor(&self, kind: StatusEffectKind) -> f32 {
+        1. + self
+            .of_kind(kind)
+            .map(|effect| effect.strength - 1.)
+            .sum::<f32>()
+    }
+
+    /// Combined strength of the effects of the kind, `0` without any.
+    pub fn sum(&self, kind: StatusEffectKind) -> f32 {
+        self.of_kind(kind).map(|effect| effect.strength).sum()
+    }
+
+    fn of_kind(&self, kind: StatusEffectKind) -> impl Iterator<Item = &StatusEffect> {
+        self.active
+            .iter()
+            .map(|active| &active.effect)
+            .filter(move |effect| effect.kind == kind)
+    }
+}
+
+/// Send this to give an entity an effect. Entities without [`StatusEffects`] get them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApplyStatusEffect {
+    pub target: Entity,
+    pub effect: StatusEffect,
+}
+
+fn apply_status_effects(
+    mut commands: Commands,
+    mut apply_events: EventReader<ApplyStatusEffect>,
+    mut characters: Query<&mut StatusEffects>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_status_effects").entered();
+    // Entities without effects yet get all of this frame's effects at once
+    let mut new_status_effects: HashMap<Entity, StatusEffects> = default();
+    for event in apply_events.iter() {
+        match characters.get_mut(event.target) {
+            Ok(mut status_effects) => status_effects.apply(event.effect),
+            Err(_) => new_status_effects
+                .entry(event.target)
+                .or_default()
+                .apply(event.effect),
+        }
+    }
+    for (entity, status_effects) in new_status_effects {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.insert(status_effects);
+        }
+    }
+}
+
+fn tick_status_effects(
+    time: Res<Time>,
+    mut characters: Query<(&mut StatusEffects, Option<&mut Health>)>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("tick_status_effects").entered();
+    let dt = time.delta_seconds();
+    for (mut status_effects, health) in characters.iter_mut() {
+        if status_effects.active.is_empty() {
+            continue;
+        }
+        let regeneration = status_effects.sum(StatusEffectKind::Regeneration);
+        if let Some(mut health) = health {
+            if regeneration > 0. && !health.is_dead() && health.current < health.max {
+                health.current = (health.current + regeneration * dt).min(health.max);
+            }
+        }
+        for active in status_effects.active.iter_mut() {
+            active.remaining -= dt;
+        }
+        status_effects.active.retain(|active| active.remaining > 0.);
+    }
+}
+
+fn show_status_effects(
+    players: Query<&StatusEffects, With<Player>>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_status_effects").entered();
+    let Some(status_effects) = players.iter().next() else {
+        return;
+    };
+    if status_effects.active.is_empty() {
+        return;
+    }
+    egui::Area::new("Status Effects")
+        // Below the health bar
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(20., 50.))
+        .interactable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for active in &status_effects.active {
+                    ui.vertical(|ui| {
+                        draw_icon(ui, active);
+                        ui.small(format!("{:.0}s", active.remaining.ceil()));
+                    });
+                }
+            });
+        });
+}
+
+/// Draws a symbol of the effect's kind surrounded by a ring that empties as the effect wears off.
+fn draw_icon(ui: &mut egui::Ui, active: &ActiveStatusEffect) {
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(ICON_SIZE), egui::Sense::hover());
+    let painter = ui.painter();
+    let center = rect.center();
+    let radius = ICON_SIZE / 2.;
+    let color = match active.effect.kind {
+        StatusEffectKind::Speed => egui::Color32::from_rgb(80, 170, 240),
+        StatusEffectKind::JumpBoost => egui::Color32::from_rgb(120, 210, 90),
+        StatusEffectKind::Regeneration => egui::Color32::from_rgb(230, 80, 110),
+    };
+    painter.circle_filled(center, radius, egui::Color32::from_black_alpha(160));
+
+    let segments = 32;
+    let filled = (active.fraction() * segments as f32).ceil() as usize;
+    let ring: Vec<_> = (0..=filled)
+        .map(|segment| {
+            // Starts at the top and runs clockwise
+            let angle = std::f32::consts::TAU * segment as f32 / segments as f32
+                - std::f32::consts::FRAC_PI_2;
+            center + (radius - 2.) * egui::Vec2::angled(angle)
+        })
+        .collect();
+    if ring.len() > 1 {
+        painter.add(egui::Shape::line(ring, egui::Stroke::new(3., color)));
+    }
+
+    let stroke = egui::Stroke::new(2.5, color);
+    let size = radius * 0.45;
+    match active.effect.kind {
+        StatusEffectKind::Speed => {
+            // Two chevrons pointing forward
+            for offset in [-size * 0.5, size * 0.5] {
+                let tip = center + egui::vec2(offset + size * 0.4, 0.);
+                painter.line_segment([tip + egui::vec2(-size * 0.6, -size), tip], stroke);
+                painter.line_segment([tip + egui::vec2(-size * 0.6, size), tip], stroke);
+            }
+        }
+        StatusEffectKind::JumpBoost => {
+            // An arrow pointing up
+            let tip = center + egui::vec2(0., -size);
+            painter.line_segment([center + egui::vec2(0., size), tip], stroke);
+            painter.line_segment([tip + egui::vec2(-size * 0.7, size * 0.7), tip], stroke);
+            painter.line_segment([tip + egui::vec2(size * 0.7, size * 0.7), tip], stroke);
+        }
+        StatusEffectKind::Regeneration => {
+            // A cross
+            painter.line_segment(
+                [
+                    center + egui::vec2(0., -size),
+                    center + egui::vec2(0., size),
+                ],
+                stroke,
+            );
+            painter.line_segment(
+                [
+                    center + egui::vec2(-size, 0.),
+                    center + egui::vec2(size, 0.),
+                ],
+                stroke,
+            );
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::file_system_interaction::audio::{PlaySoundEvent, Sound};
+use crate::movement::general_movement::Model;
+use crate::player_control::actions::ActionsFrozen;
+use crate::player_control::player_embodiment::Player;
+use crate::shader::Materials;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_rapier3d::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use std::sync::LazyLock;
+
+/// Time in seconds for which player input is ignored after teleporting.
+const INPUT_LOCK_DURATION: f32 = 0.3;
+const FLOURISH_DURATION: f32 = 0.5;
+const FLOURISH_SIZE: f32 = 1.5;
+
+/// Handles teleporter pads. Pads are linked by a `[teleporter: <link>]` marker in their name, which works both for glTF nodes
+/// and for entities renamed in the editor. Stepping on a pad moves the player to the next pad with the same link,
+/// rotating their velocity and orientation by the difference between the pads' orientations.
+pub fn teleporter_plugin(app: &mut App) {
+    app.register_type::<Teleporter>().add_systems(
+        (
+            read_teleporter_links,
+            teleport_players,
+            release_input_lock,
+            animate_flourishes,
+        )
+            .chain()
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Teleporter {
+    pub link: String,
+}
+
+/// Present on the player until they step off the pad they arrived on, so that they don't get teleported straight back.
+#[derive(Debug, Clone, Eq, PartialEq, Component)]
+pub struct RecentlyTeleported {
+    pub destination: Entity,
+}
+
+#[derive(Debug, Clone, Component)]
+struct TeleportInputLock(Timer);
+
+#[derive(Debug, Clone, Component)]
+struct TeleportFlourish(Timer);
+
+static TELEPORTER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[teleporter:\s*([^\]]+?)\s*\]").expect("Failed to compile teleporter regex")
+});
+
+fn read_teleporter_links(
+    mut commands: Commands,
+    changed_name: Query<(Entity, &Name), Changed<Name>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_teleporter_links").entered();
+    for (entity, name) in changed_name.iter() {
+        if let Some(captures) = TELEPORTER_REGEX.captures(&name.to_lowercase()) {
+            commands.entity(entity).insert(Teleporter {
+                link: captures[1].to_owned(),
+            });
+        }
+    }
+}
+
+fn get_or_add_flourish_mesh(meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x3e5f07a9c2d18b46);
+    let handle = MESH_HANDLE.typed();
+    meshes.get_or_insert_with(handle.clone_weak(), || {
+        Mesh::from(shape::UVSphere {
+            radius: 1.0,
+            ..default()
+        })
+    });
+    handle
+}
+
+fn teleport_players(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut players: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            Option<&RecentlyTeleported>,
+            Option<&TeleportInputLock>,
+        ),
+        With<Player>,
+    >,
+    mut models: Query<(&Model, &mut Transform), Without<Player>>,
+    parents: Query<&Parent>,
+    teleporters: Query<(Entity, &Teleporter, &GlobalTransform)>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+    mut sound_events: EventWriter<PlaySoundEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    materials: Res<Materials>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("teleport_players").entered();
+    for event in collision_events.iter() {
+        let (entity_a, entity_b, started) = match event {
+            CollisionEvent::Started(a, b, _) => (*a, *b, true),
+            CollisionEvent::Stopped(a, b, _) => (*a, *b, false),
+        };
+        let (player, pad) = if players.contains(entity_a) {
+            (entity_a, entity_b)
+        } else if players.contains(entity_b) {
+            (entity_b, entity_a)
+        } else {
+            continue;
+        };
+        let pad = parents.get(pad).map(|parent| parent.get()).unwrap_or(pad);
+        let Ok((_, teleporter, source_transform)) = teleporters.get(pad) else {
+            continue;
+        };
+        let Ok((_, mut transform, mut velocity, recently_teleported, input_lock)) =
+            players.get_mut(player)
+        else {
+            continue;
+        };
+        let arrived_here = recently_teleported
+            .map(|recently_teleported| recently_teleported.destination == pad)
+            .unwrap_or_default();
+        if !started {
+            if arrived_here {
+                commands.entity(player).remove::<RecentlyTeleported>();
+            }
+            continue;
+        }
+        if arrived_here {
+            continue;
+        }
+
+        // Cycle through all pads with the same link, ordered by entity
+        let mut linked: Vec<_> = teleporters
+            .iter()
+            .filter(|(_, other, _)| other.link == teleporter.link)
+            .map(|(entity, _, transform)| (entity, *transform))
+            .collect();
+        linked.sort_by_key(|(entity, _)| *entity);
+        let Some(index) = linked.iter().position(|(entity, _)| *entity == pad) else {
+            continue;
+        };
+        let (destination, destination_transform) = linked[(index + 1) % linked.len()];
+        if destination == pad {
+            continue;
+        }
+
+        let (_, source_rotation, source_translation) =
+            source_transform.to_scale_rotation_translation();
+        let (_, destination_rotation, destination_translation) =
+            destination_transform.to_scale_rotation_translation();
+        let rotation = destination_rotation * source_rotation.inverse();
+        let offset = transform.translation - source_translation;
+        transform.translation = destination_translation + rotation * offset;
+        transform.rotation = rotation * transform.rotation;
+        velocity.linvel = rotation * velocity.linvel;
+        // Don't let the model smoothly fly over to the new position
+        for (model, mut model_transform) in models.iter_mut() {
+            if model.target == player {
+                *model_transform = *transform;
+            }
+        }
+
+        commands
+            .entity(player)
+            .insert(RecentlyTeleported { destination });
+        if input_lock.is_none() {
+            actions_frozen.freeze();
+        }
+        commands
+            .entity(player)
+            .insert(TeleportInputLock(Timer::from_seconds(
+                INPUT_LOCK_DURATION,
+                TimerMode::Once,
+            )));
+        sound_events.send(PlaySoundEvent::at(Sound::Teleport, player));
+        for translation in [source_translation, destination_translation] {
+            commands.spawn((
+                MaterialMeshBundle {
+                    mesh: get_or_add_flourish_mesh(&mut meshes),
+                    material: materials.glowy.clone(),
+                    transform: Transform::from_translation(translation).with_scale(Vec3::ZERO),
+                    ..default()
+                },
+                Name::new("Teleport Flourish"),
+                TeleportFlourish(Timer::from_seconds(FLOURISH_DURATION, TimerMode::Once)),
+            ));
+        }
+    }
+}
+
+fn release_input_lock(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut players: Query<(Entity, &mut TeleportInputLock)>,
+    mut actions_frozen: ResMut<ActionsFrozen>,
+) {
+    for (entity, mut input_lock) in players.iter_mut() {
+        if input_lock.0.tick(time.delta()).just_finished() {
+            actions_frozen.unfreeze();
+            commands.entity(entity).remove::<TeleportInputLock>();
+        }
+    }
+}
+
+fn animate_flourishes(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut flourishes: Query<(Entity, &mut TeleportFlourish, &mut Transform)>,
+) {
+    for (entity, mut flourish, mut transform) in flourishes.iter_mut() {
+        flourish.0.tick(time.delta());
+        if flourish.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Grow and shrink again
+        let size = (flourish.0.percent() * PI).sin() * FLOURISH_SIZE;
+        transform.scale = Vec3::splat(size);
+    }
+}
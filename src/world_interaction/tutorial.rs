@@ -0,0 +1,166 @@
+use crate::file_system_interaction::asset_loading::TutorialAssets;
+use crate::file_system_interaction::player_profile::PlayerProfile;
+use crate::movement::general_movement::Walking;
+use crate::player_control::actions::PlayerAction;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::carrying::Carrying;
+use crate::world_interaction::condition::{ActiveConditions, ConditionId};
+use crate::world_interaction::dialog::CurrentDialog;
+use crate::world_interaction::interactions_ui::InteractionUi;
+use crate::GameState;
+use anyhow::{Context, Result};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy_egui::{egui, EguiContexts};
+use bevy_mod_sysfail::macros::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// Shows contextual tutorial popups such as "Press Space to jump" the first time their
+/// [`TutorialTrigger`] applies. The popups are authored in `assets/tutorials/tutorials.tut.ron`
+/// and every shown popup is recorded in the [`PlayerProfile`] so that it is never shown again.
+pub fn tutorial_plugin(app: &mut App) {
+    app.add_systems(
+        (trigger_tutorials, show_tutorial_popup)
+            .chain()
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid, Default, Deref, DerefMut)]
+#[uuid = "2b9e6cf4-8a1d-4f0e-9d55-3c1f7b0e6a42"]
+pub struct Tutorials(pub Vec<Tutorial>);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tutorial {
+    /// Key under which the tutorial is remembered in the [`PlayerProfile`].
+    pub id: String,
+    pub text: String,
+    pub trigger: TutorialTrigger,
+    /// Tutorials that need to have been shown before this one can trigger.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    /// Closes the popup early once this action is pressed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dismiss_on: Option<PlayerAction>,
+    /// Seconds for which the popup is shown.
+    #[serde(default = "default_duration")]
+    pub duration: f32,
+}
+
+fn default_duration() -> f32 {
+    6.0
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TutorialTrigger {
+    /// The player has been spawned.
+    Spawned,
+    /// The player is walking in any direction.
+    Walking,
+    /// The player is standing in front of something they can interact with.
+    InteractionAvailable,
+    /// A dialog is running.
+    DialogStarted,
+    /// The player is carrying a prop.
+    Carrying,
+    /// The given condition has been set, e.g. by a dialog choice.
+    Condition(ConditionId),
+}
+
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct TutorialPopup {
+    pub id: String,
+    pub text: String,
+    pub dismiss_on: Option<PlayerAction>,
+    pub timer: Timer,
+}
+
+#[sysfail(log(level = "error"))]
+fn trigger_tutorials(
+    mut commands: Commands,
+    tutorial_assets: Res<TutorialAssets>,
+    tutorials: Res<Assets<Tutorials>>,
+    mut profile: ResMut<PlayerProfile>,
+    popup: Option<Res<TutorialPopup>>,
+    player_query: Query<(&Walking, Option<&Carrying>), With<Player>>,
+    interaction_ui: Option<Res<InteractionUi>>,
+    current_dialog: Option<Res<CurrentDialog>>,
+    active_conditions: Res<ActiveConditions>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("trigger_tutorials").entered();
+    if popup.is_some() {
+        return Ok(());
+    }
+    let Some((walking, carrying)) = player_query.iter().next() else {
+        return Ok(());
+    };
+    let tutorials = tutorials
+        .get(&tutorial_assets.tutorials)
+        .context("Failed to get tutorials from handle")?;
+
+    let is_triggered = |trigger: &TutorialTrigger| match trigger {
+        TutorialTrigger::Spawned => true,
+        TutorialTrigger::Walking => walking.direction.is_some(),
+        TutorialTrigger::InteractionAvailable => interaction_ui.is_some(),
+        TutorialTrigger::DialogStarted => current_dialog.is_some(),
+        TutorialTrigger::Carrying => carrying.is_some(),
+        TutorialTrigger::Condition(condition) => active_conditions.0.contains(condition),
+    };
+    let Some(tutorial) = tutorials.iter().find(|tutorial| {
+        !profile.has_seen_tutorial(&tutorial.id)
+            && tutorial
+                .requires
+                .iter()
+                .all(|id| profile.has_seen_tutorial(id))
+            && is_triggered(&tutorial.trigger)
+    }) else {
+        return Ok(());
+    };
+
+    // Marked as seen right away so that quitting while the popup is open doesn't bring it back
+    profile.seen_tutorials.insert(tutorial.id.clone());
+    commands.insert_resource(TutorialPopup {
+        id: tutorial.id.clone(),
+        text: tutorial.text.clone(),
+        dismiss_on: tutorial.dismiss_on,
+        timer: Timer::from_seconds(tutorial.duration, TimerMode::Once),
+    });
+    Ok(())
+}
+
+fn show_tutorial_popup(
+    mut commands: Commands,
+    time: Res<Time>,
+    popup: Option<ResMut<TutorialPopup>>,
+    actions_query: Query<&ActionState<PlayerAction>, With<Player>>,
+    mut egui_contexts: EguiContexts,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("show_tutorial_popup").entered();
+    let Some(mut popup) = popup else {
+        return;
+    };
+    let is_dismissed = popup
+        .dismiss_on
+        .map(|action| {
+            actions_query
+                .iter()
+                .any(|actions| actions.just_pressed(action))
+        })
+        .unwrap_or_default();
+    if popup.timer.tick(time.delta()).finished() || is_dismissed {
+        commands.remove_resource::<TutorialPopup>();
+        return;
+    }
+
+    egui::Window::new("Tutorial")
+        .id(egui::Id::new(("Tutorial", &popup.id)))
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::Vec2::new(0., 40.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(&popup.text);
+        });
+}
@@ -0,0 +1,424 @@
+use crate::level_instantiation::spawning::metadata::ObjectMetadata;
+use crate::movement::general_movement::{reset_forces_and_impulses, CharacterControllerSystemSet};
+use crate::movement::gravity::Gravity;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_mod_sysfail::macros::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Metadata key of a volume's shape and size in m, e.g. `box 4, 2, 4`, `sphere 3` or `cylinder 2, 1.5` for height and radius.
+pub const VOLUME_SHAPE_KEY: &str = "shape";
+/// Metadata key of what a volume is used for, e.g. `water`.
+pub const VOLUME_KIND_KEY: &str = "kind";
+
+/// Smallest extent in m a volume can be resized to.
+pub const MIN_VOLUME_EXTENT: f32 = 0.1;
+
+/// Upwards acceleration of bodies in water as a multiple of the gravity, so that they slowly float up.
+const WATER_BUOYANCY: f32 = 1.2;
+/// Fraction of their velocity per second that bodies in water lose.
+const WATER_DRAG: f32 = 2.;
+/// Acceleration in m/s² of bodies in wind.
+const WIND_ACCELERATION: f32 = 8.;
+
+/// Handles [`Volume`]s, which are invisible sensor regions of adjustable shape and size, e.g. triggers, water or wind.
+/// Their shape and kind are stored in their [`ObjectMetadata`] under [`VOLUME_SHAPE_KEY`] and [`VOLUME_KIND_KEY`],
+/// and kept in sync in both directions like those of splines. The collider follows the shape.
+/// Whenever something enters or leaves a volume, a [`VolumeEvent`] is sent, which other systems filter by [`VolumeKind`].
+/// The events keep track of the [`VolumeOccupants`], which water carries up and slows down, and which wind pushes along the volume's forward direction.
+pub fn volume_plugin(app: &mut App) {
+    app.register_type::<Volume>()
+        .register_type::<VolumeShape>()
+        .register_type::<VolumeKind>()
+        .add_event::<VolumeEvent>()
+        .add_systems(
+            (
+                read_volume_metadata,
+                write_volume_metadata,
+                update_volume_colliders,
+                send_volume_events,
+                track_volume_occupants,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_system(
+            apply_volume_forces
+                .after(reset_forces_and_impulses)
+                .in_set(CharacterControllerSystemSet)
+                .in_set(OnUpdate(GameState::Playing)),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Volume {
+    pub shape: VolumeShape,
+    pub kind: VolumeKind,
+}
+
+/// Shape of a [`Volume`] in its local space, centered on its origin.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect, Serialize, Deserialize)]
+#[reflect(Serialize, Deserialize)]
+pub enum VolumeShape {
+    /// Size in m along each axis
+    Box {
+        size: Vec3,
+    },
+    Sphere {
+        radius: f32,
+    },
+    /// Upright along the y axis
+    Cylinder {
+        height: f32,
+        radius: f32,
+    },
+}
+
+impl Default for VolumeShape {
+    fn default() -> Self {
+        Self::Box {
+            size: Vec3::splat(2.),
+        }
+    }
+}
+
+impl VolumeShape {
+    pub fn collider(self) -> Collider {
+        match self {
+            VolumeShape::Box { size } => Collider::cuboid(size.x / 2., size.y / 2., size.z / 2.),
+            VolumeShape::Sphere { radius } => Collider::ball(radius),
+            VolumeShape::Cylinder { height, radius } => Collider::cylinder(height / 2., radius),
+        }
+    }
+
+    /// Keeps every extent at least [`MIN_VOLUME_EXTENT`].
+    pub fn clamped(self) -> Self {
+        let clamp = |extent: f32| extent.max(MIN_VOLUME_EXTENT);
+        match self {
+            VolumeShape::Box { size } => VolumeShape::Box {
+                size: size.max(Vec3::splat(MIN_VOLUME_EXTENT)),
+            },
+            VolumeShape::Sphere { radius } => VolumeShape::Sphere {
+                radius: clamp(radius),
+            },
+            VolumeShape::Cylinder { height, radius } => VolumeShape::Cylinder {
+                height: clamp(height),
+                radius: clamp(radius),
+            },
+        }
+    }
+
+    /// Half the size of the box around the shape.
+    pub fn half_extents(self) -> Vec3 {
+        match self {
+            VolumeShape::Box { size } => size / 2.,
+            VolumeShape::Sphere { radius } => Vec3::splat(radius),
+            VolumeShape::Cylinder { height, radius } => Vec3::new(radius, height / 2., radius),
+        }
+    }
+
+    /// Whether the point, given in the volume's local space, lies inside the shape.
+    pub fn contains(self, point: Vec3) -> bool {
+        match self {
+            VolumeShape::Box { size } => point.abs().cmple(size / 2.).all(),
+            VolumeShape::Sphere { radius } => point.length_squared() <= radius * radius,
+            VolumeShape::Cylinder { height, radius } => {
+                point.y.abs() <= height / 2.
+                    && Vec2::new(point.x, point.z).length_squared() <= radius * radius
+            }
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VolumeShape::Box { .. } => "box",
+            VolumeShape::Sphere { .. } => "sphere",
+            VolumeShape::Cylinder { .. } => "cylinder",
+        }
+    }
+
+    /// The shape of another kind that roughly covers the same space.
+    pub fn converted(self, name: &str) -> Result<Self> {
+        let (width, height) = match self {
+            VolumeShape::Box { size } => (size.x.max(size.z), size.y),
+            VolumeShape::Sphere { radius } => (radius * 2., radius * 2.),
+            VolumeShape::Cylinder { height, radius } => (radius * 2., height),
+        };
+        Ok(match name {
+            "box" => VolumeShape::Box {
+                size: Vec3::new(width, height, width),
+            },
+            "sphere" => VolumeShape::Sphere {
+                radius: width.max(height) / 2.,
+            },
+            "cylinder" => VolumeShape::Cylinder {
+                height,
+                radius: width / 2.,
+            },
+            _ => bail!("Unknown volume shape \"{name}\""),
+        })
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let (name, sizes) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+        let sizes = sizes
+            .split(',')
+            .map(str::trim)
+            .filter(|size| !size.is_empty())
+            .map(|size| {
+                size.parse::<f32>()
+                    .with_context(|| format!("Failed to parse size \"{size}\""))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let shape = match (name, sizes.as_slice()) {
+            ("box", [x, y, z]) => VolumeShape::Box {
+                size: Vec3::new(*x, *y, *z),
+            },
+            ("sphere", [radius]) => VolumeShape::Sphere { radius: *radius },
+            ("cylinder", [height, radius]) => VolumeShape::Cylinder {
+                height: *height,
+                radius: *radius,
+            },
+            _ => {
+                bail!("Expected \"box x, y, z\", \"sphere radius\" or \"cylinder height, radius\"")
+            }
+        };
+        Ok(shape.clamped())
+    }
+
+    fn format(self) -> String {
+        match self {
+            VolumeShape::Box { size } => format!("box {}, {}, {}", size.x, size.y, size.z),
+            VolumeShape::Sphere { radius } => format!("sphere {radius}"),
+            VolumeShape::Cylinder { height, radius } => format!("cylinder {height}, {radius}"),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, Reflect, FromReflect, Serialize, Deserialize, Default,
+)]
+#[reflect(Serialize, Deserialize)]
+pub enum VolumeKind {
+    #[default]
+    Trigger,
+    /// Carries dynamic bodies up and slows them down
+    Water,
+    /// Pushes dynamic bodies along the volume's forward direction
+    Wind,
+    /// Switches the camera to a side view that stays within the volume's bounds
+    CameraRoom,
+    /// Launches bodies that enter it like a bounce pad, see [`BouncePad`](crate::world_interaction::bounce_pad::BouncePad)
+    Launch,
+    /// Hurts characters inside it like a hazard, e.g. lava, see [`Damage`](crate::world_interaction::health::Damage)
+    Hazard,
+    /// Bounds the props that are reset when a timed challenge starts, see [`ChallengeGate`](crate::world_interaction::challenge::ChallengeGate)
+    Challenge,
+}
+
+impl VolumeKind {
+    pub const ALL: [VolumeKind; 7] = [
+        VolumeKind::Trigger,
+        VolumeKind::Water,
+        VolumeKind::Wind,
+        VolumeKind::CameraRoom,
+        VolumeKind::Launch,
+        VolumeKind::Hazard,
+        VolumeKind::Challenge,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VolumeKind::Trigger => "trigger",
+            VolumeKind::Water => "water",
+            VolumeKind::Wind => "wind",
+            VolumeKind::CameraRoom => "camera_room",
+            VolumeKind::Launch => "launch",
+            VolumeKind::Hazard => "hazard",
+            VolumeKind::Challenge => "challenge",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        let value = value.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == value)
+            .with_context(|| format!("Unknown volume kind \"{value}\""))
+    }
+}
+
+/// Sent when a collider starts or stops touching a [`Volume`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VolumeEvent {
+    pub volume: Entity,
+    pub kind: VolumeKind,
+    /// The collider that entered or left
+    pub entity: Entity,
+    pub entered: bool,
+}
+
+/// The colliders that are currently inside a [`Volume`].
+/// Not saved, as they are found again by the collisions after loading.
+#[derive(Debug, Clone, PartialEq, Eq, Component, Default)]
+pub struct VolumeOccupants(pub HashSet<Entity>);
+
+impl Volume {
+    fn read_metadata(&mut self, metadata: &ObjectMetadata) -> Result<()> {
+        if let Some(shape) = metadata.get(VOLUME_SHAPE_KEY) {
+            self.shape = VolumeShape::parse(shape)
+                .with_context(|| format!("Failed to parse volume shape \"{shape}\""))?;
+        }
+        if let Some(kind) = metadata.get(VOLUME_KIND_KEY) {
+            self.kind = VolumeKind::parse(kind)?;
+        }
+        Ok(())
+    }
+
+    fn write_metadata(&self, metadata: &mut ObjectMetadata) {
+        metadata.insert(VOLUME_SHAPE_KEY, self.shape.format());
+        metadata.insert(VOLUME_KIND_KEY, self.kind.name());
+    }
+}
+
+#[sysfail(log(level = "error"))]
+fn read_volume_metadata(
+    mut volumes: Query<(&ObjectMetadata, &mut Volume), Changed<ObjectMetadata>>,
+) -> Result<()> {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("read_volume_metadata").entered();
+    for (metadata, mut volume) in volumes.iter_mut() {
+        let mut read = *volume;
+        read.read_metadata(metadata)?;
+        if read != *volume {
+            *volume = read;
+        }
+    }
+    Ok(())
+}
+
+fn write_volume_metadata(mut volumes: Query<(&Volume, &mut ObjectMetadata), Changed<Volume>>) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("write_volume_metadata").entered();
+    for (volume, mut metadata) in volumes.iter_mut() {
+        let mut written = metadata.clone();
+        volume.write_metadata(&mut written);
+        if written != *metadata {
+            *metadata = written;
+        }
+    }
+}
+
+fn update_volume_colliders(
+    mut commands: Commands,
+    volumes: Query<(Entity, &Volume), Changed<Volume>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_volume_colliders").entered();
+    for (entity, volume) in volumes.iter() {
+        commands
+            .entity(entity)
+            .insert(volume.shape.clamped().collider());
+    }
+}
+
+fn send_volume_events(
+    mut collision_events: EventReader<CollisionEvent>,
+    volumes: Query<&Volume>,
+    mut volume_events: EventWriter<VolumeEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("send_volume_events").entered();
+    for event in collision_events.iter() {
+        let (a, b, entered) = match event {
+            CollisionEvent::Started(a, b, _) => (*a, *b, true),
+            CollisionEvent::Stopped(a, b, _) => (*a, *b, false),
+        };
+        for (volume, entity) in [(a, b), (b, a)] {
+            if let Ok(Volume { kind, .. }) = volumes.get(volume) {
+                volume_events.send(VolumeEvent {
+                    volume,
+                    kind: *kind,
+                    entity,
+                    entered,
+                });
+            }
+        }
+    }
+}
+
+fn track_volume_occupants(
+    mut commands: Commands,
+    mut volume_events: EventReader<VolumeEvent>,
+    mut volumes: Query<Option<&mut VolumeOccupants>, With<Volume>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("track_volume_occupants").entered();
+    for event in volume_events.iter() {
+        let Ok(occupants) = volumes.get_mut(event.volume) else {
+            continue;
+        };
+        match (occupants, event.entered) {
+            (Some(mut occupants), true) => {
+                occupants.0.insert(event.entity);
+            }
+            (Some(mut occupants), false) => {
+                occupants.0.remove(&event.entity);
+            }
+            (None, true) => {
+                commands
+                    .entity(event.volume)
+                    .insert(VolumeOccupants(HashSet::from_iter([event.entity])));
+            }
+            (None, false) => {}
+        }
+    }
+}
+
+fn apply_volume_forces(
+    mut commands: Commands,
+    gravity: Res<Gravity>,
+    volumes: Query<(&Volume, &VolumeOccupants, &GlobalTransform)>,
+    mut bodies: Query<(
+        &Velocity,
+        &ReadMassProperties,
+        &RigidBody,
+        Option<&mut ExternalForce>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_volume_forces").entered();
+    for (volume, occupants, transform) in volumes.iter() {
+        for &entity in occupants.0.iter() {
+            let Ok((velocity, mass, rigid_body, force)) = bodies.get_mut(entity) else {
+                continue;
+            };
+            if *rigid_body != RigidBody::Dynamic {
+                continue;
+            }
+            let acceleration = match volume.kind {
+                VolumeKind::Water => -gravity.0 * WATER_BUOYANCY - velocity.linvel * WATER_DRAG,
+                VolumeKind::Wind => {
+                    let (_, rotation, _) = transform.to_scale_rotation_translation();
+                    rotation * Vec3::NEG_Z * WIND_ACCELERATION
+                }
+                _ => continue,
+            };
+            let additional_force = acceleration * mass.0.mass;
+            match force {
+                Some(mut force) => force.force += additional_force,
+                None => {
+                    commands.entity(entity).insert(ExternalForce {
+                        force: additional_force,
+                        ..default()
+                    });
+                }
+            }
+        }
+    }
+}
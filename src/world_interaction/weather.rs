@@ -0,0 +1,118 @@
+use crate::console::AddConsoleCommandExt;
+use crate::util::smoothness_to_lerp_factor;
+use crate::GameState;
+use anyhow::{bail, Result};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Time in seconds it roughly takes the lights to settle after the weather changed.
+const TRANSITION_SMOOTHNESS: f32 = 3.;
+
+/// Handles the [`Weather`], which sets how bright the sun and the ambient light are.
+/// When the weather changes, e.g. through a world event or the `weather` console command, the lights ease over to it.
+pub fn weather_plugin(app: &mut App) {
+    app.register_type::<Weather>()
+        .init_resource::<Weather>()
+        .add_system(apply_weather.in_set(OnUpdate(GameState::Playing)))
+        .add_console_command(
+            "weather",
+            "Changes the weather, e.g. \"weather storm\". Without arguments, shows the current weather",
+            run_weather_command,
+        );
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Hash,
+    Resource,
+    Reflect,
+    FromReflect,
+    Serialize,
+    Deserialize,
+    Default,
+)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Overcast,
+    Storm,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 3] = [Weather::Clear, Weather::Overcast, Weather::Storm];
+
+    /// Illuminance of the sun in lux.
+    pub fn sun_illuminance(self) -> f32 {
+        match self {
+            Weather::Clear => 100_000.,
+            Weather::Overcast => 40_000.,
+            Weather::Storm => 10_000.,
+        }
+    }
+
+    pub fn ambient_brightness(self) -> f32 {
+        match self {
+            Weather::Clear => 0.3,
+            Weather::Overcast => 0.25,
+            Weather::Storm => 0.15,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|weather| format!("{weather:?}").eq_ignore_ascii_case(name))
+    }
+}
+
+fn apply_weather(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    mut suns: Query<&mut DirectionalLight>,
+    ambient_light: Option<ResMut<AmbientLight>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("apply_weather").entered();
+    let factor = smoothness_to_lerp_factor(TRANSITION_SMOOTHNESS, time.delta_seconds());
+    let ease = |current: f32, target: f32| {
+        // Settled lights are left alone so they don't show up as changed every frame
+        if (current - target).abs() <= target * 1e-3 {
+            None
+        } else {
+            Some(current + (target - current) * factor)
+        }
+    };
+    for mut sun in suns.iter_mut() {
+        if let Some(illuminance) = ease(sun.illuminance, weather.sun_illuminance()) {
+            sun.illuminance = illuminance;
+        }
+    }
+    if let Some(mut ambient_light) = ambient_light {
+        if let Some(brightness) = ease(ambient_light.brightness, weather.ambient_brightness()) {
+            ambient_light.brightness = brightness;
+        }
+    }
+}
+
+fn run_weather_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let mut weather = world.resource_mut::<Weather>();
+    match args {
+        [] => {}
+        [name] => {
+            let Some(new_weather) = Weather::from_name(name) else {
+                bail!(
+                    "No weather named \"{name}\", expected one of {:?}",
+                    Weather::ALL
+                );
+            };
+            *weather = new_weather;
+        }
+        _ => bail!("Usage: weather [clear|overcast|storm]"),
+    }
+    Ok(format!("The weather is {:?}", *weather))
+}
@@ -0,0 +1,361 @@
+use crate::console::AddConsoleCommandExt;
+use crate::file_system_interaction::asset_loading::{CutsceneAssets, WorldEventAssets};
+use crate::level_instantiation::spawning::custom::{CustomObject, ObjectKind};
+use crate::level_instantiation::spawning::GameObject;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::cutscene::{get_rail_asset_path, PlayCutsceneEvent};
+use crate::world_interaction::dialog::{DialogContext, DialogEvent, DialogId, PageId};
+use crate::world_interaction::weather::Weather;
+use crate::GameState;
+use anyhow::{bail, Context, Result};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+
+/// Hours in an in-game day.
+const DAY_LENGTH: f32 = 24.;
+
+/// Handles scripted world events like a merchant arriving or a storm beginning, following [`WorldEvent`]s authored in `assets/world_events/`.
+/// An event is named after its file, e.g. `storm` for `world_events/storm.event.ron`.
+/// The [`WorldClock`] keeps the in-game time of day and the play time, which only pass while the player is in a level.
+/// An event fires once its [`WorldEventTrigger`] is met and sends a [`WorldEventFired`], upon which its actions spawn objects,
+/// start dialogs or cutscenes and change the [`Weather`] through their usual events. Spawned objects are marked with a [`WorldEventObject`].
+/// Firing also sets the flag `event.<name>` in the [`DialogContext`], which lets dialogs and quest stages react to the event
+/// and keeps events that don't repeat from firing a second time.
+/// Save games store the flags together with the clock, the weather and the [`WorldEventObjects`], so a loaded save continues
+/// where the events left off.
+pub fn world_event_plugin(app: &mut App) {
+    app.register_type::<WorldClock>()
+        .register_type::<WorldEventObject>()
+        .init_resource::<WorldClock>()
+        .add_event::<WorldEventFired>()
+        .add_systems(
+            (
+                schedule_world_events.run_if(any_with_component::<Player>()),
+                run_world_events,
+            )
+                .chain()
+                .in_set(OnUpdate(GameState::Playing)),
+        )
+        .add_console_command(
+            "world_event",
+            "Fires a world event right away, e.g. \"world_event storm\"",
+            run_world_event_command,
+        )
+        .add_console_command(
+            "clock",
+            "Shows the in-game time or sets the hour of the day, e.g. \"clock 18.5\"",
+            run_clock_command,
+        );
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TypeUuid)]
+#[uuid = "8c4d1f7a-2e95-4b06-a3d8-61f0e7b92c45"]
+pub struct WorldEvent {
+    pub trigger: WorldEventTrigger,
+    /// Whether the event fires again every in-game day or every time its play time has passed once more
+    #[serde(default)]
+    pub repeat: bool,
+    pub actions: Vec<WorldEventAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WorldEventTrigger {
+    /// Fires when the in-game clock reaches the hour of the day, e.g. `18.5` for half past six in the evening
+    AtHour(f32),
+    /// Fires after the play time in seconds has passed
+    AfterPlayTime(f32),
+}
+
+impl WorldEventTrigger {
+    /// Whether the trigger was met while the clock went from `previous` to `now`.
+    pub fn is_met(self, previous: &WorldClock, now: &WorldClock) -> bool {
+        match self {
+            WorldEventTrigger::AtHour(hour) => {
+                if now.day == previous.day {
+                    previous.hour < hour && hour <= now.hour
+                } else {
+                    // The clock passed midnight
+                    previous.hour < hour || hour <= now.hour
+                }
+            }
+            WorldEventTrigger::AfterPlayTime(seconds) => {
+                seconds > 0.
+                    && (previous.play_time / seconds).floor() < (now.play_time / seconds).floor()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WorldEventAction {
+    /// Spawns a built-in or custom object. Prefabs are not supported, as their objects could not be kept in save games.
+    Spawn {
+        object: ObjectKind,
+        position: Vec3,
+    },
+    /// Starts a dialog spoken by the first entity other than the player with the [`Name`], e.g. one spawned by an earlier event
+    Dialog {
+        dialog: DialogId,
+        speaker: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        page: Option<PageId>,
+    },
+    /// Plays the cutscene with the name, e.g. `intro`
+    Cutscene(String),
+    Weather(Weather),
+}
+
+/// Sent when a [`WorldEvent`] fires, either on schedule or through the `world_event` console command.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct WorldEventFired {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct WorldClock {
+    /// Hour of the in-game day, from 0 up to 24
+    pub hour: f32,
+    /// Number of in-game days that have passed
+    pub day: u32,
+    /// Time in seconds spent playing
+    pub play_time: f32,
+    /// In-game hours that pass per second of play time
+    pub hours_per_second: f32,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self {
+            hour: 8.,
+            day: 0,
+            play_time: 0.,
+            // A day lasts 20 minutes
+            hours_per_second: DAY_LENGTH / (20. * 60.),
+        }
+    }
+}
+
+impl WorldClock {
+    pub fn advance(&mut self, seconds: f32) {
+        self.play_time += seconds;
+        self.hour += seconds * self.hours_per_second;
+        while self.hour >= DAY_LENGTH {
+            self.hour -= DAY_LENGTH;
+            self.day += 1;
+        }
+    }
+}
+
+/// Marks an object spawned by a world event, so that it is kept in save games.
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct WorldEventObject {
+    /// Name of the event that spawned the object
+    pub event: String,
+}
+
+/// The objects spawned by world events in the current level, as stored in save games.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct WorldEventObjects(pub Vec<(ObjectKind, Transform, String)>);
+
+impl WorldEventObjects {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn collect<'a>(
+        objects: impl IntoIterator<
+            Item = (
+                &'a Transform,
+                &'a WorldEventObject,
+                Option<&'a GameObject>,
+                Option<&'a CustomObject>,
+            ),
+        >,
+    ) -> Self {
+        Self(
+            objects
+                .into_iter()
+                .filter_map(|(transform, marker, object, custom)| {
+                    let kind = match (object, custom) {
+                        (Some(object), _) => ObjectKind::Builtin(*object),
+                        (None, Some(custom)) => ObjectKind::Custom(custom.name.clone()),
+                        (None, None) => return None,
+                    };
+                    Some((kind, *transform, marker.event.clone()))
+                })
+                .collect(),
+        )
+    }
+
+    pub fn restore(&self, commands: &mut Commands) {
+        for (object, transform, event) in self.0.iter() {
+            object.spawn_with(
+                commands,
+                *transform,
+                WorldEventObject {
+                    event: event.clone(),
+                },
+            );
+        }
+    }
+}
+
+/// Name of a world event asset, e.g. `storm` for `world_events/storm.event.ron`.
+fn get_world_event_name(path: &str) -> &str {
+    path.trim_start_matches("world_events/")
+        .trim_end_matches(".event.ron")
+}
+
+fn get_world_event<'a>(
+    name: &str,
+    world_event_assets: &WorldEventAssets,
+    world_events: &'a Assets<WorldEvent>,
+) -> Option<&'a WorldEvent> {
+    let path = format!("world_events/{name}.event.ron");
+    world_event_assets
+        .events
+        .get(&path)
+        .and_then(|handle| world_events.get(handle))
+}
+
+fn schedule_world_events(
+    time: Res<Time>,
+    mut clock: ResMut<WorldClock>,
+    world_event_assets: Res<WorldEventAssets>,
+    world_events: Res<Assets<WorldEvent>>,
+    context: Res<DialogContext>,
+    mut fired_events: EventWriter<WorldEventFired>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("schedule_world_events").entered();
+    let previous = *clock;
+    clock.advance(time.delta_seconds());
+    for (path, handle) in world_event_assets.events.iter() {
+        let Some(event) = world_events.get(handle) else {
+            continue;
+        };
+        let name = get_world_event_name(path);
+        if !event.repeat && context.has_flag(&format!("event.{name}")) {
+            continue;
+        }
+        if event.trigger.is_met(&previous, &clock) {
+            fired_events.send(WorldEventFired {
+                name: name.to_owned(),
+            });
+        }
+    }
+}
+
+fn run_world_events(
+    mut commands: Commands,
+    mut fired_events: EventReader<WorldEventFired>,
+    world_event_assets: Res<WorldEventAssets>,
+    world_events: Res<Assets<WorldEvent>>,
+    cutscene_assets: Res<CutsceneAssets>,
+    speakers: Query<(Entity, &Name), Without<Player>>,
+    mut context: ResMut<DialogContext>,
+    mut weather: ResMut<Weather>,
+    mut dialog_events: EventWriter<DialogEvent>,
+    mut cutscene_events: EventWriter<PlayCutsceneEvent>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("run_world_events").entered();
+    for fired in fired_events.iter() {
+        let name = &fired.name;
+        let Some(event) = get_world_event(name, &world_event_assets, &world_events) else {
+            warn!("Fired unknown world event \"{name}\"");
+            continue;
+        };
+        info!("World event \"{name}\" fired");
+        context.flags.insert(format!("event.{name}"));
+        for action in &event.actions {
+            match action {
+                WorldEventAction::Spawn { object, position } => {
+                    if let ObjectKind::Prefab(prefab) = object {
+                        warn!("World event \"{name}\" cannot spawn prefab \"{prefab}\"");
+                        continue;
+                    }
+                    object.spawn_with(
+                        &mut commands,
+                        Transform::from_translation(*position),
+                        WorldEventObject {
+                            event: name.clone(),
+                        },
+                    );
+                }
+                WorldEventAction::Dialog {
+                    dialog,
+                    speaker,
+                    page,
+                } => {
+                    let Some((source, _)) = speakers
+                        .iter()
+                        .find(|(_, speaker_name)| speaker_name.as_str() == speaker)
+                    else {
+                        warn!("World event \"{name}\" has no speaker named \"{speaker}\"");
+                        continue;
+                    };
+                    dialog_events.send(DialogEvent {
+                        dialog: dialog.clone(),
+                        source,
+                        page: page.clone(),
+                        resumed: false,
+                    });
+                }
+                WorldEventAction::Cutscene(cutscene) => {
+                    let path = get_rail_asset_path(cutscene);
+                    let Some(rail) = cutscene_assets.rails.get(&path) else {
+                        warn!("World event \"{name}\" plays unknown cutscene \"{cutscene}\"");
+                        continue;
+                    };
+                    cutscene_events.send(PlayCutsceneEvent { rail: rail.clone() });
+                }
+                WorldEventAction::Weather(new_weather) => {
+                    *weather = *new_weather;
+                }
+            }
+        }
+    }
+}
+
+fn run_world_event_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let [name] = args else {
+        bail!("Usage: world_event <name>");
+    };
+    let path = format!("world_events/{name}.event.ron");
+    world
+        .get_resource::<WorldEventAssets>()
+        .filter(|assets| assets.events.contains_key(&path))
+        .with_context(|| format!("No world event named \"{name}\""))?;
+    world.send_event(WorldEventFired {
+        name: (*name).to_owned(),
+    });
+    Ok(format!("Firing world event \"{name}\""))
+}
+
+fn run_clock_command(world: &mut World, args: &[&str]) -> Result<String> {
+    let mut clock = world.resource_mut::<WorldClock>();
+    match args {
+        [] => {}
+        [hour] => {
+            let hour: f32 = hour
+                .parse()
+                .with_context(|| format!("Failed to parse hour \"{hour}\""))?;
+            if !(0. ..DAY_LENGTH).contains(&hour) {
+                bail!("The hour must be at least 0 and less than {DAY_LENGTH}");
+            }
+            clock.hour = hour;
+        }
+        _ => bail!("Usage: clock [hour]"),
+    }
+    let minutes = (clock.hour.fract() * 60.).floor();
+    Ok(format!(
+        "Day {}, {:02}:{minutes:02}, {:.0}s played",
+        clock.day + 1,
+        clock.hour.floor(),
+        clock.play_time
+    ))
+}
@@ -0,0 +1,269 @@
+use crate::movement::general_movement::{CharacterControllerSystemSet, Jumping};
+use crate::player_control::actions::PlayerAction;
+use crate::player_control::player_embodiment::Player;
+use crate::world_interaction::interactions_ui::InteractionUi;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::{Deserialize, Serialize};
+
+/// Distance in m between the cable and the center of the character hanging from it.
+const HANG_DISTANCE: f32 = 0.6;
+const MIN_SPEED: f32 = 1.5;
+const MAX_SPEED: f32 = 14.0;
+/// How strongly a character hanging from a cable is pulled back onto it.
+const CABLE_STIFFNESS: f32 = 10.0;
+const GRAVITY: f32 = 9.81;
+
+/// Handles ziplines. Every [`ZiplineAnchor`] is connected by a cable to the closest other anchor if that one is also
+/// closest to it, so placing two anchors in the editor is enough to create a zipline.
+/// Interacting with an anchor slides the player along the cable towards the other end, accelerating downhill.
+/// Jumping lets go of the cable. While sliding, the camera looks at the destination.
+pub fn zipline_plugin(app: &mut App) {
+    app.register_type::<ZiplineAnchor>().add_systems(
+        (
+            pair_anchors,
+            update_cables,
+            start_ziplining,
+            slide_along_cables,
+        )
+            .chain()
+            .after(CharacterControllerSystemSet)
+            .in_set(OnUpdate(GameState::Playing)),
+    );
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Component, Reflect, Serialize, Deserialize, Default)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct ZiplineAnchor;
+
+/// The anchor at the other end of the cable.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Component)]
+pub struct ZiplinePartner(pub Entity);
+
+/// Present on characters while they hang on a zipline.
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct Ziplining {
+    pub start: Vec3,
+    pub end: Vec3,
+    /// Distance in m travelled along the cable.
+    pub distance: f32,
+    /// Speed in m/s along the cable.
+    pub speed: f32,
+}
+
+fn pair_anchors(
+    mut commands: Commands,
+    anchors: Query<(Entity, &GlobalTransform, Option<&ZiplinePartner>), With<ZiplineAnchor>>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("pair_anchors").entered();
+    let closest: HashMap<_, _> = anchors
+        .iter()
+        .filter_map(|(entity, transform, _)| {
+            anchors
+                .iter()
+                .filter(|(other, ..)| *other != entity)
+                .map(|(other, other_transform, _)| {
+                    let distance = transform
+                        .translation()
+                        .distance(other_transform.translation());
+                    (other, distance)
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(other, _)| (entity, other))
+        })
+        .collect();
+    for (entity, _, partner) in anchors.iter() {
+        let new_partner = closest
+            .get(&entity)
+            .filter(|other| closest.get(*other) == Some(&entity))
+            .copied();
+        match (partner.map(|partner| partner.0), new_partner) {
+            (old, new) if old == new => {}
+            (_, Some(new)) => {
+                commands.entity(entity).insert(ZiplinePartner(new));
+            }
+            (_, None) => {
+                commands.entity(entity).remove::<ZiplinePartner>();
+            }
+        }
+    }
+}
+
+fn get_or_add_cable_mesh(meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+    const MESH_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(Mesh::TYPE_UUID, 0x4a8c2e17f09b5d63);
+    let handle = MESH_HANDLE.typed();
+    meshes.get_or_insert_with(handle.clone_weak(), || {
+        Mesh::from(shape::Cylinder {
+            radius: 0.02,
+            height: 1.0,
+            ..default()
+        })
+    });
+    handle
+}
+
+fn get_or_add_cable_material(materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+    const MATERIAL_HANDLE: HandleUntyped =
+        HandleUntyped::weak_from_u64(StandardMaterial::TYPE_UUID, 0x91d35b0e6f2a47c8);
+    let handle = MATERIAL_HANDLE.typed();
+    materials.get_or_insert_with(handle.clone_weak(), || Color::DARK_GRAY.into());
+    handle
+}
+
+/// Keeps one cable mesh stretched between every pair of anchors.
+fn update_cables(
+    mut commands: Commands,
+    anchors: Query<(Entity, &GlobalTransform, &ZiplinePartner), With<ZiplineAnchor>>,
+    transforms: Query<&GlobalTransform>,
+    mut cables: Local<HashMap<(Entity, Entity), Entity>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cable_transforms: Query<&mut Transform>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("update_cables").entered();
+    let mut pairs = HashMap::new();
+    for (entity, transform, partner) in anchors.iter() {
+        // Each pair is visited from both sides, only handle it once
+        if entity > partner.0 {
+            continue;
+        }
+        let Ok(partner_transform) = transforms.get(partner.0) else {
+            continue;
+        };
+        let start = transform.translation();
+        let end = partner_transform.translation();
+        let cable_transform = Transform::from_translation((start + end) / 2.)
+            .with_rotation(Quat::from_rotation_arc(
+                Vec3::Y,
+                (end - start).normalize_or_zero(),
+            ))
+            .with_scale(Vec3::new(1., start.distance(end), 1.));
+        pairs.insert((entity, partner.0), cable_transform);
+    }
+
+    cables.retain(|pair, cable| {
+        let keep = pairs.contains_key(pair);
+        if !keep {
+            if let Some(cable) = commands.get_entity(*cable) {
+                cable.despawn_recursive();
+            }
+        }
+        keep
+    });
+    for (pair, cable_transform) in pairs {
+        if let Some(cable) = cables.get(&pair) {
+            if let Ok(mut transform) = cable_transforms.get_mut(*cable) {
+                *transform = cable_transform;
+            }
+        } else {
+            let cable = commands
+                .spawn((
+                    PbrBundle {
+                        mesh: get_or_add_cable_mesh(&mut meshes),
+                        material: get_or_add_cable_material(&mut materials),
+                        transform: cable_transform,
+                        ..default()
+                    },
+                    Name::new("Zipline Cable"),
+                ))
+                .id();
+            cables.insert(pair, cable);
+        }
+    }
+}
+
+fn start_ziplining(
+    mut commands: Commands,
+    interaction_ui: Option<Res<InteractionUi>>,
+    mut players: Query<
+        (Entity, &ActionState<PlayerAction>, &mut Transform),
+        (With<Player>, Without<Ziplining>),
+    >,
+    anchors: Query<(&GlobalTransform, &ZiplinePartner), With<ZiplineAnchor>>,
+    transforms: Query<&GlobalTransform>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("start_ziplining").entered();
+    let Some(interaction_ui) = interaction_ui else {
+        return;
+    };
+    let Ok((anchor_transform, partner)) = anchors.get(interaction_ui.source) else {
+        return;
+    };
+    let Ok(partner_transform) = transforms.get(partner.0) else {
+        return;
+    };
+    for (player, actions, mut transform) in players.iter_mut() {
+        if !actions.just_pressed(PlayerAction::Interact) {
+            continue;
+        }
+        let start = anchor_transform.translation();
+        transform.translation = start - Vec3::Y * HANG_DISTANCE;
+        commands.entity(player).insert((
+            Ziplining {
+                start,
+                end: partner_transform.translation(),
+                distance: 0.0,
+                speed: MIN_SPEED,
+            },
+            GravityScale(0.0),
+        ));
+    }
+}
+
+fn slide_along_cables(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut characters: Query<(
+        Entity,
+        &mut Ziplining,
+        &Transform,
+        &mut Velocity,
+        &mut ExternalForce,
+        &Jumping,
+        Option<&ActionState<PlayerAction>>,
+    )>,
+) {
+    #[cfg(feature = "tracing")]
+    let _span = info_span!("slide_along_cables").entered();
+    let dt = time.delta_seconds();
+    for (entity, mut ziplining, transform, mut velocity, mut force, jumping, actions) in
+        characters.iter_mut()
+    {
+        let cable = ziplining.end - ziplining.start;
+        let length = cable.length();
+        let direction = cable.normalize_or_zero();
+        // Gravity accelerates downhill and decelerates uphill
+        ziplining.speed =
+            (ziplining.speed - GRAVITY * direction.y * dt).clamp(MIN_SPEED, MAX_SPEED);
+        ziplining.distance += ziplining.speed * dt;
+
+        let jumped = actions
+            .map(|actions| actions.just_pressed(PlayerAction::Jump))
+            .unwrap_or_default();
+        if jumped || ziplining.distance >= length {
+            velocity.linvel = direction * ziplining.speed;
+            if jumped {
+                velocity.linvel += Vec3::Y * jumping.speed;
+            }
+            commands
+                .entity(entity)
+                .remove::<Ziplining>()
+                .insert(GravityScale(1.0));
+            continue;
+        }
+
+        let target = ziplining.start + direction * ziplining.distance - Vec3::Y * HANG_DISTANCE;
+        velocity.linvel =
+            direction * ziplining.speed + (target - transform.translation) * CABLE_STIFFNESS;
+        // Walking has no effect while hanging
+        force.force = Vec3::ZERO;
+    }
+}
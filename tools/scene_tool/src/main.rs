@@ -0,0 +1,220 @@
+//! Command-line tool for the levels in `assets/levels` and the save games in `saves/`.
+//! It reads and writes them with the same code as the game, so levels of older format versions are migrated
+//! exactly like they would be when loaded.
+//!
+//! ```text
+//! scene-tool validate <paths>...
+//! scene-tool migrate [--dry-run] <paths>...
+//! scene-tool diff <old> <new>
+//! scene-tool print <path>
+//! ```
+//! Directories are searched recursively for `.lvl.ron` and `.sav.ron` files.
+
+use anyhow::{bail, Context, Result};
+use foxtrot::file_system_interaction::game_state_serialization::{
+    deserialize_save, serialize_save, SaveModel,
+};
+use foxtrot::file_system_interaction::level_serialization::{
+    deserialize_level, serialize_level, SerializedLevel,
+};
+use glob::glob;
+use ron::ser::PrettyConfig;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+const USAGE: &str = "Usage:
+    scene-tool validate <paths>...             Checks that levels and saves can be loaded
+    scene-tool migrate [--dry-run] <paths>...  Rewrites levels and saves in the current format
+    scene-tool diff <old> <new>                Shows the differences between two levels or saves
+    scene-tool print <path>                    Prints a level or save in the current format";
+
+const LEVEL_EXTENSION: &str = ".lvl.ron";
+const SAVE_EXTENSION: &str = ".sav.ron";
+
+fn main() -> Result<()> {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["validate", paths @ ..] if !paths.is_empty() => validate(paths),
+        ["migrate", "--dry-run", paths @ ..] if !paths.is_empty() => migrate(paths, true),
+        ["migrate", paths @ ..] if !paths.is_empty() => migrate(paths, false),
+        ["diff", old, new] => diff(Path::new(old), Path::new(new)),
+        ["print", path] => {
+            print!("{}", read(Path::new(path))?.to_pretty_string()?);
+            Ok(())
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            bail!("Invalid arguments");
+        }
+    }
+}
+
+enum SceneFile {
+    Level(SerializedLevel),
+    Save(SaveModel),
+}
+
+impl SceneFile {
+    /// Serializes the file the way the game would write it.
+    fn to_stored_string(&self) -> Result<String> {
+        match self {
+            SceneFile::Level(level) => serialize_level(level),
+            SceneFile::Save(save) => serialize_save(save),
+        }
+    }
+
+    fn to_pretty_string(&self) -> Result<String> {
+        match self {
+            SceneFile::Level(level) => serialize_level(level),
+            SceneFile::Save(save) => ron::ser::to_string_pretty(save, PrettyConfig::default())
+                .context("Failed to serialize save"),
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<SceneFile> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let name = path.to_string_lossy();
+    let file = if name.ends_with(LEVEL_EXTENSION) {
+        deserialize_level(&bytes).map(SceneFile::Level)
+    } else if name.ends_with(SAVE_EXTENSION) {
+        std::str::from_utf8(&bytes)
+            .context("Save is not valid UTF-8")
+            .and_then(deserialize_save)
+            .map(SceneFile::Save)
+    } else {
+        bail!(
+            "Expected {} to end in {LEVEL_EXTENSION} or {SAVE_EXTENSION}",
+            path.display()
+        );
+    };
+    file.with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// Replaces directories with the levels and saves inside them.
+fn collect_files(paths: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        if !path.is_dir() {
+            files.push(path.to_owned());
+            continue;
+        }
+        for extension in [LEVEL_EXTENSION, SAVE_EXTENSION] {
+            let pattern = path.join("**").join(format!("*{extension}"));
+            let pattern = pattern.to_string_lossy();
+            let mut found: Vec<_> = glob(&pattern)
+                .with_context(|| format!("Failed to read glob pattern {pattern}"))?
+                .filter_map(|entry| entry.ok())
+                .collect();
+            found.sort();
+            files.extend(found);
+        }
+    }
+    Ok(files)
+}
+
+fn validate(paths: &[&str]) -> Result<()> {
+    let files = collect_files(paths)?;
+    let mut failures = 0;
+    for path in &files {
+        match read(path).and_then(|file| check_references(path, &file)) {
+            Ok(()) => println!("ok      {}", path.display()),
+            Err(e) => {
+                failures += 1;
+                println!("failed  {}: {e:?}", path.display());
+            }
+        }
+    }
+    if failures > 0 {
+        bail!("{failures} of {} files failed validation", files.len());
+    }
+    println!("All {} files are valid", files.len());
+    Ok(())
+}
+
+/// Saves are made in a level, which needs to still exist for the save to be loadable.
+fn check_references(path: &Path, file: &SceneFile) -> Result<()> {
+    let SceneFile::Save(save) = file else {
+        return Ok(());
+    };
+    let level_path = Path::new("assets")
+        .join("levels")
+        .join(save.scene())
+        .with_extension("lvl.ron");
+    if !level_path.is_file() {
+        bail!(
+            "{} was made in the level \"{}\", but {} does not exist",
+            path.display(),
+            save.scene(),
+            level_path.display()
+        );
+    }
+    Ok(())
+}
+
+fn migrate(paths: &[&str], dry_run: bool) -> Result<()> {
+    let files = collect_files(paths)?;
+    let mut migrated = 0;
+    for path in &files {
+        let original = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let serialized = read(path)?.to_stored_string()?;
+        if serialized == original {
+            continue;
+        }
+        migrated += 1;
+        if dry_run {
+            println!("would migrate  {}", path.display());
+        } else {
+            fs::write(path, serialized)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("migrated  {}", path.display());
+        }
+    }
+    println!(
+        "{migrated} of {} files {} not in the current format",
+        files.len(),
+        if dry_run { "are" } else { "were" }
+    );
+    Ok(())
+}
+
+fn diff(old: &Path, new: &Path) -> Result<()> {
+    let old = read(old)?.to_pretty_string()?;
+    let new = read(new)?.to_pretty_string()?;
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    // Longest common subsequence of lines, filled from the back so the diff can be walked from the front
+    let mut common = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut changes = 0;
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            println!("+{}", new[j]);
+            changes += 1;
+            j += 1;
+        } else {
+            println!("-{}", old[i]);
+            changes += 1;
+            i += 1;
+        }
+    }
+    if changes == 0 {
+        println!("No differences");
+    }
+    Ok(())
+}